@@ -11,6 +11,7 @@ use crate::output::{emit_err, emit_json_ok, Format};
 use accshift_core::diagnostics::{
     bundle, event::Level, health, levels, query, sanitize_log_text, schema,
 };
+use accshift_core::snapshot_manifest::{self, Verification};
 use clap::Subcommand;
 use serde_json::{json, Value};
 
@@ -57,6 +58,13 @@ pub enum Diag {
     },
     /// Run the health invariants and report what holds.
     Check,
+    /// Check every account snapshot against the manifest written when it was
+    /// captured. Reads only; nothing is restored or repaired.
+    VerifySnapshots {
+        /// Only this platform's snapshots.
+        #[arg(long)]
+        platform: Option<String>,
+    },
     /// Show or change the per-module log level.
     Level {
         /// Module the level applies to. Omit for the default level.
@@ -108,6 +116,7 @@ impl Diag {
             Diag::Logs { .. } => "diag-logs",
             Diag::Explain { .. } => "diag-explain",
             Diag::Check => "diag-check",
+            Diag::VerifySnapshots { .. } => "diag-verify-snapshots",
             Diag::Level { .. } => "diag-level",
             Diag::Bundle { .. } => "diag-bundle",
             Diag::Schema { .. } => "diag-schema",
@@ -145,6 +154,7 @@ pub fn run(format: Format, action: Diag) -> u8 {
         ),
         Diag::Explain { code } => cmd_explain(format, &code),
         Diag::Check => cmd_check(format),
+        Diag::VerifySnapshots { platform } => cmd_verify_snapshots(format, platform.as_deref()),
         Diag::Level {
             module,
             set,
@@ -340,6 +350,84 @@ fn cmd_check(format: Format) -> u8 {
     }
 }
 
+fn cmd_verify_snapshots(format: Format, platform: Option<&str>) -> u8 {
+    let ctx = match crate::build_ctx(format, "diag-verify-snapshots") {
+        Ok(ctx) => ctx,
+        Err(code) => return code,
+    };
+
+    let audits = match snapshot_manifest::audit(&*ctx) {
+        Ok(audits) => audits,
+        Err(reason) => {
+            emit_err(format, "diag-verify-snapshots", "io", &reason);
+            return exit::IO;
+        }
+    };
+    let audits: Vec<_> = audits
+        .into_iter()
+        .filter(|audit| platform.is_none_or(|id| audit.platform_id == id))
+        .collect();
+    let failed = audits
+        .iter()
+        .filter(|audit| audit.verification.failure().is_some())
+        .count();
+
+    match format {
+        Format::Json => emit_json_ok(
+            "diag-verify-snapshots",
+            json!({
+                "snapshots": audits.iter().map(|audit| {
+                    let manifest = match &audit.verification {
+                        Verification::Intact(m) | Verification::Mismatch(m, _) => Some(m),
+                        _ => None,
+                    };
+                    json!({
+                        "platform": audit.platform_id,
+                        "account": audit.account_id,
+                        "generation": audit.generation,
+                        "status": audit.verification.status(),
+                        "files": manifest.map(|m| m.files.len()),
+                        "capturedAt": manifest.map(|m| m.captured_at),
+                        "problem": audit.verification.failure(),
+                    })
+                }).collect::<Vec<_>>(),
+                "failed": failed,
+            }),
+        ),
+        Format::Human => {
+            if audits.is_empty() {
+                println!("No snapshot to check.");
+            }
+            for audit in &audits {
+                let generation = audit
+                    .generation
+                    .map(|g| format!(" (generation {g})"))
+                    .unwrap_or_default();
+                println!(
+                    "{:<10} {:<12} {}{generation}",
+                    audit.verification.status(),
+                    audit.platform_id,
+                    audit.account_id
+                );
+                if let Some(problem) = audit.verification.failure() {
+                    println!("      {}", sanitize_log_text(&problem));
+                }
+            }
+            if failed > 0 {
+                println!(
+                    "{failed} snapshot(s) would be refused. Sign in to those accounts again to capture fresh ones."
+                );
+            }
+        }
+    }
+
+    if failed == 0 {
+        exit::OK
+    } else {
+        exit::GENERIC
+    }
+}
+
 fn cmd_level(
     format: Format,
     module: Option<String>,
@@ -4,11 +4,13 @@ mod folders;
 mod output;
 mod pin;
 mod settings;
+mod steam;
 mod telemetry;
+mod transfer;
 
 use accshift_core::error::PlatformErrorKind;
 use accshift_core::lock::{acquire_exclusive, LockError};
-use accshift_core::platforms::descriptor::plan::DryRunPlan;
+use accshift_core::platforms::descriptor::plan::{DryRunOperation, DryRunPlan};
 use accshift_core::platforms::get_service;
 use clap::{Parser, Subcommand};
 use context::CliAppContext;
@@ -71,45 +73,116 @@ enum Command {
         platform: String,
         /// Account identifier (for Steam: the account name from `list`).
         account_id: String,
-        /// Steam: start Steam in online mode (default when neither set).
-        #[arg(long, conflicts_with = "invisible")]
-        online: bool,
-        /// Steam: start Steam in invisible mode.
-        #[arg(long)]
-        invisible: bool,
-        /// Steam: kill Steam gracefully (default falls back to GUI setting).
-        #[arg(long, conflicts_with = "force")]
-        graceful: bool,
-        /// Steam: force-kill Steam (default falls back to GUI setting).
-        #[arg(long)]
-        force: bool,
-        /// Steam: relaunch with admin rights (falls back to GUI setting).
-        #[arg(long, conflicts_with = "no_admin")]
-        admin: bool,
-        /// Steam: explicitly disable admin rights for this run.
-        #[arg(long = "no-admin")]
-        no_admin: bool,
-        /// Steam: launch options passed to steam.exe (falls back to GUI
-        /// setting; pass an empty string to override with none).
-        #[arg(long)]
-        launch_options: Option<String>,
+        #[command(flatten)]
+        overrides: SwitchOverrides,
+        /// Restore this earlier capture of the account's snapshot instead of
+        /// the latest (see `accshift snapshots list`).
+        #[arg(long, value_name = "CAPTURED_AT")]
+        generation: Option<u64>,
+    },
+    /// List the earlier captures kept of an account's snapshot, or change how
+    /// many are kept.
+    Snapshots {
+        #[command(subcommand)]
+        action: SnapshotsAction,
     },
     /// List the descriptors in the user folder, and why any was refused.
-    Descriptors,
-    /// Print everything a switch would read, copy, write, close and launch,
-    /// without doing any of it.
+    Descriptors {
+        #[command(subcommand)]
+        action: Option<DescriptorsAction>,
+    },
+    /// Print everything a switch (or a forget, a setup, a capture) would
+    /// read, copy, write, close and launch, without doing any of it.
     #[command(name = "dry-run")]
     DryRun {
         /// Platform identifier (see `accshift platforms`).
         platform: String,
-        /// Account identifier (see `accshift list <platform>`).
-        account_id: String,
+        /// Account identifier (see `accshift list <platform>`). Required for
+        /// switch and forget; setup and capture act on whoever is signed in.
+        account_id: Option<String>,
+        /// The operation to plan: switch, forget, setup or capture.
+        #[arg(long, default_value = "switch")]
+        operation: String,
+        /// The switch options to plan with, as `switch` takes them.
+        #[command(flatten)]
+        overrides: SwitchOverrides,
+        /// Steam: plan the switch that launches this game (its app id).
+        #[arg(long)]
+        app_id: Option<String>,
+    },
+    /// Write every account with a snapshot, and its notes, folders and
+    /// personas, to one archive sealed with a passphrase.
+    Export {
+        /// The archive to write.
+        file: std::path::PathBuf,
+        /// Read the passphrase from this environment variable instead of
+        /// asking for it.
+        #[arg(long, value_name = "VAR")]
+        passphrase_env: Option<String>,
+    },
+    /// Add the accounts in an archive written by `export` to this machine.
+    /// Accounts it already has are reported, never overwritten.
+    Import {
+        /// The archive to read.
+        file: std::path::PathBuf,
+        /// Read the passphrase from this environment variable instead of
+        /// asking for it.
+        #[arg(long, value_name = "VAR")]
+        passphrase_env: Option<String>,
     },
     /// Read the log, explain a code, check the invariants, pack a report.
     Diag {
         #[command(subcommand)]
         action: diagnostics::Diag,
     },
+    /// Look across every Steam account on this machine.
+    Steam {
+        #[command(subcommand)]
+        action: steam::Steam,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnapshotsAction {
+    /// List the generations kept of an account's snapshot, newest first.
+    List {
+        /// Platform identifier (see `accshift platforms`).
+        platform: String,
+        /// Account identifier (see `accshift list <platform>`).
+        account_id: String,
+    },
+    /// Show or set how many generations are kept per account.
+    Keep {
+        /// New count, 0 to keep none.
+        count: Option<u32>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DescriptorsAction {
+    /// Print the JSON Schema of the descriptor format.
+    Schema {
+        /// Regenerate docs/descriptor-schema.json in this directory.
+        #[arg(long)]
+        write: Option<std::path::PathBuf>,
+    },
+    /// Run a descriptor's setup, switch and forget against a scratch folder,
+    /// without touching the launcher or this machine's files.
+    Test {
+        /// The descriptor file to run.
+        file: std::path::PathBuf,
+        /// Run the profile for this OS instead of the current one.
+        #[arg(long, value_parser = ["windows", "macos", "linux"])]
+        os: Option<String>,
+        /// Leave the scratch folder in place to look at afterwards.
+        #[arg(long)]
+        keep: bool,
+    },
+    /// Point out what a valid descriptor probably did not mean to do.
+    Lint {
+        /// The descriptor file to read.
+        file: std::path::PathBuf,
+    },
 }
 
 impl Command {
@@ -120,9 +193,27 @@ impl Command {
             Command::List { .. } => "list",
             Command::Platforms => "platforms",
             Command::Switch { .. } => "switch",
-            Command::Descriptors => "descriptors",
+            Command::Snapshots {
+                action: SnapshotsAction::List { .. },
+            } => "snapshots-list",
+            Command::Snapshots {
+                action: SnapshotsAction::Keep { .. },
+            } => "snapshots-keep",
+            Command::Descriptors { action: None } => "descriptors",
+            Command::Descriptors {
+                action: Some(DescriptorsAction::Schema { .. }),
+            } => "descriptors-schema",
+            Command::Descriptors {
+                action: Some(DescriptorsAction::Test { .. }),
+            } => "descriptors-test",
+            Command::Descriptors {
+                action: Some(DescriptorsAction::Lint { .. }),
+            } => "descriptors-lint",
             Command::DryRun { .. } => "dry-run",
+            Command::Export { .. } => "export",
+            Command::Import { .. } => "import",
             Command::Diag { action } => action.name(),
+            Command::Steam { action } => action.name(),
         }
     }
 }
@@ -145,33 +236,53 @@ fn main() -> ExitCode {
         Command::Switch {
             platform,
             account_id,
-            online,
-            invisible,
-            graceful,
-            force,
-            admin,
-            no_admin,
-            launch_options,
-        } => cmd_switch(
-            format,
-            &platform,
-            &account_id,
-            SwitchOverrides {
-                online,
-                invisible,
-                graceful,
-                force,
-                admin,
-                no_admin,
-                launch_options,
-            },
-        ),
-        Command::Descriptors => cmd_descriptors(format),
+            overrides,
+            generation,
+        } => cmd_switch(format, &platform, &account_id, overrides, generation),
+        Command::Snapshots {
+            action:
+                SnapshotsAction::List {
+                    platform,
+                    account_id,
+                },
+        } => cmd_snapshots_list(format, &platform, &account_id),
+        Command::Snapshots {
+            action: SnapshotsAction::Keep { count },
+        } => cmd_snapshots_keep(format, count),
+        Command::Descriptors { action: None } => cmd_descriptors(format),
+        Command::Descriptors {
+            action: Some(DescriptorsAction::Schema { write }),
+        } => cmd_descriptor_schema(format, write),
+        Command::Descriptors {
+            action: Some(DescriptorsAction::Test { file, os, keep }),
+        } => cmd_descriptor_test(format, &file, os.as_deref(), keep),
+        Command::Descriptors {
+            action: Some(DescriptorsAction::Lint { file }),
+        } => cmd_descriptor_lint(format, &file),
         Command::DryRun {
             platform,
             account_id,
-        } => cmd_dry_run(format, &platform, &account_id),
+            operation,
+            overrides,
+            app_id,
+        } => cmd_dry_run(
+            format,
+            &platform,
+            &operation,
+            account_id.as_deref(),
+            overrides,
+            app_id,
+        ),
+        Command::Export {
+            file,
+            passphrase_env,
+        } => transfer::cmd_export(format, &file, passphrase_env.as_deref()),
+        Command::Import {
+            file,
+            passphrase_env,
+        } => transfer::cmd_import(format, &file, passphrase_env.as_deref()),
         Command::Diag { action } => diagnostics::run(format, action),
+        Command::Steam { action } => steam::run(format, action),
     };
 
     if let Some(reporter) = reporter {
@@ -281,6 +392,7 @@ fn cmd_list(format: Format, platform_id: &str, folder: Option<&str>) -> u8 {
                 rows,
                 current.as_deref(),
                 folder_filter.as_ref(),
+                &service.account_fields(),
             );
         }
     }
@@ -320,14 +432,97 @@ fn resolve_folder(
         .map_err(FolderResolveError::NotFound)
 }
 
+#[derive(clap::Args)]
 struct SwitchOverrides {
+    /// Steam: start Steam in online mode (default when neither set).
+    #[arg(long, conflicts_with = "invisible")]
     online: bool,
+    /// Steam: start Steam in invisible mode.
+    #[arg(long)]
     invisible: bool,
+    /// Steam: kill Steam gracefully (default falls back to GUI setting).
+    #[arg(long, conflicts_with = "force")]
     graceful: bool,
+    /// Steam: force-kill Steam (default falls back to GUI setting).
+    #[arg(long)]
     force: bool,
+    /// Steam: relaunch with admin rights (falls back to GUI setting).
+    #[arg(long, conflicts_with = "no_admin")]
     admin: bool,
+    /// Steam: explicitly disable admin rights for this run.
+    #[arg(long = "no-admin")]
     no_admin: bool,
+    /// Steam: launch options passed to steam.exe (falls back to GUI
+    /// setting; pass an empty string to override with none).
+    #[arg(long)]
     launch_options: Option<String>,
+    /// Descriptor platforms: a value the launch arguments read, for this
+    /// switch only. Repeat it for several.
+    #[arg(long = "launch-value", value_name = "NAME=VALUE", value_parser = parse_launch_value)]
+    launch_values: Vec<(String, String)>,
+}
+
+fn parse_launch_value(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("Cannot read {value} as NAME=VALUE.")),
+    }
+}
+
+impl SwitchOverrides {
+    /// The `params` a switch is given: each flag, or the GUI setting it
+    /// falls back to.
+    fn params(self, steam_defaults: settings::SteamSettings) -> Value {
+        let run_as_admin = if self.admin {
+            true
+        } else if self.no_admin {
+            false
+        } else {
+            steam_defaults.run_as_admin
+        };
+
+        let shutdown = if self.force {
+            "force"
+        } else if self.graceful {
+            "graceful"
+        } else {
+            match steam_defaults.shutdown_mode.as_deref() {
+                Some("force") => "force",
+                Some("graceful") => "graceful",
+                _ => "graceful",
+            }
+        };
+
+        // Only force a persona mode when the user asked for one. A plain switch
+        // must not touch the account's existing online/invisible state.
+        let mode = if self.invisible {
+            Some("invisible")
+        } else if self.online {
+            Some("online")
+        } else {
+            None
+        };
+
+        let launch_options = self.launch_options.unwrap_or(steam_defaults.launch_options);
+
+        let mut params = json!({
+            "runAsAdmin": run_as_admin,
+            "launchOptions": launch_options,
+            "shutdownMode": shutdown,
+        });
+        if let Some(mode) = mode {
+            params["mode"] = json!(mode);
+        }
+        if !self.launch_values.is_empty() {
+            params["launchValues"] = json!(self
+                .launch_values
+                .into_iter()
+                .collect::<std::collections::BTreeMap<_, _>>());
+        }
+        params
+    }
 }
 
 fn cmd_switch(
@@ -335,6 +530,7 @@ fn cmd_switch(
     platform_id: &str,
     account_id: &str,
     overrides: SwitchOverrides,
+    generation: Option<u64>,
 ) -> u8 {
     let ctx = match build_ctx(format, "switch") {
         Ok(c) => c,
@@ -365,7 +561,7 @@ fn cmd_switch(
     // the same lock here so the CLI cannot bypass it. Prompt before taking the
     // lock so we never hold it while waiting on stdin.
     if app_settings.pin_enabled {
-        if let Err(code) = pin::enforce(format, &app_settings.pin_hash) {
+        if let Err(code) = pin::enforce(format, "switch", &app_settings.pin_hash) {
             return code;
         }
     }
@@ -387,49 +583,9 @@ fn cmd_switch(
         }
     };
 
-    let steam_defaults = app_settings.platform_settings.steam;
-
-    let run_as_admin = if overrides.admin {
-        true
-    } else if overrides.no_admin {
-        false
-    } else {
-        steam_defaults.run_as_admin
-    };
-
-    let shutdown = if overrides.force {
-        "force"
-    } else if overrides.graceful {
-        "graceful"
-    } else {
-        match steam_defaults.shutdown_mode.as_deref() {
-            Some("force") => "force",
-            Some("graceful") => "graceful",
-            _ => "graceful",
-        }
-    };
-
-    // Only force a persona mode when the user asked for one. A plain switch
-    // must not touch the account's existing online/invisible state.
-    let mode = if overrides.invisible {
-        Some("invisible")
-    } else if overrides.online {
-        Some("online")
-    } else {
-        None
-    };
-
-    let launch_options = overrides
-        .launch_options
-        .unwrap_or(steam_defaults.launch_options);
-
-    let mut params = json!({
-        "runAsAdmin": run_as_admin,
-        "launchOptions": launch_options,
-        "shutdownMode": shutdown,
-    });
-    if let Some(mode) = mode {
-        params["mode"] = json!(mode);
+    let mut params = overrides.params(app_settings.platform_settings.steam);
+    if let Some(generation) = generation {
+        params["snapshotGeneration"] = json!(generation);
     }
 
     match service.switch_account(ctx, account_id, params) {
@@ -452,6 +608,101 @@ fn cmd_switch(
     }
 }
 
+fn cmd_snapshots_list(format: Format, platform_id: &str, account_id: &str) -> u8 {
+    let ctx = match build_ctx(format, "snapshots-list") {
+        Ok(c) => c,
+        Err(code) => return code,
+    };
+
+    if !settings::load(&*ctx).cli_enabled {
+        emit_err(
+            format,
+            "snapshots-list",
+            "cli_disabled",
+            CLI_DISABLED_MESSAGE,
+        );
+        return exit::CLI_DISABLED;
+    }
+
+    let Some(service) = get_service(platform_id) else {
+        emit_err(
+            format,
+            "snapshots-list",
+            "platform_unavailable",
+            &format!("Unknown platform: {platform_id}"),
+        );
+        return exit::PLATFORM_UNAVAILABLE;
+    };
+
+    let generations = match service.snapshot_generations(ctx, account_id) {
+        Ok(generations) => generations,
+        Err(e) => {
+            let message = e.to_string();
+            let (code, status) = classify(&e, &message);
+            emit_err(format, "snapshots-list", code, &message);
+            return status;
+        }
+    };
+
+    match format {
+        Format::Json => emit_json_ok(
+            "snapshots-list",
+            json!({
+                "platform": platform_id,
+                "accountId": account_id,
+                "generations": generations,
+            }),
+        ),
+        Format::Human => output::render_generations(&generations),
+    }
+    exit::OK
+}
+
+fn cmd_snapshots_keep(format: Format, count: Option<u32>) -> u8 {
+    let ctx = match build_ctx(format, "snapshots-keep") {
+        Ok(c) => c,
+        Err(code) => return code,
+    };
+
+    if !settings::load(&*ctx).cli_enabled {
+        emit_err(
+            format,
+            "snapshots-keep",
+            "cli_disabled",
+            CLI_DISABLED_MESSAGE,
+        );
+        return exit::CLI_DISABLED;
+    }
+
+    if let Some(count) = count {
+        let max = accshift_core::snapshot_history::MAX_GENERATIONS;
+        if usize::try_from(count).map_or(true, |count| count > max) {
+            emit_err(
+                format,
+                "snapshots-keep",
+                "bad_argument",
+                &format!("Keep at most {max} generations."),
+            );
+            return exit::GENERIC;
+        }
+        if let Err(e) = accshift_core::config::update_config(&*ctx, |cfg| {
+            cfg.snapshot_generations = Some(count);
+        }) {
+            emit_err(format, "snapshots-keep", "io", &e);
+            return exit::IO;
+        }
+    }
+
+    // Older generations past a lowered count go with each account's next
+    // capture, not now: trimming here would mean walking every platform.
+    let kept = accshift_core::snapshot_history::generations_kept(&*ctx);
+    match format {
+        Format::Json => emit_json_ok("snapshots-keep", json!({ "generations": kept })),
+        Format::Human => println!("Keeping {kept} earlier capture(s) per account."),
+    }
+    exit::OK
+}
+
 /// Maps a platform failure onto the CLI's error code and exit status.
 ///
 /// Typed discriminant first: platforms that already tag their errors with
@@ -467,32 +718,63 @@ fn classify(error: &accshift_core::error::PlatformError, message: &str) -> (&'st
     let unknown_account = error.kind == PlatformErrorKind::AccountNotFound
         || message.contains("Invalid username") // Steam
         || message.contains("account not found") // Battle.net, Roblox
-        || message.contains("profile not found") // Riot
         || message.contains("No auth snapshot found for account") // Ubisoft, Epic
         || message.contains("Invalid Ubisoft account UUID")
         || message.contains("Invalid Epic account ID")
         || message.contains("Invalid GOG account ID")
         || message.contains("Invalid Jagex account ID")
-        || message.contains("Invalid Discord account ID");
+        || message.contains("Invalid Discord account ID")
+        || message.contains("Invalid Riot account ID");
     if unknown_account {
         ("unknown_account", exit::UNKNOWN_ACCOUNT)
+    } else if error.kind == PlatformErrorKind::SnapshotCorrupt {
+        ("snapshot_corrupt", exit::GENERIC)
     } else {
         ("platform_error", exit::GENERIC)
     }
 }
 
-/// The dry run: the same walk over the same descriptor a switch would take,
-/// stopping short of every write.
+/// The dry run: the same walk over the same descriptor the operation would
+/// take, stopping short of every write.
 ///
 /// Deliberately outside the operation lock. It changes nothing, so making it
 /// contend with a running switch would only teach users to run it less.
-fn cmd_dry_run(format: Format, platform_id: &str, account_id: &str) -> u8 {
+fn cmd_dry_run(
+    format: Format,
+    platform_id: &str,
+    operation: &str,
+    account_id: Option<&str>,
+    overrides: SwitchOverrides,
+    app_id: Option<String>,
+) -> u8 {
+    let operation = match operation.parse::<DryRunOperation>() {
+        Ok(operation) => operation,
+        Err(message) => {
+            emit_err(format, "dry-run", "bad_argument", &message);
+            return exit::GENERIC;
+        }
+    };
+    let account_id = match account_id {
+        Some(id) => id,
+        None if operation.takes_account() => {
+            emit_err(
+                format,
+                "dry-run",
+                "bad_argument",
+                &format!("A {} needs the account it acts on.", operation.as_str()),
+            );
+            return exit::GENERIC;
+        }
+        None => "",
+    };
+
     let ctx = match build_ctx(format, "dry-run") {
         Ok(c) => c,
         Err(code) => return code,
     };
 
-    if !settings::load(&*ctx).cli_enabled {
+    let app_settings = settings::load(&*ctx);
+    if !app_settings.cli_enabled {
         emit_err(format, "dry-run", "cli_disabled", CLI_DISABLED_MESSAGE);
         return exit::CLI_DISABLED;
     }
@@ -512,17 +794,25 @@ fn cmd_dry_run(format: Format, platform_id: &str, account_id: &str) -> u8 {
 
     // Asked rather than inferred from the error text: a platform with no plan
     // is a different answer from a plan that failed to build.
-    if !service.supports_dry_run() {
+    if !service.supports_dry_run(operation) {
         emit_err(
             format,
             "dry-run",
             "dry_run_unsupported",
-            &format!("{platform_id} is not described by a descriptor, so it has no plan to show."),
+            &format!(
+                "{platform_id} has no {} plan to show: only Steam's switch and platforms \
+                 described by a descriptor do.",
+                operation.as_str()
+            ),
         );
         return exit::GENERIC;
     }
 
-    let value = match service.dry_run(ctx, account_id) {
+    let mut params = overrides.params(app_settings.platform_settings.steam);
+    if let Some(app_id) = app_id {
+        params["appId"] = json!(app_id);
+    }
+    let value = match service.dry_run(ctx, operation, account_id, params) {
         Ok(v) => v,
         Err(e) => {
             let message = e.to_string();
@@ -593,3 +883,124 @@ fn cmd_descriptors(format: Format) -> u8 {
     // status that would also mean "could not look".
     exit::OK
 }
+
+/// The descriptor format as JSON Schema, for an editor to complete against.
+/// Needs no context: the schema is the same on every machine.
+fn cmd_descriptor_schema(format: Format, write: Option<std::path::PathBuf>) -> u8 {
+    use accshift_core::platforms::descriptor::json_schema;
+
+    if let Some(dir) = write {
+        return match json_schema::write_docs(&dir) {
+            Ok(written) => {
+                match format {
+                    Format::Json => {
+                        emit_json_ok("descriptors-schema", json!({ "written": written }))
+                    }
+                    Format::Human => {
+                        for name in written {
+                            println!("wrote {}", dir.join(name).display());
+                        }
+                    }
+                }
+                exit::OK
+            }
+            Err(reason) => {
+                emit_err(format, "descriptors-schema", "io", &reason);
+                exit::IO
+            }
+        };
+    }
+
+    let schema = json_schema::descriptor_schema();
+    match format {
+        Format::Json => emit_json_ok("descriptors-schema", schema),
+        Format::Human => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&schema).unwrap_or_default()
+            );
+        }
+    }
+
+    exit::OK
+}
+
+/// Runs a descriptor end to end against a scratch folder. Needs no context
+/// either: nothing it does reaches the app's own data.
+fn cmd_descriptor_test(format: Format, file: &std::path::Path, os: Option<&str>, keep: bool) -> u8 {
+    use accshift_core::platforms::descriptor::harness::{self, HarnessOptions};
+    use accshift_core::platforms::descriptor::schema::Os;
+
+    let os = os.map(|name| match name {
+        "windows" => Os::Windows,
+        "macos" => Os::Macos,
+        _ => Os::Linux,
+    });
+
+    let report = match harness::run_file(file, &HarnessOptions { os, keep }) {
+        Ok(report) => report,
+        // A file that cannot be read has no field to blame.
+        Err(e) if e.field.is_empty() => {
+            let message = format!("{}: {}", e.source, e.problem);
+            emit_err(format, "descriptors-test", "descriptor_invalid", &message);
+            return exit::GENERIC;
+        }
+        Err(e) => {
+            emit_err(
+                format,
+                "descriptors-test",
+                "descriptor_invalid",
+                &e.to_string(),
+            );
+            return exit::GENERIC;
+        }
+    };
+
+    match format {
+        Format::Json => emit_json_ok("descriptors-test", &report),
+        Format::Human => output::render_harness(&report),
+    }
+
+    if report.ok {
+        exit::OK
+    } else {
+        exit::GENERIC
+    }
+}
+
+/// Lints a descriptor file. Like `descriptors test`, it needs no context and
+/// reads nothing but the file.
+fn cmd_descriptor_lint(format: Format, file: &std::path::Path) -> u8 {
+    use accshift_core::platforms::descriptor::lint;
+
+    let warnings = match lint::lint_file(file) {
+        Ok(warnings) => warnings,
+        Err(e) if e.field.is_empty() => {
+            let message = format!("{}: {}", e.source, e.problem);
+            emit_err(format, "descriptors-lint", "descriptor_invalid", &message);
+            return exit::GENERIC;
+        }
+        Err(e) => {
+            emit_err(
+                format,
+                "descriptors-lint",
+                "descriptor_invalid",
+                &e.to_string(),
+            );
+            return exit::GENERIC;
+        }
+    };
+
+    match format {
+        Format::Json => emit_json_ok("descriptors-lint", json!({ "warnings": warnings })),
+        Format::Human => output::render_lint(&warnings),
+    }
+
+    // A warning fails the run, so a check in CI can hold a descriptor to
+    // clean; the file still loads in the app either way.
+    if warnings.is_empty() {
+        exit::OK
+    } else {
+        exit::GENERIC
+    }
+}
@@ -4,11 +4,18 @@
 //! Piped or with `--json`: a stable `accshift.v1` envelope on stdout.
 //! Errors always go to stderr so stdout stays parseable.
 
+use accshift_core::diagnostics::query::format_ts;
+use accshift_core::platforms::descriptor::harness::HarnessReport;
+use accshift_core::platforms::descriptor::lint::LintWarning;
 use accshift_core::platforms::descriptor::plan::DryRunPlan;
+use accshift_core::platforms::descriptor::schema::AccountField;
+use accshift_core::platforms::steam::library::{AccountLibrary, AppOwner};
 use accshift_core::platforms::UserPlatformReport;
+use accshift_core::snapshot_history::SnapshotGeneration;
 use is_terminal::IsTerminal;
 use serde::Serialize;
 use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use unicode_width::UnicodeWidthStr;
 
 pub const SCHEMA: &str = "accshift.v1";
@@ -84,7 +91,35 @@ pub fn render_descriptors(report: &UserPlatformReport) {
     } else {
         println!("Added platforms:");
         for descriptor in &report.loaded {
-            println!("  {} ({})", descriptor.id, descriptor.name);
+            let signer = report
+                .signers
+                .get(&descriptor.id)
+                .map(|signer| signer.label())
+                .unwrap_or_default();
+            if descriptor.extends.is_empty() {
+                println!("  {} ({}), {signer}", descriptor.id, descriptor.name);
+            } else {
+                println!(
+                    "  {} ({}), {signer}, extends {}",
+                    descriptor.id, descriptor.name, descriptor.extends
+                );
+                // The fields the file leaves to its parent, so a reader can
+                // tell an upstream change from one made here.
+                println!("    inherited: {}", descriptor.inherited.join(", "));
+            }
+        }
+    }
+
+    if !report.lint.is_empty() {
+        println!();
+        println!("Warnings:");
+        for (id, warnings) in &report.lint {
+            for warning in warnings {
+                println!(
+                    "  {id}: [{}] `{}` {}",
+                    warning.code, warning.field, warning.message
+                );
+            }
         }
     }
 
@@ -112,11 +147,46 @@ pub fn render_descriptors(report: &UserPlatformReport) {
     }
 }
 
+pub fn render_lint(warnings: &[LintWarning]) {
+    if warnings.is_empty() {
+        println!("No warnings.");
+        return;
+    }
+    for warning in warnings {
+        println!("[{}] `{}`", warning.code, warning.field);
+        println!("  {}", warning.message);
+    }
+}
+
+pub fn render_harness(report: &HarnessReport) {
+    for check in &report.checks {
+        println!(
+            "{:<5} {:<10} {}",
+            check.outcome.as_str(),
+            check.step,
+            check.detail
+        );
+    }
+
+    if !report.root.is_empty() {
+        println!();
+        println!("Scratch folder kept at {}", report.root);
+    }
+
+    println!();
+    if report.ok {
+        println!("{} passed.", report.platform);
+    } else {
+        println!("{} failed.", report.platform);
+    }
+}
+
 pub fn render_accounts(
     platform_id: &str,
     accounts: &[Value],
     current: Option<&str>,
     folder_filter: Option<&std::collections::HashSet<String>>,
+    fields: &[AccountField],
 ) {
     let mut rows: Vec<AccountRow> = accounts
         .iter()
@@ -136,22 +206,33 @@ pub fn render_accounts(
         return;
     }
 
-    let id_header = id_header_for(platform_id);
-    let primary_header = primary_header_for(platform_id);
+    let column = |header: &str, cell: &dyn Fn(&AccountRow) -> String| {
+        (
+            header.to_string(),
+            rows.iter().map(cell).collect::<Vec<_>>(),
+        )
+    };
+    let mut columns = vec![
+        column(id_header_for(platform_id), &|r| r.id.clone()),
+        column(primary_header_for(platform_id), &|r| r.primary.clone()),
+    ];
     let secondary_header = secondary_header_for(platform_id);
-
-    let id_w = column_width(&rows, |r| &r.id, id_header);
-    let primary_w = column_width(&rows, |r| &r.primary, primary_header);
+    if !secondary_header.is_empty() {
+        columns.push(column(secondary_header, &|r| r.secondary.clone()));
+    }
+    // A descriptor's account fields follow, a path excepted: it names a file
+    // for the app to show, not something to read in a terminal.
+    for field in fields.iter().filter(|field| field.kind.is_listed()) {
+        columns.push(column(&field.label.to_uppercase(), &|r| {
+            r.fields.get(&field.name).cloned().unwrap_or_default()
+        }));
+    }
 
     // The "  " leading gutter holds the "*" marker for the current account.
-    println!(
-        "  {}  {}  {}",
-        pad(id_header, id_w),
-        pad(primary_header, primary_w),
-        secondary_header,
-    );
+    let headers: Vec<&str> = columns.iter().map(|(header, _)| header.as_str()).collect();
+    println!("  {}", join_columns(&columns, &headers));
 
-    for row in &rows {
+    for (index, row) in rows.iter().enumerate() {
         let marker = if current
             .map(|c| c.eq_ignore_ascii_case(&row.id))
             .unwrap_or(false)
@@ -160,12 +241,11 @@ pub fn render_accounts(
         } else {
             " "
         };
-        println!(
-            "{marker} {}  {}  {}",
-            pad(&row.id, id_w),
-            pad(&row.primary, primary_w),
-            row.secondary,
-        );
+        let cells: Vec<&str> = columns
+            .iter()
+            .map(|(_, cells)| cells[index].as_str())
+            .collect();
+        println!("{marker} {}", join_columns(&columns, &cells));
     }
 
     println!();
@@ -178,17 +258,114 @@ pub fn render_accounts(
     }
 }
 
+/// A day is as precise as "last played" needs to be.
+fn played_on(last_played: Option<u64>) -> String {
+    last_played.map_or_else(
+        || "never".to_string(),
+        |secs| format_ts(u128::from(secs) * 1000)[..10].to_string(),
+    )
+}
+
+fn hours(minutes: u64) -> String {
+    format!("{:.1}h", minutes as f64 / 60.0)
+}
+
+pub fn render_libraries(accounts: &[AccountLibrary]) {
+    if accounts.is_empty() {
+        println!("No Steam accounts on this machine.");
+        return;
+    }
+    for (index, library) in accounts.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!(
+            "{} ({}), {} game(s)",
+            library.account_name,
+            library.steam_id,
+            library.games.len()
+        );
+        if let Some(error) = &library.error {
+            println!("  play times unavailable: {error}");
+        }
+        for game in &library.games {
+            let name = if game.name.is_empty() {
+                "-"
+            } else {
+                game.name.as_str()
+            };
+            println!(
+                "  {:<10} {:>8} {:<9} {:>8}  {name}",
+                played_on(game.last_played),
+                hours(game.playtime_minutes),
+                if game.installed { "installed" } else { "" },
+                game.app_id,
+            );
+        }
+    }
+}
+
+pub fn render_app_owners(app_id: u32, name: Option<&str>, owners: &[AppOwner]) {
+    match name {
+        Some(name) => println!("{name} ({app_id})"),
+        None => println!("App {app_id}"),
+    }
+    if owners.is_empty() {
+        println!("No account on this machine has it.");
+        return;
+    }
+    println!(
+        "{:<20} {:<10} {:>8} INSTALLED",
+        "ACCOUNT", "PLAYED", "PLAYTIME"
+    );
+    for owner in owners {
+        println!(
+            "{:<20} {:<10} {:>8} {}",
+            owner.account_name,
+            played_on(owner.last_played),
+            hours(owner.playtime_minutes),
+            if owner.installed { "yes" } else { "no" }
+        );
+    }
+}
+
 pub fn render_switch_ok(platform_id: &str, account_id: &str) {
     println!("Switched {platform_id} to {account_id}.");
 }
 
 /// The dry run, read top to bottom: what stays untouched, then what would
 /// happen in order, then what would go wrong.
-pub fn render_dry_run(plan: &DryRunPlan) {
+pub fn render_generations(generations: &[SnapshotGeneration]) {
+    if generations.is_empty() {
+        println!("No earlier capture kept.");
+        return;
+    }
     println!(
-        "Dry run: {} {} to {}. Nothing below is written.",
-        plan.operation, plan.platform_id, plan.account_id,
+        "{:<15} {:<22} {:<11} FILES",
+        "GENERATION", "CAPTURED", "STATUS"
     );
+    for generation in generations {
+        println!(
+            "{:<15} {:<22} {:<11} {}",
+            generation.captured_at,
+            format_ts(u128::from(generation.captured_at)),
+            generation.status,
+            generation
+                .files
+                .map_or_else(|| "-".to_string(), |files| files.to_string())
+        );
+    }
+}
+
+pub fn render_dry_run(plan: &DryRunPlan) {
+    let heading = match (plan.operation.as_str(), plan.account_id.as_str()) {
+        ("switch", account) => format!("switch {} to {account}", plan.platform_id),
+        ("setup", "") => format!("add a {} account", plan.platform_id),
+        ("setup", account) => format!("add the signed-in {} account {account}", plan.platform_id),
+        ("capture", "") => format!("capture {}", plan.platform_id),
+        (operation, account) => format!("{operation} {} account {account}", plan.platform_id),
+    };
+    println!("Dry run: {heading}. Nothing below is written.");
 
     println!();
     if plan.roots.is_empty() {
@@ -231,6 +408,8 @@ pub struct AccountRow {
     /// The ID used by the GUI folder store to reference this account.
     /// Differs from `id` for Steam (account_name vs steam_id) and Roblox.
     pub folder_id: String,
+    /// A descriptor platform's account fields, by name.
+    pub fields: BTreeMap<String, String>,
 }
 
 pub fn extract_row(platform_id: &str, account: &Value) -> Option<AccountRow> {
@@ -252,6 +431,7 @@ pub fn extract_row(platform_id: &str, account: &Value) -> Option<AccountRow> {
                 secondary: steam_id.clone(),
                 sort_key: get_num("last_login_at"),
                 folder_id: steam_id,
+                fields: BTreeMap::new(),
             })
         }
         "roblox" => {
@@ -262,16 +442,7 @@ pub fn extract_row(platform_id: &str, account: &Value) -> Option<AccountRow> {
                 secondary: user_id.clone(),
                 sort_key: get_num("last_used_at"),
                 folder_id: user_id,
-            })
-        }
-        "riot" => {
-            let pid = nonempty(get("id"))?;
-            Some(AccountRow {
-                id: pid.clone(),
-                primary: get("label"),
-                secondary: format_riot_tag(&get("account_name"), &get("account_tag_line")),
-                sort_key: get_num("last_used_at"),
-                folder_id: pid,
+                fields: BTreeMap::new(),
             })
         }
         "battle-net" => {
@@ -282,46 +453,32 @@ pub fn extract_row(platform_id: &str, account: &Value) -> Option<AccountRow> {
                 secondary: String::new(),
                 sort_key: get_num("last_used_at"),
                 folder_id: email,
+                fields: BTreeMap::new(),
             })
         }
-        "ubisoft" => {
-            let uuid = nonempty(get("uuid"))?;
-            Some(AccountRow {
-                id: uuid.clone(),
-                primary: get("label"),
-                secondary: String::new(),
-                sort_key: get_num("last_used_at"),
-                folder_id: uuid,
-            })
-        }
-        "epic" => {
-            let account_id = nonempty(get("account_id"))?;
-            Some(AccountRow {
-                id: account_id.clone(),
-                primary: get("label"),
-                secondary: String::new(),
-                sort_key: get_num("last_used_at"),
-                folder_id: account_id,
-            })
-        }
-        "gog" | "jagex" => {
-            let account_id = nonempty(get("account_id"))?;
-            Some(AccountRow {
-                id: account_id.clone(),
-                primary: get("label"),
-                secondary: String::new(),
-                sort_key: get_num("last_used_at"),
-                folder_id: account_id,
-            })
-        }
-        "discord" => {
-            let account_id = nonempty(get("account_id"))?;
+        // Every platform the descriptor engine runs, shipped or added by the
+        // user, lists its accounts in the one shape.
+        _ if account.get("accountId").is_some() => {
+            let account_id = nonempty(get("accountId"))?;
+            let fields = account
+                .get("fields")
+                .and_then(Value::as_object)
+                .map(|fields| {
+                    fields
+                        .iter()
+                        .filter_map(|(name, value)| {
+                            Some((name.clone(), value.as_str()?.to_string()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
             Some(AccountRow {
                 id: account_id.clone(),
                 primary: get("label"),
                 secondary: String::new(),
-                sort_key: get_num("last_used_at"),
+                sort_key: get_num("lastUsedAt"),
                 folder_id: account_id,
+                fields,
             })
         }
         _ => {
@@ -340,6 +497,7 @@ pub fn extract_row(platform_id: &str, account: &Value) -> Option<AccountRow> {
                 secondary: String::new(),
                 sort_key: 0,
                 folder_id: id,
+                fields: BTreeMap::new(),
             })
         }
     }
@@ -353,23 +511,13 @@ fn nonempty(s: String) -> Option<String> {
     }
 }
 
-fn format_riot_tag(name: &str, tag: &str) -> String {
-    match (name.is_empty(), tag.is_empty()) {
-        (true, true) => String::new(),
-        (false, true) => name.to_string(),
-        (true, false) => format!("#{tag}"),
-        (false, false) => format!("{name}#{tag}"),
-    }
-}
-
 fn id_header_for(platform_id: &str) -> &'static str {
     match platform_id {
         "steam" => "ACCOUNT",
         "roblox" => "USERNAME",
-        "riot" => "PROFILE ID",
         "battle-net" => "EMAIL",
         "ubisoft" => "UUID",
-        "epic" | "gog" | "jagex" | "discord" => "ACCOUNT ID",
+        "riot" | "epic" | "gog" | "jagex" | "discord" => "ACCOUNT ID",
         _ => "ID",
     }
 }
@@ -388,17 +536,33 @@ fn secondary_header_for(platform_id: &str) -> &'static str {
     match platform_id {
         "steam" => "STEAM ID",
         "roblox" => "USER ID",
-        "riot" => "RIOT ID",
         _ => "",
     }
 }
 
-fn column_width<F: Fn(&AccountRow) -> &str>(rows: &[AccountRow], field: F, header: &str) -> usize {
-    rows.iter()
-        .map(|r| display_width(field(r)))
-        .max()
-        .unwrap_or(0)
-        .max(display_width(header))
+/// One line of a table: each cell padded to its column, the last left as it
+/// is so no line ends in spaces.
+fn join_columns(columns: &[(String, Vec<String>)], cells: &[&str]) -> String {
+    let last = cells.len().saturating_sub(1);
+    cells
+        .iter()
+        .zip(columns)
+        .enumerate()
+        .map(|(index, (cell, (header, values)))| {
+            if index == last {
+                cell.to_string()
+            } else {
+                let width = values
+                    .iter()
+                    .map(|value| display_width(value))
+                    .chain([display_width(header)])
+                    .max()
+                    .unwrap_or(0);
+                pad(cell, width)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
 }
 
 /// Display width of a string in terminal columns (handles CJK + emoji).
@@ -27,22 +27,31 @@ const PBKDF2_ITERATIONS: u32 = 100_000;
 const HASH_BYTES: usize = 32;
 
 /// Prompt for the PIN and verify it against the stored hash. Returns `Ok(())`
-/// when the PIN matches; otherwise an exit code the caller should return
-/// without switching.
-pub fn enforce(format: Format, stored_hash: &str) -> Result<(), u8> {
+/// when the PIN matches; otherwise an exit code `command` should return
+/// without doing anything.
+pub fn enforce(format: Format, command: &str, stored_hash: &str) -> Result<(), u8> {
     if stored_hash.is_empty() {
         // PIN enabled but no usable hash recorded. Fail closed rather than
         // letting the switch through.
         emit_err(
             format,
-            "switch",
+            command,
             "pin_required",
             "PIN lock is enabled but no PIN hash is configured. Set a PIN in the app first.",
         );
         return Err(exit::PIN_DENIED);
     }
 
-    let attempt = match read_pin(format) {
+    if !std::io::stdin().is_terminal() {
+        emit_err(
+            format,
+            command,
+            "pin_required",
+            "PIN lock is enabled. Run this command from an interactive terminal to enter the PIN.",
+        );
+        return Err(exit::PIN_DENIED);
+    }
+    let attempt = match read_hidden(format, command, "Enter PIN") {
         Some(p) => p,
         None => return Err(exit::PIN_DENIED),
     };
@@ -50,41 +59,31 @@ pub fn enforce(format: Format, stored_hash: &str) -> Result<(), u8> {
     if verify_pin_code(&attempt, stored_hash) {
         Ok(())
     } else {
-        emit_err(
-            format,
-            "switch",
-            "pin_invalid",
-            "Incorrect PIN. The account switch was cancelled.",
-        );
+        let message = if command == "switch" {
+            "Incorrect PIN. The account switch was cancelled.".to_string()
+        } else {
+            format!("Incorrect PIN. `accshift {command}` was cancelled.")
+        };
+        emit_err(format, command, "pin_invalid", &message);
         Err(exit::PIN_DENIED)
     }
 }
 
-/// Read a PIN from the terminal. Local echo is suppressed with a best-effort,
-/// dependency-free platform call (no `rpassword` crate is available to
-/// `accshift-cli`); if suppression fails for any reason we fall back to a
-/// visible prompt and say so, rather than pretending the input is hidden.
-/// Returns `None` if no PIN could be read (no stdin, EOF).
-fn read_pin(format: Format) -> Option<String> {
-    // Only prompt interactively on a real TTY. In a pipe there is no human to
-    // answer, so refuse rather than block or silently pass.
-    if !std::io::stdin().is_terminal() {
-        emit_err(
-            format,
-            "switch",
-            "pin_required",
-            "PIN lock is enabled. Run this command from an interactive terminal to enter the PIN.",
-        );
-        return None;
-    }
-
+/// Read one line from the terminal after printing `label`. Local echo is
+/// suppressed with a best-effort, dependency-free platform call (no
+/// `rpassword` crate is available to `accshift-cli`); if suppression fails for
+/// any reason we fall back to a visible prompt and say so, rather than
+/// pretending the input is hidden. Returns `None` if nothing could be read
+/// (EOF). The caller checks stdin is a terminal first: in a pipe there is no
+/// human to answer.
+pub fn read_hidden(format: Format, command: &str, label: &str) -> Option<String> {
     let echo_guard = disable_echo();
 
     // Prompt on stderr so a `--json` stdout stays clean.
     if echo_guard.is_some() {
-        eprint!("Enter PIN: ");
+        eprint!("{label}: ");
     } else {
-        eprint!("Enter PIN (visible): ");
+        eprint!("{label} (visible): ");
     }
     let _ = std::io::stderr().flush();
 
@@ -102,7 +101,7 @@ fn read_pin(format: Format) -> Option<String> {
         Ok(0) => None, // EOF, no input
         Ok(_) => Some(line),
         Err(e) => {
-            emit_err(format, "switch", "io", &e.to_string());
+            emit_err(format, command, "io", &e.to_string());
             None
         }
     }
@@ -113,7 +112,7 @@ fn read_pin(format: Format) -> Option<String> {
 // ---------------------------------------------------------------------------
 //
 // This stays dependency-light and platform-gated. `disable_echo` returns `None` whenever it
-// cannot be sure echo was actually turned off, and `read_pin` treats that as
+// cannot be sure echo was actually turned off, and `read_hidden` treats that as
 // "stay visible" rather than silently claiming to hide input it did not hide.
 
 /// Opaque token needed to restore the terminal's previous echo state.
@@ -403,7 +402,7 @@ mod tests {
     // terminal is expected to get None back rather than an actual toggle.
     // The point of this test is only to lock the fail-safe contract: neither
     // call ever panics, and restoring a `None` guard is always a no-op, so
-    // read_pin's "fall back to a visible prompt" branch stays reachable
+    // read_hidden's "fall back to a visible prompt" branch stays reachable
     // instead of the whole read failing.
     #[test]
     fn echo_toggle_never_panics_and_none_guard_restores_as_no_op() {
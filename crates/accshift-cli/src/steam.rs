@@ -0,0 +1,112 @@
+//! `accshift steam games`: every Steam account's games in one list, or the
+//! accounts that have one game.
+//!
+//! Nothing is written, so this runs outside the operation lock like
+//! `dry-run`.
+
+use crate::output::{self, emit_err, emit_json_ok, Format};
+use crate::{exit, settings, CLI_DISABLED_MESSAGE};
+use accshift_core::platforms::steam::{
+    self,
+    library::{sort_games, LibrarySort},
+};
+use clap::Subcommand;
+use serde_json::json;
+
+const COMMAND: &str = "steam-games";
+
+#[derive(Subcommand)]
+pub enum Steam {
+    /// List each account's games, most recently played first, from what the
+    /// accounts left on this machine.
+    Games {
+        /// Only this account: its SteamID64 or account name.
+        #[arg(long)]
+        account: Option<String>,
+        /// List the accounts that have this app id instead.
+        #[arg(long, value_name = "APP_ID", conflicts_with = "account")]
+        app: Option<u32>,
+        /// The order games are listed in.
+        #[arg(long, default_value = "last-played", value_parser = ["last-played", "name", "playtime"])]
+        sort: String,
+    },
+}
+
+impl Steam {
+    /// Name reported to telemetry: the action, never its arguments.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Steam::Games { .. } => COMMAND,
+        }
+    }
+}
+
+pub fn run(format: Format, action: Steam) -> u8 {
+    match action {
+        Steam::Games { account, app, sort } => cmd_games(format, account.as_deref(), app, &sort),
+    }
+}
+
+fn cmd_games(format: Format, account: Option<&str>, app: Option<u32>, sort: &str) -> u8 {
+    let ctx = match crate::build_ctx(format, COMMAND) {
+        Ok(c) => c,
+        Err(code) => return code,
+    };
+    if !settings::load(&*ctx).cli_enabled {
+        emit_err(format, COMMAND, "cli_disabled", CLI_DISABLED_MESSAGE);
+        return exit::CLI_DISABLED;
+    }
+
+    let index = match steam::get_library(ctx) {
+        Ok(index) => index,
+        Err(e) => {
+            let message = e.to_string();
+            let (code, status) = crate::classify(&e, &message);
+            emit_err(format, COMMAND, code, &message);
+            return status;
+        }
+    };
+
+    if let Some(app_id) = app {
+        let owners = index.owners(app_id);
+        let name = index.app_name(app_id);
+        match format {
+            Format::Json => emit_json_ok(
+                COMMAND,
+                json!({ "appId": app_id, "name": name, "owners": owners }),
+            ),
+            Format::Human => output::render_app_owners(app_id, name, &owners),
+        }
+        return exit::OK;
+    }
+
+    let mut accounts = index.accounts;
+    if let Some(wanted) = account {
+        accounts.retain(|library| {
+            library.steam_id == wanted || library.account_name.eq_ignore_ascii_case(wanted)
+        });
+        if accounts.is_empty() {
+            emit_err(
+                format,
+                COMMAND,
+                "unknown_account",
+                &format!("No Steam account {wanted} on this machine."),
+            );
+            return exit::UNKNOWN_ACCOUNT;
+        }
+    }
+    let sort = match sort {
+        "name" => LibrarySort::Name,
+        "playtime" => LibrarySort::Playtime,
+        _ => LibrarySort::LastPlayed,
+    };
+    for library in &mut accounts {
+        sort_games(&mut library.games, sort);
+    }
+
+    match format {
+        Format::Json => emit_json_ok(COMMAND, json!({ "accounts": accounts })),
+        Format::Human => output::render_libraries(&accounts),
+    }
+    exit::OK
+}
@@ -0,0 +1,186 @@
+//! `accshift export` and `accshift import`: move accounts to another machine
+//! in one passphrase-sealed archive.
+//!
+//! Both run under the operation lock and behind the PIN, like a switch: an
+//! export holds every session in the clear until it is sealed, and an import
+//! writes them into this machine's secret store.
+
+use crate::output::{emit_err, emit_json_ok, Format};
+use crate::{exit, pin, settings, CLI_DISABLED_MESSAGE, LOCK_TIMEOUT};
+use accshift_core::lock::{acquire_exclusive, LockError};
+use accshift_core::transfer::{self, TransferError};
+use is_terminal::IsTerminal;
+use serde_json::json;
+use std::path::Path;
+
+pub fn cmd_export(format: Format, file: &Path, passphrase_env: Option<&str>) -> u8 {
+    let ctx = match prepare(format, "export") {
+        Ok(ctx) => ctx,
+        Err(code) => return code,
+    };
+    let passphrase = match passphrase(format, "export", passphrase_env, true) {
+        Ok(passphrase) => passphrase,
+        Err(code) => return code,
+    };
+    let _lock = match lock(format, "export", &ctx) {
+        Ok(guard) => guard,
+        Err(code) => return code,
+    };
+
+    let summary = match transfer::export(&*ctx, file, &passphrase) {
+        Ok(summary) => summary,
+        Err(e) => return fail(format, "export", &e),
+    };
+    match format {
+        Format::Json => emit_json_ok(
+            "export",
+            json!({
+                "path": file.display().to_string(),
+                "accounts": summary.accounts,
+                "skipped": summary.skipped,
+            }),
+        ),
+        Format::Human => {
+            println!(
+                "Exported {} account(s) to {}.",
+                summary.accounts.len(),
+                file.display()
+            );
+            for skipped in &summary.skipped {
+                println!(
+                    "  left out {} {}: {}",
+                    skipped.platform_id, skipped.account_id, skipped.reason
+                );
+            }
+            println!("Anyone with the file and its passphrase can sign in as these accounts.");
+        }
+    }
+    exit::OK
+}
+
+pub fn cmd_import(format: Format, file: &Path, passphrase_env: Option<&str>) -> u8 {
+    let ctx = match prepare(format, "import") {
+        Ok(ctx) => ctx,
+        Err(code) => return code,
+    };
+    let passphrase = match passphrase(format, "import", passphrase_env, false) {
+        Ok(passphrase) => passphrase,
+        Err(code) => return code,
+    };
+    let _lock = match lock(format, "import", &ctx) {
+        Ok(guard) => guard,
+        Err(code) => return code,
+    };
+
+    let report = match transfer::import(&*ctx, file, &passphrase) {
+        Ok(report) => report,
+        Err(e) => return fail(format, "import", &e),
+    };
+    match format {
+        Format::Json => emit_json_ok("import", json!(report)),
+        Format::Human => {
+            println!("Imported {} account(s).", report.imported.len());
+            for conflict in &report.conflicts {
+                println!(
+                    "  kept {} {} as it was: {}",
+                    conflict.platform_id, conflict.account_id, conflict.reason
+                );
+            }
+        }
+    }
+    exit::OK
+}
+
+/// Context, the CLI toggle and the PIN, in the order a switch checks them.
+fn prepare(format: Format, command: &str) -> Result<accshift_core::AppCtx, u8> {
+    let ctx = crate::build_ctx(format, command)?;
+    let app_settings = settings::load(&*ctx);
+    if !app_settings.cli_enabled {
+        emit_err(format, command, "cli_disabled", CLI_DISABLED_MESSAGE);
+        return Err(exit::CLI_DISABLED);
+    }
+    if app_settings.pin_enabled {
+        pin::enforce(format, command, &app_settings.pin_hash)?;
+    }
+    Ok(ctx)
+}
+
+/// The passphrase from the named environment variable, or typed at the
+/// terminal. An export asks twice: a typo there locks the archive for good.
+fn passphrase(
+    format: Format,
+    command: &str,
+    env: Option<&str>,
+    confirm: bool,
+) -> Result<String, u8> {
+    if let Some(name) = env {
+        return std::env::var(name).map_err(|_| {
+            emit_err(
+                format,
+                command,
+                "bad_argument",
+                &format!("The environment variable {name} is not set."),
+            );
+            exit::GENERIC
+        });
+    }
+    if !std::io::stdin().is_terminal() {
+        emit_err(
+            format,
+            command,
+            "passphrase_required",
+            "Run this command from an interactive terminal to enter the passphrase, or pass --passphrase-env <VAR>.",
+        );
+        return Err(exit::GENERIC);
+    }
+
+    let read = |label: &str| {
+        pin::read_hidden(format, command, label)
+            .map(|line| line.trim_end_matches(['\r', '\n']).to_string())
+            .ok_or(exit::GENERIC)
+    };
+    let first = read("Passphrase")?;
+    if confirm && read("Repeat passphrase")? != first {
+        emit_err(
+            format,
+            command,
+            "passphrase_mismatch",
+            "The two passphrases differ. Nothing was written.",
+        );
+        return Err(exit::GENERIC);
+    }
+    Ok(first)
+}
+
+fn lock(
+    format: Format,
+    command: &str,
+    ctx: &accshift_core::AppCtx,
+) -> Result<accshift_core::lock::LockGuard, u8> {
+    acquire_exclusive(&**ctx, LOCK_TIMEOUT).map_err(|e| match e {
+        LockError::Contended => {
+            emit_err(
+                format,
+                command,
+                "lock_contended",
+                "Another accshift instance is running. Retry once it finishes, or close the GUI.",
+            );
+            exit::LOCK_CONTENDED
+        }
+        LockError::Io(e) => {
+            emit_err(format, command, "io", &e);
+            exit::IO
+        }
+    })
+}
+
+fn fail(format: Format, command: &str, error: &TransferError) -> u8 {
+    let (code, status) = match error {
+        TransferError::Passphrase => ("bad_passphrase", exit::GENERIC),
+        TransferError::WeakPassphrase => ("bad_argument", exit::GENERIC),
+        TransferError::Invalid(_) => ("invalid_archive", exit::GENERIC),
+        TransferError::Io(_) => ("io", exit::IO),
+    };
+    emit_err(format, command, code, &error.to_string());
+    status
+}
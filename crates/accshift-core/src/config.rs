@@ -52,6 +52,8 @@ pub struct RiotProfileConfig {
     pub last_captured_at: Option<u64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_used_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub launch_values: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -100,6 +102,10 @@ pub struct UbisoftAccountConfig {
     pub label: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_used_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub launch_values: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -130,6 +136,10 @@ pub struct EpicAccountConfig {
     pub label: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_used_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub launch_values: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -148,6 +158,10 @@ pub struct GogAccountConfig {
     pub label: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_used_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub launch_values: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -166,6 +180,10 @@ pub struct JagexAccountConfig {
     pub label: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_used_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub launch_values: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -176,6 +194,10 @@ pub struct DiscordAccountConfig {
     pub label: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_used_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub launch_values: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -210,6 +232,23 @@ pub struct CustomAccountConfig {
     pub label: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_used_at: Option<u64>,
+    /// Per-account values a descriptor's `launch` block expands through
+    /// `${account.<name>}`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub launch_values: BTreeMap<String, String>,
+    /// The descriptor's account fields (tag line, region...), as its identity
+    /// source last read them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+}
+
+/// A descriptor signing key the user vouched for.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+pub struct TrustedPublisher {
+    /// How a descriptor signed with this key is labelled.
+    pub name: String,
+    /// The raw 32-byte Ed25519 public key, base64.
+    pub public_key: String,
 }
 
 /// The section of a platform this build was never compiled to know about.
@@ -308,6 +347,15 @@ pub struct AppConfig {
     pub custom_platforms: BTreeMap<String, CustomPlatformConfig>,
     #[serde(default)]
     pub telemetry: TelemetryConfig,
+    /// Earlier captures kept per account next to its snapshot. Unset keeps
+    /// [`crate::snapshot_history::DEFAULT_GENERATIONS`], 0 keeps none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_generations: Option<u32>,
+    /// Keys a user descriptor may be signed with to be shown as coming from a
+    /// known publisher. Portable: the same team trusts the same keys on every
+    /// machine.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_publishers: Vec<TrustedPublisher>,
     #[serde(default)]
     pub window_width: Option<f64>,
     #[serde(default)]
@@ -337,6 +385,10 @@ struct RawAppConfig {
     custom_platforms: Option<BTreeMap<String, CustomPlatformConfig>>,
     #[serde(default)]
     telemetry: Option<TelemetryConfig>,
+    #[serde(default)]
+    snapshot_generations: Option<u32>,
+    #[serde(default)]
+    trusted_publishers: Vec<TrustedPublisher>,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     steam_api_key: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -370,6 +422,8 @@ struct RawRiotProfileConfig {
     #[serde(default)]
     last_used_at: Option<u64>,
     #[serde(default)]
+    launch_values: BTreeMap<String, String>,
+    #[serde(default)]
     username: String,
     #[serde(default)]
     display_name: String,
@@ -493,6 +547,7 @@ fn normalize_riot_profile(raw: RawRiotProfileConfig) -> RiotProfileConfig {
         notes: raw.notes,
         last_captured_at: raw.last_captured_at.or(raw.last_login_at),
         last_used_at: raw.last_used_at.or(raw.last_login_at),
+        launch_values: raw.launch_values,
     }
 }
 
@@ -554,6 +609,8 @@ fn normalize_config(raw: RawAppConfig) -> AppConfig {
         discord,
         custom_platforms,
         telemetry,
+        snapshot_generations: raw.snapshot_generations,
+        trusted_publishers: raw.trusted_publishers,
         window_width: raw.window_width,
         window_height: raw.window_height,
     }
@@ -1373,6 +1430,11 @@ mod tests {
             },
             custom_platforms: Default::default(),
             telemetry: TelemetryConfig::default(),
+            snapshot_generations: Some(5),
+            trusted_publishers: vec![TrustedPublisher {
+                name: "Team".into(),
+                public_key: "key".into(),
+            }],
             window_width: Some(1200.0),
             window_height: Some(800.0),
         };
@@ -1402,6 +1464,8 @@ mod tests {
         assert_eq!(p.riot.profiles.len(), 1);
         assert_eq!(p.riot.profiles[0].label, "Main");
         assert_eq!(p.roblox.accounts[0].username, "player1");
+        assert_eq!(p.snapshot_generations, Some(5));
+        assert_eq!(p.trusted_publishers.len(), 1);
     }
 
     #[test]
@@ -1460,6 +1524,11 @@ mod tests {
             },
             custom_platforms: Default::default(),
             telemetry: TelemetryConfig::default(),
+            snapshot_generations: Some(5),
+            trusted_publishers: vec![TrustedPublisher {
+                name: "Team".into(),
+                public_key: "key".into(),
+            }],
             window_width: Some(1024.0),
             window_height: Some(768.0),
         };
@@ -1477,6 +1546,8 @@ mod tests {
         assert_eq!(l.gog.path_override, "C:\\GOG");
         assert_eq!(l.jagex.path_override, "C:\\Jagex");
         assert_eq!(l.window_width, Some(1024.0));
+        assert!(l.snapshot_generations.is_none());
+        assert!(l.trusted_publishers.is_empty());
         assert_eq!(l.window_height, Some(768.0));
 
         // Roblox local keeps user_id + cookie, but not username/display_name
@@ -79,6 +79,10 @@ pub enum PlatformErrorKind {
     Io,
     /// Cryptography failure (secret encryption/decryption, snapshot crypto).
     Crypto,
+    /// A stored snapshot no longer matches the manifest written when it was
+    /// captured: truncated, partly synced or altered. Restoring it would sign
+    /// the account out, so the restore is refused.
+    SnapshotCorrupt,
     /// Unclassified: the default for errors migrated from plain strings.
     Other,
 }
@@ -186,6 +190,18 @@ impl From<crate::lock::LockError> for PlatformError {
     }
 }
 
+impl From<crate::transfer::TransferError> for PlatformError {
+    fn from(e: crate::transfer::TransferError) -> Self {
+        use crate::transfer::TransferError;
+        let kind = match &e {
+            TransferError::Passphrase => PlatformErrorKind::Crypto,
+            TransferError::Io(_) => PlatformErrorKind::Io,
+            TransferError::WeakPassphrase | TransferError::Invalid(_) => PlatformErrorKind::Other,
+        };
+        Self::new(kind, e.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
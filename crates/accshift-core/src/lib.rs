@@ -9,8 +9,12 @@ pub mod os;
 pub mod platforms;
 pub mod runtime;
 pub mod snapshot_crypto;
+pub mod snapshot_history;
+pub mod snapshot_manifest;
 pub mod storage;
 pub mod telemetry;
 pub mod themes;
+pub mod transfer;
+pub mod watch;
 
 pub use context::{AppContext, AppCtx};
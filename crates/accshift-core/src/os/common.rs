@@ -257,6 +257,38 @@ pub fn kill_process(process_name: &str) -> Result<(), AppError> {
     }
 }
 
+/// pids of the live processes matching any of `process_names`, from one scan.
+/// Windows needs them to find the windows those processes own.
+#[cfg(target_os = "windows")]
+pub fn live_pids(process_names: &[&str]) -> Vec<u32> {
+    let matchers: Vec<NameMatcher> = process_names.iter().map(|n| NameMatcher::new(n)).collect();
+    with_refreshed_system(|system| {
+        system
+            .processes()
+            .values()
+            .filter(|p| is_live(p) && matchers.iter().any(|m| m.matches(p)))
+            .map(|p| p.pid().as_u32())
+            .collect()
+    })
+}
+
+/// Send `SIGTERM` to every live process in `process_names`, which runs their
+/// exit handlers where `kill_process` would not. Returns whether any process
+/// took the signal.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub fn terminate_processes(process_names: &[&str]) -> bool {
+    let matchers: Vec<NameMatcher> = process_names.iter().map(|n| NameMatcher::new(n)).collect();
+    with_refreshed_system(|system| {
+        let mut signalled = false;
+        for p in system.processes().values() {
+            if is_live(p) && matchers.iter().any(|m| m.matches(p)) {
+                signalled |= p.kill_with(Signal::Term).unwrap_or(false);
+            }
+        }
+        signalled
+    })
+}
+
 /// Kill every process in `process_names`, best effort. Failures are ignored:
 /// callers that need a guarantee re-check with `any_process_running`.
 pub fn kill_processes(process_names: &[&str]) {
@@ -348,6 +380,13 @@ pub fn open_url(url: &str) -> Result<(), AppError> {
     open::that_detached(url).map_err(|e| AppError::ProcessStart(e.to_string()))
 }
 
+/// Hands `uri` to the handler registered for its scheme. There is no
+/// allowlist, unlike [`open_url`]: this is only reachable from a descriptor's
+/// close strategies, whose schema already refuses web and file URIs.
+pub fn open_protocol_uri(uri: &str) -> Result<(), AppError> {
+    open::that_detached(uri.trim()).map_err(|e| AppError::ProcessStart(e.to_string()))
+}
+
 pub fn open_folder(path: &Path) -> Result<(), AppError> {
     open::that_detached(path).map_err(|e| AppError::FolderOpen(e.to_string()))
 }
@@ -111,6 +111,10 @@ fn registry_vdf_path() -> Result<PathBuf, AppError> {
     Ok(home.join(".steam/registry.vdf"))
 }
 
+pub fn auto_login_store() -> Result<super::AutoLoginStore, AppError> {
+    Ok(super::AutoLoginStore::VdfFile(registry_vdf_path()?))
+}
+
 pub fn get_auto_login_user() -> Result<String, AppError> {
     super::steam_registry::get_auto_login_user(&registry_vdf_path()?)
 }
@@ -149,6 +153,10 @@ pub fn request_steam_shutdown(steam_path: &Path) -> bool {
         .is_ok()
 }
 
+pub fn request_window_close(process_names: &[&str]) -> bool {
+    super::common::terminate_processes(process_names)
+}
+
 pub fn launch_steam(
     steam_path: &Path,
     _run_as_admin: bool,
@@ -161,6 +169,16 @@ pub fn launch_steam(
     Ok(())
 }
 
+pub fn steam_launch_line(steam_path: &Path, launch_options: &[String]) -> String {
+    let mut command = steam_launch_command(steam_path);
+    command.args(launch_options);
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 // Builds the base command to launch/signal Steam for the install `steam_path`
 // resolved to, ready for the caller to append its own arguments (a
 // `-shutdown` request or the account's launch options).
@@ -50,6 +50,10 @@ fn registry_vdf_path() -> Result<PathBuf, AppError> {
     Ok(dir.join("registry.vdf"))
 }
 
+pub fn auto_login_store() -> Result<super::AutoLoginStore, AppError> {
+    Ok(super::AutoLoginStore::VdfFile(registry_vdf_path()?))
+}
+
 pub fn get_auto_login_user() -> Result<String, AppError> {
     super::steam_registry::get_auto_login_user(&registry_vdf_path()?)
 }
@@ -137,6 +141,20 @@ pub fn request_steam_shutdown(steam_path: &Path) -> bool {
     true
 }
 
+pub fn steam_launch_line(_steam_path: &Path, launch_options: &[String]) -> String {
+    // The `open` route, which is the one that normally succeeds.
+    let mut line = String::from("open -a Steam");
+    if !launch_options.is_empty() {
+        line.push_str(" --args ");
+        line.push_str(&launch_options.join(" "));
+    }
+    line
+}
+
+pub fn request_window_close(process_names: &[&str]) -> bool {
+    super::common::terminate_processes(process_names)
+}
+
 pub fn launch_steam(
     steam_path: &Path,
     _run_as_admin: bool,
@@ -87,6 +87,12 @@ pub fn open_url(url: &str) -> Result<(), AppError> {
     common::open_url(url)
 }
 
+/// Open a launcher's own protocol URI, such as `steam://exit`. Not for
+/// anything the webview can reach: see [`open_url`] for that.
+pub fn open_protocol_uri(uri: &str) -> Result<(), AppError> {
+    common::open_protocol_uri(uri)
+}
+
 pub fn open_folder(path: &Path) -> Result<(), AppError> {
     common::open_folder(path)
 }
@@ -190,6 +196,19 @@ pub fn clear_auto_login_user() -> Result<(), AppError> {
     imp::clear_auto_login_user()
 }
 
+/// Where [`set_auto_login_user`] writes: a registry key on Windows, Steam's
+/// own `registry.vdf` elsewhere. Named, never opened, by the Steam dry run.
+pub enum AutoLoginStore {
+    /// The key holding `AutoLoginUser` and `RememberPassword`.
+    Registry(&'static str),
+    /// The file holding them under `HKCU/Software/Valve/Steam`.
+    VdfFile(PathBuf),
+}
+
+pub fn auto_login_store() -> Result<AutoLoginStore, AppError> {
+    imp::auto_login_store()
+}
+
 /// Ask the running Steam client to exit cleanly. Returns `true` when the
 /// request was actually delivered; callers should only wait for the process
 /// to exit when this succeeds, and fall back to killing otherwise.
@@ -212,6 +231,18 @@ pub fn launch_steam(
     imp::launch_steam(steam_path, run_as_admin, launch_options)
 }
 
+/// The command line [`launch_steam`] would run, for a dry run to print.
+pub fn steam_launch_line(steam_path: &Path, launch_options: &[String]) -> String {
+    imp::steam_launch_line(steam_path, launch_options)
+}
+
+/// Ask `process_names` to exit the way closing their window would: `WM_CLOSE`
+/// to their top-level windows on Windows, `SIGTERM` elsewhere. Returns `true`
+/// when the request reached at least one process.
+pub fn request_window_close(process_names: &[&str]) -> bool {
+    imp::request_window_close(process_names)
+}
+
 pub fn select_folder(title: &str) -> Result<String, AppError> {
     imp::select_folder(title)
 }
@@ -5,12 +5,26 @@
 //! everything else is identical and lives here.
 
 use crate::error::AppError;
-use crate::platforms::steam::vdf::vdf_set_nested_value;
+use crate::platforms::steam::keyvalues::Document;
 use std::fs;
 use std::path::Path;
 
-const REGISTRY_PATH: &[&str] = &["HKCU", "Software", "Valve", "Steam", "AutoLoginUser"];
-const REMEMBER_PATH: &[&str] = &["HKCU", "Software", "Valve", "Steam", "RememberPassword"];
+const REGISTRY_PATH: &[&str] = &[
+    "Registry",
+    "HKCU",
+    "Software",
+    "Valve",
+    "Steam",
+    "AutoLoginUser",
+];
+const REMEMBER_PATH: &[&str] = &[
+    "Registry",
+    "HKCU",
+    "Software",
+    "Valve",
+    "Steam",
+    "RememberPassword",
+];
 
 pub fn get_auto_login_user(path: &Path) -> Result<String, AppError> {
     let content = match fs::read_to_string(path) {
@@ -18,7 +32,8 @@ pub fn get_auto_login_user(path: &Path) -> Result<String, AppError> {
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(String::new()),
         Err(e) => return Err(AppError::FileRead(e.to_string())),
     };
-    Ok(extract_registry_value(&content, "AutoLoginUser").unwrap_or_default())
+    let document = parse(path, &content)?;
+    Ok(document.get(REGISTRY_PATH).unwrap_or_default().to_string())
 }
 
 pub fn set_auto_login_user(path: &Path, username: &str) -> Result<(), AppError> {
@@ -30,9 +45,12 @@ pub fn set_auto_login_user(path: &Path, username: &str) -> Result<(), AppError>
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => empty_registry_vdf(),
         Err(e) => return Err(AppError::FileRead(e.to_string())),
     };
-    let updated = vdf_set_nested_value(&existing, REGISTRY_PATH, username);
-    let updated = vdf_set_nested_value(&updated, REMEMBER_PATH, "1");
-    crate::storage::write_bytes_atomic(path, updated.as_bytes())
+    let mut document = parse(path, &existing)?;
+    document
+        .set(REGISTRY_PATH, username)
+        .and_then(|()| document.set(REMEMBER_PATH, "1"))
+        .map_err(AppError::RegistryWrite)?;
+    crate::storage::write_bytes_atomic(path, document.to_string().as_bytes())
         .map_err(|e| AppError::RegistryWrite(describe_write_error(path, e)))
 }
 
@@ -42,8 +60,11 @@ pub fn clear_auto_login_user(path: &Path) -> Result<(), AppError> {
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
         Err(e) => return Err(AppError::FileRead(e.to_string())),
     };
-    let updated = vdf_set_nested_value(&existing, REGISTRY_PATH, "");
-    crate::storage::write_bytes_atomic(path, updated.as_bytes())
+    let mut document = parse(path, &existing)?;
+    document
+        .set(REGISTRY_PATH, "")
+        .map_err(AppError::RegistryWrite)?;
+    crate::storage::write_bytes_atomic(path, document.to_string().as_bytes())
         .map_err(|e| AppError::RegistryWrite(describe_write_error(path, e)))
 }
 
@@ -65,18 +86,10 @@ or fix the file permissions, then retry.",
     error
 }
 
-fn extract_registry_value(content: &str, key: &str) -> Option<String> {
-    // Use the escape-aware tokenizer, not split('"'): a value containing an
-    // escaped quote (written correctly by escape_vdf_string) would otherwise be
-    // truncated at the first inner quote and re-persisted mangled on the switch
-    // rollback path.
-    for line in content.lines() {
-        let tokens = crate::platforms::steam::vdf::vdf_tokenize_line(line.trim());
-        if tokens.len() >= 2 && tokens[0].eq_ignore_ascii_case(key) {
-            return Some(tokens[1].clone());
-        }
-    }
-    None
+/// A registry.vdf that does not parse is refused rather than rewritten: the
+/// file holds far more than autologin, and Steam would lose all of it.
+fn parse(path: &Path, content: &str) -> Result<Document, AppError> {
+    Document::parse(content).map_err(|e| AppError::FileRead(format!("{}: {e}", path.display())))
 }
 
 fn empty_registry_vdf() -> String {
@@ -158,9 +171,8 @@ mod tests {
 
     #[test]
     fn username_with_quote_round_trips() {
-        // escape_vdf_string escapes the inner quote on write; the read side must
-        // use the escape-aware tokenizer to get it back verbatim rather than
-        // truncating at the escaped quote.
+        // The inner quote is escaped on write and must come back verbatim
+        // rather than truncating the name at the escaped quote.
         let path = tmp_path("quote");
         set_auto_login_user(&path, "jo\"hn").unwrap();
         assert_eq!(get_auto_login_user(&path).unwrap(), "jo\"hn");
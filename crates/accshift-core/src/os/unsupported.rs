@@ -55,6 +55,10 @@ pub fn clear_auto_login_user() -> Result<(), AppError> {
     Err(unsupported("Steam auto-login write"))
 }
 
+pub fn auto_login_store() -> Result<super::AutoLoginStore, AppError> {
+    Err(unsupported("Steam auto-login lookup"))
+}
+
 pub fn kill_and_relaunch_steam_elevated(
     _steam_path: &Path,
     _launch_options: &[String],
@@ -66,6 +70,10 @@ pub fn request_steam_shutdown(_steam_path: &Path) -> bool {
     false
 }
 
+pub fn request_window_close(_process_names: &[&str]) -> bool {
+    false
+}
+
 pub fn launch_steam(
     _steam_path: &Path,
     _run_as_admin: bool,
@@ -74,6 +82,10 @@ pub fn launch_steam(
     Err(unsupported("Steam launch"))
 }
 
+pub fn steam_launch_line(_steam_path: &Path, _launch_options: &[String]) -> String {
+    String::new()
+}
+
 pub fn select_folder(_title: &str) -> Result<String, AppError> {
     Err(unsupported("Folder picker"))
 }
@@ -332,6 +332,12 @@ pub fn clear_auto_login_user() -> Result<(), AppError> {
     Ok(())
 }
 
+pub fn auto_login_store() -> Result<super::AutoLoginStore, AppError> {
+    Ok(super::AutoLoginStore::Registry(
+        "HKCU\\Software\\Valve\\Steam",
+    ))
+}
+
 pub fn get_auto_login_user() -> Result<String, AppError> {
     let hkcu = RegKey::predef(HKEY_CURRENT_USER);
     let steam_key = hkcu
@@ -438,6 +444,59 @@ pub fn request_steam_shutdown(steam_path: &Path) -> bool {
     hidden_command(&steam_exe).arg("-shutdown").spawn().is_ok()
 }
 
+/// Posts `WM_CLOSE` to every visible, unowned top-level window of the
+/// matching processes: the message their close button sends. Owned windows
+/// are dialogs and tool windows, which close with their owner.
+pub fn request_window_close(process_names: &[&str]) -> bool {
+    use windows_sys::Win32::Foundation::{HWND, LPARAM};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindow, GetWindowThreadProcessId, IsWindowVisible, PostMessageW, GW_OWNER,
+        WM_CLOSE,
+    };
+
+    struct Search {
+        pids: Vec<u32>,
+        posted: bool,
+    }
+
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> i32 {
+        let search = unsafe { &mut *(lparam as *mut Search) };
+        let mut pid = 0u32;
+        unsafe { GetWindowThreadProcessId(hwnd, &mut pid) };
+        let top_level = unsafe { GetWindow(hwnd, GW_OWNER) }.is_null();
+        if search.pids.contains(&pid)
+            && top_level
+            && unsafe { IsWindowVisible(hwnd) } != 0
+            && unsafe { PostMessageW(hwnd, WM_CLOSE, 0, 0) } != 0
+        {
+            search.posted = true;
+        }
+        // Keep enumerating: a launcher often has several top-level windows.
+        1
+    }
+
+    let pids = super::common::live_pids(process_names);
+    if pids.is_empty() {
+        return false;
+    }
+    let mut search = Search {
+        pids,
+        posted: false,
+    };
+    unsafe { EnumWindows(Some(visit), &mut search as *mut Search as LPARAM) };
+    search.posted
+}
+
+pub fn steam_launch_line(steam_path: &Path, launch_options: &[String]) -> String {
+    let steam_exe = steam_path.join(steam_executable_name());
+    let args = quote_windows_args(launch_options);
+    if args.is_empty() {
+        steam_exe.display().to_string()
+    } else {
+        format!("{} {args}", steam_exe.display())
+    }
+}
+
 pub fn launch_steam(
     steam_path: &Path,
     run_as_admin: bool,
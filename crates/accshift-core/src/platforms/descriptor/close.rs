@@ -0,0 +1,106 @@
+//! Asking a launcher to exit before its processes are terminated.
+//!
+//! [`CloseStrategy`] says how to ask; this module sends one request and waits
+//! on its answer. The engine walks a profile's strategies in order and only
+//! terminates what is still running once every one of them had its turn.
+
+use super::hooks::{HookContext, NativeHook};
+use super::paths::PathResolver;
+use super::schema::CloseStrategy;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Sends the request `strategy` describes to `processes`. `Ok` means the
+/// request was delivered, not that the launcher acted on it: only the
+/// processes exiting says that, see [`wait_for_exit`].
+///
+/// `hook` is the identity source's native hook with its resolved paths, when
+/// the profile has one; only the `nativeHook` strategy reads it.
+pub(super) fn request(
+    strategy: &CloseStrategy,
+    resolver: &PathResolver,
+    processes: &[&str],
+    hook: Option<(&dyn NativeHook, &HookContext)>,
+) -> Result<(), String> {
+    match strategy {
+        CloseStrategy::Command { command, args, .. } => {
+            let program = resolver.resolve(command)?;
+            // Not waited on: a shutdown flag usually forwards the request to
+            // the running instance and returns, but some wait for it to exit.
+            crate::os::hidden_command(&program)
+                .args(args)
+                .spawn()
+                .map_err(|e| format!("Could not run {}: {e}", program.display()))?;
+            Ok(())
+        }
+        CloseStrategy::Uri { uri, .. } => {
+            crate::os::open_protocol_uri(uri).map_err(|e| e.to_string())
+        }
+        CloseStrategy::HttpPost { url, body, .. } => crate::runtime::block_on(async {
+            let mut request = local_client().post(url.as_str());
+            if serde_json::from_str::<serde_json::Value>(body).is_ok() {
+                request = request.header(reqwest::header::CONTENT_TYPE, "application/json");
+            }
+            let response = request
+                .body(body.clone())
+                .send()
+                .await
+                .map_err(|e| format!("POST {url} failed: {e}"))?;
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(format!("POST {url} answered {}", response.status()))
+            }
+        }),
+        CloseStrategy::CloseWindow { .. } => {
+            if crate::os::request_window_close(processes) {
+                Ok(())
+            } else {
+                Err("None of the launcher's windows took the request".to_string())
+            }
+        }
+        CloseStrategy::NativeHook { .. } => match hook {
+            Some((hook, ctx)) => hook.close(ctx),
+            None => Err("The native hook's paths did not resolve".to_string()),
+        },
+    }
+}
+
+/// What a strategy is aimed at, for a log line or a dry-run step.
+pub(super) fn describe(strategy: &CloseStrategy) -> String {
+    match strategy {
+        CloseStrategy::Command { command, args, .. } => std::iter::once(command.as_str())
+            .chain(args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" "),
+        CloseStrategy::Uri { uri, .. } => uri.clone(),
+        CloseStrategy::HttpPost { url, .. } => format!("POST {url}"),
+        CloseStrategy::CloseWindow { .. } => "main window".to_string(),
+        CloseStrategy::NativeHook { .. } => "native hook".to_string(),
+    }
+}
+
+/// Waits up to `timeout_ms` in all for every one of `processes` to exit.
+pub(super) fn wait_for_exit(processes: &[&str], timeout_ms: u32) -> bool {
+    let deadline = Instant::now() + Duration::from_millis(u64::from(timeout_ms));
+    processes.iter().all(|name| {
+        let left = deadline.saturating_duration_since(Instant::now());
+        crate::os::wait_for_process_exit(name, u32::try_from(left.as_millis()).unwrap_or(u32::MAX))
+    })
+}
+
+/// Shared client for local endpoints. Launchers serve them with self-signed
+/// certificates, and the schema only lets a loopback host through, so the
+/// certificate is not checked. The timeouts keep a hung launcher from
+/// holding the switch past its own strategy timeout.
+fn local_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .connect_timeout(Duration::from_secs(2))
+            .timeout(Duration::from_secs(5))
+            .build()
+            .expect("local close client should build")
+    })
+}
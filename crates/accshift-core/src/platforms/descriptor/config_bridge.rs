@@ -13,10 +13,11 @@
 
 use crate::config::{
     self, CustomAccountConfig, CustomPlatformConfig, DiscordAccountConfig, EpicAccountConfig,
-    GogAccountConfig, JagexAccountConfig, UbisoftAccountConfig,
+    GogAccountConfig, JagexAccountConfig, RiotProfileConfig, UbisoftAccountConfig,
 };
 use crate::platforms::ids;
 use crate::AppContext;
+use std::collections::BTreeMap;
 
 /// One account as the config stores it, whichever section it came from.
 #[derive(Debug, Clone, Default)]
@@ -24,11 +25,15 @@ pub struct AccountRecord {
     pub account_id: String,
     pub label: String,
     pub last_used_at: Option<u64>,
+    /// What `${account.<name>}` expands to in the descriptor's `launch` block.
+    pub launch_values: BTreeMap<String, String>,
+    /// The descriptor's account fields, as the identity source last read them.
+    pub fields: BTreeMap<String, String>,
 }
 
 /// One stored account row, whichever typed section it lives in.
 ///
-/// The sections hold different types with the same three fields under
+/// The sections hold different types with the same five fields under
 /// different names, so the operations below are written once against this and
 /// monomorphised per section.
 trait AccountRow {
@@ -38,6 +43,16 @@ trait AccountRow {
     fn set_label(&mut self, label: String);
     fn last_used_at(&self) -> Option<u64>;
     fn set_last_used_at(&mut self, at: Option<u64>);
+    fn launch_values(&self) -> &BTreeMap<String, String>;
+    fn set_launch_values(&mut self, values: BTreeMap<String, String>);
+    fn fields(&self) -> BTreeMap<String, String>;
+    /// Stores `fields` over the values the row had.
+    fn extend_fields(&mut self, fields: &BTreeMap<String, String>);
+    /// The id the launcher reports for this account, when the row is stored
+    /// under another one.
+    fn identity(&self) -> Option<&str> {
+        None
+    }
 }
 
 macro_rules! impl_account_row {
@@ -48,6 +63,8 @@ macro_rules! impl_account_row {
                     $id_field: account_id,
                     label,
                     last_used_at,
+                    launch_values: BTreeMap::new(),
+                    fields: BTreeMap::new(),
                 }
             }
             fn account_id(&self) -> &str {
@@ -65,6 +82,18 @@ macro_rules! impl_account_row {
             fn set_last_used_at(&mut self, at: Option<u64>) {
                 self.last_used_at = at;
             }
+            fn launch_values(&self) -> &BTreeMap<String, String> {
+                &self.launch_values
+            }
+            fn set_launch_values(&mut self, values: BTreeMap<String, String>) {
+                self.launch_values = values;
+            }
+            fn fields(&self) -> BTreeMap<String, String> {
+                self.fields.clone()
+            }
+            fn extend_fields(&mut self, fields: &BTreeMap<String, String>) {
+                self.fields.extend(fields.clone());
+            }
         }
     };
 }
@@ -76,6 +105,76 @@ impl_account_row!(UbisoftAccountConfig, uuid);
 impl_account_row!(DiscordAccountConfig, account_id);
 impl_account_row!(CustomAccountConfig, account_id);
 
+/// Riot profiles predate account fields: the Riot ID already had columns of
+/// its own, so the descriptor's two fields are mapped onto those rather than
+/// stored a second time.
+///
+/// A profile from before the engine is stored under a `riot-profile-<uuid>` id
+/// its snapshot is filed under, while the hook reports the puuid. The puuid
+/// the profile recorded is its identity, so signing back into it finds it.
+impl AccountRow for RiotProfileConfig {
+    fn create(account_id: String, label: String, last_used_at: Option<u64>) -> Self {
+        Self {
+            // Every id the engine hands in is one the hook read: a puuid.
+            account_puuid: account_id.clone(),
+            id: account_id,
+            label,
+            last_used_at,
+            ..Self::default()
+        }
+    }
+    fn account_id(&self) -> &str {
+        &self.id
+    }
+    fn label(&self) -> &str {
+        &self.label
+    }
+    fn set_label(&mut self, label: String) {
+        self.label = label;
+    }
+    fn last_used_at(&self) -> Option<u64> {
+        self.last_used_at
+    }
+    fn set_last_used_at(&mut self, at: Option<u64>) {
+        self.last_used_at = at;
+    }
+    fn launch_values(&self) -> &BTreeMap<String, String> {
+        &self.launch_values
+    }
+    fn set_launch_values(&mut self, values: BTreeMap<String, String>) {
+        self.launch_values = values;
+    }
+    fn fields(&self) -> BTreeMap<String, String> {
+        [
+            (RIOT_GAME_NAME, &self.account_name),
+            (RIOT_TAG_LINE, &self.account_tag_line),
+        ]
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect()
+    }
+    fn extend_fields(&mut self, fields: &BTreeMap<String, String>) {
+        if let Some(name) = fields.get(RIOT_GAME_NAME) {
+            self.account_name = name.clone();
+        }
+        if let Some(tag) = fields.get(RIOT_TAG_LINE) {
+            self.account_tag_line = tag.clone();
+        }
+    }
+    fn identity(&self) -> Option<&str> {
+        // A profile left mid-setup is not an account, see [`accounts`].
+        if self.snapshot_state == "setup_pending" {
+            return None;
+        }
+        Some(self.account_puuid.trim()).filter(|puuid| !puuid.is_empty())
+    }
+}
+
+/// The riot descriptor's field names, see `descriptors/riot.json`.
+const RIOT_GAME_NAME: &str = "gameName";
+const RIOT_TAG_LINE: &str = "tagLine";
+
 /// Runs the same body against whichever section the platform owns.
 ///
 /// Each arm binds `$accounts` to that section's account vector. The vectors
@@ -104,6 +203,10 @@ macro_rules! with_accounts {
                 let $accounts = &mut $cfg.discord.accounts;
                 $body
             }
+            ids::RIOT => {
+                let $accounts = &mut $cfg.riot.profiles;
+                $body
+            }
             other => {
                 let $accounts = &mut custom_section($cfg, other).accounts;
                 $body
@@ -138,10 +241,28 @@ fn rows<T: AccountRow>(accounts: &[T]) -> Vec<AccountRecord> {
             account_id: account.account_id().trim().to_string(),
             label: account.label().trim().to_string(),
             last_used_at: account.last_used_at(),
+            launch_values: account.launch_values().clone(),
+            fields: account.fields(),
         })
         .collect()
 }
 
+/// The stored id of the row `live_id` belongs to. A row stored under that very
+/// id wins over one that only names it as its identity.
+fn identity_row<T: AccountRow>(accounts: &[T], live_id: &str) -> Option<String> {
+    accounts
+        .iter()
+        .find(|account| same_account(account.account_id(), live_id))
+        .or_else(|| {
+            accounts.iter().find(|account| {
+                account
+                    .identity()
+                    .is_some_and(|identity| same_account(identity, live_id))
+            })
+        })
+        .map(|account| account.account_id().trim().to_string())
+}
+
 fn touch_row<T: AccountRow>(accounts: &mut Vec<T>, key: &str, now: u64) {
     match accounts
         .iter_mut()
@@ -162,6 +283,56 @@ fn label_row<T: AccountRow>(accounts: &mut Vec<T>, key: &str, label: &str) {
     }
 }
 
+fn launch_values_row<T: AccountRow>(
+    accounts: &mut Vec<T>,
+    key: &str,
+    values: BTreeMap<String, String>,
+) {
+    match accounts
+        .iter_mut()
+        .find(|account| same_account(account.account_id(), key))
+    {
+        Some(existing) => existing.set_launch_values(values),
+        None => {
+            let mut row = T::create(key.to_string(), String::new(), None);
+            row.set_launch_values(values);
+            accounts.push(row);
+        }
+    }
+}
+
+fn add_row<T: AccountRow>(accounts: &mut Vec<T>, record: &AccountRecord) -> bool {
+    if accounts
+        .iter()
+        .any(|account| same_account(account.account_id(), &record.account_id))
+    {
+        return false;
+    }
+    let mut row = T::create(
+        record.account_id.clone(),
+        record.label.clone(),
+        record.last_used_at,
+    );
+    row.set_launch_values(record.launch_values.clone());
+    row.extend_fields(&record.fields);
+    accounts.push(row);
+    true
+}
+
+fn fields_row<T: AccountRow>(accounts: &mut Vec<T>, key: &str, fields: &BTreeMap<String, String>) {
+    let row = match accounts
+        .iter()
+        .position(|account| same_account(account.account_id(), key))
+    {
+        Some(index) => &mut accounts[index],
+        None => {
+            accounts.push(T::create(key.to_string(), String::new(), None));
+            accounts.last_mut().expect("just pushed")
+        }
+    };
+    row.extend_fields(fields);
+}
+
 /// Every account the config holds for this platform, in stored order.
 pub fn accounts(app: &dyn AppContext, platform_id: &str) -> Vec<AccountRecord> {
     let cfg = config::load_config(app);
@@ -171,6 +342,16 @@ pub fn accounts(app: &dyn AppContext, platform_id: &str) -> Vec<AccountRecord> {
         ids::EPIC => rows(&cfg.epic.accounts),
         ids::UBISOFT => rows(&cfg.ubisoft.accounts),
         ids::DISCORD => rows(&cfg.discord.accounts),
+        // A profile still mid-setup in the old flow never got a snapshot, so
+        // it is not an account anyone can switch to.
+        ids::RIOT => rows(
+            &cfg.riot
+                .profiles
+                .iter()
+                .filter(|profile| profile.snapshot_state != "setup_pending")
+                .cloned()
+                .collect::<Vec<_>>(),
+        ),
         other => match cfg.custom_platforms.get(other) {
             Some(section) => rows(&section.accounts),
             None => Vec::new(),
@@ -178,6 +359,19 @@ pub fn accounts(app: &dyn AppContext, platform_id: &str) -> Vec<AccountRecord> {
     }
 }
 
+/// The account the launcher's `live_id` is stored as: the same id, or the
+/// one a row predating the engine keeps beside it. `None` when no row has it.
+pub fn account_for_identity(
+    app: &dyn AppContext,
+    platform_id: &str,
+    live_id: &str,
+) -> Option<String> {
+    let mut cfg = config::load_config(app);
+    with_accounts!(&mut cfg, platform_id, |accounts| {
+        identity_row(accounts, live_id.trim())
+    })
+}
+
 /// Stamps the account as just used, adding it if the config never saw it.
 pub fn touch_account(
     app: &dyn AppContext,
@@ -212,6 +406,65 @@ pub fn set_label(
     })
 }
 
+/// Replaces the account's launch values, adding it if the config never saw
+/// it. Empty values are dropped rather than stored.
+pub fn set_launch_values(
+    app: &dyn AppContext,
+    platform_id: &str,
+    account_id: &str,
+    values: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    let key = account_id.trim().to_string();
+    let values: BTreeMap<String, String> = values
+        .iter()
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .filter(|(name, value)| !name.is_empty() && !value.is_empty())
+        .collect();
+    config::update_config(app, |cfg| {
+        with_accounts!(cfg, platform_id, |accounts| {
+            launch_values_row(accounts, &key, values.clone());
+        });
+    })
+}
+
+/// Stores `fields` on the account over the values it had, adding it if the
+/// config never saw it. A field not named keeps its value: a source that
+/// could not read one this time has not said it is gone.
+pub fn set_fields(
+    app: &dyn AppContext,
+    platform_id: &str,
+    account_id: &str,
+    fields: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    let key = account_id.trim().to_string();
+    config::update_config(app, |cfg| {
+        with_accounts!(cfg, platform_id, |accounts| {
+            fields_row(accounts, &key, fields);
+        });
+    })
+}
+
+/// Adds the account exactly as `record` describes it, timestamps included,
+/// unless the config already holds it. Returns whether it was added.
+pub fn add_account(
+    app: &dyn AppContext,
+    platform_id: &str,
+    record: &AccountRecord,
+) -> Result<bool, String> {
+    let record = AccountRecord {
+        account_id: record.account_id.trim().to_string(),
+        label: record.label.trim().to_string(),
+        ..record.clone()
+    };
+    let mut added = false;
+    config::update_config(app, |cfg| {
+        with_accounts!(cfg, platform_id, |accounts| {
+            added = add_row(accounts, &record);
+        });
+    })?;
+    Ok(added)
+}
+
 /// Drops the account, and the current-account marker when it pointed at it.
 pub fn remove_account(
     app: &dyn AppContext,
@@ -252,6 +505,7 @@ fn current_account_field<'a>(cfg: &'a config::AppConfig, platform_id: &str) -> O
     match platform_id {
         ids::JAGEX => Some(&cfg.jagex.current_account),
         ids::DISCORD => Some(&cfg.discord.current_account_id),
+        ids::RIOT => Some(&cfg.riot.current_profile_id),
         // None here means "never stored", not "no such field": a custom
         // platform whose launcher does expose an id simply leaves it empty.
         other => cfg
@@ -265,6 +519,7 @@ fn set_current_account_field(cfg: &mut config::AppConfig, platform_id: &str, val
     match platform_id {
         ids::JAGEX => cfg.jagex.current_account = value,
         ids::DISCORD => cfg.discord.current_account_id = value,
+        ids::RIOT => cfg.riot.current_profile_id = value,
         other => custom_section(cfg, other).current_account = value,
     }
 }
@@ -325,6 +580,7 @@ pub fn path_override(app: &dyn AppContext, platform_id: &str) -> String {
         ids::EPIC => cfg.epic.path_override.trim().to_string(),
         ids::UBISOFT => cfg.ubisoft.path_override.trim().to_string(),
         ids::DISCORD => cfg.discord.path_override.trim().to_string(),
+        ids::RIOT => cfg.riot.path_override.trim().to_string(),
         other => match cfg.custom_platforms.get(other) {
             Some(section) => section.path_override.trim().to_string(),
             None => String::new(),
@@ -344,6 +600,7 @@ pub fn set_path_override(
         ids::EPIC => cfg.epic.path_override = path.clone(),
         ids::UBISOFT => cfg.ubisoft.path_override = path.clone(),
         ids::DISCORD => cfg.discord.path_override = path.clone(),
+        ids::RIOT => cfg.riot.path_override = path.clone(),
         other => custom_section(cfg, other).path_override = path.clone(),
     })
 }
@@ -438,4 +695,79 @@ mod tests {
 
         let _ = std::fs::remove_dir_all(&root);
     }
+
+    #[test]
+    fn riot_profiles_are_the_accounts_and_keep_their_riot_id() {
+        let _guard = config_guard();
+        let root = scratch("bridge-riot");
+        let ctx = TempCtx { root: root.clone() };
+        config::update_config(&ctx, |cfg| {
+            cfg.riot.profiles.push(RiotProfileConfig {
+                id: "riot-profile-1".into(),
+                label: "Main".into(),
+                account_name: "Player".into(),
+                account_tag_line: "EUW".into(),
+                account_puuid: "puuid-1".into(),
+                snapshot_state: "ready".into(),
+                ..RiotProfileConfig::default()
+            });
+            cfg.riot.profiles.push(RiotProfileConfig {
+                id: "riot-profile-2".into(),
+                account_puuid: "puuid-2".into(),
+                snapshot_state: "setup_pending".into(),
+                ..RiotProfileConfig::default()
+            });
+            cfg.riot.current_profile_id = "riot-profile-1".into();
+        })
+        .unwrap();
+
+        let stored = accounts(&ctx, ids::RIOT);
+        assert_eq!(stored.len(), 1, "a profile mid-setup is not an account");
+        assert_eq!(stored[0].account_id, "riot-profile-1");
+        assert_eq!(stored[0].label, "Main");
+        assert_eq!(
+            stored[0].fields.get(RIOT_GAME_NAME).map(String::as_str),
+            Some("Player")
+        );
+        assert_eq!(
+            current_account(&ctx, ids::RIOT).as_deref(),
+            Some("riot-profile-1")
+        );
+
+        assert_eq!(
+            account_for_identity(&ctx, ids::RIOT, "puuid-1").as_deref(),
+            Some("riot-profile-1")
+        );
+        assert_eq!(account_for_identity(&ctx, ids::RIOT, "puuid-2"), None);
+        touch_account(&ctx, ids::RIOT, "puuid-3", 1).unwrap();
+        assert_eq!(
+            account_for_identity(&ctx, ids::RIOT, "puuid-3").as_deref(),
+            Some("puuid-3")
+        );
+
+        let fields = BTreeMap::from([(RIOT_TAG_LINE.to_string(), "NA1".to_string())]);
+        set_fields(&ctx, ids::RIOT, "riot-profile-1", &fields).unwrap();
+        let cfg = config::load_config(&ctx);
+        assert_eq!(cfg.riot.profiles[0].account_name, "Player");
+        assert_eq!(cfg.riot.profiles[0].account_tag_line, "NA1");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn the_riot_descriptor_declares_the_fields_the_profiles_map() {
+        let riot = crate::platforms::descriptor::load_embedded()
+            .0
+            .into_iter()
+            .find(|descriptor| descriptor.id == ids::RIOT)
+            .expect("riot ships as a descriptor");
+        let windows = &riot.os[&crate::platforms::descriptor::schema::Os::Windows];
+        let names: Vec<&str> = windows
+            .identity
+            .fields
+            .iter()
+            .map(|field| field.name.as_str())
+            .collect();
+        assert_eq!(names, [RIOT_GAME_NAME, RIOT_TAG_LINE]);
+    }
 }
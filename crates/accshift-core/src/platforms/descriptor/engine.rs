@@ -10,7 +10,7 @@
 //! template and checks it against the descriptor's roots. There is no other
 //! way to obtain one, so a step added later cannot skip the sandbox.
 
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -30,17 +30,24 @@ use crate::snapshot_crypto::{
     self, decrypted_copy_file, delete_encrypted_file_secret, encrypted_copy_file, free_dir_secrets,
     read_decrypted_bytes, write_encrypted_bytes, DirCopyOptions,
 };
+use crate::snapshot_history;
+use crate::snapshot_manifest;
 use crate::{AppContext, AppCtx};
 
-use super::config_bridge;
+use super::close;
+use super::config_bridge::{self, AccountRecord};
 use super::hooks::{self, HookContext, HookIdentity};
+use super::keys;
 use super::paths::{PathResolver, Sandbox};
-use super::plan::{DryRunPlan, PlanAction, PlanStep, PlanTargetKind};
-use super::reg;
+use super::plan::{DryRunOperation, DryRunPlan, PlanAction, PlanStep, PlanTargetKind};
+use super::reg::Registry;
+use super::schema::{expand_account_values, is_launch_value_name, BUILTIN_LAUNCH_VALUES};
 use super::schema::{
-    Condition, CurrentSource, Descriptor, DirItem, Discovery, EntryKind, Executable,
-    ExecutableCandidate, IdentitySource, OsProfile, PathSpec, PathTemplate, INSTALL_DIR,
+    AccountField, Close, CloseStrategy, Condition, CurrentSource, Descriptor, DirItem, Discovery,
+    EntryKind, Executable, ExecutableCandidate, IdentitySource, IniKey, KeySelect, Launch, Os,
+    OsProfile, PathSpec, PathTemplate, RegistryHive, Wine, INSTALL_DIR,
 };
+use super::wine;
 
 /// Where a descriptor came from. Shipped descriptors are read-only; a user
 /// descriptor lives in the data folder and can be edited or removed.
@@ -60,6 +67,11 @@ struct DescriptorAccount {
     label: String,
     last_used_at: Option<u64>,
     snapshot_saved: bool,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    launch_values: BTreeMap<String, String>,
+    /// The descriptor's account fields this account has a value for.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    fields: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -69,6 +81,57 @@ struct DescriptorStartupSnapshot {
     current_account: String,
 }
 
+/// A plan's warning when the descriptor's `captureWhen` does not hold.
+const CAPTURE_GATE_CLOSED: &str =
+    "The live state does not meet the descriptor's capture conditions, \
+     so the stored snapshot would be kept as it is.";
+
+/// What `launch` starts the binary with, once the account values are in.
+struct LaunchCommand {
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    /// The arguments and variables left out, each with the value it named.
+    skipped: Vec<(String, String)>,
+}
+
+impl LaunchCommand {
+    fn build(launch: &Launch, executable: &Path, values: &BTreeMap<String, String>) -> Self {
+        let mut command = Self {
+            args: Vec::new(),
+            env: Vec::new(),
+            skipped: Vec::new(),
+        };
+        for arg in launch.args_for(executable) {
+            match expand_account_values(arg, values) {
+                Ok(arg) => command.args.push(arg),
+                Err(name) => command.skipped.push((arg.clone(), name)),
+            }
+        }
+        for (name, value) in &launch.env {
+            match expand_account_values(value, values) {
+                Ok(value) => command.env.push((name.clone(), value)),
+                Err(missing) => command.skipped.push((name.clone(), missing)),
+            }
+        }
+        command
+    }
+}
+
+/// The launch values a caller passed for this one switch, from the
+/// `launchValues` object of its params. They win over the stored ones.
+pub fn launch_overrides(params: &Value) -> BTreeMap<String, String> {
+    params
+        .get("launchValues")
+        .and_then(Value::as_object)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Setup jobs remember which accounts existed when the flow started, so a
 /// "new" account can be told from the one already signed in.
 #[derive(Clone, Default)]
@@ -85,6 +148,16 @@ pub struct DescriptorService {
     /// Overrides the environment templates resolve against. Used by tests and
     /// by a dry run asked to reason about a machine other than this one.
     env_override: Option<Vec<(String, String)>>,
+    /// Set when the service runs against a scratch folder instead of this
+    /// machine. See [`DescriptorService::offline`].
+    offline: Option<Offline>,
+}
+
+/// What an offline run puts in place of the machine: the profile it reads
+/// and the hives registry values live in.
+struct Offline {
+    os: Os,
+    registry: wine::Prefix,
 }
 
 impl DescriptorService {
@@ -99,6 +172,7 @@ impl DescriptorService {
             origin,
             jobs: SetupJobs::new(label, DEFAULT_SETUP_TTL_MS),
             env_override: None,
+            offline: None,
         }
     }
 
@@ -111,6 +185,18 @@ impl DescriptorService {
         self
     }
 
+    /// Runs the profile for `os` with no launcher behind it: nothing is ever
+    /// started or closed, and registry values live in `registry`'s text hives
+    /// unless the profile has a Wine prefix of its own.
+    ///
+    /// Only the offline harness builds one. Paired with
+    /// [`Self::with_environment`], every operation runs unchanged against a
+    /// scratch folder.
+    pub fn offline(mut self, os: Os, registry: wine::Prefix) -> Self {
+        self.offline = Some(Offline { os, registry });
+        self
+    }
+
     pub fn descriptor(&self) -> &Descriptor {
         &self.descriptor
     }
@@ -127,8 +213,12 @@ impl DescriptorService {
     // Runtime assembly
     // -----------------------------------------------------------------------
 
-    fn profile(&self) -> Result<&OsProfile, String> {
-        self.descriptor.current_profile().ok_or_else(|| {
+    pub(super) fn profile(&self) -> Result<&OsProfile, String> {
+        let profile = match &self.offline {
+            Some(offline) => self.descriptor.os.get(&offline.os),
+            None => self.descriptor.current_profile(),
+        };
+        profile.ok_or_else(|| {
             format!(
                 "{} is not supported on this operating system",
                 self.descriptor.name
@@ -136,10 +226,54 @@ impl DescriptorService {
         })
     }
 
-    fn base_resolver(&self) -> PathResolver {
-        match &self.env_override {
+    pub(super) fn base_resolver(&self) -> PathResolver {
+        let resolver = match &self.env_override {
             Some(env) => PathResolver::from_env(env.iter().map(|(k, v)| (k.clone(), v.clone()))),
             None => PathResolver::from_process_env(),
+        };
+        match self
+            .profile()
+            .ok()
+            .and_then(|profile| profile.wine.as_ref())
+        {
+            Some(wine) => match self.wine_prefix(&resolver, wine) {
+                Some(prefix) => resolver.with_wine_prefix(prefix),
+                None => resolver,
+            },
+            None => resolver,
+        }
+    }
+
+    /// The prefix candidate that exists, or the first that resolves, like
+    /// any other path list. A profile whose prefix resolves nowhere keeps the
+    /// plain resolver, and its Windows locations then fail to resolve by name.
+    fn wine_prefix(&self, resolver: &PathResolver, wine: &Wine) -> Option<wine::Prefix> {
+        let resolved: Vec<PathBuf> = wine
+            .prefix
+            .candidates()
+            .iter()
+            .filter_map(|template| resolver.resolve(template).ok())
+            .collect();
+        let root = resolved
+            .iter()
+            .find(|path| path.is_dir())
+            .or(resolved.first())?
+            .clone();
+        if wine.user.is_empty() {
+            let login = resolver.var("USER").or_else(|| resolver.var("LOGNAME"));
+            Some(wine::Prefix::detect(root, login.as_deref()))
+        } else {
+            Some(wine::Prefix::new(root, wine.user.clone()))
+        }
+    }
+
+    /// The registry this profile's keys live in: the machine's own, or the
+    /// hives of its Wine prefix, or an offline run's stand-in.
+    pub(super) fn registry(&self, resolver: &PathResolver) -> Registry {
+        match (resolver.wine_prefix(), &self.offline) {
+            (Some(prefix), _) => Registry::Wine(prefix.clone()),
+            (None, Some(offline)) => Registry::Wine(offline.registry.clone()),
+            (None, None) => Registry::Native,
         }
     }
 
@@ -148,7 +282,7 @@ impl DescriptorService {
     /// The install directory is only looked up when a template actually asks
     /// for it: resolving the executable reads the config and hits the disk,
     /// and most operations never need it.
-    fn runtime(&self, app: &dyn AppContext) -> Result<Runtime<'_>, String> {
+    pub(super) fn runtime(&self, app: &dyn AppContext) -> Result<Runtime<'_>, String> {
         let profile = self.profile()?;
         let mut resolver = self.base_resolver();
         if profile_uses_install_dir(profile) {
@@ -159,10 +293,12 @@ impl DescriptorService {
             }
         }
         let sandbox = Sandbox::new(&profile.roots, &resolver);
+        let registry = self.registry(&resolver);
         Ok(Runtime {
             profile,
             resolver,
             sandbox,
+            registry,
         })
     }
 
@@ -191,6 +327,7 @@ impl DescriptorService {
         }
 
         let resolver = self.base_resolver();
+        let registry = self.registry(&resolver);
         for candidate in &executable.candidates {
             let base = match candidate {
                 ExecutableCandidate::Path { template } => match resolver.resolve(template) {
@@ -200,19 +337,33 @@ impl DescriptorService {
                     Err(_) => continue,
                 },
                 ExecutableCandidate::Registry { root, key, value } => {
-                    let Some(raw) = reg::read(*root, key, value) else {
+                    let Some(raw) = registry.read(*root, key, value) else {
                         continue;
                     };
-                    PathBuf::from(raw.trim().trim_end_matches(['\\', '/']))
+                    resolver.host_path(raw.trim().trim_end_matches(['\\', '/']))
                 }
                 ExecutableCandidate::UninstallEntry {
                     display_name,
                     value,
                 } => {
-                    let Some(raw) = reg::uninstall_entry(display_name, value) else {
+                    let Some(raw) = registry.uninstall_entry(display_name, value) else {
+                        continue;
+                    };
+                    resolver.host_path(raw.trim().trim_end_matches(['\\', '/']))
+                }
+                ExecutableCandidate::JsonPointer { path, pointer } => {
+                    let Some(raw) = resolver
+                        .resolve(path)
+                        .ok()
+                        .and_then(|file| read_shared(&file))
+                        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+                        .and_then(|document| {
+                            document.pointer(pointer)?.as_str().map(str::to_owned)
+                        })
+                    else {
                         continue;
                     };
-                    PathBuf::from(raw.trim().trim_end_matches(['\\', '/']))
+                    resolver.host_path(raw.trim().trim_end_matches(['\\', '/']))
                 }
             };
             if let Some(found) = locate_binary(&base, executable) {
@@ -226,19 +377,63 @@ impl DescriptorService {
         ))
     }
 
-    fn launch(&self, app: &dyn AppContext) -> Result<(), String> {
+    /// The values `${account.<name>}` expands to when `account_id` is
+    /// launched: its fields, then what the user stored on it, then
+    /// `overrides`, then the built-in `id` and `label`.
+    fn launch_values(
+        &self,
+        app: &dyn AppContext,
+        account_id: &str,
+        overrides: &BTreeMap<String, String>,
+    ) -> BTreeMap<String, String> {
+        let stored = config_bridge::accounts(app, &self.descriptor.id)
+            .into_iter()
+            .find(|account| self.normalise_id(&account.account_id) == account_id);
+        let mut values = BTreeMap::new();
+        if let Some(stored) = &stored {
+            values.extend(stored.fields.clone());
+            values.extend(stored.launch_values.clone());
+        }
+        values.extend(overrides.clone());
+        values.insert("id".to_string(), account_id.to_string());
+        values.insert(
+            "label".to_string(),
+            self.account_label(account_id, stored.as_ref()),
+        );
+        values.retain(|_, value| !value.trim().is_empty());
+        values
+    }
+
+    /// Starts the launcher with `values` expanded into its arguments and
+    /// environment. Empty `values` launch it for nobody in particular, as a
+    /// sign-in does.
+    fn launch(
+        &self,
+        app: &dyn AppContext,
+        values: &BTreeMap<String, String>,
+    ) -> Result<(), String> {
         let profile = self.profile()?;
         let Some(launch) = profile.launch.as_ref() else {
             return Ok(());
         };
         let executable = self.resolve_executable(app)?;
-        let mut command = Command::new(&executable);
+        let mut command = match &profile.wine {
+            Some(wine) => self.wine_command(wine, &executable)?,
+            None => Command::new(&executable),
+        };
         if launch.working_directory_is_install_dir {
             if let Some(install_dir) = executable.parent() {
                 command.current_dir(install_dir);
             }
         }
-        command.args(launch.args_for(&executable));
+        let expanded = LaunchCommand::build(launch, &executable, values);
+        command.args(&expanded.args);
+        command.envs(expanded.env);
+        if self.offline.is_some() {
+            // Everything up to here ran, so a launcher that would not resolve
+            // still fails the operation. Only the process itself is left out.
+            return Ok(());
+        }
         command.spawn().map_err(|e| {
             format!(
                 "Could not launch {} {}: {e}",
@@ -249,6 +444,28 @@ impl DescriptorService {
         Ok(())
     }
 
+    /// The runner, started with the launcher binary as its argument and the
+    /// prefix in its environment.
+    fn wine_command(&self, wine: &Wine, executable: &Path) -> Result<Command, String> {
+        let resolver = self.base_resolver();
+        let prefix = resolver.wine_prefix().ok_or_else(|| {
+            format!(
+                "Could not launch {}: its Wine prefix was not found",
+                self.descriptor.name
+            )
+        })?;
+        let runner = resolver.resolve(&wine.runner.command)?;
+        let mut command = Command::new(runner);
+        command
+            .args(&wine.runner.args)
+            .arg(executable)
+            .env("WINEPREFIX", prefix.root());
+        for (name, value) in &wine.runner.env {
+            command.env(name, resolver.resolve(value)?);
+        }
+        Ok(command)
+    }
+
     fn process_names(&self) -> Vec<String> {
         self.profile()
             .map(|profile| profile.close.processes.clone())
@@ -257,7 +474,7 @@ impl DescriptorService {
 
     fn is_running(&self) -> bool {
         let names = self.process_names();
-        if names.is_empty() {
+        if names.is_empty() || self.offline.is_some() {
             return false;
         }
         let refs: Vec<&str> = names.iter().map(String::as_str).collect();
@@ -265,20 +482,89 @@ impl DescriptorService {
     }
 
     /// Closes the launcher and waits for it to actually exit, so nothing races
-    /// its exit-time flush of the session files to disk.
-    fn quit_and_wait(&self) {
+    /// its exit-time flush of the session files to disk. The close strategies
+    /// go first; whatever is still running after the last one is terminated.
+    fn quit_and_wait(&self, app: &dyn AppContext) {
         let Ok(profile) = self.profile() else {
             return;
         };
-        if profile.close.processes.is_empty() {
+        if profile.close.processes.is_empty() || self.offline.is_some() {
             return;
         }
         let refs: Vec<&str> = profile.close.processes.iter().map(String::as_str).collect();
-        crate::os::quit_processes_and_wait(
-            &refs,
-            profile.close.timeout_ms,
-            Duration::from_millis(profile.close.settle_ms),
+        let settle = Duration::from_millis(profile.close.settle_ms);
+        if !profile.close.strategies.is_empty()
+            && crate::os::any_process_running(&refs)
+            && self.close_gracefully(app, &profile.close, &refs)
+        {
+            std::thread::sleep(settle);
+            return;
+        }
+        crate::os::quit_processes_and_wait(&refs, profile.close.timeout_ms, settle);
+    }
+
+    /// Tries each close strategy in order, logging every attempt, until the
+    /// processes have all exited. `false` leaves the rest to termination.
+    fn close_gracefully(&self, app: &dyn AppContext, close: &Close, processes: &[&str]) -> bool {
+        let source = format!("{}.close", self.descriptor.id);
+        let runtime = self.runtime(app).ok();
+        let resolver = match &runtime {
+            Some(runtime) => runtime.resolver.clone(),
+            None => self.base_resolver(),
+        };
+        let native = runtime
+            .as_ref()
+            .and_then(|runtime| match &runtime.profile.identity.source {
+                IdentitySource::NativeHook { name, paths } => {
+                    Some((hooks::hook(name)?, hook_context(runtime, paths)))
+                }
+                _ => None,
+            });
+        let native = native.as_ref().map(|(hook, ctx)| (*hook, ctx));
+        let count = close.strategies.len();
+        for (index, strategy) in close.strategies.iter().enumerate() {
+            let attempt = format!(
+                "{} close strategy {}/{count} ({})",
+                self.descriptor.short_name,
+                index + 1,
+                strategy.as_str()
+            );
+            let target = close::describe(strategy);
+            if let Err(error) = close::request(strategy, &resolver, processes, native) {
+                log_platform_error(
+                    app,
+                    &source,
+                    &format!("{attempt} failed"),
+                    format!("target={target}; error={error}"),
+                );
+                continue;
+            }
+            if close::wait_for_exit(processes, strategy.timeout_ms()) {
+                log_platform_info(
+                    app,
+                    &source,
+                    &format!("{attempt} closed the launcher"),
+                    format!("target={target}"),
+                );
+                return true;
+            }
+            log_platform_info(
+                app,
+                &source,
+                &format!("{attempt} timed out"),
+                format!("target={target}; waited_ms={}", strategy.timeout_ms()),
+            );
+        }
+        log_platform_info(
+            app,
+            &source,
+            &format!(
+                "{} still running, terminating it",
+                self.descriptor.short_name
+            ),
+            format!("processes={}", processes.join(",")),
         );
+        false
     }
 
     // -----------------------------------------------------------------------
@@ -340,40 +626,59 @@ impl DescriptorService {
     #[cfg(test)]
     fn read_identity(&self, app: &dyn AppContext) -> Option<String> {
         let runtime = self.runtime(app).ok()?;
-        self.read_identity_in(&runtime)
+        self.read_identity_in(app, &runtime)
     }
 
     /// The id the launcher currently reports, when it reports one at all.
     ///
     /// Takes a runtime the caller already built, so a poll that touches
     /// several things does not resolve the launcher once per step.
-    fn read_identity_in(&self, runtime: &Runtime<'_>) -> Option<String> {
-        self.read_identity_detail(runtime).map(|found| found.id)
+    fn read_identity_in(&self, app: &dyn AppContext, runtime: &Runtime<'_>) -> Option<String> {
+        self.read_identity_detail(app, runtime)
+            .map(|found| found.id)
     }
 
-    /// The signed-in account with whatever name came with it. Only a native
-    /// hook reports a name; every other source knows the id alone.
-    fn read_identity_detail(&self, runtime: &Runtime<'_>) -> Option<HookIdentity> {
+    /// [`Self::read_live_identity`], under the id the account is stored as.
+    /// A Riot profile from before the engine is filed under an id of its own
+    /// and knows the puuid the launcher reports only as a field; reporting
+    /// the puuid would list that account a second time.
+    fn read_identity_detail(
+        &self,
+        app: &dyn AppContext,
+        runtime: &Runtime<'_>,
+    ) -> Option<HookIdentity> {
+        let mut found = self.read_live_identity(runtime)?;
+        if let Some(stored) =
+            config_bridge::account_for_identity(app, &self.descriptor.id, &found.id)
+        {
+            found.id = self.normalise_id(&stored);
+        }
+        Some(found)
+    }
+
+    /// The signed-in account with whatever name came with it, as the
+    /// launcher reports it. A native hook and the structured sources can
+    /// report a name; the others know the id alone.
+    fn read_live_identity(&self, runtime: &Runtime<'_>) -> Option<HookIdentity> {
         let found = match &runtime.profile.identity.source {
             IdentitySource::Registry { root, key, value } => {
-                reg::read(*root, key, value).map(bare_identity)
+                runtime.registry.read(*root, key, value).map(bare_identity)
             }
             IdentitySource::LogTail { .. } => {
                 self.read_identity_from_log(runtime).map(bare_identity)
             }
             // Nothing readable: the account is whatever we last put there.
             IdentitySource::Synthetic => None,
+            IdentitySource::JsonPointer { .. } | IdentitySource::IniKey { .. } => {
+                self.read_identity_from_file(runtime)
+            }
+            IdentitySource::SqliteQuery { path, query } => query_identity(
+                &runtime.path(path).ok()?,
+                query,
+                &runtime.profile.identity.fields,
+            ),
             IdentitySource::NativeHook { name, paths } => {
-                let hook = hooks::hook(name)?;
-                // Only paths the descriptor declared, and only after the
-                // sandbox has cleared them: a hook cannot widen its own reach.
-                let resolved = paths
-                    .iter()
-                    .filter_map(|(key, template)| {
-                        runtime.path(template).ok().map(|path| (key.clone(), path))
-                    })
-                    .collect();
-                hook.identity(&HookContext::new(resolved))
+                hooks::hook(name)?.identity(&hook_context(runtime, paths))
             }
         }?;
         let id = self.normalise_id(&found.id);
@@ -383,9 +688,138 @@ impl DescriptorService {
                 .display_name
                 .map(|name| name.trim().to_string())
                 .filter(|name| !name.is_empty()),
+            fields: self.typed_fields(&found.fields),
         })
     }
 
+    /// The values a declared field accepts, under its name and in the form
+    /// it stores. Anything else the source reported is dropped.
+    fn typed_fields(&self, raw: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        let Ok(profile) = self.profile() else {
+            return BTreeMap::new();
+        };
+        profile
+            .identity
+            .fields
+            .iter()
+            .filter_map(|field| {
+                let value = field.kind.accept(raw.get(&field.name)?)?;
+                Some((field.name.clone(), value))
+            })
+            .collect()
+    }
+
+    /// What the account is called: the label the user gave it, or else the
+    /// descriptor's `labelFormat` filled from its fields. Empty when neither
+    /// says anything.
+    fn account_label(&self, account_id: &str, stored: Option<&AccountRecord>) -> String {
+        if let Some(label) = stored
+            .map(|stored| stored.label.clone())
+            .filter(|label| !label.is_empty())
+        {
+            return label;
+        }
+        let Some(format) = self
+            .profile()
+            .ok()
+            .map(|profile| profile.identity.label_format.as_str())
+            .filter(|format| !format.is_empty())
+        else {
+            return String::new();
+        };
+        let mut values = stored
+            .map(|stored| stored.fields.clone())
+            .unwrap_or_default();
+        values.insert("id".to_string(), account_id.to_string());
+        // A field not read yet leaves the account unnamed rather than named
+        // with a gap in the middle.
+        expand_account_values(format, &values).unwrap_or_default()
+    }
+
+    /// Keeps the fields the identity source read for `found`, so a tag line
+    /// or a region is still listed once the account has signed out.
+    fn remember_fields(&self, app: &dyn AppContext, found: &HookIdentity) {
+        if found.fields.is_empty() {
+            return;
+        }
+        if let Err(error) =
+            config_bridge::set_fields(app, &self.descriptor.id, &found.id, &found.fields)
+        {
+            log_platform_error(
+                app,
+                &format!("{}.fields", self.descriptor.id),
+                "Could not store the account fields",
+                error,
+            );
+        }
+    }
+
+    /// Reads the id, and the name and fields when the descriptor points at
+    /// them, out of a settings file the launcher may hold open.
+    fn read_identity_from_file(&self, runtime: &Runtime<'_>) -> Option<HookIdentity> {
+        /// What one selected key stands for.
+        enum Slot<'a> {
+            Id,
+            DisplayName,
+            Field(&'a str),
+        }
+
+        let identity = &runtime.profile.identity;
+        let (path, id_key, name_key) = match &identity.source {
+            IdentitySource::JsonPointer {
+                path,
+                pointer,
+                display_name_pointer,
+            } => (path, pointer, display_name_pointer),
+            IdentitySource::IniKey {
+                path,
+                key,
+                display_name_key,
+                ..
+            } => (path, key, display_name_key),
+            _ => return None,
+        };
+        let mut wanted = vec![(Slot::Id, id_key), (Slot::DisplayName, name_key)];
+        wanted.extend(
+            identity
+                .fields
+                .iter()
+                .map(|field| (Slot::Field(&field.name), &field.from)),
+        );
+        wanted.retain(|(_, key)| !key.is_empty());
+        let select = match &identity.source {
+            IdentitySource::IniKey { section, .. } => KeySelect::IniKey {
+                keys: wanted
+                    .iter()
+                    .map(|(_, key)| IniKey {
+                        section: section.clone(),
+                        key: (*key).clone(),
+                    })
+                    .collect(),
+            },
+            _ => KeySelect::JsonPointer {
+                pointers: wanted.iter().map(|(_, key)| (*key).clone()).collect(),
+            },
+        };
+
+        let content = read_shared(&runtime.path(path).ok()?)?;
+        let captured = keys::capture(&content, &select).ok()?;
+        let mut found = bare_identity(String::new());
+        for ((slot, _), label) in wanted.iter().zip(keys::labels(&select)) {
+            let Some(value) = captured.present.get(&label).and_then(scalar_text) else {
+                continue;
+            };
+            match slot {
+                Slot::Id => found.id = value,
+                Slot::DisplayName => found.display_name = Some(value),
+                Slot::Field(name) => {
+                    found.fields.insert(name.to_string(), value);
+                }
+            }
+        }
+        (!found.id.is_empty()).then_some(found)
+    }
+
     /// Reads the id out of the launcher's own log, most recent line first.
     fn read_identity_from_log(&self, runtime: &Runtime<'_>) -> Option<String> {
         let IdentitySource::LogTail {
@@ -452,18 +886,50 @@ impl DescriptorService {
     /// Ids the platform leaves lying around outside its own session files, so
     /// an account added without accshift still shows up.
     ///
+    /// Each id maps to the name the launcher shows for it, empty when the
+    /// source has none; the first source to name an account wins.
+    ///
     /// Forgotten ids are filtered out here: a blocklist that discovery ignored
     /// would put the account straight back on the next poll.
-    fn discovered_ids(&self, app: &dyn AppContext, runtime: &Runtime<'_>) -> BTreeSet<String> {
+    fn discovered_ids(
+        &self,
+        app: &dyn AppContext,
+        runtime: &Runtime<'_>,
+    ) -> BTreeMap<String, String> {
         let identity = &runtime.profile.identity;
-        let mut ids = BTreeSet::new();
+        let mut ids = BTreeMap::new();
         for entry in &identity.discovery {
-            let Discovery::DirectoryEntries {
-                path,
-                entries,
-                strip_prefixes,
-                strip_extension,
-            } = entry;
+            let (path, entries, strip_prefixes, strip_extension) = match entry {
+                Discovery::DirectoryEntries {
+                    path,
+                    entries,
+                    strip_prefixes,
+                    strip_extension,
+                } => (path, entries, strip_prefixes, strip_extension),
+                Discovery::JsonArray {
+                    path,
+                    pointer,
+                    id_field,
+                    display_name_field,
+                } => {
+                    let Some(content) = runtime.path(path).ok().and_then(|p| read_shared(&p))
+                    else {
+                        continue;
+                    };
+                    for (raw, name) in
+                        json_array_entries(&content, pointer, id_field, display_name_field)
+                    {
+                        let id = self.normalise_id(&raw);
+                        if self.id_is_valid(&id) {
+                            let known = ids.entry(id).or_insert_with(String::new);
+                            if known.is_empty() {
+                                *known = name;
+                            }
+                        }
+                    }
+                    continue;
+                }
+            };
             let Ok(dir) = runtime.path(path) else {
                 continue;
             };
@@ -494,13 +960,13 @@ impl DescriptorService {
                 }
                 let id = self.normalise_id(candidate);
                 if self.id_is_valid(&id) {
-                    ids.insert(id);
+                    ids.entry(id).or_default();
                 }
             }
         }
         if identity.blocklist_on_forget {
             let blocked = self.blocked_ids(app);
-            ids.retain(|id| !blocked.contains(id));
+            ids.retain(|id, _| !blocked.contains(id));
         }
         ids
     }
@@ -527,7 +993,7 @@ impl DescriptorService {
 
     fn current_account_id_in(&self, app: &dyn AppContext, runtime: &Runtime<'_>) -> Option<String> {
         match runtime.profile.identity.current {
-            CurrentSource::Identity => self.read_identity_in(runtime),
+            CurrentSource::Identity => self.read_identity_in(app, runtime),
             CurrentSource::Config => config_bridge::current_account(app, &self.descriptor.id)
                 .map(|id| self.normalise_id(&id))
                 .filter(|id| self.id_is_valid(id)),
@@ -542,31 +1008,43 @@ impl DescriptorService {
     // Snapshots
     // -----------------------------------------------------------------------
 
+    /// Captures the live state as the account's snapshot. The snapshot it
+    /// replaces is kept as a generation, and put back if the capture fails.
     fn save_snapshot(&self, app: &dyn AppContext, account_id: &str) -> Result<(), String> {
         let runtime = self.runtime(app)?;
-        let cache_dir = self.snapshot_root(app, account_id)?;
-        fs::create_dir_all(&cache_dir)
-            .map_err(|e| format!("Could not create auth cache dir: {e}"))?;
+        let snapshots_dir = crate::storage::platform_snapshots_dir(app, &self.descriptor.id)?;
+        let capture = snapshot_history::Capture::begin(&snapshots_dir, account_id)?;
+        let result = self.write_snapshot(app, &runtime, &capture);
+        capture.finish(result, snapshot_history::generations_kept(app))
+    }
+
+    /// Writes every declared item into the empty directory `capture` holds.
+    /// An item with nothing live to take keeps its previous capture unless
+    /// the descriptor says to drop it.
+    fn write_snapshot(
+        &self,
+        app: &dyn AppContext,
+        runtime: &Runtime,
+        capture: &snapshot_history::Capture,
+    ) -> Result<(), String> {
+        let cache_dir = capture.dir();
 
         for item in &runtime.profile.state.files {
             let live = runtime.spec_path(&item.live)?;
-            let dest = cache_dir.join(&item.snapshot);
             if live.is_file() {
-                delete_encrypted_file_secret(&dest);
-                encrypted_copy_file(&live, &dest)?;
-            } else if item.clear_snapshot_when_source_missing {
-                // Nothing live to capture: drop the stale snapshot so a later
-                // restore cannot resurrect another account's file.
-                delete_encrypted_file_secret(&dest);
-                let _ = fs::remove_file(&dest);
+                encrypted_copy_file(&live, &cache_dir.join(&item.snapshot))?;
+            } else if !item.clear_snapshot_when_source_missing {
+                // Nothing live to capture. An item that must not outlive its
+                // source is left out instead, so a later restore cannot
+                // resurrect another account's file.
+                capture.keep_previous(&item.snapshot)?;
             }
         }
 
         for item in &runtime.profile.state.registry_values {
             let dest = cache_dir.join(&item.snapshot);
-            match reg::read(item.root, &item.key, &item.value) {
+            match runtime.registry.read(item.root, &item.key, &item.value) {
                 Some(value) => {
-                    delete_encrypted_file_secret(&dest);
                     if let Err(e) = write_encrypted_bytes(&dest, value.as_bytes()) {
                         // Non-fatal: the session files still carry the account,
                         // and failing the whole switch over one value would
@@ -577,23 +1055,32 @@ impl DescriptorService {
                             "Could not encrypt registry value for snapshot",
                             e,
                         );
+                        capture.keep_previous(&item.snapshot)?;
                     }
                 }
-                None if item.clear_snapshot_when_source_missing => {
-                    // Freeing the secret has to happen with the file, never
-                    // before deciding to keep it: a kept snapshot whose key is
-                    // gone can no longer be decrypted.
-                    delete_encrypted_file_secret(&dest);
-                    let _ = fs::remove_file(&dest);
-                }
-                None => {}
+                None if item.clear_snapshot_when_source_missing => {}
+                None => capture.keep_previous(&item.snapshot)?,
+            }
+        }
+
+        for item in &runtime.profile.state.keys {
+            let live = runtime.spec_path(&item.live)?;
+            let captured = keys::capture(&read_key_file(&live)?, &item.select)
+                .map_err(|e| format!("{}: {e}", live.display()))?;
+            if !captured.is_empty() {
+                let bytes = serde_json::to_vec(&captured)
+                    .map_err(|e| format!("Could not serialize captured keys: {e}"))?;
+                write_encrypted_bytes(&cache_dir.join(&item.snapshot), &bytes)?;
+            } else if !item.clear_snapshot_when_source_missing {
+                // None of the keys is there. Left out when the descriptor
+                // says so: a snapshot of nothing would restore as a sign-out.
+                capture.keep_previous(&item.snapshot)?;
             }
         }
 
         for item in &runtime.profile.state.directories {
             let live = runtime.spec_path(&item.live)?;
             let dest = cache_dir.join(&item.snapshot);
-            let _ = fs::remove_dir_all(&dest);
             let ignored: Vec<&str> = item.ignored_names.iter().map(String::as_str).collect();
             snapshot_crypto::encrypted_copy_dir(
                 &live,
@@ -605,10 +1092,22 @@ impl DescriptorService {
             )?;
         }
 
+        snapshot_manifest::write(
+            cache_dir,
+            &self.descriptor.id,
+            Some(self.descriptor.schema_version),
+            now_unix_ms(),
+        )?;
         Ok(())
     }
 
-    fn restore_snapshot(&self, app: &dyn AppContext, account_id: &str) -> Result<(), String> {
+    /// Puts the account's snapshot back over the live state, once its
+    /// manifest vouches for every file in it.
+    fn restore_snapshot(
+        &self,
+        app: &dyn AppContext,
+        account_id: &str,
+    ) -> Result<(), PlatformError> {
         let runtime = self.runtime(app)?;
         let cache_dir = self.snapshot_root(app, account_id)?;
 
@@ -619,7 +1118,43 @@ impl DescriptorService {
                 message.push(' ');
                 message.push_str(hint);
             }
-            return Err(message);
+            return Err(message.into());
+        }
+        snapshot_manifest::ensure_restorable(&cache_dir, account_id)?;
+
+        // Selected keys are merged into what the live file holds now. Every
+        // merge is worked out before anything is written, so a snapshot that
+        // does not decrypt or a file that does not parse fails the restore
+        // while the live state is still whole.
+        let mut key_edits: Vec<(PathBuf, String, bool)> = Vec::new();
+        for item in &runtime.profile.state.keys {
+            let source = cache_dir.join(&item.snapshot);
+            if !source.exists() {
+                continue;
+            }
+            let live = runtime.spec_path(&item.live)?;
+            let captured: keys::Captured = read_decrypted_bytes(&source)
+                .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()))
+                .map_err(|e| format!("Could not read key snapshot {}: {e}", source.display()))?;
+            // Two items may select keys in one file; the second merges into
+            // the first one's result, not into the file on disk.
+            let pending = key_edits.iter().position(|(path, _, _)| *path == live);
+            let current = match pending {
+                Some(index) => key_edits[index].1.clone(),
+                None => read_key_file(&live)?,
+            };
+            let merged = keys::apply(&current, &item.select, &captured)
+                .map_err(|e| format!("{}: {e}", live.display()))?;
+            match pending {
+                Some(index) => {
+                    key_edits[index].2 |= merged != current;
+                    key_edits[index].1 = merged;
+                }
+                None => {
+                    let changed = merged != current;
+                    key_edits.push((live, merged, changed));
+                }
+            }
         }
 
         // Every file is decrypted next to its destination first and only moved
@@ -643,7 +1178,7 @@ impl DescriptorService {
                     let _ = fs::remove_file(path);
                 }
                 let _ = fs::remove_file(&staging);
-                return Err(error);
+                return Err(error.into());
             }
             staged.push((staging, live, item.remove_live_before_restore));
         }
@@ -662,6 +1197,12 @@ impl DescriptorService {
             }
         }
 
+        for (live, text, changed) in &key_edits {
+            if *changed {
+                crate::storage::write_bytes_atomic(live, text.as_bytes())?;
+            }
+        }
+
         for item in &runtime.profile.state.registry_values {
             let source = cache_dir.join(&item.snapshot);
             if !source.exists() {
@@ -669,7 +1210,9 @@ impl DescriptorService {
             }
             if let Ok(bytes) = read_decrypted_bytes(&source) {
                 if let Ok(text) = String::from_utf8(bytes) {
-                    let _ = reg::write(item.root, &item.key, &item.value, text.trim());
+                    let _ = runtime
+                        .registry
+                        .write(item.root, &item.key, &item.value, text.trim());
                 }
             }
         }
@@ -726,6 +1269,7 @@ impl DescriptorService {
             .map(|profile| {
                 profile.state.files.iter().any(|i| i.snapshot_marker)
                     || profile.state.directories.iter().any(|i| i.snapshot_marker)
+                    || profile.state.keys.iter().any(|i| i.snapshot_marker)
                     || profile
                         .state
                         .registry_values
@@ -760,9 +1304,16 @@ impl DescriptorService {
             .iter()
             .filter(|i| i.snapshot_marker)
             .map(|i| &i.snapshot);
+        let keys = profile
+            .state
+            .keys
+            .iter()
+            .filter(|i| i.snapshot_marker)
+            .map(|i| &i.snapshot);
         files
             .chain(dirs)
             .chain(values)
+            .chain(keys)
             .map(|name| cache_dir.join(name))
             .collect()
     }
@@ -780,7 +1331,20 @@ impl DescriptorService {
         }
         for item in &runtime.profile.state.registry_values {
             if item.clear_on_setup {
-                reg::delete(item.root, &item.key, &item.value);
+                runtime.registry.delete(item.root, &item.key, &item.value);
+            }
+        }
+        for item in &runtime.profile.state.keys {
+            if item.clear_on_setup {
+                let live = runtime.spec_path(&item.live)?;
+                if live.is_file() {
+                    let current = read_key_file(&live)?;
+                    let cleared = keys::remove(&current, &item.select)
+                        .map_err(|e| format!("{}: {e}", live.display()))?;
+                    if cleared != current {
+                        crate::storage::write_bytes_atomic(&live, cleared.as_bytes())?;
+                    }
+                }
             }
         }
         for item in &runtime.profile.state.directories {
@@ -798,7 +1362,7 @@ impl DescriptorService {
     }
 
     /// Frees the keyring entries the snapshot files point at, then removes the
-    /// account's snapshot directory.
+    /// account's snapshot directory and every generation kept of it.
     fn delete_snapshot(&self, app: &dyn AppContext, account_id: &str) {
         let Ok(profile) = self.profile() else {
             return;
@@ -812,10 +1376,17 @@ impl DescriptorService {
         for item in &profile.state.registry_values {
             delete_encrypted_file_secret(&cache_dir.join(&item.snapshot));
         }
+        for item in &profile.state.keys {
+            delete_encrypted_file_secret(&cache_dir.join(&item.snapshot));
+        }
         for item in &profile.state.directories {
             free_dir_secrets(&cache_dir.join(&item.snapshot));
         }
+        delete_encrypted_file_secret(&cache_dir.join(snapshot_manifest::MANIFEST_FILE_NAME));
         let _ = fs::remove_dir_all(&cache_dir);
+        if let Some(snapshots_dir) = cache_dir.parent() {
+            snapshot_history::forget(snapshots_dir, account_id);
+        }
     }
 
     /// Records usage of the signed-in account and refreshes its snapshot
@@ -834,6 +1405,14 @@ impl DescriptorService {
             return Ok(());
         }
         let _ = config_bridge::touch_account(app, &self.descriptor.id, &current_id, now_unix_ms());
+        if let Some(found) = self
+            .runtime(app)
+            .ok()
+            .and_then(|runtime| self.read_identity_detail(app, &runtime))
+            .filter(|found| found.id == current_id)
+        {
+            self.remember_fields(app, &found);
+        }
         self.save_snapshot(app, &current_id)
     }
 
@@ -850,6 +1429,169 @@ impl DescriptorService {
             .all(|condition| self.condition_holds(&runtime, condition, ConditionInput::default()))
     }
 
+    // -----------------------------------------------------------------------
+    // Rollback
+    // -----------------------------------------------------------------------
+
+    /// Copies every live file, directory and registry value the descriptor
+    /// declares, and every file holding selected keys, verbatim, into a fresh
+    /// folder under [`crate::storage::switch_rollback_dir`], and notes the
+    /// ones that do not exist so a rollback can remove what the switch
+    /// created.
+    ///
+    /// Fails closed: a switch that cannot take this copy aborts before anything
+    /// live is replaced. The caller removes the directory with
+    /// [`LiveRollback::discard`] on both the success and the failure path.
+    fn stage_live_state(
+        &self,
+        app: &dyn AppContext,
+        current_marker: Option<String>,
+    ) -> Result<LiveRollback, String> {
+        let runtime = self.runtime(app)?;
+        let dir = crate::storage::switch_rollback_dir(app)?.join(format!(
+            "{}-rollback-{}",
+            self.descriptor.id,
+            Uuid::new_v4()
+        ));
+        crate::storage::create_private_dir(&dir)?;
+        let mut rollback = LiveRollback {
+            dir,
+            files: Vec::new(),
+            directories: Vec::new(),
+            registry_values: Vec::new(),
+            current_marker,
+            registry: runtime.registry.clone(),
+        };
+
+        let staged = (|| {
+            for (index, item) in runtime.profile.state.files.iter().enumerate() {
+                let live = runtime.spec_path(&item.live)?;
+                let copy = live
+                    .is_file()
+                    .then(|| rollback.dir.join(format!("file-{index}")));
+                if let Some(copy) = &copy {
+                    fs::copy(&live, copy).map_err(|e| {
+                        format!("Could not stage {} for rollback: {e}", live.display())
+                    })?;
+                }
+                rollback.files.push((live, copy));
+            }
+            for (index, item) in runtime.profile.state.keys.iter().enumerate() {
+                // The whole file: nothing but the switch writes to it while
+                // the launcher is closed, so putting it back whole is exact.
+                let live = runtime.spec_path(&item.live)?;
+                let copy = live
+                    .is_file()
+                    .then(|| rollback.dir.join(format!("keys-{index}")));
+                if let Some(copy) = &copy {
+                    fs::copy(&live, copy).map_err(|e| {
+                        format!("Could not stage {} for rollback: {e}", live.display())
+                    })?;
+                }
+                rollback.files.push((live, copy));
+            }
+            for (index, item) in runtime.profile.state.directories.iter().enumerate() {
+                let live = runtime.spec_path(&item.live)?;
+                let copy = live
+                    .is_dir()
+                    .then(|| rollback.dir.join(format!("dir-{index}")));
+                if let Some(copy) = &copy {
+                    crate::fs_utils::copy_dir_recursive(&live, copy, &[])?;
+                }
+                rollback.directories.push((live, copy));
+            }
+            for item in &runtime.profile.state.registry_values {
+                rollback.registry_values.push((
+                    item.root,
+                    item.key.clone(),
+                    item.value.clone(),
+                    runtime.registry.read(item.root, &item.key, &item.value),
+                ));
+            }
+            Ok::<(), String>(())
+        })();
+
+        match staged {
+            Ok(()) => Ok(rollback),
+            Err(error) => {
+                rollback.discard();
+                Err(error)
+            }
+        }
+    }
+
+    /// Puts the live state staged by [`Self::stage_live_state`] back, byte for
+    /// byte: what existed is copied back over whatever the switch left, what
+    /// did not exist is removed. Best-effort: the caller is already returning
+    /// an error and has nothing better to fall back on, so a step that fails
+    /// here is logged and the others still run.
+    fn roll_back_live_state(&self, app: &dyn AppContext, rollback: &LiveRollback) {
+        let source = format!("{}.switch_rollback", self.descriptor.id);
+        let report = |what: &Path, error: String| {
+            log_platform_error(
+                app,
+                &source,
+                "Could not restore live item from rollback copy",
+                format!("item={}; error={error}", what.display()),
+            );
+        };
+
+        for (live, copy) in &rollback.files {
+            let result = match copy {
+                Some(copy) => live
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|()| fs::copy(copy, live).map(|_| ()))
+                    .map_err(|e| e.to_string()),
+                None if live.exists() => fs::remove_file(live).map_err(|e| e.to_string()),
+                None => Ok(()),
+            };
+            if let Err(error) = result {
+                report(live, error);
+            }
+        }
+
+        for (live, copy) in &rollback.directories {
+            // Cleared first, so a file the incoming account added cannot
+            // outlive the rollback next to the outgoing account's.
+            if live.exists() {
+                if let Err(e) = fs::remove_dir_all(live) {
+                    report(live, e.to_string());
+                    continue;
+                }
+            }
+            if let Some(copy) = copy {
+                if let Err(error) = crate::fs_utils::copy_dir_recursive(copy, live, &[]) {
+                    report(live, error);
+                }
+            }
+        }
+
+        let registry = &rollback.registry;
+        for (root, key, value, data) in &rollback.registry_values {
+            match data {
+                Some(data) => {
+                    if let Err(error) = registry.write(*root, key, value, data) {
+                        report(Path::new(&registry.display(*root, key, value)), error);
+                    }
+                }
+                None => registry.delete(*root, key, value),
+            }
+        }
+
+        if let Some(marker) = &rollback.current_marker {
+            if let Err(error) = config_bridge::set_current_account(app, &self.descriptor.id, marker)
+            {
+                log_platform_error(
+                    app,
+                    &source,
+                    "Could not restore the current account marker",
+                    error,
+                );
+            }
+        }
+    }
+
     // -----------------------------------------------------------------------
     // Reads
     // -----------------------------------------------------------------------
@@ -857,10 +1599,7 @@ impl DescriptorService {
     fn read_accounts(&self, app: &dyn AppContext) -> Result<Vec<DescriptorAccount>, String> {
         let runtime = self.runtime(app)?;
         let blocked = self.blocked_ids(app);
-        let mut discovered: HashSet<String> = self
-            .discovered_ids(app, &runtime)
-            .into_iter()
-            .collect::<HashSet<_>>();
+        let mut discovered = self.discovered_ids(app, &runtime);
         // The account signed in right now counts as discovered, unless it is
         // one the user forgot and has not used since.
         //
@@ -870,10 +1609,10 @@ impl DescriptorService {
         // there would show that same account a second time under its real name.
         if runtime.profile.identity.current == CurrentSource::Identity {
             if let Some(id) = self
-                .read_identity_in(&runtime)
+                .read_identity_in(app, &runtime)
                 .filter(|id| !blocked.contains(id))
             {
-                discovered.insert(id);
+                discovered.entry(id).or_default();
             }
         }
         let stored = config_bridge::accounts(app, &self.descriptor.id);
@@ -889,25 +1628,35 @@ impl DescriptorService {
             if id.is_empty() || !seen.insert(id.clone()) {
                 continue;
             }
+            // The launcher's own name for the account stands in until the
+            // user gives it one.
+            let label = Some(self.account_label(&id, Some(account)))
+                .filter(|label| !label.is_empty())
+                .or_else(|| discovered.get(&id).cloned())
+                .unwrap_or_default();
             accounts.push(DescriptorAccount {
                 snapshot_saved: self.has_snapshot(app, &id),
+                label,
                 account_id: id,
-                label: account.label.clone(),
                 last_used_at: account.last_used_at,
+                launch_values: account.launch_values.clone(),
+                fields: account.fields.clone(),
             });
         }
 
         // An account signed in outside accshift is real even with no config
         // entry, so it is listed too.
-        for id in &discovered {
+        for (id, name) in &discovered {
             if !seen.insert(id.clone()) {
                 continue;
             }
             accounts.push(DescriptorAccount {
                 account_id: id.clone(),
-                label: String::new(),
+                label: name.clone(),
                 last_used_at: None,
                 snapshot_saved: self.has_snapshot(app, id),
+                launch_values: BTreeMap::new(),
+                fields: BTreeMap::new(),
             });
         }
 
@@ -916,7 +1665,7 @@ impl DescriptorService {
             .map(|a| self.normalise_id(&a.account_id))
             .collect();
         accounts.retain(|a| {
-            discovered.contains(&a.account_id)
+            discovered.contains_key(&a.account_id)
                 || stored_ids.contains(&a.account_id)
                 || a.snapshot_saved
         });
@@ -928,8 +1677,17 @@ impl DescriptorService {
     // Operations
     // -----------------------------------------------------------------------
 
-    fn switch(&self, app: &dyn AppContext, account_id: &str) -> Result<(), String> {
-        let account_id = self.validate_account_id(account_id)?;
+    /// Switches to `account_id`. With a `generation`, that earlier capture
+    /// becomes the account's snapshot once the outgoing account is captured,
+    /// and is what gets restored.
+    fn switch(
+        &self,
+        app: &dyn AppContext,
+        account_id: &str,
+        overrides: &BTreeMap<String, String>,
+        generation: Option<u64>,
+    ) -> Result<(), PlatformError> {
+        let account_id = self.validate_account_id(account_id)?;
         let source = format!("{}.switch_account", self.descriptor.id);
         log_platform_info(
             app,
@@ -937,22 +1695,37 @@ impl DescriptorService {
             &format!("{} switch requested", self.descriptor.short_name),
             format!("target={}", redact_id(&account_id)),
         );
+        self.ensure_not_in_use("switching")?;
 
         let close_first = self.closes_before_capture();
         if close_first {
             // This client holds its session in memory and writes it out as it
             // exits, so capturing it while it runs would store nothing.
-            self.quit_and_wait();
+            self.quit_and_wait(app);
         }
 
         // Snapshot the outgoing account first. Aborting here is the point:
         // going further would overwrite its live session with the target's.
         self.capture_current_account(app)?;
+        if let Some(generation) = generation {
+            // After the capture, not before: when the target is the account
+            // signed in, the capture would otherwise replace the generation
+            // just put back.
+            let snapshots_dir = crate::storage::platform_snapshots_dir(app, &self.descriptor.id)?;
+            snapshot_history::promote(
+                &snapshots_dir,
+                &account_id,
+                generation,
+                snapshot_history::generations_kept(app),
+            )?;
+        }
 
         let uses_config_marker = self
             .profile()
             .map(|p| p.identity.current == CurrentSource::Config)
             .unwrap_or(false);
+        let previous_marker = uses_config_marker
+            .then(|| config_bridge::current_account(app, &self.descriptor.id).unwrap_or_default());
         if uses_config_marker {
             // Clear the marker before touching live files: a restore that
             // fails midway leaves a mix of two accounts, which must not be
@@ -961,16 +1734,24 @@ impl DescriptorService {
         }
 
         if !close_first {
-            self.quit_and_wait();
-        }
-        self.restore_snapshot(app, &account_id)?;
-        self.clear_caches(app);
-        config_bridge::touch_account(app, &self.descriptor.id, &account_id, now_unix_ms())?;
-        if uses_config_marker {
-            config_bridge::set_current_account(app, &self.descriptor.id, &account_id)?;
+            self.quit_and_wait(app);
         }
 
-        let result = self.launch(app);
+        // Staged only once the launcher is closed: a running client holds its
+        // files locked, and could still rewrite them after the copy.
+        let rollback = match self.stage_live_state(app, previous_marker.clone()) {
+            Ok(rollback) => rollback,
+            Err(error) => {
+                // Nothing live was touched yet, so the marker cleared above is
+                // simply put back and the switch aborts.
+                if let Some(marker) = &previous_marker {
+                    let _ = config_bridge::set_current_account(app, &self.descriptor.id, marker);
+                }
+                return Err(error.into());
+            }
+        };
+
+        let result = self.apply_switch(app, &account_id, uses_config_marker, overrides);
         match &result {
             Ok(()) => log_platform_info(
                 app,
@@ -978,16 +1759,45 @@ impl DescriptorService {
                 &format!("{} switch completed", self.descriptor.short_name),
                 format!("target={}", redact_id(&account_id)),
             ),
-            Err(error) => log_platform_error(
-                app,
-                &source,
-                &format!("{} switch failed", self.descriptor.short_name),
-                format!("target={}; error={error}", redact_id(&account_id)),
-            ),
+            Err(error) => {
+                self.roll_back_live_state(app, &rollback);
+                log_platform_error(
+                    app,
+                    &source,
+                    &format!(
+                        "{} switch failed, live state rolled back",
+                        self.descriptor.short_name
+                    ),
+                    format!("target={}; error={error}", redact_id(&account_id)),
+                );
+            }
         }
+        rollback.discard();
         result
     }
 
+    /// Everything a switch does to the live state once the outgoing account is
+    /// safe. Any error here is undone by [`Self::roll_back_live_state`].
+    fn apply_switch(
+        &self,
+        app: &dyn AppContext,
+        account_id: &str,
+        uses_config_marker: bool,
+        overrides: &BTreeMap<String, String>,
+    ) -> Result<(), PlatformError> {
+        self.restore_snapshot(app, account_id)?;
+        self.clear_caches(app);
+        if uses_config_marker {
+            config_bridge::set_current_account(app, &self.descriptor.id, account_id)?;
+        }
+        self.launch(app, &self.launch_values(app, account_id, overrides))?;
+        // Stamped only once the launcher is up: a switch rolled back before
+        // then was never a use of the account. The session is live by now, so
+        // a config write failing here is not worth undoing it over.
+        let _ = config_bridge::touch_account(app, &self.descriptor.id, account_id, now_unix_ms());
+        Ok(())
+    }
+
     fn begin(&self, app: &dyn AppContext) -> Result<SetupStatus, String> {
         let source = format!("{}.begin_account_setup", self.descriptor.id);
         log_platform_info(
@@ -996,23 +1806,20 @@ impl DescriptorService {
             &format!("{} account setup requested", self.descriptor.short_name),
             "",
         );
+        self.ensure_not_in_use("adding an account")?;
 
         let was_running = self.is_running();
         let close_first = self.closes_before_capture();
         if close_first {
-            self.quit_and_wait();
+            self.quit_and_wait(app);
         }
 
         // Everything that already exists, so the flow can tell the account the
         // user is about to add from the ones that were there before.
         let runtime = self.runtime(app)?;
-        let stored: HashSet<String> = config_bridge::accounts(app, &self.descriptor.id)
-            .iter()
-            .map(|account| self.normalise_id(&account.account_id))
-            .filter(|id| !id.is_empty())
-            .collect();
-        let live = self.read_identity_detail(&runtime);
-        let mut known: HashSet<String> = self.discovered_ids(app, &runtime).into_iter().collect();
+        let stored = self.stored_ids(app);
+        let live = self.read_identity_detail(app, &runtime);
+        let mut known: HashSet<String> = self.discovered_ids(app, &runtime).into_keys().collect();
         known.extend(live.as_ref().map(|found| found.id.clone()));
         known.extend(stored.iter().cloned());
 
@@ -1034,7 +1841,7 @@ impl DescriptorService {
         )?;
 
         if !close_first {
-            self.quit_and_wait();
+            self.quit_and_wait(app);
         }
         self.clear_live_state(app)?;
         if self
@@ -1046,7 +1853,7 @@ impl DescriptorService {
             config_bridge::set_current_account(app, &self.descriptor.id, "")?;
         }
 
-        self.launch(app).inspect_err(|e| {
+        self.launch(app, &BTreeMap::new()).inspect_err(|e| {
             log_platform_error(
                 app,
                 &source,
@@ -1064,33 +1871,49 @@ impl DescriptorService {
         ))
     }
 
-    /// Takes the session that is already signed in as the account being added,
-    /// when nothing tracks it yet.
+    /// The ids the account list already holds.
+    fn stored_ids(&self, app: &dyn AppContext) -> HashSet<String> {
+        config_bridge::accounts(app, &self.descriptor.id)
+            .iter()
+            .map(|account| self.normalise_id(&account.account_id))
+            .filter(|id| !id.is_empty())
+            .collect()
+    }
+
+    /// The live session setup would take as the account being added, when
+    /// nothing tracks it yet.
     ///
     /// Without this, adding an account starts by wiping the one the user was
     /// already using, so their first account is signed out just to be listed.
     /// Adopting is refused as soon as a current account is recorded: that
     /// session already belongs to a tracked account, possibly under an id we
     /// minted ourselves, and adopting would list it twice.
-    fn try_adopt(
+    fn adoptable(
         &self,
         app: &dyn AppContext,
         live: Option<HookIdentity>,
         stored: &HashSet<String>,
-        was_running: bool,
-    ) -> Result<Option<SetupStatus>, String> {
+    ) -> Option<HookIdentity> {
         if self
             .current_account_id(app)
             .is_some_and(|current| !current.is_empty())
         {
-            return Ok(None);
+            return None;
         }
-        let Some(found) = live else {
+        live.filter(|found| !stored.contains(&found.id))
+    }
+
+    /// Adopts the session [`Self::adoptable`] picks, if any.
+    fn try_adopt(
+        &self,
+        app: &dyn AppContext,
+        live: Option<HookIdentity>,
+        stored: &HashSet<String>,
+        was_running: bool,
+    ) -> Result<Option<SetupStatus>, String> {
+        let Some(found) = self.adoptable(app, live, stored) else {
             return Ok(None);
         };
-        if stored.contains(&found.id) {
-            return Ok(None);
-        }
 
         self.save_snapshot(app, &found.id)?;
         if self.declares_snapshot_marker() && !self.snapshot_has_content(app, &found.id) {
@@ -1112,7 +1935,8 @@ impl DescriptorService {
 
         // Put the client back the way we found it: same session, no sign-in.
         if was_running {
-            let _ = self.launch(app).inspect_err(|e| {
+            let values = self.launch_values(app, &found.id, &BTreeMap::new());
+            let _ = self.launch(app, &values).inspect_err(|e| {
                 log_platform_error(
                     app,
                     &format!("{}.begin_account_setup", self.descriptor.id),
@@ -1141,8 +1965,10 @@ impl DescriptorService {
     }
 
     /// Names a freshly captured account after whatever the platform calls it,
-    /// so the list never opens on a raw id. Returns the name to report.
+    /// so the list never opens on a raw id, and keeps the fields read with
+    /// it. Returns the name to report.
     fn seed_label(&self, app: &dyn AppContext, found: &HookIdentity) -> String {
+        self.remember_fields(app, found);
         match &found.display_name {
             Some(name) => {
                 let _ = config_bridge::set_label(app, &self.descriptor.id, &found.id, name);
@@ -1168,6 +1994,29 @@ impl DescriptorService {
             .unwrap_or(false)
     }
 
+    /// Refuses `action` while a process the descriptor lists under
+    /// `refuseWhileRunning` is up: closing the launcher then would pull it out
+    /// from under a game.
+    fn ensure_not_in_use(&self, action: &str) -> Result<(), String> {
+        let Ok(profile) = self.profile() else {
+            return Ok(());
+        };
+        if profile.close.refuse_while_running.is_empty() || self.offline.is_some() {
+            return Ok(());
+        }
+        let names: Vec<&str> = profile
+            .close
+            .refuse_while_running
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let running = crate::os::running_process_names(&names);
+        if running.is_empty() {
+            return Ok(());
+        }
+        Err(format!("Close {} before {action}", running.join(", ")))
+    }
+
     fn setup_status(&self, app: &dyn AppContext, setup_id: &str) -> Result<SetupStatus, String> {
         let job = self.jobs.touch(setup_id)?;
         let runtime = self.runtime(app)?;
@@ -1176,11 +2025,11 @@ impl DescriptorService {
         // The launcher's own marker first, then anything the platform leaves on
         // disk: some write the id where we can read it only after a restart.
         let found = self
-            .read_identity_detail(&runtime)
+            .read_identity_detail(app, &runtime)
             .filter(|found| !job.known_account_ids.contains(&found.id));
         let new_identity = found.as_ref().map(|found| found.id.clone()).or_else(|| {
             self.discovered_ids(app, &runtime)
-                .into_iter()
+                .into_keys()
                 .find(|id| !job.known_account_ids.contains(id))
         });
         let input = ConditionInput {
@@ -1198,7 +2047,7 @@ impl DescriptorService {
             // A trigger only says the user got through the login screen. The
             // launcher may still hold the session in memory, so it is closed
             // and the conditions re-checked before anything is captured.
-            self.quit_and_wait();
+            self.quit_and_wait(app);
 
             let still_holds = setup
                 .confirm
@@ -1265,7 +2114,7 @@ impl DescriptorService {
     ) -> bool {
         match condition {
             Condition::NewIdentity => input.new_identity.is_some(),
-            Condition::IdentityPresent => self.read_identity_in(runtime).is_some(),
+            Condition::IdentityPresent => self.read_live_identity(runtime).is_some(),
             Condition::SinceStart { ms } => match input.started_at {
                 Some(started_at) => now_unix_ms().saturating_sub(started_at) >= *ms,
                 // Asked outside a setup flow, where there is no start to
@@ -1310,34 +2159,54 @@ impl DescriptorService {
     // Dry run
     // -----------------------------------------------------------------------
 
+    /// Describes `operation` the way this descriptor would perform it,
+    /// writing nothing. `account_id` is ignored by the operations that act on
+    /// whatever is signed in, and `overrides` by all but the switch.
+    pub fn plan(
+        &self,
+        app: &dyn AppContext,
+        operation: DryRunOperation,
+        account_id: &str,
+        overrides: &BTreeMap<String, String>,
+    ) -> Result<DryRunPlan, String> {
+        match operation {
+            DryRunOperation::Switch => self.plan_switch(app, account_id, overrides),
+            DryRunOperation::Forget => self.plan_forget(app, account_id),
+            DryRunOperation::Setup => self.plan_setup(app),
+            DryRunOperation::Capture => self.plan_capture(app),
+        }
+    }
+
     /// Describes the switch this descriptor would perform, writing nothing.
     pub fn plan_switch(
         &self,
         app: &dyn AppContext,
         account_id: &str,
+        overrides: &BTreeMap<String, String>,
     ) -> Result<DryRunPlan, String> {
         let account_id = self.validate_account_id(account_id)?;
         let runtime = self.runtime(app)?;
         let cache_dir = self.snapshot_root(app, &account_id)?;
 
-        let mut plan = DryRunPlan::new(&self.descriptor.id, "switch", &account_id).with_roots(
-            runtime
-                .sandbox
-                .roots()
-                .iter()
-                .map(|root| root.display().to_string()),
-        );
+        let mut plan = self.empty_plan(&runtime, DryRunOperation::Switch, &account_id);
 
+        if let Err(refusal) = self.ensure_not_in_use("switching") {
+            plan.warn(format!("{refusal}: the switch would be refused now."));
+        }
         if !cache_dir.exists() {
             plan.warn(format!(
                 "No snapshot stored for account {account_id}: the switch would fail here."
             ));
+        } else if let Some(problem) = snapshot_manifest::verify(&cache_dir).failure() {
+            plan.warn(format!(
+                "The snapshot of account {account_id} fails its integrity check, so the switch would stop before restoring it: {problem}."
+            ));
         }
 
         match self.current_account_id(app) {
             Some(current) => {
                 let current_dir = self.snapshot_root(app, &current)?;
-                self.plan_capture(&runtime, &mut plan, &current_dir);
+                self.capture_steps(&runtime, &mut plan, &current_dir);
             }
             None => plan.warn(
                 "No account is signed in, so nothing would be captured before the switch."
@@ -1345,26 +2214,237 @@ impl DescriptorService {
             ),
         }
 
-        for name in &runtime.profile.close.processes {
-            plan.simple_step(PlanAction::Close, PlanTargetKind::Process, name, "");
+        Self::close_steps(&runtime, &mut plan);
+        self.restore_steps(&runtime, &mut plan, &cache_dir);
+        let values = self.launch_values(app, &account_id, overrides);
+        self.launch_step(app, &mut plan, "", &values);
+
+        Ok(plan)
+    }
+
+    /// Describes forgetting `account_id`: the list entry and the snapshot go,
+    /// the live session stays.
+    pub fn plan_forget(
+        &self,
+        app: &dyn AppContext,
+        account_id: &str,
+    ) -> Result<DryRunPlan, String> {
+        let account_id = self.validate_account_id(account_id)?;
+        let runtime = self.runtime(app)?;
+        let mut plan = self.empty_plan(&runtime, DryRunOperation::Forget, &account_id);
+
+        let mut notes = Vec::new();
+        if !self.stored_ids(app).contains(&account_id) {
+            notes.push("not in the list");
+        }
+        if runtime.profile.identity.blocklist_on_forget {
+            notes.push("kept out of discovery from then on");
+        }
+        plan.simple_step(
+            PlanAction::Delete,
+            PlanTargetKind::Account,
+            &account_id,
+            notes.join(", "),
+        );
+
+        // The same guard as the real forget: the id is joined into the path.
+        if self.id_is_valid(&account_id) {
+            let cache_dir = self.snapshot_root(app, &account_id)?;
+            plan.simple_step(
+                PlanAction::Delete,
+                PlanTargetKind::Directory,
+                cache_dir.display().to_string(),
+                if cache_dir.is_dir() {
+                    ""
+                } else {
+                    "no snapshot"
+                },
+            );
+        } else {
+            plan.warn(format!(
+                "{account_id} is not a well-formed id, so no snapshot would be looked for."
+            ));
+        }
+
+        Ok(plan)
+    }
+
+    /// Describes starting to add an account: adopting the session already
+    /// signed in when [`Self::adoptable`] allows it, otherwise capturing it,
+    /// clearing the live state and launching the sign-in.
+    pub fn plan_setup(&self, app: &dyn AppContext) -> Result<DryRunPlan, String> {
+        let runtime = self.runtime(app)?;
+        let mut plan = self.empty_plan(&runtime, DryRunOperation::Setup, "");
+
+        if let Err(refusal) = self.ensure_not_in_use("adding an account") {
+            plan.warn(format!("{refusal}: the setup would be refused now."));
+        }
+        let close_first = runtime.profile.close.before_capture;
+        if close_first {
+            Self::close_steps(&runtime, &mut plan);
+        }
+
+        if runtime.profile.setup.adopt_signed_in {
+            let live = self.read_identity_detail(app, &runtime);
+            if let Some(found) = self.adoptable(app, live, &self.stored_ids(app)) {
+                let cache_dir = self.snapshot_root(app, &found.id)?;
+                self.capture_steps(&runtime, &mut plan, &cache_dir);
+                if self.declares_snapshot_marker() {
+                    plan.warn(
+                        "Should the capture hold no session, setup would clear the live state \
+                         and launch the sign-in instead."
+                            .to_string(),
+                    );
+                }
+                if self.is_running() {
+                    let values = self.launch_values(app, &found.id, &BTreeMap::new());
+                    self.launch_step(app, &mut plan, "started again on the same session", &values);
+                }
+                plan.account_id = found.id;
+                return Ok(plan);
+            }
+        }
+
+        match self.current_account_id_in(app, &runtime) {
+            Some(current) if self.capture_worth_running(app) => {
+                let cache_dir = self.snapshot_root(app, &current)?;
+                self.capture_steps(&runtime, &mut plan, &cache_dir);
+            }
+            Some(_) => plan.warn(CAPTURE_GATE_CLOSED.to_string()),
+            None => {}
+        }
+        if !close_first {
+            Self::close_steps(&runtime, &mut plan);
+        }
+        self.clear_steps(&runtime, &mut plan);
+        self.launch_step(app, &mut plan, "for the sign-in", &BTreeMap::new());
+
+        Ok(plan)
+    }
+
+    /// Describes refreshing the signed-in account's snapshot from the live
+    /// state, the capture a switch starts with.
+    pub fn plan_capture(&self, app: &dyn AppContext) -> Result<DryRunPlan, String> {
+        let runtime = self.runtime(app)?;
+        let current = self.current_account_id_in(app, &runtime);
+        let mut plan = self.empty_plan(
+            &runtime,
+            DryRunOperation::Capture,
+            current.as_deref().unwrap_or(""),
+        );
+
+        match current {
+            Some(_) if !self.capture_worth_running(app) => {
+                plan.warn(CAPTURE_GATE_CLOSED.to_string())
+            }
+            Some(current) => {
+                let cache_dir = self.snapshot_root(app, &current)?;
+                self.capture_steps(&runtime, &mut plan, &cache_dir);
+            }
+            None => {
+                plan.warn("No account is signed in, so there is nothing to capture.".to_string())
+            }
         }
 
-        self.plan_restore(&runtime, &mut plan, &cache_dir);
+        Ok(plan)
+    }
+
+    fn empty_plan(
+        &self,
+        runtime: &Runtime<'_>,
+        operation: DryRunOperation,
+        account_id: &str,
+    ) -> DryRunPlan {
+        DryRunPlan::new(&self.descriptor.id, operation.as_str(), account_id).with_roots(
+            runtime
+                .sandbox
+                .roots()
+                .iter()
+                .map(|root| root.display().to_string()),
+        )
+    }
+
+    /// Each close strategy as a request, then the processes, terminated if
+    /// the requests left them running.
+    fn close_steps(runtime: &Runtime<'_>, plan: &mut DryRunPlan) {
+        let close = &runtime.profile.close;
+        for strategy in &close.strategies {
+            plan.simple_step(
+                PlanAction::Close,
+                PlanTargetKind::Request,
+                close::describe(strategy),
+                format!(
+                    "{}, then waits up to {} ms for the launcher to exit",
+                    strategy.as_str(),
+                    strategy.timeout_ms()
+                ),
+            );
+        }
+        let note = if close.strategies.is_empty() {
+            ""
+        } else {
+            "terminated if still running"
+        };
+        for name in &close.processes {
+            plan.simple_step(PlanAction::Close, PlanTargetKind::Process, name, note);
+        }
+    }
 
-        match self.resolve_executable(app) {
-            Ok(exe) => plan.simple_step(
+    /// The launch, as the command line it would run and the environment it
+    /// would set.
+    fn launch_step(
+        &self,
+        app: &dyn AppContext,
+        plan: &mut DryRunPlan,
+        note: &str,
+        values: &BTreeMap<String, String>,
+    ) {
+        let exe = match self.resolve_executable(app) {
+            Ok(exe) => exe,
+            Err(e) => return plan.warn(e),
+        };
+        let Some(launch) = self.profile().ok().and_then(|p| p.launch.as_ref()) else {
+            return plan.simple_step(
                 PlanAction::Launch,
                 PlanTargetKind::Executable,
                 exe.display().to_string(),
-                "",
-            ),
-            Err(e) => plan.warn(e),
+                note,
+            );
+        };
+        let command = LaunchCommand::build(launch, &exe, values);
+        let mut target = exe.display().to_string();
+        for arg in &command.args {
+            target.push(' ');
+            if arg.contains(char::is_whitespace) {
+                target.push_str(&format!("\"{arg}\""));
+            } else {
+                target.push_str(arg);
+            }
+        }
+        let mut notes: Vec<String> = Vec::new();
+        if !note.is_empty() {
+            notes.push(note.to_string());
+        }
+        notes.extend(
+            command
+                .env
+                .iter()
+                .map(|(name, value)| format!("{name}={value}")),
+        );
+        plan.simple_step(
+            PlanAction::Launch,
+            PlanTargetKind::Executable,
+            target,
+            notes.join(", "),
+        );
+        for (skipped, name) in command.skipped {
+            plan.warn(format!(
+                "`{skipped}` would be left out: the account has no `{name}` launch value."
+            ));
         }
-
-        Ok(plan)
     }
 
-    fn plan_capture(&self, runtime: &Runtime<'_>, plan: &mut DryRunPlan, cache_dir: &Path) {
+    fn capture_steps(&self, runtime: &Runtime<'_>, plan: &mut DryRunPlan, cache_dir: &Path) {
         for item in &runtime.profile.state.files {
             match runtime.spec_path(&item.live) {
                 Ok(live) => plan.path_step(
@@ -1378,11 +2458,14 @@ impl DescriptorService {
             }
         }
         for item in &runtime.profile.state.registry_values {
-            let present = reg::read(item.root, &item.key, &item.value).is_some();
+            let present = runtime
+                .registry
+                .read(item.root, &item.key, &item.value)
+                .is_some();
             plan.push(PlanStep {
                 action: PlanAction::Capture,
                 kind: PlanTargetKind::RegistryValue,
-                target: reg::display(item.root, &item.key, &item.value),
+                target: runtime.registry.display(item.root, &item.key, &item.value),
                 snapshot: cache_dir.join(&item.snapshot).display().to_string(),
                 note: if present {
                     String::new()
@@ -1391,6 +2474,35 @@ impl DescriptorService {
                 },
             });
         }
+        for item in &runtime.profile.state.keys {
+            let live = match runtime.spec_path(&item.live) {
+                Ok(live) => live,
+                Err(e) => {
+                    plan.warn(e.to_string());
+                    continue;
+                }
+            };
+            let captured = read_key_file(&live)
+                .and_then(|content| keys::capture(&content, &item.select))
+                .unwrap_or_else(|e| {
+                    plan.warn(format!("{}: {e}", live.display()));
+                    keys::Captured::default()
+                });
+            let snapshot = cache_dir.join(&item.snapshot).display().to_string();
+            for label in keys::labels(&item.select) {
+                plan.push(PlanStep {
+                    action: PlanAction::Capture,
+                    kind: PlanTargetKind::FileKey,
+                    target: format!("{}#{label}", live.display()),
+                    snapshot: snapshot.clone(),
+                    note: if captured.present.contains_key(&label) {
+                        String::new()
+                    } else {
+                        "not set".into()
+                    },
+                });
+            }
+        }
         for item in &runtime.profile.state.directories {
             match runtime.spec_path(&item.live) {
                 Ok(live) => plan.path_step(
@@ -1405,7 +2517,7 @@ impl DescriptorService {
         }
     }
 
-    fn plan_restore(&self, runtime: &Runtime<'_>, plan: &mut DryRunPlan, cache_dir: &Path) {
+    fn restore_steps(&self, runtime: &Runtime<'_>, plan: &mut DryRunPlan, cache_dir: &Path) {
         for item in &runtime.profile.state.files {
             let snapshot = cache_dir.join(&item.snapshot);
             match runtime.spec_path(&item.live) {
@@ -1428,7 +2540,7 @@ impl DescriptorService {
             plan.push(PlanStep {
                 action: PlanAction::Restore,
                 kind: PlanTargetKind::RegistryValue,
-                target: reg::display(item.root, &item.key, &item.value),
+                target: runtime.registry.display(item.root, &item.key, &item.value),
                 snapshot: snapshot.display().to_string(),
                 note: if snapshot.exists() {
                     String::new()
@@ -1437,6 +2549,29 @@ impl DescriptorService {
                 },
             });
         }
+        for item in &runtime.profile.state.keys {
+            let snapshot = cache_dir.join(&item.snapshot);
+            let live = match runtime.spec_path(&item.live) {
+                Ok(live) => live,
+                Err(e) => {
+                    plan.warn(e.to_string());
+                    continue;
+                }
+            };
+            for label in keys::labels(&item.select) {
+                plan.push(PlanStep {
+                    action: PlanAction::Restore,
+                    kind: PlanTargetKind::FileKey,
+                    target: format!("{}#{label}", live.display()),
+                    snapshot: snapshot.display().to_string(),
+                    note: if snapshot.exists() {
+                        String::new()
+                    } else {
+                        "no snapshot, skipped".into()
+                    },
+                });
+            }
+        }
         for item in &runtime.profile.state.directories {
             let snapshot = cache_dir.join(&item.snapshot);
             match runtime.spec_path(&item.live) {
@@ -1454,6 +2589,86 @@ impl DescriptorService {
                 Err(e) => plan.warn(e.to_string()),
             }
         }
+        Self::cache_steps(runtime, plan);
+    }
+
+    /// What setup removes before the sign-in, in [`Self::clear_live_state`]'s
+    /// order.
+    fn clear_steps(&self, runtime: &Runtime<'_>, plan: &mut DryRunPlan) {
+        let state = &runtime.profile.state;
+        for item in state.files.iter().filter(|item| item.clear_on_setup) {
+            match runtime.spec_path(&item.live) {
+                Ok(live) => plan.simple_step(
+                    PlanAction::Delete,
+                    PlanTargetKind::File,
+                    live.display().to_string(),
+                    if live.is_file() { "" } else { "not present" },
+                ),
+                Err(e) => plan.warn(e.to_string()),
+            }
+        }
+        for item in state
+            .registry_values
+            .iter()
+            .filter(|item| item.clear_on_setup)
+        {
+            let present = runtime
+                .registry
+                .read(item.root, &item.key, &item.value)
+                .is_some();
+            plan.simple_step(
+                PlanAction::Delete,
+                PlanTargetKind::RegistryValue,
+                runtime.registry.display(item.root, &item.key, &item.value),
+                if present { "" } else { "not set" },
+            );
+        }
+        for item in state.keys.iter().filter(|item| item.clear_on_setup) {
+            let live = match runtime.spec_path(&item.live) {
+                Ok(live) => live,
+                Err(e) => {
+                    plan.warn(e.to_string());
+                    continue;
+                }
+            };
+            let captured = if live.is_file() {
+                read_key_file(&live)
+                    .and_then(|content| keys::capture(&content, &item.select))
+                    .unwrap_or_else(|e| {
+                        plan.warn(format!("{}: {e}", live.display()));
+                        keys::Captured::default()
+                    })
+            } else {
+                keys::Captured::default()
+            };
+            for label in keys::labels(&item.select) {
+                plan.simple_step(
+                    PlanAction::Delete,
+                    PlanTargetKind::FileKey,
+                    format!("{}#{label}", live.display()),
+                    if captured.present.contains_key(&label) {
+                        ""
+                    } else {
+                        "not set"
+                    },
+                );
+            }
+        }
+        for item in state.directories.iter().filter(|item| item.clear_on_setup) {
+            match runtime.spec_path(&item.live) {
+                Ok(live) => plan.simple_step(
+                    PlanAction::Delete,
+                    PlanTargetKind::Directory,
+                    live.display().to_string(),
+                    if live.is_dir() { "" } else { "not present" },
+                ),
+                Err(e) => plan.warn(e.to_string()),
+            }
+        }
+        Self::cache_steps(runtime, plan);
+    }
+
+    fn cache_steps(runtime: &Runtime<'_>, plan: &mut DryRunPlan) {
         for template in &runtime.profile.state.caches {
             match runtime.path(template) {
                 Ok(path) => plan.simple_step(
@@ -1473,15 +2688,16 @@ impl DescriptorService {
 // ---------------------------------------------------------------------------
 
 /// One operation's resolved view of a descriptor.
-struct Runtime<'a> {
-    profile: &'a OsProfile,
-    resolver: PathResolver,
-    sandbox: Sandbox,
+pub(super) struct Runtime<'a> {
+    pub(super) profile: &'a OsProfile,
+    pub(super) resolver: PathResolver,
+    pub(super) sandbox: Sandbox,
+    pub(super) registry: Registry,
 }
 
 impl Runtime<'_> {
     /// Resolves a state path and refuses it if it falls outside the roots.
-    fn path(&self, template: &PathTemplate) -> Result<PathBuf, PlatformError> {
+    pub(super) fn path(&self, template: &PathTemplate) -> Result<PathBuf, PlatformError> {
         let resolved = self.resolver.resolve(template)?;
         self.sandbox.ensure_allowed(&resolved)?;
         Ok(resolved)
@@ -1491,7 +2707,7 @@ impl Runtime<'_> {
     ///
     /// Falling back to the first is what makes a file the launcher has not
     /// written yet land where it expects to find it, instead of failing.
-    fn spec_path(&self, spec: &PathSpec) -> Result<PathBuf, PlatformError> {
+    pub(super) fn spec_path(&self, spec: &PathSpec) -> Result<PathBuf, PlatformError> {
         let mut fallback: Option<PathBuf> = None;
         let mut failure: Option<PlatformError> = None;
         for template in spec.candidates() {
@@ -1548,12 +2764,17 @@ fn profile_uses_install_dir(profile: &OsProfile) -> bool {
     for item in &profile.state.directories {
         placeholders.extend(item.live.placeholders());
     }
+    for item in &profile.state.keys {
+        placeholders.extend(item.live.placeholders());
+    }
     for entry in &profile.identity.discovery {
-        let Discovery::DirectoryEntries { path, .. } = entry;
-        placeholders.extend(path.placeholders());
+        placeholders.extend(entry.path().placeholders());
     }
     match &profile.identity.source {
-        IdentitySource::LogTail { path, .. } => placeholders.extend(path.placeholders()),
+        IdentitySource::LogTail { path, .. }
+        | IdentitySource::JsonPointer { path, .. }
+        | IdentitySource::IniKey { path, .. }
+        | IdentitySource::SqliteQuery { path, .. } => placeholders.extend(path.placeholders()),
         IdentitySource::NativeHook { paths, .. } => {
             for path in paths.values() {
                 placeholders.extend(path.placeholders());
@@ -1561,6 +2782,11 @@ fn profile_uses_install_dir(profile: &OsProfile) -> bool {
         }
         _ => {}
     }
+    for strategy in &profile.close.strategies {
+        if let CloseStrategy::Command { command, .. } = strategy {
+            placeholders.extend(command.placeholders());
+        }
+    }
     for condition in profile
         .setup
         .trigger
@@ -1613,14 +2839,38 @@ struct ConditionInput<'a> {
     started_at: Option<u64>,
 }
 
+/// A live file holding selected keys, read whole. One that does not exist yet
+/// holds none of them, which is not an error: restoring creates it.
+fn read_key_file(live: &Path) -> Result<String, String> {
+    match fs::read_to_string(live) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(format!("Could not read {}: {e}", live.display())),
+    }
+}
+
 /// An id with no name attached, which is every source but a native hook.
 fn bare_identity(id: String) -> HookIdentity {
     HookIdentity {
         id,
         display_name: None,
+        fields: BTreeMap::new(),
     }
 }
 
+/// What a native hook gets to see: the paths its descriptor declared, and
+/// only those the sandbox has cleared, so a hook cannot widen its own reach.
+fn hook_context(runtime: &Runtime<'_>, paths: &BTreeMap<String, PathTemplate>) -> HookContext {
+    HookContext::new(
+        paths
+            .iter()
+            .filter_map(|(key, template)| {
+                runtime.path(template).ok().map(|path| (key.clone(), path))
+            })
+            .collect(),
+    )
+}
+
 /// Where a decrypted file waits until every one of its siblings has landed.
 fn staging_path(live: &Path) -> PathBuf {
     let mut name = live.file_name().unwrap_or_default().to_os_string();
@@ -1628,27 +2878,180 @@ fn staging_path(live: &Path) -> PathBuf {
     live.with_file_name(name)
 }
 
-/// Reads the last `tail_bytes` of a file the launcher keeps open.
+/// The live state as it stood before a switch replaced it.
 ///
-/// Shared access is the point on Windows: the launcher holds its log open, and
-/// an ordinary open would simply fail. The tail is decoded lossily so a cut
-/// through a multi-byte character cannot fail the read, and only the end is
-/// read because the log runs to megabytes and the sign-in sits at the bottom.
-fn read_log_tail(path: &Path, tail_bytes: u64) -> Option<String> {
-    use std::io::{Read, Seek, SeekFrom};
+/// Copies are plain, not encrypted: a rollback must not depend on the keyring
+/// that may be the very thing that failed. They sit in the app's own local
+/// data folder, readable only by the user, for the length of one switch; a
+/// switch cut short by a crash leaves them for [`sweep_switch_rollbacks`].
+struct LiveRollback {
+    dir: PathBuf,
+    /// Each live file, with its copy, or `None` when it did not exist.
+    files: Vec<(PathBuf, Option<PathBuf>)>,
+    directories: Vec<(PathBuf, Option<PathBuf>)>,
+    /// Each registry value, with its data, or `None` when it was absent.
+    registry_values: Vec<(RegistryHive, String, String, Option<String>)>,
+    /// The config marker before the switch cleared it, for platforms that
+    /// track the current account themselves.
+    current_marker: Option<String>,
+    /// Where the registry values were read, so they go back to the same one.
+    registry: Registry,
+}
 
+impl LiveRollback {
+    fn discard(self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Removes the rollback copies a switch left behind when the process died
+/// partway, returning how many. Skipped while another instance holds the
+/// operation lock, since its switch may still need its copy.
+pub fn sweep_switch_rollbacks(app: &dyn AppContext) -> usize {
+    let Ok(_lock) = crate::lock::acquire_exclusive(app, Duration::ZERO) else {
+        return 0;
+    };
+    let Ok(entries) = crate::storage::switch_rollback_dir(app).and_then(|dir| {
+        fs::read_dir(&dir).map_err(|e| format!("Could not list {}: {e}", dir.display()))
+    }) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().contains("-rollback-"))
+        .filter(|entry| fs::remove_dir_all(entry.path()).is_ok())
+        .count()
+}
+
+/// Opens a file for reading without locking the launcher that has it open
+/// out of its own writes.
+fn open_shared(path: &Path) -> Option<fs::File> {
     #[cfg(windows)]
-    let mut file = {
+    {
         use std::os::windows::fs::OpenOptionsExt;
         // FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE
         fs::OpenOptions::new()
             .read(true)
             .share_mode(0x0000_0001 | 0x0000_0002 | 0x0000_0004)
             .open(path)
-            .ok()?
-    };
+            .ok()
+    }
     #[cfg(not(windows))]
-    let mut file = fs::File::open(path).ok()?;
+    {
+        fs::File::open(path).ok()
+    }
+}
+
+/// A whole settings file, read with shared access.
+fn read_shared(path: &Path) -> Option<String> {
+    use std::io::Read;
+
+    let mut content = String::new();
+    open_shared(path)?.read_to_string(&mut content).ok()?;
+    Some(content)
+}
+
+/// The first row of `query` against the database at `path`: the id in the
+/// first column, a display name in the second when there is one, and each
+/// field in the column its `from` names.
+///
+/// Opened read-only, and the statement itself must be one SQLite reports as
+/// read-only, so a descriptor can never write to a launcher's database. A
+/// launcher mid-write holds the lock only briefly; waiting a moment for it
+/// beats reporting nobody signed in.
+fn query_identity(path: &Path, query: &str, fields: &[AccountField]) -> Option<HookIdentity> {
+    use rusqlite::{types::ValueRef, Connection, OpenFlags};
+
+    if !path.is_file() {
+        return None;
+    }
+    let connection = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .ok()?;
+    connection.busy_timeout(Duration::from_millis(250)).ok()?;
+    let mut statement = connection.prepare(query).ok()?;
+    if !statement.readonly() {
+        return None;
+    }
+    let columns = statement.column_count();
+    let names: Vec<String> = statement
+        .column_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let mut rows = statement.query([]).ok()?;
+    let row = rows.next().ok()??;
+    let text = |index: usize| match row.get_ref(index).ok()? {
+        ValueRef::Text(bytes) => String::from_utf8(bytes.to_vec()).ok(),
+        ValueRef::Integer(number) => Some(number.to_string()),
+        _ => None,
+    };
+    let found = fields
+        .iter()
+        .filter_map(|field| {
+            let index = names.iter().position(|name| *name == field.from)?;
+            Some((field.name.clone(), text(index)?))
+        })
+        .collect();
+    Some(HookIdentity {
+        id: text(0)?,
+        display_name: if columns > 1 { text(1) } else { None },
+        fields: found,
+    })
+}
+
+/// An id or a name read out of a structured file. Launchers write numeric ids
+/// as JSON numbers as often as strings.
+/// The `(id, name)` of each element of the array `pointer` names in a JSON
+/// document, the name empty when the element has none. Elements without an id
+/// are skipped: launchers leave half-written entries behind after a failed
+/// login.
+fn json_array_entries(
+    content: &str,
+    pointer: &str,
+    id_field: &str,
+    display_name_field: &str,
+) -> Vec<(String, String)> {
+    let Ok(document) = serde_json::from_str::<Value>(content) else {
+        return Vec::new();
+    };
+    let Some(Value::Array(items)) = document.pointer(pointer) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            let id = item.get(id_field).and_then(scalar_text)?;
+            let name = Some(display_name_field)
+                .filter(|field| !field.is_empty())
+                .and_then(|field| item.get(field))
+                .and_then(scalar_text)
+                .unwrap_or_default();
+            Some((id, name))
+        })
+        .collect()
+}
+
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+/// Reads the last `tail_bytes` of a file the launcher keeps open.
+///
+/// Shared access is the point on Windows: the launcher holds its log open, and
+/// an ordinary open would simply fail. The tail is decoded lossily so a cut
+/// through a multi-byte character cannot fail the read, and only the end is
+/// read because the log runs to megabytes and the sign-in sits at the bottom.
+fn read_log_tail(path: &Path, tail_bytes: u64) -> Option<String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = open_shared(path)?;
 
     let len = file.metadata().ok()?.len();
     let start = len.saturating_sub(tail_bytes);
@@ -1811,9 +3214,14 @@ impl PlatformService for DescriptorService {
         &self,
         app: AppCtx,
         account_id: &str,
-        _params: Value,
+        params: Value,
     ) -> Result<(), PlatformError> {
-        self.switch(&app, account_id).map_err(Into::into)
+        self.switch(
+            &app,
+            account_id,
+            &launch_overrides(&params),
+            snapshot_history::requested_generation(&params),
+        )
     }
 
     fn forget_account(&self, app: AppCtx, account_id: &str) -> Result<(), PlatformError> {
@@ -1889,12 +3297,53 @@ impl PlatformService for DescriptorService {
         config_bridge::set_label(&app, &self.descriptor.id, &account_id, label).map_err(Into::into)
     }
 
-    fn supports_dry_run(&self) -> bool {
+    fn snapshot_generations(
+        &self,
+        app: AppCtx,
+        account_id: &str,
+    ) -> Result<Vec<snapshot_history::SnapshotGeneration>, PlatformError> {
+        let account_id = self.validate_account_id(account_id)?;
+        let snapshots_dir = crate::storage::platform_snapshots_dir(&app, &self.descriptor.id)?;
+        snapshot_history::list(&snapshots_dir, &account_id).map_err(Into::into)
+    }
+
+    fn set_account_launch_values(
+        &self,
+        app: AppCtx,
+        account_id: &str,
+        values: BTreeMap<String, String>,
+    ) -> Result<(), PlatformError> {
+        let account_id = self.validate_account_id(account_id)?;
+        for name in values.keys() {
+            if !is_launch_value_name(name) || BUILTIN_LAUNCH_VALUES.contains(&name.as_str()) {
+                return Err(PlatformError::other(format!(
+                    "`{name}` cannot name a launch value: use letters, digits, `_` or `-`, \
+                     and neither `id` nor `label`"
+                )));
+            }
+        }
+        config_bridge::set_launch_values(&app, &self.descriptor.id, &account_id, &values)
+            .map_err(Into::into)
+    }
+
+    fn account_fields(&self) -> Vec<AccountField> {
+        self.profile()
+            .map(|profile| profile.identity.fields.clone())
+            .unwrap_or_default()
+    }
+
+    fn supports_dry_run(&self, _operation: DryRunOperation) -> bool {
         true
     }
 
-    fn dry_run(&self, app: AppCtx, account_id: &str) -> Result<Value, PlatformError> {
-        let plan = self.plan_switch(&app, account_id)?;
+    fn dry_run(
+        &self,
+        app: AppCtx,
+        operation: DryRunOperation,
+        account_id: &str,
+        params: Value,
+    ) -> Result<Value, PlatformError> {
+        let plan = self.plan(&app, operation, account_id, &launch_overrides(&params))?;
         serde_json::to_value(plan).map_err(|e| PlatformError::other(e.to_string()))
     }
 }
@@ -2075,12 +3524,70 @@ mod tests {
 
         let err = service.restore_snapshot(&ctx, "a3f0c2d1").unwrap_err();
         assert_eq!(
-            err,
+            err.message,
             "No auth snapshot found for account a3f0c2d1. Add this account through setup first."
         );
         let _ = fs::remove_dir_all(&root);
     }
 
+    /// Writes a snapshot by hand, in plaintext, which restores read the same
+    /// way as the encrypted files a capture writes, so no OS backend is needed.
+    fn plant_snapshot(
+        service: &DescriptorService,
+        ctx: &TempCtx,
+        account_id: &str,
+        files: &[(&str, &[u8])],
+    ) {
+        let dir = service.snapshot_root(ctx, account_id).unwrap();
+        for (relative, content) in files {
+            let path = dir.join(relative);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+    }
+
+    #[test]
+    fn a_snapshot_that_fails_its_manifest_leaves_the_live_session_alone() {
+        let _config = config_guard();
+        let root = scratch("corrupt-snapshot");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        let service = service(&live);
+        seed_live_session(&live, b"signed-in");
+
+        // Plaintext stands in for the encrypted files, which read the same way.
+        let cache_dir = service.snapshot_root(&ctx, "a3f0c2d1").unwrap();
+        fs::create_dir_all(&cache_dir).unwrap();
+        fs::write(cache_dir.join("session.json"), b"trunc").unwrap();
+        let manifest = snapshot_manifest::SnapshotManifest {
+            manifest_version: snapshot_manifest::MANIFEST_VERSION,
+            platform_id: "test".to_string(),
+            descriptor_version: Some(1),
+            captured_at: 0,
+            files: vec![snapshot_manifest::ManifestEntry {
+                path: "session.json".to_string(),
+                size: 10,
+                sha256: "00".repeat(32),
+            }],
+        };
+        fs::write(
+            cache_dir.join(snapshot_manifest::MANIFEST_FILE_NAME),
+            serde_json::to_vec(&manifest).unwrap(),
+        )
+        .unwrap();
+
+        let err = service.restore_snapshot(&ctx, "a3f0c2d1").unwrap_err();
+        assert_eq!(err.kind, crate::error::PlatformErrorKind::SnapshotCorrupt);
+        assert!(
+            err.message
+                .contains("session.json is 5 bytes, captured at 10"),
+            "{}",
+            err.message
+        );
+        assert_eq!(fs::read(live.join("session.json")).unwrap(), b"signed-in");
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn a_live_path_outside_the_roots_never_reaches_the_engine() {
         let root = scratch("sandbox");
@@ -2110,7 +3617,9 @@ mod tests {
         seed_live_session(&live, b"live-session");
 
         let service = service(&live);
-        let plan = service.plan_switch(&ctx, "a3f0c2d1").unwrap();
+        let plan = service
+            .plan_switch(&ctx, "a3f0c2d1", &BTreeMap::new())
+            .unwrap();
 
         assert_eq!(plan.platform_id, "gog");
         assert!(!plan.applied);
@@ -2132,6 +3641,73 @@ mod tests {
         let _ = fs::remove_dir_all(&root);
     }
 
+    /// [`fixture`] with a launcher binary in the live folder, started with
+    /// per-account arguments and environment.
+    fn launch_service(live_root: &Path) -> DescriptorService {
+        let mut value: serde_json::Value = serde_json::from_str(&fixture(live_root)).unwrap();
+        let live = live_root.display().to_string().replace('\\', "/");
+        for profile in value["os"].as_object_mut().unwrap().values_mut() {
+            profile["executable"] = serde_json::json!({
+                "fileName": "launcher",
+                "candidates": [{ "kind": "path", "template": live }]
+            });
+            profile["launch"] = serde_json::json!({
+                "args": ["--lang=${account.lang}", "/game=${account.game}", "--region=${account.region}"],
+                "env": { "ACME_USER": "${account.id}" }
+            });
+        }
+        let descriptor = Descriptor::parse("test", &value.to_string()).unwrap();
+        DescriptorService::new(descriptor, DescriptorOrigin::Embedded)
+    }
+
+    #[test]
+    fn a_switch_plan_launches_with_the_accounts_values_and_the_callers_overrides() {
+        let _config = config_guard();
+        let root = scratch("dry-run-launch");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        fs::create_dir_all(&live).unwrap();
+        fs::write(live.join("launcher"), b"").unwrap();
+        config_bridge::set_launch_values(
+            &ctx,
+            "gog",
+            "a3f0c2d1",
+            &BTreeMap::from([
+                ("lang".to_string(), "fr".to_string()),
+                ("game".to_string(), "witcher3".to_string()),
+            ]),
+        )
+        .unwrap();
+
+        let overrides = BTreeMap::from([("game".to_string(), "cyberpunk".to_string())]);
+        let plan = launch_service(&live)
+            .plan_switch(&ctx, "a3f0c2d1", &overrides)
+            .unwrap();
+
+        let launch = plan
+            .steps
+            .iter()
+            .find(|s| s.action == PlanAction::Launch)
+            .unwrap();
+        assert!(
+            launch
+                .target
+                .ends_with("launcher --lang=fr /game=cyberpunk"),
+            "{}",
+            launch.target
+        );
+        assert_eq!(launch.note, "ACME_USER=a3f0c2d1");
+        // The account stores no region, so that argument is dropped whole.
+        assert!(
+            plan.warnings
+                .iter()
+                .any(|w| w.contains("`--region=${account.region}` would be left out")),
+            "{:?}",
+            plan.warnings
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn dry_run_reports_the_roots_it_would_stay_inside() {
         let _config = config_guard();
@@ -2140,12 +3716,150 @@ mod tests {
         let ctx = TempCtx { root: root.clone() };
         fs::create_dir_all(&live).unwrap();
 
-        let plan = service(&live).plan_switch(&ctx, "a3f0c2d1").unwrap();
+        let plan = service(&live)
+            .plan_switch(&ctx, "a3f0c2d1", &BTreeMap::new())
+            .unwrap();
         assert_eq!(plan.roots.len(), 1);
         assert!(plan.roots[0].to_lowercase().contains("live"));
         let _ = fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn a_switch_plan_asks_the_launcher_to_close_before_terminating_it() {
+        let _config = config_guard();
+        let root = scratch("dry-run-close");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        fs::create_dir_all(&live).unwrap();
+        let mut value: serde_json::Value = serde_json::from_str(&fixture(&live)).unwrap();
+        for profile in value["os"].as_object_mut().unwrap().values_mut() {
+            profile["close"]["strategies"] = serde_json::json!([
+                { "kind": "uri", "uri": "acme://exit", "timeoutMs": 3000 },
+                { "kind": "closeWindow" },
+            ]);
+        }
+        let descriptor = Descriptor::parse("test", &value.to_string()).unwrap();
+        let service = DescriptorService::new(descriptor, DescriptorOrigin::Embedded);
+
+        let plan = service
+            .plan_switch(&ctx, "a3f0c2d1", &BTreeMap::new())
+            .unwrap();
+        let closes: Vec<&PlanStep> = plan
+            .steps
+            .iter()
+            .filter(|s| s.action == PlanAction::Close)
+            .collect();
+        assert_eq!(closes[0].kind, PlanTargetKind::Request);
+        assert_eq!(closes[0].target, "acme://exit");
+        assert!(closes[0].note.contains("3000 ms"), "{}", closes[0].note);
+        assert_eq!(closes[1].target, "main window");
+        assert!(closes[2..]
+            .iter()
+            .all(|s| s.kind == PlanTargetKind::Process && s.note == "terminated if still running"));
+        assert!(closes.len() > 2);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_forget_plan_names_the_list_entry_and_the_snapshot_and_removes_neither() {
+        let _config = config_guard();
+        let root = scratch("dry-run-forget");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        let service = service(&live);
+        let snapshot = service.snapshot_root(&ctx, "aaaa1111").unwrap();
+        fs::create_dir_all(&snapshot).unwrap();
+
+        let plan = service
+            .plan(&ctx, DryRunOperation::Forget, "aaaa1111", &BTreeMap::new())
+            .unwrap();
+        assert_eq!(plan.operation, "forget");
+        assert_eq!(plan.steps[0].kind, PlanTargetKind::Account);
+        assert_eq!(plan.steps[0].note, "not in the list");
+        assert_eq!(plan.steps[1].action, PlanAction::Delete);
+        assert_eq!(plan.steps[1].target, snapshot.display().to_string());
+        assert!(snapshot.exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_setup_plan_lists_what_the_clear_step_would_remove() {
+        let _config = config_guard();
+        let root = scratch("dry-run-setup");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        seed_live_session(&live, b"session");
+
+        let plan = service(&live)
+            .plan(&ctx, DryRunOperation::Setup, "", &BTreeMap::new())
+            .unwrap();
+        let deleted: Vec<&str> = plan
+            .steps
+            .iter()
+            .filter(|s| s.action == PlanAction::Delete)
+            .map(|s| s.target.as_str())
+            .collect();
+        assert_eq!(deleted.len(), 2, "{deleted:?}");
+        assert!(deleted[0].ends_with("session.json"));
+        assert!(deleted[1].ends_with("auth"));
+        assert!(plan.account_id.is_empty());
+
+        // Nobody is signed in, so nothing is captured, and nothing was cleared.
+        assert!(!plan.steps.iter().any(|s| s.action == PlanAction::Capture));
+        assert!(live.join("session.json").exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_setup_plan_adopts_a_signed_in_session_instead_of_clearing_it() {
+        let _config = config_guard();
+        let root = scratch("dry-run-adopt");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        seed_store(&live, SNOWFLAKE, Some("bebou"));
+
+        let service = hook_service(&live);
+        let plan = service
+            .plan(&ctx, DryRunOperation::Setup, "", &BTreeMap::new())
+            .unwrap();
+        assert_eq!(plan.account_id, SNOWFLAKE);
+        assert!(plan
+            .steps
+            .iter()
+            .any(|s| s.action == PlanAction::Capture && s.target.ends_with("leveldb")));
+        assert!(!plan.steps.iter().any(|s| s.action == PlanAction::Delete));
+        assert!(!service.snapshot_root(&ctx, SNOWFLAKE).unwrap().exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_capture_plan_captures_only_the_account_signed_in() {
+        let _config = config_guard();
+        let root = scratch("dry-run-capture");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        let service = service(&live);
+        seed_live_session(&live, b"session");
+
+        let plan = service
+            .plan(&ctx, DryRunOperation::Capture, "", &BTreeMap::new())
+            .unwrap();
+        assert!(plan.steps.is_empty());
+        assert!(plan.warnings[0].contains("No account is signed in"));
+
+        config_bridge::set_current_account(&ctx, "gog", "aaaa1111").unwrap();
+        let plan = service
+            .plan(&ctx, DryRunOperation::Capture, "", &BTreeMap::new())
+            .unwrap();
+        let snapshot = service.snapshot_root(&ctx, "aaaa1111").unwrap();
+        assert_eq!(plan.account_id, "aaaa1111");
+        assert_eq!(plan.steps.len(), 2);
+        assert!(plan.steps.iter().all(|s| s.action == PlanAction::Capture
+            && s.snapshot.starts_with(&*snapshot.to_string_lossy())));
+        assert!(!snapshot.exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
     // The tests below capture a snapshot, which encrypts through the OS
     // backend. That is DPAPI on Windows and always available; elsewhere it is
     // the login keyring, which a headless build has no way to reach. Windows
@@ -2238,6 +3952,145 @@ mod tests {
         let _ = fs::remove_dir_all(&root);
     }
 
+    /// [`fixture`] plus a settings file of which only the token is session.
+    fn keys_fixture(live_root: &Path) -> String {
+        let live = live_root.display().to_string().replace('\\', "/");
+        fixture(live_root).replace(
+            r#""directories": ["#,
+            &format!(
+                r#""keys": [
+                      {{ "live": "{live}/settings.json", "snapshot": "settings-keys",
+                         "select": {{ "kind": "jsonPointer", "pointers": ["/auth/token"] }},
+                         "clearOnSetup": true }}
+                    ],
+                    "directories": ["#
+            ),
+        )
+    }
+
+    fn keys_service(live_root: &Path) -> DescriptorService {
+        let descriptor = Descriptor::parse("test.json", &keys_fixture(live_root)).unwrap();
+        DescriptorService::new(descriptor, DescriptorOrigin::Embedded)
+    }
+
+    fn write_settings(live_root: &Path, token: &str, width: u32) {
+        fs::create_dir_all(live_root).unwrap();
+        fs::write(
+            live_root.join("settings.json"),
+            format!(r#"{{"auth":{{"token":"{token}"}},"window":{{"width":{width}}}}}"#),
+        )
+        .unwrap();
+    }
+
+    fn read_settings(live_root: &Path) -> Value {
+        serde_json::from_slice(&fs::read(live_root.join("settings.json")).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn clearing_the_live_state_removes_only_the_selected_keys() {
+        let _config = config_guard();
+        let root = scratch("clear-keys");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        write_settings(&live, "secret", 1280);
+
+        keys_service(&live).clear_live_state(&ctx).unwrap();
+
+        let settings = read_settings(&live);
+        assert!(settings["auth"].get("token").is_none());
+        assert_eq!(settings["window"]["width"], 1280);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn dry_run_names_each_selected_key_after_its_file() {
+        let _config = config_guard();
+        let root = scratch("dry-run-keys");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        write_settings(&live, "secret", 1280);
+        let before = fs::read(live.join("settings.json")).unwrap();
+
+        let plan = keys_service(&live)
+            .plan_switch(&ctx, "a3f0c2d1", &BTreeMap::new())
+            .unwrap();
+
+        let step = plan
+            .steps
+            .iter()
+            .find(|s| s.kind == PlanTargetKind::FileKey && s.action == PlanAction::Restore)
+            .expect("a restore step for the key");
+        assert!(
+            step.target.ends_with("settings.json#/auth/token"),
+            "{}",
+            step.target
+        );
+        assert_eq!(step.note, "no snapshot, skipped");
+        assert_eq!(fs::read(live.join("settings.json")).unwrap(), before);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_key_item_carries_the_session_keys_and_leaves_the_rest_of_the_file() {
+        let _config = config_guard();
+        let root = scratch("keys-round-trip");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        let service = keys_service(&live);
+        plant_snapshot(
+            &service,
+            &ctx,
+            "aaaa1111",
+            &[(
+                "settings-keys",
+                br#"{"present":{"/auth/token":"token-one"},"absent":[]}"#,
+            )],
+        );
+
+        // The second account signs in and the user resizes the window.
+        write_settings(&live, "token-two", 1920);
+        let before = fs::read_to_string(live.join("settings.json")).unwrap();
+        service.restore_snapshot(&ctx, "aaaa1111").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(live.join("settings.json")).unwrap(),
+            before.replace("token-two", "token-one")
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn a_key_snapshot_holds_only_the_selected_keys_and_never_in_plaintext() {
+        let _config = config_guard();
+        let root = scratch("keys-capture");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        let service = keys_service(&live);
+
+        write_settings(&live, "token-one", 800);
+        service.save_snapshot(&ctx, "aaaa1111").unwrap();
+        let snapshot = service
+            .snapshot_root(&ctx, "aaaa1111")
+            .unwrap()
+            .join("settings-keys");
+        assert!(!String::from_utf8_lossy(&fs::read(&snapshot).unwrap()).contains("token-one"));
+
+        let captured: keys::Captured =
+            serde_json::from_slice(&read_decrypted_bytes(&snapshot).unwrap()).unwrap();
+        assert_eq!(
+            captured.present.into_iter().collect::<Vec<_>>(),
+            vec![("/auth/token".to_string(), Value::from("token-one"))]
+        );
+
+        write_settings(&live, "token-two", 1920);
+        service.restore_snapshot(&ctx, "aaaa1111").unwrap();
+        let settings = read_settings(&live);
+        assert_eq!(settings["auth"]["token"], "token-one");
+        assert_eq!(settings["window"]["width"], 1920);
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[cfg(windows)]
     #[test]
     fn forgetting_an_account_removes_its_snapshot_directory() {
@@ -2415,6 +4268,233 @@ mod tests {
         let _ = fs::remove_dir_all(&root);
     }
 
+    /// [`log_fixture`] with the id read from `source` instead of the log, and
+    /// a numeric id format.
+    fn structured_service(live_root: &Path, source: Value) -> DescriptorService {
+        let mut value: Value = serde_json::from_str(&log_fixture(live_root)).unwrap();
+        for profile in value["os"].as_object_mut().unwrap().values_mut() {
+            profile["identity"]["source"] = source.clone();
+            profile["identity"]["format"] = serde_json::json!({
+                "charset": "digits",
+                "maxLength": 20
+            });
+        }
+        let descriptor = Descriptor::parse("test", &value.to_string()).unwrap();
+        DescriptorService::new(descriptor, DescriptorOrigin::Embedded)
+    }
+
+    fn read_detail(service: &DescriptorService, ctx: &TempCtx) -> Option<HookIdentity> {
+        service.read_identity_detail(ctx, &service.runtime(ctx).unwrap())
+    }
+
+    /// [`structured_service`] declaring account `fields`, and a `labelFormat`.
+    fn fielded_service(
+        live_root: &Path,
+        source: Value,
+        fields: Value,
+        label_format: &str,
+    ) -> DescriptorService {
+        let base = structured_service(live_root, source);
+        let mut value = serde_json::to_value(base.descriptor()).unwrap();
+        for profile in value["os"].as_object_mut().unwrap().values_mut() {
+            profile["identity"]["fields"] = fields.clone();
+            profile["identity"]["labelFormat"] = serde_json::json!(label_format);
+        }
+        let descriptor = Descriptor::parse("test", &value.to_string()).unwrap();
+        DescriptorService::new(descriptor, DescriptorOrigin::Embedded)
+    }
+
+    #[test]
+    fn a_json_settings_file_gives_the_id_and_the_name_beside_it() {
+        let _config = config_guard();
+        let root = scratch("json-identity");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        fs::create_dir_all(&live).unwrap();
+        fs::write(
+            live.join("settings.json"),
+            r#"{ "account": { "id": 4815162342, "profile": { "name": "  Hugo " } } }"#,
+        )
+        .unwrap();
+        let service = structured_service(
+            &live,
+            serde_json::json!({
+                "kind": "jsonPointer",
+                "path": format!("{}/settings.json", live.display()).replace('\\', "/"),
+                "pointer": "/account/id",
+                "displayNamePointer": "/account/profile/name"
+            }),
+        );
+
+        let found = read_detail(&service, &ctx).unwrap();
+
+        assert_eq!(found.id, "4815162342");
+        assert_eq!(found.display_name.as_deref(), Some("Hugo"));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn an_ini_value_outside_the_charset_is_no_identity() {
+        let _config = config_guard();
+        let root = scratch("ini-identity");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        fs::create_dir_all(&live).unwrap();
+        let source = serde_json::json!({
+            "kind": "iniKey",
+            "path": format!("{}/user.ini", live.display()).replace('\\', "/"),
+            "section": "Account",
+            "key": "UserId"
+        });
+        let service = structured_service(&live, source);
+
+        fs::write(live.join("user.ini"), "[Account]\nUserId = 12345\n").unwrap();
+        assert_eq!(service.read_identity(&ctx).as_deref(), Some("12345"));
+
+        fs::write(live.join("user.ini"), "[Account]\nUserId = ../12345\n").unwrap();
+        assert_eq!(service.read_identity(&ctx), None);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_sqlite_identity_is_the_first_row_of_its_query_and_leaves_the_database_alone() {
+        let _config = config_guard();
+        let root = scratch("sqlite-identity");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        fs::create_dir_all(&live).unwrap();
+        let db = live.join("cache.db");
+        {
+            let connection = rusqlite::Connection::open(&db).unwrap();
+            connection
+                .execute_batch(
+                    "CREATE TABLE users (id INTEGER, name TEXT, seen INTEGER);
+                     INSERT INTO users VALUES (111, 'Old', 1), (222, 'Current', 2);",
+                )
+                .unwrap();
+        }
+        let before = fs::read(&db).unwrap();
+        let source = |query: &str| {
+            serde_json::json!({
+                "kind": "sqliteQuery",
+                "path": format!("{}/cache.db", live.display()).replace('\\', "/"),
+                "query": query
+            })
+        };
+
+        let service = structured_service(
+            &live,
+            source("SELECT id, name FROM users ORDER BY seen DESC LIMIT 1"),
+        );
+        let found = read_detail(&service, &ctx).unwrap();
+        assert_eq!(found.id, "222");
+        assert_eq!(found.display_name.as_deref(), Some("Current"));
+
+        let id_only = structured_service(&live, source("SELECT id FROM users ORDER BY seen"));
+        let found = read_detail(&id_only, &ctx).unwrap();
+        assert_eq!(found.id, "111");
+        assert_eq!(found.display_name, None);
+
+        assert_eq!(fs::read(&db).unwrap(), before);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn declared_fields_are_read_typed_kept_and_listed() {
+        let _config = config_guard();
+        let root = scratch("fields");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        fs::create_dir_all(&live).unwrap();
+        fs::write(
+            live.join("settings.json"),
+            r#"{ "account": { "id": 4815162342, "riotId": { "name": "Hugo", "tag": " EUW " },
+                 "mail": "not an address", "level": 42 } }"#,
+        )
+        .unwrap();
+        let service = fielded_service(
+            &live,
+            serde_json::json!({
+                "kind": "jsonPointer",
+                "path": format!("{}/settings.json", live.display()).replace('\\', "/"),
+                "pointer": "/account/id"
+            }),
+            serde_json::json!([
+                { "name": "gameName", "label": "Name", "from": "/account/riotId/name" },
+                { "name": "tagLine", "label": "Tag line", "from": "/account/riotId/tag" },
+                { "name": "email", "label": "Email", "type": "email", "from": "/account/mail" },
+                { "name": "level", "label": "Level", "type": "number", "from": "/account/level" }
+            ]),
+            "${account.gameName}#${account.tagLine}",
+        );
+
+        let found = read_detail(&service, &ctx).unwrap();
+        // The address is not one, so it is dropped rather than listed.
+        assert_eq!(
+            found.fields,
+            BTreeMap::from([
+                ("gameName".to_string(), "Hugo".to_string()),
+                ("level".to_string(), "42".to_string()),
+                ("tagLine".to_string(), "EUW".to_string()),
+            ])
+        );
+
+        service.remember_fields(&ctx, &found);
+        let label_of = |service: &DescriptorService| {
+            let accounts = service.read_accounts(&ctx).unwrap();
+            let account = accounts
+                .iter()
+                .find(|account| account.account_id == "4815162342")
+                .unwrap();
+            assert_eq!(account.fields["tagLine"], "EUW");
+            account.label.clone()
+        };
+        assert_eq!(label_of(&service), "Hugo#EUW");
+
+        // The user's own label wins over the format, and launch arguments
+        // read the fields like any other account value.
+        config_bridge::set_label(&ctx, service.id(), "4815162342", "Main").unwrap();
+        assert_eq!(label_of(&service), "Main");
+        let values = service.launch_values(&ctx, "4815162342", &BTreeMap::new());
+        assert_eq!(values["tagLine"], "EUW");
+        assert_eq!(values["label"], "Main");
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_sqlite_field_is_the_column_its_from_names() {
+        let _config = config_guard();
+        let root = scratch("sqlite-fields");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        fs::create_dir_all(&live).unwrap();
+        let db = live.join("cache.db");
+        rusqlite::Connection::open(&db)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE users (id INTEGER, name TEXT, region TEXT);
+                 INSERT INTO users VALUES (222, 'Current', 'eu-west');",
+            )
+            .unwrap();
+        let service = fielded_service(
+            &live,
+            serde_json::json!({
+                "kind": "sqliteQuery",
+                "path": format!("{}/cache.db", live.display()).replace('\\', "/"),
+                "query": "SELECT id, name, region AS zone FROM users"
+            }),
+            serde_json::json!([{ "name": "region", "label": "Region", "from": "zone" }]),
+            "",
+        );
+
+        let found = read_detail(&service, &ctx).unwrap();
+        assert_eq!(
+            found.fields.get("region").map(String::as_str),
+            Some("eu-west")
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn an_id_written_in_the_launchers_own_case_reads_back_as_one_account() {
         let _config = config_guard();
@@ -2491,6 +4571,69 @@ mod tests {
         let _ = fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn accounts_a_launcher_remembers_in_a_json_list_are_named_and_not_captured() {
+        let _config = config_guard();
+        let root = scratch("json-discovery");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        fs::create_dir_all(&live).unwrap();
+        fs::write(
+            live.join("recent.json"),
+            serde_json::json!({
+                "recent": [
+                    { "userId": UUID_ONE.to_uppercase(), "name": "Hugo" },
+                    { "userId": UUID_TWO },
+                    { "userId": "not-an-id", "name": "Broken" },
+                    { "name": "half-written" }
+                ]
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let mut value: Value = serde_json::from_str(&log_fixture(&live)).unwrap();
+        let live_path = live.display().to_string().replace('\\', "/");
+        for profile in value["os"].as_object_mut().unwrap().values_mut() {
+            profile["identity"]["discovery"] = serde_json::json!([{
+                "kind": "jsonArray",
+                "path": format!("{live_path}/recent.json"),
+                "pointer": "/recent",
+                "idField": "userId",
+                "displayNameField": "name"
+            }]);
+        }
+        let descriptor = Descriptor::parse("test", &value.to_string()).unwrap();
+        let service = DescriptorService::new(descriptor, DescriptorOrigin::Embedded);
+
+        let mut accounts: Vec<(String, String, bool)> = service
+            .read_accounts(&ctx)
+            .unwrap()
+            .into_iter()
+            .map(|a| (a.account_id, a.label, a.snapshot_saved))
+            .collect();
+        accounts.sort();
+        // The id is normalised like any other, and a malformed one or an
+        // element without one is left out.
+        assert_eq!(
+            accounts,
+            vec![
+                (UUID_ONE.to_string(), "Hugo".to_string(), false),
+                (UUID_TWO.to_string(), String::new(), false),
+            ]
+        );
+
+        service.forget(&ctx, UUID_ONE).unwrap();
+        let after: Vec<String> = service
+            .read_accounts(&ctx)
+            .unwrap()
+            .into_iter()
+            .map(|a| a.account_id)
+            .collect();
+        assert_eq!(after, vec![UUID_TWO.to_string()]);
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn a_path_with_several_candidates_uses_the_one_that_exists() {
         let _config = config_guard();
@@ -2506,7 +4649,9 @@ mod tests {
         )
         .unwrap();
 
-        let plan = log_service(&live).plan_switch(&ctx, UUID_ONE).unwrap();
+        let plan = log_service(&live)
+            .plan_switch(&ctx, UUID_ONE, &BTreeMap::new())
+            .unwrap();
         let restored: Vec<&str> = plan
             .steps
             .iter()
@@ -2530,7 +4675,9 @@ mod tests {
         let ctx = TempCtx { root: root.clone() };
         fs::create_dir_all(&live).unwrap();
 
-        let plan = log_service(&live).plan_switch(&ctx, UUID_ONE).unwrap();
+        let plan = log_service(&live)
+            .plan_switch(&ctx, UUID_ONE, &BTreeMap::new())
+            .unwrap();
         let restored: Vec<&str> = plan
             .steps
             .iter()
@@ -2589,6 +4736,148 @@ mod tests {
         let _ = fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn a_rollback_puts_back_exactly_the_live_state_it_staged() {
+        let _config = config_guard();
+        let root = scratch("rollback");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        let service = service(&live);
+        fs::create_dir_all(live.join("auth").join("nested")).unwrap();
+        fs::write(
+            live.join("auth").join("nested").join("token.bin"),
+            b"outgoing",
+        )
+        .unwrap();
+        config_bridge::set_current_account(&ctx, "gog", "aaaa1111").unwrap();
+
+        let rollback = service
+            .stage_live_state(&ctx, Some("aaaa1111".to_string()))
+            .unwrap();
+        assert!(rollback
+            .dir
+            .starts_with(crate::storage::switch_rollback_dir(&ctx).unwrap()));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&rollback.dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700, "the copies are the user's alone");
+        }
+
+        // A switch that got halfway: a session file that was absent now
+        // exists, the folder holds the incoming account's files, and the
+        // marker names the target.
+        fs::write(live.join("session.json"), b"incoming").unwrap();
+        fs::remove_dir_all(live.join("auth")).unwrap();
+        fs::create_dir_all(live.join("auth")).unwrap();
+        fs::write(live.join("auth").join("other.bin"), b"incoming").unwrap();
+        config_bridge::set_current_account(&ctx, "gog", "bbbb2222").unwrap();
+
+        service.roll_back_live_state(&ctx, &rollback);
+        let staged_dir = rollback.dir.clone();
+        rollback.discard();
+
+        assert!(!live.join("session.json").exists());
+        assert!(!live.join("auth").join("other.bin").exists());
+        assert_eq!(
+            fs::read(live.join("auth").join("nested").join("token.bin")).unwrap(),
+            b"outgoing"
+        );
+        assert_eq!(
+            config_bridge::current_account(&ctx, "gog").as_deref(),
+            Some("aaaa1111")
+        );
+        assert!(!staged_dir.exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_switch_that_fails_halfway_leaves_the_outgoing_session_in_place() {
+        let _config = config_guard();
+        let root = scratch("switch-rollback");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        let service = service(&live);
+
+        // The folder snapshot is a file, so the restore fails after the
+        // session file has already been moved into place.
+        plant_snapshot(
+            &service,
+            &ctx,
+            "bbbb2222",
+            &[("session.json", b"incoming"), ("auth", b"not a folder")],
+        );
+
+        seed_live_session(&live, b"outgoing");
+        assert!(service
+            .switch(&ctx, "bbbb2222", &BTreeMap::new(), None)
+            .is_err());
+
+        assert_eq!(fs::read(live.join("session.json")).unwrap(), b"outgoing");
+        assert_eq!(
+            fs::read(live.join("auth").join("nested").join("token.bin")).unwrap(),
+            b"outgoing"
+        );
+        assert_eq!(
+            config_bridge::current_account(&ctx, "gog").as_deref(),
+            Some("")
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_rollback_left_by_an_interrupted_switch_is_swept() {
+        let _config = config_guard();
+        let root = scratch("rollback-sweep");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        let service = service(&live);
+        seed_live_session(&live, b"outgoing");
+
+        // The process dies before the switch discards its copy.
+        let staged_dir = service.stage_live_state(&ctx, None).unwrap().dir;
+        assert!(staged_dir.exists());
+
+        assert_eq!(sweep_switch_rollbacks(&ctx), 1);
+        assert!(!staged_dir.exists());
+        assert_eq!(sweep_switch_rollbacks(&ctx), 0);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_switch_whose_launch_fails_leaves_the_account_unused() {
+        let _config = config_guard();
+        let root = scratch("launch-fails");
+        let live = root.join("live");
+        let ctx = TempCtx { root: root.clone() };
+        // No launcher binary in the live folder, so the launch cannot start.
+        let service = launch_service(&live);
+        config_bridge::touch_account(&ctx, "gog", "bbbb2222", 1234).unwrap();
+        plant_snapshot(
+            &service,
+            &ctx,
+            "bbbb2222",
+            &[
+                ("session.json", b"incoming"),
+                ("auth/nested/token.bin", b"incoming"),
+            ],
+        );
+
+        seed_live_session(&live, b"outgoing");
+        assert!(service
+            .switch(&ctx, "bbbb2222", &BTreeMap::new(), None)
+            .is_err());
+
+        assert_eq!(fs::read(live.join("session.json")).unwrap(), b"outgoing");
+        let accounts = config_bridge::accounts(&ctx, "gog");
+        let incoming = accounts
+            .iter()
+            .find(|account| account.account_id == "bbbb2222")
+            .unwrap();
+        assert_eq!(incoming.last_used_at, Some(1234));
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn non_empty_check_walks_into_subdirectories_only_when_asked() {
         let root = scratch("non-empty");
@@ -2712,7 +5001,7 @@ mod tests {
 
         let service = hook_service(&live);
         let runtime = service.runtime(&ctx).unwrap();
-        let found = service.read_identity_detail(&runtime).unwrap();
+        let found = service.read_identity_detail(&ctx, &runtime).unwrap();
         assert_eq!(found.id, SNOWFLAKE);
         assert_eq!(found.display_name.as_deref(), Some("bebou"));
         let _ = fs::remove_dir_all(&root);
@@ -2845,6 +5134,85 @@ mod tests {
         let _ = fs::remove_dir_all(&root);
     }
 
+    /// Modelled on Riot after the move onto the engine: the id is `riot`, so
+    /// the profiles the old module stored are its accounts, and the puuid is
+    /// read from a file rather than the client's local API.
+    fn riot_like_service(live_root: &Path) -> DescriptorService {
+        let live = live_root.display().to_string().replace('\\', "/");
+        let mut value: Value = serde_json::from_str(&log_fixture(live_root)).unwrap();
+        value["id"] = Value::from("riot");
+        for profile in value["os"].as_object_mut().unwrap().values_mut() {
+            profile["identity"] = serde_json::json!({
+                "source": {
+                    "kind": "jsonPointer",
+                    "path": format!("{live}/settings.json"),
+                    "pointer": "/sub"
+                },
+                "format": { "charset": "slug", "maxLength": 128 },
+                "current": "config"
+            });
+            profile["state"] = serde_json::json!({
+                "files": [{
+                    "live": format!("{live}/user.dat"),
+                    "snapshot": "user.dat",
+                    "snapshotMarker": true,
+                    "clearOnSetup": true
+                }]
+            });
+            profile["setup"] = serde_json::json!({
+                "trigger": [{ "kind": "newIdentity" }],
+                "adoptSignedIn": true
+            });
+        }
+        let descriptor = Descriptor::parse("test", &value.to_string()).unwrap();
+        DescriptorService::new(descriptor, DescriptorOrigin::Embedded)
+    }
+
+    #[test]
+    fn signing_back_into_a_migrated_riot_profile_finds_it_instead_of_adding_it() {
+        let _config = config_guard();
+        let root = scratch("riot-migrated");
+        let live = root.join("live");
+        let ctx: AppCtx = Arc::new(TempCtx { root: root.clone() });
+        crate::config::update_config(&*ctx, |cfg| {
+            cfg.riot.profiles.push(crate::config::RiotProfileConfig {
+                id: "riot-profile-1".into(),
+                label: "Main".into(),
+                account_puuid: "puuid-one".into(),
+                snapshot_state: "ready".into(),
+                ..crate::config::RiotProfileConfig::default()
+            });
+        })
+        .unwrap();
+        let sign_in = || {
+            fs::create_dir_all(&live).unwrap();
+            fs::write(live.join("settings.json"), r#"{"sub":"puuid-one"}"#).unwrap();
+            fs::write(live.join("user.dat"), b"session").unwrap();
+        };
+        sign_in();
+
+        let service = riot_like_service(&live);
+        assert_eq!(
+            service.read_identity(&*ctx).as_deref(),
+            Some("riot-profile-1")
+        );
+
+        // The session already belongs to the profile, so it is not adopted as
+        // a new account; and signing into it again during setup is not one.
+        let status = service.begin_setup(ctx.clone(), Value::Null).unwrap();
+        assert_eq!(status.state, "waiting_for_client");
+        sign_in();
+        let status = service
+            .get_setup_status(ctx.clone(), &status.setup_id)
+            .unwrap();
+        assert_ne!(status.state, "ready");
+
+        let profiles = crate::config::load_config(&*ctx).riot.profiles;
+        assert_eq!(profiles.len(), 1, "{profiles:?}");
+        assert_eq!(profiles[0].id, "riot-profile-1");
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn a_hand_off_argument_is_passed_only_to_the_binary_that_understands_it() {
         let launch: super::super::schema::Launch = serde_json::from_value(serde_json::json!({
@@ -2855,4 +5223,82 @@ mod tests {
         assert_eq!(launch.args_for(Path::new("C:/App/Update.exe")).len(), 2);
         assert!(launch.args_for(Path::new("C:/App/Client.exe")).is_empty());
     }
+
+    /// A Windows launcher run through Wine: the profile is written for
+    /// Windows, and only the `wine` block says where that Windows lives.
+    #[cfg(not(windows))]
+    fn wine_fixture() -> String {
+        let profile = r#"{
+          "roots": {
+            "files": ["${LOCALAPPDATA}/Acme"],
+            "registry": [{ "root": "HKCU", "key": "Software\\Acme" }]
+          },
+          "detect": { "executableResolves": true },
+          "executable": {
+            "fileName": "Acme.exe",
+            "candidates": [{ "kind": "registry", "root": "HKCU", "key": "Software\\Acme", "value": "InstallPath" }]
+          },
+          "identity": {
+            "source": { "kind": "registry", "root": "HKCU", "key": "Software\\Acme", "value": "UserId" },
+            "format": { "charset": "digits", "maxLength": 16 },
+            "current": "identity"
+          },
+          "state": {
+            "files": [{ "live": "${LOCALAPPDATA}/Acme/session.json", "snapshot": "session.json" }],
+            "registryValues": [{ "root": "HKCU", "key": "Software\\Acme", "value": "Token", "snapshot": "token.txt" }]
+          },
+          "close": { "processes": ["Acme.exe"] },
+          "launch": {},
+          "wine": { "prefix": "${WINEPREFIX}" }
+        }"#;
+        format!(
+            r#"{{ "id": "acme", "schemaVersion": 1, "name": "Acme Launcher", "shortName": "Acme",
+                 "os": {{ "linux": {profile}, "macos": {profile} }} }}"#
+        )
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn a_launcher_in_a_wine_prefix_is_read_through_its_drive_c_and_hives() {
+        let _config = config_guard();
+        let root = scratch("wine");
+        let prefix = root.join("prefix");
+        let ctx = TempCtx { root: root.clone() };
+        fs::create_dir_all(prefix.join("drive_c/users/demo")).unwrap();
+        fs::create_dir_all(prefix.join("drive_c/Games/Acme")).unwrap();
+        fs::write(prefix.join("drive_c/Games/Acme/Acme.exe"), b"MZ").unwrap();
+        fs::write(
+            prefix.join("user.reg"),
+            "WINE REGISTRY Version 2\n\n[Software\\\\Acme] 1700000000\n\
+             \"InstallPath\"=\"C:\\\\Games\\\\Acme\"\n\"UserId\"=\"424242\"\n",
+        )
+        .unwrap();
+
+        let descriptor = Descriptor::parse("test", &wine_fixture()).unwrap();
+        let service = DescriptorService::new(descriptor, DescriptorOrigin::Embedded)
+            .with_environment([
+                ("WINEPREFIX", prefix.display().to_string()),
+                ("USER", "demo".to_string()),
+            ]);
+
+        assert_eq!(service.read_identity(&ctx).as_deref(), Some("424242"));
+        assert_eq!(
+            service.resolve_executable(&ctx).unwrap(),
+            prefix.join("drive_c/Games/Acme/Acme.exe")
+        );
+
+        let plan = service
+            .plan_switch(&ctx, "424242", &BTreeMap::new())
+            .unwrap();
+        let session = prefix.join("drive_c/users/demo/AppData/Local/Acme/session.json");
+        assert!(plan
+            .steps
+            .iter()
+            .any(|s| s.action == PlanAction::Restore && s.target == session.display().to_string()));
+        assert!(plan
+            .steps
+            .iter()
+            .any(|s| s.kind == PlanTargetKind::RegistryValue && s.target.contains("user.reg")));
+        let _ = fs::remove_dir_all(&root);
+    }
 }
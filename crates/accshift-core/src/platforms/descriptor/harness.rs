@@ -0,0 +1,1313 @@
+//! Running a descriptor end to end against a scratch folder.
+//!
+//! A dry run shows what a switch would touch; this proves it works. Every
+//! placeholder a profile names is pointed at its own folder under a throwaway
+//! root, registry values land in text hives there, and the launcher is never
+//! started or closed. Two fake sessions are seeded the way a sign-in would
+//! leave them, then the real engine adds both through setup, switches between
+//! them and forgets one.
+//!
+//! Nothing here reimplements an operation: every step goes through
+//! [`PlatformService`] on an ordinary [`DescriptorService`]. The harness only
+//! writes what a launcher would have written and reads back what the engine
+//! left, so a descriptor that passes here fails on a real machine only where
+//! the launcher itself disagrees with it.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::platforms::PlatformService;
+use crate::snapshot_crypto::ENCRYPTED_HEADER;
+use crate::{AppContext, AppCtx};
+
+use super::engine::{DescriptorOrigin, DescriptorService, Runtime};
+use super::keys::{self, Captured};
+use super::paths::{lexically_normalise, PathResolver};
+use super::reg;
+use super::schema::{
+    Condition, CurrentSource, Descriptor, DescriptorError, ExecutableCandidate, IdFormat,
+    IdentitySource, IniKey, KeySelect, Os, OsProfile, PathSpec, PathTemplate, RegistryHive,
+    INSTALL_DIR,
+};
+use super::wine;
+
+/// Written into every seeded value, so a snapshot holding any of them in
+/// plaintext is found by a byte search.
+const TOKEN: &str = "accshift-harness";
+
+/// Enough for a condition with no delay to be seen on the first few polls.
+const SETUP_GRACE_MS: u64 = 3_000;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The steps, in the order they run. A failed step skips every later one.
+const STEPS: [&str; 8] = [
+    "sandbox",
+    "setup-a",
+    "encryption",
+    "setup-b",
+    "switch-a",
+    "switch-b",
+    "forget",
+    "confined",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct HarnessOptions {
+    /// The profile to run. Defaults to this machine's OS; any other profile
+    /// runs just as well, since nothing it names is real.
+    pub os: Option<Os>,
+    /// Leave the scratch folder on disk to look at afterwards.
+    pub keep: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Pass,
+    Fail,
+    Skip,
+}
+
+impl Outcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Pass => "pass",
+            Outcome::Fail => "fail",
+            Outcome::Skip => "skip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarnessCheck {
+    pub step: String,
+    pub outcome: Outcome,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarnessReport {
+    pub platform: String,
+    pub os: Option<Os>,
+    /// True when no step failed. Skipped steps do not count against it.
+    pub ok: bool,
+    /// The scratch folder when it was kept, empty once it is removed.
+    pub root: String,
+    pub checks: Vec<HarnessCheck>,
+}
+
+/// Reads a descriptor file and runs it. A file that does not load is refused
+/// the same way the descriptor folder refuses it.
+pub fn run_file(path: &Path, options: &HarnessOptions) -> Result<HarnessReport, DescriptorError> {
+    let source = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    let body = fs::read_to_string(path)
+        .map_err(|e| DescriptorError::new(&source, "", format!("could not be read: {e}")))?;
+    let descriptor = Descriptor::parse(&source, &body)?;
+    Ok(run(&descriptor, options))
+}
+
+/// Runs every step against a fresh scratch folder and reports each one.
+pub fn run(descriptor: &Descriptor, options: &HarnessOptions) -> HarnessReport {
+    let os = options.os.or_else(Os::current);
+    let mut report = HarnessReport {
+        platform: descriptor.id.clone(),
+        os,
+        ok: false,
+        root: String::new(),
+        checks: Vec::new(),
+    };
+    let Some(profile) = os.and_then(|os| descriptor.os.get(&os)) else {
+        let described: Vec<&str> = descriptor.os.keys().map(Os::as_str).collect();
+        report.checks.push(HarnessCheck {
+            step: "profile".into(),
+            outcome: Outcome::Fail,
+            detail: format!(
+                "`{}` describes {} and not {}; pick one with --os",
+                descriptor.id,
+                described.join(", "),
+                os.map(|os| os.as_str()).unwrap_or("this operating system")
+            ),
+        });
+        return report;
+    };
+    let os = os.expect("a profile was found for it");
+
+    let root = std::env::temp_dir().join(format!(
+        "accshift-harness-{}-{}",
+        descriptor.id,
+        Uuid::new_v4().simple()
+    ));
+    let world = root.join("world");
+    let service = DescriptorService::new(descriptor.clone(), DescriptorOrigin::Embedded)
+        .with_environment(environment(profile, &world))
+        .offline(os, wine::Prefix::new(root.join("registry"), "accshift"));
+    let app: AppCtx = Arc::new(ScratchContext {
+        root: root.join("app"),
+    });
+
+    let mut harness = Harness {
+        service: &service,
+        app,
+        world,
+        ids: Vec::new(),
+        expected: Vec::new(),
+        outside_roots: Vec::new(),
+        unseeded: HashSet::new(),
+    };
+    match harness.prepare(&root) {
+        Ok(()) => harness.run_steps(&mut report.checks),
+        Err(reason) => report.checks.push(HarnessCheck {
+            step: "prepare".into(),
+            outcome: Outcome::Fail,
+            detail: reason,
+        }),
+    }
+    harness.clean_up();
+
+    if options.keep {
+        report.root = root.display().to_string();
+    } else {
+        let _ = fs::remove_dir_all(&root);
+    }
+    report.ok = !report
+        .checks
+        .iter()
+        .any(|check| check.outcome == Outcome::Fail);
+    report
+}
+
+/// Every placeholder the profile names, pointed at a folder of its own under
+/// the scratch world. `${installDir}` is left out: it is wherever the seeded
+/// launcher binary ends up.
+fn environment(profile: &OsProfile, world: &Path) -> Vec<(String, String)> {
+    let mut names = Vec::new();
+    if let Ok(value) = serde_json::to_value(profile) {
+        collect_placeholders(&value, &mut names);
+    }
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter(|name| name != INSTALL_DIR)
+        .map(|name| {
+            let dir = world.join("env").join(&name);
+            (name, dir.to_string_lossy().into_owned())
+        })
+        .collect()
+}
+
+fn collect_placeholders(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::String(text) => out.extend(PathTemplate::new(text.as_str()).placeholders()),
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_placeholders(item, out)),
+        Value::Object(map) => map
+            .values()
+            .for_each(|item| collect_placeholders(item, out)),
+        _ => {}
+    }
+}
+
+/// The id the harness signs account `index` in under: the charset's own
+/// sample with its last character changed, so both accounts are valid and
+/// distinct whatever the format.
+fn account_id(format: &IdFormat, index: usize) -> String {
+    let length = format.max_length.min(16).max(format.min_length).max(1);
+    let mut id = format.charset.sample(length);
+    id.pop();
+    id.push(char::from(b'1' + index as u8));
+    id
+}
+
+fn account_letter(index: usize) -> char {
+    char::from(b'a' + index as u8)
+}
+
+fn seeded_value(index: usize, what: &str) -> String {
+    format!("{TOKEN}-{}-{what}", account_letter(index))
+}
+
+/// The longest `sinceStart` any setup condition waits for.
+fn longest_wait(conditions: &[Condition]) -> u64 {
+    conditions
+        .iter()
+        .map(|condition| match condition {
+            Condition::SinceStart { ms } => *ms,
+            Condition::AnyOf { conditions } => longest_wait(conditions),
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn condition_paths<'a>(conditions: &'a [Condition], out: &mut Vec<(&'a PathSpec, bool)>) {
+    for condition in conditions {
+        match condition {
+            Condition::PathNonEmpty { path, recursive } => out.push((path, *recursive)),
+            Condition::PathFresh { path, .. } => out.push((path, false)),
+            Condition::AnyOf { conditions } => condition_paths(conditions, out),
+            _ => {}
+        }
+    }
+}
+
+/// A key no descriptor selects, written next to the selected ones. It has to
+/// survive every restore untouched: a key item carries its keys, not the file.
+fn untouched_key(select: &KeySelect) -> KeySelect {
+    match select {
+        KeySelect::JsonPointer { .. } => KeySelect::JsonPointer {
+            pointers: vec!["/accshiftHarness".into()],
+        },
+        KeySelect::IniKey { .. } => KeySelect::IniKey {
+            keys: vec![IniKey {
+                section: "accshift-harness".into(),
+                key: "untouched".into(),
+            }],
+        },
+        KeySelect::KeyValues { paths } => {
+            let root = paths
+                .first()
+                .and_then(|path| path.split('/').next())
+                .unwrap_or("AccshiftHarness");
+            KeySelect::KeyValues {
+                paths: vec![format!("{root}/AccshiftHarness")],
+            }
+        }
+    }
+}
+
+fn captured(select: &KeySelect, value: impl Fn(&str) -> String) -> Captured {
+    Captured {
+        present: keys::labels(select)
+            .into_iter()
+            .map(|label| {
+                let text = value(&label);
+                (label, Value::String(text))
+            })
+            .collect(),
+        absent: Vec::new(),
+    }
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Could not create {}: {e}", parent.display()))?;
+    }
+    fs::write(path, bytes).map_err(|e| format!("Could not write {}: {e}", path.display()))
+}
+
+fn edit_keys(path: &Path, select: &KeySelect, wanted: &Captured) -> Result<(), String> {
+    let current = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Could not read {}: {e}", path.display())),
+    };
+    let edited =
+        keys::apply(&current, select, wanted).map_err(|e| format!("{}: {e}", path.display()))?;
+    write_file(path, edited.as_bytes())
+}
+
+fn read_keys(path: &Path, select: &KeySelect) -> Captured {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    keys::capture(&content, select).unwrap_or_default()
+}
+
+/// Every file below `dir`, relative to it, with its bytes.
+fn read_tree(dir: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+    let mut files = BTreeMap::new();
+    for path in walk(dir) {
+        if let (Ok(relative), Ok(bytes)) = (path.strip_prefix(dir), fs::read(&path)) {
+            files.insert(relative.to_path_buf(), bytes);
+        }
+    }
+    files
+}
+
+fn walk(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(walk(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
+
+fn inside(path: &Path, root: &Path) -> bool {
+    path.is_absolute() && lexically_normalise(path).starts_with(lexically_normalise(root))
+}
+
+/// One piece of live state as the harness reads it back.
+#[derive(Debug, Clone, PartialEq)]
+enum Live {
+    File(Option<Vec<u8>>),
+    Dir(BTreeMap<PathBuf, Vec<u8>>),
+    Registry(Option<String>),
+    Keys(Captured),
+}
+
+/// What an account's session looked like once setup captured it, by name.
+type LiveState = Vec<(String, Live)>;
+
+struct Harness<'a> {
+    service: &'a DescriptorService,
+    app: AppCtx,
+    world: PathBuf,
+    /// The ids setup reported, in the order the accounts were added.
+    ids: Vec<String>,
+    expected: Vec<LiveState>,
+    /// Files the harness put outside the declared roots on purpose: the
+    /// launcher binary and the registry hives.
+    outside_roots: Vec<PathBuf>,
+    /// State files that are also a key or identity file, written through
+    /// those edits instead of whole.
+    unseeded: HashSet<PathBuf>,
+}
+
+impl<'a> Harness<'a> {
+    fn profile(&self) -> &'a OsProfile {
+        self.service
+            .profile()
+            .expect("the harness only runs a profile it found")
+    }
+
+    /// The folders and files a machine with the launcher installed already
+    /// has: the registry hives, the Wine prefix and the binary.
+    fn prepare(&mut self, root: &Path) -> Result<(), String> {
+        fs::create_dir_all(&self.world)
+            .map_err(|e| format!("Could not create {}: {e}", self.world.display()))?;
+        let resolver = self.service.base_resolver();
+
+        let mut hives = vec![wine::Prefix::new(root.join("registry"), "accshift")];
+        if let Some(prefix) = resolver.wine_prefix() {
+            fs::create_dir_all(prefix.drive_c().join("users").join(prefix.user()))
+                .map_err(|e| format!("Could not create the Wine prefix: {e}"))?;
+            hives.push(prefix.clone());
+        }
+        for prefix in hives {
+            for hive in [RegistryHive::CurrentUser, RegistryHive::LocalMachine] {
+                let file = prefix.hive_file(hive);
+                if !file.exists() {
+                    write_file(&file, b"WINE REGISTRY Version 2\n")?;
+                    self.outside_roots.push(file);
+                }
+            }
+        }
+
+        self.seed_executable(&resolver)
+    }
+
+    /// Puts a launcher binary where the first candidate that can point into
+    /// the scratch world says it is.
+    fn seed_executable(&mut self, resolver: &PathResolver) -> Result<(), String> {
+        let Some(executable) = &self.profile().executable else {
+            return Ok(());
+        };
+        let registry = self.service.registry(resolver);
+        let install_dir = self.world.join("install");
+        for candidate in &executable.candidates {
+            let binary = match candidate {
+                ExecutableCandidate::Path { template } => {
+                    let Ok(path) = resolver.resolve(template) else {
+                        continue;
+                    };
+                    if !inside(&path, &self.world) {
+                        continue;
+                    }
+                    let names_binary = path
+                        .file_name()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(&executable.file_name));
+                    if names_binary {
+                        path
+                    } else {
+                        path.join(&executable.file_name)
+                    }
+                }
+                ExecutableCandidate::Registry { root, key, value } => {
+                    registry.write(*root, key, value, &install_dir.to_string_lossy())?;
+                    install_dir.join(&executable.file_name)
+                }
+                ExecutableCandidate::UninstallEntry {
+                    display_name,
+                    value,
+                } => {
+                    let key =
+                        format!("Software\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\{TOKEN}");
+                    let hive = RegistryHive::LocalMachine;
+                    registry.write(hive, &key, "DisplayName", display_name)?;
+                    registry.write(hive, &key, value, &install_dir.to_string_lossy())?;
+                    install_dir.join(&executable.file_name)
+                }
+                ExecutableCandidate::JsonPointer { path, pointer } => {
+                    let Ok(file) = resolver.resolve(path) else {
+                        continue;
+                    };
+                    if !inside(&file, &self.world) {
+                        continue;
+                    }
+                    let select = KeySelect::JsonPointer {
+                        pointers: vec![pointer.clone()],
+                    };
+                    let mut captured = Captured::default();
+                    captured.present.insert(
+                        pointer.clone(),
+                        Value::String(install_dir.to_string_lossy().into_owned()),
+                    );
+                    let content = fs::read_to_string(&file).unwrap_or_default();
+                    write_file(&file, keys::apply(&content, &select, &captured)?.as_bytes())?;
+                    self.outside_roots.push(file);
+                    install_dir.join(&executable.file_name)
+                }
+            };
+            write_file(&binary, TOKEN.as_bytes())?;
+            self.outside_roots.push(binary);
+            return Ok(());
+        }
+        Err(format!(
+            "No executable candidate can point into the scratch folder: each one is \
+             a path outside every ${{...}} placeholder, so {} cannot be placed",
+            executable.file_name
+        ))
+    }
+
+    fn runtime(&self) -> Result<Runtime<'a>, String> {
+        let service = self.service;
+        service.runtime(&*self.app)
+    }
+
+    fn run_steps(&mut self, checks: &mut Vec<HarnessCheck>) {
+        let unsupported = match &self.profile().identity.source {
+            IdentitySource::NativeHook { name, .. } => Some(format!(
+                "the identity is read by the `{name}` hook, compiled code the harness \
+                 cannot seed a session for"
+            )),
+            IdentitySource::SqliteQuery { .. } => Some(
+                "the identity is a SQLite query, and the harness cannot know the \
+                 tables it expects"
+                    .to_string(),
+            ),
+            _ => None,
+        };
+
+        let mut stopped: Option<String> = None;
+        for step in STEPS {
+            if let Some(reason) = &stopped {
+                checks.push(HarnessCheck {
+                    step: step.into(),
+                    outcome: Outcome::Skip,
+                    detail: reason.clone(),
+                });
+                continue;
+            }
+            if step != "sandbox" {
+                if let Some(reason) = &unsupported {
+                    stopped = Some(format!("not run: {reason}"));
+                    checks.push(HarnessCheck {
+                        step: step.into(),
+                        outcome: Outcome::Skip,
+                        detail: format!("not run: {reason}"),
+                    });
+                    continue;
+                }
+            }
+            let result = match step {
+                "sandbox" => self.check_sandbox(),
+                "setup-a" => self.setup(0),
+                "encryption" => self.check_encryption(),
+                "setup-b" => self.setup(1),
+                "switch-a" => self.switch(0),
+                "switch-b" => self.switch(1),
+                "forget" => self.forget(),
+                _ => self.check_confined(),
+            };
+            let (outcome, detail) = match result {
+                Ok(detail) => (Outcome::Pass, detail),
+                Err(detail) => {
+                    stopped = Some(format!("not run: {step} failed"));
+                    (Outcome::Fail, detail)
+                }
+            };
+            checks.push(HarnessCheck {
+                step: step.into(),
+                outcome,
+                detail,
+            });
+        }
+    }
+
+    /// Every root lands in the scratch world, and every path the profile
+    /// names stays inside a root. A root that does not is one the harness
+    /// could not redirect, and running on would write to the real machine.
+    fn check_sandbox(&mut self) -> Result<String, String> {
+        let runtime = self.runtime()?;
+        let profile = runtime.profile;
+        for template in &profile.roots.files {
+            let resolved = runtime
+                .resolver
+                .resolve(template)
+                .map_err(|e| format!("root `{}` does not resolve: {e}", template.as_str()))?;
+            if !inside(&resolved, &self.world) {
+                return Err(format!(
+                    "root `{}` resolves to {}, outside the scratch folder: only \
+                     ${{...}} placeholders can be redirected",
+                    template.as_str(),
+                    resolved.display()
+                ));
+            }
+        }
+
+        let mut named: Vec<(String, PathSpec)> = Vec::new();
+        let state = &profile.state;
+        for item in &state.files {
+            named.push((format!("state file `{}`", item.snapshot), item.live.clone()));
+        }
+        for item in &state.directories {
+            named.push((
+                format!("state folder `{}`", item.snapshot),
+                item.live.clone(),
+            ));
+        }
+        for item in &state.keys {
+            named.push((format!("key file `{}`", item.snapshot), item.live.clone()));
+        }
+        for template in &state.caches {
+            named.push(("cache".into(), PathSpec::One(template.clone())));
+        }
+        match &profile.identity.source {
+            IdentitySource::LogTail { path, .. }
+            | IdentitySource::JsonPointer { path, .. }
+            | IdentitySource::IniKey { path, .. }
+            | IdentitySource::SqliteQuery { path, .. } => {
+                named.push(("identity".into(), PathSpec::One(path.clone())))
+            }
+            IdentitySource::NativeHook { paths, .. } => {
+                for (key, path) in paths {
+                    named.push((format!("hook path `{key}`"), PathSpec::One(path.clone())));
+                }
+            }
+            IdentitySource::Registry { .. } | IdentitySource::Synthetic => {}
+        }
+        for entry in &profile.identity.discovery {
+            named.push(("discovery".into(), PathSpec::One(entry.path().clone())));
+        }
+        let mut conditions = Vec::new();
+        condition_paths(&profile.setup.trigger, &mut conditions);
+        condition_paths(&profile.setup.confirm, &mut conditions);
+        condition_paths(&profile.state.capture_when, &mut conditions);
+        for (path, _) in conditions {
+            named.push(("condition".into(), path.clone()));
+        }
+
+        for (what, spec) in &named {
+            for template in spec.candidates() {
+                runtime
+                    .path(template)
+                    .map_err(|e| format!("{what} `{}`: {e}", template.as_str()))?;
+            }
+        }
+        Ok(format!(
+            "{} roots in the scratch folder, {} declared paths inside them",
+            profile.roots.files.len(),
+            named.len()
+        ))
+    }
+
+    /// Writes account `index`'s session the way a sign-in would leave it.
+    fn seed(&mut self, index: usize, id: &str) -> Result<(), String> {
+        let runtime = self.runtime()?;
+        let profile = runtime.profile;
+        let letter = account_letter(index);
+
+        // Files written through a key or identity edit first, so seeding
+        // the state files does not clobber what those edits parse.
+        self.unseeded.clear();
+        for item in &profile.state.keys {
+            self.unseeded
+                .insert(runtime.spec_path(&item.live).map_err(|e| e.to_string())?);
+        }
+        match &profile.identity.source {
+            IdentitySource::JsonPointer { path, .. } | IdentitySource::IniKey { path, .. } => {
+                self.unseeded
+                    .insert(runtime.path(path).map_err(|e| e.to_string())?);
+            }
+            _ => {}
+        }
+
+        for item in &profile.state.files {
+            let live = runtime.spec_path(&item.live).map_err(|e| e.to_string())?;
+            if !self.unseeded.contains(&live) {
+                write_file(&live, seeded_value(index, &item.snapshot).as_bytes())?;
+            }
+        }
+        for item in &profile.state.directories {
+            let live = runtime.spec_path(&item.live).map_err(|e| e.to_string())?;
+            write_file(
+                &live.join(format!("{TOKEN}-{letter}.bin")),
+                seeded_value(index, &item.snapshot).as_bytes(),
+            )?;
+            write_file(
+                &live.join(TOKEN).join("nested.bin"),
+                seeded_value(index, "nested").as_bytes(),
+            )?;
+        }
+        let identity_value = match &profile.identity.source {
+            IdentitySource::Registry { root, key, value } => Some((*root, key, value)),
+            _ => None,
+        };
+        for item in &profile.state.registry_values {
+            let is_identity = identity_value.is_some_and(|(root, key, value)| {
+                root == item.root
+                    && key.eq_ignore_ascii_case(&item.key)
+                    && value.eq_ignore_ascii_case(&item.value)
+            });
+            if !is_identity {
+                runtime.registry.write(
+                    item.root,
+                    &item.key,
+                    &item.value,
+                    &seeded_value(index, &item.value),
+                )?;
+            }
+        }
+        for item in &profile.state.keys {
+            let live = runtime.spec_path(&item.live).map_err(|e| e.to_string())?;
+            let marker = untouched_key(&item.select);
+            edit_keys(
+                &live,
+                &marker,
+                &captured(&marker, |_| seeded_value(index, "untouched")),
+            )?;
+            edit_keys(
+                &live,
+                &item.select,
+                &captured(&item.select, |label| seeded_value(index, label)),
+            )?;
+        }
+        self.seed_identity(&runtime, index, id)?;
+
+        // Whatever a trigger waits on that the state does not already cover.
+        let mut conditions = Vec::new();
+        condition_paths(&profile.setup.trigger, &mut conditions);
+        condition_paths(&profile.setup.confirm, &mut conditions);
+        condition_paths(&profile.state.capture_when, &mut conditions);
+        for (spec, recursive) in conditions {
+            let path = runtime.spec_path(spec).map_err(|e| e.to_string())?;
+            if path.exists() {
+                continue;
+            }
+            let file = if recursive {
+                path.join(format!("{TOKEN}.bin"))
+            } else {
+                path
+            };
+            write_file(&file, seeded_value(index, "condition").as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Writes the id where the identity source reads it, as the launcher does
+    /// once an account is signed in. A synthetic identity has nowhere to go.
+    fn seed_identity(&self, runtime: &Runtime<'_>, index: usize, id: &str) -> Result<(), String> {
+        let name = format!(
+            "Harness account {}",
+            account_letter(index).to_ascii_uppercase()
+        );
+        match &runtime.profile.identity.source {
+            IdentitySource::Registry { root, key, value } => {
+                runtime.registry.write(*root, key, value, id)
+            }
+            IdentitySource::JsonPointer {
+                path,
+                pointer,
+                display_name_pointer,
+            } => {
+                let pointers = [pointer, display_name_pointer]
+                    .into_iter()
+                    .filter(|pointer| !pointer.is_empty())
+                    .cloned()
+                    .collect();
+                let select = KeySelect::JsonPointer { pointers };
+                let live = runtime.path(path).map_err(|e| e.to_string())?;
+                edit_keys(
+                    &live,
+                    &select,
+                    &captured(&select, |label| {
+                        if label == pointer.as_str() {
+                            id.to_string()
+                        } else {
+                            name.clone()
+                        }
+                    }),
+                )
+            }
+            IdentitySource::IniKey {
+                path,
+                section,
+                key,
+                display_name_key,
+            } => {
+                let keys = [key, display_name_key]
+                    .into_iter()
+                    .filter(|key| !key.is_empty())
+                    .map(|key| IniKey {
+                        section: section.clone(),
+                        key: key.clone(),
+                    })
+                    .collect();
+                let select = KeySelect::IniKey { keys };
+                let id_label = keys::labels(&select).remove(0);
+                let live = runtime.path(path).map_err(|e| e.to_string())?;
+                edit_keys(
+                    &live,
+                    &select,
+                    &captured(&select, |label| {
+                        if label == id_label {
+                            id.to_string()
+                        } else {
+                            name.clone()
+                        }
+                    }),
+                )
+            }
+            IdentitySource::LogTail {
+                path,
+                line_contains,
+                prefix,
+                near_word,
+                ..
+            } => {
+                let live = runtime.path(path).map_err(|e| e.to_string())?;
+                let line = if prefix.is_empty() {
+                    format!("{line_contains} {near_word} {id}\n")
+                } else {
+                    format!("{line_contains} {prefix}{id}\n")
+                };
+                let mut text = fs::read_to_string(&live).unwrap_or_default();
+                text.push_str(&line);
+                write_file(&live, text.as_bytes())
+            }
+            IdentitySource::Synthetic
+            | IdentitySource::NativeHook { .. }
+            | IdentitySource::SqliteQuery { .. } => Ok(()),
+        }
+    }
+
+    /// The live state the descriptor declares, read back.
+    fn live_state(&self) -> Result<LiveState, String> {
+        let runtime = self.runtime()?;
+        let profile = runtime.profile;
+        // A file holding selected keys keeps the outgoing account's other
+        // keys by design, so only its keys are compared.
+        let key_files = profile
+            .state
+            .keys
+            .iter()
+            .map(|item| runtime.spec_path(&item.live))
+            .collect::<Result<HashSet<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        let mut state = Vec::new();
+        for item in &profile.state.files {
+            let live = runtime.spec_path(&item.live).map_err(|e| e.to_string())?;
+            if !key_files.contains(&live) {
+                state.push((live.display().to_string(), Live::File(fs::read(&live).ok())));
+            }
+        }
+        for item in &profile.state.directories {
+            let live = runtime.spec_path(&item.live).map_err(|e| e.to_string())?;
+            state.push((live.display().to_string(), Live::Dir(read_tree(&live))));
+        }
+        for item in &profile.state.registry_values {
+            state.push((
+                reg::display(item.root, &item.key, &item.value),
+                Live::Registry(runtime.registry.read(item.root, &item.key, &item.value)),
+            ));
+        }
+        for item in &profile.state.keys {
+            let live = runtime.spec_path(&item.live).map_err(|e| e.to_string())?;
+            state.push((
+                format!(
+                    "{} {}",
+                    live.display(),
+                    keys::labels(&item.select).join(", ")
+                ),
+                Live::Keys(read_keys(&live, &item.select)),
+            ));
+        }
+        Ok(state)
+    }
+
+    /// Adds account `index` through the real setup flow: begin, sign in,
+    /// poll until the engine captures it.
+    fn setup(&mut self, index: usize) -> Result<String, String> {
+        let profile = self.profile();
+        let seeded_id = account_id(&profile.identity.format, index);
+        let begun = self
+            .service
+            .begin_setup(self.app.clone(), Value::Null)
+            .map_err(|e| format!("setup did not start: {e}"))?;
+        if begun.state == "ready" {
+            return Err(format!(
+                "setup adopted {} instead of waiting for a sign-in: nothing was signed in yet",
+                begun.account_id
+            ));
+        }
+        self.seed(index, &seeded_id)?;
+
+        let wait = longest_wait(&profile.setup.trigger).max(longest_wait(&profile.setup.confirm))
+            + SETUP_GRACE_MS;
+        let deadline = Instant::now() + Duration::from_millis(wait);
+        let status = loop {
+            let status = self
+                .service
+                .get_setup_status(self.app.clone(), &begun.setup_id)
+                .map_err(|e| format!("setup failed: {e}"))?;
+            if status.state == "ready" || Instant::now() >= deadline {
+                break status;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        };
+        if status.state != "ready" {
+            return Err(format!(
+                "setup was still `{}` {} ms after the sign-in was seeded: its trigger or \
+                 confirm conditions never held",
+                status.state, wait
+            ));
+        }
+        let synthetic = matches!(profile.identity.source, IdentitySource::Synthetic);
+        if !synthetic && status.account_id != seeded_id {
+            return Err(format!(
+                "setup captured account {}, but the session seeded was {seeded_id}",
+                status.account_id
+            ));
+        }
+
+        self.ids.push(status.account_id.clone());
+        self.expected.push(self.live_state()?);
+        Ok(format!("added account {}", status.account_id))
+    }
+
+    fn snapshot_root(&self, id: &str) -> Result<PathBuf, String> {
+        Ok(crate::storage::platform_snapshots_dir(&*self.app, self.service.id())?.join(id))
+    }
+
+    /// Every snapshot file is encrypted, and none holds a seeded value in
+    /// plaintext.
+    fn check_encryption(&mut self) -> Result<String, String> {
+        let dir = self.snapshot_root(&self.ids[0])?;
+        let files = walk(&dir);
+        if files.is_empty() {
+            return Err(format!("the snapshot at {} holds no file", dir.display()));
+        }
+        for file in &files {
+            let bytes = fs::read(file).map_err(|e| format!("{}: {e}", file.display()))?;
+            if !bytes.starts_with(ENCRYPTED_HEADER) {
+                return Err(format!("{} is not encrypted", file.display()));
+            }
+            if bytes
+                .windows(TOKEN.len())
+                .any(|window| window == TOKEN.as_bytes())
+            {
+                return Err(format!(
+                    "{} holds part of the session in plaintext",
+                    file.display()
+                ));
+            }
+        }
+        Ok(format!(
+            "{} snapshot files, every one encrypted",
+            files.len()
+        ))
+    }
+
+    /// Switches to account `index` and compares what is live with what setup
+    /// captured for it, byte for byte.
+    fn switch(&mut self, index: usize) -> Result<String, String> {
+        let runtime = self.runtime()?;
+        let profile = runtime.profile;
+        let id = self.ids[index].clone();
+
+        let mut caches = Vec::new();
+        for template in &profile.state.caches {
+            let dir = runtime.path(template).map_err(|e| e.to_string())?;
+            write_file(&dir.join(format!("{TOKEN}.bin")), TOKEN.as_bytes())?;
+            caches.push(dir);
+        }
+        let mut untouched = Vec::new();
+        for item in &profile.state.keys {
+            let live = runtime.spec_path(&item.live).map_err(|e| e.to_string())?;
+            let marker = untouched_key(&item.select);
+            let before = read_keys(&live, &marker);
+            untouched.push((live, marker, before));
+        }
+
+        self.service
+            .switch_account(self.app.clone(), &id, Value::Null)
+            .map_err(|e| format!("switch to {id} failed: {e}"))?;
+
+        let live = self.live_state()?;
+        let differing: Vec<&str> = live
+            .iter()
+            .zip(&self.expected[index])
+            .filter(|(now, then)| now != then)
+            .map(|(now, _)| now.0.as_str())
+            .collect();
+        if !differing.is_empty() {
+            return Err(format!(
+                "after switching to {id}, these differ from what setup captured: {}",
+                differing.join("; ")
+            ));
+        }
+        for (path, marker, before) in &untouched {
+            if read_keys(path, marker) != *before {
+                return Err(format!(
+                    "restoring {} changed a key the descriptor does not select",
+                    path.display()
+                ));
+            }
+        }
+        if let Some(cache) = caches.iter().find(|dir| dir.exists()) {
+            return Err(format!("cache {} survived the switch", cache.display()));
+        }
+
+        // A log is what the launcher writes once it runs, never restored
+        // state, so the harness writes it in the launcher's place.
+        if matches!(profile.identity.source, IdentitySource::LogTail { .. }) {
+            self.seed_identity(&runtime, index, &id)?;
+        }
+        let current = self
+            .service
+            .get_current_account(self.app.clone())
+            .map_err(|e| e.to_string())?;
+        if current != id {
+            let source = match profile.identity.current {
+                CurrentSource::Identity => "the restored state does not carry the identity",
+                CurrentSource::Config => "the config does not record it",
+            };
+            return Err(format!(
+                "after switching to {id} the current account reads as `{current}`: {source}"
+            ));
+        }
+        Ok(format!(
+            "{} items restored byte for byte, current account is {id}",
+            live.len()
+        ))
+    }
+
+    fn forget(&mut self) -> Result<String, String> {
+        let id = self.ids[0].clone();
+        self.service
+            .forget_account(self.app.clone(), &id)
+            .map_err(|e| format!("forgetting {id} failed: {e}"))?;
+        let dir = self.snapshot_root(&id)?;
+        if dir.exists() {
+            return Err(format!("{} survived forgetting {id}", dir.display()));
+        }
+        let accounts = self
+            .service
+            .get_accounts(self.app.clone())
+            .map_err(|e| e.to_string())?;
+        let listed = accounts
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|account| account["accountId"].as_str() == Some(id.as_str()));
+        if listed {
+            return Err(format!("{id} is still listed after it was forgotten"));
+        }
+        Ok(format!("{id} forgotten, its snapshot removed"))
+    }
+
+    /// Nothing the engine wrote landed outside the declared roots, and no
+    /// staging file was left behind.
+    fn check_confined(&mut self) -> Result<String, String> {
+        let runtime = self.runtime()?;
+        let roots = runtime.sandbox.roots();
+        let mut strays = Vec::new();
+        let files = walk(&self.world);
+        for file in &files {
+            let staged = file
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().ends_with(".accshift-restore-tmp"));
+            let allowed = self.outside_roots.iter().any(|path| path == file)
+                || roots.iter().any(|root| inside(file, root));
+            if staged || !allowed {
+                strays.push(file.display().to_string());
+            }
+        }
+        if !strays.is_empty() {
+            return Err(format!(
+                "written outside the declared roots or left staged: {}",
+                strays.join(", ")
+            ));
+        }
+        Ok(format!(
+            "{} files, all inside the declared roots",
+            files.len()
+        ))
+    }
+
+    /// Forgets whatever is still stored, so no keyring entry outlives the
+    /// scratch folder.
+    fn clean_up(&self) {
+        let Ok(dir) = crate::storage::platform_snapshots_dir(&*self.app, self.service.id()) else {
+            return;
+        };
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let id = entry.file_name().to_string_lossy().into_owned();
+            let _ = self.service.forget_account(self.app.clone(), &id);
+        }
+    }
+}
+
+/// The app's own folders, inside the scratch root.
+struct ScratchContext {
+    root: PathBuf,
+}
+
+impl AppContext for ScratchContext {
+    fn app_config_dir(&self) -> Result<PathBuf, String> {
+        Ok(self.root.clone())
+    }
+    fn app_data_dir(&self) -> Result<PathBuf, String> {
+        Ok(self.root.clone())
+    }
+    fn app_local_data_dir(&self) -> Result<PathBuf, String> {
+        Ok(self.root.clone())
+    }
+    fn app_cache_dir(&self) -> Result<PathBuf, String> {
+        Ok(self.root.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platforms::descriptor::schema::Charset;
+
+    /// The config cache is process-global; see the engine's tests.
+    fn config_guard() -> std::sync::MutexGuard<'static, ()> {
+        crate::config::config_io_test_mutex()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A Linux launcher keeping its id in an INI file and its session in
+    /// selected JSON keys. `state_file` is the whole file captured with them.
+    fn acme(roots: &str, state_file: &str) -> Descriptor {
+        let body = format!(
+            r#"{{
+              "id": "acme",
+              "schemaVersion": 1,
+              "name": "Acme Launcher",
+              "shortName": "Acme",
+              "os": {{
+                "linux": {{
+                  "roots": {{ "files": [{roots}] }},
+                  "detect": {{ "pathExists": ["${{HOME}}/.config/acme"] }},
+                  "identity": {{
+                    "source": {{
+                      "kind": "iniKey",
+                      "path": "${{HOME}}/.config/acme/account.ini",
+                      "section": "Account",
+                      "key": "Id"
+                    }},
+                    "format": {{ "charset": "digits", "maxLength": 12 }},
+                    "current": "identity"
+                  }},
+                  "state": {{
+                    "files": [
+                      {{ "live": "${{HOME}}/.config/acme/{state_file}", "snapshot": "{state_file}", "snapshotMarker": true }}
+                    ],
+                    "keys": [
+                      {{
+                        "live": "${{HOME}}/.config/acme/settings.json",
+                        "snapshot": "keys.json",
+                        "select": {{ "kind": "jsonPointer", "pointers": ["/auth/token"] }},
+                        "clearOnSetup": true
+                      }}
+                    ],
+                    "caches": ["${{HOME}}/.config/acme/cache"]
+                  }},
+                  "close": {{ "processes": ["acme"] }},
+                  "setup": {{ "trigger": [{{ "kind": "newIdentity" }}] }}
+                }}
+              }}
+            }}"#
+        );
+        Descriptor::parse("acme.json", &body).unwrap()
+    }
+
+    fn linux() -> HarnessOptions {
+        HarnessOptions {
+            os: Some(Os::Linux),
+            keep: false,
+        }
+    }
+
+    #[test]
+    fn the_two_accounts_get_distinct_ids_every_charset_accepts() {
+        for charset in Charset::ALL {
+            let format = IdFormat {
+                charset,
+                max_length: 36,
+                min_length: 1,
+                lowercase: false,
+                invalid_message: String::new(),
+            };
+            let (a, b) = (account_id(&format, 0), account_id(&format, 1));
+            assert_ne!(a, b, "{charset:?}");
+            assert!(charset.accepts(&a) && charset.accepts(&b), "{charset:?}");
+        }
+    }
+
+    #[test]
+    fn every_placeholder_points_into_the_scratch_world() {
+        let descriptor = acme(r#""${HOME}/.config/acme""#, "account.ini");
+        let world = Path::new("/scratch/world");
+        let env = environment(&descriptor.os[&Os::Linux], world);
+        assert_eq!(
+            env,
+            vec![(
+                "HOME".to_string(),
+                world
+                    .join("env")
+                    .join("HOME")
+                    .to_string_lossy()
+                    .into_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn a_root_no_placeholder_can_redirect_stops_the_run_at_the_sandbox() {
+        let _guard = config_guard();
+        let descriptor = acme(r#""/var/lib/acme", "${HOME}/.config/acme""#, "account.ini");
+        let report = run(&descriptor, &linux());
+
+        assert!(!report.ok);
+        assert_eq!(report.checks[0].step, "sandbox");
+        assert_eq!(report.checks[0].outcome, Outcome::Fail);
+        assert!(
+            report.checks[0].detail.contains("/var/lib/acme"),
+            "{}",
+            report.checks[0].detail
+        );
+        assert!(report.checks[1..]
+            .iter()
+            .all(|check| check.outcome == Outcome::Skip));
+        assert!(report.root.is_empty());
+    }
+
+    #[test]
+    fn a_native_hook_identity_is_skipped_after_the_sandbox_rather_than_failed() {
+        let _guard = config_guard();
+        let (descriptors, _) = super::super::load_embedded();
+        let discord = descriptors.iter().find(|d| d.id == "discord").unwrap();
+        let report = run(
+            discord,
+            &HarnessOptions {
+                os: Some(Os::Windows),
+                keep: false,
+            },
+        );
+
+        assert!(report.ok, "{:?}", report.checks);
+        assert_eq!(report.checks[0].outcome, Outcome::Pass);
+        assert!(report.checks[1..]
+            .iter()
+            .all(|check| check.outcome == Outcome::Skip && check.detail.contains("hook")));
+    }
+
+    #[test]
+    fn a_profile_the_descriptor_does_not_have_is_named() {
+        let descriptor = acme(r#""${HOME}/.config/acme""#, "account.ini");
+        let report = run(
+            &descriptor,
+            &HarnessOptions {
+                os: Some(Os::Macos),
+                keep: false,
+            },
+        );
+
+        assert!(!report.ok);
+        assert_eq!(report.checks.len(), 1);
+        assert!(report.checks[0]
+            .detail
+            .contains("describes linux and not macos"));
+    }
+
+    // Snapshot encryption on Linux needs a keyring, so the runs that reach a
+    // capture are Windows-only, like the engine's own.
+
+    #[cfg(windows)]
+    #[test]
+    fn every_shipped_descriptor_passes_offline() {
+        let _guard = config_guard();
+        let (descriptors, _) = super::super::load_embedded();
+        for descriptor in &descriptors {
+            let report = run(
+                descriptor,
+                &HarnessOptions {
+                    os: Some(Os::Windows),
+                    keep: false,
+                },
+            );
+            assert!(report.ok, "{}: {:?}", descriptor.id, report.checks);
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn a_full_run_passes_every_step() {
+        let _guard = config_guard();
+        let report = run(&acme(r#""${HOME}/.config/acme""#, "account.ini"), &linux());
+        assert!(
+            report
+                .checks
+                .iter()
+                .all(|check| check.outcome == Outcome::Pass),
+            "{:?}",
+            report.checks
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn an_identity_the_restore_does_not_bring_back_fails_the_switch() {
+        let _guard = config_guard();
+        // The id file is left out of the state, so a switch restores the
+        // session but not who it belongs to.
+        let report = run(&acme(r#""${HOME}/.config/acme""#, "other.bin"), &linux());
+        let switch = report
+            .checks
+            .iter()
+            .find(|check| check.step == "switch-a")
+            .unwrap();
+        assert_eq!(switch.outcome, Outcome::Fail, "{:?}", report.checks);
+        assert!(switch.detail.contains("does not carry the identity"));
+    }
+}
@@ -9,6 +9,7 @@
 //! the public username. It must never read out, log, or store tokens or any
 //! other value found in leveldb.
 
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
@@ -258,6 +259,7 @@ fn scan_identity_in_dir(leveldb: &Path) -> Option<HookIdentity> {
     Some(HookIdentity {
         id: user_id,
         display_name: username,
+        fields: BTreeMap::new(),
     })
 }
 
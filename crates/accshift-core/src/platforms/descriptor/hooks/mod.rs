@@ -16,6 +16,7 @@ use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 pub(super) mod discord;
+pub(super) mod riot;
 
 /// What a hook is allowed to know about this machine.
 ///
@@ -45,6 +46,9 @@ pub struct HookIdentity {
     pub id: String,
     /// Shown instead of the raw id when the platform exposes a name.
     pub display_name: Option<String>,
+    /// Values for the descriptor's account fields, keyed by field name. The
+    /// engine keeps those the descriptor declares and drops the rest.
+    pub fields: BTreeMap<String, String>,
 }
 
 /// One compiled step, named by the descriptors entitled to it.
@@ -61,12 +65,22 @@ pub trait NativeHook: Sync {
     /// `None` means "could not tell", never "signed out": callers must not
     /// treat it as an empty session.
     fn identity(&self, ctx: &HookContext) -> Option<HookIdentity>;
+
+    /// Asks the launcher to exit, for a descriptor whose close strategies
+    /// name `nativeHook`. `Ok` means the request was delivered; the engine
+    /// still waits on the processes to see it acted upon.
+    fn close(&self, _ctx: &HookContext) -> Result<(), String> {
+        Err(format!(
+            "The {} hook cannot close its launcher",
+            self.name()
+        ))
+    }
 }
 
 /// Every hook a descriptor may name. The schema validates against this list,
 /// so a typo is a load error naming the field, not a step that silently does
 /// nothing at run time.
-static HOOKS: &[&(dyn NativeHook + 'static)] = &[&discord::LEVELDB];
+static HOOKS: &[&(dyn NativeHook + 'static)] = &[&discord::LEVELDB, &riot::LOCAL_API];
 
 pub fn hook(name: &str) -> Option<&'static dyn NativeHook> {
     HOOKS.iter().copied().find(|hook| hook.name() == name)
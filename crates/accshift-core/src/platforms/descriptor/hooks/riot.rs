@@ -0,0 +1,590 @@
+//! Reading the signed-in Riot account through the Riot Client's local API.
+//!
+//! The client keeps the signed-in account in memory and answers for it on a
+//! loopback HTTPS port whose number and password it writes to a lockfile each
+//! time it starts. Nothing on disk names the account in a form worth parsing,
+//! so this hook asks the client instead, and asks it to quit the same way: a
+//! quit through the API flushes the session to disk, a terminated client
+//! does not.
+//!
+//! PRIVACY CONSTRAINT: the settings file is only checked for whether it holds
+//! a login at all. No token, cookie or password is ever returned, logged or
+//! stored by this hook.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use super::{HookContext, HookIdentity, NativeHook};
+
+/// The lockfile the client writes while it runs.
+const LOCKFILE_PATH: &str = "lockfile";
+/// `RiotGamesPrivateSettings.yaml`, where a persisted login lives.
+const SETTINGS_PATH: &str = "settings";
+
+/// Field names the riot descriptor declares for the Riot ID.
+const GAME_NAME_FIELD: &str = "gameName";
+const TAG_LINE_FIELD: &str = "tagLine";
+
+pub struct LocalApiHook;
+
+pub static LOCAL_API: LocalApiHook = LocalApiHook;
+
+impl NativeHook for LocalApiHook {
+    fn name(&self) -> &'static str {
+        "riot-local-api"
+    }
+
+    fn required_paths(&self) -> &'static [&'static str] {
+        &[LOCKFILE_PATH, SETTINGS_PATH]
+    }
+
+    /// The account the client is signed in as, reported only once it chose to
+    /// stay signed in: a session that is not persisted to the settings file
+    /// cannot be snapshotted, so it is no account to switch back to.
+    fn identity(&self, ctx: &HookContext) -> Option<HookIdentity> {
+        let settings = fs::read_to_string(ctx.path(SETTINGS_PATH)?).ok()?;
+        if !yaml_has_auth_tokens(&settings) {
+            return None;
+        }
+        let access = read_access(ctx.path(LOCKFILE_PATH)?).ok()?;
+        crate::runtime::block_on(live_identity(&access))
+    }
+
+    fn close(&self, ctx: &HookContext) -> Result<(), String> {
+        let lockfile = ctx
+            .path(LOCKFILE_PATH)
+            .ok_or_else(|| "The Riot lockfile path did not resolve".to_string())?;
+        let access = read_access(lockfile)?;
+        crate::runtime::block_on(async {
+            let response = local_client()
+                .post(access.url("/process-control/v1/process/quit"))
+                .basic_auth("riot", Some(access.password.as_str()))
+                .send()
+                .await
+                .map_err(|e| format!("Riot quit request failed: {e}"))?;
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(format!("Riot quit request answered {}", response.status()))
+            }
+        })
+    }
+}
+
+/// Where the local API listens and the password it wants.
+#[derive(Debug, PartialEq, Eq)]
+struct LocalApiAccess {
+    protocol: String,
+    port: u16,
+    password: String,
+}
+
+impl LocalApiAccess {
+    fn url(&self, path: &str) -> String {
+        format!("{}://127.0.0.1:{}{path}", self.protocol, self.port)
+    }
+}
+
+fn read_access(lockfile: &Path) -> Result<LocalApiAccess, String> {
+    let content = fs::read_to_string(lockfile)
+        .map_err(|e| format!("Could not read Riot lockfile {}: {e}", lockfile.display()))?;
+    parse_lockfile(&content)
+}
+
+/// `name:pid:port:password:protocol`, as the client writes it.
+fn parse_lockfile(content: &str) -> Result<LocalApiAccess, String> {
+    let parts: Vec<&str> = content.trim().split(':').collect();
+    if parts.len() != 5 {
+        return Err("Riot lockfile format is invalid".into());
+    }
+    let port = parts[2]
+        .parse::<u16>()
+        .map_err(|e| format!("Invalid Riot lockfile port: {e}"))?;
+    if port < 1024 {
+        return Err("Riot lockfile port is outside the expected range".into());
+    }
+    let protocol = parts[4].trim();
+    if protocol != "http" && protocol != "https" {
+        return Err("Riot lockfile protocol is invalid".into());
+    }
+    let password = parts[3].trim();
+    if password.is_empty() {
+        return Err("Riot lockfile password is empty".into());
+    }
+    Ok(LocalApiAccess {
+        protocol: protocol.to_string(),
+        port,
+        password: password.to_string(),
+    })
+}
+
+/// The client serves its API with a self-signed certificate on loopback. The
+/// timeouts keep a hung client from holding a poll or a switch.
+fn local_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .connect_timeout(Duration::from_secs(2))
+            .timeout(Duration::from_secs(5))
+            .build()
+            .expect("Riot local API client should build")
+    })
+}
+
+async fn fetch_json(access: &LocalApiAccess, path: &str) -> Option<Value> {
+    let response = local_client()
+        .get(access.url(path))
+        .basic_auth("riot", Some(access.password.as_str()))
+        .send()
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.json::<Value>().await.ok()
+}
+
+async fn live_identity(access: &LocalApiAccess) -> Option<HookIdentity> {
+    let status = fetch_json(access, "/riot-login/v1/status").await?;
+    let logged_in = status
+        .get("phase")
+        .and_then(Value::as_str)
+        .is_some_and(|phase| phase.eq_ignore_ascii_case("logged_in"));
+    let persisted = status
+        .get("persist")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if !logged_in || !persisted {
+        return None;
+    }
+
+    let userinfo = fetch_json(access, "/riot-client-auth/v1/userinfo").await?;
+    let puuid = text(&userinfo, "sub")?;
+    let alias = fetch_json(access, "/player-account/aliases/v1/active")
+        .await
+        .unwrap_or(Value::Null);
+    Some(identity_from(
+        puuid,
+        text(&alias, "game_name"),
+        text(&alias, "tag_line"),
+    ))
+}
+
+fn text(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+/// The puuid as the id, and the Riot ID, `name#tag`, as the name.
+fn identity_from(
+    puuid: String,
+    game_name: Option<String>,
+    tag_line: Option<String>,
+) -> HookIdentity {
+    let display_name = match (&game_name, &tag_line) {
+        (Some(name), Some(tag)) => Some(format!("{name}#{tag}")),
+        (Some(name), None) => Some(name.clone()),
+        _ => None,
+    };
+    let mut fields = BTreeMap::new();
+    fields.extend(game_name.map(|name| (GAME_NAME_FIELD.to_string(), name)));
+    fields.extend(tag_line.map(|tag| (TAG_LINE_FIELD.to_string(), tag)));
+    HookIdentity {
+        id: puuid,
+        display_name,
+        fields,
+    }
+}
+
+/// Decide whether a `RiotGamesPrivateSettings.yaml` body carries real login
+/// tokens. Riot stores the persistent credentials as a non-empty `private`
+/// blob and, once a session exists, under `sessions`/token entries. A freshly
+/// reset file has those keys empty (or only a `tdid` cookie), which makes a
+/// captured snapshot useless. This is a lightweight line check on purpose:
+/// `serde_yaml` is not a dependency and the format is shallow.
+fn yaml_has_auth_tokens(contents: &str) -> bool {
+    // Return the part after `key:` only when the line is exactly that key (not a
+    // longer key that merely starts with it, e.g. `privateKey`).
+    fn value_for_key<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+        let rest = line.strip_prefix(key)?;
+        let rest = rest.strip_prefix(':')?;
+        Some(rest.trim())
+    }
+
+    fn strip_yaml_comment(value: &str) -> &str {
+        let mut in_single_quote = false;
+        let mut in_double_quote = false;
+        let mut escaped = false;
+        let mut previous_was_whitespace = true;
+
+        for (index, ch) in value.char_indices() {
+            if in_double_quote && escaped {
+                escaped = false;
+                continue;
+            }
+            if in_double_quote && ch == '\\' {
+                escaped = true;
+                continue;
+            }
+            match ch {
+                '\'' if !in_double_quote => in_single_quote = !in_single_quote,
+                '"' if !in_single_quote => in_double_quote = !in_double_quote,
+                '#' if !in_single_quote
+                    && !in_double_quote
+                    && (index == 0 || previous_was_whitespace) =>
+                {
+                    return value[..index].trim_end();
+                }
+                _ => {}
+            }
+            previous_was_whitespace = ch.is_whitespace();
+        }
+        value.trim_end()
+    }
+
+    fn normalized_yaml_value(value: &str) -> &str {
+        strip_yaml_comment(value.trim()).trim()
+    }
+
+    fn is_empty_yaml_value(value: &str) -> bool {
+        let value = normalized_yaml_value(value);
+        value.is_empty()
+            || value == "{}"
+            || value == "[]"
+            || value == "''"
+            || value == "\"\""
+            || value.eq_ignore_ascii_case("null")
+            || value == "~"
+    }
+
+    #[derive(Default)]
+    struct CookieEntry {
+        indent: usize,
+        is_ssid: bool,
+        has_value: bool,
+    }
+
+    fn update_cookie_entry(entry: &mut CookieEntry, line: &str) {
+        if let Some(value) = value_for_key(line, "name") {
+            entry.is_ssid =
+                normalized_yaml_value(value).trim_matches(|ch| ch == '"' || ch == '\'') == "ssid";
+        }
+        if let Some(value) = value_for_key(line, "value") {
+            entry.has_value = !is_empty_yaml_value(value);
+        }
+    }
+
+    // Newer Riot Client versions dropped the `private`/`sessions` blob format
+    // and store the persistent login as browser-style cookies under
+    // `riot-login: persist: session: cookies:`. The `ssid` cookie is the auth
+    // session token; a logged-out or reset file only carries tracking cookies
+    // (`tdid`, `clid`, ...). Both `name` and a non-empty `value` must belong to
+    // the same sequence entry; key order is not stable between client versions.
+
+    let mut has_private = false;
+    let mut has_sessions = false;
+    let mut has_token = false;
+    let mut cookie_entry: Option<CookieEntry> = None;
+    let mut pending_sessions_indent: Option<usize> = None;
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        let indent = line.len() - line.trim_start().len();
+        let meaningful = !trimmed.is_empty() && !trimmed.starts_with('#');
+
+        if let Some(sessions_indent) = pending_sessions_indent {
+            if meaningful {
+                if indent > sessions_indent {
+                    has_sessions = true;
+                }
+                pending_sessions_indent = None;
+            }
+        }
+
+        if let Some(after_dash) = trimmed.strip_prefix('-') {
+            if let Some(previous) = cookie_entry.take() {
+                has_token |= previous.is_ssid && previous.has_value;
+            }
+            let mut entry = CookieEntry {
+                indent,
+                ..Default::default()
+            };
+            update_cookie_entry(&mut entry, after_dash.trim());
+            cookie_entry = Some(entry);
+        } else if let Some(entry) = cookie_entry.as_mut() {
+            if meaningful && indent <= entry.indent {
+                let previous = cookie_entry.take().expect("cookie entry exists");
+                has_token |= previous.is_ssid && previous.has_value;
+            } else if meaningful {
+                update_cookie_entry(entry, trimmed);
+            }
+        }
+        if let Some(value) = value_for_key(trimmed, "private") {
+            // Riot writes `private` as an inline base64 blob; a reset file has it
+            // empty (`private: ''` / `private:`). Only a non-empty value counts.
+            if !is_empty_yaml_value(value) {
+                has_private = true;
+            }
+        }
+        if let Some(value) = value_for_key(trimmed, "sessions") {
+            let value = normalized_yaml_value(value);
+            if value.is_empty() {
+                // A bare `sessions:` is only populated if the next meaningful
+                // line is indented beneath it. Blank lines and comments do not
+                // manufacture a session entry.
+                pending_sessions_indent = Some(indent);
+            } else if !is_empty_yaml_value(value) {
+                has_sessions = true;
+            }
+        }
+        for key in ["access_token", "refresh_token", "id_token"] {
+            if value_for_key(trimmed, key).is_some_and(|value| !is_empty_yaml_value(value)) {
+                has_token = true;
+            }
+        }
+    }
+    if let Some(entry) = cookie_entry {
+        has_token |= entry.is_ssid && entry.has_value;
+    }
+
+    has_private || has_sessions || has_token
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lockfile_yields_the_port_password_and_protocol() {
+        let access = parse_lockfile("Riot Client:1234:51234:s3cret:https\n").unwrap();
+        assert_eq!(
+            access,
+            LocalApiAccess {
+                protocol: "https".into(),
+                port: 51234,
+                password: "s3cret".into(),
+            }
+        );
+        assert_eq!(access.url("/x"), "https://127.0.0.1:51234/x");
+    }
+
+    #[test]
+    fn malformed_lockfiles_are_refused() {
+        assert!(parse_lockfile("").is_err());
+        assert!(parse_lockfile("Riot Client:1234:51234:s3cret").is_err());
+        assert!(parse_lockfile("Riot Client:1234:80:s3cret:https").is_err());
+        assert!(parse_lockfile("Riot Client:1234:port:s3cret:https").is_err());
+        assert!(parse_lockfile("Riot Client:1234:51234::https").is_err());
+        assert!(parse_lockfile("Riot Client:1234:51234:s3cret:ftp").is_err());
+    }
+
+    #[test]
+    fn the_riot_id_becomes_the_name_and_the_fields() {
+        let found = identity_from("puuid-1".into(), Some("Player".into()), Some("EUW".into()));
+        assert_eq!(found.id, "puuid-1");
+        assert_eq!(found.display_name.as_deref(), Some("Player#EUW"));
+        assert_eq!(
+            found.fields.get(GAME_NAME_FIELD).map(String::as_str),
+            Some("Player")
+        );
+        assert_eq!(
+            found.fields.get(TAG_LINE_FIELD).map(String::as_str),
+            Some("EUW")
+        );
+
+        let bare = identity_from("puuid-2".into(), None, Some("EUW".into()));
+        assert_eq!(bare.display_name, None);
+    }
+
+    #[test]
+    fn empty_private_and_sessions_are_not_ready() {
+        // A freshly reset settings file: only a tdid cookie, empty auth blocks.
+        let yaml = "\
+private: ''
+sessions: {}
+tdid: some-tracking-cookie-value
+";
+        assert!(!yaml_has_auth_tokens(yaml));
+    }
+
+    #[test]
+    fn null_and_tilde_private_are_not_ready() {
+        assert!(!yaml_has_auth_tokens("private: null\nsessions: {}\n"));
+        assert!(!yaml_has_auth_tokens("private: ~\nsessions: []\n"));
+        assert!(!yaml_has_auth_tokens("private:\nsessions: {}\n"));
+    }
+
+    #[test]
+    fn non_empty_private_blob_is_ready() {
+        // Riot writes the persistent credentials as an inline base64 blob.
+        let yaml = "private: eyJhbGciOiJ...base64-blob...XVCJ9\nsessions: {}\n";
+        assert!(yaml_has_auth_tokens(yaml));
+    }
+
+    #[test]
+    fn populated_sessions_map_is_ready() {
+        let yaml = "\
+private: ''
+sessions:
+  some-session-id:
+    type: account
+";
+        assert!(yaml_has_auth_tokens(yaml));
+    }
+
+    #[test]
+    fn bare_sessions_key_requires_a_real_indented_child() {
+        assert!(!yaml_has_auth_tokens("sessions:\n"));
+        assert!(!yaml_has_auth_tokens(
+            "sessions: # no entries yet\n  # comment only\n\nother: value\n"
+        ));
+        assert!(!yaml_has_auth_tokens(
+            "sessions:\n# same-level comment\nnext_setting: true\n"
+        ));
+    }
+
+    #[test]
+    fn token_entries_are_ready() {
+        assert!(yaml_has_auth_tokens("data:\n  access_token: abc.def.ghi\n"));
+        assert!(yaml_has_auth_tokens("refresh_token: zzz\n"));
+        assert!(yaml_has_auth_tokens("id_token: yyy\n"));
+        assert!(yaml_has_auth_tokens(
+            "access_token: abc.def.ghi # refreshed token\n"
+        ));
+        assert!(yaml_has_auth_tokens(
+            "refresh_token: \"abc # part-of-token\"\n"
+        ));
+    }
+
+    #[test]
+    fn token_entries_require_exact_keys_and_non_empty_values() {
+        let yaml = "\
+# access_token: only-a-comment
+access_token_backup: not-the-key
+my_refresh_token: not-the-key
+id_token_suffix: not-the-key
+access_token:
+refresh_token: ''
+id_token: null
+";
+        assert!(!yaml_has_auth_tokens(yaml));
+        assert!(!yaml_has_auth_tokens("access_token: # comment only\n"));
+        assert!(!yaml_has_auth_tokens("refresh_token: \"\"\nid_token: ~\n"));
+    }
+
+    #[test]
+    fn keys_that_merely_start_with_private_do_not_match() {
+        // `privateKey` is a different key and must not be read as `private`.
+        assert!(!yaml_has_auth_tokens("privateKey: should-not-count\n"));
+        assert!(!yaml_has_auth_tokens("sessionsCount: 3\n"));
+    }
+
+    #[test]
+    fn indented_keys_still_match() {
+        // The real file nests these under a top-level key.
+        let yaml = "\
+riot-login:
+  private: real-blob-here
+  sessions: {}
+";
+        assert!(yaml_has_auth_tokens(yaml));
+    }
+
+    #[test]
+    fn cookie_format_with_ssid_is_ready() {
+        // Newer Riot Client format: persistent login stored as cookies, the
+        // `ssid` cookie being the auth session token.
+        let yaml = "\
+riot-login:
+    persist:
+        region: \"EUW\"
+        session:
+            cookies:
+            -   domain: \"auth.riotgames.com\"
+                name: \"asid\"
+                persistent: false
+            -   domain: \"auth.riotgames.com\"
+                name: \"ssid\"
+                persistent: true
+                value: \"opaque-session-token\"
+";
+        assert!(yaml_has_auth_tokens(yaml));
+    }
+
+    #[test]
+    fn cookie_format_ssid_as_first_mapping_key_is_ready() {
+        // `name` can be the first key of the cookie entry, carrying the dash.
+        let yaml = "cookies:\n- name: \"ssid\"\n  value: \"tok\"\n";
+        assert!(yaml_has_auth_tokens(yaml));
+    }
+
+    #[test]
+    fn ssid_cookie_requires_a_non_empty_value_in_the_same_entry() {
+        assert!(!yaml_has_auth_tokens("cookies:\n- name: \"ssid\"\n"));
+        assert!(!yaml_has_auth_tokens(
+            "cookies:\n- name: \"ssid\"\n  value: \"\"\n"
+        ));
+        assert!(!yaml_has_auth_tokens(
+            "cookies:\n- name: \"ssid\"\n  value: null\n"
+        ));
+        assert!(!yaml_has_auth_tokens(
+            "cookies:\n- name: \"ssid\"\n- name: \"tdid\"\n  value: token\n"
+        ));
+        assert!(!yaml_has_auth_tokens(
+            "cookies:\n- name: \"ssid\"\n  value: # comment only\n"
+        ));
+    }
+
+    #[test]
+    fn ssid_cookie_accepts_value_before_name() {
+        let yaml =
+            "cookies:\n- value: opaque-session-token\n  persistent: true\n  name: \"ssid\"\n";
+        assert!(yaml_has_auth_tokens(yaml));
+        assert!(yaml_has_auth_tokens(
+            "cookies:\n- name: \"ssid\" # auth cookie\n  value: token # current value\n"
+        ));
+    }
+
+    #[test]
+    fn cookie_format_with_only_tracking_cookies_is_not_ready() {
+        // Logged-out file: tracking cookies only, no ssid auth cookie.
+        let yaml = "\
+riot-login:
+    persist:
+        session:
+            cookies:
+            -   domain: \"auth.riotgames.com\"
+                name: \"tdid\"
+                persistent: true
+            -   domain: \"auth.riotgames.com\"
+                name: \"clid\"
+                persistent: true
+";
+        assert!(!yaml_has_auth_tokens(yaml));
+    }
+
+    #[test]
+    fn non_ssid_name_keys_do_not_match() {
+        assert!(!yaml_has_auth_tokens("name: \"ssidfoo\"\n"));
+        assert!(!yaml_has_auth_tokens("nickname: \"ssid\"\n"));
+    }
+
+    #[test]
+    fn small_token_file_is_ready_despite_being_under_old_size_threshold() {
+        // The old heuristic required >1000 bytes; a small file with a real token
+        // would have been wrongly rejected. The structural check accepts it.
+        let yaml = "private: tok\n";
+        assert!(yaml.len() < 1000);
+        assert!(yaml_has_auth_tokens(yaml));
+    }
+}
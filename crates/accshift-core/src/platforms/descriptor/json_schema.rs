@@ -0,0 +1,795 @@
+//! The descriptor format, published as JSON Schema.
+//!
+//! `docs/descriptor-schema.json` is generated here and a test fails when it
+//! drifts, the same arrangement as the log schema. An editor pointed at it
+//! completes field names and flags a typo while the file is being written,
+//! instead of at the next reload.
+//!
+//! The schema states every rule that can be checked on one value alone: the
+//! charsets, the hook names, the shape of a path template, a snapshot name or a
+//! JSON pointer. Rules that relate two fields (a path inside a declared root, a
+//! unique snapshot name) stay with the loader, which remains the authority. A
+//! file the schema accepts can still be refused; a file it refuses never loads.
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+use super::hooks;
+use super::schema::{
+    self, Charset, Close, CurrentSource, EntryKind, FieldKind, Os, RegistryHive, Runner,
+    BUILTIN_LAUNCH_VALUES, CURRENT_SCHEMA_VERSION, MAX_FIELD_CHARS, NATIVE_HOOK_ALLOWLIST,
+};
+
+pub const SCHEMA_FILE_NAME: &str = "descriptor-schema.json";
+
+/// JSON Schema (draft 2020-12) of one descriptor file.
+pub fn descriptor_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": "https://accshift.app/schemas/descriptor.json",
+        "title": "accshift platform descriptor",
+        "description": "One platform, described end to end for every OS it supports. A descriptor naming `extends` is merged over that shipped descriptor and checked once merged, so only its own top level is checked here.",
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "$schema": {
+                "type": "string",
+                "description": "Ignored by the loader. Lets an editor find this schema.",
+            },
+            "id": {
+                "type": "string",
+                "pattern": "^[a-z0-9-]{1,32}$",
+                "description": "Canonical platform id, the key everything else in the app uses.",
+            },
+            "extends": {
+                "enum": super::shipped_ids(),
+                "description": "The shipped descriptor this one starts from. Objects merge key by key, anything else replaces the parent's value whole, and null removes it.",
+            },
+            "schemaVersion": {
+                "const": CURRENT_SCHEMA_VERSION,
+                "description": "Schema version this build understands.",
+            },
+            "name": {
+                "$ref": "#/$defs/displayName",
+                "description": "Display name, used in messages about the launcher itself.",
+            },
+            "shortName": {
+                "$ref": "#/$defs/displayName",
+                "description": "Short name used in account-id error messages.",
+            },
+            "os": {
+                "type": "object",
+                "description": "One profile per OS the platform supports. An OS with no profile simply has no service.",
+            },
+        },
+        "if": { "required": ["extends"] },
+        "then": {
+            "required": ["id", "extends"],
+            "properties": {
+                "os": { "propertyNames": { "enum": os_names() } },
+            },
+        },
+        "else": {
+            "required": ["id", "schemaVersion", "name", "shortName", "os"],
+            "properties": {
+                "os": {
+                    "minProperties": 1,
+                    "additionalProperties": false,
+                    "properties": {
+                        "windows": {
+                            "allOf": [{ "$ref": "#/$defs/osProfile" }],
+                            "not": { "required": ["wine"] },
+                            "description": "Windows runs the launcher natively: a Wine prefix belongs on a Linux or macOS profile.",
+                        },
+                        "macos": { "$ref": "#/$defs/osProfile" },
+                        "linux": { "$ref": "#/$defs/osProfile" },
+                    },
+                },
+            },
+        },
+        "allOf": [native_hook_allowlist()],
+        "$defs": definitions(),
+    })
+}
+
+/// Only the allowlisted platforms, or a descriptor extending one of them, may
+/// name a native hook on any of their profiles.
+fn native_hook_allowlist() -> Value {
+    let profile_uses_hook = json!({
+        "required": ["identity"],
+        "properties": {
+            "identity": {
+                "required": ["source"],
+                "properties": {
+                    "source": {
+                        "required": ["kind"],
+                        "properties": { "kind": { "const": "nativeHook" } },
+                    },
+                },
+            },
+        },
+    });
+    json!({
+        "if": {
+            "required": ["os"],
+            // "Not every profile lacks a hook": some profile names one.
+            "properties": {
+                "os": { "not": { "additionalProperties": { "not": profile_uses_hook } } },
+            },
+        },
+        "then": {
+            "if": { "required": ["extends"] },
+            "then": { "properties": { "extends": { "enum": NATIVE_HOOK_ALLOWLIST } } },
+            "else": { "properties": { "id": { "enum": NATIVE_HOOK_ALLOWLIST } } },
+        },
+    })
+}
+
+fn definitions() -> Value {
+    let close = Close::default();
+    let runner = Runner::default();
+    json!({
+        "displayName": {
+            "type": "string",
+            "pattern": r"\S",
+        },
+        "pathTemplate": {
+            "type": "string",
+            // Not blank, every `${` closed around a usable name, and no `..`
+            // segment to climb out of the roots with.
+            "pattern": r"^(?=.*\S)(?!(?:.*[/\\])?\.\.(?:[/\\]|$))(?:[^$]|\$(?!\{)|\$\{[A-Za-z0-9_()]+\})*$",
+            "description": "A location with ${...} placeholders. ${installDir} is the directory holding the launcher binary; any other name is an environment variable. Both separators are accepted.",
+        },
+        "launchTemplate": {
+            "type": "string",
+            // Every `${` closed around `account.` and a launch value name.
+            "pattern": r"^(?:[^$]|\$(?!\{)|\$\{account\.[A-Za-z0-9_-]+\})*$",
+            "description": "A launch argument or variable. ${account.<name>} is a value of the account launched: id, label, or one stored on it.",
+        },
+        "pathSpec": {
+            "oneOf": [
+                { "$ref": "#/$defs/pathTemplate" },
+                {
+                    "type": "array",
+                    "minItems": 1,
+                    "items": { "$ref": "#/$defs/pathTemplate" },
+                    "description": "Candidates tried in order: the first that exists wins, and the first one listed is used when none do.",
+                },
+            ],
+        },
+        "registryHive": { "enum": names(&[RegistryHive::CurrentUser, RegistryHive::LocalMachine]) },
+        "registryKey": {
+            "type": "string",
+            "pattern": r"^(?=.*\S)(?!\\)(?!.*\\$)(?!.*\\\\)(?!(?:.*\\)?\.\.(?:\\|$))[^/]+$",
+            "description": "A backslash-separated key below the hive.",
+        },
+        "registryValue": {
+            "type": "string",
+            "pattern": r"\S",
+        },
+        "bareName": {
+            "type": "string",
+            "pattern": r"^(?=.*\S)[^/\\]+$",
+            "description": "A file or process name, without any directory.",
+        },
+        "snapshotName": {
+            "type": "string",
+            "pattern": r"^(?!\.\.?$)(?!\s)(?!.*\s$)[^/\\]+$",
+            "description": "File or directory name inside the account's snapshot directory. Unique within a profile, ignoring case.",
+        },
+        "jsonPointer": {
+            "type": "string",
+            "pattern": "^(?:/(?:[^~]|~[01])*)+$",
+            "description": "RFC 6901 pointer, e.g. /auth/refreshToken.",
+        },
+        "iniKey": {
+            "type": "string",
+            "pattern": r"^(?![\[;#])(?!\s)(?!.*\s$)[^=\r\n]+$",
+        },
+        "iniSection": {
+            "type": "string",
+            "pattern": r"^(?!\s)(?!.*\s$)[^\]\r\n]*$",
+            "description": "Empty for the part of the file before any section header.",
+        },
+        "registryRoot": object(&["root", "key"], json!({
+            "root": { "$ref": "#/$defs/registryHive" },
+            "key": { "$ref": "#/$defs/registryKey" },
+        })),
+        "osProfile": object(&["roots", "detect", "identity"], json!({
+            "roots": object(&[], json!({
+                "files": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/pathTemplate" },
+                    "description": "Every path read or written as state must sit under one of these.",
+                },
+                "registry": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/registryRoot" },
+                    "description": "Every registry value read or written must sit under one of these keys.",
+                },
+            })),
+            "detect": with(
+                object(&[], json!({
+                    "executableResolves": { "type": "boolean", "default": false },
+                    "pathExists": { "type": "array", "items": { "$ref": "#/$defs/pathTemplate" } },
+                })),
+                "anyOf",
+                json!([
+                    { "required": ["executableResolves"], "properties": { "executableResolves": { "const": true } } },
+                    { "required": ["pathExists"], "properties": { "pathExists": { "minItems": 1 } } },
+                ]),
+            ),
+            "executable": { "$ref": "#/$defs/executable" },
+            "identity": { "$ref": "#/$defs/identity" },
+            "state": { "$ref": "#/$defs/state" },
+            "close": object(&[], json!({
+                "processes": { "type": "array", "items": { "$ref": "#/$defs/bareName" } },
+                "timeoutMs": { "type": "integer", "minimum": 0, "default": close.timeout_ms },
+                "settleMs": { "type": "integer", "minimum": 0, "default": close.settle_ms },
+                "beforeCapture": { "type": "boolean", "default": false },
+                "strategies": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/closeStrategy" },
+                    "description": "Tried in order before the processes are terminated.",
+                },
+                "refuseWhileRunning": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/bareName" },
+                    "description": "A switch or a setup is refused while any of these runs.",
+                },
+            })),
+            "launch": object(&[], json!({
+                "args": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/launchTemplate" },
+                    "description": "${account.<name>} reads a value of the account launched. An argument naming a value it does not have is left out.",
+                },
+                "argsOnlyFor": {
+                    "type": "string",
+                    "pattern": r"^[^/\\]*$",
+                    "description": "Pass args only when the resolved binary carries this name.",
+                },
+                "workingDirectoryIsInstallDir": { "type": "boolean", "default": true },
+                "env": {
+                    "type": "object",
+                    "propertyNames": { "pattern": "^[A-Za-z_][A-Za-z0-9_]*$" },
+                    "additionalProperties": { "$ref": "#/$defs/launchTemplate" },
+                    "description": "Environment for the launched process, expanded like args.",
+                },
+            })),
+            "setup": object(&[], json!({
+                "trigger": { "type": "array", "items": { "$ref": "#/$defs/condition" } },
+                "confirm": { "type": "array", "items": { "$ref": "#/$defs/condition" } },
+                "displayNameFromId": { "type": "boolean", "default": false },
+                "missingSnapshotHint": { "type": "string" },
+                "adoptSignedIn": { "type": "boolean", "default": false },
+            })),
+            "wine": object(&["prefix"], json!({
+                "prefix": {
+                    "$ref": "#/$defs/pathSpec",
+                    "description": "The directory holding drive_c. ${installDir} is not available here.",
+                },
+                "user": {
+                    "type": "string",
+                    "pattern": r"^(?!\.\.?$)(?!\s)(?!.*\s$)[^/\\]*$",
+                    "description": "The Windows user inside the prefix. Empty picks whichever the prefix has.",
+                },
+                "runner": object(&[], json!({
+                    "command": { "$ref": "#/$defs/pathTemplate", "default": runner.command },
+                    "args": { "type": "array", "items": { "type": "string" } },
+                    "env": {
+                        "type": "object",
+                        "propertyNames": { "pattern": "^[A-Za-z_][A-Za-z0-9_]*$" },
+                        "additionalProperties": { "$ref": "#/$defs/pathTemplate" },
+                    },
+                })),
+            })),
+        })),
+        "executable": object(&["fileName", "candidates"], json!({
+            "fileName": { "$ref": "#/$defs/bareName" },
+            "candidates": {
+                "type": "array",
+                "minItems": 1,
+                "items": { "$ref": "#/$defs/executableCandidate" },
+            },
+            "relativeProbes": {
+                "type": "array",
+                "items": {
+                    "type": "string",
+                    "pattern": r"^(?![/\\])(?!\s)(?!.*\s$)(?!.*:)(?!(?:.*[/\\])?(?:\.\.)?(?:[/\\]|$))[\s\S]+$",
+                },
+            },
+            "selectFilter": { "type": "string", "default": schema::default_exe_filter() },
+        })),
+        "executableCandidate": tagged(vec![
+            variant("path", &["template"], json!({
+                "template": {
+                    "$ref": "#/$defs/pathTemplate",
+                    "description": "${installDir} is what this candidate resolves, so it is not available here.",
+                },
+            })),
+            variant("registry", &["root", "key", "value"], json!({
+                "root": { "$ref": "#/$defs/registryHive" },
+                "key": { "$ref": "#/$defs/registryKey" },
+                "value": { "$ref": "#/$defs/registryValue" },
+            })),
+            variant("uninstallEntry", &["displayName"], json!({
+                "displayName": { "$ref": "#/$defs/displayName" },
+                "value": { "$ref": "#/$defs/registryValue", "default": schema::default_install_location() },
+            })),
+            variant("jsonPointer", &["path", "pointer"], json!({
+                "path": {
+                    "$ref": "#/$defs/pathTemplate",
+                    "description": "A JSON file holding the install path. ${installDir} is not available here.",
+                },
+                "pointer": { "$ref": "#/$defs/jsonPointer" },
+            })),
+        ]),
+        "identity": with(
+            object(&["source", "format", "current"], json!({
+                "source": { "$ref": "#/$defs/identitySource" },
+                "format": object(&["charset", "maxLength"], json!({
+                    "charset": {
+                        "enum": names(&Charset::ALL),
+                        "description": "Ids are joined into snapshot paths, so this is a path traversal guard first.",
+                    },
+                    "maxLength": { "type": "integer", "minimum": 1, "maximum": 256 },
+                    "minLength": { "type": "integer", "minimum": 1, "default": schema::default_min_length() },
+                    "lowercase": { "type": "boolean", "default": false },
+                    "invalidMessage": { "type": "string" },
+                })),
+                "current": { "enum": names(&[CurrentSource::Identity, CurrentSource::Config]) },
+                "discovery": { "type": "array", "items": { "$ref": "#/$defs/discovery" } },
+                "blocklistOnForget": { "type": "boolean", "default": false },
+                "fields": { "type": "array", "items": { "$ref": "#/$defs/accountField" } },
+                "labelFormat": {
+                    "$ref": "#/$defs/launchTemplate",
+                    "description": "How an account the user never labelled is named. ${account.<name>} reads its id or one of its fields.",
+                },
+            })),
+            "allOf",
+            json!([
+                {
+                    "if": { "required": ["source"], "properties": { "source": { "properties": { "kind": { "enum": ["registry", "synthetic", "logTail"] } } } } },
+                    "then": { "properties": { "fields": { "maxItems": 0 } } },
+                },
+                {
+                    "if": { "required": ["source"], "properties": { "source": { "properties": { "kind": { "const": "synthetic" } } } } },
+                    "then": { "properties": { "current": { "const": "config" } } },
+                },
+                {
+                    "if": { "required": ["blocklistOnForget"], "properties": { "blocklistOnForget": { "const": true } } },
+                    "then": { "required": ["discovery"], "properties": { "discovery": { "minItems": 1 } } },
+                },
+            ]),
+        ),
+        "accountField": object(&["name", "label"], json!({
+            "name": {
+                "type": "string",
+                "pattern": "^[A-Za-z0-9_-]+$",
+                "not": { "enum": BUILTIN_LAUNCH_VALUES },
+                "description": "What the value is stored under, and read back by as ${account.<name>}.",
+            },
+            "label": {
+                "$ref": "#/$defs/displayName",
+                "description": "The column heading in lists.",
+            },
+            "type": {
+                "enum": names(&FieldKind::ALL),
+                "default": FieldKind::default(),
+                "description": format!(
+                    "A value that does not fit is dropped. Values run to {MAX_FIELD_CHARS} characters, and a path is not shown as a column."
+                ),
+            },
+            "from": {
+                "type": "string",
+                "description": "Where the identity source finds it: a JSON pointer, an INI key of the same section, or a column the query selects. Empty for a native hook, which reports fields by name.",
+            },
+        })),
+        "identitySource": tagged(vec![
+            variant("registry", &["root", "key", "value"], json!({
+                "root": { "$ref": "#/$defs/registryHive" },
+                "key": { "$ref": "#/$defs/registryKey" },
+                "value": { "$ref": "#/$defs/registryValue" },
+            })),
+            variant("synthetic", &[], json!({})),
+            with(
+                variant("logTail", &["path", "lineContains"], json!({
+                    "path": { "$ref": "#/$defs/pathTemplate" },
+                    "tailBytes": { "type": "integer", "minimum": 1, "default": schema::default_tail_bytes() },
+                    "lineContains": { "type": "string", "pattern": r"\S" },
+                    "prefix": { "type": "string" },
+                    "nearWord": { "type": "string" },
+                })),
+                "anyOf",
+                json!([
+                    { "required": ["prefix"], "properties": { "prefix": { "minLength": 1 } } },
+                    { "required": ["nearWord"], "properties": { "nearWord": { "minLength": 1 } } },
+                ]),
+            ),
+            variant("nativeHook", &["name"], json!({
+                "name": {
+                    "enum": hooks::names(),
+                    "description": format!(
+                        "A compiled hook. Only {} may name one.",
+                        NATIVE_HOOK_ALLOWLIST.join(", ")
+                    ),
+                },
+                "paths": {
+                    "type": "object",
+                    "additionalProperties": { "$ref": "#/$defs/pathTemplate" },
+                    "description": "The locations the hook works on, each held to the roots.",
+                },
+            })),
+            variant("jsonPointer", &["path", "pointer"], json!({
+                "path": { "$ref": "#/$defs/pathTemplate" },
+                "pointer": { "$ref": "#/$defs/jsonPointer" },
+                "displayNamePointer": {
+                    "anyOf": [{ "const": "" }, { "$ref": "#/$defs/jsonPointer" }],
+                },
+            })),
+            variant("iniKey", &["path", "key"], json!({
+                "path": { "$ref": "#/$defs/pathTemplate" },
+                "section": { "$ref": "#/$defs/iniSection" },
+                "key": { "$ref": "#/$defs/iniKey" },
+                "displayNameKey": {
+                    "anyOf": [{ "const": "" }, { "$ref": "#/$defs/iniKey" }],
+                },
+            })),
+            variant("sqliteQuery", &["path", "query"], json!({
+                "path": { "$ref": "#/$defs/pathTemplate" },
+                "query": {
+                    "type": "string",
+                    "pattern": r"^\s*(?:[Ss][Ee][Ll][Ee][Cc][Tt]|[Ww][Ii][Tt][Hh])(?:\s[^;]*)?[;\s]*$",
+                    "description": "A single SELECT. The first column is the id, an optional second one the display name.",
+                },
+            })),
+        ]),
+        "discovery": tagged(vec![
+            variant("directoryEntries", &["path"], json!({
+                "path": { "$ref": "#/$defs/pathTemplate" },
+                "entries": {
+                    "enum": names(&[EntryKind::Any, EntryKind::Directories, EntryKind::Files]),
+                    "default": EntryKind::default(),
+                },
+                "stripPrefixes": { "type": "array", "items": { "type": "string", "minLength": 1 } },
+                "stripExtension": { "type": "boolean", "default": false },
+            })),
+            variant("jsonArray", &["path", "idField"], json!({
+                "path": { "$ref": "#/$defs/pathTemplate" },
+                "pointer": {
+                    "anyOf": [{ "const": "" }, { "$ref": "#/$defs/jsonPointer" }],
+                    "default": "",
+                    "description": "The array of remembered accounts. Empty when the whole file is the array.",
+                },
+                "idField": {
+                    "type": "string",
+                    "pattern": r"\S",
+                    "description": "Field of each element holding the account id.",
+                },
+                "displayNameField": {
+                    "type": "string",
+                    "default": "",
+                    "description": "Field of each element holding the name shown for the account. Empty for none.",
+                },
+            })),
+        ]),
+        "state": object(&[], json!({
+            "files": { "type": "array", "items": { "$ref": "#/$defs/fileItem" } },
+            "directories": { "type": "array", "items": { "$ref": "#/$defs/dirItem" } },
+            "registryValues": { "type": "array", "items": { "$ref": "#/$defs/registryItem" } },
+            "keys": { "type": "array", "items": { "$ref": "#/$defs/keyItem" } },
+            "caches": { "type": "array", "items": { "$ref": "#/$defs/pathTemplate" } },
+            "captureWhen": { "type": "array", "items": { "$ref": "#/$defs/condition" } },
+        })),
+        "fileItem": object(&["live", "snapshot"], json!({
+            "live": { "$ref": "#/$defs/pathSpec" },
+            "snapshot": { "$ref": "#/$defs/snapshotName" },
+            "clearOnSetup": { "type": "boolean", "default": false },
+            "snapshotMarker": { "type": "boolean", "default": false },
+            "removeLiveBeforeRestore": { "type": "boolean", "default": false },
+            "clearSnapshotWhenSourceMissing": { "type": "boolean", "default": true },
+        })),
+        "dirItem": object(&["live", "snapshot"], json!({
+            "live": { "$ref": "#/$defs/pathSpec" },
+            "snapshot": { "$ref": "#/$defs/snapshotName" },
+            "clearOnSetup": { "type": "boolean", "default": false },
+            "snapshotMarker": { "type": "boolean", "default": false },
+            "ignoredNames": { "type": "array", "items": { "type": "string" } },
+            "followSymlinks": { "type": "boolean", "default": false },
+        })),
+        "registryItem": object(&["root", "key", "value", "snapshot"], json!({
+            "root": { "$ref": "#/$defs/registryHive" },
+            "key": { "$ref": "#/$defs/registryKey" },
+            "value": { "$ref": "#/$defs/registryValue" },
+            "snapshot": { "$ref": "#/$defs/snapshotName" },
+            "clearOnSetup": { "type": "boolean", "default": false },
+            "snapshotMarker": { "type": "boolean", "default": false },
+            "clearSnapshotWhenSourceMissing": { "type": "boolean", "default": true },
+        })),
+        "keyItem": object(&["live", "snapshot", "select"], json!({
+            "live": { "$ref": "#/$defs/pathSpec" },
+            "snapshot": { "$ref": "#/$defs/snapshotName" },
+            "select": { "$ref": "#/$defs/keySelect" },
+            "clearOnSetup": { "type": "boolean", "default": false },
+            "snapshotMarker": { "type": "boolean", "default": false },
+            "clearSnapshotWhenSourceMissing": { "type": "boolean", "default": true },
+        })),
+        "keySelect": tagged(vec![
+            variant("jsonPointer", &["pointers"], json!({
+                "pointers": { "type": "array", "minItems": 1, "items": { "$ref": "#/$defs/jsonPointer" } },
+            })),
+            variant("iniKey", &["keys"], json!({
+                "keys": {
+                    "type": "array",
+                    "minItems": 1,
+                    "items": object(&["key"], json!({
+                        "section": { "$ref": "#/$defs/iniSection" },
+                        "key": { "$ref": "#/$defs/iniKey" },
+                    })),
+                },
+            })),
+            variant("keyValues", &["paths"], json!({
+                "paths": {
+                    "type": "array",
+                    "minItems": 1,
+                    "items": {
+                        "type": "string",
+                        "pattern": "^[^/\"\\r\\n]+(?:/[^/\"\\r\\n]+)+$",
+                        "description": "A path from the root key down, separated by /.",
+                    },
+                },
+            })),
+        ]),
+        "closeStrategy": tagged(vec![
+            variant("command", &["command"], json!({
+                "command": { "$ref": "#/$defs/pathTemplate" },
+                "args": { "type": "array", "items": { "type": "string" } },
+                "timeoutMs": { "$ref": "#/$defs/strategyTimeout" },
+            })),
+            variant("uri", &["uri"], json!({
+                "uri": {
+                    "type": "string",
+                    "pattern": r"^(?![hH][tT][tT][pP][sS]?:|[fF][iI][lL][eE]:)[A-Za-z][A-Za-z0-9+.-]*:\S+$",
+                },
+                "timeoutMs": { "$ref": "#/$defs/strategyTimeout" },
+            })),
+            variant("httpPost", &["url"], json!({
+                "url": {
+                    "type": "string",
+                    "pattern": r"^https?://(127\.0\.0\.1|localhost|\[::1\])([:/?#]|$)",
+                },
+                "body": { "type": "string" },
+                "timeoutMs": { "$ref": "#/$defs/strategyTimeout" },
+            })),
+            variant("closeWindow", &[], json!({
+                "timeoutMs": { "$ref": "#/$defs/strategyTimeout" },
+            })),
+            variant("nativeHook", &[], json!({
+                "timeoutMs": { "$ref": "#/$defs/strategyTimeout" },
+            })),
+        ]),
+        "strategyTimeout": { "type": "integer", "minimum": 1, "default": 5000 },
+        "condition": tagged(vec![
+            variant("newIdentity", &[], json!({})),
+            variant("identityPresent", &[], json!({})),
+            variant("pathNonEmpty", &["path"], json!({
+                "path": { "$ref": "#/$defs/pathSpec" },
+                "recursive": { "type": "boolean", "default": false },
+            })),
+            variant("pathFresh", &["path", "windowMs"], json!({
+                "path": { "$ref": "#/$defs/pathSpec" },
+                "windowMs": { "type": "integer", "minimum": 1 },
+            })),
+            variant("anyOf", &["conditions"], json!({
+                "conditions": { "type": "array", "minItems": 1, "items": { "$ref": "#/$defs/condition" } },
+            })),
+            variant("sinceStart", &["ms"], json!({
+                "ms": { "type": "integer", "minimum": 1 },
+            })),
+        ]),
+    })
+}
+
+/// A closed object: unknown fields are refused, as the loader refuses them.
+fn object(required: &[&str], properties: Value) -> Value {
+    let mut out = Map::new();
+    out.insert("type".into(), json!("object"));
+    if !required.is_empty() {
+        out.insert("required".into(), json!(required));
+    }
+    out.insert("additionalProperties".into(), json!(false));
+    out.insert("properties".into(), properties);
+    Value::Object(out)
+}
+
+/// One variant of an enum tagged by `kind`.
+fn variant(kind: &str, required: &[&str], properties: Value) -> Value {
+    let mut properties = properties;
+    if let Some(map) = properties.as_object_mut() {
+        map.insert("kind".into(), json!({ "const": kind }));
+    }
+    let mut with_kind = vec!["kind"];
+    with_kind.extend_from_slice(required);
+    object(&with_kind, properties)
+}
+
+/// Every variant of an enum tagged by `kind`. The discriminator is checked
+/// first so an editor reports one mismatch, not one per variant.
+fn tagged(variants: Vec<Value>) -> Value {
+    let kinds: Vec<Value> = variants
+        .iter()
+        .map(|variant| variant["properties"]["kind"]["const"].clone())
+        .collect();
+    json!({
+        "type": "object",
+        "required": ["kind"],
+        "properties": { "kind": { "enum": kinds } },
+        "oneOf": variants,
+    })
+}
+
+fn with(mut schema: Value, keyword: &str, value: Value) -> Value {
+    if let Some(map) = schema.as_object_mut() {
+        map.insert(keyword.into(), value);
+    }
+    schema
+}
+
+/// The names serde gives `values`, so a rename in the types renames them here.
+fn names<T: Serialize>(values: &[T]) -> Vec<Value> {
+    values
+        .iter()
+        .map(|value| serde_json::to_value(value).unwrap_or(Value::Null))
+        .collect()
+}
+
+fn os_names() -> Vec<Value> {
+    names(&[Os::Windows, Os::Macos, Os::Linux])
+}
+
+fn render(value: &Value) -> String {
+    let mut text = serde_json::to_string_pretty(value).unwrap_or_default();
+    text.push('\n');
+    text
+}
+
+/// Write the generated schema into `docs/`. Used by the test that keeps it
+/// honest, and by the CLI so a contributor can regenerate it.
+pub fn write_docs(docs_dir: &Path) -> Result<Vec<String>, String> {
+    let path = docs_dir.join(SCHEMA_FILE_NAME);
+    std::fs::write(&path, render(&descriptor_schema()))
+        .map_err(|reason| format!("Could not write {}: {reason}", path.display()))?;
+    Ok(vec![SCHEMA_FILE_NAME.to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn docs_dir() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join("..")
+            .join("docs")
+    }
+
+    /// Same contract as the log schema: `ACCSHIFT_UPDATE_DOCS=1` regenerates
+    /// the file instead of failing, and the comparison is on the parsed value
+    /// so the repository formatter keeps the layout.
+    #[test]
+    fn generated_docs_are_up_to_date() {
+        if std::env::var("ACCSHIFT_UPDATE_DOCS").as_deref() == Ok("1") {
+            write_docs(&docs_dir()).expect("regenerate the published schema");
+            return;
+        }
+
+        let path = docs_dir().join(SCHEMA_FILE_NAME);
+        let on_disk: Value = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or(Value::Null);
+        assert_eq!(
+            on_disk,
+            descriptor_schema(),
+            "{SCHEMA_FILE_NAME} is stale. Regenerate it with `accshift descriptors schema --write docs`, \
+             or `ACCSHIFT_UPDATE_DOCS=1 cargo test -p accshift-core`.",
+        );
+    }
+
+    /// Follows `value` through `node`, recording every field the schema does
+    /// not declare and every `kind` it does not know.
+    fn undeclared(value: &Value, node: &Value, root: &Value, at: &str, out: &mut Vec<String>) {
+        if let Some(reference) = node["$ref"].as_str() {
+            let target = root
+                .pointer(reference.trim_start_matches('#'))
+                .expect("every $ref resolves");
+            undeclared(value, target, root, at, out);
+        }
+        for part in node["allOf"].as_array().into_iter().flatten() {
+            undeclared(value, part, root, at, out);
+        }
+        if let (Some(variants), Some(kind)) = (node["oneOf"].as_array(), value.get("kind")) {
+            match variants
+                .iter()
+                .find(|variant| &variant["properties"]["kind"]["const"] == kind)
+            {
+                Some(variant) => undeclared(value, variant, root, at, out),
+                None => out.push(format!("{at}.kind = {kind}")),
+            }
+        }
+        if let (Some(fields), Some(properties)) =
+            (value.as_object(), node["properties"].as_object())
+        {
+            for (key, field) in fields {
+                let at = format!("{at}.{key}");
+                match properties.get(key) {
+                    Some(child) => undeclared(field, child, root, &at, out),
+                    None if node["additionalProperties"] == json!(false) => out.push(at),
+                    None => undeclared(field, &node["additionalProperties"], root, &at, out),
+                }
+            }
+        }
+        if let (Some(items), Some(schema)) = (value.as_array(), node.get("items")) {
+            for (index, item) in items.iter().enumerate() {
+                undeclared(item, schema, root, &format!("{at}[{index}]"), out);
+            }
+        }
+    }
+
+    #[test]
+    fn every_field_the_shipped_descriptors_use_is_declared() {
+        let schema = descriptor_schema();
+        let os = &schema["else"]["properties"]["os"];
+        for (name, body) in super::super::EMBEDDED {
+            let value: Value = serde_json::from_str(body).expect("valid JSON");
+            let mut out = Vec::new();
+            undeclared(&value, &schema, &schema, "", &mut out);
+            undeclared(&value["os"], os, &schema, ".os", &mut out);
+            assert!(out.is_empty(), "{name} uses undeclared {out:?}");
+        }
+    }
+
+    #[test]
+    fn the_walk_reports_a_misspelt_field_and_an_unknown_kind() {
+        let schema = descriptor_schema();
+        let profile = json!({
+            "roots": { "files": ["${APPDATA}/Demo"] },
+            "detect": { "pathExist": [] },
+            "identity": { "source": { "kind": "registryy" } },
+        });
+        let mut out = Vec::new();
+        undeclared(
+            &profile,
+            &schema["$defs"]["osProfile"],
+            &schema,
+            "",
+            &mut out,
+        );
+        assert_eq!(
+            out,
+            vec![".detect.pathExist", ".identity.source.kind = \"registryy\""]
+        );
+    }
+
+    #[test]
+    fn the_hook_rules_come_from_the_code() {
+        let schema = descriptor_schema();
+        let hook = schema["$defs"]["identitySource"]["oneOf"]
+            .as_array()
+            .expect("variants")
+            .iter()
+            .find(|variant| variant["properties"]["kind"]["const"] == "nativeHook")
+            .expect("a nativeHook variant");
+        assert_eq!(hook["properties"]["name"]["enum"], json!(hooks::names()));
+        assert_eq!(
+            schema["allOf"][0]["then"]["else"]["properties"]["id"]["enum"],
+            json!(NATIVE_HOOK_ALLOWLIST)
+        );
+        assert_eq!(
+            schema["$defs"]["identity"]["properties"]["format"]["properties"]["charset"]["enum"],
+            json!(["digits", "hex", "alphanumeric", "uuid", "slug"])
+        );
+    }
+}
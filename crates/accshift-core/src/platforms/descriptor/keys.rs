@@ -0,0 +1,914 @@
+//! Key-level state: a few keys inside a file the launcher shares with
+//! settings that are not the session.
+//!
+//! A `files` entry owns a whole file, which is wrong for a launcher that keeps
+//! its refresh token next to the user's window size and language: restoring
+//! the file would hand the incoming account the outgoing account's settings.
+//! A `keys` entry names the keys that are the session, and only those travel.
+//!
+//! Everything here works on text and never touches the disk; the engine reads
+//! the live file, hands it over, and writes back what comes out. INI files are
+//! edited line by line and KeyValues files through a lossless tree, so a
+//! comment, a blank line or an odd indent the launcher wrote survives a
+//! switch. JSON is parsed only to check it and find where each selected value
+//! sits; the new value is spliced into the original text, so the other keys
+//! keep their order and the file its layout.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::schema::{IniKey, KeySelect};
+use crate::platforms::steam::keyvalues::Document;
+
+/// What a capture found, keyed by [`labels`]. A key that was absent is
+/// recorded as such, so restoring removes one the incoming account never had
+/// instead of leaving the outgoing account's in place.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Captured {
+    #[serde(default)]
+    pub present: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub absent: Vec<String>,
+}
+
+impl Captured {
+    /// True when none of the selected keys was in the file.
+    pub fn is_empty(&self) -> bool {
+        self.present.is_empty()
+    }
+}
+
+/// One readable name per selected key, used as the snapshot's keys and as the
+/// dry run's target after the file path.
+pub fn labels(select: &KeySelect) -> Vec<String> {
+    match select {
+        KeySelect::JsonPointer { pointers } => pointers.clone(),
+        KeySelect::IniKey { keys } => keys.iter().map(ini_label).collect(),
+        KeySelect::KeyValues { paths } => paths.clone(),
+    }
+}
+
+/// Reads the selected keys out of `content`. An empty file holds none of them.
+pub fn capture(content: &str, select: &KeySelect) -> Result<Captured, String> {
+    let mut captured = Captured::default();
+    let mut record = |label: String, value: Option<Value>| match value {
+        Some(value) => {
+            captured.present.insert(label, value);
+        }
+        None => captured.absent.push(label),
+    };
+    match select {
+        KeySelect::JsonPointer { pointers } => {
+            let document = parse_json(content)?;
+            for pointer in pointers {
+                record(pointer.clone(), document.pointer(pointer).cloned());
+            }
+        }
+        KeySelect::IniKey { keys } => {
+            let lines = Lines::split(content);
+            for key in keys {
+                let value = ini_locate(&lines.lines, key)
+                    .line
+                    .map(|index| Value::String(ini_value(&lines.lines[index]).to_string()));
+                record(ini_label(key), value);
+            }
+        }
+        KeySelect::KeyValues { paths } => {
+            let document = parse_kv(content)?;
+            for path in paths {
+                let value = document
+                    .get(&kv_segments(path))
+                    .map(|value| Value::String(value.to_string()));
+                record(path.clone(), value);
+            }
+        }
+    }
+    Ok(captured)
+}
+
+/// Writes `captured` back into `content`: keys it holds are set, keys it saw
+/// absent are removed, and a key it says nothing about is left alone.
+pub fn apply(content: &str, select: &KeySelect, captured: &Captured) -> Result<String, String> {
+    let wanted = |label: &str| -> Option<Option<&Value>> {
+        if let Some(value) = captured.present.get(label) {
+            Some(Some(value))
+        } else if captured.absent.iter().any(|absent| absent == label) {
+            Some(None)
+        } else {
+            None
+        }
+    };
+
+    match select {
+        KeySelect::JsonPointer { pointers } => {
+            let mut document = parse_json(content)?;
+            if !content.trim().is_empty() {
+                let mut text = content.to_string();
+                for pointer in pointers {
+                    match wanted(pointer) {
+                        Some(Some(value)) => json_splice_set(&mut text, pointer, value)?,
+                        Some(None) => json_splice_remove(&mut text, pointer)?,
+                        None => {}
+                    }
+                }
+                return Ok(text);
+            }
+            // A new file has no layout to keep.
+            for pointer in pointers {
+                if let Some(Some(value)) = wanted(pointer) {
+                    json_set(&mut document, pointer, value.clone())?;
+                }
+            }
+            if document.as_object().is_some_and(serde_json::Map::is_empty) {
+                return Ok(content.to_string());
+            }
+            let mut text = serde_json::to_string_pretty(&document)
+                .map_err(|e| format!("Could not serialize JSON: {e}"))?;
+            text.push('\n');
+            Ok(text)
+        }
+        KeySelect::IniKey { keys } => {
+            let mut lines = Lines::split(content);
+            for key in keys {
+                match wanted(&ini_label(key)) {
+                    Some(Some(value)) => ini_set(&mut lines.lines, key, &scalar(value)?)?,
+                    Some(None) => {
+                        if let Some(index) = ini_locate(&lines.lines, key).line {
+                            lines.lines.remove(index);
+                        }
+                    }
+                    None => {}
+                }
+            }
+            Ok(lines.join())
+        }
+        KeySelect::KeyValues { paths } => {
+            let mut document = parse_kv(content)?;
+            for path in paths {
+                let segments = kv_segments(path);
+                match wanted(path) {
+                    Some(Some(value)) => document
+                        .set(&segments, &scalar(value)?)
+                        .map_err(|e| format!("KeyValues path `{path}`: {e}"))?,
+                    Some(None) => {
+                        document.remove(&segments);
+                    }
+                    None => {}
+                }
+            }
+            Ok(document.to_string())
+        }
+    }
+}
+
+/// Removes every selected key from `content`, for a setup that starts from a
+/// signed-out launcher.
+pub fn remove(content: &str, select: &KeySelect) -> Result<String, String> {
+    let captured = Captured {
+        present: BTreeMap::new(),
+        absent: labels(select),
+    };
+    apply(content, select, &captured)
+}
+
+// ---------------------------------------------------------------------------
+// Lines
+// ---------------------------------------------------------------------------
+
+/// A text file as lines, remembering the line ending and whether the last
+/// line had one, so joining it back changes nothing that was not edited.
+struct Lines {
+    lines: Vec<String>,
+    newline: &'static str,
+    trailing: bool,
+}
+
+impl Lines {
+    fn split(content: &str) -> Self {
+        let newline = if content.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        Self {
+            lines: content.lines().map(str::to_string).collect(),
+            newline,
+            trailing: content.is_empty() || content.ends_with('\n'),
+        }
+    }
+
+    fn join(&self) -> String {
+        let mut text = self.lines.join(self.newline);
+        if self.trailing && !self.lines.is_empty() {
+            text.push_str(self.newline);
+        }
+        text
+    }
+}
+
+/// The text an INI or KeyValues key is set to. Anything spanning lines could
+/// write a key of its own in an INI file, so it is refused rather than
+/// escaped; KeyValues refuses it too, as launchers never store one.
+fn scalar(value: &Value) -> Result<String, String> {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    if text.contains(['\n', '\r']) {
+        return Err("Refusing to write a value spanning several lines".into());
+    }
+    Ok(text)
+}
+
+// ---------------------------------------------------------------------------
+// JSON
+// ---------------------------------------------------------------------------
+
+fn parse_json(content: &str) -> Result<Value, String> {
+    if content.trim().is_empty() {
+        return Ok(Value::Object(serde_json::Map::new()));
+    }
+    serde_json::from_str(content).map_err(|e| format!("Could not parse JSON: {e}"))
+}
+
+/// The unescaped reference tokens of an RFC 6901 pointer.
+fn pointer_tokens(pointer: &str) -> Vec<String> {
+    pointer
+        .split('/')
+        .skip(1)
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+/// Sets the value at `pointer`, creating the objects leading to it. Arrays are
+/// walked but never grown: an index past the end means the document is not
+/// the shape the descriptor expects.
+fn json_set(document: &mut Value, pointer: &str, value: Value) -> Result<(), String> {
+    let tokens = pointer_tokens(pointer);
+    let Some((last, parents)) = tokens.split_last() else {
+        return Err(format!("Empty JSON pointer `{pointer}`"));
+    };
+    let mut node = document;
+    for token in parents {
+        node = match node {
+            Value::Object(map) => map
+                .entry(token.clone())
+                .or_insert_with(|| Value::Object(serde_json::Map::new())),
+            Value::Array(items) => token
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| items.get_mut(index))
+                .ok_or_else(|| format!("JSON pointer `{pointer}` runs past an array"))?,
+            _ => return Err(format!("JSON pointer `{pointer}` runs through a scalar")),
+        };
+    }
+    match node {
+        Value::Object(map) => {
+            map.insert(last.clone(), value);
+            Ok(())
+        }
+        Value::Array(items) => {
+            let slot = last
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| items.get_mut(index))
+                .ok_or_else(|| format!("JSON pointer `{pointer}` runs past an array"))?;
+            *slot = value;
+            Ok(())
+        }
+        _ => Err(format!("JSON pointer `{pointer}` runs through a scalar")),
+    }
+}
+
+/// Where a JSON value sits in the text, and where its members or items do.
+struct Span {
+    start: usize,
+    end: usize,
+    kind: SpanKind,
+    entries: Vec<Entry>,
+}
+
+#[derive(PartialEq)]
+enum SpanKind {
+    Object,
+    Array,
+    Scalar,
+}
+
+/// An object member or array item. For an item `key` is `None` and `start`
+/// and `key_end` are where its value starts.
+struct Entry {
+    key: Option<String>,
+    start: usize,
+    key_end: usize,
+    value: Span,
+}
+
+/// Walks text `serde_json` already accepted, recording spans. It checks only
+/// what it needs to find its way, so it is never the one to reject a file.
+struct Scanner<'a> {
+    text: &'a [u8],
+    at: usize,
+}
+
+impl Scanner<'_> {
+    fn scan(text: &str) -> Result<Span, String> {
+        Scanner {
+            text: text.as_bytes(),
+            at: 0,
+        }
+        .value()
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.text.get(self.at) {
+            self.at += 1;
+        }
+        self.text.get(self.at).copied()
+    }
+
+    fn value(&mut self) -> Result<Span, String> {
+        let (kind, close) = match self.peek() {
+            Some(b'{') => (SpanKind::Object, b'}'),
+            Some(b'[') => (SpanKind::Array, b']'),
+            Some(b'"') => {
+                let start = self.at;
+                self.string()?;
+                return Ok(self.span(start, SpanKind::Scalar, Vec::new()));
+            }
+            Some(_) => {
+                let start = self.at;
+                while !matches!(
+                    self.text.get(self.at),
+                    None | Some(b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r')
+                ) {
+                    self.at += 1;
+                }
+                return Ok(self.span(start, SpanKind::Scalar, Vec::new()));
+            }
+            None => return Err("Unexpected end of JSON".into()),
+        };
+        let start = self.at;
+        self.at += 1;
+        let mut entries = Vec::new();
+        loop {
+            match self.peek() {
+                Some(byte) if byte == close => {
+                    self.at += 1;
+                    return Ok(self.span(start, kind, entries));
+                }
+                Some(b',') => self.at += 1,
+                Some(_) if kind == SpanKind::Object => {
+                    let key_start = self.at;
+                    let key = self.string()?;
+                    let key_end = self.at;
+                    if self.peek() != Some(b':') {
+                        return Err("Expected `:` after a JSON key".into());
+                    }
+                    self.at += 1;
+                    entries.push(Entry {
+                        key: Some(key),
+                        start: key_start,
+                        key_end,
+                        value: self.value()?,
+                    });
+                }
+                Some(_) => {
+                    let value = self.value()?;
+                    entries.push(Entry {
+                        key: None,
+                        start: value.start,
+                        key_end: value.start,
+                        value,
+                    });
+                }
+                None => return Err("Unexpected end of JSON".into()),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let start = self.at;
+        self.at += 1;
+        loop {
+            match self.text.get(self.at) {
+                Some(b'\\') => self.at += 2,
+                Some(b'"') => break,
+                Some(_) => self.at += 1,
+                None => return Err("Unterminated JSON string".into()),
+            }
+        }
+        self.at += 1;
+        serde_json::from_slice(&self.text[start..self.at])
+            .map_err(|e| format!("Could not parse JSON: {e}"))
+    }
+
+    fn span(&self, start: usize, kind: SpanKind, entries: Vec<Entry>) -> Span {
+        Span {
+            start,
+            end: self.at,
+            kind,
+            entries,
+        }
+    }
+}
+
+impl Span {
+    /// The member named `token`, the last one as `serde_json` reads it when a
+    /// key repeats, or the item at index `token`.
+    fn entry(&self, token: &str) -> Option<(usize, &Entry)> {
+        match self.kind {
+            SpanKind::Object => self
+                .entries
+                .iter()
+                .enumerate()
+                .rev()
+                .find(|(_, entry)| entry.key.as_deref() == Some(token)),
+            SpanKind::Array => token
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| Some((index, self.entries.get(index)?))),
+            SpanKind::Scalar => None,
+        }
+    }
+}
+
+fn to_json(value: &impl Serialize) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|e| format!("Could not serialize JSON: {e}"))
+}
+
+/// Sets the value at `pointer` in the text itself. A value already there is
+/// replaced where it stands; a missing member, with the objects leading to it,
+/// is added after the object's last member, laid out like it. Arrays are
+/// walked but never grown, as in [`json_set`].
+fn json_splice_set(text: &mut String, pointer: &str, value: &Value) -> Result<(), String> {
+    let tokens = pointer_tokens(pointer);
+    if tokens.is_empty() {
+        return Err(format!("Empty JSON pointer `{pointer}`"));
+    }
+    let root = Scanner::scan(text)?;
+    let mut node = &root;
+    for (depth, token) in tokens.iter().enumerate() {
+        if node.kind == SpanKind::Scalar {
+            return Err(format!("JSON pointer `{pointer}` runs through a scalar"));
+        }
+        match node.entry(token) {
+            Some((_, entry)) => node = &entry.value,
+            None if node.kind == SpanKind::Array => {
+                return Err(format!("JSON pointer `{pointer}` runs past an array"));
+            }
+            None => {
+                let mut nested = value.clone();
+                for inner in tokens[depth + 1..].iter().rev() {
+                    let mut map = serde_json::Map::new();
+                    map.insert(inner.clone(), nested);
+                    nested = Value::Object(map);
+                }
+                return json_insert_member(text, node, token, &nested);
+            }
+        }
+    }
+    let current = &text[node.start..node.end];
+    if serde_json::from_str::<Value>(current).ok().as_ref() != Some(value) {
+        text.replace_range(node.start..node.end, &to_json(value)?);
+    }
+    Ok(())
+}
+
+fn json_insert_member(
+    text: &mut String,
+    object: &Span,
+    key: &str,
+    value: &Value,
+) -> Result<(), String> {
+    let Some(last) = object.entries.last() else {
+        let mut map = serde_json::Map::new();
+        map.insert(key.to_string(), value.clone());
+        text.replace_range(object.start..object.end, &to_json(&map)?);
+        return Ok(());
+    };
+    // What separates the last member from the one before it, or from the
+    // brace when it is the only one: a newline and indent, or a space.
+    let before = match object.entries.len() {
+        1 => object.start + 1,
+        n => object.entries[n - 2].value.end,
+    };
+    let indent = text[before..last.start]
+        .rsplit(',')
+        .next()
+        .unwrap_or_default();
+    let colon = &text[last.key_end..last.value.start];
+    let member = format!(",{indent}{}{colon}{}", to_json(&key)?, to_json(value)?);
+    text.insert_str(last.value.end, &member);
+    Ok(())
+}
+
+/// Removes the value at `pointer` from the text with the comma that joined it
+/// to its neighbours. A pointer to nothing leaves the text alone.
+fn json_splice_remove(text: &mut String, pointer: &str) -> Result<(), String> {
+    let tokens = pointer_tokens(pointer);
+    let Some((last, parents)) = tokens.split_last() else {
+        return Ok(());
+    };
+    let root = Scanner::scan(text)?;
+    let mut parent = &root;
+    for token in parents {
+        match parent.entry(token) {
+            Some((_, entry)) => parent = &entry.value,
+            None => return Ok(()),
+        }
+    }
+    let Some((index, entry)) = parent.entry(last) else {
+        return Ok(());
+    };
+    let range = if index > 0 {
+        parent.entries[index - 1].value.end..entry.value.end
+    } else if let Some(next) = parent.entries.get(1) {
+        entry.start..next.start
+    } else {
+        parent.start + 1..parent.end - 1
+    };
+    text.replace_range(range, "");
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// INI
+// ---------------------------------------------------------------------------
+
+fn ini_label(key: &IniKey) -> String {
+    if key.section.is_empty() {
+        key.key.clone()
+    } else {
+        format!("[{}] {}", key.section, key.key)
+    }
+}
+
+/// Where a key is, and where it would go if it is not there.
+struct IniSpot {
+    /// The line holding the key.
+    line: Option<usize>,
+    /// Just past the section's last key, when the section exists.
+    section_end: Option<usize>,
+}
+
+/// Finds `key` in its section, matching both names without regard to case,
+/// as the Windows profile API does. The unnamed section is everything before
+/// the first header, and always exists.
+fn ini_locate(lines: &[String], key: &IniKey) -> IniSpot {
+    let mut spot = IniSpot {
+        line: None,
+        section_end: key.section.is_empty().then_some(0),
+    };
+    let mut inside = key.section.is_empty();
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if let Some(name) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once(']'))
+            .map(|(name, _)| name.trim())
+        {
+            inside = name.eq_ignore_ascii_case(&key.section);
+            if inside && spot.section_end.is_none() {
+                spot.section_end = Some(index + 1);
+            }
+            continue;
+        }
+        if !inside || trimmed.is_empty() || trimmed.starts_with([';', '#']) {
+            continue;
+        }
+        spot.section_end = Some(index + 1);
+        if let Some((name, _)) = trimmed.split_once('=') {
+            if spot.line.is_none() && name.trim().eq_ignore_ascii_case(&key.key) {
+                spot.line = Some(index);
+            }
+        }
+    }
+    spot
+}
+
+fn ini_value(line: &str) -> &str {
+    line.split_once('=').map_or("", |(_, value)| value.trim())
+}
+
+/// Sets `key`, keeping the key's spelling and the spacing around `=` on a line
+/// that already holds it.
+fn ini_set(lines: &mut Vec<String>, key: &IniKey, value: &str) -> Result<(), String> {
+    let spot = ini_locate(lines, key);
+    if let Some(index) = spot.line {
+        let line = &lines[index];
+        let Some(equals) = line.find('=') else {
+            return Err(format!("INI line for `{}` has no `=`", ini_label(key)));
+        };
+        let rest = &line[equals + 1..];
+        let keep = equals + 1 + (rest.len() - rest.trim_start().len());
+        lines[index] = format!("{}{value}", &line[..keep]);
+        return Ok(());
+    }
+    let entry = format!("{}={value}", key.key);
+    match spot.section_end {
+        Some(at) => lines.insert(at, entry),
+        None => {
+            if lines.last().is_some_and(|last| !last.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{}]", key.section));
+            lines.push(entry);
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Valve KeyValues
+// ---------------------------------------------------------------------------
+
+fn kv_segments(path: &str) -> Vec<&str> {
+    path.split('/').collect()
+}
+
+fn parse_kv(content: &str) -> Result<Document, String> {
+    Document::parse(content).map_err(|e| format!("Could not parse KeyValues: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pointers(list: &[&str]) -> KeySelect {
+        KeySelect::JsonPointer {
+            pointers: list.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    fn ini(list: &[(&str, &str)]) -> KeySelect {
+        KeySelect::IniKey {
+            keys: list
+                .iter()
+                .map(|(section, key)| IniKey {
+                    section: section.to_string(),
+                    key: key.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn kv(list: &[&str]) -> KeySelect {
+        KeySelect::KeyValues {
+            paths: list.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    const INI: &str = "; written by the launcher\r\n\
+                       [General]\r\n\
+                       Language = en\r\n\
+                       \r\n\
+                       [Auth]\r\n\
+                       RefreshToken = aaa\r\n\
+                       WindowWidth=1280\r\n";
+
+    const VDF: &str = "\"UserLocalConfigStore\"\n\
+                       {\n\
+                       \t\"friends\"\n\
+                       \t{\n\
+                       \t\t\"PersonaName\"\t\t\"Alice\"\n\
+                       \t\t\"DoNotDisturb\"\t\t\"0\"\n\
+                       \t}\n\
+                       \t\"system\"\n\
+                       \t{\n\
+                       \t\t\"EnableGameOverlay\"\t\t\"1\"\n\
+                       \t}\n\
+                       }\n";
+
+    #[test]
+    fn a_json_capture_moves_only_the_pointed_keys_between_files() {
+        let select = pointers(&["/auth/token", "/auth/user~1id"]);
+        let outgoing = r#"{"auth":{"token":"old","user/id":7},"window":{"w":800}}"#;
+        let incoming = r#"{"auth":{"token":"new","user/id":9},"window":{"w":1920}}"#;
+
+        let captured = capture(incoming, &select).unwrap();
+        let restored: Value =
+            serde_json::from_str(&apply(outgoing, &select, &captured).unwrap()).unwrap();
+
+        assert_eq!(restored["auth"]["token"], "new");
+        assert_eq!(restored["auth"]["user/id"], 9);
+        assert_eq!(restored["window"]["w"], 800, "unselected keys stay put");
+    }
+
+    #[test]
+    fn a_key_captured_absent_is_removed_on_restore() {
+        let select = pointers(&["/auth/token"]);
+        let captured = capture(r#"{"window":{}}"#, &select).unwrap();
+        assert!(captured.is_empty());
+
+        let restored: Value = serde_json::from_str(
+            &apply(r#"{"auth":{"token":"x","keep":1}}"#, &select, &captured).unwrap(),
+        )
+        .unwrap();
+
+        assert!(restored["auth"].get("token").is_none());
+        assert_eq!(restored["auth"]["keep"], 1);
+    }
+
+    #[test]
+    fn an_unchanged_json_file_is_returned_byte_for_byte() {
+        let select = pointers(&["/a"]);
+        let content = "{ \"a\": 1,   \"b\": 2 }";
+        let captured = capture(content, &select).unwrap();
+        assert_eq!(apply(content, &select, &captured).unwrap(), content);
+    }
+
+    #[test]
+    fn a_json_restore_keeps_the_key_order_and_layout_byte_for_byte() {
+        let select = pointers(&["/session/token", "/session/user", "/session/expires"]);
+        let outgoing = "{\n    \"zoom\": 1.25,\n    \"session\": {\n        \"user\": \"alice\",\n        \"token\": \"old\",\n        \"expires\": 10\n    },\n    \"language\": \"en\"\n}\n";
+        let captured = Captured {
+            present: [
+                ("/session/token".to_string(), Value::from("new")),
+                ("/session/user".to_string(), Value::from("alice")),
+            ]
+            .into_iter()
+            .collect(),
+            absent: vec!["/session/expires".to_string()],
+        };
+
+        let restored = apply(outgoing, &select, &captured).unwrap();
+
+        assert_eq!(
+            restored,
+            "{\n    \"zoom\": 1.25,\n    \"session\": {\n        \"user\": \"alice\",\n        \"token\": \"new\"\n    },\n    \"language\": \"en\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn a_missing_json_key_is_added_after_its_siblings_in_their_layout() {
+        let select = pointers(&["/session/token", "/auth/refresh"]);
+        let outgoing = "{\r\n  \"z\": 1,\r\n  \"session\": {\"user\":\"a\"}\r\n}";
+        let captured = Captured {
+            present: [
+                ("/session/token".to_string(), Value::from("t")),
+                ("/auth/refresh".to_string(), Value::from("r")),
+            ]
+            .into_iter()
+            .collect(),
+            absent: Vec::new(),
+        };
+
+        let restored = apply(outgoing, &select, &captured).unwrap();
+
+        assert_eq!(
+            restored,
+            "{\r\n  \"z\": 1,\r\n  \"session\": {\"user\":\"a\",\"token\":\"t\"},\r\n  \"auth\": {\"refresh\":\"r\"}\r\n}"
+        );
+    }
+
+    #[test]
+    fn removing_a_json_key_takes_its_comma_and_leaves_its_neighbours() {
+        let content = r#"{"b": [1, 2, 3], "a": {"only": true}, "c": null}"#;
+
+        assert_eq!(
+            remove(content, &pointers(&["/b"])).unwrap(),
+            r#"{"a": {"only": true}, "c": null}"#
+        );
+        assert_eq!(
+            remove(content, &pointers(&["/b/2", "/a/only", "/c"])).unwrap(),
+            r#"{"b": [1, 2], "a": {}}"#
+        );
+    }
+
+    #[test]
+    fn a_pointer_into_a_missing_object_creates_it() {
+        let select = pointers(&["/auth/token"]);
+        let captured = capture(r#"{"auth":{"token":"t"}}"#, &select).unwrap();
+        let restored: Value =
+            serde_json::from_str(&apply("", &select, &captured).unwrap()).unwrap();
+        assert_eq!(restored["auth"]["token"], "t");
+    }
+
+    #[test]
+    fn an_ini_restore_rewrites_only_the_value_and_keeps_the_rest_byte_for_byte() {
+        let select = ini(&[("auth", "refreshtoken")]);
+        let captured = capture(&INI.replace("aaa", "bbb"), &select).unwrap();
+        assert_eq!(captured.present["[auth] refreshtoken"], "bbb");
+
+        let restored = apply(INI, &select, &captured).unwrap();
+
+        assert_eq!(
+            restored,
+            INI.replace("RefreshToken = aaa", "RefreshToken = bbb")
+        );
+    }
+
+    #[test]
+    fn a_missing_ini_key_goes_after_the_last_key_of_its_section_or_a_new_one() {
+        let select = ini(&[("General", "Token"), ("Session", "Id"), ("", "Top")]);
+        let captured = Captured {
+            present: [
+                ("[General] Token".to_string(), Value::from("t")),
+                ("[Session] Id".to_string(), Value::from("42")),
+                ("Top".to_string(), Value::from("1")),
+            ]
+            .into_iter()
+            .collect(),
+            absent: Vec::new(),
+        };
+
+        let restored = apply(INI, &select, &captured).unwrap();
+
+        assert!(restored.starts_with("Top=1\r\n; written"), "{restored}");
+        assert!(
+            restored.contains("Language = en\r\nToken=t\r\n\r\n[Auth]"),
+            "{restored}"
+        );
+        assert!(
+            restored.ends_with("WindowWidth=1280\r\n\r\n[Session]\r\nId=42\r\n"),
+            "{restored}"
+        );
+    }
+
+    #[test]
+    fn removing_an_ini_key_drops_its_line_alone() {
+        let restored = remove(INI, &ini(&[("Auth", "RefreshToken")])).unwrap();
+        assert_eq!(restored, INI.replace("RefreshToken = aaa\r\n", ""));
+    }
+
+    #[test]
+    fn a_keyvalues_restore_replaces_the_value_in_place() {
+        let select = kv(&["UserLocalConfigStore/friends/PersonaName"]);
+        let captured = capture(&VDF.replace("Alice", r#"Bob \"B\""#), &select).unwrap();
+        assert_eq!(
+            captured.present["UserLocalConfigStore/friends/PersonaName"],
+            "Bob \"B\""
+        );
+
+        let restored = apply(VDF, &select, &captured).unwrap();
+
+        assert_eq!(restored, VDF.replace("\"Alice\"", "\"Bob \\\"B\\\"\""));
+    }
+
+    #[test]
+    fn a_missing_keyvalues_key_is_added_with_the_sections_it_needs() {
+        let select = kv(&[
+            "UserLocalConfigStore/system/InGameOverlayShortcutKey",
+            "UserLocalConfigStore/auth/session/Token",
+        ]);
+        let captured = Captured {
+            present: [
+                (
+                    "UserLocalConfigStore/system/InGameOverlayShortcutKey".to_string(),
+                    Value::from("Shift\tKEY_TAB"),
+                ),
+                (
+                    "UserLocalConfigStore/auth/session/Token".to_string(),
+                    Value::from("abc"),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            absent: Vec::new(),
+        };
+
+        let restored = apply(VDF, &select, &captured).unwrap();
+        let back = capture(&restored, &select).unwrap();
+
+        assert_eq!(back, captured);
+        assert!(restored.contains(
+            "\t\t\"EnableGameOverlay\"\t\t\"1\"\n\t\t\"InGameOverlayShortcutKey\"\t\t\"Shift\tKEY_TAB\"\n\t}\n"
+        ));
+        assert!(restored.ends_with(
+            "\t\"auth\"\n\t{\n\t\t\"session\"\n\t\t{\n\t\t\t\"Token\"\t\t\"abc\"\n\t\t}\n\t}\n}\n"
+        ));
+    }
+
+    #[test]
+    fn removing_a_keyvalues_key_leaves_its_neighbours() {
+        let restored = remove(VDF, &kv(&["userlocalconfigstore/FRIENDS/personaname"])).unwrap();
+        assert_eq!(
+            restored,
+            VDF.replace("\t\t\"PersonaName\"\t\t\"Alice\"\n", "")
+        );
+    }
+
+    #[test]
+    fn a_value_spanning_lines_is_refused_for_line_based_formats() {
+        let select = ini(&[("Auth", "RefreshToken")]);
+        let captured = Captured {
+            present: [("[Auth] RefreshToken".to_string(), Value::from("a\n[Evil]"))]
+                .into_iter()
+                .collect(),
+            absent: Vec::new(),
+        };
+        assert!(apply(INI, &select, &captured).is_err());
+    }
+
+    #[test]
+    fn a_key_the_snapshot_does_not_mention_is_left_alone() {
+        // A descriptor that gained a selector after the snapshot was taken
+        // must not wipe that key from the live file.
+        let select = ini(&[("Auth", "RefreshToken")]);
+        assert_eq!(apply(INI, &select, &Captured::default()).unwrap(), INI);
+    }
+}
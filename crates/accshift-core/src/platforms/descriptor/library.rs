@@ -10,8 +10,10 @@
 //! dry run uses, so what the preview shows is what a switch would do.
 
 use super::engine::{DescriptorOrigin, DescriptorService};
+use super::lint::{self, LintWarning};
 use super::plan::DryRunPlan;
 use super::schema::{Descriptor, DescriptorError, Os};
+use super::signature::{self, Signer};
 use super::user_dir;
 use crate::context::AppContext;
 use serde::Serialize;
@@ -24,6 +26,10 @@ pub struct DescriptorPreview {
     /// The file the user picked, as they picked it.
     pub source: String,
     pub descriptor: Descriptor,
+    /// Who signed the file, from the `.sig` next to it.
+    pub signer: Signer,
+    /// What the file probably did not mean to do. Never blocks the install.
+    pub lint: Vec<LintWarning>,
     /// The name it would take in the descriptor folder. Always `<id>.json`, so
     /// two files describing the same platform cannot both be installed.
     pub file_name: String,
@@ -50,6 +56,9 @@ pub fn preview_file(
     let body = std::fs::read_to_string(path)
         .map_err(|e| DescriptorError::new(&source, "", format!("could not be read: {e}")))?;
     let descriptor = Descriptor::parse(&source, &body)?;
+    let trusted = crate::config::load_config(app).trusted_publishers;
+    let signer = signature::verify(path, body.as_bytes(), &trusted)
+        .map_err(|problem| DescriptorError::new(&source, "", problem))?;
 
     let file_name = format!("{}.json", descriptor.id);
     let replaces = user_dir(app)
@@ -57,6 +66,7 @@ pub fn preview_file(
         .unwrap_or(false);
 
     let blocked = blocking_reason(&descriptor);
+    let lint = lint::lint(&descriptor);
     let (plan, plan_problem) = match plan_for(app, &descriptor) {
         Ok(plan) => (Some(plan), String::new()),
         Err(problem) => (None, problem),
@@ -65,6 +75,8 @@ pub fn preview_file(
     Ok(DescriptorPreview {
         source,
         descriptor,
+        signer,
+        lint,
         file_name,
         replaces,
         blocked,
@@ -93,6 +105,17 @@ pub fn install_file(app: &dyn AppContext, path: &Path) -> Result<String, String>
     // failed install must not have eaten it.
     std::fs::copy(path, &target)
         .map_err(|e| format!("Could not write {}: {e}", target.display()))?;
+
+    // The signature follows the file under its new name. An unsigned file
+    // replacing a signed one takes the old signature away with it, or the
+    // folder would refuse the new file as edited after signing.
+    let target_sig = signature::signature_path(&target);
+    if preview.signer == Signer::Unsigned {
+        remove_if_present(&target_sig)?;
+    } else {
+        std::fs::copy(signature::signature_path(path), &target_sig)
+            .map_err(|e| format!("Could not write {}: {e}", target_sig.display()))?;
+    }
     Ok(preview.file_name)
 }
 
@@ -110,7 +133,12 @@ pub fn remove(app: &dyn AppContext, id: &str) -> Result<(), String> {
     }
 
     let target = user_dir(app)?.join(format!("{id}.json"));
-    match std::fs::remove_file(&target) {
+    remove_if_present(&target)?;
+    remove_if_present(&signature::signature_path(&target))
+}
+
+fn remove_if_present(target: &Path) -> Result<(), String> {
+    match std::fs::remove_file(target) {
         Ok(()) => Ok(()),
         // Already gone is the state the caller wanted.
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
@@ -148,7 +176,7 @@ fn plan_for(app: &dyn AppContext, descriptor: &Descriptor) -> Result<DryRunPlan,
     let sample = format.charset.sample(format.min_length.max(1));
 
     let service = DescriptorService::new(descriptor.clone(), DescriptorOrigin::Embedded);
-    service.plan_switch(app, &sample)
+    service.plan_switch(app, &sample, &Default::default())
 }
 
 /// The file name a preview reports, falling back to the whole path when the
@@ -296,6 +324,42 @@ mod tests {
         let _ = std::fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn a_signature_is_shown_in_the_preview_and_follows_the_file_in() {
+        use super::super::signature::test_support::{key_pair, public_key, sign_next_to};
+
+        let root = scratch("install-signed");
+        let ctx = TempCtx { root: root.clone() };
+        let body = fixture("acme", &root);
+        let picked = write(&root, "downloaded.json", &body);
+        let pair = key_pair(4);
+        sign_next_to(&picked, &body, &pair);
+
+        let preview = preview_file(&ctx, &picked).unwrap();
+        assert_eq!(
+            preview.signer,
+            Signer::Unknown {
+                public_key: public_key(&pair)
+            }
+        );
+        install_file(&ctx, &picked).unwrap();
+        let installed = user_dir(&ctx).unwrap().join("acme.json");
+        assert!(signature::signature_path(&installed).is_file());
+
+        // An unsigned replacement must not inherit a signature that no longer
+        // matches, or the folder would refuse it.
+        let unsigned = write(&root, "unsigned.json", &body.replace("Fixture", "Mine"));
+        assert_eq!(
+            preview_file(&ctx, &unsigned).unwrap().signer,
+            Signer::Unsigned
+        );
+        install_file(&ctx, &unsigned).unwrap();
+        assert!(!signature::signature_path(&installed).exists());
+
+        remove(&ctx, "acme").unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn removing_deletes_only_a_descriptor_in_the_folder() {
         let root = scratch("remove");
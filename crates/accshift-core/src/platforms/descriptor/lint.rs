@@ -0,0 +1,405 @@
+//! What a valid descriptor may still be getting wrong.
+//!
+//! [`Descriptor::validate`] refuses what the engine cannot run. This pass looks
+//! at what it can run but probably should not as written: a root nothing lives
+//! under, a snapshot nothing marks, a marker that outlives its file. None of it
+//! stops a file from loading; each warning carries a stable code and the field
+//! it is about, so an editor, the CLI and the settings screen can all point at
+//! the same line.
+
+use super::schema::{
+    is_under_registry_root, is_under_root, Condition, Descriptor, DescriptorError, Discovery,
+    IdentitySource, OsProfile, PathTemplate, RegistryHive,
+};
+use serde::Serialize;
+use std::path::Path;
+
+/// A log tail larger than this is read on every refresh of the account list.
+/// Sixteen times the default, and more than any launcher log line needs.
+pub const LARGE_TAIL_BYTES: u64 = 1024 * 1024;
+
+/// One thing a descriptor does that it likely did not mean to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintWarning {
+    /// Stable identifier, for a script or a suppression list.
+    pub code: &'static str,
+    /// Dotted path of the field the warning is about.
+    pub field: String,
+    pub message: String,
+}
+
+/// Every warning for every OS profile in the file, in profile order.
+pub fn lint(descriptor: &Descriptor) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+    for (os, profile) in &descriptor.os {
+        lint_profile(profile, &format!("os.{}", os.as_str()), &mut warnings);
+    }
+    warnings
+}
+
+/// Reads, validates and lints a descriptor file. A file that does not load is
+/// the error; one that loads with warnings is not.
+pub fn lint_file(path: &Path) -> Result<Vec<LintWarning>, DescriptorError> {
+    let source = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    let body = std::fs::read_to_string(path)
+        .map_err(|e| DescriptorError::new(&source, "", format!("could not be read: {e}")))?;
+    Ok(lint(&Descriptor::parse(&source, &body)?))
+}
+
+fn lint_profile(profile: &OsProfile, field: &str, out: &mut Vec<LintWarning>) {
+    let mut warn = |code, field: String, message: String| {
+        out.push(LintWarning {
+            code,
+            field,
+            message,
+        })
+    };
+
+    let paths = file_paths(profile);
+    for (index, root) in profile.roots.files.iter().enumerate() {
+        if !paths.iter().any(|path| is_under_root(path, root)) {
+            warn(
+                "unused_root",
+                format!("{field}.roots.files[{index}]"),
+                format!(
+                    "nothing the descriptor reads or writes is under `{}`; the sandbox is wider than it needs to be",
+                    root.as_str()
+                ),
+            );
+        }
+    }
+    let keys = registry_keys(profile);
+    for (index, root) in profile.roots.registry.iter().enumerate() {
+        if !keys
+            .iter()
+            .any(|(hive, key)| is_under_registry_root(*hive, key, root))
+        {
+            warn(
+                "unused_root",
+                format!("{field}.roots.registry[{index}]"),
+                format!(
+                    "nothing the descriptor reads or writes is under `{}\\{}`; the sandbox is wider than it needs to be",
+                    root.root.as_str(),
+                    root.key
+                ),
+            );
+        }
+    }
+
+    let state = &profile.state;
+    let kept_markers = state
+        .files
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.snapshot_marker && !item.clear_snapshot_when_source_missing)
+        .map(|(index, _)| format!("{field}.state.files[{index}]"))
+        .chain(
+            state
+                .registry_values
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| {
+                    item.snapshot_marker && !item.clear_snapshot_when_source_missing
+                })
+                .map(|(index, _)| format!("{field}.state.registryValues[{index}]")),
+        )
+        .chain(
+            state
+                .keys
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| {
+                    item.snapshot_marker && !item.clear_snapshot_when_source_missing
+                })
+                .map(|(index, _)| format!("{field}.state.keys[{index}]")),
+        );
+    for at in kept_markers {
+        warn(
+            "stale_marker",
+            format!("{at}.clearSnapshotWhenSourceMissing"),
+            "this item marks a snapshot but is kept when the live copy is gone, so a capture of a signed-out launcher still reads as a saved account"
+                .to_string(),
+        );
+    }
+
+    let has_items = !state.files.is_empty()
+        || !state.directories.is_empty()
+        || !state.registry_values.is_empty()
+        || !state.keys.is_empty();
+    let has_marker = state.files.iter().any(|item| item.snapshot_marker)
+        || state.directories.iter().any(|item| item.snapshot_marker)
+        || state
+            .registry_values
+            .iter()
+            .any(|item| item.snapshot_marker)
+        || state.keys.iter().any(|item| item.snapshot_marker);
+    if has_items && !has_marker {
+        warn(
+            "no_snapshot_marker",
+            format!("{field}.state"),
+            "no state item sets `snapshotMarker`, so no account ever shows as having a snapshot"
+                .to_string(),
+        );
+    }
+
+    if let IdentitySource::LogTail { tail_bytes, .. } = &profile.identity.source {
+        if *tail_bytes > LARGE_TAIL_BYTES {
+            warn(
+                "large_log_tail",
+                format!("{field}.identity.source.tailBytes"),
+                format!(
+                    "{tail_bytes} bytes of log are read on every refresh; {LARGE_TAIL_BYTES} is already more than a launcher needs"
+                ),
+            );
+        }
+    }
+
+    // An updater stub that hands off to the real client and exits (the one
+    // `launch.argsOnlyFor` names) is never the process left running.
+    let stub = profile
+        .launch
+        .as_ref()
+        .map(|launch| launch.args_only_for.as_str())
+        .filter(|name| !name.is_empty());
+    if let Some(executable) = &profile.executable {
+        let processes = &profile.close.processes;
+        let is_stub = stub.is_some_and(|name| name.eq_ignore_ascii_case(&executable.file_name));
+        if !processes.is_empty()
+            && !is_stub
+            && !processes
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&executable.file_name))
+        {
+            warn(
+                "launcher_not_closed",
+                format!("{field}.close.processes"),
+                format!(
+                    "`{}` is the launcher's own binary but is not in the list, so a switch can restore files under a running launcher",
+                    executable.file_name
+                ),
+            );
+        }
+    }
+}
+
+/// Every file location the profile reads or writes, each one checked against
+/// the roots at load time.
+fn file_paths(profile: &OsProfile) -> Vec<&PathTemplate> {
+    let state = &profile.state;
+    let mut paths: Vec<&PathTemplate> = Vec::new();
+    paths.extend(state.files.iter().flat_map(|i| i.live.candidates()));
+    paths.extend(state.directories.iter().flat_map(|i| i.live.candidates()));
+    paths.extend(state.keys.iter().flat_map(|i| i.live.candidates()));
+    paths.extend(state.caches.iter());
+
+    match &profile.identity.source {
+        IdentitySource::LogTail { path, .. }
+        | IdentitySource::JsonPointer { path, .. }
+        | IdentitySource::IniKey { path, .. }
+        | IdentitySource::SqliteQuery { path, .. } => paths.push(path),
+        IdentitySource::NativeHook { paths: hook, .. } => paths.extend(hook.values()),
+        IdentitySource::Registry { .. } | IdentitySource::Synthetic => {}
+    }
+    paths.extend(profile.identity.discovery.iter().map(Discovery::path));
+
+    let conditions = state
+        .capture_when
+        .iter()
+        .chain(&profile.setup.trigger)
+        .chain(&profile.setup.confirm);
+    for condition in conditions {
+        condition_paths(condition, &mut paths);
+    }
+    paths
+}
+
+fn condition_paths<'a>(condition: &'a Condition, paths: &mut Vec<&'a PathTemplate>) {
+    match condition {
+        Condition::PathNonEmpty { path, .. } | Condition::PathFresh { path, .. } => {
+            paths.extend(path.candidates())
+        }
+        Condition::AnyOf { conditions } => {
+            for nested in conditions {
+                condition_paths(nested, paths);
+            }
+        }
+        Condition::NewIdentity | Condition::IdentityPresent | Condition::SinceStart { .. } => {}
+    }
+}
+
+/// Every registry key the profile reads or writes under its registry roots.
+fn registry_keys(profile: &OsProfile) -> Vec<(RegistryHive, &str)> {
+    let mut keys: Vec<(RegistryHive, &str)> = profile
+        .state
+        .registry_values
+        .iter()
+        .map(|item| (item.root, item.key.as_str()))
+        .collect();
+    if let IdentitySource::Registry { root, key, .. } = &profile.identity.source {
+        keys.push((*root, key));
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linted(mutate: impl Fn(&mut serde_json::Value)) -> Vec<LintWarning> {
+        let mut value = serde_json::json!({
+          "id": "demo",
+          "schemaVersion": 1,
+          "name": "Demo Launcher",
+          "shortName": "Demo",
+          "os": {
+            "windows": {
+              "roots": {
+                "files": ["${LOCALAPPDATA}/Demo"],
+                "registry": [{ "root": "HKCU", "key": "Software\\Demo" }]
+              },
+              "detect": { "executableResolves": true },
+              "executable": {
+                "fileName": "Demo.exe",
+                "candidates": [{ "kind": "path", "template": "${ProgramFiles}/Demo/Demo.exe" }]
+              },
+              "identity": {
+                "source": { "kind": "registry", "root": "HKCU", "key": "Software\\Demo", "value": "userId" },
+                "format": { "charset": "digits", "maxLength": 32 },
+                "current": "identity"
+              },
+              "state": {
+                "files": [{
+                  "live": "${LOCALAPPDATA}/Demo/session.json",
+                  "snapshot": "session.json",
+                  "snapshotMarker": true
+                }]
+              },
+              "close": { "processes": ["Demo.exe"] }
+            }
+          }
+        });
+        mutate(&mut value["os"]["windows"]);
+        let descriptor = Descriptor::parse("demo.json", &value.to_string()).unwrap();
+        lint(&descriptor)
+    }
+
+    fn codes(warnings: &[LintWarning]) -> Vec<&'static str> {
+        warnings.iter().map(|warning| warning.code).collect()
+    }
+
+    #[test]
+    fn a_careful_descriptor_has_nothing_to_say() {
+        assert_eq!(linted(|_| {}), vec![]);
+    }
+
+    #[test]
+    fn a_root_nothing_lives_under_is_named_by_its_index() {
+        let warnings = linted(|profile| {
+            profile["roots"]["files"]
+                .as_array_mut()
+                .unwrap()
+                .push("${APPDATA}/Unused".into());
+            profile["roots"]["registry"]
+                .as_array_mut()
+                .unwrap()
+                .push(serde_json::json!({ "root": "HKLM", "key": "Software\\Demo" }));
+        });
+
+        assert_eq!(codes(&warnings), ["unused_root", "unused_root"]);
+        assert_eq!(warnings[0].field, "os.windows.roots.files[1]");
+        assert_eq!(warnings[1].field, "os.windows.roots.registry[1]");
+    }
+
+    #[test]
+    fn a_marker_that_outlives_its_file_is_flagged() {
+        let warnings = linted(|profile| {
+            profile["state"]["files"][0]["clearSnapshotWhenSourceMissing"] = false.into();
+        });
+
+        assert_eq!(codes(&warnings), ["stale_marker"]);
+        assert_eq!(
+            warnings[0].field,
+            "os.windows.state.files[0].clearSnapshotWhenSourceMissing"
+        );
+    }
+
+    #[test]
+    fn state_with_no_marker_is_flagged_once() {
+        let warnings = linted(|profile| {
+            profile["state"]["files"][0]["snapshotMarker"] = false.into();
+        });
+
+        assert_eq!(codes(&warnings), ["no_snapshot_marker"]);
+        assert_eq!(warnings[0].field, "os.windows.state");
+    }
+
+    #[test]
+    fn a_huge_log_tail_is_flagged_and_the_default_is_not() {
+        let source = |tail: Option<u64>| {
+            move |profile: &mut serde_json::Value| {
+                profile["identity"]["source"] = serde_json::json!({
+                    "kind": "logTail",
+                    "path": "${LOCALAPPDATA}/Demo/app.log",
+                    "lineContains": "user",
+                    "prefix": "id="
+                });
+                if let Some(tail) = tail {
+                    profile["identity"]["source"]["tailBytes"] = tail.into();
+                }
+                profile["roots"]["registry"] = serde_json::json!([]);
+            }
+        };
+
+        assert_eq!(linted(source(None)), vec![]);
+        let warnings = linted(source(Some(64 * 1024 * 1024)));
+        assert_eq!(codes(&warnings), ["large_log_tail"]);
+        assert_eq!(warnings[0].field, "os.windows.identity.source.tailBytes");
+    }
+
+    #[test]
+    fn a_close_list_without_the_launcher_itself_is_flagged() {
+        let warnings = linted(|profile| {
+            profile["close"]["processes"] = serde_json::json!(["DemoHelper.exe"]);
+        });
+
+        assert_eq!(codes(&warnings), ["launcher_not_closed"]);
+        assert!(warnings[0].message.contains("Demo.exe"), "{warnings:?}");
+
+        // Case does not matter to the process table on Windows.
+        let warnings = linted(|profile| {
+            profile["close"]["processes"] = serde_json::json!(["demo.EXE"]);
+        });
+        assert_eq!(warnings, vec![]);
+    }
+
+    #[test]
+    fn an_updater_stub_need_not_be_in_the_close_list() {
+        let warnings = linted(|profile| {
+            profile["executable"]["fileName"] = "Update.exe".into();
+            profile["launch"] = serde_json::json!({
+                "args": ["--processStart", "Demo.exe"],
+                "argsOnlyFor": "Update.exe"
+            });
+        });
+
+        assert_eq!(warnings, vec![]);
+    }
+
+    #[test]
+    fn shipped_descriptors_lint_clean() {
+        let (loaded, _) = super::super::load_embedded();
+        for descriptor in &loaded {
+            // GOG, Epic and Ubisoft keep their login file on purpose: those
+            // launchers rewrite it on sign-out rather than delete it.
+            let warnings: Vec<_> = lint(descriptor)
+                .into_iter()
+                .filter(|warning| warning.code != "stale_marker")
+                .collect();
+            assert_eq!(warnings, vec![], "{}", descriptor.id);
+        }
+    }
+}
@@ -14,24 +14,31 @@
 //! never override a shipped one: an id this build already answers for is
 //! refused by name, so a dropped-in file cannot quietly take over Steam.
 //!
-//! Four platforms stay hand-written, because none of them is a session made of
-//! files. Steam parses VDF, reads ban state and edits accounts in bulk.
+//! Three platforms stay hand-written, because none of them is a session made
+//! of files. Steam parses VDF, reads ban state and edits accounts in bulk.
 //! Battle.net has no per-account snapshot at all: switching rewrites one
 //! quote-aware CSV field inside a shared config. Roblox keeps its session in a
 //! single registry cookie and needs an HTTP client for quick-login codes and
-//! avatars. Riot fits the state model but not the account model: it exposes
-//! profiles carrying a tag line and a puuid, which the engine's accounts do not
-//! describe. Forcing any of them through a descriptor would mean a descriptor
+//! avatars. Forcing any of them through a descriptor would mean a descriptor
 //! that is mostly a native hook, which is the code path this module replaces.
+//! Riot is a session made of files whose account only its local API can name,
+//! so it is a descriptor with a hook for that one read.
 
+mod close;
 pub mod config_bridge;
 pub mod engine;
+pub mod harness;
 pub mod hooks;
+pub mod json_schema;
+pub mod keys;
 pub mod library;
+pub mod lint;
 pub mod paths;
 pub mod plan;
 pub mod reg;
 pub mod schema;
+pub mod signature;
+pub mod wine;
 
 use crate::context::AppContext;
 use std::path::PathBuf;
@@ -39,6 +46,7 @@ use std::sync::OnceLock;
 
 pub use engine::{DescriptorOrigin, DescriptorService};
 pub use schema::{Descriptor, DescriptorError};
+pub use signature::Signer;
 
 /// The descriptors shipped with the app, as `(source name, body)`.
 const EMBEDDED: &[(&str, &str)] = &[
@@ -47,6 +55,7 @@ const EMBEDDED: &[(&str, &str)] = &[
     ("epic.json", include_str!("descriptors/epic.json")),
     ("ubisoft.json", include_str!("descriptors/ubisoft.json")),
     ("discord.json", include_str!("descriptors/discord.json")),
+    ("riot.json", include_str!("descriptors/riot.json")),
 ];
 
 /// Parses every shipped descriptor, keeping the failures rather than hiding
@@ -63,6 +72,28 @@ pub fn load_embedded() -> (Vec<Descriptor>, Vec<DescriptorError>) {
     (loaded, errors)
 }
 
+/// The text of the shipped descriptor answering to `id`, for a user
+/// descriptor that `extends` it.
+pub(crate) fn shipped_body(id: &str) -> Option<&'static str> {
+    EMBEDDED
+        .iter()
+        .map(|(_, body)| *body)
+        .find(|body| embedded_id(body).as_deref() == Some(id))
+}
+
+/// The ids a user descriptor may extend, in shipping order.
+pub(crate) fn shipped_ids() -> Vec<String> {
+    EMBEDDED
+        .iter()
+        .filter_map(|(_, body)| embedded_id(body))
+        .collect()
+}
+
+fn embedded_id(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    value["id"].as_str().map(str::to_string)
+}
+
 /// Every descriptor-driven service usable on this OS, built once.
 ///
 /// A descriptor with no profile for the running OS is skipped: the platform is
@@ -106,13 +137,22 @@ pub fn user_dir(app: &dyn AppContext) -> Result<PathBuf, String> {
     Ok(crate::storage::app_config_root(app)?.join("descriptors"))
 }
 
-/// Every `*.json` in [`user_dir`], parsed, each with the file it came from.
+/// A descriptor read from [`user_dir`].
+#[derive(Debug, Clone)]
+pub struct UserDescriptor {
+    pub path: PathBuf,
+    pub descriptor: Descriptor,
+    pub signer: Signer,
+}
+
+/// Every `*.json` in [`user_dir`], parsed and its signature checked, each with
+/// the file it came from.
 ///
 /// A missing folder is not an error: it means the user added none. A file that
-/// does not validate is kept as an error rather than dropped, so whatever
-/// lists them can name the file and the field instead of showing one platform
-/// fewer than the user put there.
-pub fn load_user(app: &dyn AppContext) -> (Vec<(PathBuf, Descriptor)>, Vec<DescriptorError>) {
+/// does not validate, or whose signature does not match it, is kept as an
+/// error rather than dropped, so whatever lists them can name the file and the
+/// field instead of showing one platform fewer than the user put there.
+pub fn load_user(app: &dyn AppContext) -> (Vec<UserDescriptor>, Vec<DescriptorError>) {
     let mut loaded = Vec::new();
     let mut errors = Vec::new();
 
@@ -141,6 +181,7 @@ pub fn load_user(app: &dyn AppContext) -> (Vec<(PathBuf, Descriptor)>, Vec<Descr
         .collect();
     files.sort();
 
+    let trusted = crate::config::load_config(app).trusted_publishers;
     for path in files {
         let source = path
             .file_name()
@@ -148,7 +189,14 @@ pub fn load_user(app: &dyn AppContext) -> (Vec<(PathBuf, Descriptor)>, Vec<Descr
             .unwrap_or_else(|| path.display().to_string());
         match std::fs::read_to_string(&path) {
             Ok(body) => match Descriptor::parse(&source, &body) {
-                Ok(descriptor) => loaded.push((path, descriptor)),
+                Ok(descriptor) => match signature::verify(&path, body.as_bytes(), &trusted) {
+                    Ok(signer) => loaded.push(UserDescriptor {
+                        path,
+                        descriptor,
+                        signer,
+                    }),
+                    Err(problem) => errors.push(DescriptorError::new(&source, "", problem)),
+                },
                 Err(error) => errors.push(error),
             },
             Err(e) => errors.push(DescriptorError::new(
@@ -269,8 +317,51 @@ mod tests {
 
         assert!(errors.is_empty(), "{errors:?}");
         assert_eq!(loaded.len(), 1);
-        assert_eq!(loaded[0].1.id, "acme");
-        assert_eq!(loaded[0].0.file_name().unwrap(), "acme.json");
+        assert_eq!(loaded[0].descriptor.id, "acme");
+        assert_eq!(loaded[0].path.file_name().unwrap(), "acme.json");
+        assert_eq!(loaded[0].signer, Signer::Unsigned);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_signed_file_is_read_with_its_signer_and_a_tampered_one_is_refused() {
+        use signature::test_support::{key_pair, public_key, sign_next_to};
+
+        let root = scratch("signed");
+        let ctx = TempCtx { root: root.clone() };
+        let pair = key_pair(3);
+        let mut cfg = crate::config::load_config(&ctx);
+        cfg.trusted_publishers = vec![crate::config::TrustedPublisher {
+            name: "Platform team".into(),
+            public_key: public_key(&pair),
+        }];
+        crate::config::save_config(&ctx, &cfg).unwrap();
+
+        let dir = user_dir(&ctx).unwrap();
+        let body = fixture("acme", &root);
+        drop_in(&ctx, "acme.json", &body);
+        sign_next_to(&dir.join("acme.json"), &body, &pair);
+        let other = fixture("other", &root);
+        drop_in(&ctx, "other.json", &other);
+        sign_next_to(&dir.join("other.json"), &other, &pair);
+        drop_in(&ctx, "other.json", &other.replace("Fixture", "Edited"));
+
+        let (loaded, errors) = load_user(&ctx);
+
+        assert_eq!(loaded.len(), 1, "{errors:?}");
+        assert_eq!(
+            loaded[0].signer,
+            Signer::Trusted {
+                publisher: "Platform team".into()
+            }
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].source, "other.json");
+        assert!(
+            errors[0].problem.contains("after it was signed"),
+            "{}",
+            errors[0]
+        );
         let _ = std::fs::remove_dir_all(&root);
     }
 
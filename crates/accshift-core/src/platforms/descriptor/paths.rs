@@ -11,6 +11,7 @@ use std::path::{Component, Path, PathBuf};
 use crate::error::{PlatformError, PlatformErrorKind};
 
 use super::schema::{PathTemplate, Roots, INSTALL_DIR};
+use super::wine;
 
 /// The values a template can be resolved against.
 ///
@@ -21,6 +22,7 @@ use super::schema::{PathTemplate, Roots, INSTALL_DIR};
 pub struct PathResolver {
     env: HashMap<String, String>,
     install_dir: Option<PathBuf>,
+    wine_prefix: Option<wine::Prefix>,
 }
 
 impl PathResolver {
@@ -29,6 +31,7 @@ impl PathResolver {
         Self {
             env: std::env::vars().collect(),
             install_dir: None,
+            wine_prefix: None,
         }
     }
 
@@ -45,6 +48,7 @@ impl PathResolver {
                 .map(|(k, v)| (k.into(), v.into()))
                 .collect(),
             install_dir: None,
+            wine_prefix: None,
         }
     }
 
@@ -59,6 +63,34 @@ impl PathResolver {
         self.install_dir.as_deref()
     }
 
+    /// Points the Windows variables into a Wine prefix, so a profile written
+    /// against `${LOCALAPPDATA}` or `C:/ProgramData` lands inside `drive_c`
+    /// rather than nowhere. The prefix's values shadow the host's.
+    pub fn with_wine_prefix(mut self, prefix: wine::Prefix) -> Self {
+        self.env.extend(prefix.environment());
+        self.wine_prefix = Some(prefix);
+        self
+    }
+
+    pub fn wine_prefix(&self) -> Option<&wine::Prefix> {
+        self.wine_prefix.as_ref()
+    }
+
+    /// A variable of the environment templates resolve against.
+    pub fn var(&self, name: &str) -> Option<String> {
+        self.lookup(name)
+    }
+
+    /// Where a path the launcher itself recorded lives on this machine. Inside
+    /// a prefix that is a Windows path to map onto `drive_c`; anywhere else it
+    /// is already a host path.
+    pub fn host_path(&self, recorded: &str) -> PathBuf {
+        self.wine_prefix
+            .as_ref()
+            .and_then(|prefix| prefix.host_path(recorded))
+            .unwrap_or_else(|| PathBuf::from(recorded))
+    }
+
     fn lookup(&self, name: &str) -> Option<String> {
         if name == INSTALL_DIR {
             return self
@@ -110,6 +142,13 @@ impl PathResolver {
         }
         out.push_str(rest);
 
+        if let Some(mapped) = self
+            .wine_prefix
+            .as_ref()
+            .and_then(|prefix| prefix.host_path(&out))
+        {
+            return Ok(mapped);
+        }
         Ok(PathBuf::from(normalise_separators(&out)))
     }
 }
@@ -349,6 +388,38 @@ mod tests {
         assert_eq!(sandbox.roots().len(), 1);
     }
 
+    #[cfg(not(windows))]
+    #[test]
+    fn inside_a_wine_prefix_windows_locations_land_in_drive_c() {
+        let resolver = PathResolver::from_env([("LOCALAPPDATA", "/nowhere")])
+            .with_wine_prefix(wine::Prefix::new("/games/acme", "demo"));
+        let local = resolver
+            .resolve(&template("${LOCALAPPDATA}/Acme/session.json"))
+            .unwrap();
+        assert_eq!(
+            local,
+            PathBuf::from("/games/acme/drive_c/users/demo/AppData/Local/Acme/session.json")
+        );
+        let literal = resolver
+            .resolve(&template("C:/ProgramData/Acme/state.ini"))
+            .unwrap();
+        assert_eq!(
+            literal,
+            PathBuf::from("/games/acme/drive_c/ProgramData/Acme/state.ini")
+        );
+
+        // The roots move with it, so the sandbox guards the prefix.
+        let roots = Roots {
+            files: vec![template("${LOCALAPPDATA}/Acme")],
+            registry: Vec::new(),
+        };
+        let sandbox = Sandbox::new(&roots, &resolver);
+        assert!(sandbox.ensure_allowed(&local).is_ok());
+        assert!(sandbox
+            .ensure_allowed(Path::new("/nowhere/Acme/session.json"))
+            .is_err());
+    }
+
     #[test]
     fn normalisation_keeps_a_leading_parent_segment_it_cannot_fold() {
         assert_eq!(
@@ -6,6 +6,59 @@
 
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::str::FromStr;
+
+/// Which operation a plan describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DryRunOperation {
+    /// Capture the signed-in account, restore another, relaunch.
+    Switch,
+    /// Drop an account from the list and delete its snapshot.
+    Forget,
+    /// Start adding an account: adopt the live session, or clear it and
+    /// launch the sign-in.
+    Setup,
+    /// Refresh the signed-in account's snapshot from the live state.
+    Capture,
+}
+
+impl DryRunOperation {
+    pub const ALL: [DryRunOperation; 4] = [
+        DryRunOperation::Switch,
+        DryRunOperation::Forget,
+        DryRunOperation::Setup,
+        DryRunOperation::Capture,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DryRunOperation::Switch => "switch",
+            DryRunOperation::Forget => "forget",
+            DryRunOperation::Setup => "setup",
+            DryRunOperation::Capture => "capture",
+        }
+    }
+
+    /// Whether the operation acts on an account the caller names. Setup and
+    /// capture act on whatever is signed in.
+    pub fn takes_account(self) -> bool {
+        matches!(self, DryRunOperation::Switch | DryRunOperation::Forget)
+    }
+}
+
+impl FromStr for DryRunOperation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|operation| operation.as_str() == s)
+            .ok_or_else(|| {
+                format!("Unknown operation `{s}`: expected switch, forget, setup or capture")
+            })
+    }
+}
 
 /// What a step would do.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -19,6 +72,8 @@ pub enum PlanAction {
     Restore,
     /// Live state removed.
     Delete,
+    /// A value set in place, overwriting whatever was there.
+    Write,
     /// Launcher asked to exit.
     Close,
     /// Launcher started.
@@ -31,9 +86,16 @@ pub enum PlanAction {
 pub enum PlanTargetKind {
     File,
     Directory,
+    /// Selected keys inside a file, the rest of which is left alone.
+    FileKey,
     RegistryValue,
     Process,
     Executable,
+    /// A request the launcher is sent: a command, a URI, a local endpoint or
+    /// its window.
+    Request,
+    /// An entry in the app's own account list.
+    Account,
 }
 
 /// A plan is serialized for the GUI and the CLI's `--json`, and read back by
@@ -61,6 +123,8 @@ pub struct DryRunPlan {
     pub platform_id: String,
     /// Which operation was planned, e.g. `switch`.
     pub operation: String,
+    /// The account the operation acts on. Empty for a setup that would wait
+    /// for a sign-in, and for a capture with nobody signed in.
     pub account_id: String,
     /// Always false. Present so a caller reading the JSON cannot mistake a
     /// plan for a report of work already done.
@@ -146,6 +210,7 @@ impl DryRunPlan {
                     PlanAction::Capture => "capture",
                     PlanAction::Restore => "restore",
                     PlanAction::Delete => "delete",
+                    PlanAction::Write => "write",
                     PlanAction::Close => "close",
                     PlanAction::Launch => "launch",
                 };
@@ -173,6 +238,14 @@ mod tests {
         assert_eq!(json["applied"], serde_json::json!(false));
     }
 
+    #[test]
+    fn every_operation_parses_back_from_its_name() {
+        for operation in DryRunOperation::ALL {
+            assert_eq!(operation.as_str().parse(), Ok(operation));
+        }
+        assert!("restore".parse::<DryRunOperation>().is_err());
+    }
+
     #[test]
     fn rendered_lines_name_the_action_the_target_and_the_source() {
         let mut plan = DryRunPlan::new("gog", "switch", "123");
@@ -3,13 +3,75 @@
 //! Descriptors name a hive as data, so the engine cannot use the `winreg`
 //! constants directly, and the engine itself is not Windows-only: a descriptor
 //! may describe a Linux profile with no registry at all. On a non-Windows
-//! build these become the honest answer, "there is no registry here".
+//! build the native functions become the honest answer, "there is no registry
+//! here", and [`Registry::Wine`] reaches the one a Wine prefix keeps instead.
 //!
 //! No sandbox check happens at run time: registry keys are literal strings in
 //! the descriptor, never derived from the environment, so the roots check at
 //! load time already covers every key that can ever be touched.
 
 use super::schema::RegistryHive;
+use super::wine;
+
+/// Which registry a profile's keys live in.
+#[derive(Debug, Clone)]
+pub enum Registry {
+    /// This machine's own, which outside Windows is none at all.
+    Native,
+    /// The text hives of a Wine prefix.
+    Wine(wine::Prefix),
+}
+
+impl Registry {
+    pub fn read(&self, root: RegistryHive, key: &str, value: &str) -> Option<String> {
+        match self {
+            Registry::Native => read(root, key, value),
+            Registry::Wine(prefix) => prefix.read(root, key, value),
+        }
+    }
+
+    pub fn write(
+        &self,
+        root: RegistryHive,
+        key: &str,
+        value: &str,
+        data: &str,
+    ) -> Result<(), String> {
+        match self {
+            Registry::Native => write(root, key, value, data),
+            Registry::Wine(prefix) => prefix.write(root, key, value, data),
+        }
+    }
+
+    pub fn delete(&self, root: RegistryHive, key: &str, value: &str) {
+        match self {
+            Registry::Native => delete(root, key, value),
+            Registry::Wine(prefix) => prefix.delete(root, key, value),
+        }
+    }
+
+    /// The recorded value, as the launcher wrote it. Inside a prefix that is a
+    /// Windows path; the caller maps it onto the host.
+    pub fn uninstall_entry(&self, display_name: &str, value: &str) -> Option<String> {
+        match self {
+            Registry::Native => uninstall_entry(display_name, value),
+            Registry::Wine(prefix) => prefix.uninstall_entry(display_name, value),
+        }
+    }
+
+    /// Like [`display`], naming the hive file when the value lives in one, so
+    /// a dry run says which prefix it would edit.
+    pub fn display(&self, hive: RegistryHive, key: &str, value: &str) -> String {
+        match self {
+            Registry::Native => display(hive, key, value),
+            Registry::Wine(prefix) => format!(
+                "{} (in {})",
+                display(hive, key, value),
+                prefix.hive_file(hive).display()
+            ),
+        }
+    }
+}
 
 /// Renders a value's full location for messages and dry-run output.
 pub fn display(hive: RegistryHive, key: &str, value: &str) -> String {
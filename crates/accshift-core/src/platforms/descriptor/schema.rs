@@ -106,8 +106,19 @@ impl Os {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Descriptor {
+    /// Ignored. Lets an editor find the published JSON Schema.
+    #[serde(default, rename = "$schema", skip_serializing)]
+    pub json_schema: Option<String>,
     /// Canonical platform id, the key everything else in the app uses.
     pub id: String,
+    /// The shipped descriptor this one starts from. Only the fields written
+    /// here replace the parent's; see [`Descriptor::parse`].
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub extends: String,
+    /// Dotted paths of the fields taken from `extends` unchanged, filled in by
+    /// the merge so a reader can tell what the file itself says.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Vec::is_empty")]
+    pub inherited: Vec<String>,
     pub schema_version: u32,
     /// Display name, and the one used in user-facing messages about the
     /// launcher itself ("Could not locate GOG Galaxy executable").
@@ -137,6 +148,10 @@ pub struct OsProfile {
     pub launch: Option<Launch>,
     #[serde(default)]
     pub setup: Setup,
+    /// A Windows launcher run through Wine on this system. Only a Linux or
+    /// macOS profile may declare one.
+    #[serde(default)]
+    pub wine: Option<Wine>,
 }
 
 /// The sandbox. Every path a descriptor reads or writes as state must sit
@@ -206,7 +221,7 @@ pub struct Executable {
     pub select_filter: String,
 }
 
-fn default_exe_filter() -> String {
+pub(super) fn default_exe_filter() -> String {
     "Executable files (*.exe)|*.exe|All files (*.*)|*.*".to_string()
 }
 
@@ -236,9 +251,13 @@ pub enum ExecutableCandidate {
         #[serde(default = "default_install_location")]
         value: String,
     },
+    /// A string in a JSON file the launcher keeps its own location in, read
+    /// with shared access. Like the other candidates it may name the
+    /// directory or the binary itself.
+    JsonPointer { path: PathTemplate, pointer: String },
 }
 
-fn default_install_location() -> String {
+pub(super) fn default_install_location() -> String {
     "InstallLocation".to_string()
 }
 
@@ -260,6 +279,14 @@ pub struct Identity {
     /// section carries the list.
     #[serde(default)]
     pub blocklist_on_forget: bool,
+    /// Values kept on each account beside its id and label, filled in by the
+    /// identity source whenever it reads that account signed in.
+    #[serde(default)]
+    pub fields: Vec<AccountField>,
+    /// How an account the user never labelled is named, from its fields:
+    /// `${account.gameName}#${account.tagLine}`. Empty leaves it unnamed.
+    #[serde(default)]
+    pub label_format: String,
 }
 
 /// A place account ids can be enumerated from.
@@ -281,6 +308,29 @@ pub enum Discovery {
         #[serde(default)]
         strip_extension: bool,
     },
+    /// Elements of an array in a JSON file the launcher keeps its remembered
+    /// logins in, read with shared access. Each element is an object; the id
+    /// is its `idField`, the name shown for it its `displayNameField`.
+    JsonArray {
+        path: PathTemplate,
+        /// RFC 6901 pointer to the array, e.g. `/accounts`. Empty when the
+        /// whole file is the array.
+        #[serde(default)]
+        pointer: String,
+        id_field: String,
+        /// Empty for none.
+        #[serde(default)]
+        display_name_field: String,
+    },
+}
+
+impl Discovery {
+    /// The file or directory the entry reads.
+    pub fn path(&self) -> &PathTemplate {
+        match self {
+            Discovery::DirectoryEntries { path, .. } | Discovery::JsonArray { path, .. } => path,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
@@ -332,9 +382,33 @@ pub enum IdentitySource {
         #[serde(default)]
         paths: BTreeMap<String, PathTemplate>,
     },
+    /// The id is a value in a JSON settings file, read with shared access.
+    JsonPointer {
+        path: PathTemplate,
+        /// RFC 6901 pointer to the id, e.g. `/account/userId`.
+        pointer: String,
+        /// Pointer to the name shown instead of the raw id. Empty for none.
+        #[serde(default)]
+        display_name_pointer: String,
+    },
+    /// The id is a key in an INI file, read with shared access.
+    IniKey {
+        path: PathTemplate,
+        /// Empty for the part of the file before any section header.
+        #[serde(default)]
+        section: String,
+        key: String,
+        /// Key in the same section holding the name shown instead of the id.
+        #[serde(default)]
+        display_name_key: String,
+    },
+    /// The id is the first column of the first row a query returns from a
+    /// SQLite database, opened read-only. A second column, when the query
+    /// selects one, is the name shown instead of the id.
+    SqliteQuery { path: PathTemplate, query: String },
 }
 
-fn default_tail_bytes() -> u64 {
+pub(super) fn default_tail_bytes() -> u64 {
     64 * 1024
 }
 
@@ -357,7 +431,7 @@ pub struct IdFormat {
     pub invalid_message: String,
 }
 
-fn default_min_length() -> usize {
+pub(super) fn default_min_length() -> usize {
     1
 }
 
@@ -371,9 +445,21 @@ pub enum Charset {
     Alphanumeric,
     /// The canonical 8-4-4-4-12 form, dashes included.
     Uuid,
+    /// Letters, digits, `-` and `_`. For ids a hand-written module minted
+    /// before its platform moved to a descriptor, which no narrower charset
+    /// describes; still nothing a path could be climbed out of with.
+    Slug,
 }
 
 impl Charset {
+    pub const ALL: [Charset; 5] = [
+        Charset::Digits,
+        Charset::Hex,
+        Charset::Alphanumeric,
+        Charset::Uuid,
+        Charset::Slug,
+    ];
+
     /// An id of `length` characters this charset accepts.
     ///
     /// Used to plan a switch for a platform that has no accounts yet, so the
@@ -384,7 +470,7 @@ impl Charset {
         match self {
             Charset::Uuid => "00000000-0000-0000-0000-000000000000".to_string(),
             Charset::Digits => "0".repeat(length),
-            Charset::Hex | Charset::Alphanumeric => "a".repeat(length),
+            Charset::Hex | Charset::Alphanumeric | Charset::Slug => "a".repeat(length),
         }
     }
 
@@ -400,6 +486,7 @@ impl Charset {
             Charset::Digits => c.is_ascii_digit(),
             Charset::Hex => c.is_ascii_hexdigit(),
             Charset::Alphanumeric => c.is_ascii_alphanumeric(),
+            Charset::Slug => c.is_ascii_alphanumeric() || matches!(c, '-' | '_'),
             Charset::Uuid => unreachable!("handled above"),
         })
     }
@@ -414,6 +501,77 @@ pub enum CurrentSource {
     Config,
 }
 
+/// One value a platform keeps about an account: a tag line, a region, an
+/// email, the path of an avatar.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AccountField {
+    /// What the value is stored under, and read back by as
+    /// `${account.<name>}`.
+    pub name: String,
+    /// Shown as the column heading in lists.
+    pub label: String,
+    #[serde(default, rename = "type")]
+    pub kind: FieldKind,
+    /// Where the identity source finds it: a JSON pointer for `jsonPointer`,
+    /// a key of the same section for `iniKey`, a column name for
+    /// `sqliteQuery`. A native hook reports its fields by name and takes none.
+    #[serde(default)]
+    pub from: String,
+}
+
+/// What a field holds. A value the launcher wrote that does not fit is
+/// dropped rather than stored, so a list never shows a half-parsed blob.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FieldKind {
+    #[default]
+    Text,
+    Number,
+    Email,
+    /// A file on this machine, such as an avatar. Not shown as a column.
+    Path,
+}
+
+/// Longest value a field keeps. Anything longer is not a tag line or a region.
+pub const MAX_FIELD_CHARS: usize = 256;
+
+impl FieldKind {
+    pub const ALL: [FieldKind; 4] = [
+        FieldKind::Text,
+        FieldKind::Number,
+        FieldKind::Email,
+        FieldKind::Path,
+    ];
+
+    /// The value as it is stored, or `None` when this kind does not accept it.
+    pub fn accept(&self, raw: &str) -> Option<String> {
+        let value = raw.trim();
+        if value.is_empty()
+            || value.chars().count() > MAX_FIELD_CHARS
+            || value.chars().any(char::is_control)
+        {
+            return None;
+        }
+        let fits = match self {
+            FieldKind::Text | FieldKind::Path => true,
+            FieldKind::Number => value.parse::<f64>().is_ok_and(f64::is_finite),
+            FieldKind::Email => value.split_once('@').is_some_and(|(user, domain)| {
+                !user.is_empty()
+                    && domain.contains('.')
+                    && !domain.contains('@')
+                    && !value.contains(char::is_whitespace)
+            }),
+        };
+        fits.then(|| value.to_string())
+    }
+
+    /// Whether a list shows it as a column.
+    pub fn is_listed(&self) -> bool {
+        !matches!(self, FieldKind::Path)
+    }
+}
+
 /// The per-account material captured, restored and cleared.
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
@@ -424,6 +582,10 @@ pub struct State {
     pub directories: Vec<DirItem>,
     #[serde(default)]
     pub registry_values: Vec<RegistryItem>,
+    /// Selected keys inside a file the launcher shares with settings that are
+    /// not the session. Only those keys are captured and restored.
+    #[serde(default)]
+    pub keys: Vec<KeyItem>,
     /// Directories wiped once the incoming session is in place, and never
     /// captured. A launcher cache keyed to the outgoing account makes the next
     /// sign-in show the wrong name, or fail outright.
@@ -441,6 +603,7 @@ impl State {
         self.files.is_empty()
             && self.directories.is_empty()
             && self.registry_values.is_empty()
+            && self.keys.is_empty()
             && self.caches.is_empty()
     }
 }
@@ -505,6 +668,52 @@ pub struct RegistryItem {
     pub clear_snapshot_when_source_missing: bool,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct KeyItem {
+    /// Live file holding the keys. Everything else in it is left as it is.
+    pub live: PathSpec,
+    /// File name inside the account's snapshot directory holding the captured
+    /// keys, encrypted like every other snapshot file.
+    pub snapshot: String,
+    pub select: KeySelect,
+    /// The selected keys are removed when a setup flow clears the live session.
+    #[serde(default)]
+    pub clear_on_setup: bool,
+    #[serde(default)]
+    pub snapshot_marker: bool,
+    /// Drop a stale snapshot when the live file, or every selected key in it,
+    /// is gone at capture time.
+    #[serde(default = "default_true")]
+    pub clear_snapshot_when_source_missing: bool,
+}
+
+/// Which keys a [`KeyItem`] owns, in the syntax of the file's format.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum KeySelect {
+    /// RFC 6901 pointers into a JSON document, e.g. `/auth/refreshToken`.
+    JsonPointer { pointers: Vec<String> },
+    /// Keys of an INI file. An empty `section` is the part before the first
+    /// section header.
+    IniKey { keys: Vec<IniKey> },
+    /// Valve KeyValues paths from the root key down, separated by `/`, e.g.
+    /// `UserLocalConfigStore/friends/PersonaName`.
+    KeyValues { paths: Vec<String> },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct IniKey {
+    #[serde(default)]
+    pub section: String,
+    pub key: String,
+}
+
 /// How the launcher is shut down before its files are touched.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
@@ -522,6 +731,16 @@ pub struct Close {
     /// on exit are captured empty otherwise.
     #[serde(default)]
     pub before_capture: bool,
+    /// Polite ways to ask the launcher to exit, tried in order. The processes
+    /// are terminated only once every one of them has failed or timed out: a
+    /// launcher killed mid-write can corrupt its own config.
+    #[serde(default)]
+    pub strategies: Vec<CloseStrategy>,
+    /// Processes that mean the launcher is in use, typically the games it
+    /// started. A switch or a setup is refused while any of them runs rather
+    /// than closing the launcher out from under a game.
+    #[serde(default)]
+    pub refuse_while_running: Vec<String>,
 }
 
 impl Default for Close {
@@ -531,8 +750,166 @@ impl Default for Close {
             timeout_ms: default_quit_timeout_ms(),
             settle_ms: default_settle_ms(),
             before_capture: false,
+            strategies: Vec::new(),
+            refuse_while_running: Vec::new(),
+        }
+    }
+}
+
+/// One way of asking the launcher to exit. `timeoutMs` is how long the
+/// processes get to exit once the request went through, before the next
+/// strategy is tried.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum CloseStrategy {
+    /// Runs a program, typically the launcher's own binary with its shutdown
+    /// flag. A bare name is looked up on `PATH`; `${installDir}` reaches the
+    /// launcher's directory.
+    Command {
+        command: PathTemplate,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default = "default_strategy_timeout_ms")]
+        timeout_ms: u32,
+    },
+    /// Opens a URI the launcher registered a handler for, e.g. `steam://exit`.
+    Uri {
+        uri: String,
+        #[serde(default = "default_strategy_timeout_ms")]
+        timeout_ms: u32,
+    },
+    /// POSTs to an endpoint the launcher serves on this machine. Local
+    /// services use self-signed certificates, so the certificate is not
+    /// checked; the host must be a loopback address.
+    HttpPost {
+        url: String,
+        #[serde(default)]
+        body: String,
+        #[serde(default = "default_strategy_timeout_ms")]
+        timeout_ms: u32,
+    },
+    /// Asks the launcher's windows to close, as their close button would. On
+    /// Linux and macOS the processes are sent `SIGTERM` instead.
+    CloseWindow {
+        #[serde(default = "default_strategy_timeout_ms")]
+        timeout_ms: u32,
+    },
+    /// Leaves the request to the identity source's native hook, for a
+    /// launcher that only takes it through a channel the hook knows how to
+    /// open: a local API whose password sits in a lockfile, say.
+    NativeHook {
+        #[serde(default = "default_strategy_timeout_ms")]
+        timeout_ms: u32,
+    },
+}
+
+impl CloseStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CloseStrategy::Command { .. } => "command",
+            CloseStrategy::Uri { .. } => "uri",
+            CloseStrategy::HttpPost { .. } => "httpPost",
+            CloseStrategy::CloseWindow { .. } => "closeWindow",
+            CloseStrategy::NativeHook { .. } => "nativeHook",
+        }
+    }
+
+    pub fn timeout_ms(&self) -> u32 {
+        match self {
+            CloseStrategy::Command { timeout_ms, .. }
+            | CloseStrategy::Uri { timeout_ms, .. }
+            | CloseStrategy::HttpPost { timeout_ms, .. }
+            | CloseStrategy::CloseWindow { timeout_ms }
+            | CloseStrategy::NativeHook { timeout_ms } => *timeout_ms,
         }
     }
+
+    fn validate(&self, source: &str, field: &str) -> Result<(), DescriptorError> {
+        if self.timeout_ms() == 0 {
+            return Err(DescriptorError::new(
+                source,
+                format!("{field}.timeoutMs"),
+                "expected a wait above zero, found 0: a request nobody waits for cannot work",
+            ));
+        }
+        match self {
+            CloseStrategy::Command { command, .. } => {
+                command.validate(source, &format!("{field}.command"))
+            }
+            CloseStrategy::Uri { uri, .. } => match uri_scheme(uri) {
+                Some(scheme) if !matches!(scheme.as_str(), "http" | "https" | "file") => Ok(()),
+                Some(scheme) => Err(DescriptorError::new(
+                    source,
+                    format!("{field}.uri"),
+                    format!(
+                        "expected a launcher's own scheme, found `{scheme}:`: use `httpPost` for a web request"
+                    ),
+                )),
+                None => Err(DescriptorError::new(
+                    source,
+                    format!("{field}.uri"),
+                    format!("expected `scheme:...` with no whitespace, found `{uri}`"),
+                )),
+            },
+            CloseStrategy::HttpPost { url, .. } => {
+                if is_loopback_url(url) {
+                    Ok(())
+                } else {
+                    Err(DescriptorError::new(
+                        source,
+                        format!("{field}.url"),
+                        format!(
+                            "expected an http(s) URL on 127.0.0.1, localhost or [::1], found `{url}`"
+                        ),
+                    ))
+                }
+            }
+            CloseStrategy::CloseWindow { .. } | CloseStrategy::NativeHook { .. } => Ok(()),
+        }
+    }
+}
+
+fn default_strategy_timeout_ms() -> u32 {
+    5000
+}
+
+/// The lowercased scheme of a URI, when it has a well-formed one and no
+/// whitespace anywhere.
+pub fn uri_scheme(uri: &str) -> Option<String> {
+    if uri.chars().any(char::is_whitespace) {
+        return None;
+    }
+    let (scheme, rest) = uri.split_once(':')?;
+    let well_formed = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    (well_formed && !rest.is_empty()).then(|| scheme.to_ascii_lowercase())
+}
+
+/// Whether `url` is http or https on a loopback host. Anything else would
+/// send a request off the machine.
+pub fn is_loopback_url(url: &str) -> bool {
+    let Some(rest) = url
+        .strip_prefix("http://")
+        .or_else(|| url.strip_prefix("https://"))
+    else {
+        return false;
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = if let Some(bracketed) = authority.strip_prefix('[') {
+        match bracketed.split_once(']') {
+            Some((host, port)) if port.is_empty() || port.starts_with(':') => host,
+            _ => return false,
+        }
+    } else {
+        authority.split(':').next().unwrap_or("")
+    };
+    matches!(host, "127.0.0.1" | "localhost" | "::1")
 }
 
 fn default_quit_timeout_ms() -> u32 {
@@ -548,6 +925,10 @@ fn default_settle_ms() -> u64 {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Launch {
+    /// `${account.<name>}` expands to a value of the account being launched:
+    /// `id`, `label`, or one the user stored on it. An argument naming a value
+    /// the account does not have is left out whole, so a flag and its value
+    /// belong in one argument (`--lang=${account.lang}`).
     #[serde(default)]
     pub args: Vec<String>,
     /// Pass `args` only when the resolved binary carries this name, empty to
@@ -560,6 +941,10 @@ pub struct Launch {
     /// resolve their own resources relative to it.
     #[serde(default = "default_true")]
     pub working_directory_is_install_dir: bool,
+    /// Environment for the launched process, expanded like `args`. A variable
+    /// naming a value the account does not have is not set.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
 }
 
 impl Launch {
@@ -580,6 +965,59 @@ impl Launch {
     }
 }
 
+/// The Wine prefix a Windows launcher runs in, under Wine, Proton, or a
+/// manager built on them.
+///
+/// With one declared, the Windows variables (`${LOCALAPPDATA}`,
+/// `${ProgramFiles(x86)}`, ...) and drive-absolute paths resolve inside the
+/// prefix's `drive_c`, `${WINEPREFIX}` is the prefix itself, and registry
+/// values are read from and written to its `user.reg` and `system.reg`. The
+/// rest of the profile is written exactly as it would be for Windows.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Wine {
+    /// The prefix directory, the one holding `drive_c`. A list covers the
+    /// places different managers put it; the first that exists wins.
+    pub prefix: PathSpec,
+    /// The Windows user inside the prefix. Empty picks whichever the prefix
+    /// has: the login name under Wine, `steamuser` under Proton.
+    #[serde(default)]
+    pub user: String,
+    #[serde(default)]
+    pub runner: Runner,
+}
+
+/// What starts a binary inside the prefix: `wine` itself, a Proton script, or
+/// a wrapper such as `umu-run`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Runner {
+    /// A bare name is looked up on `PATH`; anything else is a path template.
+    #[serde(default = "default_wine_runner")]
+    pub command: PathTemplate,
+    /// Passed before the launcher binary (`run` for Proton).
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment for the runner, resolved like a path so a value can
+    /// name the prefix. `WINEPREFIX` is always set.
+    #[serde(default)]
+    pub env: BTreeMap<String, PathTemplate>,
+}
+
+impl Default for Runner {
+    fn default() -> Self {
+        Self {
+            command: default_wine_runner(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_wine_runner() -> PathTemplate {
+    PathTemplate::new("wine")
+}
+
 /// The "sign in to add an account" flow.
 ///
 /// `trigger` is polled while the user signs in. Once every trigger holds, the
@@ -803,18 +1241,83 @@ impl PathSpec {
 
 impl Descriptor {
     /// Parse and validate in one step, so no unvalidated descriptor exists.
+    ///
+    /// A descriptor naming `extends` is merged over that shipped descriptor
+    /// first and validated as the result: objects merge key by key, anything
+    /// else the file writes replaces the parent's value whole, and `null`
+    /// removes it. Lists replace rather than append, so a file can always say
+    /// exactly which roots or processes it means.
     pub fn parse(source: &str, json: &str) -> Result<Self, DescriptorError> {
-        let descriptor: Descriptor = serde_json::from_str(json).map_err(|e| {
+        let read_error = |e: serde_json::Error| {
             DescriptorError::new(
                 source,
                 format!("line {} column {}", e.line(), e.column()),
                 format!("could not be read: {e}"),
             )
-        })?;
+        };
+        let value: serde_json::Value = serde_json::from_str(json).map_err(read_error)?;
+        let descriptor = if value.get("extends").is_some() {
+            Self::extend(source, value)?
+        } else {
+            // Straight from the text rather than the value, so an unknown
+            // field is still located by line and column.
+            serde_json::from_str(json).map_err(read_error)?
+        };
         descriptor.validate(source)?;
         Ok(descriptor)
     }
 
+    /// Merges `child` over the shipped descriptor it extends.
+    fn extend(source: &str, child: serde_json::Value) -> Result<Self, DescriptorError> {
+        let Some(parent_id) = child["extends"].as_str().map(str::to_string) else {
+            return Err(DescriptorError::new(
+                source,
+                "extends",
+                format!(
+                    "expected the id of a shipped descriptor, found {}",
+                    child["extends"]
+                ),
+            ));
+        };
+        let Some(body) = super::shipped_body(&parent_id) else {
+            return Err(DescriptorError::new(
+                source,
+                "extends",
+                format!(
+                    "expected the id of a shipped descriptor ({}), found `{parent_id}`",
+                    super::shipped_ids().join(", ")
+                ),
+            ));
+        };
+        // An id is the one thing never inherited: two platforms answering to
+        // one id would share accounts, snapshots and settings.
+        match child["id"].as_str() {
+            Some(id) if id != parent_id => {}
+            _ => {
+                return Err(DescriptorError::new(
+                    source,
+                    "id",
+                    format!("expected an id of its own, distinct from `{parent_id}`"),
+                ))
+            }
+        }
+
+        let mut merged: serde_json::Value = serde_json::from_str(body).map_err(|e| {
+            DescriptorError::new(source, "extends", format!("could not be read: {e}"))
+        })?;
+        let mut inherited = Vec::new();
+        merge_over(&mut merged, child, "", &mut inherited);
+        let mut descriptor: Descriptor = serde_json::from_value(merged).map_err(|e| {
+            DescriptorError::new(
+                source,
+                "",
+                format!("could not be read once merged over `{parent_id}`: {e}"),
+            )
+        })?;
+        descriptor.inherited = inherited;
+        Ok(descriptor)
+    }
+
     /// The profile for the OS this build runs on, if the platform supports it.
     pub fn current_profile(&self) -> Option<&OsProfile> {
         Os::current().and_then(|os| self.os.get(&os))
@@ -848,13 +1351,62 @@ impl Descriptor {
                 "expected at least one of `windows`, `macos` or `linux`, found none",
             ));
         }
+        // A platform written on top of a shipped one keeps that platform's
+        // entitlements: a portable install of an allowlisted launcher still
+        // needs its hook.
+        let lineage = if self.extends.is_empty() {
+            &self.id
+        } else {
+            &self.extends
+        };
         for (os, profile) in &self.os {
-            profile.validate(source, &format!("os.{}", os.as_str()), &self.id)?;
+            profile.validate(source, &format!("os.{}", os.as_str()), lineage, *os)?;
         }
         Ok(())
     }
 }
 
+/// Merges `patch` into `base` as a JSON merge patch (RFC 7396), recording the
+/// dotted path of every key `base` keeps untouched.
+fn merge_over(
+    base: &mut serde_json::Value,
+    patch: serde_json::Value,
+    path: &str,
+    inherited: &mut Vec<String>,
+) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match (base, patch) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(patch)) => {
+            for key in base.keys() {
+                if !patch.contains_key(key) {
+                    inherited.push(join(key));
+                }
+            }
+            for (key, value) in patch {
+                if value.is_null() {
+                    base.remove(&key);
+                    continue;
+                }
+                match base.get_mut(&key) {
+                    Some(existing) if existing.is_object() && value.is_object() => {
+                        merge_over(existing, value, &join(&key), inherited);
+                    }
+                    _ => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
 fn validate_id(source: &str, field: &str, id: &str) -> Result<(), DescriptorError> {
     let ok = !id.is_empty()
         && id.len() <= 32
@@ -878,6 +1430,7 @@ impl OsProfile {
         source: &str,
         field: &str,
         platform_id: &str,
+        os: Os,
     ) -> Result<(), DescriptorError> {
         // The escape hatch is per platform, and it is checked here rather than
         // where the source is validated so the allowlist reads once.
@@ -939,6 +1492,7 @@ impl OsProfile {
         self.identity
             .validate(source, &format!("{field}.identity"), &self.roots)?;
         self.validate_state(source, field)?;
+        self.validate_wine(source, field, os)?;
 
         if let Some(launch) = &self.launch {
             let guard = launch.args_only_for.trim();
@@ -956,17 +1510,55 @@ impl OsProfile {
                     "expected the arguments `argsOnlyFor` guards, found none",
                 ));
             }
+            for (index, arg) in launch.args.iter().enumerate() {
+                validate_launch_template(source, &format!("{field}.launch.args[{index}]"), arg)?;
+            }
+            for (name, value) in &launch.env {
+                if !is_env_name(name) {
+                    return Err(DescriptorError::new(
+                        source,
+                        format!("{field}.launch.env"),
+                        format!("expected an environment variable name, found `{name}`"),
+                    ));
+                }
+                validate_launch_template(source, &format!("{field}.launch.env.{name}"), value)?;
+            }
         }
 
-        for (index, process) in self.close.processes.iter().enumerate() {
-            if process.trim().is_empty() || process.contains(['/', '\\']) {
+        for (index, strategy) in self.close.strategies.iter().enumerate() {
+            let at = format!("{field}.close.strategies[{index}]");
+            strategy.validate(source, &at)?;
+            if matches!(strategy, CloseStrategy::NativeHook { .. })
+                && !matches!(self.identity.source, IdentitySource::NativeHook { .. })
+            {
                 return Err(DescriptorError::new(
                     source,
-                    format!("{field}.close.processes[{index}]"),
-                    format!("expected a bare process name, found `{process}`"),
+                    at,
+                    "expected a `nativeHook` identity source to ask, found another kind",
                 ));
             }
         }
+        if !self.close.strategies.is_empty() && self.close.processes.is_empty() {
+            return Err(DescriptorError::new(
+                source,
+                format!("{field}.close.processes"),
+                "expected the processes the strategies wait on, found none",
+            ));
+        }
+        for (list, processes) in [
+            ("processes", &self.close.processes),
+            ("refuseWhileRunning", &self.close.refuse_while_running),
+        ] {
+            for (index, process) in processes.iter().enumerate() {
+                if process.trim().is_empty() || process.contains(['/', '\\']) {
+                    return Err(DescriptorError::new(
+                        source,
+                        format!("{field}.close.{list}[{index}]"),
+                        format!("expected a bare process name, found `{process}`"),
+                    ));
+                }
+            }
+        }
 
         for (index, condition) in self
             .setup
@@ -989,6 +1581,88 @@ impl OsProfile {
         Ok(())
     }
 
+    fn validate_wine(&self, source: &str, field: &str, os: Os) -> Result<(), DescriptorError> {
+        let Some(wine) = &self.wine else {
+            // Outside Windows a registry only exists inside a prefix. Without
+            // one, every read would come back empty and every write fail,
+            // halfway through a switch instead of here.
+            if os != Os::Windows {
+                if let Some(at) = self.first_registry_use(field) {
+                    return Err(DescriptorError::new(
+                        source,
+                        at,
+                        format!(
+                            "expected a `wine` prefix to hold the registry, found none on a {} profile",
+                            os.as_str()
+                        ),
+                    ));
+                }
+            }
+            return Ok(());
+        };
+        let at = format!("{field}.wine");
+        if os == Os::Windows {
+            return Err(DescriptorError::new(
+                source,
+                at,
+                "expected no Wine prefix on a Windows profile, found one",
+            ));
+        }
+        wine.prefix.validate(source, &format!("{at}.prefix"))?;
+        // The install directory is found through the prefix, so the prefix
+        // cannot be found through it.
+        if wine.prefix.placeholders().iter().any(|p| p == INSTALL_DIR) {
+            return Err(DescriptorError::new(
+                source,
+                format!("{at}.prefix"),
+                "expected a template that does not use `${installDir}`: the install directory is located inside the prefix",
+            ));
+        }
+        let user = wine.user.as_str();
+        if user != user.trim() || user.contains(['/', '\\']) || user == "." || user == ".." {
+            return Err(DescriptorError::new(
+                source,
+                format!("{at}.user"),
+                format!("expected a bare Windows user name, found `{user}`"),
+            ));
+        }
+        wine.runner
+            .command
+            .validate(source, &format!("{at}.runner.command"))?;
+        for (name, value) in &wine.runner.env {
+            if !is_env_name(name) {
+                return Err(DescriptorError::new(
+                    source,
+                    format!("{at}.runner.env"),
+                    format!("expected an environment variable name, found `{name}`"),
+                ));
+            }
+            value.validate(source, &format!("{at}.runner.env.{name}"))?;
+        }
+        Ok(())
+    }
+
+    /// The field of the first thing in the profile that reads or writes the
+    /// registry, if anything does.
+    fn first_registry_use(&self, field: &str) -> Option<String> {
+        if let IdentitySource::Registry { .. } = self.identity.source {
+            return Some(format!("{field}.identity.source"));
+        }
+        if !self.state.registry_values.is_empty() {
+            return Some(format!("{field}.state.registryValues"));
+        }
+        let candidates = self.executable.iter().flat_map(|e| e.candidates.iter());
+        for (index, candidate) in candidates.enumerate() {
+            if matches!(
+                candidate,
+                ExecutableCandidate::Registry { .. } | ExecutableCandidate::UninstallEntry { .. }
+            ) {
+                return Some(format!("{field}.executable.candidates[{index}]"));
+            }
+        }
+        None
+    }
+
     fn validate_state(&self, source: &str, field: &str) -> Result<(), DescriptorError> {
         let mut snapshot_names: Vec<&str> = Vec::new();
 
@@ -1036,6 +1710,13 @@ impl OsProfile {
             validate_snapshot_name(source, &format!("{at}.snapshot"), &item.snapshot)?;
             snapshot_names.push(&item.snapshot);
         }
+        for (index, item) in self.state.keys.iter().enumerate() {
+            let at = format!("{field}.state.keys[{index}]");
+            validate_spec_in_file_roots(source, &format!("{at}.live"), &item.live, &self.roots)?;
+            validate_snapshot_name(source, &format!("{at}.snapshot"), &item.snapshot)?;
+            item.select.validate(source, &format!("{at}.select"))?;
+            snapshot_names.push(&item.snapshot);
+        }
 
         // Two entries writing the same snapshot name would silently overwrite
         // each other, and the loser would restore the winner's bytes.
@@ -1056,19 +1737,73 @@ impl OsProfile {
     }
 }
 
-impl Executable {
+impl KeySelect {
     fn validate(&self, source: &str, field: &str) -> Result<(), DescriptorError> {
-        if self.file_name.trim().is_empty() || self.file_name.contains(['/', '\\']) {
+        let empty = match self {
+            Self::JsonPointer { pointers } => pointers.is_empty(),
+            Self::IniKey { keys } => keys.is_empty(),
+            Self::KeyValues { paths } => paths.is_empty(),
+        };
+        if empty {
+            // An item selecting nothing would capture an empty snapshot and
+            // still count as one.
             return Err(DescriptorError::new(
                 source,
-                format!("{field}.fileName"),
-                format!("expected a bare binary name, found `{}`", self.file_name),
+                field,
+                "expected at least one key, found none",
             ));
         }
-        if self.candidates.is_empty() {
-            return Err(DescriptorError::new(
-                source,
-                format!("{field}.candidates"),
+        match self {
+            Self::JsonPointer { pointers } => {
+                for (index, pointer) in pointers.iter().enumerate() {
+                    validate_json_pointer(source, &format!("{field}.pointers[{index}]"), pointer)?;
+                }
+            }
+            Self::IniKey { keys } => {
+                for (index, entry) in keys.iter().enumerate() {
+                    let at = format!("{field}.keys[{index}]");
+                    validate_ini_key(source, &format!("{at}.key"), &entry.key)?;
+                    validate_ini_section(source, &format!("{at}.section"), &entry.section)?;
+                }
+            }
+            Self::KeyValues { paths } => {
+                for (index, path) in paths.iter().enumerate() {
+                    // A root key and a key inside it at the very least: a
+                    // KeyValues file has nothing but sections at its top.
+                    let segments: Vec<&str> = path.split('/').collect();
+                    let bad = segments.len() < 2
+                        || segments.iter().any(|segment| {
+                            segment.is_empty() || segment.contains(['"', '\n', '\r'])
+                        });
+                    if bad {
+                        return Err(DescriptorError::new(
+                            source,
+                            format!("{field}.paths[{index}]"),
+                            format!(
+                                "expected a `/`-separated path from the root key down, found `{path}`"
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Executable {
+    fn validate(&self, source: &str, field: &str) -> Result<(), DescriptorError> {
+        if self.file_name.trim().is_empty() || self.file_name.contains(['/', '\\']) {
+            return Err(DescriptorError::new(
+                source,
+                format!("{field}.fileName"),
+                format!("expected a bare binary name, found `{}`", self.file_name),
+            ));
+        }
+        if self.candidates.is_empty() {
+            return Err(DescriptorError::new(
+                source,
+                format!("{field}.candidates"),
                 "expected at least one place to look for the binary, found none",
             ));
         }
@@ -1111,6 +1846,17 @@ impl Executable {
                         ));
                     }
                 }
+                ExecutableCandidate::JsonPointer { path, pointer } => {
+                    path.validate(source, &format!("{at}.path"))?;
+                    if path.placeholders().iter().any(|p| p == INSTALL_DIR) {
+                        return Err(DescriptorError::new(
+                            source,
+                            format!("{at}.path"),
+                            "expected a template that does not use `${installDir}`: the install directory is what this candidate resolves",
+                        ));
+                    }
+                    validate_json_pointer(source, &format!("{at}.pointer"), pointer)?;
+                }
                 ExecutableCandidate::UninstallEntry {
                     display_name,
                     value,
@@ -1175,6 +1921,32 @@ impl Identity {
                         }
                     }
                 }
+                Discovery::JsonArray {
+                    path,
+                    pointer,
+                    id_field,
+                    display_name_field,
+                } => {
+                    path.validate(source, &format!("{at}.path"))?;
+                    validate_in_file_roots(source, &format!("{at}.path"), path, roots)?;
+                    if !pointer.is_empty() {
+                        validate_json_pointer(source, &format!("{at}.pointer"), pointer)?;
+                    }
+                    if id_field.trim().is_empty() {
+                        return Err(DescriptorError::new(
+                            source,
+                            format!("{at}.idField"),
+                            "expected the name of the field holding the id, found an empty string",
+                        ));
+                    }
+                    if display_name_field == id_field {
+                        return Err(DescriptorError::new(
+                            source,
+                            format!("{at}.displayNameField"),
+                            "expected a field other than `idField`: the id would be shown as its own name",
+                        ));
+                    }
+                }
             }
         }
         if self.blocklist_on_forget && self.discovery.is_empty() {
@@ -1242,6 +2014,45 @@ impl Identity {
                     ));
                 }
             }
+            IdentitySource::JsonPointer {
+                path,
+                pointer,
+                display_name_pointer,
+            } => {
+                path.validate(source, &format!("{field}.source.path"))?;
+                validate_in_file_roots(source, &format!("{field}.source.path"), path, roots)?;
+                validate_json_pointer(source, &format!("{field}.source.pointer"), pointer)?;
+                if !display_name_pointer.is_empty() {
+                    validate_json_pointer(
+                        source,
+                        &format!("{field}.source.displayNamePointer"),
+                        display_name_pointer,
+                    )?;
+                }
+            }
+            IdentitySource::IniKey {
+                path,
+                section,
+                key,
+                display_name_key,
+            } => {
+                path.validate(source, &format!("{field}.source.path"))?;
+                validate_in_file_roots(source, &format!("{field}.source.path"), path, roots)?;
+                validate_ini_section(source, &format!("{field}.source.section"), section)?;
+                validate_ini_key(source, &format!("{field}.source.key"), key)?;
+                if !display_name_key.is_empty() {
+                    validate_ini_key(
+                        source,
+                        &format!("{field}.source.displayNameKey"),
+                        display_name_key,
+                    )?;
+                }
+            }
+            IdentitySource::SqliteQuery { path, query } => {
+                path.validate(source, &format!("{field}.source.path"))?;
+                validate_in_file_roots(source, &format!("{field}.source.path"), path, roots)?;
+                validate_sqlite_query(source, &format!("{field}.source.query"), query)?;
+            }
             IdentitySource::NativeHook { name, paths } => {
                 let hook = validate_hook_name(source, &format!("{field}.source.name"), name)?;
                 // The hook only ever sees paths the descriptor declared here,
@@ -1265,6 +2076,101 @@ impl Identity {
                 }
             }
         }
+        self.validate_fields(source, field)
+    }
+
+    fn validate_fields(&self, source: &str, field: &str) -> Result<(), DescriptorError> {
+        let reads_id_alone = match &self.source {
+            IdentitySource::Registry { .. } => Some("registry"),
+            IdentitySource::Synthetic => Some("synthetic"),
+            IdentitySource::LogTail { .. } => Some("logTail"),
+            _ => None,
+        };
+        if let (Some(kind), false) = (reads_id_alone, self.fields.is_empty()) {
+            return Err(DescriptorError::new(
+                source,
+                format!("{field}.fields"),
+                format!("expected no fields: a `{kind}` source reads the id alone"),
+            ));
+        }
+
+        let mut seen: Vec<&str> = Vec::new();
+        for (index, declared) in self.fields.iter().enumerate() {
+            let at = format!("{field}.fields[{index}]");
+            let name = declared.name.as_str();
+            if !is_launch_value_name(name) {
+                return Err(DescriptorError::new(
+                    source,
+                    format!("{at}.name"),
+                    format!("expected letters, digits, `_` or `-`, found `{name}`"),
+                ));
+            }
+            if BUILTIN_LAUNCH_VALUES
+                .iter()
+                .chain(&seen)
+                .any(|taken| taken.eq_ignore_ascii_case(name))
+            {
+                return Err(DescriptorError::new(
+                    source,
+                    format!("{at}.name"),
+                    format!(
+                        "expected a name no other field or built-in value uses, found `{name}`"
+                    ),
+                ));
+            }
+            seen.push(name);
+            if declared.label.trim().is_empty() {
+                return Err(DescriptorError::new(
+                    source,
+                    format!("{at}.label"),
+                    "expected a column heading, found an empty string",
+                ));
+            }
+            let from = declared.from.as_str();
+            let from_at = format!("{at}.from");
+            match &self.source {
+                IdentitySource::JsonPointer { .. } => {
+                    validate_json_pointer(source, &from_at, from)?
+                }
+                IdentitySource::IniKey { .. } => validate_ini_key(source, &from_at, from)?,
+                IdentitySource::SqliteQuery { .. } => {
+                    if from.trim().is_empty() || from != from.trim() {
+                        return Err(DescriptorError::new(
+                            source,
+                            from_at,
+                            format!(
+                                "expected the name of a column the query selects, found `{from}`"
+                            ),
+                        ));
+                    }
+                }
+                IdentitySource::NativeHook { .. } => {
+                    if !from.is_empty() {
+                        return Err(DescriptorError::new(
+                            source,
+                            from_at,
+                            format!("expected nothing: a native hook reports its fields by name, found `{from}`"),
+                        ));
+                    }
+                }
+                _ => unreachable!("refused above"),
+            }
+        }
+
+        if !self.label_format.is_empty() {
+            let at = format!("{field}.labelFormat");
+            validate_launch_template(source, &at, &self.label_format)?;
+            // The label itself is what this produces, so it cannot be read.
+            for name in account_value_names(&self.label_format) {
+                if name != "id" && !seen.contains(&name) {
+                    return Err(DescriptorError::new(
+                        source,
+                        at,
+                        format!("expected `id` or a declared field, found `${{account.{name}}}`"),
+                    ));
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -1317,6 +2223,107 @@ impl Condition {
 /// Name of the placeholder standing for the launcher's install directory.
 pub const INSTALL_DIR: &str = "installDir";
 
+/// What a `launch` placeholder starts with: `${account.lang}` reads the
+/// account's `lang` value.
+pub const ACCOUNT_PLACEHOLDER: &str = "account.";
+
+/// Launch values every account has, whatever the user stored on it.
+pub const BUILTIN_LAUNCH_VALUES: &[&str] = &["id", "label"];
+
+/// A name the user can store a launch value under and a descriptor can read
+/// it back by.
+pub fn is_launch_value_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+}
+
+/// Expands the `${account.<name>}` placeholders of one launch argument or
+/// environment value. The error is the first name `values` has no entry for.
+pub fn expand_account_values(
+    template: &str,
+    values: &BTreeMap<String, String>,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        // Validation refused anything else, so this only guards a template
+        // that never went through it.
+        let Some(end) = after.find('}') else {
+            return Err(after.to_string());
+        };
+        let name = after[..end]
+            .strip_prefix(ACCOUNT_PLACEHOLDER)
+            .unwrap_or(&after[..end]);
+        match values.get(name) {
+            Some(value) if !value.is_empty() => out.push_str(value),
+            _ => return Err(name.to_string()),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// The names a template reads through `${account.<name>}`, in order.
+pub fn account_value_names(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        if let Some(name) = after[..end].strip_prefix(ACCOUNT_PLACEHOLDER) {
+            names.push(name);
+        }
+        rest = &after[end + 1..];
+    }
+    names
+}
+
+fn validate_launch_template(
+    source: &str,
+    field: &str,
+    template: &str,
+) -> Result<(), DescriptorError> {
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            return Err(DescriptorError::new(
+                source,
+                field,
+                format!("expected every `${{` to be closed by `}}`, found `{template}`"),
+            ));
+        };
+        let placeholder = &after[..end];
+        let valid = placeholder
+            .strip_prefix(ACCOUNT_PLACEHOLDER)
+            .is_some_and(is_launch_value_name);
+        if !valid {
+            return Err(DescriptorError::new(
+                source,
+                field,
+                format!(
+                    "expected `${{account.<name>}}` with a name of letters, digits, `_` or `-`, found `${{{placeholder}}}`"
+                ),
+            ));
+        }
+        rest = &after[end + 1..];
+    }
+    Ok(())
+}
+
+fn is_env_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 /// Resolves a hook name against the compiled registry. A descriptor naming a
 /// hook this build does not have would otherwise be a step that silently does
 /// nothing on the machine where it matters.
@@ -1369,6 +2376,71 @@ fn validate_snapshot_name(source: &str, field: &str, name: &str) -> Result<(), D
     }
 }
 
+fn validate_json_pointer(source: &str, field: &str, pointer: &str) -> Result<(), DescriptorError> {
+    // The empty pointer is the whole document, which is never one key.
+    let escapes_ok = pointer
+        .match_indices('~')
+        .all(|(at, _)| matches!(pointer.as_bytes().get(at + 1), Some(b'0' | b'1')));
+    if pointer.starts_with('/') && escapes_ok {
+        Ok(())
+    } else {
+        Err(DescriptorError::new(
+            source,
+            field,
+            format!("expected a JSON pointer such as `/auth/token`, found `{pointer}`"),
+        ))
+    }
+}
+
+fn validate_ini_key(source: &str, field: &str, key: &str) -> Result<(), DescriptorError> {
+    let ok = !key.trim().is_empty()
+        && key == key.trim()
+        && !key.contains(['=', '\n', '\r'])
+        && !key.starts_with(['[', ';', '#']);
+    if ok {
+        Ok(())
+    } else {
+        Err(DescriptorError::new(
+            source,
+            field,
+            format!("expected an INI key name, found `{key}`"),
+        ))
+    }
+}
+
+fn validate_ini_section(source: &str, field: &str, section: &str) -> Result<(), DescriptorError> {
+    if !section.contains([']', '\n', '\r']) && section == section.trim() {
+        Ok(())
+    } else {
+        Err(DescriptorError::new(
+            source,
+            field,
+            format!("expected an INI section name, found `{section}`"),
+        ))
+    }
+}
+
+/// A query that can only read: one `SELECT` (or `WITH ... SELECT`) statement.
+/// The database is opened read-only as well; this makes the mistake visible
+/// at load instead of at the first poll.
+fn validate_sqlite_query(source: &str, field: &str, query: &str) -> Result<(), DescriptorError> {
+    let body = query.trim().trim_end_matches(';').trim_end();
+    let first = body
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if (first == "select" || first == "with") && !body.contains(';') {
+        Ok(())
+    } else {
+        Err(DescriptorError::new(
+            source,
+            field,
+            format!("expected a single SELECT statement, found `{query}`"),
+        ))
+    }
+}
+
 fn validate_registry_key(source: &str, field: &str, key: &str) -> Result<(), DescriptorError> {
     let ok = !key.trim().is_empty()
         && !key.contains('/')
@@ -1400,13 +2472,7 @@ fn validate_in_file_roots(
             "expected at least one entry in `roots.files`, found none while the descriptor reads or writes files",
         ));
     }
-    let candidate = normalise_template_text(path.as_str());
-    let covered = roots.files.iter().any(|root| {
-        let root_text = normalise_template_text(root.as_str());
-        candidate == root_text
-            || candidate.starts_with(&format!("{}/", root_text.trim_end_matches('/')))
-    });
-    if covered {
+    if roots.files.iter().any(|root| is_under_root(path, root)) {
         Ok(())
     } else {
         Err(DescriptorError::new(
@@ -1426,6 +2492,24 @@ fn validate_in_file_roots(
     }
 }
 
+/// Whether `path` is `root` itself or sits below it, on the template text.
+pub(super) fn is_under_root(path: &PathTemplate, root: &PathTemplate) -> bool {
+    let candidate = normalise_template_text(path.as_str());
+    let root_text = normalise_template_text(root.as_str());
+    candidate == root_text
+        || candidate.starts_with(&format!("{}/", root_text.trim_end_matches('/')))
+}
+
+/// Whether registry `key` in `hive` is `root` itself or sits below it.
+pub(super) fn is_under_registry_root(hive: RegistryHive, key: &str, root: &RegistryRoot) -> bool {
+    root.root == hive
+        && (key.eq_ignore_ascii_case(&root.key)
+            || key.to_ascii_lowercase().starts_with(&format!(
+                "{}\\",
+                root.key.trim_end_matches('\\').to_ascii_lowercase()
+            )))
+}
+
 /// Same check for a spec: every candidate has to sit inside the roots, since
 /// any of them may be the one that ends up being read or written.
 fn validate_spec_in_file_roots(
@@ -1454,15 +2538,11 @@ fn validate_in_registry_roots(
     key: &str,
     roots: &Roots,
 ) -> Result<(), DescriptorError> {
-    let covered = roots.registry.iter().any(|root| {
-        root.root == hive
-            && (key.eq_ignore_ascii_case(&root.key)
-                || key.to_ascii_lowercase().starts_with(&format!(
-                    "{}\\",
-                    root.key.trim_end_matches('\\').to_ascii_lowercase()
-                )))
-    });
-    if covered {
+    if roots
+        .registry
+        .iter()
+        .any(|root| is_under_registry_root(hive, key, root))
+    {
         Ok(())
     } else {
         Err(DescriptorError::new(
@@ -1560,6 +2640,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn a_schema_reference_is_the_one_unknown_field_accepted() {
+        // Editors find the published schema through it, so a file written
+        // with completion on has to load as it is.
+        let descriptor = with_windows(|v| {
+            v["$schema"] = serde_json::json!("../docs/descriptor-schema.json");
+        })
+        .unwrap();
+        assert_eq!(descriptor.id, "demo");
+        assert!(serde_json::to_value(&descriptor).unwrap()["$schema"].is_null());
+    }
+
+    #[test]
+    fn a_launch_argument_may_only_read_the_account_it_launches() {
+        // An environment variable in an argument would be passed unexpanded:
+        // paths are the only place `${VAR}` means the machine's environment.
+        let err = with_windows(|v| {
+            v["os"]["windows"]["launch"]["args"] = serde_json::json!(["--lang=${LANG}"]);
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "os.windows.launch.args[0]");
+        assert!(err.problem.contains("${account.<name>}"), "{}", err.problem);
+
+        let err = with_windows(|v| {
+            v["os"]["windows"]["launch"]["env"] = serde_json::json!({ "1LANG": "fr" });
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "os.windows.launch.env");
+
+        with_windows(|v| {
+            v["os"]["windows"]["launch"] = serde_json::json!({
+                "args": ["--lang=${account.lang}"],
+                "env": { "DEMO_USER": "${account.id}" }
+            });
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn an_account_value_missing_from_a_launch_argument_is_named() {
+        let values = BTreeMap::from([("lang".to_string(), "fr".to_string())]);
+        assert_eq!(
+            expand_account_values("--lang=${account.lang}", &values).unwrap(),
+            "--lang=fr"
+        );
+        assert_eq!(
+            expand_account_values("--region=${account.region}", &values).unwrap_err(),
+            "region"
+        );
+    }
+
     #[test]
     fn state_path_outside_the_declared_roots_is_refused() {
         let err = with_windows(|v| {
@@ -1613,6 +2744,342 @@ mod tests {
         assert!(err.problem.contains("twice"), "{}", err.problem);
     }
 
+    #[test]
+    fn key_items_select_keys_in_the_syntax_of_their_file() {
+        let descriptor = with_windows(|v| {
+            v["os"]["windows"]["state"]["keys"] = serde_json::json!([
+                {
+                    "live": "${LOCALAPPDATA}/Demo/settings.json",
+                    "snapshot": "settings-keys",
+                    "select": { "kind": "jsonPointer", "pointers": ["/auth/refresh~1token"] }
+                },
+                {
+                    "live": "${LOCALAPPDATA}/Demo/user.ini",
+                    "snapshot": "ini-keys",
+                    "select": { "kind": "iniKey", "keys": [{ "section": "Auth", "key": "Token" }] }
+                },
+                {
+                    "live": "${LOCALAPPDATA}/Demo/local.vdf",
+                    "snapshot": "vdf-keys",
+                    "select": { "kind": "keyValues", "paths": ["Store/friends/PersonaName"] }
+                }
+            ]);
+        })
+        .unwrap();
+        assert_eq!(descriptor.os[&Os::Windows].state.keys.len(), 3);
+    }
+
+    #[test]
+    fn a_key_selection_that_cannot_name_a_key_is_refused() {
+        for (select, field) in [
+            (
+                serde_json::json!({ "kind": "jsonPointer", "pointers": [] }),
+                "os.windows.state.keys[0].select",
+            ),
+            (
+                serde_json::json!({ "kind": "jsonPointer", "pointers": ["auth/token"] }),
+                "os.windows.state.keys[0].select.pointers[0]",
+            ),
+            (
+                serde_json::json!({ "kind": "jsonPointer", "pointers": ["/a~2"] }),
+                "os.windows.state.keys[0].select.pointers[0]",
+            ),
+            (
+                serde_json::json!({ "kind": "iniKey", "keys": [{ "key": "a=b" }] }),
+                "os.windows.state.keys[0].select.keys[0].key",
+            ),
+            (
+                serde_json::json!({ "kind": "keyValues", "paths": ["PersonaName"] }),
+                "os.windows.state.keys[0].select.paths[0]",
+            ),
+        ] {
+            let err = with_windows(|v| {
+                v["os"]["windows"]["state"]["keys"] = serde_json::json!([{
+                    "live": "${LOCALAPPDATA}/Demo/settings.json",
+                    "snapshot": "settings-keys",
+                    "select": select.clone()
+                }]);
+            })
+            .unwrap_err();
+            assert_eq!(err.field, field, "{err}");
+        }
+    }
+
+    #[test]
+    fn a_key_item_stays_inside_the_roots_and_shares_the_snapshot_names() {
+        let err = with_windows(|v| {
+            v["os"]["windows"]["state"]["keys"] = serde_json::json!([{
+                "live": "${APPDATA}/Elsewhere/settings.json",
+                "snapshot": "settings-keys",
+                "select": { "kind": "jsonPointer", "pointers": ["/token"] }
+            }]);
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "os.windows.state.keys[0].live");
+
+        let err = with_windows(|v| {
+            v["os"]["windows"]["state"]["keys"] = serde_json::json!([{
+                "live": "${LOCALAPPDATA}/Demo/settings.json",
+                "snapshot": "session.json",
+                "select": { "kind": "jsonPointer", "pointers": ["/token"] }
+            }]);
+        })
+        .unwrap_err();
+        assert!(err.problem.contains("twice"), "{}", err.problem);
+    }
+
+    #[test]
+    fn account_fields_are_checked_against_the_source_that_fills_them() {
+        let json_source = serde_json::json!({
+            "kind": "jsonPointer",
+            "path": "${LOCALAPPDATA}/Demo/settings.json",
+            "pointer": "/account/id"
+        });
+        let with_fields = |source: &serde_json::Value, fields: serde_json::Value, format: &str| {
+            with_windows(|v| {
+                let identity = &mut v["os"]["windows"]["identity"];
+                identity["source"] = source.clone();
+                identity["fields"] = fields.clone();
+                identity["labelFormat"] = serde_json::json!(format);
+            })
+        };
+        let tag = serde_json::json!([
+            { "name": "tagLine", "label": "Tag line", "from": "/account/tag" }
+        ]);
+
+        let descriptor = with_fields(
+            &json_source,
+            tag.clone(),
+            "${account.id}#${account.tagLine}",
+        )
+        .unwrap();
+        let identity = &descriptor.os[&Os::Windows].identity;
+        assert_eq!(identity.fields[0].kind, FieldKind::Text);
+
+        let refused = [
+            (
+                with_fields(
+                    &json_source,
+                    serde_json::json!([{ "name": "label", "label": "Label", "from": "/a" }]),
+                    "",
+                ),
+                "os.windows.identity.fields[0].name",
+            ),
+            (
+                with_fields(
+                    &json_source,
+                    serde_json::json!([{ "name": "tagLine", "label": "Tag", "from": "tag" }]),
+                    "",
+                ),
+                "os.windows.identity.fields[0].from",
+            ),
+            (
+                with_fields(&json_source, tag.clone(), "${account.region}"),
+                "os.windows.identity.labelFormat",
+            ),
+            (
+                // The registry source reads one value, the id, and nothing else.
+                with_windows(|v| v["os"]["windows"]["identity"]["fields"] = tag.clone()),
+                "os.windows.identity.fields",
+            ),
+        ];
+        for (result, field) in refused {
+            assert_eq!(result.unwrap_err().field, field);
+        }
+    }
+
+    #[test]
+    fn a_field_value_is_kept_only_in_the_shape_its_type_names() {
+        assert_eq!(FieldKind::Text.accept("  EUW ").as_deref(), Some("EUW"));
+        assert_eq!(FieldKind::Text.accept("two\nlines"), None);
+        assert_eq!(FieldKind::Text.accept("   "), None);
+        assert_eq!(FieldKind::Number.accept("42").as_deref(), Some("42"));
+        assert_eq!(FieldKind::Number.accept("lots"), None);
+        assert_eq!(
+            FieldKind::Email.accept("hugo@example.com").as_deref(),
+            Some("hugo@example.com")
+        );
+        assert_eq!(FieldKind::Email.accept("hugo@localhost"), None);
+        assert_eq!(FieldKind::Email.accept("hu go@example.com"), None);
+        assert!(!FieldKind::Path.is_listed());
+    }
+
+    #[test]
+    fn structured_identity_sources_are_checked_like_the_others() {
+        let accepted = [
+            serde_json::json!({
+                "kind": "jsonPointer",
+                "path": "${LOCALAPPDATA}/Demo/settings.json",
+                "pointer": "/account/id",
+                "displayNamePointer": "/account/name"
+            }),
+            serde_json::json!({
+                "kind": "iniKey",
+                "path": "${LOCALAPPDATA}/Demo/user.ini",
+                "section": "Account",
+                "key": "Id",
+                "displayNameKey": "Name"
+            }),
+            serde_json::json!({
+                "kind": "sqliteQuery",
+                "path": "${LOCALAPPDATA}/Demo/cache.db",
+                "query": "SELECT id, name FROM users ORDER BY last_login DESC LIMIT 1;"
+            }),
+        ];
+        for source in accepted {
+            with_windows(|v| v["os"]["windows"]["identity"]["source"] = source.clone())
+                .unwrap_or_else(|e| panic!("{source}: {e}"));
+        }
+
+        let refused = [
+            (
+                serde_json::json!({
+                    "kind": "jsonPointer",
+                    "path": "${APPDATA}/Elsewhere/settings.json",
+                    "pointer": "/id"
+                }),
+                "os.windows.identity.source.path",
+            ),
+            (
+                serde_json::json!({
+                    "kind": "jsonPointer",
+                    "path": "${LOCALAPPDATA}/Demo/settings.json",
+                    "pointer": "/id",
+                    "displayNamePointer": "name"
+                }),
+                "os.windows.identity.source.displayNamePointer",
+            ),
+            (
+                serde_json::json!({
+                    "kind": "iniKey",
+                    "path": "${LOCALAPPDATA}/Demo/user.ini",
+                    "key": ""
+                }),
+                "os.windows.identity.source.key",
+            ),
+            (
+                serde_json::json!({
+                    "kind": "sqliteQuery",
+                    "path": "${LOCALAPPDATA}/Demo/cache.db",
+                    "query": "DELETE FROM users"
+                }),
+                "os.windows.identity.source.query",
+            ),
+            (
+                serde_json::json!({
+                    "kind": "sqliteQuery",
+                    "path": "${LOCALAPPDATA}/Demo/cache.db",
+                    "query": "SELECT id FROM users; DROP TABLE users"
+                }),
+                "os.windows.identity.source.query",
+            ),
+        ];
+        for (source, field) in refused {
+            let err = with_windows(|v| v["os"]["windows"]["identity"]["source"] = source.clone())
+                .unwrap_err();
+            assert_eq!(err.field, field, "{err}");
+        }
+    }
+
+    /// A portable Jagex install: the shipped descriptor with one binary
+    /// location of its own.
+    const PORTABLE_JAGEX: &str = r#"{
+      "id": "jagex-portable",
+      "extends": "jagex",
+      "name": "Jagex Launcher (portable)",
+      "os": {
+        "windows": {
+          "executable": {
+            "candidates": [{ "kind": "path", "template": "D:/Games/Jagex/JagexLauncher.exe" }]
+          },
+          "launch": null
+        }
+      }
+    }"#;
+
+    #[test]
+    fn an_extending_descriptor_replaces_only_the_fields_it_writes() {
+        let descriptor = Descriptor::parse("portable.json", PORTABLE_JAGEX).unwrap();
+        let profile = &descriptor.os[&Os::Windows];
+
+        assert_eq!(descriptor.id, "jagex-portable");
+        assert_eq!(descriptor.extends, "jagex");
+        assert_eq!(descriptor.name, "Jagex Launcher (portable)");
+        assert_eq!(descriptor.short_name, "Jagex");
+        let executable = profile.executable.as_ref().unwrap();
+        assert_eq!(executable.file_name, "JagexLauncher.exe");
+        assert_eq!(
+            executable.candidates.len(),
+            1,
+            "a list replaces, never appends"
+        );
+        assert!(profile.launch.is_none(), "null removes the parent's value");
+        assert_eq!(profile.state.files.len(), 3);
+
+        for field in [
+            "shortName",
+            "os.windows.roots",
+            "os.windows.executable.fileName",
+        ] {
+            assert!(descriptor.inherited.iter().any(|f| f == field), "{field}");
+        }
+        for field in [
+            "id",
+            "name",
+            "os.windows.executable.candidates",
+            "os.windows.launch",
+        ] {
+            assert!(!descriptor.inherited.iter().any(|f| f == field), "{field}");
+        }
+    }
+
+    #[test]
+    fn an_extending_descriptor_names_a_shipped_parent_and_an_id_of_its_own() {
+        let err = Descriptor::parse(
+            "x",
+            &PORTABLE_JAGEX.replace(r#""extends": "jagex""#, r#""extends": "steam""#),
+        )
+        .unwrap_err();
+        assert_eq!(err.field, "extends");
+        assert!(
+            err.problem.contains("jagex"),
+            "lists the valid parents: {err}"
+        );
+
+        let err =
+            Descriptor::parse("x", &PORTABLE_JAGEX.replace("jagex-portable", "jagex")).unwrap_err();
+        assert_eq!(err.field, "id");
+
+        let err = Descriptor::parse(
+            "x",
+            &PORTABLE_JAGEX.replace("\"launch\": null", "\"lanuch\": {}"),
+        )
+        .unwrap_err();
+        assert!(err.problem.contains("lanuch"), "{err}");
+    }
+
+    #[test]
+    fn the_merged_descriptor_is_validated_as_a_whole() {
+        // The parent's state paths must still fall inside the roots the child
+        // narrows them to.
+        let body = PORTABLE_JAGEX.replace(
+            r#""launch": null"#,
+            r#""roots": { "files": ["D:/Games/Jagex"] }"#,
+        );
+        let err = Descriptor::parse("x", &body).unwrap_err();
+        assert!(err.field.starts_with("os.windows.state."), "{err}");
+    }
+
+    #[test]
+    fn a_descriptor_extending_an_allowlisted_platform_keeps_its_hook() {
+        let body = r#"{ "id": "discord-ptb", "extends": "discord", "name": "Discord PTB" }"#;
+        let descriptor = Descriptor::parse("ptb.json", body).unwrap();
+        assert!(descriptor
+            .os
+            .values()
+            .any(|p| matches!(p.identity.source, IdentitySource::NativeHook { .. })));
+    }
+
     #[test]
     fn detect_with_no_condition_is_refused() {
         let err = with_windows(|v| {
@@ -1725,6 +3192,115 @@ mod tests {
         assert_eq!(err.field, "os.windows.close.processes");
     }
 
+    #[test]
+    fn close_strategies_are_read_in_order_with_their_timeouts() {
+        let descriptor = with_windows(|v| {
+            v["os"]["windows"]["close"]["strategies"] = serde_json::json!([
+                { "kind": "command", "command": "${installDir}/Demo.exe", "args": ["--quit"] },
+                { "kind": "uri", "uri": "demo://exit", "timeoutMs": 2000 },
+                { "kind": "httpPost", "url": "https://127.0.0.1:5120/quit" },
+                { "kind": "closeWindow" },
+            ]);
+        })
+        .unwrap();
+        let strategies = &descriptor.os[&Os::Windows].close.strategies;
+        let kinds: Vec<&str> = strategies.iter().map(CloseStrategy::as_str).collect();
+        assert_eq!(kinds, ["command", "uri", "httpPost", "closeWindow"]);
+        assert_eq!(strategies[0].timeout_ms(), 5000);
+        assert_eq!(strategies[1].timeout_ms(), 2000);
+    }
+
+    #[test]
+    fn a_close_request_cannot_leave_the_machine_or_open_a_file() {
+        for (strategy, field) in [
+            (
+                serde_json::json!({ "kind": "httpPost", "url": "https://example.com/quit" }),
+                "os.windows.close.strategies[0].url",
+            ),
+            (
+                serde_json::json!({ "kind": "httpPost", "url": "http://localhost.example.com/" }),
+                "os.windows.close.strategies[0].url",
+            ),
+            (
+                serde_json::json!({ "kind": "uri", "uri": "file:///C:/Windows/notepad.exe" }),
+                "os.windows.close.strategies[0].uri",
+            ),
+            (
+                serde_json::json!({ "kind": "uri", "uri": "https://example.com" }),
+                "os.windows.close.strategies[0].uri",
+            ),
+            (
+                serde_json::json!({ "kind": "closeWindow", "timeoutMs": 0 }),
+                "os.windows.close.strategies[0].timeoutMs",
+            ),
+        ] {
+            let err = with_windows(|v| {
+                v["os"]["windows"]["close"]["strategies"] = serde_json::json!([strategy]);
+            })
+            .unwrap_err();
+            assert_eq!(err.field, field, "{strategy}");
+        }
+        assert!(is_loopback_url("http://[::1]:8080/quit"));
+        assert!(is_loopback_url("http://localhost"));
+    }
+
+    #[test]
+    fn close_strategies_need_processes_to_wait_on() {
+        let err = with_windows(|v| {
+            v["os"]["windows"]["close"] = serde_json::json!({
+                "processes": [],
+                "strategies": [{ "kind": "closeWindow" }],
+            });
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "os.windows.close.processes");
+        assert!(err.problem.contains("strategies"), "{}", err.problem);
+    }
+
+    #[test]
+    fn a_native_hook_close_needs_a_hook_to_ask() {
+        let err = with_windows(|v| {
+            v["os"]["windows"]["close"]["strategies"] =
+                serde_json::json!([{ "kind": "nativeHook" }]);
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "os.windows.close.strategies[0]");
+
+        let descriptor = with_windows(|v| {
+            v["id"] = serde_json::json!("discord");
+            v["os"]["windows"]["identity"]["source"] = serde_json::json!({
+                "kind": "nativeHook",
+                "name": "discord-leveldb",
+                "paths": { "leveldb": "${LOCALAPPDATA}/Demo/leveldb" },
+            });
+            v["os"]["windows"]["close"]["strategies"] =
+                serde_json::json!([{ "kind": "nativeHook", "timeoutMs": 8000 }]);
+        })
+        .unwrap();
+        let strategy = &descriptor.os[&Os::Windows].close.strategies[0];
+        assert_eq!(strategy.as_str(), "nativeHook");
+        assert_eq!(strategy.timeout_ms(), 8000);
+    }
+
+    #[test]
+    fn processes_that_refuse_a_switch_are_bare_names() {
+        let descriptor = with_windows(|v| {
+            v["os"]["windows"]["close"]["refuseWhileRunning"] = serde_json::json!(["Game.exe"]);
+        })
+        .unwrap();
+        assert_eq!(
+            descriptor.os[&Os::Windows].close.refuse_while_running,
+            ["Game.exe"]
+        );
+
+        let err = with_windows(|v| {
+            v["os"]["windows"]["close"]["refuseWhileRunning"] =
+                serde_json::json!(["C:/Games/Game.exe"]);
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "os.windows.close.refuseWhileRunning[0]");
+    }
+
     #[test]
     fn unbalanced_placeholder_is_refused() {
         let err = with_windows(|v| {
@@ -1849,6 +3425,38 @@ mod tests {
         assert!(err.problem.contains("discovery"), "{}", err.problem);
     }
 
+    #[test]
+    fn a_json_list_discovery_needs_an_id_field_and_a_pointer_that_is_one() {
+        let discovery = |entry: serde_json::Value| {
+            move |v: &mut serde_json::Value| {
+                v["os"]["windows"]["identity"]["discovery"] = serde_json::json!([entry.clone()]);
+            }
+        };
+        let entry = |pointer: &str, id_field: &str, name_field: &str| {
+            serde_json::json!({
+                "kind": "jsonArray",
+                "path": "${LOCALAPPDATA}/Demo/recent.json",
+                "pointer": pointer,
+                "idField": id_field,
+                "displayNameField": name_field
+            })
+        };
+
+        assert!(with_windows(discovery(entry("/recent", "userId", "name"))).is_ok());
+        // The whole file may be the array.
+        assert!(with_windows(discovery(entry("", "userId", ""))).is_ok());
+
+        let err = with_windows(discovery(entry("recent", "userId", ""))).unwrap_err();
+        assert_eq!(err.field, "os.windows.identity.discovery[0].pointer");
+        let err = with_windows(discovery(entry("/recent", " ", ""))).unwrap_err();
+        assert_eq!(err.field, "os.windows.identity.discovery[0].idField");
+        let err = with_windows(discovery(entry("/recent", "userId", "userId"))).unwrap_err();
+        assert_eq!(
+            err.field,
+            "os.windows.identity.discovery[0].displayNameField"
+        );
+    }
+
     #[test]
     fn a_log_source_with_no_way_to_find_the_id_is_refused() {
         // Without a prefix or a nearby word, any identifier on the line would
@@ -1888,6 +3496,34 @@ mod tests {
         assert_eq!(err.field, "os.windows.executable.candidates[0].displayName");
     }
 
+    #[test]
+    fn a_json_install_record_needs_a_pointer_and_cannot_point_at_itself() {
+        for (candidate, field) in [
+            (
+                serde_json::json!({
+                    "kind": "jsonPointer",
+                    "path": "${PROGRAMDATA}/Demo/installs.json",
+                    "pointer": "live",
+                }),
+                "os.windows.executable.candidates[0].pointer",
+            ),
+            (
+                serde_json::json!({
+                    "kind": "jsonPointer",
+                    "path": "${installDir}/installs.json",
+                    "pointer": "/live",
+                }),
+                "os.windows.executable.candidates[0].path",
+            ),
+        ] {
+            let err = with_windows(|v| {
+                v["os"]["windows"]["executable"]["candidates"] = serde_json::json!([candidate]);
+            })
+            .unwrap_err();
+            assert_eq!(err.field, field, "{candidate}");
+        }
+    }
+
     #[test]
     fn charset_guards_the_account_id() {
         assert!(Charset::Digits.accepts("12345"));
@@ -1895,6 +3531,9 @@ mod tests {
         assert!(Charset::Alphanumeric.accepts("a3f0c2d1"));
         assert!(!Charset::Alphanumeric.accepts("a3f0-c2d1"));
         assert!(!Charset::Alphanumeric.accepts("../evil"));
+        assert!(Charset::Slug.accepts("riot-profile-a9da419c_2"));
+        assert!(!Charset::Slug.accepts("../evil"));
+        assert!(!Charset::Slug.accepts("a b"));
     }
 
     #[test]
@@ -1908,6 +3547,55 @@ mod tests {
         assert!(!Charset::Uuid.accepts(""));
     }
 
+    /// The demo profile moved to Linux, inside a Wine prefix.
+    fn with_linux(mutate: impl Fn(&mut serde_json::Value)) -> Result<Descriptor, DescriptorError> {
+        with_windows(|v| {
+            let profile = v["os"]["windows"].take();
+            v["os"] = serde_json::json!({ "linux": profile });
+            v["os"]["linux"]["wine"] = serde_json::json!({
+                "prefix": ["${WINEPREFIX}", "${HOME}/Games/demo"],
+                "runner": { "command": "umu-run", "env": { "GAMEID": "0" } }
+            });
+            mutate(v);
+        })
+    }
+
+    #[test]
+    fn a_windows_profile_runs_on_linux_through_a_wine_prefix() {
+        let descriptor = with_linux(|_| {}).unwrap();
+        let wine = descriptor.os[&Os::Linux].wine.as_ref().unwrap();
+        assert_eq!(wine.prefix.candidates().len(), 2);
+        assert_eq!(wine.runner.command.as_str(), "umu-run");
+    }
+
+    #[test]
+    fn a_linux_profile_reading_the_registry_needs_a_prefix_to_hold_it() {
+        let err = with_linux(|v| {
+            v["os"]["linux"].as_object_mut().unwrap().remove("wine");
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "os.linux.identity.source");
+        assert!(err.problem.contains("`wine` prefix"), "{}", err.problem);
+    }
+
+    #[test]
+    fn a_windows_profile_cannot_declare_a_prefix() {
+        let err = with_windows(|v| {
+            v["os"]["windows"]["wine"] = serde_json::json!({ "prefix": "${WINEPREFIX}" });
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "os.windows.wine");
+    }
+
+    #[test]
+    fn a_runner_variable_must_be_a_variable_name() {
+        let err = with_linux(|v| {
+            v["os"]["linux"]["wine"]["runner"]["env"] = serde_json::json!({ "BAD NAME": "x" });
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "os.linux.wine.runner.env");
+    }
+
     #[test]
     fn os_profiles_may_be_partial_without_breaking_the_descriptor() {
         // A platform present on Windows only is not a broken descriptor.
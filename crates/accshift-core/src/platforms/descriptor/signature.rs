@@ -0,0 +1,243 @@
+//! Who wrote a user descriptor, when its author says so.
+//!
+//! A descriptor may travel with a detached signature: `<file>.sig` next to
+//! it, a small JSON object holding the Ed25519 public key that signed and the
+//! signature over the descriptor's exact bytes, both base64. The key travels
+//! with the signature so a file signed by someone the user never heard of can
+//! still be told apart from a file that was edited after signing: the first
+//! is labelled with the key, the second is refused.
+//!
+//! Signing is optional. An unsigned file loads as before and is labelled as
+//! such; a signature only ever adds a name, it never unlocks anything the
+//! engine would otherwise refuse.
+
+use crate::config::TrustedPublisher;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// What a signature file holds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureFile {
+    /// The raw 32-byte Ed25519 public key, base64.
+    pub public_key: String,
+    /// The 64-byte signature over the descriptor file, base64.
+    pub signature: String,
+}
+
+/// Who a descriptor file says wrote it, once the signature has been checked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum Signer {
+    /// No signature next to the file.
+    Unsigned,
+    /// Signed by a key the config lists, under the name it gives it.
+    Trusted { publisher: String },
+    /// Signed, and intact, by a key the config does not list. The key is
+    /// shown so the user can decide to add it.
+    #[serde(rename_all = "camelCase")]
+    Unknown { public_key: String },
+}
+
+impl Signer {
+    /// One line for a list: the publisher, the key, or that there is none.
+    pub fn label(&self) -> String {
+        match self {
+            Signer::Unsigned => "unsigned".to_string(),
+            Signer::Trusted { publisher } => format!("signed by {publisher}"),
+            Signer::Unknown { public_key } => format!("signed by unknown key {public_key}"),
+        }
+    }
+}
+
+/// Where the signature of `descriptor` is looked for: the same name with
+/// `.sig` appended, so `acme.json` is signed by `acme.json.sig`.
+pub fn signature_path(descriptor: &Path) -> PathBuf {
+    let mut name = descriptor.as_os_str().to_owned();
+    name.push(".sig");
+    PathBuf::from(name)
+}
+
+/// Checks the signature next to `path` against `body`, the bytes that were
+/// read from it.
+///
+/// An error means the file cannot be vouched for as it stands: the signature
+/// is unreadable, or it does not match, which is what an edit after signing
+/// looks like. Both refuse the file rather than quietly demoting it to
+/// unsigned, because a signature that stopped matching is the one case the
+/// user asked to be told about.
+pub fn verify(path: &Path, body: &[u8], trusted: &[TrustedPublisher]) -> Result<Signer, String> {
+    let sig_path = signature_path(path);
+    let raw = match std::fs::read_to_string(&sig_path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Signer::Unsigned),
+        Err(e) => return Err(format!("its signature could not be read: {e}")),
+    };
+    let file: SignatureFile =
+        serde_json::from_str(&raw).map_err(|e| format!("its signature file is not valid: {e}"))?;
+    check(body, &file, trusted)
+}
+
+/// [`verify`], for a signature already in hand.
+fn check(
+    body: &[u8],
+    file: &SignatureFile,
+    trusted: &[TrustedPublisher],
+) -> Result<Signer, String> {
+    let key = decode_key(&file.public_key)
+        .ok_or_else(|| "its signature names a key that is not an Ed25519 public key".to_string())?;
+    let signature = STANDARD
+        .decode(file.signature.trim())
+        .map_err(|_| "its signature is not base64".to_string())?;
+
+    ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, &key)
+        .verify(body, &signature)
+        .map_err(|_| {
+            "its signature does not match; the file changed after it was signed".to_string()
+        })?;
+
+    let publisher = trusted
+        .iter()
+        .find(|publisher| decode_key(&publisher.public_key).is_some_and(|known| known == key));
+    Ok(match publisher {
+        Some(publisher) => Signer::Trusted {
+            publisher: publisher.name.clone(),
+        },
+        None => Signer::Unknown {
+            public_key: STANDARD.encode(key),
+        },
+    })
+}
+
+/// A base64 Ed25519 public key, or `None` for anything that is not 32 bytes.
+fn decode_key(encoded: &str) -> Option<[u8; 32]> {
+    STANDARD.decode(encoded.trim()).ok()?.try_into().ok()
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    /// A fixed key, so a failing test names the same key every run.
+    pub fn key_pair(seed: u8) -> Ed25519KeyPair {
+        Ed25519KeyPair::from_seed_unchecked(&[seed; 32]).unwrap()
+    }
+
+    pub fn public_key(pair: &Ed25519KeyPair) -> String {
+        STANDARD.encode(pair.public_key().as_ref())
+    }
+
+    /// Signs `body` and writes the signature next to `path`.
+    pub fn sign_next_to(path: &Path, body: &str, pair: &Ed25519KeyPair) {
+        let file = SignatureFile {
+            public_key: public_key(pair),
+            signature: STANDARD.encode(pair.sign(body.as_bytes()).as_ref()),
+        };
+        std::fs::write(signature_path(path), serde_json::to_string(&file).unwrap()).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::*;
+    use super::*;
+
+    fn scratch(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "accshift-descriptor-signature-{}-{}",
+            tag,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn team(pair: &ring::signature::Ed25519KeyPair) -> Vec<TrustedPublisher> {
+        vec![TrustedPublisher {
+            name: "Platform team".into(),
+            public_key: public_key(pair),
+        }]
+    }
+
+    #[test]
+    fn the_signature_sits_next_to_the_file_it_signs() {
+        assert_eq!(
+            signature_path(Path::new("/d/acme.json")),
+            PathBuf::from("/d/acme.json.sig")
+        );
+    }
+
+    #[test]
+    fn a_file_without_a_signature_is_unsigned_not_refused() {
+        let dir = scratch("unsigned");
+        let path = dir.join("acme.json");
+
+        assert_eq!(verify(&path, b"{}", &[]), Ok(Signer::Unsigned));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn a_listed_key_names_its_publisher() {
+        let dir = scratch("trusted");
+        let path = dir.join("acme.json");
+        let pair = key_pair(1);
+        sign_next_to(&path, "{}", &pair);
+
+        assert_eq!(
+            verify(&path, b"{}", &team(&pair)),
+            Ok(Signer::Trusted {
+                publisher: "Platform team".into()
+            })
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn an_unlisted_key_is_shown_so_it_can_be_added() {
+        let dir = scratch("unknown");
+        let path = dir.join("acme.json");
+        let stranger = key_pair(2);
+        sign_next_to(&path, "{}", &stranger);
+
+        assert_eq!(
+            verify(&path, b"{}", &team(&key_pair(1))),
+            Ok(Signer::Unknown {
+                public_key: public_key(&stranger)
+            })
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn an_edit_after_signing_is_refused_even_from_a_trusted_key() {
+        let dir = scratch("edited");
+        let path = dir.join("acme.json");
+        let pair = key_pair(1);
+        sign_next_to(&path, "{}", &pair);
+
+        let error = verify(&path, b"{ }", &team(&pair)).unwrap_err();
+        assert!(error.contains("changed after it was signed"), "{error}");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn a_signature_file_that_is_not_one_is_refused_by_what_is_wrong() {
+        let dir = scratch("garbled");
+        let path = dir.join("acme.json");
+        std::fs::write(signature_path(&path), "not json").unwrap();
+        assert!(verify(&path, b"{}", &[]).unwrap_err().contains("not valid"));
+
+        let short_key = SignatureFile {
+            public_key: STANDARD.encode([0u8; 16]),
+            signature: STANDARD.encode([0u8; 64]),
+        };
+        assert!(check(b"{}", &short_key, &[])
+            .unwrap_err()
+            .contains("not an Ed25519 public key"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,713 @@
+//! Wine prefixes: a Windows launcher running on Linux or macOS through Wine,
+//! Proton, or a manager built on them (Lutris, Heroic, Bottles).
+//!
+//! Inside a prefix the launcher sees an ordinary Windows machine: `C:` is the
+//! `drive_c` folder, its profile is under `drive_c/users/<name>`, and its
+//! registry is two text files next to them. A descriptor written for Windows
+//! therefore describes a prefixed launcher almost unchanged, provided the
+//! engine knows where that machine lives on the host. That is all this module
+//! is: the layout of a prefix, and a reader and writer for its hives.
+//!
+//! The hives are edited line by line rather than parsed and rewritten. Wine
+//! keeps a lot in them that means nothing to us, some of it in formats it
+//! alone understands, and a rewrite that normalised any of it could break the
+//! prefix for every program in it, not just the launcher.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::schema::RegistryHive;
+
+/// The uninstall keys inside `system.reg`, in the same order Windows scans
+/// them, the redirected 32-bit view second.
+const UNINSTALL_ROOTS: &[&str] = &[
+    "Software\\Microsoft\\Windows\\CurrentVersion\\Uninstall",
+    "Software\\Wow6432Node\\Microsoft\\Windows\\CurrentVersion\\Uninstall",
+];
+
+/// The Windows user Proton creates in every prefix, whatever the login name.
+const PROTON_USER: &str = "steamuser";
+
+/// One prefix on the host, and the Windows user the launcher runs as in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prefix {
+    root: PathBuf,
+    user: String,
+}
+
+impl Prefix {
+    pub fn new(root: impl Into<PathBuf>, user: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            user: user.into(),
+        }
+    }
+
+    /// Picks the Windows user a prefix actually has.
+    ///
+    /// Wine names it after the login, Proton always calls it `steamuser`.
+    /// The login name wins when both folders exist, and is assumed when
+    /// neither does: a prefix Wine has not initialised yet will create it.
+    pub fn detect(root: impl Into<PathBuf>, login: Option<&str>) -> Self {
+        let root = root.into();
+        let users = root.join("drive_c").join("users");
+        let login = login.map(str::trim).filter(|name| !name.is_empty());
+        let user = match login {
+            Some(name) if users.join(name).is_dir() => name.to_string(),
+            _ if users.join(PROTON_USER).is_dir() => PROTON_USER.to_string(),
+            Some(name) => name.to_string(),
+            None => PROTON_USER.to_string(),
+        };
+        Self { root, user }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    pub fn drive_c(&self) -> PathBuf {
+        self.root.join("drive_c")
+    }
+
+    /// The variables a Windows descriptor is written against, pointed into
+    /// the prefix. They shadow the host's own, which on Linux mostly do not
+    /// exist anyway; `WINEPREFIX` is the prefix itself.
+    pub fn environment(&self) -> Vec<(String, String)> {
+        let drive_c = self.drive_c();
+        let profile = drive_c.join("users").join(&self.user);
+        let text = |path: PathBuf| path.to_string_lossy().into_owned();
+        vec![
+            ("WINEPREFIX".into(), text(self.root.clone())),
+            ("SystemDrive".into(), text(drive_c.clone())),
+            ("SystemRoot".into(), text(drive_c.join("windows"))),
+            ("windir".into(), text(drive_c.join("windows"))),
+            ("USERPROFILE".into(), text(profile.clone())),
+            (
+                "APPDATA".into(),
+                text(profile.join("AppData").join("Roaming")),
+            ),
+            (
+                "LOCALAPPDATA".into(),
+                text(profile.join("AppData").join("Local")),
+            ),
+            ("PUBLIC".into(), text(drive_c.join("users").join("Public"))),
+            ("ProgramData".into(), text(drive_c.join("ProgramData"))),
+            ("ProgramFiles".into(), text(drive_c.join("Program Files"))),
+            (
+                "ProgramFiles(x86)".into(),
+                text(drive_c.join("Program Files (x86)")),
+            ),
+            (
+                "CommonProgramFiles".into(),
+                text(drive_c.join("Program Files").join("Common Files")),
+            ),
+        ]
+    }
+
+    /// Where a Windows path the launcher wrote (`C:\Program Files\...`) lives
+    /// on the host. `None` for anything that is not drive-absolute.
+    ///
+    /// `C:` is `drive_c`; any other letter goes through `dosdevices`, which is
+    /// where Wine keeps the links it maps drives with.
+    pub fn host_path(&self, windows_path: &str) -> Option<PathBuf> {
+        let bytes = windows_path.as_bytes();
+        let drive_absolute = bytes.len() >= 2
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && (bytes.len() == 2 || matches!(bytes[2], b'\\' | b'/'));
+        if !drive_absolute {
+            return None;
+        }
+        let letter = (bytes[0] as char).to_ascii_lowercase();
+        let mut host = if letter == 'c' {
+            self.drive_c()
+        } else {
+            self.root.join("dosdevices").join(format!("{letter}:"))
+        };
+        for part in windows_path[2..].split(['\\', '/']) {
+            if !part.is_empty() {
+                host.push(part);
+            }
+        }
+        Some(host)
+    }
+
+    /// The text file holding one hive: `user.reg` for the current user,
+    /// `system.reg` for the machine.
+    pub fn hive_file(&self, hive: RegistryHive) -> PathBuf {
+        self.root.join(match hive {
+            RegistryHive::CurrentUser => "user.reg",
+            RegistryHive::LocalMachine => "system.reg",
+        })
+    }
+
+    pub fn read(&self, hive: RegistryHive, key: &str, value: &str) -> Option<String> {
+        let text = fs::read_to_string(self.hive_file(hive)).ok()?;
+        let lines: Vec<&str> = text.lines().collect();
+        let section = find_section(&lines, key)?;
+        let entry = find_value(&lines, &section, value)?;
+        let data = parse_string_data(&entry.data(&lines))?;
+        let trimmed = data.trim();
+        (!trimmed.is_empty()).then(|| trimmed.to_string())
+    }
+
+    /// Sets a string value, creating its key when the hive has none.
+    ///
+    /// A hive that does not exist is an error rather than something to
+    /// create: it means Wine has never run in this prefix, and a file we
+    /// invented would be missing everything Wine puts in it on first start.
+    pub fn write(
+        &self,
+        hive: RegistryHive,
+        key: &str,
+        value: &str,
+        data: &str,
+    ) -> Result<(), String> {
+        let file = self.hive_file(hive);
+        let text = fs::read_to_string(&file).map_err(|e| {
+            format!(
+                "Could not write registry value {key}\\{value}: could not read {}: {e}",
+                file.display()
+            )
+        })?;
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let new_line = format!("{}={}", quote_name(value), quote_string(data));
+        let borrowed: Vec<&str> = lines.iter().map(String::as_str).collect();
+        match find_section(&borrowed, key) {
+            Some(section) => match find_value(&borrowed, &section, value) {
+                Some(entry) => {
+                    lines.splice(entry.start..entry.end, [new_line]);
+                }
+                None => {
+                    let at = section.values_end(&borrowed);
+                    lines.insert(at, new_line);
+                }
+            },
+            None => {
+                if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.push(format!("[{}] {}", escape_key(key), unix_now()));
+                lines.push(new_line);
+            }
+        }
+        write_hive(&file, &lines)
+    }
+
+    /// Removes a value. A value or key that is already gone is not an error.
+    pub fn delete(&self, hive: RegistryHive, key: &str, value: &str) {
+        let file = self.hive_file(hive);
+        let Ok(text) = fs::read_to_string(&file) else {
+            return;
+        };
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let borrowed: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let Some(entry) =
+            find_section(&borrowed, key).and_then(|section| find_value(&borrowed, &section, value))
+        else {
+            return;
+        };
+        lines.drain(entry.start..entry.end);
+        let _ = write_hive(&file, &lines);
+    }
+
+    /// The install path an uninstall entry inside the prefix records, found by
+    /// the name the launcher displays. Same lookup as on Windows, against
+    /// `system.reg`.
+    pub fn uninstall_entry(&self, display_name: &str, value: &str) -> Option<String> {
+        let text = fs::read_to_string(self.hive_file(RegistryHive::LocalMachine)).ok()?;
+        let lines: Vec<&str> = text.lines().collect();
+        for root in UNINSTALL_ROOTS {
+            let prefix = format!("{}\\", root.to_ascii_lowercase());
+            let mut index = 0;
+            while let Some(section) = next_section(&lines, index) {
+                index = section.header + 1;
+                let lowered = section.key.to_ascii_lowercase();
+                let Some(rest) = lowered.strip_prefix(&prefix) else {
+                    continue;
+                };
+                if rest.contains('\\') {
+                    continue;
+                }
+                let name = find_value(&lines, &section, "DisplayName")
+                    .and_then(|entry| parse_string_data(&entry.data(&lines)));
+                if name.as_deref().map(str::trim) != Some(display_name) {
+                    continue;
+                }
+                let location = find_value(&lines, &section, value)
+                    .and_then(|entry| parse_string_data(&entry.data(&lines)));
+                if let Some(location) = location.filter(|l| !l.trim().is_empty()) {
+                    return Some(location);
+                }
+            }
+        }
+        None
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Hive text
+// ---------------------------------------------------------------------------
+
+/// A `[key]` header and the lines up to the next one.
+struct Section {
+    key: String,
+    header: usize,
+    end: usize,
+}
+
+impl Section {
+    /// Where a new value goes: after the last non-blank line of the section,
+    /// so the blank line Wine leaves between keys stays where it was.
+    fn values_end(&self, lines: &[&str]) -> usize {
+        let mut at = self.end;
+        while at > self.header + 1 && lines[at - 1].trim().is_empty() {
+            at -= 1;
+        }
+        at
+    }
+}
+
+/// One value's lines: usually one, more when binary data wraps with a
+/// trailing backslash.
+struct Entry {
+    start: usize,
+    end: usize,
+    /// Byte offset of the data in the first line, just past the `=`.
+    data_at: usize,
+}
+
+impl Entry {
+    fn data(&self, lines: &[&str]) -> String {
+        let mut data = lines[self.start][self.data_at..].to_string();
+        for line in &lines[self.start + 1..self.end] {
+            data.push_str(line.trim_start());
+        }
+        data
+    }
+}
+
+fn next_section(lines: &[&str], from: usize) -> Option<Section> {
+    let header = (from..lines.len()).find(|&i| lines[i].starts_with('['))?;
+    let key = parse_key(lines[header])?;
+    let end = (header + 1..lines.len())
+        .find(|&i| lines[i].starts_with('['))
+        .unwrap_or(lines.len());
+    Some(Section { key, header, end })
+}
+
+fn find_section(lines: &[&str], key: &str) -> Option<Section> {
+    let wanted = key.trim_end_matches('\\');
+    let mut index = 0;
+    while let Some(section) = next_section(lines, index) {
+        if section.key.eq_ignore_ascii_case(wanted) {
+            return Some(section);
+        }
+        index = section.header + 1;
+    }
+    None
+}
+
+fn find_value(lines: &[&str], section: &Section, value: &str) -> Option<Entry> {
+    let mut index = section.header + 1;
+    while index < section.end {
+        let start = index;
+        // A value whose data wraps ends at the first line without a trailing
+        // backslash; the lines in between belong to it, not to the key.
+        while index + 1 < section.end && lines[index].ends_with('\\') {
+            index += 1;
+        }
+        index += 1;
+        let line = lines[start];
+        let Some((name, data_at)) = parse_value_name(line) else {
+            continue;
+        };
+        if name.eq_ignore_ascii_case(value) {
+            return Some(Entry {
+                start,
+                end: index,
+                data_at,
+            });
+        }
+    }
+    None
+}
+
+/// The key out of `[Software\\Acme\\Launcher] 1700000000`, unescaped.
+fn parse_key(line: &str) -> Option<String> {
+    let mut chars = line.strip_prefix('[')?.chars();
+    let mut raw = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                raw.push(c);
+                raw.push(chars.next()?);
+            }
+            ']' => return Some(unescape(&raw)),
+            _ => raw.push(c),
+        }
+    }
+    None
+}
+
+/// The name of a value line, and where its data starts. `@` is the key's
+/// default value, which reads as an empty name.
+fn parse_value_name(line: &str) -> Option<(String, usize)> {
+    if let Some(rest) = line.strip_prefix("@=") {
+        return Some((String::new(), line.len() - rest.len()));
+    }
+    let (name, after) = parse_quoted(line)?;
+    let offset = line.len() - after.len();
+    after.starts_with('=').then(|| (name, offset + 1))
+}
+
+/// A string value's data. Numbers and binary data are not strings, and are
+/// reported as absent exactly like the Windows registry read does.
+fn parse_string_data(data: &str) -> Option<String> {
+    let data = data.trim();
+    let quoted = data
+        .strip_prefix("str(2):")
+        .or_else(|| data.strip_prefix("str(1):"))
+        .unwrap_or(data);
+    parse_quoted(quoted).map(|(text, _)| text)
+}
+
+/// A quoted, escaped string at the start of `text`, and what follows it.
+fn parse_quoted(text: &str) -> Option<(String, &str)> {
+    let rest = text.strip_prefix('"')?;
+    let mut raw = String::new();
+    let mut chars = rest.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                raw.push(c);
+                raw.push(chars.next()?.1);
+            }
+            '"' => return Some((unescape(&raw), &rest[index + 1..])),
+            _ => raw.push(c),
+        }
+    }
+    None
+}
+
+/// Undoes Wine's escaping: the C escapes, and `\x` followed by up to four hex
+/// digits for anything outside printable ASCII.
+fn unescape(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut units: Vec<u16> = Vec::new();
+    let mut chars = raw.chars().peekable();
+    let flush = |units: &mut Vec<u16>, out: &mut String| {
+        if !units.is_empty() {
+            out.push_str(&String::from_utf16_lossy(units));
+            units.clear();
+        }
+    };
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            flush(&mut units, &mut out);
+            out.push(c);
+            continue;
+        }
+        let Some(escaped) = chars.next() else {
+            break;
+        };
+        if escaped == 'x' {
+            let mut code: u32 = 0;
+            let mut digits = 0;
+            while digits < 4 {
+                match chars.peek().and_then(|d| d.to_digit(16)) {
+                    Some(digit) => {
+                        code = code * 16 + digit;
+                        digits += 1;
+                        chars.next();
+                    }
+                    None => break,
+                }
+            }
+            // Kept as UTF-16 units until the run ends, so a surrogate pair
+            // written as two escapes decodes to one character.
+            units.push(code as u16);
+            continue;
+        }
+        flush(&mut units, &mut out);
+        out.push(match escaped {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            'a' => '\u{7}',
+            'b' => '\u{8}',
+            'e' => '\u{1b}',
+            'f' => '\u{c}',
+            'v' => '\u{b}',
+            other => other,
+        });
+    }
+    flush(&mut units, &mut out);
+    out
+}
+
+/// Escapes text the way Wine writes it, so the line reads back the same in
+/// Wine as in this module.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ' '..='~' => out.push(c),
+            _ => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    out.push_str(&format!("\\x{unit:04x}"));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn escape_key(key: &str) -> String {
+    escape(key.trim_end_matches('\\')).replace(']', "\\]")
+}
+
+fn quote_name(name: &str) -> String {
+    if name.is_empty() {
+        "@".to_string()
+    } else {
+        format!("\"{}\"", escape(name))
+    }
+}
+
+fn quote_string(data: &str) -> String {
+    format!("\"{}\"", escape(data))
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// Replaces the hive in one rename, so Wine starting mid-write never reads
+/// half a file.
+fn write_hive(file: &Path, lines: &[String]) -> Result<(), String> {
+    let mut text = lines.join("\n");
+    text.push('\n');
+    let mut staging_name = file.file_name().unwrap_or_default().to_os_string();
+    staging_name.push(".accshift-tmp");
+    let staging = file.with_file_name(staging_name);
+    fs::write(&staging, text).map_err(|e| format!("Could not write {}: {e}", staging.display()))?;
+    fs::rename(&staging, file).map_err(|e| {
+        let _ = fs::remove_file(&staging);
+        format!("Could not replace {}: {e}", file.display())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER_REG: &str = r#"WINE REGISTRY Version 2
+;; All keys relative to \\User\\S-1-5-21-0-0-0-1000
+
+#arch=win64
+
+[Software\\Acme\\Launcher] 1700000000
+#time=1da1b2c3d4e5f60
+"AccountId"="123456"
+"Display"="Ren\x00e9e \"R\" \\ Smith"
+"Blob"=hex:01,02,03,\
+  04,05
+"Count"=dword:00000007
+"After"="kept"
+
+[Software\\Wine] 1700000000
+"Version"="win10"
+"#;
+
+    fn scratch(tag: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "accshift-wine-{}-{}-{:?}",
+            tag,
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn prefix(tag: &str) -> Prefix {
+        let root = scratch(tag);
+        fs::write(root.join("user.reg"), USER_REG).unwrap();
+        Prefix::new(root, "demo")
+    }
+
+    const KEY: &str = "Software\\Acme\\Launcher";
+
+    #[test]
+    fn string_values_read_back_unescaped_and_keys_match_in_any_case() {
+        let prefix = prefix("read");
+        let hive = RegistryHive::CurrentUser;
+        assert_eq!(
+            prefix.read(hive, KEY, "AccountId").as_deref(),
+            Some("123456")
+        );
+        assert_eq!(
+            prefix
+                .read(hive, "software\\acme\\LAUNCHER", "accountid")
+                .as_deref(),
+            Some("123456")
+        );
+        assert_eq!(
+            prefix.read(hive, KEY, "Display").as_deref(),
+            Some("Renée \"R\" \\ Smith")
+        );
+        let _ = fs::remove_dir_all(prefix.root());
+    }
+
+    #[test]
+    fn numbers_and_binary_data_are_not_strings() {
+        let prefix = prefix("types");
+        let hive = RegistryHive::CurrentUser;
+        assert_eq!(prefix.read(hive, KEY, "Count"), None);
+        assert_eq!(prefix.read(hive, KEY, "Blob"), None);
+        // The wrapped binary value does not swallow the value after it.
+        assert_eq!(prefix.read(hive, KEY, "After").as_deref(), Some("kept"));
+        let _ = fs::remove_dir_all(prefix.root());
+    }
+
+    #[test]
+    fn a_write_changes_one_line_and_leaves_the_rest_of_the_hive_alone() {
+        let prefix = prefix("write");
+        let hive = RegistryHive::CurrentUser;
+        prefix.write(hive, KEY, "AccountId", "987654").unwrap();
+
+        let text = fs::read_to_string(prefix.hive_file(hive)).unwrap();
+        assert_eq!(text, USER_REG.replace("\"123456\"", "\"987654\""));
+        assert_eq!(
+            prefix.read(hive, KEY, "AccountId").as_deref(),
+            Some("987654")
+        );
+        let _ = fs::remove_dir_all(prefix.root());
+    }
+
+    #[test]
+    fn a_write_adds_the_value_or_the_key_when_the_hive_has_neither() {
+        let prefix = prefix("add");
+        let hive = RegistryHive::CurrentUser;
+        prefix
+            .write(hive, "Software\\Wine", "Token", "é\"x")
+            .unwrap();
+        prefix
+            .write(hive, "Software\\Other Launcher", "Token", "abc")
+            .unwrap();
+
+        assert_eq!(
+            prefix.read(hive, "Software\\Wine", "Token").as_deref(),
+            Some("é\"x")
+        );
+        assert_eq!(
+            prefix.read(hive, "Software\\Wine", "Version").as_deref(),
+            Some("win10")
+        );
+        assert_eq!(
+            prefix
+                .read(hive, "Software\\Other Launcher", "Token")
+                .as_deref(),
+            Some("abc")
+        );
+        let text = fs::read_to_string(prefix.hive_file(hive)).unwrap();
+        assert!(text.contains("[Software\\\\Other Launcher] "), "{text}");
+        assert!(text.contains("\"Token\"=\"\\x00e9\\\"x\""), "{text}");
+        let _ = fs::remove_dir_all(prefix.root());
+    }
+
+    #[test]
+    fn deleting_removes_every_line_of_the_value_and_nothing_else() {
+        let prefix = prefix("delete");
+        let hive = RegistryHive::CurrentUser;
+        prefix.delete(hive, KEY, "Blob");
+        prefix.delete(hive, KEY, "NotThere");
+
+        let text = fs::read_to_string(prefix.hive_file(hive)).unwrap();
+        assert!(!text.contains("hex:01"), "{text}");
+        assert!(!text.contains("  04,05"), "{text}");
+        assert_eq!(prefix.read(hive, KEY, "After").as_deref(), Some("kept"));
+        let _ = fs::remove_dir_all(prefix.root());
+    }
+
+    #[test]
+    fn a_prefix_wine_never_ran_in_is_not_written_into() {
+        let root = scratch("no-hive");
+        let prefix = Prefix::new(&root, "demo");
+        let err = prefix
+            .write(RegistryHive::LocalMachine, KEY, "AccountId", "1")
+            .unwrap_err();
+        assert!(err.contains("system.reg"), "{err}");
+        assert!(!prefix.hive_file(RegistryHive::LocalMachine).exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn an_uninstall_entry_inside_the_prefix_is_found_by_its_display_name() {
+        let root = scratch("uninstall");
+        fs::write(
+            root.join("system.reg"),
+            "WINE REGISTRY Version 2\n\n\
+             [Software\\\\Wow6432Node\\\\Microsoft\\\\Windows\\\\CurrentVersion\\\\Uninstall\\\\{ABC}] 1\n\
+             \"DisplayName\"=\"Acme Launcher\"\n\
+             \"InstallLocation\"=\"C:\\\\Program Files (x86)\\\\Acme\\\\\"\n",
+        )
+        .unwrap();
+        let prefix = Prefix::new(&root, "demo");
+        assert_eq!(
+            prefix
+                .uninstall_entry("Acme Launcher", "InstallLocation")
+                .as_deref(),
+            Some("C:\\Program Files (x86)\\Acme\\")
+        );
+        assert_eq!(prefix.uninstall_entry("Other", "InstallLocation"), None);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn windows_paths_map_onto_the_prefix() {
+        let prefix = Prefix::new("/games/acme", "demo");
+        assert_eq!(
+            prefix.host_path("C:\\Program Files\\Acme\\Acme.exe"),
+            Some(PathBuf::from(
+                "/games/acme/drive_c/Program Files/Acme/Acme.exe"
+            ))
+        );
+        assert_eq!(
+            prefix.host_path("d:/Games"),
+            Some(PathBuf::from("/games/acme/dosdevices/d:/Games"))
+        );
+        assert_eq!(prefix.host_path("relative\\path"), None);
+    }
+
+    #[test]
+    fn the_user_is_the_login_under_wine_and_steamuser_under_proton() {
+        let root = scratch("users");
+        fs::create_dir_all(root.join("drive_c/users/steamuser")).unwrap();
+        assert_eq!(Prefix::detect(&root, Some("alex")).user(), "steamuser");
+
+        fs::create_dir_all(root.join("drive_c/users/alex")).unwrap();
+        assert_eq!(Prefix::detect(&root, Some("alex")).user(), "alex");
+
+        let fresh = scratch("users-fresh");
+        assert_eq!(Prefix::detect(&fresh, Some("alex")).user(), "alex");
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_dir_all(&fresh);
+    }
+}
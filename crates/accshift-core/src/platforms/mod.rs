@@ -1,9 +1,13 @@
 use crate::context::{AppContext, AppCtx};
 use crate::error::PlatformError;
-use descriptor::{Descriptor, DescriptorOrigin, DescriptorService};
+use crate::snapshot_history::SnapshotGeneration;
+use descriptor::lint::LintWarning;
+use descriptor::plan::DryRunOperation;
+use descriptor::schema::AccountField;
+use descriptor::{Descriptor, DescriptorOrigin, DescriptorService, Signer, UserDescriptor};
 use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use std::sync::{OnceLock, RwLock};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -40,12 +44,10 @@ pub mod ids {
 #[cfg(any(windows, target_os = "macos"))]
 pub mod battle_net;
 /// Platforms described by a JSON descriptor and run by a single engine. GOG,
-/// Jagex, Epic, Ubisoft and Discord live here instead of in a module of their
-/// own.
+/// Jagex, Epic, Ubisoft, Discord and Riot live here instead of in a module of
+/// their own.
 pub mod descriptor;
 #[cfg(windows)]
-pub mod riot;
-#[cfg(windows)]
 pub mod roblox;
 pub(crate) mod setup_jobs;
 pub mod steam;
@@ -227,23 +229,62 @@ pub trait PlatformService: Send + Sync {
         Err(PlatformError::other("Account labeling not supported"))
     }
 
-    /// Whether [`Self::dry_run`] answers on this platform.
+    /// Stores the values a descriptor's `launch` block reads through
+    /// `${account.<name>}`, replacing the ones the account had. Only
+    /// descriptor-driven platforms have such a block.
+    fn set_account_launch_values(
+        &self,
+        _app: AppCtx,
+        _account_id: &str,
+        _values: BTreeMap<String, String>,
+    ) -> Result<(), PlatformError> {
+        Err(PlatformError::other("Launch values not supported"))
+    }
+
+    /// The extra values this platform keeps on each account, in the order its
+    /// descriptor declares them. An account's values sit in the `fields`
+    /// object of its [`Self::get_accounts`] entry. Only descriptor-driven
+    /// platforms declare any.
+    fn account_fields(&self) -> Vec<AccountField> {
+        Vec::new()
+    }
+
+    /// The earlier captures kept of the account's snapshot, newest first. A
+    /// switch restores one when its params carry `snapshotGeneration`. Only
+    /// platforms that snapshot a session keep any.
+    fn snapshot_generations(
+        &self,
+        _app: AppCtx,
+        _account_id: &str,
+    ) -> Result<Vec<SnapshotGeneration>, PlatformError> {
+        Err(PlatformError::other("Snapshot history not supported"))
+    }
+
+    /// Whether [`Self::dry_run`] answers `operation` on this platform.
     ///
     /// Asked before calling, so a caller reports "this platform has no plan"
     /// without reading an error message to find out.
-    fn supports_dry_run(&self) -> bool {
+    fn supports_dry_run(&self, _operation: DryRunOperation) -> bool {
         false
     }
 
-    /// Everything switching to `account_id` would read, copy, write and close,
-    /// without doing any of it.
+    /// Everything `operation` would read, copy, write and close, without doing
+    /// any of it. `account_id` names the account a switch or a forget acts on;
+    /// setup and capture act on whatever is signed in and ignore it. `params`
+    /// are the ones [`Self::switch_account`] would be given.
     ///
-    /// Only descriptor-driven platforms answer this today: the hand-written
-    /// modules would each need their own plan, and the point of the descriptors
-    /// is that they no longer have to.
-    fn dry_run(&self, _app: AppCtx, _account_id: &str) -> Result<Value, PlatformError> {
+    /// Descriptor-driven platforms answer every operation, and Steam answers
+    /// the switch. The other hand-written modules would each need their own
+    /// plan, and the point of the descriptors is that they no longer have to.
+    fn dry_run(
+        &self,
+        _app: AppCtx,
+        _operation: DryRunOperation,
+        _account_id: &str,
+        _params: Value,
+    ) -> Result<Value, PlatformError> {
         Err(PlatformError::other(
-            "Dry run is only available for platforms described by a descriptor",
+            "Dry run is only available for Steam and for platforms described by a descriptor",
         ))
     }
 }
@@ -260,7 +301,6 @@ fn platform_registry() -> &'static HashMap<&'static str, &'static dyn PlatformSe
         }
         #[cfg(windows)]
         {
-            map.insert(ids::RIOT, &riot::RIOT_SERVICE);
             map.insert(ids::BATTLE_NET, &battle_net::BATTLE_NET_SERVICE);
             map.insert(ids::ROBLOX, &roblox::ROBLOX_SERVICE);
         }
@@ -321,6 +361,13 @@ pub struct UserPlatformReport {
     /// platform entry from it and so needs no second description of a platform
     /// this build was never compiled to know about.
     pub loaded: Vec<Descriptor>,
+    /// Who signed each loaded descriptor, by platform id. Every id in
+    /// `loaded` has an entry, `unsigned` included, so a list can label all of
+    /// them without guessing what a missing entry means.
+    pub signers: BTreeMap<String, Signer>,
+    /// What [`descriptor::lint`] found in each loaded descriptor, by platform
+    /// id. Only ids with something to say have an entry.
+    pub lint: BTreeMap<String, Vec<LintWarning>>,
     pub skipped: Vec<SkippedPlatform>,
     pub rejected: Vec<RejectedDescriptor>,
 }
@@ -332,7 +379,10 @@ pub struct UserPlatformReport {
 /// Services are leaked, because the registry hands out `&'static dyn
 /// PlatformService`. A reload therefore leaks the descriptors it replaces, a
 /// few kilobytes each: bounded by how often a human edits a file, and the
-/// alternative is handing out a reference that can dangle mid-switch.
+/// alternative is handing out a reference that can dangle mid-switch. A file
+/// that reads back the same as the service already answering for its id keeps
+/// that service, so a reload nothing changed, or a watcher noticing a file
+/// that was only touched, leaks nothing.
 pub fn reload_user_platforms(app: &dyn AppContext) -> UserPlatformReport {
     let mut report = UserPlatformReport {
         dir: descriptor::user_dir(app)
@@ -351,8 +401,17 @@ pub fn reload_user_platforms(app: &dyn AppContext) -> UserPlatformReport {
         })
         .collect();
 
+    let previous = user_registry()
+        .read()
+        .map(|registry| registry.clone())
+        .unwrap_or_default();
     let mut fresh: HashMap<String, &'static DescriptorService> = HashMap::new();
-    for (path, descriptor) in descriptors {
+    for UserDescriptor {
+        path,
+        descriptor,
+        signer,
+    } in descriptors
+    {
         let id = descriptor.id.clone();
         if platform_registry().contains_key(id.as_str()) {
             report.skipped.push(SkippedPlatform {
@@ -376,8 +435,17 @@ pub fn reload_user_platforms(app: &dyn AppContext) -> UserPlatformReport {
             continue;
         }
         report.loaded.push(descriptor.clone());
-        let service = DescriptorService::new(descriptor, DescriptorOrigin::User(path));
-        fresh.insert(id, Box::leak(Box::new(service)));
+        report.signers.insert(id.clone(), signer);
+        let warnings = descriptor::lint::lint(&descriptor);
+        if !warnings.is_empty() {
+            report.lint.insert(id.clone(), warnings);
+        }
+        let origin = DescriptorOrigin::User(path);
+        let service = match previous.get(&id) {
+            Some(&service) if same_source(service, &descriptor, &origin) => service,
+            _ => Box::leak(Box::new(DescriptorService::new(descriptor, origin))),
+        };
+        fresh.insert(id, service);
     }
 
     if let Ok(mut registry) = user_registry().write() {
@@ -386,6 +454,18 @@ pub fn reload_user_platforms(app: &dyn AppContext) -> UserPlatformReport {
     report
 }
 
+/// Whether a running service was built from this very descriptor, read from
+/// this very file. Compared through serde, which is how the descriptor was
+/// read in the first place: two values that serialize alike behave alike.
+fn same_source(
+    service: &DescriptorService,
+    descriptor: &Descriptor,
+    origin: &DescriptorOrigin,
+) -> bool {
+    service.origin() == origin
+        && serde_json::to_value(service.descriptor()).ok() == serde_json::to_value(descriptor).ok()
+}
+
 /// Whether this build compiled a platform in under that id.
 ///
 /// The one question that separates "the user may add this" from "this is ours":
@@ -509,6 +589,29 @@ mod tests {
         );
         assert!(all_ids().contains(&"acme".to_string()));
 
+        // Reloading a folder nothing changed in keeps the service that was
+        // answering, so a watcher that fires on a touched file leaks nothing.
+        // An edit does replace it.
+        let acme = || std::ptr::from_ref(user_registry().read().unwrap()["acme"]);
+        let before = acme();
+        reload_user_platforms(&ctx);
+        assert_eq!(
+            acme(),
+            before,
+            "an unchanged file must not build a new service"
+        );
+        drop_in(
+            &ctx,
+            "acme.json",
+            &fixture("acme", &root).replace("Fixture Launcher", "Acme Launcher"),
+        );
+        reload_user_platforms(&ctx);
+        assert_ne!(acme(), before, "an edited file must take effect");
+        assert_eq!(
+            user_registry().read().unwrap()["acme"].descriptor().name,
+            "Acme Launcher"
+        );
+
         // A shipped id is refused by name rather than shadowed: a file dropped
         // in a folder must not be able to take over Steam.
         drop_in(&ctx, "steam.json", &fixture("steam", &root));
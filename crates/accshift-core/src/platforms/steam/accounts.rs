@@ -3,12 +3,16 @@ use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use super::appinfo;
+use super::keyvalues::Document;
 #[cfg(not(target_os = "windows"))]
-use super::vdf::vdf_set_nested_value;
-use super::vdf::{parse_vdf, read_persona_state, set_persona_state};
+use super::vdf::write_document;
+use super::vdf::{localconfig_path, read_document, read_persona_state, set_persona_state};
 use crate::error::AppError;
 use crate::fs_utils;
-use crate::os;
+use crate::os::{self, AutoLoginStore};
+use crate::platforms::descriptor::plan::{DryRunPlan, PlanAction, PlanTargetKind};
+use crate::platforms::ids;
 
 const KILL_WAIT_MS: u32 = 5000;
 // Windows exits in 2-5s; the wait stops as soon as it does.
@@ -137,28 +141,42 @@ fn write_auto_login(next_username: Option<&str>) -> Result<(), AppError> {
 #[cfg(not(target_os = "windows"))]
 fn set_login_user_flags(steam_path: &Path, target: Option<&str>) -> Result<(), AppError> {
     let path = steam_path.join("config").join("loginusers.vdf");
-    if !path.exists() {
+    let Some(mut document) = read_document(&path)? else {
         return Ok(());
-    }
-
-    let content = fs::read_to_string(&path).map_err(|e| AppError::FileRead(e.to_string()))?;
-    let users = parse_vdf(&content);
-    if users.is_empty() {
+    };
+    let flags = login_user_flags(&document, target);
+    if flags.is_empty() {
         return Ok(());
     }
 
-    let mut updated = content;
-    for (steam_id, fields) in &users {
-        let account_name = fields.get("accountname").cloned().unwrap_or_default();
-        let is_target = target
-            .map(|t| account_name == t && !account_name.is_empty())
-            .unwrap_or(false);
-        let flag = if is_target { "1" } else { "0" };
-        updated = vdf_set_nested_value(&updated, &[steam_id.as_str(), "AllowAutoLogin"], flag);
-        updated = vdf_set_nested_value(&updated, &[steam_id.as_str(), "MostRecent"], flag);
+    for (steam_id, flag) in &flags {
+        for key in ["AllowAutoLogin", "MostRecent"] {
+            document
+                .set(&["users", steam_id, key], flag)
+                .map_err(AppError::FileRead)?;
+        }
     }
 
-    crate::storage::write_bytes_atomic(&path, updated.as_bytes()).map_err(AppError::FileRead)
+    write_document(&path, &document)
+}
+
+/// Each user in loginusers.vdf with the value its flags should carry.
+#[cfg(not(target_os = "windows"))]
+fn login_user_flags(document: &Document, target: Option<&str>) -> Vec<(String, &'static str)> {
+    let Some(users) = document.section(&["users"]) else {
+        return Vec::new();
+    };
+    users
+        .sections()
+        .map(|(steam_id, user)| {
+            let account_name = user.value("AccountName").unwrap_or_default();
+            let is_target = target
+                .map(|t| account_name == t && !account_name.is_empty())
+                .unwrap_or(false);
+            let flag = if is_target { "1" } else { "0" };
+            (steam_id.to_string(), flag)
+        })
+        .collect()
 }
 
 #[cfg(target_os = "windows")]
@@ -166,6 +184,45 @@ fn set_login_user_flags(_steam_path: &Path, _target: Option<&str>) -> Result<(),
     Ok(())
 }
 
+/// The flags [`set_login_user_flags`] would change, each with the value it
+/// would take. Flags already at that value are left out.
+#[cfg(not(target_os = "windows"))]
+fn plan_login_user_flags(
+    plan: &mut DryRunPlan,
+    steam_path: &Path,
+    target: Option<&str>,
+) -> Result<(), AppError> {
+    let path = steam_path.join("config").join("loginusers.vdf");
+    let Some(document) = read_document(&path)? else {
+        return Ok(());
+    };
+    let mut users = login_user_flags(&document, target);
+    users.sort_by(|a, b| a.0.cmp(&b.0));
+    for (steam_id, flag) in &users {
+        for key in ["AllowAutoLogin", "MostRecent"] {
+            if document.get(&["users", steam_id, key]) == Some(*flag) {
+                continue;
+            }
+            plan.simple_step(
+                PlanAction::Write,
+                PlanTargetKind::FileKey,
+                format!("{}#{steam_id}/{key}", path.display()),
+                format!("= {flag}"),
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn plan_login_user_flags(
+    _plan: &mut DryRunPlan,
+    _steam_path: &Path,
+    _target: Option<&str>,
+) -> Result<(), AppError> {
+    Ok(())
+}
+
 fn restore_auto_login_user(previous_username: &str) -> Result<(), AppError> {
     if previous_username.trim().is_empty() {
         os::clear_auto_login_user()
@@ -368,61 +425,54 @@ pub(crate) fn list_account_games(userdata_root: &Path) -> Result<HashSet<String>
     Ok(ids)
 }
 
-// Pulls "appid" and "name" out of an appmanifest in a single pass. Both keys
-// sit in the first handful of lines while the file goes on with hundreds of
-// InstalledDepots entries, so stop as soon as both are found.
-fn extract_appid_and_name(content: &str) -> (Option<String>, Option<String>) {
-    let mut app_id = None;
-    let mut name = None;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if !trimmed.starts_with('"') {
-            continue;
-        }
-        let mut parts = trimmed.split('"');
-        // Elements 1 and 3 of the split: the quoted key and its quoted value.
-        let Some(key) = parts.nth(1) else {
-            continue;
-        };
-        let Some(value) = parts.nth(1) else {
-            continue;
-        };
-        if app_id.is_none() && key.eq_ignore_ascii_case("appid") {
-            app_id = Some(value.to_string());
-        } else if name.is_none() && key.eq_ignore_ascii_case("name") {
-            name = Some(value.to_string());
-        }
-        if app_id.is_some() && name.is_some() {
-            break;
-        }
-    }
-
-    (app_id, name)
+/// An app installed in one of the Steam libraries, from its appmanifest.
+pub(crate) struct InstalledApp {
+    pub app_id: String,
+    pub name: Option<String>,
+    /// The SteamID64 of the account that installed or last updated it.
+    pub last_owner: Option<String>,
 }
 
-fn unescape_vdf_path(input: &str) -> String {
-    input.replace("\\\\", "\\")
+fn read_app_manifest(content: &str) -> Option<InstalledApp> {
+    let manifest = Document::parse(content).ok()?;
+    let field = |key| {
+        manifest
+            .get(&["AppState", key])
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    Some(InstalledApp {
+        app_id: field("appid")?,
+        name: field("name"),
+        // Steam writes 0 until an account has updated the install.
+        last_owner: field("LastOwner").filter(|owner| owner != "0"),
+    })
 }
 
 fn load_library_paths(steam_path: &Path) -> Vec<PathBuf> {
     let mut paths = vec![steam_path.to_path_buf()];
     let libraryfolders_path = steam_path.join("steamapps").join("libraryfolders.vdf");
-    let Ok(content) = fs::read_to_string(libraryfolders_path) else {
+    let Ok(Some(document)) = read_document(&libraryfolders_path) else {
+        return paths;
+    };
+    let Some(folders) = document.section(&["libraryfolders"]) else {
         return paths;
     };
 
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if !trimmed.starts_with('"') {
-            continue;
-        }
-        let parts: Vec<&str> = trimmed.split('"').collect();
-        if parts.len() >= 4 && parts[1].eq_ignore_ascii_case("path") {
-            let raw = parts[3].trim();
-            if !raw.is_empty() {
-                paths.push(PathBuf::from(unescape_vdf_path(raw)));
-            }
+    // Current clients give each library a section with a "path"; older ones
+    // wrote the path straight under a numbered key.
+    let sections = folders
+        .sections()
+        .filter_map(|(_, folder)| folder.value("path"));
+    let legacy = folders
+        .values()
+        .filter(|(key, _)| key.chars().all(|c| c.is_ascii_digit()))
+        .map(|(_, path)| path);
+    for raw in sections.chain(legacy) {
+        let raw = raw.trim();
+        if !raw.is_empty() {
+            paths.push(PathBuf::from(raw));
         }
     }
 
@@ -431,8 +481,9 @@ fn load_library_paths(steam_path: &Path) -> Vec<PathBuf> {
     paths
 }
 
-pub(crate) fn load_app_names(steam_path: &Path) -> HashMap<String, String> {
-    let mut names = HashMap::new();
+/// Every app with an appmanifest in any of the Steam libraries.
+pub(crate) fn installed_apps(steam_path: &Path) -> Vec<InstalledApp> {
+    let mut apps = Vec::new();
     for library_root in load_library_paths(steam_path) {
         let steamapps = if library_root
             .file_name()
@@ -458,9 +509,41 @@ pub(crate) fn load_app_names(steam_path: &Path) -> HashMap<String, String> {
             let Ok(content) = fs::read_to_string(&path) else {
                 continue;
             };
-            let (app_id, name) = extract_appid_and_name(&content);
-            if let (Some(app_id), Some(name)) = (app_id, name) {
-                names.entry(app_id).or_insert(name);
+            apps.extend(read_app_manifest(&content));
+        }
+    }
+    apps
+}
+
+/// Names for `app_ids`. Installed apps are named by their appmanifest; the
+/// rest (played once, since uninstalled) by the client's appinfo.vdf cache,
+/// read only when a manifest is missing since the cache is large.
+///
+/// Only names are looked up here: which apps a list holds is up to the caller,
+/// and a game the account owns but never launched on this machine leaves
+/// nothing behind to list it by. Ownership is only on disk in the client's
+/// license cache, which is encrypted.
+pub(crate) fn load_app_names(
+    steam_path: &Path,
+    app_ids: &HashSet<String>,
+) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    for app in installed_apps(steam_path) {
+        if let Some(name) = app.name {
+            names.entry(app.app_id).or_insert(name);
+        }
+    }
+
+    let missing: HashSet<u32> = app_ids
+        .iter()
+        .filter(|id| !names.contains_key(*id))
+        .filter_map(|id| id.parse().ok())
+        .collect();
+    if !missing.is_empty() {
+        // An unreadable cache only costs the names it would have given.
+        if let Ok(found) = appinfo::app_names(steam_path, |id| missing.contains(&id)) {
+            for (app_id, name) in found {
+                names.entry(app_id.to_string()).or_insert(name);
             }
         }
     }
@@ -535,141 +618,34 @@ pub fn get_accounts_snapshot(steam_path: &Path) -> Result<(Vec<SteamAccount>, St
 
 fn parse_login_users(steam_path: &Path) -> Result<Vec<ParsedLoginUser>, AppError> {
     let loginusers_path = steam_path.join("config").join("loginusers.vdf");
-    if !loginusers_path.exists() {
+    let Some(document) = read_document(&loginusers_path)? else {
         return Ok(Vec::new());
-    }
-
-    let content =
-        fs::read_to_string(&loginusers_path).map_err(|e| AppError::FileRead(e.to_string()))?;
-
-    let parsed = parse_vdf(&content);
+    };
+    let Some(users) = document.section(&["users"]) else {
+        return Ok(Vec::new());
+    };
 
-    let users: Vec<ParsedLoginUser> = parsed
-        .into_iter()
-        .map(|(steam_id, data)| ParsedLoginUser {
-            steam_id,
-            account_name: data.get("accountname").cloned().unwrap_or_default(),
-            persona_name: data.get("personaname").cloned().unwrap_or_default(),
-            last_login_at: data.get("timestamp").and_then(|ts| ts.parse::<u64>().ok()),
-            is_most_recent: data.get("mostrecent").map(|v| v == "1").unwrap_or(false),
+    Ok(users
+        .sections()
+        .map(|(steam_id, user)| ParsedLoginUser {
+            steam_id: steam_id.to_string(),
+            account_name: user.value("AccountName").unwrap_or_default().to_string(),
+            persona_name: user.value("PersonaName").unwrap_or_default().to_string(),
+            last_login_at: user
+                .value("Timestamp")
+                .and_then(|ts| ts.trim().parse::<u64>().ok()),
+            is_most_recent: user.value("MostRecent") == Some("1"),
         })
-        .collect();
-
-    Ok(users)
+        .collect())
 }
 
-/// Counts the net brace delta of a line, ignoring braces inside quoted
-/// strings. A double-quote toggles the in-string state; a backslash escapes
-/// the next char so an escaped quote does not close the string. Returns
-/// (opens, closes) so callers can reason about a line that both opens and
-/// closes a block.
-fn brace_counts(line: &str) -> (i32, i32) {
-    let mut opens = 0;
-    let mut closes = 0;
-    let mut in_string = false;
-    let mut escaped = false;
-
-    for ch in line.chars() {
-        if escaped {
-            escaped = false;
-            continue;
-        }
-        match ch {
-            '\\' if in_string => escaped = true,
-            '"' => in_string = !in_string,
-            '{' if !in_string => opens += 1,
-            '}' if !in_string => closes += 1,
-            _ => {}
-        }
-    }
-
-    (opens, closes)
-}
-
-/// Removes the block for `steam_id` from a loginusers.vdf string.
-///
-/// Brace accounting counts the real `{`/`}` on each line (skipping braces
-/// inside quoted strings) instead of assuming each brace sits alone on its
-/// own line. That keeps the normal Steam layout working while also handling
-/// VDF condensed by third-party tools, where the opening brace can share the
-/// key line (e.g. `"<steamid>" {`). Without this, the inline brace was never
-/// counted, depth drifted by one, and the skip never terminated, silently
-/// deleting every account after the target.
-fn remove_loginuser_entry(content: &str, steam_id: &str) -> (String, bool) {
-    let mut out: Vec<&str> = Vec::new();
-    let mut depth: i32 = 0;
-    let mut skipping = false;
-    // Depth the target block must fall back to for the skip to end. Set when
-    // the target's opening brace is seen (it may share the key line).
-    let mut skip_end_depth: i32 = 0;
-    // True once the target key was matched but its `{` has not appeared yet
-    // (only happens when the brace is on its own line).
-    let mut awaiting_open = false;
-    let mut removed = false;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-        let (opens, closes) = brace_counts(line);
-
-        if skipping {
-            // The block's own braces were already absorbed when the key line
-            // was matched (if inline) or while awaiting/inside it. Walk depth
-            // down until it returns to where the block started.
-            depth += opens - closes;
-            if depth <= skip_end_depth {
-                skipping = false;
-            }
-            continue;
-        }
-
-        if awaiting_open {
-            // The key matched on the previous line; this line carries its
-            // opening brace. From here the regular skip logic takes over.
-            depth += opens - closes;
-            awaiting_open = false;
-            skipping = true;
-            if depth <= skip_end_depth {
-                // Degenerate single-line block `{ ... }`; nothing more to skip.
-                skipping = false;
-            }
-            continue;
-        }
-
-        // A top-level account key sits at depth 1 (inside the root "users"
-        // block). Match it before counting this line's braces so the key's
-        // own inline `{` is attributed to the block being removed.
-        let parts: Vec<&str> = trimmed.split('"').collect();
-        let is_target = depth == 1 && parts.len() >= 2 && parts[1] == steam_id;
-
-        if is_target {
-            removed = true;
-            skip_end_depth = depth;
-            if opens > 0 {
-                // Inline brace: the block opens on the key line. Account for
-                // this line's net braces and start skipping the body.
-                depth += opens - closes;
-                if depth > skip_end_depth {
-                    skipping = true;
-                }
-                // If depth did not rise (e.g. `"id" {}`), the block is already
-                // closed and there is nothing further to skip.
-            } else {
-                // Brace lands on the next line.
-                awaiting_open = true;
-            }
-            continue;
-        }
-
-        out.push(line);
-        depth += opens - closes;
-    }
-
-    let mut rebuilt = out.join("\n");
-    if content.ends_with('\n') {
-        rebuilt.push('\n');
-    }
-
-    (rebuilt, removed)
+/// Removes the block for `steam_id` from a loginusers.vdf string, wherever
+/// its braces sit, and leaves every other byte of the file as it was.
+fn remove_loginuser_entry(content: &str, steam_id: &str) -> Result<(String, bool), AppError> {
+    let mut document =
+        Document::parse(content).map_err(|e| AppError::FileRead(format!("loginusers.vdf: {e}")))?;
+    let removed = document.remove(&["users", steam_id]);
+    Ok((document.to_string(), removed))
 }
 
 pub fn switch_account(
@@ -735,7 +711,7 @@ pub fn forget_account(steam_path: &Path, steam_id: &str) -> Result<(), AppError>
     if loginusers_path.exists() {
         let content =
             fs::read_to_string(&loginusers_path).map_err(|e| AppError::FileRead(e.to_string()))?;
-        let (updated, removed) = remove_loginuser_entry(&content, steam_id);
+        let (updated, removed) = remove_loginuser_entry(&content, steam_id)?;
         if removed {
             // Steam keeps loginusers.vdf in memory and rewrites it on exit.
             // Editing it while Steam runs silently resurrects the entry. Stop
@@ -762,10 +738,7 @@ pub fn switch_account_mode(
     force_kill: bool,
 ) -> Result<(), AppError> {
     let account_id = steam_id_to_account_id(steam_id);
-    let state = match mode {
-        "invisible" => "7",
-        _ => "1",
-    };
+    let state = persona_state_for(mode);
 
     // Snapshot the current persona state up front. pre_launch writes the new
     // state before Steam is relaunched; if the relaunch then fails the switch
@@ -807,6 +780,140 @@ pub fn switch_account_mode(
     persona_result
 }
 
+/// The `PersonaState` value a switch mode stands for.
+fn persona_state_for(mode: &str) -> &'static str {
+    match mode {
+        "invisible" => "7",
+        _ => "1",
+    }
+}
+
+/// What [`switch_account`], or [`switch_account_mode`] when `persona` names a
+/// SteamID and a mode, would do, in the order
+/// [`switch_autologin_and_relaunch`] does it. With `app_id` it is the plan of
+/// a switch that launches that game. Reads only.
+pub fn plan_switch(
+    steam_path: &Path,
+    username: &str,
+    persona: Option<(&str, &str)>,
+    app_id: Option<&str>,
+    run_as_admin: bool,
+    launch_options: &str,
+    force_kill: bool,
+) -> Result<DryRunPlan, AppError> {
+    let store = os::auto_login_store()?;
+    let mut roots = vec![steam_path.display().to_string()];
+    if let AutoLoginStore::VdfFile(path) = &store {
+        roots.push(path.display().to_string());
+    }
+    let mut plan = DryRunPlan::new(ids::STEAM, "switch", username).with_roots(roots);
+
+    let loginusers = steam_path.join("config").join("loginusers.vdf");
+    plan.simple_step(
+        PlanAction::Read,
+        PlanTargetKind::File,
+        loginusers.display().to_string(),
+        "",
+    );
+    if !parse_login_users(steam_path)?
+        .iter()
+        .any(|user| user.account_name == username)
+    {
+        plan.warn(format!(
+            "{username} is not in loginusers.vdf, so Steam would open on its sign-in screen."
+        ));
+    }
+
+    // The game launch skips the switch when Steam already runs as the target.
+    if let Some(app_id) = app_id {
+        let signed_in = is_steam_running()
+            && get_current_account_name(steam_path)
+                .is_ok_and(|current| current.eq_ignore_ascii_case(username));
+        if signed_in {
+            plan.simple_step(
+                PlanAction::Launch,
+                PlanTargetKind::Executable,
+                format!("steam://rungameid/{app_id}"),
+                "Steam is already signed in, so the game is handed to it",
+            );
+            return Ok(plan);
+        }
+    }
+
+    for name in [
+        os::steam_process_name(),
+        os::steam_web_helper_process_name(),
+    ] {
+        let note = if !os::is_process_running(name) {
+            "not running"
+        } else if force_kill {
+            "killed"
+        } else {
+            "asked to exit, killed if it does not"
+        };
+        plan.simple_step(PlanAction::Close, PlanTargetKind::Process, name, note);
+    }
+
+    // What `set_auto_login_user` writes, wherever this OS keeps it.
+    let values = [("AutoLoginUser", username), ("RememberPassword", "1")];
+    for (name, value) in values {
+        let (kind, target) = match &store {
+            AutoLoginStore::Registry(key) => {
+                (PlanTargetKind::RegistryValue, format!("{key}\\{name}"))
+            }
+            AutoLoginStore::VdfFile(path) => (
+                PlanTargetKind::FileKey,
+                format!("{}#HKCU/Software/Valve/Steam/{name}", path.display()),
+            ),
+        };
+        plan.simple_step(PlanAction::Write, kind, target, format!("= {value}"));
+    }
+
+    plan_login_user_flags(&mut plan, steam_path, Some(username))?;
+
+    if let Some((steam_id, mode)) = persona {
+        match steam_id_to_account_id(steam_id) {
+            Some(account_id) => {
+                let config = localconfig_path(steam_path, account_id);
+                let note = if config.is_file() {
+                    format!("= {} ({mode})", persona_state_for(mode))
+                } else {
+                    "not present, left alone".to_string()
+                };
+                plan.simple_step(
+                    PlanAction::Write,
+                    PlanTargetKind::FileKey,
+                    format!(
+                        "{}#UserLocalConfigStore/friends/PersonaState",
+                        config.display()
+                    ),
+                    note,
+                );
+            }
+            None => plan.warn(format!(
+                "No SteamID is known for {username}, so it would not be set {mode}."
+            )),
+        }
+    }
+
+    let mut args = parse_launch_options(launch_options);
+    if let Some(app_id) = app_id {
+        args.extend(["-applaunch".to_string(), app_id.to_string()]);
+    }
+    plan.simple_step(
+        PlanAction::Launch,
+        PlanTargetKind::Executable,
+        os::steam_launch_line(steam_path, &args),
+        if run_as_admin && cfg!(windows) {
+            "as administrator"
+        } else {
+            ""
+        },
+    );
+
+    Ok(plan)
+}
+
 pub fn open_userdata_with_path(steam_path: &Path, steam_id: &str) -> Result<(), AppError> {
     let userdata_path = steam_user_data_path(steam_path, steam_id)?;
 
@@ -881,7 +988,7 @@ pub fn get_copyable_games(
 ) -> Result<Vec<CopyableGame>, AppError> {
     let from_root = steam_user_data_path(steam_path, from_steam_id)?;
     let from_games = list_account_games(&from_root)?;
-    let names = load_app_names(steam_path);
+    let names = load_app_names(steam_path, &from_games);
 
     let mut games: Vec<CopyableGame> = from_games
         .iter()
@@ -1023,7 +1130,7 @@ mod tests {
 \t}\n\
 }\n";
 
-        let (out, removed) = remove_loginuser_entry(content, "111");
+        let (out, removed) = remove_loginuser_entry(content, "111").unwrap();
         assert!(removed);
         assert!(!out.contains("\"111\""));
         assert!(!out.contains("first"));
@@ -1058,7 +1165,7 @@ mod tests {
 \t}\n\
 }\n";
 
-        let (out, removed) = remove_loginuser_entry(content, "111");
+        let (out, removed) = remove_loginuser_entry(content, "111").unwrap();
         assert!(removed);
         assert!(!out.contains("\"111\""));
         assert!(!out.contains("first"));
@@ -1087,7 +1194,7 @@ mod tests {
 \t}\n\
 }\n";
 
-        let (out, removed) = remove_loginuser_entry(content, "222");
+        let (out, removed) = remove_loginuser_entry(content, "222").unwrap();
         assert!(removed);
         assert!(out.contains("\"111\""));
         assert!(out.contains("first"));
@@ -1113,7 +1220,7 @@ mod tests {
 \t}\n\
 }\n";
 
-        let (out, removed) = remove_loginuser_entry(content, "222");
+        let (out, removed) = remove_loginuser_entry(content, "222").unwrap();
         assert!(removed);
         assert!(out.contains("\"111\""));
         assert!(out.contains("first"));
@@ -1134,7 +1241,7 @@ mod tests {
 \t}\n\
 }\n";
 
-        let (out, removed) = remove_loginuser_entry(content, "999");
+        let (out, removed) = remove_loginuser_entry(content, "999").unwrap();
         assert!(!removed);
         assert_eq!(out, content);
     }
@@ -1155,7 +1262,7 @@ mod tests {
 \t}\n\
 }\n";
 
-        let (out, removed) = remove_loginuser_entry(content, "111");
+        let (out, removed) = remove_loginuser_entry(content, "111").unwrap();
         assert!(removed);
         assert!(!out.contains("weird { name }"));
         assert!(out.contains("\"222\""));
@@ -1228,4 +1335,61 @@ mod tests {
         assert!(copy_game_settings(&root, FROM_ID, TO_ID, "../evil").is_err());
         let _ = fs::remove_dir_all(&root);
     }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn plan_login_user_flags_lists_only_the_flags_that_change() {
+        use crate::platforms::descriptor::plan::DryRunPlan;
+
+        let root = copy_test_root("planflags");
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::write(
+            root.join("config").join("loginusers.vdf"),
+            "\"users\"\n{\n\
+             \t\"76561197960265729\"\n\t{\n\t\t\"AccountName\"\t\"alice\"\n\
+             \t\t\"AllowAutoLogin\"\t\"1\"\n\t\t\"MostRecent\"\t\"1\"\n\t}\n\
+             \t\"76561197960265730\"\n\t{\n\t\t\"AccountName\"\t\"bob\"\n\
+             \t\t\"AllowAutoLogin\"\t\"0\"\n\t\t\"MostRecent\"\t\"0\"\n\t}\n}\n",
+        )
+        .unwrap();
+
+        let mut plan = DryRunPlan::new("steam", "switch", "bob");
+        super::plan_login_user_flags(&mut plan, &root, Some("bob")).unwrap();
+
+        let writes: Vec<_> = plan
+            .steps
+            .iter()
+            .map(|step| {
+                let key = step.target.rsplit('#').next().unwrap().to_string();
+                (key, step.note.clone())
+            })
+            .collect();
+        assert_eq!(
+            writes,
+            vec![
+                (
+                    "76561197960265729/AllowAutoLogin".to_string(),
+                    "= 0".to_string()
+                ),
+                (
+                    "76561197960265729/MostRecent".to_string(),
+                    "= 0".to_string()
+                ),
+                (
+                    "76561197960265730/AllowAutoLogin".to_string(),
+                    "= 1".to_string()
+                ),
+                (
+                    "76561197960265730/MostRecent".to_string(),
+                    "= 1".to_string()
+                ),
+            ]
+        );
+
+        // Nothing to write once the target already holds both flags.
+        let mut plan = DryRunPlan::new("steam", "switch", "alice");
+        super::plan_login_user_flags(&mut plan, &root, Some("alice")).unwrap();
+        assert!(plan.steps.is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
 }
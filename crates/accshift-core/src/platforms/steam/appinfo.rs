@@ -0,0 +1,458 @@
+//! `appcache/appinfo.vdf`: what Steam knows about every app the client has
+//! seen, installed or not.
+//!
+//! The file is a header, then one record per app, each a short fixed header
+//! followed by the app's binary KeyValues. Version 29 moved every key into a
+//! string table at the end of the file; 28 added a hash of the binary data
+//! to each record; 27 is the layout before either. The file runs to hundreds
+//! of megabytes, so it is streamed: a record nobody asked for is seeked past
+//! without being read, and only the ones asked for are held in memory.
+
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::binary_vdf::{ReadError, Reader, Section};
+use crate::error::AppError;
+
+const MAGIC_V27: u32 = 0x0756_4427;
+const MAGIC_V28: u32 = 0x0756_4428;
+const MAGIC_V29: u32 = 0x0756_4429;
+
+/// One app as appinfo.vdf describes it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppInfo {
+    pub app_id: u32,
+    pub name: String,
+    /// `game`, `application`, `tool`, `dlc`, `demo`, ... lowercased, as
+    /// Steam is not consistent about the case.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub launch: Vec<LaunchConfig>,
+}
+
+/// One entry of the app's launch menu.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchConfig {
+    pub executable: String,
+    pub arguments: String,
+    pub description: String,
+    pub working_dir: String,
+    /// The operating systems the entry is for, e.g. `["windows"]`. Empty when
+    /// it is for all of them.
+    pub os_list: Vec<String>,
+}
+
+impl AppInfo {
+    fn from_keyvalues(app_id: u32, root: &Section) -> Self {
+        // The data sits under a single `appinfo` section.
+        let info = root.section("appinfo").unwrap_or(root);
+        let common = info.section("common");
+        let text = |key: &str| {
+            common
+                .and_then(|common| common.string(key))
+                .unwrap_or_default()
+                .to_string()
+        };
+        let launch = info
+            .section("config")
+            .and_then(|config| config.section("launch"))
+            .map(|launch| {
+                launch
+                    .sections()
+                    .map(|(_, entry)| LaunchConfig::from_keyvalues(entry))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            app_id,
+            name: text("name"),
+            kind: text("type").to_ascii_lowercase(),
+            launch,
+        }
+    }
+}
+
+impl LaunchConfig {
+    fn from_keyvalues(entry: &Section) -> Self {
+        let text = |key: &str| entry.string(key).unwrap_or_default().to_string();
+        let os_list = entry
+            .section("config")
+            .and_then(|config| config.string("oslist"))
+            .map(|list| {
+                list.split(',')
+                    .map(str::trim)
+                    .filter(|os| !os.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            executable: text("executable"),
+            arguments: text("arguments"),
+            description: text("description"),
+            working_dir: text("workingdir"),
+            os_list,
+        }
+    }
+}
+
+/// Reads the apps `wanted` accepts out of the Steam install's appinfo.vdf.
+/// No file (a client that never ran) reads as no apps.
+pub fn read_app_info(
+    steam_path: &Path,
+    wanted: impl Fn(u32) -> bool,
+) -> Result<Vec<AppInfo>, AppError> {
+    let path = steam_path.join("appcache").join("appinfo.vdf");
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(AppError::FileRead(format!(
+                "Could not read {}: {e}",
+                path.display()
+            )))
+        }
+    };
+    read_records(BufReader::new(file), wanted)
+        .map_err(|e| AppError::FileRead(format!("{}: {e}", path.display())))
+}
+
+pub fn parse_app_info(
+    data: &[u8],
+    wanted: impl Fn(u32) -> bool,
+) -> Result<Vec<AppInfo>, ReadError> {
+    read_records(Cursor::new(data), wanted)
+}
+
+fn read_records<R: Read + Seek>(
+    input: R,
+    wanted: impl Fn(u32) -> bool,
+) -> Result<Vec<AppInfo>, ReadError> {
+    let mut input = Input {
+        inner: input,
+        pos: 0,
+    };
+    let magic = input.u32()?;
+    let (record_hashes, has_table) = match magic {
+        MAGIC_V27 => (1, false),
+        MAGIC_V28 => (2, false),
+        MAGIC_V29 => (2, true),
+        other => {
+            return Err(ReadError {
+                offset: 0,
+                message: format!("unsupported appinfo.vdf version {other:#010x}"),
+            })
+        }
+    };
+    let _universe = input.u32()?;
+
+    let table = if has_table {
+        let offset = input.u64()?;
+        Some(input.string_table(offset)?)
+    } else {
+        None
+    };
+
+    let mut apps = Vec::new();
+    loop {
+        let app_id = input.u32()?;
+        if app_id == 0 {
+            break;
+        }
+        let size = input.u32()? as usize;
+        if !wanted(app_id) {
+            input.skip(size)?;
+            continue;
+        }
+        let start = input.pos;
+        let record = input.bytes(size)?;
+        let mut record_reader = Reader::new(&record, table.as_deref());
+        // info state, last updated, PICS token, text hash, change number,
+        // and from v28 on a hash of the binary data.
+        record_reader.bytes(4 + 4 + 8 + 20 * record_hashes + 4)?;
+        let keyvalues = record_reader.section(0).map_err(|e| ReadError {
+            offset: start + e.offset,
+            message: format!("app {app_id}: {}", e.message),
+        })?;
+        apps.push(AppInfo::from_keyvalues(app_id, &keyvalues));
+    }
+    Ok(apps)
+}
+
+/// The file being streamed, and how far into it the reading is, for errors.
+struct Input<R> {
+    inner: R,
+    pos: usize,
+}
+
+impl<R: Read + Seek> Input<R> {
+    fn error(&self, message: impl Into<String>) -> ReadError {
+        ReadError {
+            offset: self.pos,
+            message: message.into(),
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<Vec<u8>, ReadError> {
+        let mut buf = Vec::new();
+        (&mut self.inner)
+            .take(len as u64)
+            .read_to_end(&mut buf)
+            .map_err(|e| self.error(e.to_string()))?;
+        if buf.len() < len {
+            return Err(self.error(format!("needs {len} more bytes than the file has")));
+        }
+        self.pos += len;
+        Ok(buf)
+    }
+
+    fn u32(&mut self) -> Result<u32, ReadError> {
+        let mut array = [0; 4];
+        array.copy_from_slice(&self.bytes(4)?);
+        Ok(u32::from_le_bytes(array))
+    }
+
+    fn u64(&mut self) -> Result<u64, ReadError> {
+        let mut array = [0; 8];
+        array.copy_from_slice(&self.bytes(8)?);
+        Ok(u64::from_le_bytes(array))
+    }
+
+    /// Moves past `len` bytes without reading them. Running past the end is
+    /// noticed by the next read.
+    fn skip(&mut self, len: usize) -> Result<(), ReadError> {
+        let offset = i64::try_from(len).map_err(|_| self.error("a record is too large"))?;
+        self.inner
+            .seek_relative(offset)
+            .map_err(|e| self.error(e.to_string()))?;
+        self.pos += len;
+        Ok(())
+    }
+
+    /// The v29 key table at `offset`: a count, then that many NUL-terminated
+    /// strings. Reading it leaves the stream where it was.
+    fn string_table(&mut self, offset: u64) -> Result<Vec<String>, ReadError> {
+        let here = self.pos;
+        let len = self
+            .inner
+            .seek(SeekFrom::End(0))
+            .map_err(|e| self.error(e.to_string()))?;
+        if offset > len {
+            return Err(self.error("the string table is past the end of the file"));
+        }
+        self.inner
+            .seek(SeekFrom::Start(offset))
+            .map_err(|e| self.error(e.to_string()))?;
+        let mut data = Vec::new();
+        self.inner
+            .read_to_end(&mut data)
+            .map_err(|e| self.error(e.to_string()))?;
+        self.inner
+            .seek(SeekFrom::Start(here as u64))
+            .map_err(|e| self.error(e.to_string()))?;
+
+        let offset = offset as usize;
+        let at_table = |e: ReadError| ReadError {
+            offset: offset + e.offset,
+            message: e.message,
+        };
+        let mut reader = Reader::new(&data, None);
+        let count = reader.u32().map_err(at_table)? as usize;
+        // Each string takes at least its terminator, which bounds a bogus count.
+        if count > data.len() - reader.pos {
+            return Err(at_table(
+                reader.error(format!("the string table claims {count} strings")),
+            ));
+        }
+        (0..count)
+            .map(|_| reader.c_string().map_err(at_table))
+            .collect()
+    }
+}
+
+/// The names appinfo.vdf gives the apps `wanted` accepts, by app id. Apps it
+/// has no name for are left out.
+pub(crate) fn app_names(
+    steam_path: &Path,
+    wanted: impl Fn(u32) -> bool,
+) -> Result<Vec<(u32, String)>, AppError> {
+    Ok(read_app_info(steam_path, wanted)?
+        .into_iter()
+        .filter(|app| !app.name.trim().is_empty())
+        .map(|app| (app.app_id, app.name))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platforms::steam::binary_vdf::tests::Writer;
+    use std::fs;
+
+    fn app_keyvalues(w: &mut Writer, app_id: i32, name: &str, kind: &str) {
+        w.open("appinfo")
+            .int("appid", app_id)
+            .open("common")
+            .string("name", name)
+            .string("type", kind)
+            .close()
+            .open("config")
+            .open("launch")
+            .open("0")
+            .string("executable", "game.exe")
+            .string("arguments", "-novid")
+            .string("description", "Play")
+            .open("config")
+            .string("oslist", "windows, linux")
+            .close()
+            .close()
+            .open("1")
+            .string("executable", "tool.sh")
+            .close()
+            .close()
+            .close()
+            .close()
+            .close();
+    }
+
+    /// A synthetic appinfo.vdf in the layout of `magic`.
+    fn appinfo(magic: u32, apps: &[(u32, &str, &str)]) -> Vec<u8> {
+        let hashes = if magic == MAGIC_V27 { 1 } else { 2 };
+        let mut table = (magic == MAGIC_V29).then(Vec::new);
+        let mut body = Vec::new();
+        for (app_id, name, kind) in apps {
+            let mut w = Writer {
+                table: table.take(),
+                ..Writer::default()
+            };
+            app_keyvalues(&mut w, *app_id as i32, name, kind);
+            table = w.table.take();
+            let mut record = vec![0u8; 4 + 4 + 8 + 20 * hashes + 4];
+            record.extend(w.bytes);
+            body.extend(app_id.to_le_bytes());
+            body.extend((record.len() as u32).to_le_bytes());
+            body.extend(record);
+        }
+        body.extend(0u32.to_le_bytes());
+
+        let mut file = Vec::new();
+        file.extend(magic.to_le_bytes());
+        file.extend(1u32.to_le_bytes());
+        if let Some(table) = table {
+            let offset = file.len() + 8 + body.len();
+            file.extend((offset as u64).to_le_bytes());
+            file.extend(body);
+            file.extend((table.len() as u32).to_le_bytes());
+            for key in table {
+                file.extend(key.as_bytes());
+                file.push(0);
+            }
+        } else {
+            file.extend(body);
+        }
+        file
+    }
+
+    #[test]
+    fn every_format_version_reads_to_the_same_records() {
+        let apps = [
+            (730, "Counter-Strike 2", "Game"),
+            (228980, "Steamworks Common Redistributables", "Config"),
+        ];
+        for magic in [MAGIC_V27, MAGIC_V28, MAGIC_V29] {
+            let parsed = parse_app_info(&appinfo(magic, &apps), |_| true).unwrap();
+            assert_eq!(parsed.len(), 2, "{magic:#x}");
+            assert_eq!(
+                parsed[0],
+                AppInfo {
+                    app_id: 730,
+                    name: "Counter-Strike 2".into(),
+                    kind: "game".into(),
+                    launch: vec![
+                        LaunchConfig {
+                            executable: "game.exe".into(),
+                            arguments: "-novid".into(),
+                            description: "Play".into(),
+                            working_dir: String::new(),
+                            os_list: vec!["windows".into(), "linux".into()],
+                        },
+                        LaunchConfig {
+                            executable: "tool.sh".into(),
+                            arguments: String::new(),
+                            description: String::new(),
+                            working_dir: String::new(),
+                            os_list: Vec::new(),
+                        },
+                    ],
+                },
+                "{magic:#x}"
+            );
+            assert_eq!(parsed[1].kind, "config");
+        }
+    }
+
+    #[test]
+    fn unwanted_records_are_skipped_unread() {
+        let mut data = appinfo(
+            MAGIC_V28,
+            &[
+                (10, "Counter-Strike", "Game"),
+                (20, "Day of Defeat", "Game"),
+            ],
+        );
+        // Garble the first record's KeyValues: skipping it must not notice.
+        let garbled = 8 + 8 + 60;
+        data[garbled] = 0x7f;
+        let parsed = parse_app_info(&data, |id| id == 20).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].name, "Day of Defeat");
+        // Asked for, the garbled record is refused with where it broke.
+        let error = parse_app_info(&data, |_| true).unwrap_err();
+        assert!(error.message.starts_with("app 10:"), "{error}");
+    }
+
+    #[test]
+    fn unknown_versions_and_truncated_files_are_refused() {
+        let error = parse_app_info(&0x0756_4426u32.to_le_bytes(), |_| true).unwrap_err();
+        assert!(error.message.contains("unsupported"), "{error}");
+
+        let whole = appinfo(MAGIC_V29, &[(10, "Counter-Strike", "Game")]);
+        for cut in [6, 20, whole.len() - 3] {
+            assert!(
+                parse_app_info(&whole[..cut], |_| true).is_err(),
+                "cut at {cut}"
+            );
+        }
+        // A string table offset pointing past the file.
+        let mut bad = whole.clone();
+        bad[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(parse_app_info(&bad, |_| true).is_err());
+    }
+
+    #[test]
+    fn a_missing_file_reads_as_no_apps() {
+        let root = std::env::temp_dir().join(format!("accshift-appinfo-{}", std::process::id()));
+        assert!(read_app_info(&root, |_| true).unwrap().is_empty());
+
+        let cache = root.join("appcache");
+        fs::create_dir_all(&cache).unwrap();
+        fs::write(
+            cache.join("appinfo.vdf"),
+            appinfo(
+                MAGIC_V29,
+                &[(10, "Counter-Strike", "Game"), (20, " ", "Game")],
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            app_names(&root, |_| true).unwrap(),
+            [(10, "Counter-Strike".to_string())]
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,564 @@
+//! Valve's binary KeyValues, the format of `appcache/appinfo.vdf` and of each
+//! account's `config/shortcuts.vdf`.
+//!
+//! Every node is a type byte, a key and a value. The key is a NUL-terminated
+//! string, or, in appinfo.vdf from version 29 on, an index into a string table
+//! kept at the end of the file. A section runs until an end byte. Keys
+//! compare without regard to ASCII case, as in the text format.
+
+use std::fmt;
+
+const TYPE_SECTION: u8 = 0x00;
+const TYPE_STRING: u8 = 0x01;
+const TYPE_INT32: u8 = 0x02;
+const TYPE_FLOAT32: u8 = 0x03;
+const TYPE_POINTER: u8 = 0x04;
+const TYPE_WIDE_STRING: u8 = 0x05;
+const TYPE_COLOR: u8 = 0x06;
+const TYPE_UINT64: u8 = 0x07;
+const TYPE_END: u8 = 0x08;
+const TYPE_INT64: u8 = 0x0a;
+// Some writers close a section with this instead of TYPE_END.
+const TYPE_END_ALT: u8 = 0x0b;
+
+/// Deeper than anything Steam writes; a corrupt file cannot recurse further.
+const MAX_DEPTH: usize = 64;
+
+/// A file that is not binary KeyValues, located by byte offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "byte {}: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Section(Section),
+    String(String),
+    Int32(i32),
+    Float32(f32),
+    Pointer(u32),
+    WideString(String),
+    Color(u32),
+    UInt64(u64),
+    Int64(i64),
+}
+
+impl Value {
+    /// The value as text, for either string kind.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(text) | Value::WideString(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The value as an integer, for any integer kind. Steam is not consistent
+    /// about which it writes: an appid is an int32 in shortcuts.vdf and
+    /// sometimes a string in appinfo.vdf, so a string of digits counts too.
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int32(n) => Some(i64::from(*n)),
+            Value::Pointer(n) | Value::Color(n) => Some(i64::from(*n)),
+            Value::UInt64(n) => i64::try_from(*n).ok(),
+            Value::Int64(n) => Some(*n),
+            Value::String(text) => text.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_section(&self) -> Option<&Section> {
+        match self {
+            Value::Section(section) => Some(section),
+            _ => None,
+        }
+    }
+}
+
+/// The nodes of a section, in file order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Section {
+    entries: Vec<(String, Value)>,
+}
+
+impl Section {
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// The first node named `key`.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    pub fn string(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Value::as_str)
+    }
+
+    pub fn int(&self, key: &str) -> Option<i64> {
+        self.get(key).and_then(Value::as_int)
+    }
+
+    pub fn section(&self, key: &str) -> Option<&Section> {
+        self.get(key).and_then(Value::as_section)
+    }
+
+    /// The node at `path` below this section.
+    pub fn lookup(&self, path: &[&str]) -> Option<&Value> {
+        let (last, sections) = path.split_last()?;
+        let mut section = self;
+        for name in sections {
+            section = section.section(name)?;
+        }
+        section.get(last)
+    }
+
+    /// Each subsection with its name, in file order.
+    pub fn sections(&self) -> impl Iterator<Item = (&str, &Section)> {
+        self.entries
+            .iter()
+            .filter_map(|(key, value)| value.as_section().map(|section| (key.as_str(), section)))
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.entries
+            .iter_mut()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    pub fn section_mut(&mut self, key: &str) -> Option<&mut Section> {
+        match self.get_mut(key) {
+            Some(Value::Section(section)) => Some(section),
+            _ => None,
+        }
+    }
+
+    /// Replaces the first node named `key`, keeping its place and spelling,
+    /// or appends one.
+    pub fn set(&mut self, key: &str, value: Value) {
+        match self.get_mut(key) {
+            Some(slot) => *slot = value,
+            None => self.entries.push((key.to_string(), value)),
+        }
+    }
+
+    /// Removes the first node named `key`.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let index = self
+            .entries
+            .iter()
+            .position(|(name, _)| name.eq_ignore_ascii_case(key))?;
+        Some(self.entries.remove(index).1)
+    }
+}
+
+/// Writes `root` the way Steam writes shortcuts.vdf: its nodes, then an end
+/// byte closing the file. Reading the result gives `root` back.
+pub fn write(root: &Section) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_section(root, &mut out);
+    out
+}
+
+fn write_section(section: &Section, out: &mut Vec<u8>) {
+    for (key, value) in &section.entries {
+        let kind = match value {
+            Value::Section(_) => TYPE_SECTION,
+            Value::String(_) => TYPE_STRING,
+            Value::Int32(_) => TYPE_INT32,
+            Value::Float32(_) => TYPE_FLOAT32,
+            Value::Pointer(_) => TYPE_POINTER,
+            Value::WideString(_) => TYPE_WIDE_STRING,
+            Value::Color(_) => TYPE_COLOR,
+            Value::UInt64(_) => TYPE_UINT64,
+            Value::Int64(_) => TYPE_INT64,
+        };
+        out.push(kind);
+        write_c_string(key, out);
+        match value {
+            Value::Section(inner) => write_section(inner, out),
+            Value::String(text) => write_c_string(text, out),
+            Value::Int32(n) => out.extend(n.to_le_bytes()),
+            Value::Float32(n) => out.extend(n.to_le_bytes()),
+            Value::Pointer(n) | Value::Color(n) => out.extend(n.to_le_bytes()),
+            Value::WideString(text) => {
+                for unit in text.encode_utf16().filter(|unit| *unit != 0).chain([0]) {
+                    out.extend(unit.to_le_bytes());
+                }
+            }
+            Value::UInt64(n) => out.extend(n.to_le_bytes()),
+            Value::Int64(n) => out.extend(n.to_le_bytes()),
+        }
+    }
+    out.push(TYPE_END);
+}
+
+/// A NUL inside the text would end it early on the next read, so it is
+/// dropped rather than written.
+fn write_c_string(text: &str, out: &mut Vec<u8>) {
+    out.extend(text.bytes().filter(|byte| *byte != 0));
+    out.push(0);
+}
+
+/// Parses a whole binary KeyValues file, such as shortcuts.vdf. The file's
+/// own top-level nodes come back as the returned section's entries.
+pub fn parse(data: &[u8]) -> Result<Section, ReadError> {
+    let mut reader = Reader::new(data, None);
+    reader.section(0)
+}
+
+/// A cursor over bytes that reports where it stopped making sense.
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pub(crate) pos: usize,
+    keys: Option<&'a [String]>,
+}
+
+impl<'a> Reader<'a> {
+    /// With `keys`, a node's key is a `u32` index into that table rather than
+    /// a string of its own.
+    pub(crate) fn new(data: &'a [u8], keys: Option<&'a [String]>) -> Self {
+        Self { data, pos: 0, keys }
+    }
+
+    pub(crate) fn error(&self, message: impl Into<String>) -> ReadError {
+        ReadError {
+            offset: self.pos,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn at_end(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8], ReadError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| self.error(format!("needs {len} more bytes than the file has")))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReadError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, ReadError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, ReadError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, ReadError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    /// A NUL-terminated string. Steam writes UTF-8, but a name from before it
+    /// did must not make the whole file unreadable.
+    pub(crate) fn c_string(&mut self) -> Result<String, ReadError> {
+        let rest = &self.data[self.pos.min(self.data.len())..];
+        let len = rest
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or_else(|| self.error("a string is never terminated"))?;
+        let text = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.pos += len + 1;
+        Ok(text)
+    }
+
+    fn wide_string(&mut self) -> Result<String, ReadError> {
+        let mut units = Vec::new();
+        loop {
+            let unit = u16::from_le_bytes(self.array()?);
+            if unit == 0 {
+                break;
+            }
+            units.push(unit);
+        }
+        Ok(String::from_utf16_lossy(&units))
+    }
+
+    fn key(&mut self) -> Result<String, ReadError> {
+        match self.keys {
+            None => self.c_string(),
+            Some(keys) => {
+                let at = self.pos;
+                let index = self.u32()? as usize;
+                keys.get(index).cloned().ok_or(ReadError {
+                    offset: at,
+                    message: format!("key {index} is past the string table"),
+                })
+            }
+        }
+    }
+
+    /// Nodes up to the end byte of a section. At the top of a file the end of
+    /// the data closes it as well.
+    pub(crate) fn section(&mut self, depth: usize) -> Result<Section, ReadError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("sections nest deeper than any Steam file"));
+        }
+        let mut section = Section::default();
+        loop {
+            if depth == 0 && self.at_end() {
+                return Ok(section);
+            }
+            let at = self.pos;
+            let kind = self.u8()?;
+            if kind == TYPE_END || kind == TYPE_END_ALT {
+                return Ok(section);
+            }
+            let key = self.key()?;
+            let value = match kind {
+                TYPE_SECTION => Value::Section(self.section(depth + 1)?),
+                TYPE_STRING => Value::String(self.c_string()?),
+                TYPE_INT32 => Value::Int32(i32::from_le_bytes(self.array()?)),
+                TYPE_FLOAT32 => Value::Float32(f32::from_le_bytes(self.array()?)),
+                TYPE_POINTER => Value::Pointer(self.u32()?),
+                TYPE_WIDE_STRING => Value::WideString(self.wide_string()?),
+                TYPE_COLOR => Value::Color(self.u32()?),
+                TYPE_UINT64 => Value::UInt64(self.u64()?),
+                TYPE_INT64 => Value::Int64(i64::from_le_bytes(self.array()?)),
+                other => {
+                    return Err(ReadError {
+                        offset: at,
+                        message: format!("unknown node type {other:#04x}"),
+                    })
+                }
+            };
+            section.entries.push((key, value));
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Builds binary KeyValues by hand, the way Steam lays them out.
+    #[derive(Default)]
+    pub(crate) struct Writer {
+        pub(crate) bytes: Vec<u8>,
+        /// When set, keys are written as indices into this table, as
+        /// appinfo.vdf v29 does.
+        pub(crate) table: Option<Vec<String>>,
+    }
+
+    impl Writer {
+        fn key(&mut self, kind: u8, key: &str) -> &mut Self {
+            self.bytes.push(kind);
+            match &mut self.table {
+                Some(table) => {
+                    let index = table.iter().position(|k| k == key).unwrap_or_else(|| {
+                        table.push(key.to_string());
+                        table.len() - 1
+                    });
+                    self.bytes.extend((index as u32).to_le_bytes());
+                }
+                None => {
+                    self.bytes.extend(key.as_bytes());
+                    self.bytes.push(0);
+                }
+            }
+            self
+        }
+
+        pub(crate) fn open(&mut self, key: &str) -> &mut Self {
+            self.key(TYPE_SECTION, key)
+        }
+
+        pub(crate) fn close(&mut self) -> &mut Self {
+            self.bytes.push(TYPE_END);
+            self
+        }
+
+        pub(crate) fn string(&mut self, key: &str, value: &str) -> &mut Self {
+            self.key(TYPE_STRING, key);
+            self.bytes.extend(value.as_bytes());
+            self.bytes.push(0);
+            self
+        }
+
+        pub(crate) fn int(&mut self, key: &str, value: i32) -> &mut Self {
+            self.key(TYPE_INT32, key);
+            self.bytes.extend(value.to_le_bytes());
+            self
+        }
+
+        pub(crate) fn uint64(&mut self, key: &str, value: u64) -> &mut Self {
+            self.key(TYPE_UINT64, key);
+            self.bytes.extend(value.to_le_bytes());
+            self
+        }
+
+        pub(crate) fn wide(&mut self, key: &str, value: &str) -> &mut Self {
+            self.key(TYPE_WIDE_STRING, key);
+            for unit in value.encode_utf16().chain([0]) {
+                self.bytes.extend(unit.to_le_bytes());
+            }
+            self
+        }
+    }
+
+    #[test]
+    fn every_node_type_reads_back() {
+        let mut w = Writer::default();
+        w.open("root")
+            .string("name", "Half-Life")
+            .int("appid", -70)
+            .uint64("gameid", 1 << 40)
+            .wide("title", "Élan ✓")
+            .close();
+        w.key(TYPE_FLOAT32, "scale")
+            .bytes
+            .extend(1.5f32.to_le_bytes());
+        w.key(TYPE_COLOR, "tint")
+            .bytes
+            .extend(0xff00ffu32.to_le_bytes());
+        w.key(TYPE_INT64, "big").bytes.extend((-5i64).to_le_bytes());
+        w.key(TYPE_POINTER, "ptr").bytes.extend(9u32.to_le_bytes());
+        w.close();
+
+        let parsed = parse(&w.bytes).unwrap();
+        let root = parsed.section("ROOT").unwrap();
+        assert_eq!(root.string("Name"), Some("Half-Life"));
+        assert_eq!(root.int("appid"), Some(-70));
+        assert_eq!(root.int("gameid"), Some(1 << 40));
+        assert_eq!(root.string("title"), Some("Élan ✓"));
+        assert_eq!(parsed.get("scale"), Some(&Value::Float32(1.5)));
+        assert_eq!(parsed.int("tint"), Some(0xff00ff));
+        assert_eq!(parsed.int("big"), Some(-5));
+        assert_eq!(parsed.int("ptr"), Some(9));
+        let keys: Vec<&str> = parsed.entries().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["root", "scale", "tint", "big", "ptr"]);
+    }
+
+    #[test]
+    fn a_written_file_reads_back_and_a_steam_file_writes_back_unchanged() {
+        let mut w = Writer::default();
+        w.open("shortcuts")
+            .open("0")
+            .int("appid", -123)
+            .string("AppName", "Game")
+            .wide("w", "wide")
+            .uint64("u", 7)
+            .open("tags")
+            .string("0", "a")
+            .close()
+            .close()
+            .close()
+            .close();
+        let steam = w.bytes;
+        let parsed = parse(&steam).unwrap();
+        assert_eq!(write(&parsed), steam);
+
+        let mut edited = parsed.clone();
+        let entry = edited
+            .section_mut("shortcuts")
+            .and_then(|shortcuts| shortcuts.section_mut("0"))
+            .unwrap();
+        entry.set("APPNAME", Value::String("Renamed".into()));
+        entry.set("LaunchOptions", Value::String("-x".into()));
+        assert!(entry.remove("u").is_some());
+        assert!(entry.remove("u").is_none());
+        let reread = parse(&write(&edited)).unwrap();
+        assert_eq!(reread, edited);
+        let keys: Vec<&str> = reread
+            .lookup(&["shortcuts", "0"])
+            .and_then(Value::as_section)
+            .unwrap()
+            .entries()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(keys, ["appid", "AppName", "w", "tags", "LaunchOptions"]);
+    }
+
+    #[test]
+    fn the_alternate_end_byte_closes_a_section() {
+        let mut w = Writer::default();
+        w.open("a").string("b", "c");
+        w.bytes.push(TYPE_END_ALT);
+        w.string("d", "e");
+        let parsed = parse(&w.bytes).unwrap();
+        assert_eq!(
+            parsed.lookup(&["a", "b"]).and_then(Value::as_str),
+            Some("c")
+        );
+        assert_eq!(parsed.string("d"), Some("e"));
+    }
+
+    #[test]
+    fn truncated_or_garbled_files_are_refused_with_where() {
+        let mut w = Writer::default();
+        w.open("a").string("b", "c").close();
+        let whole = w.bytes.clone();
+
+        // Cut inside the nested section: its end byte never comes.
+        let error = parse(&whole[..whole.len() - 1]).unwrap_err();
+        assert_eq!(error.offset, whole.len() - 1);
+        // Cut inside a string.
+        assert!(parse(&whole[..6])
+            .unwrap_err()
+            .message
+            .contains("never terminated"));
+        // A type byte nothing writes.
+        let error = parse(&[0x00, b'a', 0, 0x09, b'b', 0]).unwrap_err();
+        assert_eq!(
+            (error.offset, error.message.as_str()),
+            (3, "unknown node type 0x09")
+        );
+        // Sections nested past any real file.
+        let deep: Vec<u8> = std::iter::repeat_n([TYPE_SECTION, b'a', 0], MAX_DEPTH + 2)
+            .flatten()
+            .collect();
+        assert!(parse(&deep).unwrap_err().message.contains("deeper"));
+    }
+
+    #[test]
+    fn fuzzed_bytes_never_panic() {
+        let mut w = Writer::default();
+        w.open("shortcuts")
+            .open("0")
+            .int("appid", 7)
+            .string("AppName", "x")
+            .wide("w", "y")
+            .uint64("u", 1)
+            .close()
+            .close()
+            .close();
+        let seed_bytes = w.bytes;
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        for _ in 0..5000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let mut bytes = seed_bytes.clone();
+            let at = (state % bytes.len() as u64) as usize;
+            bytes[at] = (state >> 32) as u8;
+            bytes.truncate(bytes.len() - (state >> 48) as usize % 4);
+            let _ = parse(&bytes);
+        }
+    }
+}
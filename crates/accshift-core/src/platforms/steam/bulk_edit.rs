@@ -1,13 +1,95 @@
+//! Bulk edits to the per-account Steam config files.
+//!
+//! An edit names a key in an account's `localconfig.vdf` or
+//! `sharedconfig.vdf` and either sets it or deletes it. A preview works every
+//! edit out in memory and reports each key's value before and after. Applying
+//! writes an account's edits only when every one of them can be made, and
+//! keeps a copy of every file it rewrites first, so the whole batch can be
+//! undone with [`revert_bulk_edit`].
+
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use super::accounts::{
     list_account_games, load_app_names, steam_user_data_path, CopyableGame, NON_GAME_APP_IDS,
 };
-use super::vdf::vdf_set_nested_value;
+use super::keyvalues::Document;
+use super::vdf::{read_document, write_document};
 use crate::error::AppError;
 
+const MANIFEST: &str = "manifest.json";
+/// Backups older than the last few batches are pruned.
+const KEPT_BACKUPS: usize = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigFile {
+    /// `config/localconfig.vdf`: settings kept on this machine.
+    #[default]
+    Localconfig,
+    /// `7/remote/sharedconfig.vdf`: settings Steam Cloud syncs, such as the
+    /// library's tags and hidden games.
+    Sharedconfig,
+}
+
+impl ConfigFile {
+    fn path(self, userdata: &Path) -> PathBuf {
+        match self {
+            Self::Localconfig => userdata.join("config").join("localconfig.vdf"),
+            Self::Sharedconfig => userdata.join("7").join("remote").join("sharedconfig.vdf"),
+        }
+    }
+
+    /// The file's single top-level key. Edit paths start below it.
+    fn root(self) -> &'static str {
+        match self {
+            Self::Localconfig => "UserLocalConfigStore",
+            Self::Sharedconfig => "UserRoamingConfigStore",
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Localconfig => "localconfig.vdf",
+            Self::Sharedconfig => "sharedconfig.vdf",
+        }
+    }
+}
+
+/// One change to one key. `path` starts below the file's root key, e.g.
+/// `["friends", "DoNotDisturb"]`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum VdfEdit {
+    Set {
+        #[serde(default)]
+        file: ConfigFile,
+        path: Vec<String>,
+        value: String,
+    },
+    Delete {
+        #[serde(default)]
+        file: ConfigFile,
+        path: Vec<String>,
+    },
+}
+
+impl VdfEdit {
+    fn file(&self) -> ConfigFile {
+        match self {
+            Self::Set { file, .. } | Self::Delete { file, .. } => *file,
+        }
+    }
+
+    fn path(&self) -> &[String] {
+        match self {
+            Self::Set { path, .. } | Self::Delete { path, .. } => path,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LaunchOptionEdit {
@@ -21,7 +103,80 @@ pub struct BulkEditRequest {
     pub steam_ids: Vec<String>,
     pub news_popup: Option<bool>,
     pub do_not_disturb: Option<bool>,
+    #[serde(default)]
     pub launch_options: Vec<LaunchOptionEdit>,
+    /// Any other keys, applied after the fields above.
+    #[serde(default)]
+    pub edits: Vec<VdfEdit>,
+}
+
+impl BulkEditRequest {
+    /// Every change the request makes, the fixed fields spelled out as edits.
+    fn all_edits(&self) -> Vec<VdfEdit> {
+        let set = |path: &[&str], value: &str| VdfEdit::Set {
+            file: ConfigFile::Localconfig,
+            path: path.iter().map(|key| key.to_string()).collect(),
+            value: value.to_string(),
+        };
+        let flag = |on: bool| if on { "1" } else { "0" };
+        let mut edits = Vec::new();
+        if let Some(news) = self.news_popup {
+            edits.push(set(&["news", "NotifyAvailableGames"], flag(news)));
+        }
+        if let Some(dnd) = self.do_not_disturb {
+            edits.push(set(&["friends", "DoNotDisturb"], flag(dnd)));
+        }
+        for edit in &self.launch_options {
+            if edit.app_id.is_empty() || !edit.app_id.chars().all(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            edits.push(set(
+                &[
+                    "Software",
+                    "Valve",
+                    "Steam",
+                    "apps",
+                    &edit.app_id,
+                    "LaunchOptions",
+                ],
+                &edit.value,
+            ));
+        }
+        edits.extend(self.edits.iter().cloned());
+        edits
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyOutcome {
+    Changed,
+    /// The key already held the value, or a deleted key was not there.
+    Unchanged,
+    Failed,
+}
+
+/// What one edit did, or would do, to one account.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyEditResult {
+    pub file: ConfigFile,
+    pub path: Vec<String>,
+    /// `None` when the key does not exist.
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub outcome: KeyOutcome,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountEditResult {
+    pub steam_id: String,
+    pub keys: Vec<KeyEditResult>,
+    /// Set when the account's files could not be read or written, or an edit
+    /// failed. Either way none of the account's files were changed.
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -36,78 +191,496 @@ pub struct BulkEditFailure {
 pub struct BulkEditResult {
     pub succeeded: u32,
     pub failed: Vec<BulkEditFailure>,
+    pub accounts: Vec<AccountEditResult>,
+    /// Undoes the batch when passed to [`revert_bulk_edit`]. `None` for a
+    /// preview, or when nothing was written.
+    pub backup_id: Option<String>,
 }
 
-pub fn apply_bulk_edit(steam_path: &Path, request: &BulkEditRequest) -> BulkEditResult {
-    let mut succeeded: u32 = 0;
-    let mut failed: Vec<BulkEditFailure> = Vec::new();
+/// A key a revert left alone: something has changed it since the batch, so
+/// putting back what it held before would undo that instead.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertConflict {
+    pub steam_id: String,
+    pub file: ConfigFile,
+    pub path: Vec<String>,
+    /// What the key holds now; `None` when it was deleted.
+    pub current: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertResult {
+    pub restored: u32,
+    pub failed: Vec<BulkEditFailure>,
+    pub conflicts: Vec<RevertConflict>,
+}
+
+/// Applies the request to every account, keeping a copy of each file it
+/// rewrites under `backups`.
+pub fn apply_bulk_edit(
+    steam_path: &Path,
+    request: &BulkEditRequest,
+    backups: &Path,
+) -> BulkEditResult {
+    let mut backup = Backup::new(backups);
+    let result = run(steam_path, request, Some(&mut backup));
+    prune_backups(backups);
+    result
+}
+
+/// What the request would do to every account, writing nothing.
+pub fn preview_bulk_edit(steam_path: &Path, request: &BulkEditRequest) -> BulkEditResult {
+    run(steam_path, request, None)
+}
+
+fn run(
+    steam_path: &Path,
+    request: &BulkEditRequest,
+    mut backup: Option<&mut Backup>,
+) -> BulkEditResult {
+    let edits = request.all_edits();
+    let mut result = BulkEditResult {
+        succeeded: 0,
+        failed: Vec::new(),
+        accounts: Vec::new(),
+        backup_id: None,
+    };
 
     for steam_id in &request.steam_ids {
-        match apply_for_account(steam_path, steam_id, request) {
-            Ok(()) => succeeded += 1,
-            Err(e) => failed.push(BulkEditFailure {
+        // An account's edits land together or not at all: one that cannot be
+        // applied leaves every file of the account as it was.
+        let outcome = plan_account(steam_path, steam_id, &edits).and_then(|plan| {
+            let failed = plan
+                .keys
+                .iter()
+                .filter(|key| key.outcome == KeyOutcome::Failed)
+                .count();
+            if failed > 0 {
+                let error = format!(
+                    "{failed} of {} edits could not be applied, so the account is left as it is",
+                    plan.keys.len()
+                );
+                return Ok((plan.keys, Some(error)));
+            }
+            if let Some(backup) = backup.as_deref_mut() {
+                write_account(backup, steam_id, &plan)?;
+            }
+            Ok((plan.keys, None))
+        });
+        let (keys, error) = match outcome {
+            Ok(outcome) => outcome,
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        match &error {
+            None => result.succeeded += 1,
+            Some(error) => result.failed.push(BulkEditFailure {
                 steam_id: steam_id.clone(),
-                error: e.to_string(),
+                error: error.clone(),
             }),
         }
+        result.accounts.push(AccountEditResult {
+            steam_id: steam_id.clone(),
+            keys,
+            error,
+        });
     }
 
-    BulkEditResult { succeeded, failed }
+    result.backup_id = backup
+        .filter(|backup| !backup.manifest.files.is_empty())
+        .map(|backup| backup.id.clone());
+    result
 }
 
-fn apply_for_account(
+/// Writes one account's edited files, keeping a copy of each first. When a
+/// write fails the files already written are put back, so the account is
+/// never left half edited.
+fn write_account(backup: &mut Backup, steam_id: &str, plan: &AccountPlan) -> Result<(), AppError> {
+    let mut written: Vec<(&Path, &str)> = Vec::new();
+    let mut kept: Vec<(&Path, Kept)> = Vec::new();
+    for (file, path, original, document) in &plan.changed {
+        let step = backup
+            .keep(steam_id, *file, path, original, document, &plan.keys)
+            .and_then(|entry| {
+                kept.push((path, entry));
+                write_document(path, document)
+            });
+        if let Err(e) = step {
+            for (path, original) in written {
+                let _ = crate::storage::write_bytes_atomic(path, original.as_bytes());
+            }
+            backup.take_back(kept);
+            return Err(e);
+        }
+        written.push((path, original));
+    }
+    Ok(())
+}
+
+/// One account's edits, worked out in memory.
+struct AccountPlan {
+    keys: Vec<KeyEditResult>,
+    /// The files the edits change: which, where, their text before, and the
+    /// edited document.
+    changed: Vec<(ConfigFile, PathBuf, String, Document)>,
+}
+
+fn plan_account(
     steam_path: &Path,
     steam_id: &str,
-    request: &BulkEditRequest,
-) -> Result<(), AppError> {
+    edits: &[VdfEdit],
+) -> Result<AccountPlan, AppError> {
     let userdata = steam_user_data_path(steam_path, steam_id)?;
-    let config_path = userdata.join("config").join("localconfig.vdf");
+    let mut files: Vec<(ConfigFile, PathBuf, String, Document)> = Vec::new();
+    let mut keys = Vec::new();
+    for edit in edits {
+        let file = edit.file();
+        let index = match files.iter().position(|(open, ..)| *open == file) {
+            Some(index) => index,
+            None => {
+                let path = file.path(&userdata);
+                let Some(document) = read_document(&path)? else {
+                    return Err(AppError::FileRead(format!(
+                        "{} not found for {}",
+                        file.file_name(),
+                        steam_id
+                    )));
+                };
+                // Documents round-trip byte for byte, so this is the file.
+                files.push((file, path, document.to_string(), document));
+                files.len() - 1
+            }
+        };
+        keys.push(apply_edit(&mut files[index].3, edit));
+    }
+    let changed = files
+        .into_iter()
+        .filter(|(_, _, original, document)| document.to_string() != *original)
+        .collect();
+    Ok(AccountPlan { keys, changed })
+}
 
-    if !config_path.exists() {
+fn apply_edit(document: &mut Document, edit: &VdfEdit) -> KeyEditResult {
+    let file = edit.file();
+    let path: Vec<&str> = std::iter::once(file.root())
+        .chain(edit.path().iter().map(String::as_str))
+        .collect();
+    let before = document.get(&path).map(str::to_string);
+    let applied = if edit.path().is_empty() {
+        Err("an empty path names nothing".to_string())
+    } else {
+        match edit {
+            VdfEdit::Set { value, .. } => document.set(&path, value),
+            VdfEdit::Delete { .. } => match document.root().lookup(&path) {
+                Some(entry) if entry.section().is_some() => Err(format!(
+                    "`{}` is a section, not a value",
+                    edit.path().join("/")
+                )),
+                _ => {
+                    document.remove(&path);
+                    Ok(())
+                }
+            },
+        }
+    };
+    let after = document.get(&path).map(str::to_string);
+    let (outcome, error) = match applied {
+        Ok(()) if before == after => (KeyOutcome::Unchanged, None),
+        Ok(()) => (KeyOutcome::Changed, None),
+        Err(e) => (KeyOutcome::Failed, Some(e)),
+    };
+    KeyEditResult {
+        file,
+        path: edit.path().to_vec(),
+        before,
+        after,
+        outcome,
+        error,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupManifest {
+    /// Unix milliseconds.
+    created_at: u64,
+    files: Vec<BackedUpFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackedUpFile {
+    steam_id: String,
+    file: ConfigFile,
+    /// Where the file lives in the Steam install.
+    path: PathBuf,
+    /// The copy's name in the backup folder.
+    backup: String,
+    /// SHA-256 of the text the batch wrote, to tell whether the file has been
+    /// written since.
+    written_sha256: String,
+    /// The keys the batch changed, in the order it changed them.
+    changes: Vec<KeyChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyChange {
+    path: Vec<String>,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+/// What [`Backup::keep`] recorded for one file, so a write that fails after
+/// it can be taken back.
+enum Kept {
+    /// The file's first copy in the batch.
+    New,
+    /// A file the batch kept already: what its entry said before.
+    Again {
+        written_sha256: String,
+        changes: usize,
+    },
+}
+
+/// The files one batch rewrote, as they were before it.
+struct Backup {
+    id: String,
+    dir: PathBuf,
+    manifest: BackupManifest,
+}
+
+impl Backup {
+    fn new(root: &Path) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        let mut id = created_at.to_string();
+        let mut attempt = 1;
+        while root.join(&id).exists() {
+            id = format!("{created_at}-{attempt}");
+            attempt += 1;
+        }
+        Self {
+            dir: root.join(&id),
+            id,
+            manifest: BackupManifest {
+                created_at,
+                files: Vec::new(),
+            },
+        }
+    }
+
+    /// Keeps `original` before the file at `path` is overwritten with
+    /// `document`, along with what `keys` change in it. The manifest is
+    /// rewritten each time, so a batch that stops partway can still be
+    /// undone.
+    fn keep(
+        &mut self,
+        steam_id: &str,
+        file: ConfigFile,
+        path: &Path,
+        original: &str,
+        document: &Document,
+        keys: &[KeyEditResult],
+    ) -> Result<Kept, AppError> {
+        let written_sha256 = crate::snapshot_manifest::sha256_hex(document.to_string().as_bytes());
+        let changes = keys
+            .iter()
+            .filter(|key| key.file == file && key.outcome == KeyOutcome::Changed)
+            .map(|key| KeyChange {
+                path: key.path.clone(),
+                before: key.before.clone(),
+                after: key.after.clone(),
+            });
+        // An account listed twice keeps its first copy: that is the original.
+        let kept = match self
+            .manifest
+            .files
+            .iter_mut()
+            .find(|kept| kept.path == path)
+        {
+            Some(entry) => {
+                let previous = Kept::Again {
+                    written_sha256: std::mem::replace(&mut entry.written_sha256, written_sha256),
+                    changes: entry.changes.len(),
+                };
+                entry.changes.extend(changes);
+                previous
+            }
+            None => {
+                let backup = format!("{steam_id}-{}", file.file_name());
+                crate::storage::write_bytes_atomic(&self.dir.join(&backup), original.as_bytes())
+                    .map_err(AppError::FileRead)?;
+                self.manifest.files.push(BackedUpFile {
+                    steam_id: steam_id.to_string(),
+                    file,
+                    path: path.to_path_buf(),
+                    backup,
+                    written_sha256,
+                    changes: changes.collect(),
+                });
+                Kept::New
+            }
+        };
+        if let Err(e) = self.write_manifest() {
+            self.take_back(vec![(path, kept)]);
+            return Err(e);
+        }
+        Ok(kept)
+    }
+
+    /// Undoes what [`Self::keep`] recorded for files that were put back after
+    /// all, newest first.
+    fn take_back(&mut self, kept: Vec<(&Path, Kept)>) {
+        for (path, kept) in kept.into_iter().rev() {
+            let Some(index) = self.manifest.files.iter().position(|f| f.path == path) else {
+                continue;
+            };
+            match kept {
+                Kept::New => {
+                    let entry = self.manifest.files.remove(index);
+                    let _ = fs::remove_file(self.dir.join(entry.backup));
+                }
+                Kept::Again {
+                    written_sha256,
+                    changes,
+                } => {
+                    let entry = &mut self.manifest.files[index];
+                    entry.written_sha256 = written_sha256;
+                    entry.changes.truncate(changes);
+                }
+            }
+        }
+        if self.manifest.files.is_empty() {
+            let _ = fs::remove_dir_all(&self.dir);
+        } else {
+            let _ = self.write_manifest();
+        }
+    }
+
+    fn write_manifest(&self) -> Result<(), AppError> {
+        crate::storage::write_json_atomic(&self.dir.join(MANIFEST), &self.manifest)
+            .map_err(AppError::FileRead)
+    }
+}
+
+fn prune_backups(backups: &Path) {
+    let Ok(entries) = fs::read_dir(backups) else {
+        return;
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    // Ids are creation times, so the oldest sort first.
+    dirs.sort_by_key(|dir| {
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        let (millis, attempt) = name.split_once('-').unwrap_or((&name, "0"));
+        (
+            millis.parse::<u64>().unwrap_or_default(),
+            attempt.parse::<u32>().unwrap_or_default(),
+        )
+    });
+    let excess = dirs.len().saturating_sub(KEPT_BACKUPS);
+    for dir in &dirs[..excess] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+/// Puts back every file batch `backup_id` rewrote. A file nothing has written
+/// since gets its copy back as it was; one Steam has rewritten since only has
+/// the batch's keys put back, and a key that no longer holds what the batch
+/// wrote is left alone and reported. The backup is dropped once every file is
+/// restored and kept when any is not, so the revert can be retried.
+pub fn revert_bulk_edit(backups: &Path, backup_id: &str) -> Result<RevertResult, AppError> {
+    if backup_id.is_empty() || !backup_id.chars().all(|c| c.is_ascii_digit() || c == '-') {
         return Err(AppError::FileRead(format!(
-            "localconfig.vdf not found for {}",
-            steam_id
+            "Invalid bulk edit backup id: {backup_id}"
         )));
     }
+    let dir = backups.join(backup_id);
+    let manifest: BackupManifest = crate::storage::read_json_if_exists(&dir.join(MANIFEST))
+        .map_err(AppError::FileRead)?
+        .ok_or_else(|| AppError::FileRead(format!("No bulk edit backup {backup_id}")))?;
 
-    let mut content =
-        fs::read_to_string(&config_path).map_err(|e| AppError::FileRead(e.to_string()))?;
-
-    if let Some(news) = request.news_popup {
-        let val = if news { "1" } else { "0" };
-        content = vdf_set_nested_value(&content, &["news", "NotifyAvailableGames"], val);
+    let mut result = RevertResult {
+        restored: 0,
+        failed: Vec::new(),
+        conflicts: Vec::new(),
+    };
+    for kept in &manifest.files {
+        match restore_file(&dir, kept, &mut result.conflicts) {
+            Ok(()) => result.restored += 1,
+            Err(error) => result.failed.push(BulkEditFailure {
+                steam_id: kept.steam_id.clone(),
+                error,
+            }),
+        }
     }
+    if result.failed.is_empty() {
+        let _ = fs::remove_dir_all(&dir);
+    }
+    Ok(result)
+}
 
-    if let Some(dnd) = request.do_not_disturb {
-        let val = if dnd { "1" } else { "0" };
-        content = vdf_set_nested_value(&content, &["friends", "DoNotDisturb"], val);
+fn restore_file(
+    dir: &Path,
+    kept: &BackedUpFile,
+    conflicts: &mut Vec<RevertConflict>,
+) -> Result<(), String> {
+    let current =
+        fs::read(&kept.path).map_err(|e| format!("Could not read {}: {e}", kept.path.display()))?;
+    let backup = fs::read(dir.join(&kept.backup))
+        .map_err(|e| format!("Could not read the backup of {}: {e}", kept.path.display()))?;
+    if crate::snapshot_manifest::sha256_hex(&current) == kept.written_sha256 {
+        return crate::storage::write_bytes_atomic(&kept.path, &backup);
+    }
+    // Put back by an earlier attempt at this revert that failed elsewhere.
+    if current == backup {
+        return Ok(());
     }
 
-    for edit in &request.launch_options {
-        if edit.app_id.is_empty() || !edit.app_id.chars().all(|c| c.is_ascii_digit()) {
+    let mut document = read_document(&kept.path)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("{} no longer exists", kept.path.display()))?;
+    let mut conflicted: Vec<&[String]> = Vec::new();
+    // Newest first, so a key the batch changed twice ends at its first value.
+    for change in kept.changes.iter().rev() {
+        if conflicted.contains(&change.path.as_slice()) {
             continue;
         }
-        content = vdf_set_nested_value(
-            &content,
-            &[
-                "Software",
-                "Valve",
-                "Steam",
-                "apps",
-                &edit.app_id,
-                "LaunchOptions",
-            ],
-            &edit.value,
-        );
+        let path: Vec<&str> = std::iter::once(kept.file.root())
+            .chain(change.path.iter().map(String::as_str))
+            .collect();
+        let now = document.get(&path);
+        if now != change.after.as_deref() {
+            conflicts.push(RevertConflict {
+                steam_id: kept.steam_id.clone(),
+                file: kept.file,
+                path: change.path.clone(),
+                current: now.map(str::to_string),
+            });
+            conflicted.push(&change.path);
+            continue;
+        }
+        match &change.before {
+            Some(value) => document.set(&path, value)?,
+            None => {
+                document.remove(&path);
+            }
+        }
     }
-
-    crate::storage::write_bytes_atomic(&config_path, content.as_bytes()).map_err(AppError::FileRead)
+    write_document(&kept.path, &document).map_err(|e| e.to_string())
 }
 
 pub fn get_account_games(steam_path: &Path, steam_id: &str) -> Result<Vec<CopyableGame>, AppError> {
     let userdata = steam_user_data_path(steam_path, steam_id)?;
     let game_ids = list_account_games(&userdata)?;
-    let names = load_app_names(steam_path);
+    let names = load_app_names(steam_path, &game_ids);
 
     let mut games: Vec<CopyableGame> = game_ids
         .iter()
@@ -127,6 +700,7 @@ pub fn get_account_games(steam_path: &Path, steam_id: &str) -> Result<Vec<Copyab
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
     use std::path::PathBuf;
 
     // SteamID64 whose low 32 bits (the userdata folder name) is 1.
@@ -158,7 +732,7 @@ mod tests {
     }
 
     #[test]
-    fn apply_for_account_toggles_news_popup_and_do_not_disturb() {
+    fn the_fixed_fields_toggle_news_popup_and_do_not_disturb() {
         let root = bulk_edit_test_root("toggle");
         let config_path = write_localconfig(&root, TEST_STEAM_ID, BASE_CONFIG);
 
@@ -167,10 +741,11 @@ mod tests {
             news_popup: Some(false),
             do_not_disturb: Some(true),
             launch_options: Vec::new(),
+            edits: Vec::new(),
         };
 
-        apply_for_account(&root, TEST_STEAM_ID, &request)
-            .expect("apply_for_account should succeed");
+        let result = apply_bulk_edit(&root, &request, &root.join("backups"));
+        assert_eq!(result.succeeded, 1, "{:?}", result.failed);
 
         let updated = fs::read_to_string(&config_path).unwrap();
         assert!(updated.contains("\"NotifyAvailableGames\"\t\t\"0\""));
@@ -180,7 +755,7 @@ mod tests {
     }
 
     #[test]
-    fn apply_for_account_applies_multiple_launch_options_and_skips_non_numeric_app_id() {
+    fn the_fixed_fields_apply_multiple_launch_options_and_skip_non_numeric_app_id() {
         let root = bulk_edit_test_root("launchopts");
         let config_path = write_localconfig(&root, TEST_STEAM_ID, BASE_CONFIG);
 
@@ -202,10 +777,12 @@ mod tests {
                     value: "-should-be-skipped".to_string(),
                 },
             ],
+            edits: Vec::new(),
         };
 
-        apply_for_account(&root, TEST_STEAM_ID, &request)
-            .expect("apply_for_account should succeed");
+        let result = apply_bulk_edit(&root, &request, &root.join("backups"));
+        assert_eq!(result.succeeded, 1, "{:?}", result.failed);
+        assert_eq!(result.accounts[0].keys.len(), 2);
 
         let updated = fs::read_to_string(&config_path).unwrap();
         assert!(updated.contains("\"LaunchOptions\"\t\t\"-novid -fullscreen\""));
@@ -217,7 +794,7 @@ mod tests {
     }
 
     #[test]
-    fn apply_for_account_errors_when_localconfig_is_missing() {
+    fn an_account_without_localconfig_is_an_error() {
         let root = bulk_edit_test_root("missing");
 
         let request = BulkEditRequest {
@@ -225,10 +802,12 @@ mod tests {
             news_popup: Some(true),
             do_not_disturb: None,
             launch_options: Vec::new(),
+            edits: Vec::new(),
         };
 
-        let err = apply_for_account(&root, TEST_STEAM_ID, &request)
-            .expect_err("missing localconfig.vdf must be an error");
+        let err = plan_account(&root, TEST_STEAM_ID, &request.all_edits())
+            .err()
+            .expect("missing localconfig.vdf must be an error");
         assert!(matches!(err, AppError::FileRead(_)));
 
         let _ = fs::remove_dir_all(&root);
@@ -244,13 +823,399 @@ mod tests {
             news_popup: Some(false),
             do_not_disturb: None,
             launch_options: Vec::new(),
+            edits: Vec::new(),
         };
 
-        let result = apply_bulk_edit(&root, &request);
+        let result = apply_bulk_edit(&root, &request, &root.join("backups"));
         assert_eq!(result.succeeded, 1);
         assert_eq!(result.failed.len(), 1);
         assert_eq!(result.failed[0].steam_id, MISSING_STEAM_ID);
 
         let _ = fs::remove_dir_all(&root);
     }
+
+    const SHARED_CONFIG: &str = "\"UserRoamingConfigStore\"\n{\n\t\"Software\"\n\t{\n\t\t\"Valve\"\n\t\t{\n\t\t\t\"Steam\"\n\t\t\t{\n\t\t\t\t\"apps\"\n\t\t\t\t{\n\t\t\t\t\t\"730\"\n\t\t\t\t\t{\n\t\t\t\t\t\t\"Hidden\"\t\t\"1\"\n\t\t\t\t\t}\n\t\t\t\t}\n\t\t\t}\n\t\t}\n\t}\n}\n";
+
+    fn write_sharedconfig(steam_path: &Path, steam_id: &str, content: &str) -> PathBuf {
+        let userdata = steam_user_data_path(steam_path, steam_id).unwrap();
+        let path = ConfigFile::Sharedconfig.path(&userdata);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn path(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|key| key.to_string()).collect()
+    }
+
+    fn generic_request(edits: Vec<VdfEdit>) -> BulkEditRequest {
+        BulkEditRequest {
+            steam_ids: vec![TEST_STEAM_ID.to_string()],
+            news_popup: None,
+            do_not_disturb: None,
+            launch_options: Vec::new(),
+            edits,
+        }
+    }
+
+    #[test]
+    fn generic_edits_are_read_from_tagged_operations() {
+        let request: BulkEditRequest = serde_json::from_str(
+            r#"{"steamIds": ["1"], "edits": [
+                {"op": "set", "path": ["friends", "DoNotDisturb"], "value": "1"},
+                {"op": "delete", "file": "sharedconfig", "path": ["a"]}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            request.all_edits(),
+            [
+                VdfEdit::Set {
+                    file: ConfigFile::Localconfig,
+                    path: path(&["friends", "DoNotDisturb"]),
+                    value: "1".into(),
+                },
+                VdfEdit::Delete {
+                    file: ConfigFile::Sharedconfig,
+                    path: path(&["a"]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_preview_reports_every_key_before_and_after_and_writes_nothing() {
+        let root = bulk_edit_test_root("preview");
+        let local = write_localconfig(&root, TEST_STEAM_ID, BASE_CONFIG);
+        let shared = write_sharedconfig(&root, TEST_STEAM_ID, SHARED_CONFIG);
+        let hidden = path(&["Software", "Valve", "Steam", "apps", "730", "Hidden"]);
+        let request = generic_request(vec![
+            VdfEdit::Set {
+                file: ConfigFile::Localconfig,
+                path: path(&["friends", "DoNotDisturb"]),
+                value: "1".into(),
+            },
+            VdfEdit::Set {
+                file: ConfigFile::Localconfig,
+                path: path(&["news", "NotifyAvailableGames"]),
+                value: "1".into(),
+            },
+            VdfEdit::Delete {
+                file: ConfigFile::Sharedconfig,
+                path: hidden.clone(),
+            },
+            VdfEdit::Delete {
+                file: ConfigFile::Localconfig,
+                path: path(&["friends"]),
+            },
+            VdfEdit::Set {
+                file: ConfigFile::Localconfig,
+                path: Vec::new(),
+                value: "x".into(),
+            },
+        ]);
+
+        let result = preview_bulk_edit(&root, &request);
+        assert_eq!(result.backup_id, None);
+        let keys = &result.accounts[0].keys;
+        let summary: Vec<(Option<&str>, Option<&str>, KeyOutcome)> = keys
+            .iter()
+            .map(|key| (key.before.as_deref(), key.after.as_deref(), key.outcome))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Some("0"), Some("1"), KeyOutcome::Changed),
+                (Some("1"), Some("1"), KeyOutcome::Unchanged),
+                (Some("1"), None, KeyOutcome::Changed),
+                (None, None, KeyOutcome::Failed),
+                (None, None, KeyOutcome::Failed),
+            ]
+        );
+        assert_eq!(keys[2].file, ConfigFile::Sharedconfig);
+        assert_eq!(keys[2].path, hidden);
+        assert!(keys[3].error.as_deref().unwrap().contains("section"));
+        assert_eq!(
+            result.failed[0].error,
+            "2 of 5 edits could not be applied, so the account is left as it is"
+        );
+        assert_eq!(fs::read_to_string(&local).unwrap(), BASE_CONFIG);
+        assert_eq!(fs::read_to_string(&shared).unwrap(), SHARED_CONFIG);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn an_account_with_a_failed_edit_is_written_nothing() {
+        let root = bulk_edit_test_root("all-or-nothing");
+        let backups = root.join("backups");
+        let local = write_localconfig(&root, TEST_STEAM_ID, BASE_CONFIG);
+        let shared = write_sharedconfig(&root, TEST_STEAM_ID, SHARED_CONFIG);
+        let request = generic_request(vec![
+            VdfEdit::Set {
+                file: ConfigFile::Localconfig,
+                path: path(&["friends", "DoNotDisturb"]),
+                value: "1".into(),
+            },
+            VdfEdit::Delete {
+                file: ConfigFile::Sharedconfig,
+                path: path(&["Software", "Valve", "Steam", "apps", "730", "Hidden"]),
+            },
+            VdfEdit::Delete {
+                file: ConfigFile::Localconfig,
+                path: path(&["news"]),
+            },
+        ]);
+
+        let result = apply_bulk_edit(&root, &request, &backups);
+        assert_eq!(result.succeeded, 0);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.backup_id, None);
+        let outcomes: Vec<KeyOutcome> = result.accounts[0]
+            .keys
+            .iter()
+            .map(|key| key.outcome)
+            .collect();
+        assert_eq!(
+            outcomes,
+            [KeyOutcome::Changed, KeyOutcome::Changed, KeyOutcome::Failed]
+        );
+        assert_eq!(fs::read_to_string(&local).unwrap(), BASE_CONFIG);
+        assert_eq!(fs::read_to_string(&shared).unwrap(), SHARED_CONFIG);
+        assert!(!backups.exists(), "no copy kept");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_batch_is_undone_by_one_revert() {
+        let root = bulk_edit_test_root("revert");
+        let backups = root.join("backups");
+        let local = write_localconfig(&root, TEST_STEAM_ID, BASE_CONFIG);
+        let shared = write_sharedconfig(&root, TEST_STEAM_ID, SHARED_CONFIG);
+        let request = generic_request(vec![
+            VdfEdit::Set {
+                file: ConfigFile::Localconfig,
+                path: path(&["friends", "DoNotDisturb"]),
+                value: "1".into(),
+            },
+            VdfEdit::Set {
+                file: ConfigFile::Sharedconfig,
+                path: path(&["Software", "Valve", "Steam", "apps", "440", "Hidden"]),
+                value: "1".into(),
+            },
+        ]);
+
+        let result = apply_bulk_edit(&root, &request, &backups);
+        assert_eq!(result.succeeded, 1, "{:?}", result.failed);
+        assert!(fs::read_to_string(&local)
+            .unwrap()
+            .contains("\"DoNotDisturb\"\t\t\"1\""));
+        assert!(fs::read_to_string(&shared).unwrap().contains("\"440\""));
+        let backup_id = result.backup_id.expect("a batch that wrote keeps a backup");
+
+        // A batch that changes nothing keeps none.
+        let again = apply_bulk_edit(&root, &request, &backups);
+        assert_eq!(again.backup_id, None);
+        assert!(again.accounts[0]
+            .keys
+            .iter()
+            .all(|key| key.outcome == KeyOutcome::Unchanged));
+
+        let reverted = revert_bulk_edit(&backups, &backup_id).unwrap();
+        assert_eq!(reverted.restored, 2);
+        assert!(reverted.failed.is_empty());
+        assert!(reverted.conflicts.is_empty());
+        assert_eq!(fs::read_to_string(&local).unwrap(), BASE_CONFIG);
+        assert_eq!(fs::read_to_string(&shared).unwrap(), SHARED_CONFIG);
+        // Spent once restored.
+        assert!(revert_bulk_edit(&backups, &backup_id).is_err());
+        assert!(revert_bulk_edit(&backups, "../config").is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_revert_after_steam_rewrote_the_files_puts_back_only_the_batch_keys() {
+        let root = bulk_edit_test_root("revert-since");
+        let backups = root.join("backups");
+        let local = write_localconfig(&root, TEST_STEAM_ID, BASE_CONFIG);
+        let shared = write_sharedconfig(&root, TEST_STEAM_ID, SHARED_CONFIG);
+        let hidden = path(&["Software", "Valve", "Steam", "apps", "440", "Hidden"]);
+        let request = generic_request(vec![
+            VdfEdit::Set {
+                file: ConfigFile::Localconfig,
+                path: path(&["friends", "DoNotDisturb"]),
+                value: "1".into(),
+            },
+            VdfEdit::Set {
+                file: ConfigFile::Sharedconfig,
+                path: hidden.clone(),
+                value: "1".into(),
+            },
+        ]);
+        let result = apply_bulk_edit(&root, &request, &backups);
+        let backup_id = result.backup_id.expect("a batch that wrote keeps a backup");
+
+        // Steam runs: it turns the news popup off and unhides the game.
+        let steam_writes = |file: &Path, path: &[&str], value: &str| {
+            let mut document = read_document(file).unwrap().unwrap();
+            document.set(path, value).unwrap();
+            write_document(file, &document).unwrap();
+        };
+        steam_writes(
+            &local,
+            &["UserLocalConfigStore", "news", "NotifyAvailableGames"],
+            "0",
+        );
+        let mut shared_path = vec!["UserRoamingConfigStore"];
+        shared_path.extend(hidden.iter().map(String::as_str));
+        steam_writes(&shared, &shared_path, "0");
+        let steam_shared = fs::read_to_string(&shared).unwrap();
+
+        let reverted = revert_bulk_edit(&backups, &backup_id).unwrap();
+        assert_eq!(reverted.restored, 2);
+        assert!(reverted.failed.is_empty());
+        assert_eq!(reverted.conflicts.len(), 1);
+        assert_eq!(reverted.conflicts[0].file, ConfigFile::Sharedconfig);
+        assert_eq!(reverted.conflicts[0].path, hidden);
+        assert_eq!(reverted.conflicts[0].current.as_deref(), Some("0"));
+        assert_eq!(
+            fs::read_to_string(&local).unwrap(),
+            BASE_CONFIG.replace(
+                "\"NotifyAvailableGames\"\t\t\"1\"",
+                "\"NotifyAvailableGames\"\t\t\"0\""
+            ),
+            "what Steam wrote since is kept"
+        );
+        assert_eq!(fs::read_to_string(&shared).unwrap(), steam_shared);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_retried_revert_skips_the_files_the_first_attempt_put_back() {
+        let root = bulk_edit_test_root("revert-retry");
+        let backups = root.join("backups");
+        let local = write_localconfig(&root, TEST_STEAM_ID, BASE_CONFIG);
+        let shared = write_sharedconfig(&root, TEST_STEAM_ID, SHARED_CONFIG);
+        let request = generic_request(vec![
+            VdfEdit::Set {
+                file: ConfigFile::Localconfig,
+                path: path(&["friends", "DoNotDisturb"]),
+                value: "1".into(),
+            },
+            VdfEdit::Set {
+                file: ConfigFile::Sharedconfig,
+                path: path(&["Software", "Valve", "Steam", "apps", "440", "Hidden"]),
+                value: "1".into(),
+            },
+        ]);
+        let result = apply_bulk_edit(&root, &request, &backups);
+        let backup_id = result.backup_id.expect("a batch that wrote keeps a backup");
+
+        // sharedconfig.vdf cannot be read, so the first attempt only puts
+        // localconfig.vdf back.
+        let edited_shared = fs::read_to_string(&shared).unwrap();
+        fs::remove_file(&shared).unwrap();
+        fs::create_dir(&shared).unwrap();
+        let first = revert_bulk_edit(&backups, &backup_id).unwrap();
+        assert_eq!(first.restored, 1);
+        assert_eq!(first.failed.len(), 1);
+        assert_eq!(fs::read_to_string(&local).unwrap(), BASE_CONFIG);
+
+        fs::remove_dir(&shared).unwrap();
+        fs::write(&shared, &edited_shared).unwrap();
+        let retry = revert_bulk_edit(&backups, &backup_id).unwrap();
+        assert_eq!(retry.restored, 2);
+        assert!(retry.failed.is_empty());
+        assert!(retry.conflicts.is_empty(), "{:?}", retry.conflicts);
+        assert_eq!(fs::read_to_string(&local).unwrap(), BASE_CONFIG);
+        assert_eq!(fs::read_to_string(&shared).unwrap(), SHARED_CONFIG);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_file_put_back_after_a_failed_write_leaves_the_manifest_as_it_was() {
+        let root = bulk_edit_test_root("take-back");
+        let live = root.join("localconfig.vdf");
+        let first = Document::parse(&BASE_CONFIG.replace("\"0\"", "\"1\"")).unwrap();
+        let second = Document::parse(&BASE_CONFIG.replace("\"1\"", "\"0\"")).unwrap();
+        let change = |keys: &[&str], before: &str, after: &str| KeyEditResult {
+            file: ConfigFile::Localconfig,
+            path: path(keys),
+            before: Some(before.into()),
+            after: Some(after.into()),
+            outcome: KeyOutcome::Changed,
+            error: None,
+        };
+        let read_manifest = |backup: &Backup| -> BackupManifest {
+            crate::storage::read_json_if_exists(&backup.dir.join(MANIFEST))
+                .unwrap()
+                .unwrap()
+        };
+
+        let mut backup = Backup::new(&root.join("backups"));
+        let kept = backup
+            .keep(
+                TEST_STEAM_ID,
+                ConfigFile::Localconfig,
+                &live,
+                BASE_CONFIG,
+                &first,
+                &[change(&["friends", "DoNotDisturb"], "0", "1")],
+            )
+            .unwrap();
+        assert!(matches!(kept, Kept::New));
+        let after_first = read_manifest(&backup);
+
+        // The account again, whose write then fails.
+        let again = backup
+            .keep(
+                TEST_STEAM_ID,
+                ConfigFile::Localconfig,
+                &live,
+                &first.to_string(),
+                &second,
+                &[change(&["news", "NotifyAvailableGames"], "1", "0")],
+            )
+            .unwrap();
+        assert!(matches!(again, Kept::Again { .. }));
+        assert_eq!(read_manifest(&backup).files[0].changes.len(), 2);
+        backup.take_back(vec![(&live, again)]);
+
+        let restored = read_manifest(&backup);
+        assert_eq!(
+            restored.files[0].written_sha256,
+            after_first.files[0].written_sha256
+        );
+        assert_eq!(restored.files[0].changes.len(), 1);
+
+        backup.take_back(vec![(&live, Kept::New)]);
+        assert!(!backup.dir.exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn only_the_latest_backups_are_kept() {
+        let root = bulk_edit_test_root("prune");
+        for id in [
+            "5", "40", "40-1", "300", "2000", "7", "8", "9", "10", "11", "12", "13",
+        ] {
+            fs::create_dir_all(root.join(id)).unwrap();
+        }
+        prune_backups(&root);
+        let mut left: Vec<String> = fs::read_dir(&root)
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            ["10", "11", "12", "13", "2000", "300", "40", "40-1", "8", "9"]
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
 }
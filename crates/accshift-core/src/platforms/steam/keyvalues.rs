@@ -0,0 +1,1022 @@
+//! Valve KeyValues text, as a tree that writes back exactly what it read.
+//!
+//! Steam's `.vdf` and `.acf` files are read by Steam again after we edit
+//! them, and some of them are also edited by hand or by other tools. So the
+//! tree keeps everything the file says besides its keys and values: the
+//! whitespace and comments before each entry, whether a token was quoted,
+//! `[$WIN32]`-style conditionals, and `#include` / `#base` directives. An
+//! entry nobody touched serializes to the very bytes it was parsed from, and
+//! an edit changes only the token it is about.
+//!
+//! Keys compare without regard to ASCII case, as Steam's own reader does.
+//! Where a key repeats, the first occurrence is the one queries see.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A file that is not KeyValues text, located by line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {} column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A key or a value, as written and as meant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token {
+    /// The source text, quotes and escapes included.
+    raw: String,
+    /// What it says once the quotes and escapes are taken off.
+    text: String,
+}
+
+impl Token {
+    fn quoted(text: &str) -> Self {
+        Self {
+            raw: format!("\"{}\"", escape(text)),
+            text: text.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Value(Token),
+    Section(Section),
+}
+
+/// One `"key" "value"` or `"key" { ... }`, with the text around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Whitespace and comments between the previous entry and this key.
+    lead: String,
+    key: Token,
+    /// Everything between the key and the value or the opening brace,
+    /// including a conditional written there.
+    gap: String,
+    node: Node,
+    /// A conditional written after the value, with the spaces before it.
+    /// Empty when there is none; the line break that follows belongs to the
+    /// next entry's `lead`.
+    trail: String,
+    condition: Option<String>,
+}
+
+impl Entry {
+    pub fn key(&self) -> &str {
+        &self.key.text
+    }
+
+    /// The value, or `None` for a section.
+    pub fn value(&self) -> Option<&str> {
+        match &self.node {
+            Node::Value(token) => Some(&token.text),
+            Node::Section(_) => None,
+        }
+    }
+
+    /// The section, or `None` for a value.
+    pub fn section(&self) -> Option<&Section> {
+        match &self.node {
+            Node::Section(section) => Some(section),
+            Node::Value(_) => None,
+        }
+    }
+
+    pub fn section_mut(&mut self) -> Option<&mut Section> {
+        match &mut self.node {
+            Node::Section(section) => Some(section),
+            Node::Value(_) => None,
+        }
+    }
+
+    /// The platform conditional, without its brackets: `$WIN32`, `!$OSX`.
+    pub fn condition(&self) -> Option<&str> {
+        self.condition.as_deref()
+    }
+
+    /// An `#include` or `#base` line rather than a key. Directives are kept
+    /// and written back, but never answer a query.
+    pub fn is_directive(&self) -> bool {
+        !self.key.raw.starts_with('"')
+            && (self.key.text.eq_ignore_ascii_case("#include")
+                || self.key.text.eq_ignore_ascii_case("#base"))
+    }
+}
+
+/// The entries between a pair of braces, or at the top of a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Section {
+    entries: Vec<Entry>,
+    /// Whitespace and comments after the last entry, before the closing
+    /// brace or the end of the file.
+    tail: String,
+}
+
+impl Section {
+    /// Every entry in file order, directives included.
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    /// The first entry named `key` that is not a directive.
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        self.position(key).map(|index| &self.entries[index])
+    }
+
+    /// The value of `key`, when it is a value.
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Entry::value)
+    }
+
+    /// The first section named `key`.
+    pub fn section(&self, key: &str) -> Option<&Section> {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.is_directive() && entry.key.text.eq_ignore_ascii_case(key))
+            .find_map(Entry::section)
+    }
+
+    pub fn section_mut(&mut self, key: &str) -> Option<&mut Section> {
+        self.entries
+            .iter_mut()
+            .filter(|entry| !entry.is_directive() && entry.key.text.eq_ignore_ascii_case(key))
+            .find_map(Entry::section_mut)
+    }
+
+    /// Each subsection with its name, in file order.
+    pub fn sections(&self) -> impl Iterator<Item = (&str, &Section)> {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.is_directive())
+            .filter_map(|entry| entry.section().map(|section| (entry.key(), section)))
+    }
+
+    /// Each value with its key, in file order.
+    pub fn values(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.is_directive())
+            .filter_map(|entry| entry.value().map(|value| (entry.key(), value)))
+    }
+
+    /// The entry at `path` below this section.
+    pub fn lookup(&self, path: &[&str]) -> Option<&Entry> {
+        let (last, sections) = path.split_last()?;
+        let mut section = self;
+        for name in sections {
+            section = section.section(name)?;
+        }
+        section.get(last)
+    }
+
+    /// Removes the first entry named `key`, with the whitespace and any
+    /// comment directly above it. False when there was none.
+    pub fn remove(&mut self, key: &str) -> bool {
+        match self.position(key) {
+            Some(index) => {
+                self.entries.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| !entry.is_directive() && entry.key.text.eq_ignore_ascii_case(key))
+    }
+
+    /// Sets `key` at `depth`: an existing value has its token replaced and
+    /// nothing else; a missing one is appended, laid out like its siblings.
+    fn set_value(
+        &mut self,
+        key: &str,
+        value: &str,
+        depth: usize,
+        style: &Style,
+    ) -> Result<(), String> {
+        if let Some(index) = self.position(key) {
+            return match &mut self.entries[index].node {
+                Node::Value(token) => {
+                    if token.text != value {
+                        *token = Token::quoted(value);
+                    }
+                    Ok(())
+                }
+                Node::Section(_) => Err(format!("`{key}` is a section, not a value")),
+            };
+        }
+        let gap = self
+            .entries
+            .iter()
+            .rev()
+            .find(|entry| matches!(entry.node, Node::Value(_)) && is_blank(&entry.gap))
+            .filter(|entry| !entry.gap.contains('\n'))
+            .map(|entry| entry.gap.clone())
+            .unwrap_or_else(|| "\t\t".to_string());
+        self.append(
+            Entry {
+                lead: String::new(),
+                key: Token::quoted(key),
+                gap,
+                node: Node::Value(Token::quoted(value)),
+                trail: String::new(),
+                condition: None,
+            },
+            depth,
+            style,
+        );
+        Ok(())
+    }
+
+    /// The section `key` at `depth`, added empty when missing.
+    fn section_or_insert(
+        &mut self,
+        key: &str,
+        depth: usize,
+        style: &Style,
+    ) -> Result<&mut Section, String> {
+        let index = match self.position(key) {
+            Some(index) => index,
+            None => {
+                let indent = style.indent.repeat(depth);
+                self.append(
+                    Entry {
+                        lead: String::new(),
+                        key: Token::quoted(key),
+                        gap: format!("{}{indent}", style.newline),
+                        node: Node::Section(Section {
+                            entries: Vec::new(),
+                            tail: format!("{}{indent}", style.newline),
+                        }),
+                        trail: String::new(),
+                        condition: None,
+                    },
+                    depth,
+                    style,
+                );
+                self.entries.len() - 1
+            }
+        };
+        self.entries[index]
+            .section_mut()
+            .ok_or_else(|| format!("`{key}` is a value, not a section"))
+    }
+
+    /// Adds `entry` after the last one, on a line of its own, indented like
+    /// the entries already there.
+    fn append(&mut self, mut entry: Entry, depth: usize, style: &Style) {
+        entry.lead = match self.entries.last() {
+            Some(last) if is_blank(&last.lead) && !last.lead.is_empty() => last.lead.clone(),
+            Some(last) if last.lead.contains('\n') => {
+                let indent = last.lead.rsplit('\n').next().unwrap_or_default();
+                if is_blank(indent) {
+                    format!("{}{indent}", style.newline)
+                } else {
+                    format!("{}{}", style.newline, style.indent.repeat(depth))
+                }
+            }
+            None if depth == 0 && self.tail.is_empty() => String::new(),
+            _ => format!("{}{}", style.newline, style.indent.repeat(depth)),
+        };
+        // `{}` closed on the line it opened: give the brace a line of its own
+        // now that there is something between the two.
+        if self.entries.is_empty() && depth > 0 && !self.tail.contains('\n') {
+            self.tail = format!("{}{}", style.newline, style.indent.repeat(depth - 1));
+        }
+        self.entries.push(entry);
+    }
+}
+
+/// How a file lays itself out, so an added entry matches the rest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Style {
+    newline: &'static str,
+    indent: String,
+}
+
+/// A whole KeyValues file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    body: Section,
+    style: Style,
+}
+
+impl Document {
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser { text, pos: 0 };
+        let body = parser.section(0)?;
+        let style = Style {
+            newline: if text.contains("\r\n") { "\r\n" } else { "\n" },
+            indent: detect_indent(&body),
+        };
+        Ok(Self { body, style })
+    }
+
+    /// The top of the file. Most files hold a single section here, named
+    /// after the file (`users`, `UserLocalConfigStore`, `AppState`).
+    pub fn root(&self) -> &Section {
+        &self.body
+    }
+
+    /// The value at `path`, from a top-level key down.
+    pub fn get(&self, path: &[&str]) -> Option<&str> {
+        self.body.lookup(path).and_then(Entry::value)
+    }
+
+    /// The value at `path`, parsed. `None` when it is missing or does not
+    /// parse as a `T`.
+    pub fn get_parsed<T: FromStr>(&self, path: &[&str]) -> Option<T> {
+        self.get(path)?.trim().parse().ok()
+    }
+
+    /// The section at `path`, from a top-level key down.
+    pub fn section(&self, path: &[&str]) -> Option<&Section> {
+        self.body.lookup(path).and_then(Entry::section)
+    }
+
+    pub fn section_mut(&mut self, path: &[&str]) -> Option<&mut Section> {
+        let mut section = &mut self.body;
+        for name in path {
+            section = section.section_mut(name)?;
+        }
+        Some(section)
+    }
+
+    /// Sets the value at `path`, adding whatever sections are missing on the
+    /// way. Fails when a step of the path is a value, or the last one a
+    /// section: overwriting either would lose what the file holds there.
+    pub fn set(&mut self, path: &[&str], value: &str) -> Result<(), String> {
+        let (key, sections) = path
+            .split_last()
+            .ok_or_else(|| "an empty path names nothing".to_string())?;
+        let style = self.style.clone();
+        let mut section = &mut self.body;
+        for (depth, name) in sections.iter().enumerate() {
+            section = section.section_or_insert(name, depth, &style)?;
+        }
+        section.set_value(key, value, sections.len(), &style)
+    }
+
+    /// Removes the entry at `path`. False when there was none.
+    pub fn remove(&mut self, path: &[&str]) -> bool {
+        let Some((key, sections)) = path.split_last() else {
+            return false;
+        };
+        self.section_mut(sections)
+            .is_some_and(|section| section.remove(key))
+    }
+
+    /// The `#include` and `#base` lines at the top of the file, as
+    /// `(directive, file)`. Steam merges those files in; nothing here does.
+    pub fn directives(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.body
+            .entries
+            .iter()
+            .filter(|entry| entry.is_directive())
+            .filter_map(|entry| entry.value().map(|file| (entry.key(), file)))
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        write_entries(&self.body, &mut out);
+        f.write_str(&out)
+    }
+}
+
+fn write_entries(section: &Section, out: &mut String) {
+    for entry in &section.entries {
+        out.push_str(&entry.lead);
+        out.push_str(&entry.key.raw);
+        out.push_str(&entry.gap);
+        match &entry.node {
+            Node::Value(token) => out.push_str(&token.raw),
+            Node::Section(inner) => {
+                out.push('{');
+                write_entries(inner, out);
+                out.push('}');
+            }
+        }
+        out.push_str(&entry.trail);
+    }
+    out.push_str(&section.tail);
+}
+
+/// Escapes a string for a quoted token. A line break is escaped too, so a
+/// value cannot start a line of its own and forge a key.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn is_blank(text: &str) -> bool {
+    text.chars().all(char::is_whitespace)
+}
+
+/// The indent of the first nested entry written on a line of its own, else a
+/// tab as Steam writes.
+fn detect_indent(body: &Section) -> String {
+    body.sections()
+        .flat_map(|(_, section)| section.entries.iter())
+        .filter_map(|entry| entry.lead.rsplit_once('\n').map(|(_, indent)| indent))
+        .find(|indent| !indent.is_empty() && is_blank(indent))
+        .unwrap_or("\t")
+        .to_string()
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    fn error(&self, at: usize, message: impl Into<String>) -> ParseError {
+        let before = &self.text[..at];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            + 1;
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Whitespace and `//` comments. A byte-order mark counts as whitespace,
+    /// so a file saved by an editor that adds one still parses.
+    fn trivia(&mut self) -> String {
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                Some(ch) if ch.is_whitespace() || ch == '\u{feff}' => {
+                    self.bump();
+                }
+                Some('/') if self.text[self.pos..].starts_with("//") => {
+                    while self.peek().is_some_and(|ch| ch != '\n') {
+                        self.bump();
+                    }
+                }
+                _ => break,
+            }
+        }
+        self.text[start..self.pos].to_string()
+    }
+
+    /// Entries up to the closing brace at `depth`, or the end of the file at
+    /// the top. The closing brace is consumed.
+    fn section(&mut self, depth: usize) -> Result<Section, ParseError> {
+        let open = self.pos.saturating_sub(1);
+        let mut entries = Vec::new();
+        loop {
+            let lead = self.trivia();
+            match self.peek() {
+                None if depth == 0 => {
+                    return Ok(Section {
+                        entries,
+                        tail: lead,
+                    })
+                }
+                None => return Err(self.error(open, "this `{` is never closed")),
+                Some('}') if depth == 0 => {
+                    return Err(self.error(self.pos, "expected a key, found `}`"))
+                }
+                Some('}') => {
+                    self.bump();
+                    return Ok(Section {
+                        entries,
+                        tail: lead,
+                    });
+                }
+                Some(ch @ ('{' | '[')) => {
+                    return Err(self.error(self.pos, format!("expected a key, found `{ch}`")))
+                }
+                Some(_) => {
+                    let mut entry = self.entry(depth)?;
+                    entry.lead = lead;
+                    entries.push(entry);
+                }
+            }
+        }
+    }
+
+    fn entry(&mut self, depth: usize) -> Result<Entry, ParseError> {
+        let key_at = self.pos;
+        let key = self.token()?;
+
+        let gap_start = self.pos;
+        self.trivia();
+        let mut condition = None;
+        if self.peek() == Some('[') {
+            condition = Some(self.conditional()?);
+            self.trivia();
+        }
+        let gap = self.text[gap_start..self.pos].to_string();
+
+        let node = match self.peek() {
+            Some('{') => {
+                self.bump();
+                Node::Section(self.section(depth + 1)?)
+            }
+            None | Some('}') | Some('[') => {
+                return Err(self.error(key_at, format!("`{}` has no value", key.text)))
+            }
+            Some(_) => Node::Value(self.token()?),
+        };
+
+        // A conditional after the value sits on the same line: look past
+        // spaces and tabs only, and give them back when none follows.
+        let trail_start = self.pos;
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+        let trail = if self.peek() == Some('[') && condition.is_none() {
+            condition = Some(self.conditional()?);
+            self.text[trail_start..self.pos].to_string()
+        } else {
+            self.pos = trail_start;
+            String::new()
+        };
+
+        Ok(Entry {
+            lead: String::new(),
+            key,
+            gap,
+            node,
+            trail,
+            condition,
+        })
+    }
+
+    /// `[$WIN32]`, returned without its brackets.
+    fn conditional(&mut self) -> Result<String, ParseError> {
+        let start = self.pos;
+        self.bump();
+        let inner = self.pos;
+        while let Some(ch) = self.bump() {
+            match ch {
+                ']' => return Ok(self.text[inner..self.pos - 1].trim().to_string()),
+                '\n' => break,
+                _ => {}
+            }
+        }
+        Err(self.error(start, "this `[` is never closed"))
+    }
+
+    /// A quoted token, or a bare one running to the next space, quote or
+    /// brace.
+    fn token(&mut self) -> Result<Token, ParseError> {
+        let start = self.pos;
+        if self.peek() != Some('"') {
+            while self
+                .peek()
+                .is_some_and(|ch| !ch.is_whitespace() && !matches!(ch, '"' | '{' | '}'))
+            {
+                self.bump();
+            }
+            let raw = &self.text[start..self.pos];
+            return Ok(Token {
+                raw: raw.to_string(),
+                text: raw.to_string(),
+            });
+        }
+
+        self.bump();
+        let mut text = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(self.error(start, "this string is never closed")),
+                Some('"') => break,
+                Some('\\') => match self.bump() {
+                    Some('n') => text.push('\n'),
+                    Some('r') => text.push('\r'),
+                    Some('t') => text.push('\t'),
+                    Some('\\') => text.push('\\'),
+                    Some('"') => text.push('"'),
+                    // Not an escape: a Windows path written with single
+                    // backslashes keeps them.
+                    Some(other) => {
+                        text.push('\\');
+                        text.push(other);
+                    }
+                    None => return Err(self.error(start, "this string is never closed")),
+                },
+                Some(ch) => text.push(ch),
+            }
+        }
+        Ok(Token {
+            raw: self.text[start..self.pos].to_string(),
+            text,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOGINUSERS: &str = include_str!("samples/loginusers.vdf");
+    const LOCALCONFIG: &str = include_str!("samples/localconfig.vdf");
+    const LIBRARYFOLDERS: &str = include_str!("samples/libraryfolders.vdf");
+    const APPMANIFEST: &str = include_str!("samples/appmanifest_730.acf");
+    const RESOURCE: &str = include_str!("samples/gameinfo_resource.vdf");
+
+    fn round_trip(text: &str) -> Document {
+        let document = Document::parse(text).unwrap();
+        assert_eq!(document.to_string(), text);
+        document
+    }
+
+    #[test]
+    fn real_files_come_back_byte_for_byte() {
+        for sample in [
+            LOGINUSERS,
+            LOCALCONFIG,
+            LIBRARYFOLDERS,
+            APPMANIFEST,
+            RESOURCE,
+        ] {
+            round_trip(sample);
+            round_trip(&sample.replace('\n', "\r\n"));
+        }
+    }
+
+    #[test]
+    fn queries_walk_sections_without_regard_to_case() {
+        let document = round_trip(LOGINUSERS);
+        assert_eq!(
+            document.get(&["users", "76561197960287930", "AccountName"]),
+            Some("gaben")
+        );
+        assert_eq!(
+            document.get(&["USERS", "76561197960287930", "personaname"]),
+            Some("Gabe \"the\" Newell")
+        );
+        assert_eq!(
+            document.get_parsed::<u64>(&["users", "76561197960287930", "Timestamp"]),
+            Some(1_700_000_000)
+        );
+        assert_eq!(
+            document.get_parsed::<u64>(&["users", "76561197960287930", "AccountName"]),
+            None
+        );
+        let ids: Vec<&str> = document
+            .section(&["users"])
+            .unwrap()
+            .sections()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids, ["76561197960287930", "76561198000000001"]);
+    }
+
+    #[test]
+    fn an_edit_changes_only_the_token_it_is_about() {
+        let mut document = round_trip(LOCALCONFIG);
+        document
+            .set(&["UserLocalConfigStore", "friends", "PersonaState"], "7")
+            .unwrap();
+        let edited = document.to_string();
+
+        let changed: Vec<(&str, &str)> = LOCALCONFIG
+            .lines()
+            .zip(edited.lines())
+            .filter(|(before, after)| before != after)
+            .collect();
+        assert_eq!(
+            changed,
+            [(
+                "\t\t\"PersonaState\"\t\t\"1\"",
+                "\t\t\"PersonaState\"\t\t\"7\""
+            )]
+        );
+        // The friend whose block also holds a PersonaState is not touched.
+        assert_eq!(
+            document.get(&[
+                "UserLocalConfigStore",
+                "friends",
+                "76561198000000002",
+                "PersonaState"
+            ]),
+            Some("5")
+        );
+    }
+
+    #[test]
+    fn setting_a_value_to_what_it_already_is_keeps_its_spelling() {
+        let text = "\"a\"\n{\n\tkey\tbare\n}\n";
+        let mut document = round_trip(text);
+        document.set(&["a", "key"], "bare").unwrap();
+        assert_eq!(document.to_string(), text);
+    }
+
+    #[test]
+    fn a_missing_key_and_its_sections_are_added_like_their_siblings() {
+        let mut document = round_trip(LOCALCONFIG);
+        document
+            .set(
+                &[
+                    "UserLocalConfigStore",
+                    "Software",
+                    "Valve",
+                    "Steam",
+                    "apps",
+                    "570",
+                    "LaunchOptions",
+                ],
+                "-novid",
+            )
+            .unwrap();
+        document
+            .set(&["UserLocalConfigStore", "friends", "DoNotDisturb"], "1")
+            .unwrap();
+        let edited = document.to_string();
+
+        assert!(
+            edited.contains("\t\t\t\t\t\"570\"\n\t\t\t\t\t{\n\t\t\t\t\t\t\"LaunchOptions\"\t\t\"-novid\"\n\t\t\t\t\t}\n"),
+            "{edited}"
+        );
+        assert!(
+            edited.contains("\t\t\"DoNotDisturb\"\t\t\"1\"\n"),
+            "{edited}"
+        );
+        let reparsed = round_trip(&edited);
+        assert_eq!(
+            reparsed.get(&[
+                "UserLocalConfigStore",
+                "Software",
+                "Valve",
+                "Steam",
+                "apps",
+                "570",
+                "LaunchOptions"
+            ]),
+            Some("-novid")
+        );
+        // What was already there is still there.
+        assert_eq!(
+            reparsed.get(&[
+                "UserLocalConfigStore",
+                "Software",
+                "Valve",
+                "Steam",
+                "apps",
+                "730",
+                "LaunchOptions"
+            ]),
+            Some("+exec \"autoexec.cfg\" -path C:\\Steam")
+        );
+    }
+
+    #[test]
+    fn added_lines_follow_the_files_line_endings_and_indent() {
+        let text = "\"a\"\r\n{\r\n    \"b\"\r\n    {\r\n    }\r\n}\r\n";
+        let mut document = round_trip(text);
+        document.set(&["a", "b", "c", "d"], "1").unwrap();
+        assert_eq!(
+            document.to_string(),
+            "\"a\"\r\n{\r\n    \"b\"\r\n    {\r\n        \"c\"\r\n        {\r\n            \"d\"\t\t\"1\"\r\n        }\r\n    }\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn an_entry_added_to_a_braces_only_section_gets_lines_of_its_own() {
+        let mut document = round_trip("\"a\" {}");
+        document.set(&["a", "b"], "1").unwrap();
+        assert_eq!(document.to_string(), "\"a\" {\n\t\"b\"\t\t\"1\"\n}");
+    }
+
+    #[test]
+    fn an_empty_file_grows_a_root() {
+        let mut document = round_trip("");
+        document
+            .set(&["Registry", "HKCU", "AutoLoginUser"], "gaben")
+            .unwrap();
+        let text = document.to_string();
+        assert_eq!(
+            text,
+            "\"Registry\"\n{\n\t\"HKCU\"\n\t{\n\t\t\"AutoLoginUser\"\t\t\"gaben\"\n\t}\n}"
+        );
+        round_trip(&text);
+    }
+
+    #[test]
+    fn a_value_is_never_overwritten_by_a_section_or_the_reverse() {
+        let mut document = round_trip(LOGINUSERS);
+        let before = document.to_string();
+        assert!(document
+            .set(&["users", "76561197960287930", "AccountName", "x"], "1")
+            .unwrap_err()
+            .contains("is a value"));
+        assert!(document
+            .set(&["users", "76561197960287930"], "1")
+            .unwrap_err()
+            .contains("is a section"));
+        assert_eq!(document.to_string(), before);
+    }
+
+    #[test]
+    fn removing_an_entry_takes_its_line_and_leaves_the_rest() {
+        let mut document = round_trip(LOGINUSERS);
+        assert!(document.remove(&["users", "76561197960287930"]));
+        assert!(!document.remove(&["users", "76561197960287930"]));
+        let text = document.to_string();
+        assert!(!text.contains("gaben"));
+        let reparsed = round_trip(&text);
+        assert_eq!(
+            reparsed.get(&["users", "76561198000000001", "AccountName"]),
+            Some("second")
+        );
+    }
+
+    #[test]
+    fn escapes_are_read_and_a_written_value_cannot_forge_a_line() {
+        let document = round_trip("\"k\" \"a\\\"b\\\\c\\nd\\te C:\\Games\"");
+        assert_eq!(document.get(&["k"]), Some("a\"b\\c\nd\te C:\\Games"));
+
+        let mut document = round_trip("\"a\"\n{\n}\n");
+        document
+            .set(&["a", "LaunchOptions"], "good\"\n\t\"PersonaState\"\t\"7")
+            .unwrap();
+        let text = document.to_string();
+        assert_eq!(text.lines().count(), 4, "{text}");
+        assert_eq!(
+            round_trip(&text).get(&["a", "LaunchOptions"]),
+            Some("good\"\n\t\"PersonaState\"\t\"7")
+        );
+    }
+
+    #[test]
+    fn braces_and_slashes_inside_values_are_just_text() {
+        let document = round_trip("\"a\" { \"b\" \"}{\" \"c\" \"//not a comment\" } // one");
+        assert_eq!(document.get(&["a", "b"]), Some("}{"));
+        assert_eq!(document.get(&["a", "c"]), Some("//not a comment"));
+    }
+
+    #[test]
+    fn several_keys_on_one_line_and_bare_tokens_parse() {
+        let document = round_trip("a { b 1 c \"2\" d { e 3 } }");
+        assert_eq!(document.get(&["a", "b"]), Some("1"));
+        assert_eq!(document.get(&["a", "c"]), Some("2"));
+        assert_eq!(document.get(&["a", "d", "e"]), Some("3"));
+    }
+
+    #[test]
+    fn conditionals_and_directives_are_kept_and_read() {
+        let document = round_trip(RESOURCE);
+        let directives: Vec<(&str, &str)> = document.directives().collect();
+        assert_eq!(
+            directives,
+            [("#base", "gameinfo_base.vdf"), ("#include", "platform.vdf")]
+        );
+        let section = document.section(&["GameInfo", "FileSystem"]).unwrap();
+        let conditions: Vec<(&str, Option<&str>)> = section
+            .entries()
+            .map(|entry| (entry.key(), entry.condition()))
+            .collect();
+        assert_eq!(
+            conditions,
+            [
+                ("SteamAppId", None),
+                ("ToolsAppId", Some("$WIN32")),
+                ("ToolsAppId", Some("!$WIN32")),
+                ("SearchPaths", Some("$WINDOWS")),
+            ]
+        );
+        // The first of a repeated key answers.
+        assert_eq!(
+            document.get(&["GameInfo", "FileSystem", "ToolsAppId"]),
+            Some("211")
+        );
+        // A directive is never mistaken for a key.
+        assert_eq!(document.get(&["#base"]), None);
+    }
+
+    #[test]
+    fn broken_files_are_refused_with_where() {
+        for (text, line, column, message) in [
+            ("\"a\"\n{\n\t\"b\" \"c\"\n", 2, 1, "never closed"),
+            ("\"a\" \"b\n", 1, 5, "never closed"),
+            ("\"a\" \"b\"\n}", 2, 1, "found `}`"),
+            ("\"a\"", 1, 1, "has no value"),
+            ("\"a\" { \"b\" }", 1, 7, "has no value"),
+            ("\"a\" \"b\" [$WIN32", 1, 9, "never closed"),
+            ("{ }", 1, 1, "found `{`"),
+        ] {
+            let error = Document::parse(text).unwrap_err();
+            assert_eq!(
+                (error.line, error.column),
+                (line, column),
+                "{text:?}: {error}"
+            );
+            assert!(error.message.contains(message), "{text:?}: {error}");
+        }
+    }
+
+    /// xorshift64, so a failure names the seed that found it.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    #[test]
+    fn fuzzed_text_never_panics_and_what_parses_comes_back_unchanged() {
+        const PIECES: &[&str] = &[
+            "\"", "{", "}", "[", "]", "$WIN32", "\\", "\\\"", "\\n", "//", "\n", "\r\n", "\t", " ",
+            "key", "value", "#include", "#base", "é", "\u{feff}", "1",
+        ];
+        for seed in 1..=2000u64 {
+            let mut rng = Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            let text: String = (0..rng.below(40))
+                .map(|_| PIECES[rng.below(PIECES.len())])
+                .collect();
+            if let Ok(document) = Document::parse(&text) {
+                assert_eq!(document.to_string(), text, "seed {seed}: {text:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn fuzzed_trees_survive_edits_and_a_reparse() {
+        for seed in 1..=300u64 {
+            let mut rng = Rng(seed.wrapping_mul(0x2545_f491_4f6c_dd1d));
+            let mut document = Document::parse(LOCALCONFIG).unwrap();
+            let mut expected = Vec::new();
+            for _ in 0..rng.below(8) + 1 {
+                let depth = rng.below(4) + 1;
+                let mut path = vec!["UserLocalConfigStore".to_string()];
+                path.extend((0..depth).map(|_| format!("k{}", rng.below(3))));
+                let value: String = (0..rng.below(6))
+                    .map(|_| ["a", "\"", "\\", "\n", "{", "}", " "][rng.below(7)])
+                    .collect();
+                let segments: Vec<&str> = path.iter().map(String::as_str).collect();
+                if document.set(&segments, &value).is_ok() {
+                    expected.retain(|(p, _): &(Vec<String>, String)| {
+                        // A later set under a path replaces what was there.
+                        p != &path
+                    });
+                    expected.push((path, value));
+                }
+            }
+            let reparsed = round_trip(&document.to_string());
+            for (path, value) in &expected {
+                let segments: Vec<&str> = path.iter().map(String::as_str).collect();
+                assert_eq!(reparsed.get(&segments), Some(value.as_str()), "seed {seed}");
+            }
+            assert_eq!(
+                reparsed.get(&["UserLocalConfigStore", "friends", "PersonaName"]),
+                Some("Alice"),
+                "seed {seed}"
+            );
+        }
+    }
+}
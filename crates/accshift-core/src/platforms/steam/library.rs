@@ -0,0 +1,398 @@
+//! Every account's games side by side: which accounts have a game, how long
+//! each played it and when last.
+//!
+//! Nothing on disk lists what an account owns without signing in: the
+//! client's own license cache is encrypted per account. The index is built
+//! from what each account leaves behind on this machine instead:
+//! - the `apps` section of its localconfig.vdf, with `LastPlayed` and
+//!   `Playtime` for every app it launched here;
+//! - its `userdata/<id>/<appid>` folders, where games keep settings and
+//!   cloud files;
+//! - the appmanifests of installed games, whose `LastOwner` names the
+//!   account whose license the install runs on.
+//!
+//! A game bought but never launched or installed here is therefore missing.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::accounts::{
+    installed_apps, list_account_games, load_app_names, steam_user_data_path, InstalledApp,
+    SteamAccount, NON_GAME_APP_IDS,
+};
+use super::vdf::read_document;
+
+const APPS_PATH: &[&str] = &["UserLocalConfigStore", "Software", "Valve", "Steam", "apps"];
+
+/// One game in one account's library.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryGame {
+    pub app_id: u32,
+    /// Empty when neither a manifest nor the appinfo cache names it.
+    pub name: String,
+    /// Unix seconds; `None` when never launched on this machine.
+    pub last_played: Option<u64>,
+    /// Minutes, as Steam counts them on this machine.
+    pub playtime_minutes: u64,
+    /// Installed here on this account's license.
+    pub installed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountLibrary {
+    pub steam_id: String,
+    pub account_name: String,
+    pub persona_name: String,
+    pub games: Vec<LibraryGame>,
+    /// Why the account's localconfig.vdf could not be read. Its games from
+    /// the other sources are still listed, without play times.
+    pub error: Option<String>,
+}
+
+/// An account that has a given game, for the reverse lookup.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppOwner {
+    pub steam_id: String,
+    pub account_name: String,
+    pub last_played: Option<u64>,
+    pub playtime_minutes: u64,
+    pub installed: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LibrarySort {
+    /// Most recently played first; never-played games last, by name.
+    #[default]
+    LastPlayed,
+    Name,
+    /// Most played first.
+    Playtime,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryIndex {
+    pub accounts: Vec<AccountLibrary>,
+}
+
+impl LibraryIndex {
+    /// Indexes `accounts`, each game sorted most recently played first.
+    pub fn build(steam_path: &Path, accounts: &[SteamAccount]) -> Self {
+        let installed = installed_apps(steam_path);
+        let mut libraries: Vec<AccountLibrary> = accounts
+            .iter()
+            .map(|account| read_account(steam_path, account, &installed))
+            .collect();
+
+        let app_ids: HashSet<String> = libraries
+            .iter()
+            .flat_map(|library| library.games.iter().map(|game| game.app_id.to_string()))
+            .collect();
+        let names = load_app_names(steam_path, &app_ids);
+        for library in &mut libraries {
+            for game in &mut library.games {
+                if let Some(name) = names.get(&game.app_id.to_string()) {
+                    game.name = name.clone();
+                }
+            }
+            sort_games(&mut library.games, LibrarySort::LastPlayed);
+        }
+        Self {
+            accounts: libraries,
+        }
+    }
+
+    pub fn account(&self, steam_id: &str) -> Option<&AccountLibrary> {
+        self.accounts
+            .iter()
+            .find(|library| library.steam_id == steam_id)
+    }
+
+    /// The accounts that have `app_id`, most recently played first.
+    pub fn owners(&self, app_id: u32) -> Vec<AppOwner> {
+        let mut owners: Vec<AppOwner> = self
+            .accounts
+            .iter()
+            .filter_map(|library| {
+                let game = library.games.iter().find(|game| game.app_id == app_id)?;
+                Some(AppOwner {
+                    steam_id: library.steam_id.clone(),
+                    account_name: library.account_name.clone(),
+                    last_played: game.last_played,
+                    playtime_minutes: game.playtime_minutes,
+                    installed: game.installed,
+                })
+            })
+            .collect();
+        owners.sort_by(|a, b| {
+            b.last_played
+                .cmp(&a.last_played)
+                .then_with(|| a.account_name.cmp(&b.account_name))
+        });
+        owners
+    }
+
+    /// The name the index knows `app_id` by, from whichever account has it.
+    pub fn app_name(&self, app_id: u32) -> Option<&str> {
+        self.accounts
+            .iter()
+            .flat_map(|library| &library.games)
+            .find(|game| game.app_id == app_id && !game.name.is_empty())
+            .map(|game| game.name.as_str())
+    }
+}
+
+pub fn sort_games(games: &mut [LibraryGame], sort: LibrarySort) {
+    let by_name = |a: &LibraryGame, b: &LibraryGame| {
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then(a.app_id.cmp(&b.app_id))
+    };
+    match sort {
+        LibrarySort::LastPlayed => games.sort_by(|a, b| {
+            b.last_played
+                .cmp(&a.last_played)
+                .then_with(|| by_name(a, b))
+        }),
+        LibrarySort::Name => games.sort_by(by_name),
+        LibrarySort::Playtime => games.sort_by(|a, b| {
+            b.playtime_minutes
+                .cmp(&a.playtime_minutes)
+                .then_with(|| by_name(a, b))
+        }),
+    }
+}
+
+fn read_account(
+    steam_path: &Path,
+    account: &SteamAccount,
+    installed: &[InstalledApp],
+) -> AccountLibrary {
+    let mut games: HashMap<u32, LibraryGame> = HashMap::new();
+    let mut error = None;
+
+    match steam_user_data_path(steam_path, &account.steam_id) {
+        Ok(userdata) => {
+            let localconfig = userdata.join("config").join("localconfig.vdf");
+            match read_document(&localconfig) {
+                Ok(Some(document)) => {
+                    let apps = document.section(APPS_PATH).into_iter();
+                    for (key, app) in apps.flat_map(|apps| apps.sections()) {
+                        let Ok(app_id) = key.parse::<u32>() else {
+                            continue;
+                        };
+                        let number = |key: &str| {
+                            app.value(key)
+                                .and_then(|value| value.trim().parse::<u64>().ok())
+                                .filter(|value| *value != 0)
+                        };
+                        let game = game_entry(&mut games, app_id);
+                        game.last_played = number("LastPlayed");
+                        game.playtime_minutes = number("Playtime").unwrap_or_default();
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => error = Some(e.to_string()),
+            }
+            if let Ok(folders) = list_account_games(&userdata) {
+                for app_id in folders.iter().filter_map(|id| id.parse().ok()) {
+                    game_entry(&mut games, app_id);
+                }
+            }
+        }
+        Err(e) => error = Some(e.to_string()),
+    }
+    for app in installed
+        .iter()
+        .filter(|app| app.last_owner.as_deref() == Some(account.steam_id.as_str()))
+    {
+        if let Ok(app_id) = app.app_id.parse() {
+            game_entry(&mut games, app_id).installed = true;
+        }
+    }
+
+    AccountLibrary {
+        steam_id: account.steam_id.clone(),
+        account_name: account.account_name.clone(),
+        persona_name: account.persona_name.clone(),
+        games: games
+            .into_values()
+            .filter(|game| !NON_GAME_APP_IDS.contains(&game.app_id.to_string().as_str()))
+            .collect(),
+        error,
+    }
+}
+
+fn game_entry(games: &mut HashMap<u32, LibraryGame>, app_id: u32) -> &mut LibraryGame {
+    games.entry(app_id).or_insert_with(|| LibraryGame {
+        app_id,
+        name: String::new(),
+        last_played: None,
+        playtime_minutes: 0,
+        installed: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    const ALICE: &str = "76561197960265729";
+    const BOB: &str = "76561197960265730";
+
+    fn account(steam_id: &str, name: &str) -> SteamAccount {
+        SteamAccount {
+            steam_id: steam_id.into(),
+            account_name: name.into(),
+            persona_name: name.to_uppercase(),
+            last_login_at: None,
+        }
+    }
+
+    fn localconfig(apps: &[(&str, u64, u64)]) -> String {
+        let mut out = String::from(
+            "\"UserLocalConfigStore\"\n{\n\t\"Software\"\n\t{\n\t\t\"Valve\"\n\t\t{\n\t\t\t\"Steam\"\n\t\t\t{\n\t\t\t\t\"apps\"\n\t\t\t\t{\n",
+        );
+        for (id, last_played, playtime) in apps {
+            out.push_str(&format!(
+                "\t\t\t\t\t\"{id}\"\n\t\t\t\t\t{{\n\t\t\t\t\t\t\"LastPlayed\"\t\t\"{last_played}\"\n\t\t\t\t\t\t\"Playtime\"\t\t\"{playtime}\"\n\t\t\t\t\t}}\n"
+            ));
+        }
+        out.push_str("\t\t\t\t}\n\t\t\t}\n\t\t}\n\t}\n}\n");
+        out
+    }
+
+    fn steam_root(tag: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("accshift-library-{}-{}", tag, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    fn write(path: PathBuf, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn manifest(app_id: u32, name: &str, owner: &str) -> String {
+        format!(
+            "\"AppState\"\n{{\n\t\"appid\"\t\t\"{app_id}\"\n\t\"name\"\t\t\"{name}\"\n\t\"LastOwner\"\t\t\"{owner}\"\n}}\n"
+        )
+    }
+
+    #[test]
+    fn the_index_joins_every_source_per_account() {
+        let root = steam_root("index");
+        write(
+            root.join("userdata/1/config/localconfig.vdf"),
+            &localconfig(&[("730", 1_700_000_000, 600), ("440", 0, 0), ("7", 5, 5)]),
+        );
+        fs::create_dir_all(root.join("userdata/1/570")).unwrap();
+        write(
+            root.join("userdata/2/config/localconfig.vdf"),
+            &localconfig(&[("730", 1_710_000_000, 30)]),
+        );
+        write(
+            root.join("steamapps/appmanifest_730.acf"),
+            &manifest(730, "Counter-Strike 2", ALICE),
+        );
+        write(
+            root.join("steamapps/appmanifest_620.acf"),
+            &manifest(620, "Portal 2", BOB),
+        );
+
+        let index = LibraryIndex::build(&root, &[account(ALICE, "alice"), account(BOB, "bob")]);
+
+        let alice = index.account(ALICE).unwrap();
+        assert_eq!(alice.error, None);
+        let ids: Vec<u32> = alice.games.iter().map(|game| game.app_id).collect();
+        // Played first, then the never-played by name (unnamed sort first);
+        // the Steam client's own app id is left out.
+        assert_eq!(ids, [730, 440, 570]);
+        assert_eq!(
+            alice.games[0],
+            LibraryGame {
+                app_id: 730,
+                name: "Counter-Strike 2".into(),
+                last_played: Some(1_700_000_000),
+                playtime_minutes: 600,
+                installed: true,
+            }
+        );
+        assert_eq!(alice.games[1].last_played, None);
+
+        let bob = index.account(BOB).unwrap();
+        let portal = bob.games.iter().find(|game| game.app_id == 620).unwrap();
+        assert!(portal.installed);
+        assert_eq!(portal.name, "Portal 2");
+
+        // Bob played it last, so comes first; Alice's install does not
+        // count as Bob's.
+        let owners = index.owners(730);
+        let names: Vec<&str> = owners.iter().map(|o| o.account_name.as_str()).collect();
+        assert_eq!(names, ["bob", "alice"]);
+        assert!(!owners[0].installed && owners[1].installed);
+        assert!(index.owners(999).is_empty());
+        assert_eq!(index.app_name(620), Some("Portal 2"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn an_unreadable_localconfig_keeps_the_rest_of_the_account() {
+        let root = steam_root("broken");
+        write(
+            root.join("userdata/1/config/localconfig.vdf"),
+            "\"Broken\"\n{\n",
+        );
+        fs::create_dir_all(root.join("userdata/1/570")).unwrap();
+
+        let index = LibraryIndex::build(&root, &[account(ALICE, "alice")]);
+        let alice = index.account(ALICE).unwrap();
+        assert!(alice.error.is_some());
+        assert_eq!(alice.games.len(), 1);
+        assert_eq!(alice.games[0].app_id, 570);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn games_sort_by_name_or_playtime() {
+        let game = |app_id, name: &str, last_played, playtime_minutes| LibraryGame {
+            app_id,
+            name: name.into(),
+            last_played,
+            playtime_minutes,
+            installed: false,
+        };
+        let mut games = vec![
+            game(1, "beta", None, 50),
+            game(2, "Alpha", Some(10), 5),
+            game(3, "gamma", Some(20), 500),
+        ];
+        sort_games(&mut games, LibrarySort::Name);
+        assert_eq!(
+            games.iter().map(|g| g.app_id).collect::<Vec<_>>(),
+            [2, 1, 3]
+        );
+        sort_games(&mut games, LibrarySort::Playtime);
+        assert_eq!(
+            games.iter().map(|g| g.app_id).collect::<Vec<_>>(),
+            [3, 1, 2]
+        );
+        sort_games(&mut games, LibrarySort::LastPlayed);
+        assert_eq!(
+            games.iter().map(|g| g.app_id).collect::<Vec<_>>(),
+            [3, 2, 1]
+        );
+    }
+}
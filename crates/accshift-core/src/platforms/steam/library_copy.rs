@@ -0,0 +1,972 @@
+//! Non-Steam shortcuts, their artwork and library collections, copied from
+//! one account into others.
+//!
+//! Every target is planned before anything is written: each shortcut and
+//! collection is added, replaces what the target already has, or is skipped
+//! because the target already has exactly that. Applying a plan writes every
+//! file it changes into a staging folder first and then swaps them all in, so
+//! a failure partway leaves the account as it was, as
+//! [`copy_game_settings`](super::accounts::copy_game_settings) does for a
+//! game's folder.
+//!
+//! A shortcut's id is derived from its target and name, and its artwork in
+//! `config/grid` is named after that id. A copied shortcut takes the id the
+//! target account would give it (or the one it already has there), and its
+//! artwork and collection memberships are renamed to match.
+//!
+//! Collections live in the account's cloud storage file, which Steam Cloud
+//! keeps in step with the server. A copied collection is also listed in the
+//! file's `.modified.json` sibling, where Steam queues its own local edits
+//! for upload; without that the next sync takes the cloud's copy for newer
+//! and puts it back. The record's `version` is the server's and is left
+//! alone, as Steam leaves it until the upload is acknowledged.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as Json;
+
+use super::accounts::steam_user_data_path;
+use super::binary_vdf::{self, Section, Value};
+use super::shortcuts::{shortcuts_path, Shortcut};
+use crate::error::AppError;
+
+const COLLECTION_PREFIX: &str = "user-collections.";
+/// Artwork names after the id: `<id>p.png` is the portrait capsule,
+/// `<id>.png` the wide one, then the hero, logo and icon.
+const ARTWORK_SUFFIXES: &[&str] = &["p.", "_hero.", "_logo.", "_icon.", "."];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryCopyRequest {
+    pub from_steam_id: String,
+    pub to_steam_ids: Vec<String>,
+    /// Shortcut app ids, as the source account has them.
+    #[serde(default)]
+    pub shortcut_ids: Vec<u32>,
+    /// Collection ids such as `uc-0123456789ab`, `favorite` or `hidden`.
+    #[serde(default)]
+    pub collection_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LibraryItemKind {
+    Shortcut,
+    Collection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CopyAction {
+    Add,
+    /// The target already has it and it changes: a shortcut is overwritten,
+    /// a collection gains the games it lacks.
+    Replace,
+    /// The target already has exactly this.
+    Skip,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryCopyItem {
+    pub kind: LibraryItemKind,
+    pub name: String,
+    /// The shortcut's app id or the collection's id in the source account.
+    pub source_id: String,
+    /// The id it has in the target account, which for a shortcut may differ.
+    pub target_id: String,
+    pub action: CopyAction,
+    /// The artwork files a shortcut brings, by their name in the target.
+    pub artwork: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryCopyPlan {
+    pub steam_id: String,
+    pub items: Vec<LibraryCopyItem>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryCopyFailure {
+    pub steam_id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryCopyResult {
+    pub applied: Vec<LibraryCopyPlan>,
+    pub failed: Vec<LibraryCopyFailure>,
+}
+
+/// One collection, for choosing what to copy.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    /// The games a collection lists by hand; empty for a dynamic one.
+    pub app_ids: Vec<u64>,
+    /// Filled by a filter rather than by hand.
+    pub dynamic: bool,
+}
+
+/// The id Steam gives a shortcut: a CRC-32 of its target and name, with the
+/// top bit set so it never collides with a Steam app.
+pub fn shortcut_app_id(exe: &str, name: &str) -> u32 {
+    let mut crc = !0u32;
+    for byte in exe.bytes().chain(name.bytes()) {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc | 0x8000_0000
+}
+
+/// The id Big Picture's older artwork is named after.
+fn legacy_artwork_id(app_id: u32) -> u64 {
+    (u64::from(app_id) << 32) | 0x0200_0000
+}
+
+fn collections_path(userdata: &Path) -> PathBuf {
+    userdata
+        .join("config")
+        .join("cloudstorage")
+        .join("cloud-storage-namespace-1.json")
+}
+
+/// The cloud storage keys changed on this machine and not uploaded yet.
+fn modified_keys_path(userdata: &Path) -> PathBuf {
+    userdata
+        .join("config")
+        .join("cloudstorage")
+        .join("cloud-storage-namespace-1.modified.json")
+}
+
+fn grid_dir(userdata: &Path) -> PathBuf {
+    userdata.join("config").join("grid")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// ---------------------------------------------------------------------------
+// Reading
+// ---------------------------------------------------------------------------
+
+fn read_shortcut_root(path: &Path) -> Result<Section, AppError> {
+    match fs::read(path) {
+        Ok(data) => binary_vdf::parse(&data)
+            .map_err(|e| AppError::FileRead(format!("{}: {e}", path.display()))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let mut root = Section::default();
+            root.set("shortcuts", Value::Section(Section::default()));
+            Ok(root)
+        }
+        Err(e) => Err(AppError::FileRead(format!(
+            "Could not read {}: {e}",
+            path.display()
+        ))),
+    }
+}
+
+/// The entries of the cloud storage file, each a `[key, record]` pair, or of
+/// its list of modified keys. Empty when the file does not exist.
+fn read_cloud_storage(path: &Path) -> Result<Vec<Json>, AppError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(AppError::FileRead(format!(
+                "Could not read {}: {e}",
+                path.display()
+            )))
+        }
+    };
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::FileRead(format!("{}: {e}", path.display())))
+}
+
+/// Where collection `id` sits in the cloud storage entries, deleted or not.
+fn collection_index(entries: &[Json], id: &str) -> Option<usize> {
+    let key = format!("{COLLECTION_PREFIX}{id}");
+    entries
+        .iter()
+        .position(|entry| entry.get(0).and_then(Json::as_str) == Some(key.as_str()))
+}
+
+/// A live collection's definition; `None` once it was deleted.
+fn collection_value(entry: &Json) -> Option<Json> {
+    let record = entry.get(1)?;
+    if record.get("is_deleted").and_then(Json::as_bool) == Some(true) {
+        return None;
+    }
+    serde_json::from_str(record.get("value")?.as_str()?).ok()
+}
+
+fn collection_name(id: &str, value: &Json) -> String {
+    value
+        .get("name")
+        .and_then(Json::as_str)
+        .filter(|name| !name.is_empty())
+        .unwrap_or(id)
+        .to_string()
+}
+
+/// The account's collections, in the order its file lists them.
+pub fn list_collections(steam_path: &Path, steam_id: &str) -> Result<Vec<Collection>, AppError> {
+    let userdata = steam_user_data_path(steam_path, steam_id)?;
+    let entries = read_cloud_storage(&collections_path(&userdata))?;
+    Ok(entries
+        .iter()
+        .filter_map(|entry| {
+            let id = entry.get(0)?.as_str()?.strip_prefix(COLLECTION_PREFIX)?;
+            let value = collection_value(entry)?;
+            Some(Collection {
+                id: id.to_string(),
+                name: collection_name(id, &value),
+                app_ids: value
+                    .get("added")
+                    .and_then(Json::as_array)
+                    .map(|ids| ids.iter().filter_map(Json::as_u64).collect())
+                    .unwrap_or_default(),
+                dynamic: value.get("filterSpec").is_some(),
+            })
+        })
+        .collect())
+}
+
+/// What the request takes from the source account, read once for every
+/// target.
+struct Source {
+    grid: PathBuf,
+    shortcuts: Vec<Section>,
+    collections: Vec<(String, Json)>,
+}
+
+impl Source {
+    fn read(steam_path: &Path, request: &LibraryCopyRequest) -> Result<Self, AppError> {
+        let userdata = steam_user_data_path(steam_path, &request.from_steam_id)?;
+        let root = read_shortcut_root(&shortcuts_path(steam_path, &request.from_steam_id)?)?;
+        let listed = root.section("shortcuts").cloned().unwrap_or_default();
+        let shortcuts = request
+            .shortcut_ids
+            .iter()
+            .map(|id| {
+                listed
+                    .sections()
+                    .find(|(_, entry)| Shortcut::from_keyvalues(entry).app_id == *id)
+                    .map(|(_, entry)| entry.clone())
+                    .ok_or_else(|| {
+                        AppError::FileRead(format!("Shortcut {id} is not in the source account"))
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let entries = read_cloud_storage(&collections_path(&userdata))?;
+        let collections = request
+            .collection_ids
+            .iter()
+            .map(|id| {
+                collection_index(&entries, id)
+                    .and_then(|index| collection_value(&entries[index]))
+                    .map(|value| (id.clone(), value))
+                    .ok_or_else(|| {
+                        AppError::FileRead(format!("Collection {id} is not in the source account"))
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            grid: grid_dir(&userdata),
+            shortcuts,
+            collections,
+        })
+    }
+}
+
+/// Shortcut `app_id`'s artwork in `grid`, each file with the name it takes
+/// for `target_id`.
+fn artwork(grid: &Path, app_id: u32, target_id: u32) -> Vec<(PathBuf, String)> {
+    let Ok(entries) = fs::read_dir(grid) else {
+        return Vec::new();
+    };
+    let ids = [
+        (u64::from(app_id), u64::from(target_id)),
+        (legacy_artwork_id(app_id), legacy_artwork_id(target_id)),
+    ];
+    let mut files: Vec<(PathBuf, String)> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            ids.iter().find_map(|(id, target)| {
+                let rest = name.strip_prefix(&id.to_string())?;
+                ARTWORK_SUFFIXES
+                    .iter()
+                    .any(|suffix| rest.starts_with(suffix))
+                    .then(|| (entry.path(), format!("{target}{rest}")))
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    files
+}
+
+// ---------------------------------------------------------------------------
+// Planning
+// ---------------------------------------------------------------------------
+
+/// A target's plan with the file contents that carry it out.
+struct Staged {
+    plan: LibraryCopyPlan,
+    userdata: PathBuf,
+    /// Whole files to write, by their path in the account.
+    writes: Vec<(PathBuf, Vec<u8>)>,
+    /// Artwork to copy: from the source, to the account.
+    copies: Vec<(PathBuf, PathBuf)>,
+}
+
+fn plan_target(steam_path: &Path, source: &Source, to_steam_id: &str) -> Result<Staged, AppError> {
+    let userdata = steam_user_data_path(steam_path, to_steam_id)?;
+    if !userdata.is_dir() {
+        return Err(AppError::UserdataNotFound(userdata.display().to_string()));
+    }
+    let grid = grid_dir(&userdata);
+    let mut staged = Staged {
+        plan: LibraryCopyPlan {
+            steam_id: to_steam_id.to_string(),
+            items: Vec::new(),
+        },
+        userdata: userdata.clone(),
+        writes: Vec::new(),
+        copies: Vec::new(),
+    };
+
+    // Shortcuts.
+    let shortcuts_file = shortcuts_path(steam_path, to_steam_id)?;
+    let mut root = read_shortcut_root(&shortcuts_file)?;
+    let mut renamed: HashMap<u64, u64> = HashMap::new();
+    let mut shortcuts_changed = false;
+    {
+        if root.section("shortcuts").is_none() {
+            root.set("shortcuts", Value::Section(Section::default()));
+        }
+        let listed = root
+            .section_mut("shortcuts")
+            .expect("the shortcuts section was just ensured");
+        for entry in &source.shortcuts {
+            let shortcut = Shortcut::from_keyvalues(entry);
+            let derived = shortcut_app_id(&shortcut.exe, &shortcut.name);
+            let existing = listed.sections().find_map(|(key, found)| {
+                let found = Shortcut::from_keyvalues(found);
+                (found.app_id == derived
+                    || (found.exe == shortcut.exe && found.name == shortcut.name))
+                    .then(|| (key.to_string(), found.app_id))
+            });
+            let target_id = existing.as_ref().map_or(derived, |(_, id)| *id);
+            renamed.insert(u64::from(shortcut.app_id), u64::from(target_id));
+
+            let files = artwork(&source.grid, shortcut.app_id, target_id);
+            let mut copied = entry.clone();
+            copied.set("appid", Value::Int32(target_id as i32));
+            // An icon picked from the grid folder points into the source
+            // account; point it at the copy.
+            if let Some((_, name)) = files
+                .iter()
+                .find(|(from, _)| Path::new(shortcut.icon.trim_matches('"')) == from.as_path())
+            {
+                copied.set(
+                    "icon",
+                    Value::String(grid.join(name).to_string_lossy().into_owned()),
+                );
+            }
+
+            let artwork_same = files
+                .iter()
+                .all(|(from, name)| same_file_contents(from, &grid.join(name)));
+            let action = match &existing {
+                None => {
+                    let next = listed
+                        .entries()
+                        .filter_map(|(key, _)| key.parse::<u64>().ok())
+                        .max()
+                        .map_or(0, |max| max + 1);
+                    listed.set(&next.to_string(), Value::Section(copied));
+                    CopyAction::Add
+                }
+                Some((key, _)) => {
+                    if listed.section(key) == Some(&copied) && artwork_same {
+                        CopyAction::Skip
+                    } else {
+                        listed.set(key, Value::Section(copied));
+                        CopyAction::Replace
+                    }
+                }
+            };
+            if action != CopyAction::Skip {
+                shortcuts_changed = true;
+                staged.copies.extend(
+                    files
+                        .iter()
+                        .map(|(from, name)| (from.clone(), grid.join(name))),
+                );
+            }
+            staged.plan.items.push(LibraryCopyItem {
+                kind: LibraryItemKind::Shortcut,
+                name: shortcut.name.clone(),
+                source_id: shortcut.app_id.to_string(),
+                target_id: target_id.to_string(),
+                action,
+                artwork: files.into_iter().map(|(_, name)| name).collect(),
+            });
+        }
+    }
+    if shortcuts_changed {
+        staged
+            .writes
+            .push((shortcuts_file, binary_vdf::write(&root)));
+    }
+
+    // Collections.
+    let collections_file = collections_path(&userdata);
+    let mut entries = read_cloud_storage(&collections_file)?;
+    let mut modified_keys = Vec::new();
+    for (id, value) in &source.collections {
+        let value = remap_collection(value, &renamed);
+        let key = format!("{COLLECTION_PREFIX}{id}");
+        let index = collection_index(&entries, id);
+        let current = index.and_then(|index| collection_value(&entries[index]));
+        let (action, merged) = match current {
+            None => (CopyAction::Add, value.clone()),
+            Some(current) => match merge_collection(&current, &value) {
+                Some(merged) if merged != current => (CopyAction::Replace, merged),
+                _ => (CopyAction::Skip, current),
+            },
+        };
+        if action != CopyAction::Skip {
+            modified_keys.push(key.clone());
+            let mut record = index
+                .and_then(|index| entries[index].get(1).cloned())
+                .filter(Json::is_object)
+                .unwrap_or_else(|| serde_json::json!({}));
+            let object = record.as_object_mut().expect("filtered to objects");
+            object.remove("is_deleted");
+            object.insert("key".into(), Json::String(key.clone()));
+            object.insert("timestamp".into(), Json::from(now_secs()));
+            object.insert("value".into(), Json::String(merged.to_string()));
+            let entry = Json::Array(vec![Json::String(key), record]);
+            match index {
+                Some(index) => entries[index] = entry,
+                None => entries.push(entry),
+            }
+        }
+        staged.plan.items.push(LibraryCopyItem {
+            kind: LibraryItemKind::Collection,
+            name: collection_name(id, &value),
+            source_id: id.clone(),
+            target_id: id.clone(),
+            action,
+            artwork: Vec::new(),
+        });
+    }
+    if !modified_keys.is_empty() {
+        let text = serde_json::to_string(&entries)
+            .map_err(|e| AppError::FileRead(format!("Could not serialize collections: {e}")))?;
+        staged.writes.push((collections_file, text.into_bytes()));
+
+        let modified_file = modified_keys_path(&userdata);
+        let mut modified = read_cloud_storage(&modified_file)?;
+        for key in modified_keys {
+            if !modified
+                .iter()
+                .any(|listed| listed.as_str() == Some(key.as_str()))
+            {
+                modified.push(Json::String(key));
+            }
+        }
+        let text = serde_json::to_string(&modified)
+            .map_err(|e| AppError::FileRead(format!("Could not serialize collections: {e}")))?;
+        staged.writes.push((modified_file, text.into_bytes()));
+    }
+
+    Ok(staged)
+}
+
+/// The collection with copied shortcuts listed under their new ids.
+fn remap_collection(value: &Json, renamed: &HashMap<u64, u64>) -> Json {
+    let mut value = value.clone();
+    for list in ["added", "removed"] {
+        if let Some(ids) = value.get_mut(list).and_then(Json::as_array_mut) {
+            for id in ids.iter_mut() {
+                if let Some(target) = id.as_u64().and_then(|id| renamed.get(&id)) {
+                    *id = Json::from(*target);
+                }
+            }
+        }
+    }
+    value
+}
+
+/// `current` with `incoming` merged in. A hand-made collection gains the
+/// games it lacks and keeps its own; a dynamic one becomes `incoming`.
+fn merge_collection(current: &Json, incoming: &Json) -> Option<Json> {
+    let (Some(have), Some(add)) = (
+        current.get("added").and_then(Json::as_array),
+        incoming.get("added").and_then(Json::as_array),
+    ) else {
+        return Some(incoming.clone());
+    };
+    let mut merged = current.clone();
+    let mut added = have.clone();
+    added.extend(add.iter().filter(|id| !have.contains(id)).cloned());
+    merged["added"] = Json::Array(added);
+    if let Some(removed) = merged.get_mut("removed").and_then(Json::as_array_mut) {
+        removed.retain(|id| !add.contains(id));
+    }
+    Some(merged)
+}
+
+fn same_file_contents(a: &Path, b: &Path) -> bool {
+    match (fs::read(a), fs::read(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Applying
+// ---------------------------------------------------------------------------
+
+fn validate(request: &LibraryCopyRequest) -> Result<(), AppError> {
+    if request
+        .to_steam_ids
+        .iter()
+        .any(|id| id == &request.from_steam_id)
+    {
+        return Err(AppError::FileRead(
+            "An account's library cannot be copied into itself".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// What copying would do to each target, touching nothing.
+pub fn preview_library_copy(
+    steam_path: &Path,
+    request: &LibraryCopyRequest,
+) -> Result<Vec<LibraryCopyPlan>, AppError> {
+    validate(request)?;
+    let source = Source::read(steam_path, request)?;
+    request
+        .to_steam_ids
+        .iter()
+        .map(|to| plan_target(steam_path, &source, to).map(|staged| staged.plan))
+        .collect()
+}
+
+/// Copies into every target. A target that fails is reported and left as it
+/// was; the others still get their copy.
+pub fn copy_library(
+    steam_path: &Path,
+    request: &LibraryCopyRequest,
+) -> Result<LibraryCopyResult, AppError> {
+    validate(request)?;
+    let source = Source::read(steam_path, request)?;
+    let mut result = LibraryCopyResult {
+        applied: Vec::new(),
+        failed: Vec::new(),
+    };
+    for to in &request.to_steam_ids {
+        match plan_target(steam_path, &source, to).and_then(|staged| {
+            commit(&staged)?;
+            Ok(staged.plan)
+        }) {
+            Ok(plan) => result.applied.push(plan),
+            Err(e) => result.failed.push(LibraryCopyFailure {
+                steam_id: to.clone(),
+                error: e.to_string(),
+            }),
+        }
+    }
+    Ok(result)
+}
+
+/// Writes everything into a staging folder inside the account, then moves
+/// each file into place, keeping what it replaces until all have moved.
+fn commit(staged: &Staged) -> Result<(), AppError> {
+    if staged.writes.is_empty() && staged.copies.is_empty() {
+        return Ok(());
+    }
+    let staging = staged.userdata.join(".library-copy-staging");
+    if staging.exists() {
+        fs::remove_dir_all(&staging).map_err(|e| AppError::FileRead(e.to_string()))?;
+    }
+    let backups = staging.join("backup");
+    fs::create_dir_all(&backups).map_err(|e| AppError::FileRead(e.to_string()))?;
+
+    let mut moves = Vec::new();
+    let prepared = (|| -> std::io::Result<()> {
+        for (index, (target, bytes)) in staged.writes.iter().enumerate() {
+            let file = staging.join(format!("write-{index}"));
+            fs::write(&file, bytes)?;
+            moves.push((file, target.clone()));
+        }
+        for (index, (from, target)) in staged.copies.iter().enumerate() {
+            let file = staging.join(format!("copy-{index}"));
+            fs::copy(from, &file)?;
+            moves.push((file, target.clone()));
+        }
+        Ok(())
+    })();
+    if let Err(e) = prepared {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(AppError::FileRead(e.to_string()));
+    }
+
+    let mut done: Vec<(PathBuf, Option<PathBuf>)> = Vec::new();
+    for (index, (file, target)) in moves.iter().enumerate() {
+        let moved = (|| -> std::io::Result<Option<PathBuf>> {
+            let backup = if target.exists() {
+                let backup = backups.join(index.to_string());
+                fs::rename(target, &backup)?;
+                Some(backup)
+            } else {
+                None
+            };
+            let placed = target
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|()| fs::rename(file, target));
+            if let Err(e) = placed {
+                if let Some(backup) = &backup {
+                    let _ = fs::rename(backup, target);
+                }
+                return Err(e);
+            }
+            Ok(backup)
+        })();
+        match moved {
+            Ok(backup) => done.push((target.clone(), backup)),
+            Err(e) => {
+                for (target, backup) in done.into_iter().rev() {
+                    let _ = fs::remove_file(&target);
+                    if let Some(backup) = backup {
+                        let _ = fs::rename(backup, &target);
+                    }
+                }
+                let _ = fs::remove_dir_all(&staging);
+                return Err(AppError::FileRead(e.to_string()));
+            }
+        }
+    }
+    let _ = fs::remove_dir_all(&staging);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platforms::steam::shortcuts::read_shortcuts;
+
+    // SteamID64s whose account ids are 1, 2 and 3.
+    const FROM: &str = "76561197960265729";
+    const TO: &str = "76561197960265730";
+    const OTHER: &str = "76561197960265731";
+
+    fn test_root(tag: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "accshift-librarycopy-{}-{}",
+            tag,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        for id in ["1", "2", "3"] {
+            fs::create_dir_all(root.join("userdata").join(id).join("config")).unwrap();
+        }
+        root
+    }
+
+    fn shortcut(app_id: u32, name: &str, exe: &str, icon: &str) -> Section {
+        let mut entry = Section::default();
+        entry.set("appid", Value::Int32(app_id as i32));
+        entry.set("AppName", Value::String(name.into()));
+        entry.set("Exe", Value::String(exe.into()));
+        entry.set("icon", Value::String(icon.into()));
+        entry.set("IsHidden", Value::Int32(0));
+        entry.set("tags", Value::Section(Section::default()));
+        entry
+    }
+
+    fn write_shortcuts(root: &Path, steam_id: &str, entries: Vec<Section>) {
+        let mut listed = Section::default();
+        for (index, entry) in entries.into_iter().enumerate() {
+            listed.set(&index.to_string(), Value::Section(entry));
+        }
+        let mut file = Section::default();
+        file.set("shortcuts", Value::Section(listed));
+        fs::write(
+            shortcuts_path(root, steam_id).unwrap(),
+            binary_vdf::write(&file),
+        )
+        .unwrap();
+    }
+
+    fn write_collections(root: &Path, account: &str, collections: &[(&str, Json)]) {
+        let path = collections_path(&root.join("userdata").join(account));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let entries: Vec<Json> = collections
+            .iter()
+            .map(|(id, value)| {
+                let key = format!("{COLLECTION_PREFIX}{id}");
+                serde_json::json!([key, {
+                    "key": key,
+                    "timestamp": 1,
+                    "value": value.to_string(),
+                    "version": "7",
+                }])
+            })
+            .collect();
+        fs::write(path, serde_json::to_string(&entries).unwrap()).unwrap();
+    }
+
+    fn request(shortcut_ids: Vec<u32>, collection_ids: &[&str], to: &[&str]) -> LibraryCopyRequest {
+        LibraryCopyRequest {
+            from_steam_id: FROM.into(),
+            to_steam_ids: to.iter().map(|id| id.to_string()).collect(),
+            shortcut_ids,
+            collection_ids: collection_ids.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn shortcut_ids_match_what_steam_derives() {
+        // CRC-32 of "123456789" is 0xcbf43926; Steam sets the top bit.
+        assert_eq!(shortcut_app_id("1234", "56789"), 0xcbf4_3926);
+        assert_eq!(shortcut_app_id("", ""), 0x8000_0000);
+        assert_eq!(legacy_artwork_id(0x8000_0001), 0x8000_0001_0200_0000);
+    }
+
+    #[test]
+    fn a_copy_adds_shortcuts_with_their_artwork_under_the_targets_id() {
+        let root = test_root("add");
+        let from_grid = grid_dir(&root.join("userdata").join("1"));
+        fs::create_dir_all(&from_grid).unwrap();
+        // The source's id is stale: Steam would derive another for this
+        // target and name, and the copy must use that one.
+        let stale = 0x8000_0042;
+        let derived = shortcut_app_id("\"C:\\emu.exe\"", "Emulator");
+        let icon = from_grid.join(format!("{stale}_icon.png"));
+        write_shortcuts(
+            &root,
+            FROM,
+            vec![
+                shortcut(
+                    stale,
+                    "Emulator",
+                    "\"C:\\emu.exe\"",
+                    &icon.to_string_lossy(),
+                ),
+                shortcut(0x8000_0099, "Not picked", "x", ""),
+            ],
+        );
+        for name in [
+            format!("{stale}p.png"),
+            format!("{stale}_icon.png"),
+            format!("{}.jpg", legacy_artwork_id(stale)),
+            // Another shortcut's artwork whose id merely starts the same.
+            format!("{stale}0p.png"),
+        ] {
+            fs::write(from_grid.join(name), b"art").unwrap();
+        }
+        write_collections(
+            &root,
+            "1",
+            &[(
+                "uc-emus",
+                serde_json::json!({"id": "uc-emus", "name": "Emulators", "added": [stale, 730], "removed": []}),
+            )],
+        );
+
+        let request = request(vec![stale], &["uc-emus"], &[TO, OTHER]);
+        let plans = preview_library_copy(&root, &request).unwrap();
+        assert_eq!(plans.len(), 2);
+        assert_eq!(
+            plans[0].items[0],
+            LibraryCopyItem {
+                kind: LibraryItemKind::Shortcut,
+                name: "Emulator".into(),
+                source_id: stale.to_string(),
+                target_id: derived.to_string(),
+                action: CopyAction::Add,
+                artwork: vec![
+                    format!("{}.jpg", legacy_artwork_id(derived)),
+                    format!("{derived}_icon.png"),
+                    format!("{derived}p.png"),
+                ],
+            }
+        );
+        assert_eq!(plans[0].items[1].action, CopyAction::Add);
+        assert_eq!(plans[0].items[1].name, "Emulators");
+        // A preview writes nothing.
+        assert!(!shortcuts_path(&root, TO).unwrap().exists());
+
+        let result = copy_library(&root, &request).unwrap();
+        assert!(result.failed.is_empty());
+        assert_eq!(result.applied, plans);
+
+        let to = root.join("userdata").join("2");
+        let copied = read_shortcuts(&root, TO).unwrap();
+        assert_eq!(copied.len(), 1);
+        assert_eq!(copied[0].app_id, derived);
+        assert_eq!(
+            Path::new(&copied[0].icon),
+            grid_dir(&to).join(format!("{derived}_icon.png"))
+        );
+        assert!(grid_dir(&to).join(format!("{derived}p.png")).is_file());
+        assert!(!grid_dir(&to).join(format!("{stale}0p.png")).exists());
+        assert!(!to.join(".library-copy-staging").exists());
+        let collections = list_collections(&root, TO).unwrap();
+        assert_eq!(collections[0].app_ids, [u64::from(derived), 730]);
+        assert_eq!(read_shortcuts(&root, OTHER).unwrap().len(), 1);
+
+        // Copying again finds everything already there.
+        let again = preview_library_copy(&root, &request).unwrap();
+        assert!(again[0]
+            .items
+            .iter()
+            .all(|item| item.action == CopyAction::Skip));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_shortcut_the_target_has_is_replaced_in_place_and_a_collection_merged() {
+        let root = test_root("replace");
+        let exe = "/usr/bin/emu";
+        let id = shortcut_app_id(exe, "Emulator");
+        let mut updated = shortcut(id, "Emulator", exe, "");
+        updated.set("LaunchOptions", Value::String("--fullscreen".into()));
+        write_shortcuts(&root, FROM, vec![updated]);
+        write_shortcuts(
+            &root,
+            TO,
+            vec![
+                shortcut(0x8000_0001, "Mine", "/bin/mine", ""),
+                shortcut(id, "Emulator", exe, ""),
+            ],
+        );
+        write_collections(
+            &root,
+            "1",
+            &[
+                (
+                    "favorite",
+                    serde_json::json!({"id": "favorite", "added": [10, 20]}),
+                ),
+                (
+                    "uc-dyn",
+                    serde_json::json!({"id": "uc-dyn", "name": "Recent", "filterSpec": {"a": 1}}),
+                ),
+            ],
+        );
+        let modified = modified_keys_path(&root.join("userdata").join("2"));
+        fs::create_dir_all(modified.parent().unwrap()).unwrap();
+        fs::write(&modified, r#"["user-collections.hidden"]"#).unwrap();
+        write_collections(
+            &root,
+            "2",
+            &[
+                (
+                    "favorite",
+                    serde_json::json!({"id": "favorite", "added": [20, 30], "removed": [10]}),
+                ),
+                (
+                    "uc-dyn",
+                    serde_json::json!({"id": "uc-dyn", "name": "Recent", "filterSpec": {"a": 1}}),
+                ),
+            ],
+        );
+
+        let request = request(vec![id], &["favorite", "uc-dyn"], &[TO]);
+        let result = copy_library(&root, &request).unwrap();
+        let actions: Vec<CopyAction> = result.applied[0]
+            .items
+            .iter()
+            .map(|item| item.action)
+            .collect();
+        assert_eq!(
+            actions,
+            [CopyAction::Replace, CopyAction::Replace, CopyAction::Skip]
+        );
+
+        let shortcuts = read_shortcuts(&root, TO).unwrap();
+        assert_eq!(shortcuts.len(), 2);
+        assert_eq!(shortcuts[0].name, "Mine");
+        assert_eq!(shortcuts[1].launch_options, "--fullscreen");
+
+        let favorite = list_collections(&root, TO)
+            .unwrap()
+            .into_iter()
+            .find(|collection| collection.id == "favorite")
+            .unwrap();
+        // The target's own favourites stay; the source's are added.
+        assert_eq!(favorite.app_ids, [20, 30, 10]);
+        let entries =
+            read_cloud_storage(&collections_path(&root.join("userdata").join("2"))).unwrap();
+        let record = &entries[collection_index(&entries, "favorite").unwrap()][1];
+        // Queued for upload the way Steam queues its own edits, beside what
+        // was queued already; the unchanged collection is not.
+        assert_eq!(record["version"], "7");
+        assert_eq!(
+            read_cloud_storage(&modified).unwrap(),
+            [
+                Json::from("user-collections.hidden"),
+                Json::from("user-collections.favorite")
+            ]
+        );
+        assert!(collection_value(&entries[0]).unwrap()["removed"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn bad_requests_are_refused_before_anything_is_written() {
+        let root = test_root("refuse");
+        write_shortcuts(&root, FROM, vec![shortcut(5, "A", "a", "")]);
+
+        assert!(preview_library_copy(&root, &request(vec![5], &[], &[FROM])).is_err());
+        let missing = preview_library_copy(&root, &request(vec![6], &[], &[TO])).unwrap_err();
+        assert!(missing.to_string().contains("Shortcut 6"), "{missing}");
+        let missing = preview_library_copy(&root, &request(vec![], &["uc-x"], &[TO])).unwrap_err();
+        assert!(missing.to_string().contains("Collection uc-x"), "{missing}");
+
+        // A target with a broken file fails alone.
+        fs::write(shortcuts_path(&root, OTHER).unwrap(), [0x00, b'x']).unwrap();
+        let result = copy_library(&root, &request(vec![5], &[], &[OTHER, TO])).unwrap();
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].steam_id, OTHER);
+        assert_eq!(result.applied[0].steam_id, TO);
+        assert_eq!(
+            fs::read(shortcuts_path(&root, OTHER).unwrap()).unwrap(),
+            [0x00, b'x']
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+}
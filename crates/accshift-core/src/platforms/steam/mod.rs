@@ -1,15 +1,22 @@
 use crate::config;
 use crate::error::{PlatformError, PlatformErrorKind};
 use crate::os;
+use crate::platforms::descriptor::plan::DryRunOperation;
 use crate::platforms::{
     log_platform_error, log_platform_failure, log_platform_info, PlatformService, SetupStatus,
 };
 use crate::{AppContext, AppCtx};
 pub mod accounts;
+pub mod appinfo;
 pub mod bans;
+pub mod binary_vdf;
 pub mod bulk_edit;
 pub mod cs2_bridge;
+pub mod keyvalues;
+pub mod library;
+pub mod library_copy;
 pub mod profile;
+pub mod shortcuts;
 pub mod vdf;
 
 use accounts::{CopyableGame, SteamAccount};
@@ -220,6 +227,75 @@ fn is_force_kill(params: &Value) -> bool {
         .unwrap_or(false)
 }
 
+/// The switch options `params` carries, read the same way for a switch and
+/// for its dry run.
+struct SwitchParams {
+    run_as_admin: bool,
+    launch_options: String,
+    force_kill: bool,
+    /// `online`, `invisible`, or empty to leave the persona state alone.
+    mode: String,
+}
+
+impl SwitchParams {
+    fn read(params: &Value) -> Self {
+        Self {
+            run_as_admin: params
+                .get("runAsAdmin")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            launch_options: params
+                .get("launchOptions")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string(),
+            force_kill: is_force_kill(params),
+            mode: params
+                .get("mode")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string(),
+        }
+    }
+
+    fn check_mode(&self) -> Result<(), PlatformError> {
+        if !self.mode.is_empty() && !["online", "invisible"].contains(&self.mode.as_str()) {
+            return Err("Invalid mode".into());
+        }
+        Ok(())
+    }
+}
+
+/// The SteamID whose persona state a mode switch writes.
+///
+/// Persona state lives in userdata/<account_id>/, keyed by steam id. Callers
+/// that already know it pass it in params (the GUI context menu does);
+/// otherwise resolve it from loginusers.vdf. Unknown id → plain switch. Two
+/// entries can share the same account_name (deleted/recreated account,
+/// hand-edited VDF); the accounts Vec's relative order for ties comes from
+/// HashMap iteration, not something we control, so pick the lowest steam_id
+/// deterministically instead of the first match (which would otherwise vary
+/// run to run).
+fn persona_steam_id(steam_path: &std::path::Path, account_id: &str, params: &Value) -> String {
+    params
+        .get("steamId")
+        .and_then(Value::as_str)
+        .filter(|id| validate_steam_id(id).is_ok())
+        .map(str::to_string)
+        .or_else(|| {
+            accounts::get_accounts_snapshot(steam_path)
+                .ok()
+                .and_then(|(accounts, _)| {
+                    accounts
+                        .into_iter()
+                        .filter(|a| a.account_name == account_id)
+                        .min_by(|a, b| a.steam_id.cmp(&b.steam_id))
+                        .map(|a| a.steam_id)
+                })
+        })
+        .unwrap_or_default()
+}
+
 fn resolve_steam_path(app_handle: &dyn AppContext) -> Result<PathBuf, PlatformError> {
     let cfg = config::load_config(app_handle);
     let override_path = cfg.steam.path_override.trim();
@@ -696,36 +772,86 @@ pub fn bulk_edit(
         "steam.bulk_edit",
         "Bulk edit requested",
         format!(
-            "accounts={} news_popup={:?} dnd={:?} launch_options={}",
+            "accounts={} news_popup={:?} dnd={:?} launch_options={} edits={}",
             request.steam_ids.len(),
             request.news_popup,
             request.do_not_disturb,
-            request.launch_options.len()
+            request.launch_options.len(),
+            request.edits.len()
         ),
     );
-    // Steam keeps localconfig.vdf in memory and rewrites it on exit. Edits
-    // made while it runs are silently lost. Stop it first; it stays closed.
-    match accounts::stop_steam(&steam_path, false)? {
-        accounts::StopOutcome::NeedsElevation => {
-            // Maps to ClientRunning: retry works once the elevated Steam exits.
-            return Err(crate::error::AppError::SteamElevated.into());
-        }
-        accounts::StopOutcome::NotRunning | accounts::StopOutcome::Stopped => {}
-    }
-    let result = bulk_edit::apply_bulk_edit(&steam_path, &request);
+    let backups = bulk_edit_backups_dir(&app_handle)?;
+    stop_steam_before_edit(&steam_path)?;
+    let result = bulk_edit::apply_bulk_edit(&steam_path, &request, &backups);
     log_platform_info(
         &app_handle,
         "steam.bulk_edit",
         "Bulk edit completed",
         format!(
-            "succeeded={} failed={}",
+            "succeeded={} failed={} backup={:?}",
             result.succeeded,
-            result.failed.len()
+            result.failed.len(),
+            result.backup_id
         ),
     );
     Ok(result)
 }
 
+pub fn preview_bulk_edit(
+    app_handle: AppCtx,
+    request: bulk_edit::BulkEditRequest,
+) -> Result<bulk_edit::BulkEditResult, PlatformError> {
+    for steam_id in &request.steam_ids {
+        validate_steam_id(steam_id)?;
+    }
+    let steam_path = resolve_steam_path(&app_handle)?;
+    Ok(bulk_edit::preview_bulk_edit(&steam_path, &request))
+}
+
+pub fn revert_bulk_edit(
+    app_handle: AppCtx,
+    backup_id: String,
+) -> Result<bulk_edit::RevertResult, PlatformError> {
+    let steam_path = resolve_steam_path(&app_handle)?;
+    let backups = bulk_edit_backups_dir(&app_handle)?;
+    stop_steam_before_edit(&steam_path)?;
+    let result = bulk_edit::revert_bulk_edit(&backups, &backup_id)
+        .map_err(|e| log_platform_failure(&app_handle, "steam.revert_bulk_edit", e.into()))?;
+    log_platform_info(
+        &app_handle,
+        "steam.revert_bulk_edit",
+        "Bulk edit reverted",
+        format!(
+            "backup={backup_id} restored={} failed={} conflicts={}",
+            result.restored,
+            result.failed.len(),
+            result.conflicts.len()
+        ),
+    );
+    Ok(result)
+}
+
+/// `<local data>/platforms/steam/bulk-edit-backups`, one folder per batch.
+fn bulk_edit_backups_dir(app_handle: &AppCtx) -> Result<std::path::PathBuf, PlatformError> {
+    Ok(crate::storage::app_local_data_root(app_handle.as_ref())
+        .map_err(crate::error::AppError::FileRead)?
+        .join("platforms")
+        .join("steam")
+        .join("bulk-edit-backups"))
+}
+
+/// Steam keeps its per-account files in memory and rewrites them on exit.
+/// Edits made while it runs are silently lost. Stop it first; it stays closed.
+fn stop_steam_before_edit(steam_path: &std::path::Path) -> Result<(), PlatformError> {
+    match accounts::stop_steam(steam_path, false)? {
+        accounts::StopOutcome::NeedsElevation => {
+            // Maps to ClientRunning: retry works once the elevated Steam exits.
+            Err(crate::error::AppError::SteamElevated.into())
+        }
+        accounts::StopOutcome::NotRunning | accounts::StopOutcome::Stopped => Ok(()),
+    }
+}
+
 pub fn get_account_games(
     app_handle: AppCtx,
     steam_id: String,
@@ -736,6 +862,93 @@ pub fn get_account_games(
         .map_err(|e| log_platform_failure(&app_handle, "steam.get_account_games", e.into()))
 }
 
+pub fn get_shortcuts(
+    app_handle: AppCtx,
+    steam_id: String,
+) -> Result<Vec<shortcuts::Shortcut>, PlatformError> {
+    validate_steam_id(&steam_id)?;
+    let steam_path = resolve_steam_path(&app_handle)?;
+    shortcuts::read_shortcuts(&steam_path, &steam_id)
+        .map_err(|e| log_platform_failure(&app_handle, "steam.get_shortcuts", e.into()))
+}
+
+/// Every known account's games, from what each left on this machine.
+pub fn get_library(app_handle: AppCtx) -> Result<library::LibraryIndex, PlatformError> {
+    let steam_path = resolve_steam_path(&app_handle)?;
+    let accounts = accounts::get_accounts(&steam_path)
+        .map_err(|e| log_platform_failure(&app_handle, "steam.get_library", e.into()))?;
+    Ok(library::LibraryIndex::build(&steam_path, &accounts))
+}
+
+pub fn get_app_owners(
+    app_handle: AppCtx,
+    app_id: u32,
+) -> Result<Vec<library::AppOwner>, PlatformError> {
+    Ok(get_library(app_handle)?.owners(app_id))
+}
+
+pub fn get_collections(
+    app_handle: AppCtx,
+    steam_id: String,
+) -> Result<Vec<library_copy::Collection>, PlatformError> {
+    validate_steam_id(&steam_id)?;
+    let steam_path = resolve_steam_path(&app_handle)?;
+    library_copy::list_collections(&steam_path, &steam_id)
+        .map_err(|e| log_platform_failure(&app_handle, "steam.get_collections", e.into()))
+}
+
+fn validate_library_copy(request: &library_copy::LibraryCopyRequest) -> Result<(), PlatformError> {
+    validate_steam_id(&request.from_steam_id)?;
+    for steam_id in &request.to_steam_ids {
+        validate_steam_id(steam_id)?;
+    }
+    Ok(())
+}
+
+pub fn preview_library_copy(
+    app_handle: AppCtx,
+    request: library_copy::LibraryCopyRequest,
+) -> Result<Vec<library_copy::LibraryCopyPlan>, PlatformError> {
+    validate_library_copy(&request)?;
+    let steam_path = resolve_steam_path(&app_handle)?;
+    library_copy::preview_library_copy(&steam_path, &request)
+        .map_err(|e| log_platform_failure(&app_handle, "steam.preview_library_copy", e.into()))
+}
+
+pub fn copy_library(
+    app_handle: AppCtx,
+    request: library_copy::LibraryCopyRequest,
+) -> Result<library_copy::LibraryCopyResult, PlatformError> {
+    validate_library_copy(&request)?;
+    let steam_path = resolve_steam_path(&app_handle)?;
+    log_platform_info(
+        &app_handle,
+        "steam.copy_library",
+        "Library copy requested",
+        format!(
+            "from={} targets={} shortcuts={} collections={}",
+            request.from_steam_id,
+            request.to_steam_ids.len(),
+            request.shortcut_ids.len(),
+            request.collection_ids.len()
+        ),
+    );
+    stop_steam_before_edit(&steam_path)?;
+    let result = library_copy::copy_library(&steam_path, &request)
+        .map_err(|e| log_platform_failure(&app_handle, "steam.copy_library", e.into()))?;
+    log_platform_info(
+        &app_handle,
+        "steam.copy_library",
+        "Library copy completed",
+        format!(
+            "succeeded={} failed={}",
+            result.applied.len(),
+            result.failed.len()
+        ),
+    );
+    Ok(result)
+}
+
 pub fn open_steam_api_key_page() -> Result<(), PlatformError> {
     os::open_url("https://steamcommunity.com/dev/apikey").map_err(Into::into)
 }
@@ -844,16 +1057,7 @@ impl PlatformService for SteamService {
         params: Value,
     ) -> Result<(), PlatformError> {
         validate_username(account_id)?;
-        let run_as_admin = params
-            .get("runAsAdmin")
-            .and_then(Value::as_bool)
-            .unwrap_or(false);
-        let launch_options = params
-            .get("launchOptions")
-            .and_then(Value::as_str)
-            .unwrap_or("")
-            .to_string();
-        let force_kill = is_force_kill(&params);
+        let switch = SwitchParams::read(&params);
         let steam_path = resolve_steam_path(&app)?;
 
         let auto_login_user = os::get_auto_login_user().unwrap_or_else(|e| format!("<error:{e}>"));
@@ -869,61 +1073,33 @@ impl PlatformService for SteamService {
                 Some(account_id),
                 None,
                 None,
-                run_as_admin,
-                &launch_options,
+                switch.run_as_admin,
+                &switch.launch_options,
             ),
         );
 
         // Optional persona mode (CLI --invisible/--online, GUI context menu).
         // When absent the switch leaves the account's persona state untouched.
-        let mode = params.get("mode").and_then(Value::as_str).unwrap_or("");
-        if !mode.is_empty() && !["online", "invisible"].contains(&mode) {
-            return Err("Invalid mode".into());
-        }
+        switch.check_mode()?;
 
-        let result = if mode.is_empty() {
+        let result = if switch.mode.is_empty() {
             accounts::switch_account(
                 &steam_path,
                 account_id,
-                run_as_admin,
-                &launch_options,
-                force_kill,
+                switch.run_as_admin,
+                &switch.launch_options,
+                switch.force_kill,
             )
         } else {
-            // Persona state lives in userdata/<account_id>/, keyed by steam
-            // id. Callers that already know it pass it in params (the GUI
-            // context menu does); otherwise resolve it from loginusers.vdf.
-            // Unknown id → plain switch. Two entries can share the same
-            // account_name (deleted/recreated account, hand-edited VDF); the
-            // accounts Vec's relative order for ties comes from HashMap
-            // iteration, not something we control, so pick the lowest
-            // steam_id deterministically instead of the first match (which
-            // would otherwise vary run to run).
-            let steam_id = params
-                .get("steamId")
-                .and_then(Value::as_str)
-                .filter(|id| validate_steam_id(id).is_ok())
-                .map(str::to_string)
-                .or_else(|| {
-                    accounts::get_accounts_snapshot(&steam_path)
-                        .ok()
-                        .and_then(|(accounts, _)| {
-                            accounts
-                                .into_iter()
-                                .filter(|a| a.account_name == account_id)
-                                .min_by(|a, b| a.steam_id.cmp(&b.steam_id))
-                                .map(|a| a.steam_id)
-                        })
-                })
-                .unwrap_or_default();
+            let steam_id = persona_steam_id(&steam_path, account_id, &params);
             accounts::switch_account_mode(
                 &steam_path,
                 account_id,
                 &steam_id,
-                mode,
-                run_as_admin,
-                &launch_options,
-                force_kill,
+                &switch.mode,
+                switch.run_as_admin,
+                &switch.launch_options,
+                switch.force_kill,
             )
         }
         .map_err(|e| log_platform_failure(&app, "steam.switch_account", e.into()));
@@ -940,8 +1116,8 @@ impl PlatformService for SteamService {
             Some(account_id),
             None,
             None,
-            run_as_admin,
-            &launch_options,
+            switch.run_as_admin,
+            &switch.launch_options,
         );
 
         match &result {
@@ -1002,6 +1178,54 @@ impl PlatformService for SteamService {
     fn select_path(&self) -> Result<String, PlatformError> {
         select_steam_path()
     }
+
+    /// Only the switch: setup and forget still run straight off loginusers.vdf
+    /// with no plan of their own.
+    fn supports_dry_run(&self, operation: DryRunOperation) -> bool {
+        operation == DryRunOperation::Switch
+    }
+
+    fn dry_run(
+        &self,
+        app: AppCtx,
+        operation: DryRunOperation,
+        account_id: &str,
+        params: Value,
+    ) -> Result<Value, PlatformError> {
+        if operation != DryRunOperation::Switch {
+            return Err(PlatformError::other(format!(
+                "Steam has no {} plan to show",
+                operation.as_str()
+            )));
+        }
+        validate_username(account_id)?;
+        let switch = SwitchParams::read(&params);
+        switch.check_mode()?;
+        let steam_path = resolve_steam_path(&app)?;
+
+        let steam_id = if switch.mode.is_empty() {
+            String::new()
+        } else {
+            persona_steam_id(&steam_path, account_id, &params)
+        };
+        let persona =
+            (!switch.mode.is_empty()).then_some((steam_id.as_str(), switch.mode.as_str()));
+        // A game launch is a switch that ends in `-applaunch`.
+        let app_id = params.get("appId").and_then(Value::as_str);
+        if app_id.is_some_and(|id| id.is_empty() || !id.chars().all(|c| c.is_ascii_digit())) {
+            return Err("Invalid app id".into());
+        }
+        let plan = accounts::plan_switch(
+            &steam_path,
+            account_id,
+            persona,
+            app_id,
+            switch.run_as_admin,
+            &switch.launch_options,
+            switch.force_kill,
+        )?;
+        serde_json::to_value(plan).map_err(|e| PlatformError::other(e.to_string()))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,189 @@
+//! Non-Steam games an account added to its library, from the account's
+//! `config/shortcuts.vdf`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::accounts::steam_user_data_path;
+use super::binary_vdf::{self, ReadError, Section};
+use crate::error::AppError;
+
+/// One non-Steam game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Shortcut {
+    /// The id Steam gave the shortcut; its artwork under `config/grid` is
+    /// named after it.
+    pub app_id: u32,
+    pub name: String,
+    pub exe: String,
+    pub start_dir: String,
+    pub icon: String,
+    pub launch_options: String,
+    pub hidden: bool,
+    /// Unix seconds, when it has ever been played.
+    pub last_played: Option<u32>,
+    pub tags: Vec<String>,
+}
+
+impl Shortcut {
+    pub(crate) fn from_keyvalues(entry: &Section) -> Self {
+        let text = |key: &str| entry.string(key).unwrap_or_default().to_string();
+        let int = |key: &str| entry.int(key).unwrap_or_default();
+        Self {
+            // Written as a signed int32; the id itself is unsigned.
+            app_id: int("appid") as u32,
+            name: text("AppName"),
+            exe: text("Exe"),
+            start_dir: text("StartDir"),
+            icon: text("icon"),
+            launch_options: text("LaunchOptions"),
+            hidden: int("IsHidden") != 0,
+            last_played: u32::try_from(int("LastPlayTime"))
+                .ok()
+                .filter(|at| *at != 0),
+            tags: entry
+                .section("tags")
+                .map(|tags| {
+                    tags.entries()
+                        .filter_map(|(_, tag)| tag.as_str())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+pub(crate) fn shortcuts_path(steam_path: &Path, steam_id: &str) -> Result<PathBuf, AppError> {
+    Ok(steam_user_data_path(steam_path, steam_id)?
+        .join("config")
+        .join("shortcuts.vdf"))
+}
+
+/// The account's shortcuts, in the order Steam lists them. An account that
+/// never added one has no file, which reads as none.
+pub fn read_shortcuts(steam_path: &Path, steam_id: &str) -> Result<Vec<Shortcut>, AppError> {
+    let path = shortcuts_path(steam_path, steam_id)?;
+    let data = match fs::read(&path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(AppError::FileRead(format!(
+                "Could not read {}: {e}",
+                path.display()
+            )))
+        }
+    };
+    parse_shortcuts(&data).map_err(|e| AppError::FileRead(format!("{}: {e}", path.display())))
+}
+
+pub fn parse_shortcuts(data: &[u8]) -> Result<Vec<Shortcut>, ReadError> {
+    let root = binary_vdf::parse(data)?;
+    Ok(root
+        .section("shortcuts")
+        .map(|shortcuts| {
+            shortcuts
+                .sections()
+                .map(|(_, entry)| Shortcut::from_keyvalues(entry))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platforms::steam::binary_vdf::tests::Writer;
+
+    fn shortcuts_vdf() -> Vec<u8> {
+        let mut w = Writer::default();
+        w.open("shortcuts")
+            .open("0")
+            // 0xb2c9f3a1 as Steam writes it, signed.
+            .int("appid", 0xb2c9_f3a1_u32 as i32)
+            .string("AppName", "Heroic")
+            .string("Exe", "\"C:\\Games\\Heroic\\heroic.exe\"")
+            .string("StartDir", "\"C:\\Games\\Heroic\\\"")
+            .string("icon", "")
+            .string("ShortcutPath", "")
+            .string("LaunchOptions", "--no-gui")
+            .int("IsHidden", 0)
+            .int("AllowDesktopConfig", 1)
+            .int("AllowOverlay", 1)
+            .int("OpenVR", 0)
+            .int("LastPlayTime", 1_700_000_000)
+            .open("tags")
+            .string("0", "Launchers")
+            .string("1", "favorite")
+            .close()
+            .close()
+            .open("1")
+            .int("appid", 42)
+            // Older clients spell it in lower case.
+            .string("appname", "Emulator")
+            .string("exe", "/usr/bin/emu")
+            .int("IsHidden", 1)
+            .int("LastPlayTime", 0)
+            .open("tags")
+            .close()
+            .close()
+            .close()
+            .close();
+        w.bytes
+    }
+
+    #[test]
+    fn shortcuts_read_as_typed_records() {
+        let shortcuts = parse_shortcuts(&shortcuts_vdf()).unwrap();
+        assert_eq!(
+            shortcuts,
+            [
+                Shortcut {
+                    app_id: 0xb2c9_f3a1,
+                    name: "Heroic".into(),
+                    exe: "\"C:\\Games\\Heroic\\heroic.exe\"".into(),
+                    start_dir: "\"C:\\Games\\Heroic\\\"".into(),
+                    icon: String::new(),
+                    launch_options: "--no-gui".into(),
+                    hidden: false,
+                    last_played: Some(1_700_000_000),
+                    tags: vec!["Launchers".into(), "favorite".into()],
+                },
+                Shortcut {
+                    app_id: 42,
+                    name: "Emulator".into(),
+                    exe: "/usr/bin/emu".into(),
+                    start_dir: String::new(),
+                    icon: String::new(),
+                    launch_options: String::new(),
+                    hidden: true,
+                    last_played: None,
+                    tags: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn an_account_without_the_file_has_no_shortcuts_and_a_broken_one_is_an_error() {
+        const STEAM_ID: &str = "76561197960265729";
+        let root = std::env::temp_dir().join(format!("accshift-shortcuts-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        assert!(read_shortcuts(&root, STEAM_ID).unwrap().is_empty());
+
+        let path = shortcuts_path(&root, STEAM_ID).unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut data = shortcuts_vdf();
+        fs::write(&path, &data).unwrap();
+        assert_eq!(read_shortcuts(&root, STEAM_ID).unwrap().len(), 2);
+
+        data.truncate(data.len() - 3);
+        fs::write(&path, &data).unwrap();
+        let error = read_shortcuts(&root, STEAM_ID).unwrap_err().to_string();
+        assert!(error.contains("shortcuts.vdf"), "{error}");
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,468 @@
+//! Earlier generations of each account's snapshot.
+//!
+//! A capture used to overwrite the account's snapshot in place, so one bad
+//! capture (a signed-out session, a half-written token file) replaced the only
+//! good copy. A capture now moves the snapshot it replaces to
+//! `<snapshots>/.history/<account>/<captured at>/` before writing anything, and
+//! a switch can be asked to restore one of those generations instead.
+//!
+//! Generations are moved, never copied: on Linux and macOS an encrypted file
+//! holds a keyring token, and two files holding one token would lose their
+//! secret together the first time either was freed.
+
+use crate::error::PlatformError;
+use crate::snapshot_crypto::{free_dir_secrets, read_decrypted_bytes, write_encrypted_bytes};
+use crate::snapshot_manifest::{self, Verification};
+use crate::AppContext;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory, inside a platform's snapshots directory, holding the generations
+/// of every account. Account ids never start with a dot, so it cannot be
+/// taken for one.
+pub const HISTORY_DIR_NAME: &str = ".history";
+
+/// Generations kept per account when the config does not say.
+pub const DEFAULT_GENERATIONS: usize = 3;
+
+/// Upper bound on `snapshot_generations`: each one is a full copy of the
+/// account's session, and nobody reaches for the twentieth.
+pub const MAX_GENERATIONS: usize = 20;
+
+/// One earlier capture, as the generation list reports it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotGeneration {
+    /// When it was captured, in Unix milliseconds. Also what names it: a switch
+    /// asks for a generation by this number.
+    pub captured_at: u64,
+    /// Files the manifest lists. Absent for a capture older than manifests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files: Option<usize>,
+    /// `intact`, `unverified`, `unreadable` or `corrupt`, as
+    /// [`Verification::status`] names them.
+    pub status: &'static str,
+}
+
+/// How many generations the config asks to keep per account.
+pub fn generations_kept(app_handle: &dyn AppContext) -> usize {
+    crate::config::load_config(app_handle)
+        .snapshot_generations
+        .map_or(DEFAULT_GENERATIONS, |kept| {
+            usize::try_from(kept).map_or(MAX_GENERATIONS, |kept| kept.min(MAX_GENERATIONS))
+        })
+}
+
+/// The generation a switch was asked to restore, from the `snapshotGeneration`
+/// of its params.
+pub fn requested_generation(params: &Value) -> Option<u64> {
+    params.get("snapshotGeneration").and_then(Value::as_u64)
+}
+
+/// Where the generations of `account_id` live.
+pub fn history_dir(snapshots_dir: &Path, account_id: &str) -> PathBuf {
+    snapshots_dir.join(HISTORY_DIR_NAME).join(account_id)
+}
+
+/// One capture of an account's snapshot, from the moment the snapshot it
+/// replaces is set aside until [`Capture::finish`] decides what to keep.
+pub struct Capture {
+    dir: PathBuf,
+    history: PathBuf,
+    previous: Option<PathBuf>,
+}
+
+impl Capture {
+    /// Moves the account's snapshot into its history and leaves an empty
+    /// directory in its place for the capture to write into.
+    pub fn begin(snapshots_dir: &Path, account_id: &str) -> Result<Self, String> {
+        let dir = snapshots_dir.join(account_id);
+        let history = history_dir(snapshots_dir, account_id);
+        let previous = set_aside(&dir, &history)?;
+        if let Err(e) = fs::create_dir_all(&dir) {
+            if let Some(previous) = &previous {
+                let _ = fs::rename(previous, &dir);
+            }
+            return Err(format!("Could not create snapshot directory: {e}"));
+        }
+        Ok(Self {
+            dir,
+            history,
+            previous,
+        })
+    }
+
+    /// The directory the capture writes into.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Carries `relative` over from the snapshot this capture replaces, for an
+    /// item that keeps its last capture when there is nothing live to take.
+    /// Written anew rather than copied, so the two files own separate secrets.
+    pub fn keep_previous(&self, relative: &str) -> Result<(), String> {
+        let Some(previous) = &self.previous else {
+            return Ok(());
+        };
+        let source = previous.join(relative);
+        if !source.is_file() {
+            return Ok(());
+        }
+        write_encrypted_bytes(&self.dir.join(relative), &read_decrypted_bytes(&source)?)
+    }
+
+    /// Ends the capture. A failed one is thrown away and the snapshot it
+    /// replaced put back, so the account keeps what it had. A successful one
+    /// keeps the replaced snapshot as a generation, unless it holds exactly
+    /// what was just captured, and trims the history to `keep`.
+    pub fn finish(self, result: Result<(), String>, keep: usize) -> Result<(), String> {
+        if let Err(error) = result {
+            discard(&self.dir);
+            if let Some(previous) = &self.previous {
+                if let Err(e) = fs::rename(previous, &self.dir) {
+                    return Err(format!(
+                        "{error} (the previous snapshot stays in {}: {e})",
+                        previous.display()
+                    ));
+                }
+            }
+            return Err(error);
+        }
+        if let Some(previous) = &self.previous {
+            if same_capture(previous, &self.dir) {
+                discard(previous);
+            }
+        }
+        prune(&self.history, keep);
+        Ok(())
+    }
+}
+
+/// Every generation of `account_id`, newest first.
+pub fn list(snapshots_dir: &Path, account_id: &str) -> Result<Vec<SnapshotGeneration>, String> {
+    Ok(generations(&history_dir(snapshots_dir, account_id))?
+        .into_iter()
+        .map(|(captured_at, dir)| {
+            let verification = snapshot_manifest::verify(&dir);
+            let files = match &verification {
+                Verification::Intact(manifest) | Verification::Mismatch(manifest, _) => {
+                    Some(manifest.files.len())
+                }
+                Verification::Unverified | Verification::ManifestUnreadable(_) => None,
+            };
+            SnapshotGeneration {
+                captured_at,
+                files,
+                status: verification.status(),
+            }
+        })
+        .collect())
+}
+
+/// The directory of one generation, or an error naming the account when it
+/// has no such generation.
+pub fn generation_dir(
+    snapshots_dir: &Path,
+    account_id: &str,
+    generation: u64,
+) -> Result<PathBuf, String> {
+    let dir = history_dir(snapshots_dir, account_id).join(generation.to_string());
+    if dir.is_dir() {
+        Ok(dir)
+    } else {
+        Err(format!(
+            "Account {account_id} has no snapshot generation {generation}. `accshift snapshots list` shows the ones kept."
+        ))
+    }
+}
+
+/// Makes a generation the account's snapshot again, once its manifest vouches
+/// for it. The snapshot it replaces becomes a generation in turn, so going
+/// back is itself undoable.
+pub fn promote(
+    snapshots_dir: &Path,
+    account_id: &str,
+    generation: u64,
+    keep: usize,
+) -> Result<(), PlatformError> {
+    let chosen = generation_dir(snapshots_dir, account_id, generation)?;
+    snapshot_manifest::ensure_restorable(&chosen, account_id)?;
+
+    let dir = snapshots_dir.join(account_id);
+    let history = history_dir(snapshots_dir, account_id);
+    let replaced = set_aside(&dir, &history)?;
+    // An empty directory is all `set_aside` leaves behind, and a rename does
+    // not replace a directory on every OS.
+    let _ = fs::remove_dir(&dir);
+    if let Err(e) = fs::rename(&chosen, &dir) {
+        if let Some(replaced) = &replaced {
+            let _ = fs::rename(replaced, &dir);
+        }
+        return Err(format!("Could not restore snapshot generation {generation}: {e}").into());
+    }
+    prune(&history, keep);
+    Ok(())
+}
+
+/// Frees and removes every generation of `account_id`, for a forget.
+pub fn forget(snapshots_dir: &Path, account_id: &str) {
+    let history = history_dir(snapshots_dir, account_id);
+    if history.exists() {
+        free_dir_secrets(&history);
+        let _ = fs::remove_dir_all(&history);
+    }
+    let _ = fs::remove_dir(snapshots_dir.join(HISTORY_DIR_NAME));
+}
+
+/// Moves the snapshot in `dir` into `history` under its capture time, and says
+/// where it went. A missing or empty `dir` has nothing worth keeping.
+fn set_aside(dir: &Path, history: &Path) -> Result<Option<PathBuf>, String> {
+    let holds_anything = fs::read_dir(dir)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false);
+    if !holds_anything {
+        return Ok(None);
+    }
+    fs::create_dir_all(history)
+        .map_err(|e| format!("Could not create snapshot history directory: {e}"))?;
+    let mut name = captured_at(dir);
+    while history.join(name.to_string()).exists() {
+        name += 1;
+    }
+    let target = history.join(name.to_string());
+    fs::rename(dir, &target).map_err(|e| {
+        format!(
+            "Could not move the previous snapshot into {}: {e}",
+            target.display()
+        )
+    })?;
+    Ok(Some(target))
+}
+
+/// When the snapshot in `dir` was captured: its manifest's word for it, or
+/// the directory's modification time for a capture older than manifests.
+fn captured_at(dir: &Path) -> u64 {
+    if let Ok(Some(manifest)) = snapshot_manifest::read(dir) {
+        return manifest.captured_at;
+    }
+    fs::metadata(dir)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |since| {
+            u64::try_from(since.as_millis()).unwrap_or(u64::MAX)
+        })
+}
+
+/// Whether two snapshots hold the same files with the same content, by their
+/// manifests. One without a manifest is never the same as anything.
+fn same_capture(a: &Path, b: &Path) -> bool {
+    match (snapshot_manifest::read(a), snapshot_manifest::read(b)) {
+        (Ok(Some(a)), Ok(Some(b))) => a.files == b.files,
+        _ => false,
+    }
+}
+
+/// The generations in `history`, newest first. Anything not named by a
+/// capture time is not one and is left alone.
+pub(crate) fn generations(history: &Path) -> Result<Vec<(u64, PathBuf)>, String> {
+    let entries = match fs::read_dir(history) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(format!(
+                "Could not read snapshot history {}: {e}",
+                history.display()
+            ))
+        }
+    };
+    let mut found: Vec<(u64, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter_map(|entry| {
+            let captured_at = entry.file_name().to_str()?.parse().ok()?;
+            Some((captured_at, entry.path()))
+        })
+        .collect();
+    found.sort_by_key(|(captured_at, _)| std::cmp::Reverse(*captured_at));
+    Ok(found)
+}
+
+/// Drops every generation past the newest `keep`.
+fn prune(history: &Path, keep: usize) {
+    let Ok(found) = generations(history) else {
+        return;
+    };
+    for (_, dir) in found.into_iter().skip(keep) {
+        discard(&dir);
+    }
+    // Only succeeds once nothing is left in it.
+    let _ = fs::remove_dir(history);
+    if let Some(parent) = history.parent() {
+        let _ = fs::remove_dir(parent);
+    }
+}
+
+/// Frees the secrets of every file in `dir`, then removes it.
+fn discard(dir: &Path) {
+    free_dir_secrets(dir);
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot_manifest::{ManifestEntry, SnapshotManifest, MANIFEST_FILE_NAME};
+    use sha2::Digest;
+
+    fn scratch_dir(tag: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "accshift-snapshot-history-test-{}-{}-{:?}",
+            tag,
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    /// Writes a plaintext snapshot with a plaintext manifest, so no OS crypto
+    /// backend is touched: both read back the way encrypted ones do.
+    fn write_capture(dir: &Path, captured_at: u64, body: &[u8]) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("session.json"), body).unwrap();
+        let manifest = SnapshotManifest {
+            manifest_version: snapshot_manifest::MANIFEST_VERSION,
+            platform_id: "demo".to_string(),
+            descriptor_version: None,
+            captured_at,
+            files: vec![ManifestEntry {
+                path: "session.json".to_string(),
+                size: body.len() as u64,
+                sha256: sha2::Sha256::digest(body)
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect(),
+            }],
+        };
+        fs::write(
+            dir.join(MANIFEST_FILE_NAME),
+            serde_json::to_vec(&manifest).unwrap(),
+        )
+        .unwrap();
+    }
+
+    fn captured(snapshots: &Path) -> Vec<u64> {
+        generations(&history_dir(snapshots, "a3f0c2d1"))
+            .unwrap()
+            .into_iter()
+            .map(|(captured_at, _)| captured_at)
+            .collect()
+    }
+
+    #[test]
+    fn a_capture_keeps_the_snapshot_it_replaces_and_trims_to_the_retention() {
+        let snapshots = scratch_dir("rotate");
+        let dir = snapshots.join("a3f0c2d1");
+        write_capture(&dir, 100, b"first");
+        for (at, body) in [(200, b"second"), (300, b"third!"), (400, b"fourth")] {
+            let capture = Capture::begin(&snapshots, "a3f0c2d1").unwrap();
+            assert_eq!(fs::read_dir(capture.dir()).unwrap().count(), 0);
+            write_capture(capture.dir(), at, body);
+            capture.finish(Ok(()), 2).unwrap();
+        }
+
+        assert_eq!(fs::read(dir.join("session.json")).unwrap(), b"fourth");
+        assert_eq!(captured(&snapshots), [300, 200]);
+        let _ = fs::remove_dir_all(&snapshots);
+    }
+
+    #[test]
+    fn a_capture_of_unchanged_content_adds_no_generation() {
+        let snapshots = scratch_dir("unchanged");
+        write_capture(&snapshots.join("a3f0c2d1"), 100, b"same");
+        let capture = Capture::begin(&snapshots, "a3f0c2d1").unwrap();
+        write_capture(capture.dir(), 200, b"same");
+        capture.finish(Ok(()), 3).unwrap();
+
+        assert!(captured(&snapshots).is_empty());
+        assert!(!snapshots.join(HISTORY_DIR_NAME).exists());
+        let _ = fs::remove_dir_all(&snapshots);
+    }
+
+    #[test]
+    fn a_failed_capture_puts_the_previous_snapshot_back() {
+        let snapshots = scratch_dir("failed");
+        let dir = snapshots.join("a3f0c2d1");
+        write_capture(&dir, 100, b"good");
+        let capture = Capture::begin(&snapshots, "a3f0c2d1").unwrap();
+        fs::write(capture.dir().join("session.json"), b"half").unwrap();
+        let err = capture.finish(Err("disk full".to_string()), 3).unwrap_err();
+
+        assert_eq!(err, "disk full");
+        assert_eq!(fs::read(dir.join("session.json")).unwrap(), b"good");
+        assert!(captured(&snapshots).is_empty());
+        let _ = fs::remove_dir_all(&snapshots);
+    }
+
+    // Writes through DPAPI; elsewhere the keyring would need a session.
+    #[cfg(windows)]
+    #[test]
+    fn a_kept_item_is_carried_over_from_the_previous_capture() {
+        let snapshots = scratch_dir("carry");
+        let dir = snapshots.join("a3f0c2d1");
+        write_capture(&dir, 100, b"old");
+        fs::write(dir.join("registry_token.txt"), b"token").unwrap();
+        let capture = Capture::begin(&snapshots, "a3f0c2d1").unwrap();
+        capture.keep_previous("registry_token.txt").unwrap();
+        capture.keep_previous("never_captured.txt").unwrap();
+
+        let carried = capture.dir().join("registry_token.txt");
+        assert_eq!(read_decrypted_bytes(&carried).unwrap(), b"token");
+        assert!(!capture.dir().join("never_captured.txt").exists());
+        let _ = fs::remove_dir_all(&snapshots);
+    }
+
+    #[test]
+    fn promoting_a_generation_keeps_the_snapshot_it_replaces() {
+        let snapshots = scratch_dir("promote");
+        let dir = snapshots.join("a3f0c2d1");
+        write_capture(
+            &history_dir(&snapshots, "a3f0c2d1").join("100"),
+            100,
+            b"good",
+        );
+        write_capture(&dir, 200, b"signed-out");
+
+        promote(&snapshots, "a3f0c2d1", 100, 3).unwrap();
+        assert_eq!(fs::read(dir.join("session.json")).unwrap(), b"good");
+        assert_eq!(captured(&snapshots), [200]);
+
+        let err = promote(&snapshots, "a3f0c2d1", 100, 3).unwrap_err();
+        assert!(
+            err.message.contains("no snapshot generation 100"),
+            "{}",
+            err.message
+        );
+        let _ = fs::remove_dir_all(&snapshots);
+    }
+
+    #[test]
+    fn generations_are_listed_newest_first_with_their_status() {
+        let snapshots = scratch_dir("list");
+        let history = history_dir(&snapshots, "a3f0c2d1");
+        write_capture(&history.join("100"), 100, b"older");
+        write_capture(&history.join("300"), 300, b"newer");
+        fs::create_dir_all(history.join("not-a-generation")).unwrap();
+
+        let listed = list(&snapshots, "a3f0c2d1").unwrap();
+        let at: Vec<u64> = listed.iter().map(|g| g.captured_at).collect();
+        assert_eq!(at, [300, 100]);
+        assert_eq!(listed[0].status, "intact");
+        assert_eq!(listed[0].files, Some(1));
+        let _ = fs::remove_dir_all(&snapshots);
+    }
+}
@@ -0,0 +1,482 @@
+//! Integrity manifests for per-account snapshots.
+//!
+//! A snapshot directory is only as good as its least complete file: one
+//! truncated by a full disk, or half-synced by a cloud folder, restores
+//! without error and leaves the launcher on its login screen. Each capture
+//! therefore records every file's plaintext size and SHA-256 next to the
+//! files themselves, and a restore compares against it before touching
+//! anything live.
+//!
+//! The manifest is encrypted like the files it describes: the file list alone
+//! says which launcher holds which session. Snapshots captured before
+//! manifests existed carry none and are restored as they always were.
+
+use crate::error::{PlatformError, PlatformErrorKind};
+use crate::snapshot_crypto::{
+    delete_encrypted_file_secret, read_decrypted_bytes, write_encrypted_bytes,
+};
+use crate::snapshot_history;
+use crate::AppContext;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the manifest inside an account's snapshot directory.
+pub const MANIFEST_FILE_NAME: &str = ".accshift-manifest";
+
+/// Bumped when the manifest layout changes in a way older builds cannot read.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// One snapshot file, described by its decrypted content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEntry {
+    /// Relative to the snapshot directory, `/`-separated on every OS.
+    pub path: String,
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the plaintext.
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotManifest {
+    pub manifest_version: u32,
+    pub platform_id: String,
+    /// `schemaVersion` of the descriptor that captured the snapshot. Absent for
+    /// the hand-written platforms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub descriptor_version: Option<u32>,
+    pub captured_at: u64,
+    pub files: Vec<ManifestEntry>,
+}
+
+/// Why a snapshot does not match its manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// Listed in the manifest, gone from the directory.
+    Missing { path: String },
+    /// In the directory, not in the manifest.
+    Unlisted { path: String },
+    /// Present, but not the size it was captured at.
+    Size {
+        path: String,
+        expected: u64,
+        found: u64,
+    },
+    /// The right size, the wrong bytes.
+    Digest { path: String },
+    /// Could not be read or decrypted at all.
+    Unreadable { path: String, reason: String },
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::Missing { path } => write!(f, "{path} is missing"),
+            Problem::Unlisted { path } => write!(f, "{path} was not part of the capture"),
+            Problem::Size {
+                path,
+                expected,
+                found,
+            } => write!(f, "{path} is {found} bytes, captured at {expected}"),
+            Problem::Digest { path } => write!(f, "{path} changed since it was captured"),
+            Problem::Unreadable { path, reason } => write!(f, "{path} cannot be read: {reason}"),
+        }
+    }
+}
+
+/// What checking one snapshot directory found.
+#[derive(Debug, Clone)]
+pub enum Verification {
+    /// Every file matches the manifest.
+    Intact(SnapshotManifest),
+    /// Captured before manifests existed: nothing to check against.
+    Unverified,
+    /// The manifest itself could not be read or decrypted.
+    ManifestUnreadable(String),
+    /// At least one file does not match.
+    Mismatch(SnapshotManifest, Vec<Problem>),
+}
+
+impl Verification {
+    /// One word for the outcome: `intact`, `unverified`, `unreadable` or
+    /// `corrupt`.
+    pub fn status(&self) -> &'static str {
+        match self {
+            Verification::Intact(_) => "intact",
+            Verification::Unverified => "unverified",
+            Verification::ManifestUnreadable(_) => "unreadable",
+            Verification::Mismatch(..) => "corrupt",
+        }
+    }
+
+    /// What is wrong, in one line, when the snapshot must not be restored.
+    pub fn failure(&self) -> Option<String> {
+        match self {
+            Verification::Intact(_) | Verification::Unverified => None,
+            Verification::ManifestUnreadable(reason) => Some(reason.clone()),
+            Verification::Mismatch(_, problems) => Some(
+                problems
+                    .iter()
+                    .map(Problem::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            ),
+        }
+    }
+}
+
+/// Describes every file under `dir` and writes the result, encrypted, as its
+/// manifest. Run once the capture has written everything else.
+///
+/// Each file is read back and decrypted rather than hashed on the way in, so
+/// the manifest describes what landed on disk, including files a capture kept
+/// from an earlier one.
+pub fn write(
+    dir: &Path,
+    platform_id: &str,
+    descriptor_version: Option<u32>,
+    captured_at: u64,
+) -> Result<SnapshotManifest, String> {
+    let manifest = SnapshotManifest {
+        manifest_version: MANIFEST_VERSION,
+        platform_id: platform_id.to_string(),
+        descriptor_version,
+        captured_at,
+        files: describe(dir)?,
+    };
+    let bytes = serde_json::to_vec(&manifest)
+        .map_err(|e| format!("Could not serialize snapshot manifest: {e}"))?;
+    let path = dir.join(MANIFEST_FILE_NAME);
+    delete_encrypted_file_secret(&path);
+    write_encrypted_bytes(&path, &bytes)?;
+    Ok(manifest)
+}
+
+/// The manifest stored in `dir`, or `None` for a snapshot captured before
+/// manifests existed.
+pub fn read(dir: &Path) -> Result<Option<SnapshotManifest>, String> {
+    let path = dir.join(MANIFEST_FILE_NAME);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let bytes = read_decrypted_bytes(&path)?;
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|e| format!("Could not parse {}: {e}", path.display()))
+}
+
+/// Checks every file under `dir` against its manifest.
+pub fn verify(dir: &Path) -> Verification {
+    let manifest = match read(dir) {
+        Ok(Some(manifest)) => manifest,
+        Ok(None) => return Verification::Unverified,
+        Err(reason) => return Verification::ManifestUnreadable(reason),
+    };
+    let problems = compare(dir, &manifest);
+    if problems.is_empty() {
+        Verification::Intact(manifest)
+    } else {
+        Verification::Mismatch(manifest, problems)
+    }
+}
+
+/// [`verify`], as the error a restore refuses with.
+pub fn ensure_restorable(dir: &Path, account_id: &str) -> Result<(), PlatformError> {
+    let Some(problem) = verify(dir).failure() else {
+        return Ok(());
+    };
+    Err(PlatformError::new(
+        PlatformErrorKind::SnapshotCorrupt,
+        format!(
+            "The snapshot of account {account_id} failed its integrity check and was not restored: {problem}. Sign in to the account again to capture a fresh one."
+        ),
+    ))
+}
+
+/// Every file under `dir` other than the manifest, in a stable order.
+fn describe(dir: &Path) -> Result<Vec<ManifestEntry>, String> {
+    let mut entries = Vec::new();
+    for (relative, path) in snapshot_files(dir)? {
+        let plaintext = read_decrypted_bytes(&path)?;
+        entries.push(ManifestEntry {
+            path: relative,
+            size: plaintext.len() as u64,
+            sha256: sha256_hex(&plaintext),
+        });
+    }
+    Ok(entries)
+}
+
+fn compare(dir: &Path, manifest: &SnapshotManifest) -> Vec<Problem> {
+    let on_disk = match snapshot_files(dir) {
+        Ok(files) => files,
+        Err(reason) => {
+            return vec![Problem::Unreadable {
+                path: ".".to_string(),
+                reason,
+            }]
+        }
+    };
+    let mut problems = Vec::new();
+    for entry in &manifest.files {
+        let Some((_, path)) = on_disk.iter().find(|(relative, _)| *relative == entry.path) else {
+            problems.push(Problem::Missing {
+                path: entry.path.clone(),
+            });
+            continue;
+        };
+        let plaintext = match read_decrypted_bytes(path) {
+            Ok(plaintext) => plaintext,
+            Err(reason) => {
+                problems.push(Problem::Unreadable {
+                    path: entry.path.clone(),
+                    reason,
+                });
+                continue;
+            }
+        };
+        if plaintext.len() as u64 != entry.size {
+            problems.push(Problem::Size {
+                path: entry.path.clone(),
+                expected: entry.size,
+                found: plaintext.len() as u64,
+            });
+        } else if sha256_hex(&plaintext) != entry.sha256 {
+            problems.push(Problem::Digest {
+                path: entry.path.clone(),
+            });
+        }
+    }
+    for (relative, _) in &on_disk {
+        if !manifest.files.iter().any(|entry| entry.path == *relative) {
+            problems.push(Problem::Unlisted {
+                path: relative.clone(),
+            });
+        }
+    }
+    problems
+}
+
+/// Regular files under `dir`, keyed by their `/`-separated relative path.
+/// Reparse points are skipped, as the snapshot copies skip them.
+pub(crate) fn snapshot_files(dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let mut out = Vec::new();
+    collect_files(dir, "", &mut out)?;
+    out.retain(|(relative, _)| relative != MANIFEST_FILE_NAME);
+    out.sort();
+    Ok(out)
+}
+
+fn collect_files(dir: &Path, prefix: &str, out: &mut Vec<(String, PathBuf)>) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Could not read directory {}: {e}", dir.display()))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Could not read directory entry: {e}"))?;
+        if crate::fs_utils::is_reparse_point(&entry) {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let relative = format!("{prefix}{}", entry.file_name().to_string_lossy());
+        if file_type.is_dir() {
+            collect_files(&entry.path(), &format!("{relative}/"), out)?;
+        } else if file_type.is_file() {
+            out.push((relative, entry.path()));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// One account's snapshot, as `accshift diag verify-snapshots` reports it.
+#[derive(Debug, Clone)]
+pub struct AccountAudit {
+    pub platform_id: String,
+    pub account_id: String,
+    /// The capture time of an earlier generation, `None` for the snapshot a
+    /// switch restores.
+    pub generation: Option<u64>,
+    pub dir: PathBuf,
+    pub verification: Verification,
+}
+
+/// Checks every account snapshot of every platform, and every generation kept
+/// of each, without starting or closing anything. Platforms are found on disk
+/// rather than from the list of known ones, so a user descriptor's snapshots
+/// are audited too.
+pub fn audit(app_handle: &dyn AppContext) -> Result<Vec<AccountAudit>, String> {
+    let platforms_dir = crate::storage::app_local_data_root(app_handle)?.join("platforms");
+    let mut audits = Vec::new();
+    for platform_id in sorted_dir_names(&platforms_dir)? {
+        let snapshots = platforms_dir.join(&platform_id).join("snapshots");
+        let mut accounts = sorted_dir_names(&snapshots)?;
+        accounts.retain(|name| name != snapshot_history::HISTORY_DIR_NAME);
+        for account_id in sorted_dir_names(&snapshots.join(snapshot_history::HISTORY_DIR_NAME))? {
+            if !accounts.contains(&account_id) {
+                accounts.push(account_id);
+            }
+        }
+        accounts.sort();
+        for account_id in accounts {
+            let dir = snapshots.join(&account_id);
+            if dir.is_dir() {
+                audits.push(AccountAudit {
+                    verification: verify(&dir),
+                    platform_id: platform_id.clone(),
+                    account_id: account_id.clone(),
+                    generation: None,
+                    dir,
+                });
+            }
+            let history = snapshot_history::history_dir(&snapshots, &account_id);
+            for (captured_at, dir) in snapshot_history::generations(&history)? {
+                audits.push(AccountAudit {
+                    verification: verify(&dir),
+                    platform_id: platform_id.clone(),
+                    account_id: account_id.clone(),
+                    generation: Some(captured_at),
+                    dir,
+                });
+            }
+        }
+    }
+    Ok(audits)
+}
+
+/// Names of the subdirectories of `dir`, sorted. A missing `dir` has none.
+fn sorted_dir_names(dir: &Path) -> Result<Vec<String>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Could not read directory {}: {e}", dir.display())),
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(tag: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "accshift-snapshot-manifest-test-{}-{}-{:?}",
+            tag,
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    /// A manifest describing `dir` as it is now. Built from legacy plaintext
+    /// files so no OS crypto backend is touched.
+    fn manifest_of(dir: &Path) -> SnapshotManifest {
+        SnapshotManifest {
+            manifest_version: MANIFEST_VERSION,
+            platform_id: "demo".to_string(),
+            descriptor_version: Some(1),
+            captured_at: 0,
+            files: describe(dir).unwrap(),
+        }
+    }
+
+    #[test]
+    fn sha256_matches_the_published_test_vector() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn the_manifest_lists_nested_files_with_forward_slashes_and_skips_itself() {
+        let dir = scratch_dir("describe");
+        fs::create_dir_all(dir.join("Local Storage").join("leveldb")).unwrap();
+        fs::write(dir.join("session.json"), b"{}").unwrap();
+        fs::write(
+            dir.join("Local Storage").join("leveldb").join("000003.log"),
+            b"log",
+        )
+        .unwrap();
+        fs::write(dir.join(MANIFEST_FILE_NAME), b"not listed").unwrap();
+
+        let manifest = manifest_of(&dir);
+        let paths: Vec<&str> = manifest.files.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["Local Storage/leveldb/000003.log", "session.json"]);
+        assert_eq!(manifest.files[1].size, 2);
+        assert!(compare(&dir, &manifest).is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn a_truncated_missing_or_stray_file_is_named() {
+        let dir = scratch_dir("compare");
+        fs::write(dir.join("a.dat"), b"0123456789").unwrap();
+        fs::write(dir.join("b.dat"), b"bbbb").unwrap();
+        fs::write(dir.join("c.dat"), b"cccc").unwrap();
+        let manifest = manifest_of(&dir);
+
+        fs::write(dir.join("a.dat"), b"01234").unwrap();
+        fs::write(dir.join("b.dat"), b"BBBB").unwrap();
+        fs::remove_file(dir.join("c.dat")).unwrap();
+        fs::write(dir.join("d.dat"), b"synced in later").unwrap();
+
+        assert_eq!(
+            compare(&dir, &manifest),
+            vec![
+                Problem::Size {
+                    path: "a.dat".into(),
+                    expected: 10,
+                    found: 5
+                },
+                Problem::Digest {
+                    path: "b.dat".into()
+                },
+                Problem::Missing {
+                    path: "c.dat".into()
+                },
+                Problem::Unlisted {
+                    path: "d.dat".into()
+                },
+            ]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn a_snapshot_from_before_manifests_is_still_restorable() {
+        let dir = scratch_dir("legacy");
+        fs::write(dir.join("session.json"), b"{}").unwrap();
+        assert!(matches!(verify(&dir), Verification::Unverified));
+        assert!(ensure_restorable(&dir, "a3f0c2d1").is_ok());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn an_unreadable_manifest_refuses_the_restore_with_its_own_kind() {
+        let dir = scratch_dir("unreadable");
+        // Plaintext passes through undecrypted, so this reads as garbage JSON.
+        fs::write(dir.join(MANIFEST_FILE_NAME), b"\x00\x01").unwrap();
+        let err = ensure_restorable(&dir, "a3f0c2d1").unwrap_err();
+        assert_eq!(err.kind, PlatformErrorKind::SnapshotCorrupt);
+        assert!(err.message.contains("a3f0c2d1"), "{}", err.message);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -180,6 +180,30 @@ pub fn platform_snapshots_dir(
     Ok(target)
 }
 
+/// Where a switch stages the live state it may have to put back
+/// (`<local data>/platforms/rollback`). Per user, unlike the system temp
+/// directory, which Linux and macOS share between users.
+pub fn switch_rollback_dir(app_handle: &dyn AppContext) -> Result<PathBuf, String> {
+    Ok(app_local_data_root(app_handle)?
+        .join("platforms")
+        .join("rollback"))
+}
+
+/// Creates `path` and any missing parents, readable only by the current user
+/// on Unix. On Windows the local data folder is per user already.
+pub fn create_private_dir(path: &Path) -> Result<(), String> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder
+        .create(path)
+        .map_err(|e| format!("Could not create directory {}: {e}", path.display()))
+}
+
 pub fn client_store_path(app_handle: &dyn AppContext, store_id: &str) -> Result<PathBuf, String> {
     let target = match store_id {
         STORE_SETTINGS => Ok(app_config_root(app_handle)?
@@ -149,6 +149,7 @@ pub const ERROR_CODES: &[&str] = &[
     "lock_contended",
     "io",
     "crypto",
+    "snapshot_corrupt",
     // Updater flow.
     "check_failed",
     "download_failed",
@@ -250,6 +251,7 @@ pub fn error_code_for_kind(kind: PlatformErrorKind) -> &'static str {
         PlatformErrorKind::LockContended => "lock_contended",
         PlatformErrorKind::Io => "io",
         PlatformErrorKind::Crypto => "crypto",
+        PlatformErrorKind::SnapshotCorrupt => "snapshot_corrupt",
         PlatformErrorKind::Other => UNKNOWN_CODE,
     }
 }
@@ -321,6 +323,7 @@ mod tests {
             PlatformErrorKind::LockContended,
             PlatformErrorKind::Io,
             PlatformErrorKind::Crypto,
+            PlatformErrorKind::SnapshotCorrupt,
             PlatformErrorKind::Other,
         ] {
             let code = error_code_for_kind(kind);
@@ -0,0 +1,1235 @@
+//! Moving accounts to another machine.
+//!
+//! Snapshots are encrypted for the machine that captured them (DPAPI, or a
+//! keyring entry), so copying the data folder across moves nothing usable. An
+//! export decrypts every account's snapshot, together with its config row and
+//! the client-store entries that go with it (notes, colors, folders,
+//! personas), into one archive sealed with a passphrase. An import opens it
+//! and encrypts everything again for this machine.
+//!
+//! The archive is a short header followed by the sealed payload. The header
+//! carries the Argon2id parameters and salt the key was derived with, and the
+//! XChaCha20-Poly1305 nonce. It is authenticated along with the payload, so a
+//! copy whose parameters were weakened fails to open instead of opening
+//! faster.
+//!
+//! Import never overwrites. An account this machine already lists, or already
+//! holds a snapshot for, is reported as a conflict and left out whole.
+//! Platforms that keep no snapshots (Steam, Battle.net, Roblox) are not part
+//! of an export.
+
+use crate::config::{self, RiotProfileConfig};
+use crate::platforms::descriptor::config_bridge::{self, AccountRecord};
+use crate::platforms::ids;
+use crate::snapshot_crypto::{free_dir_secrets, read_decrypted_bytes, write_encrypted_bytes};
+use crate::snapshot_manifest;
+use crate::storage;
+use crate::AppContext;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng, Payload as Sealed};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// Shortest passphrase an export accepts.
+pub const MIN_PASSPHRASE_CHARS: usize = 8;
+
+const MAGIC: &[u8; 8] = b"ACCSXPRT";
+/// Bumped when the header or the sealing changes.
+const FORMAT_VERSION: u8 = 1;
+/// Bumped when the payload changes in a way older builds cannot read.
+const PAYLOAD_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+/// Magic, format version, three `u32` KDF parameters, salt, nonce.
+const HEADER_LEN: usize = MAGIC.len() + 1 + 3 * 4 + SALT_LEN + NONCE_LEN;
+
+/// Client stores keyed by account id.
+const ACCOUNT_STORES: [&str; 3] = [
+    storage::STORE_ACCOUNT_CARD_NOTES,
+    storage::STORE_ACCOUNT_CARD_COLORS,
+    storage::STORE_ACCOUNT_DEFAULT_GAME,
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransferError {
+    /// The cipher cannot tell a wrong passphrase from an altered archive.
+    #[error("Wrong passphrase, or the archive was altered")]
+    Passphrase,
+
+    #[error("The passphrase needs at least {MIN_PASSPHRASE_CHARS} characters")]
+    WeakPassphrase,
+
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error("{0}")]
+    Io(String),
+}
+
+/// An account an export carried, or an import added.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRef {
+    pub platform_id: String,
+    pub account_id: String,
+    pub label: String,
+}
+
+/// An account left out, and why.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedAccount {
+    pub platform_id: String,
+    pub account_id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSummary {
+    pub accounts: Vec<AccountRef>,
+    /// Accounts whose snapshot failed its integrity check or would not
+    /// decrypt. Exporting them would carry a broken session to the new
+    /// machine.
+    pub skipped: Vec<SkippedAccount>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub exported_at: u64,
+    pub imported: Vec<AccountRef>,
+    pub conflicts: Vec<SkippedAccount>,
+}
+
+/// Argon2id cost, stored in the header so a later build can raise the default
+/// and still open older archives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct KdfParams {
+    memory_kib: u32,
+    iterations: u32,
+    lanes: u32,
+}
+
+impl KdfParams {
+    /// Around a quarter of a second on a desktop, once per export or import.
+    const DEFAULT: KdfParams = KdfParams {
+        memory_kib: 64 * 1024,
+        iterations: 3,
+        lanes: 1,
+    };
+
+    /// Whether an archive's header asks for a cost this build is willing to
+    /// pay. A crafted header could otherwise ask for gigabytes.
+    fn acceptable(&self) -> bool {
+        self.memory_kib <= 1024 * 1024 && self.iterations <= 64 && self.lanes <= 16
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Payload {
+    version: u32,
+    exported_at: u64,
+    platforms: Vec<PlatformEntry>,
+    /// The slice of each client store that concerns the exported accounts.
+    #[serde(default)]
+    stores: BTreeMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlatformEntry {
+    platform_id: String,
+    accounts: Vec<AccountEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountEntry {
+    account_id: String,
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    last_used_at: Option<u64>,
+    #[serde(default)]
+    launch_values: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    fields: BTreeMap<String, String>,
+    /// Riot's profile row, whole: it carries fields no other section has.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    riot_profile: Option<RiotProfileConfig>,
+    /// `None` for an account listed without a snapshot yet.
+    #[serde(default)]
+    snapshot: Option<SnapshotEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotEntry {
+    #[serde(default)]
+    descriptor_version: Option<u32>,
+    /// `None` for a snapshot captured before manifests existed.
+    #[serde(default)]
+    captured_at: Option<u64>,
+    files: Vec<SnapshotFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    /// Relative to the snapshot directory, `/`-separated.
+    path: String,
+    #[serde(with = "base64_bytes")]
+    content: Zeroizing<Vec<u8>>,
+}
+
+impl AccountEntry {
+    fn reference(&self, platform_id: &str) -> AccountRef {
+        AccountRef {
+            platform_id: platform_id.to_string(),
+            account_id: self.account_id.clone(),
+            label: self.label.clone(),
+        }
+    }
+}
+
+/// Writes every account of every snapshot platform to `path`, sealed with
+/// `passphrase`.
+pub fn export(
+    app: &dyn AppContext,
+    path: &Path,
+    passphrase: &str,
+) -> Result<ExportSummary, TransferError> {
+    export_with(app, path, passphrase, KdfParams::DEFAULT)
+}
+
+fn export_with(
+    app: &dyn AppContext,
+    path: &Path,
+    passphrase: &str,
+    params: KdfParams,
+) -> Result<ExportSummary, TransferError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(TransferError::WeakPassphrase);
+    }
+
+    let mut summary = ExportSummary::default();
+    let mut platforms = Vec::new();
+    for (platform_id, rows) in listed_accounts(app) {
+        let snapshots =
+            storage::platform_snapshots_dir(app, &platform_id).map_err(TransferError::Io)?;
+        let mut accounts = Vec::new();
+        for mut account in rows {
+            let skip = |reason: String| SkippedAccount {
+                platform_id: platform_id.clone(),
+                account_id: account.account_id.clone(),
+                reason,
+            };
+            if !is_safe_name(&account.account_id) {
+                summary
+                    .skipped
+                    .push(skip("Not a valid account id".to_string()));
+                continue;
+            }
+            let dir = snapshots.join(&account.account_id);
+            if dir.is_dir() {
+                match read_snapshot(&dir) {
+                    Ok(snapshot) => account.snapshot = Some(snapshot),
+                    Err(reason) => {
+                        summary.skipped.push(skip(reason));
+                        continue;
+                    }
+                }
+            }
+            summary.accounts.push(account.reference(&platform_id));
+            accounts.push(account);
+        }
+        if !accounts.is_empty() {
+            platforms.push(PlatformEntry {
+                platform_id,
+                accounts,
+            });
+        }
+    }
+
+    let payload = Payload {
+        version: PAYLOAD_VERSION,
+        exported_at: crate::platforms::now_unix_ms(),
+        stores: export_stores(app, &platforms)?,
+        platforms,
+    };
+    let plaintext = Zeroizing::new(
+        serde_json::to_vec(&payload)
+            .map_err(|e| TransferError::Invalid(format!("Could not serialize the export: {e}")))?,
+    );
+    let archive = seal(&plaintext, passphrase, params)?;
+    storage::write_bytes_atomic(path, &archive).map_err(TransferError::Io)?;
+    Ok(summary)
+}
+
+/// Adds the accounts in the archive at `path` to this machine. The caller
+/// holds the operation lock: the conflict check and the write must not be
+/// split by a switch or a forget.
+///
+/// A conflict leaves its account out and moves on. A failure to write one
+/// stops the import; the accounts before it are complete and stay.
+pub fn import(
+    app: &dyn AppContext,
+    path: &Path,
+    passphrase: &str,
+) -> Result<ImportReport, TransferError> {
+    let archive = fs::read(path)
+        .map_err(|e| TransferError::Io(format!("Could not read {}: {e}", path.display())))?;
+    let plaintext = open(&archive, passphrase)?;
+    let payload: Payload = serde_json::from_slice(&plaintext)
+        .map_err(|e| TransferError::Invalid(format!("The archive's content is unreadable: {e}")))?;
+    if payload.version > PAYLOAD_VERSION {
+        return Err(TransferError::Invalid(
+            "The archive was written by a newer accshift. Update this one first.".to_string(),
+        ));
+    }
+
+    let mut report = ImportReport {
+        exported_at: payload.exported_at,
+        ..ImportReport::default()
+    };
+    for platform in &payload.platforms {
+        for account in &platform.accounts {
+            match import_account(app, &platform.platform_id, account)? {
+                None => report
+                    .imported
+                    .push(account.reference(&platform.platform_id)),
+                Some(reason) => report.conflicts.push(SkippedAccount {
+                    platform_id: platform.platform_id.clone(),
+                    account_id: account.account_id.clone(),
+                    reason,
+                }),
+            }
+        }
+    }
+    import_stores(app, &payload.stores, &report.imported)?;
+    Ok(report)
+}
+
+/// Every account the config lists on a snapshot platform, without snapshots.
+fn listed_accounts(app: &dyn AppContext) -> Vec<(String, Vec<AccountEntry>)> {
+    let cfg = config::load_config(app);
+    let riot = cfg
+        .riot
+        .profiles
+        .iter()
+        .map(|profile| AccountEntry {
+            account_id: profile.id.trim().to_string(),
+            label: profile.label.trim().to_string(),
+            last_used_at: profile.last_used_at,
+            launch_values: BTreeMap::new(),
+            fields: BTreeMap::new(),
+            riot_profile: Some(profile.clone()),
+            snapshot: None,
+        })
+        .collect();
+
+    let bridged = [ids::UBISOFT, ids::EPIC, ids::GOG, ids::JAGEX, ids::DISCORD]
+        .into_iter()
+        .map(str::to_string)
+        .chain(cfg.custom_platforms.keys().cloned());
+    let mut platforms = vec![(ids::RIOT.to_string(), riot)];
+    for platform_id in bridged {
+        let rows = config_bridge::accounts(app, &platform_id)
+            .into_iter()
+            .map(|record| AccountEntry {
+                account_id: record.account_id,
+                label: record.label,
+                last_used_at: record.last_used_at,
+                launch_values: record.launch_values,
+                fields: record.fields,
+                riot_profile: None,
+                snapshot: None,
+            })
+            .collect();
+        platforms.push((platform_id, rows));
+    }
+    platforms
+}
+
+/// The decrypted files of the snapshot in `dir`, once it passed its
+/// integrity check.
+fn read_snapshot(dir: &Path) -> Result<SnapshotEntry, String> {
+    if let Some(problem) = snapshot_manifest::verify(dir).failure() {
+        return Err(format!(
+            "Its snapshot failed its integrity check: {problem}"
+        ));
+    }
+    let manifest = snapshot_manifest::read(dir)?;
+    let mut files = Vec::new();
+    for (relative, path) in snapshot_manifest::snapshot_files(dir)? {
+        files.push(SnapshotFile {
+            path: relative,
+            content: Zeroizing::new(read_decrypted_bytes(&path)?),
+        });
+    }
+    Ok(SnapshotEntry {
+        descriptor_version: manifest.as_ref().and_then(|m| m.descriptor_version),
+        captured_at: manifest.map(|m| m.captured_at),
+        files,
+    })
+}
+
+/// `None` once the account is added, the conflict otherwise.
+fn import_account(
+    app: &dyn AppContext,
+    platform_id: &str,
+    account: &AccountEntry,
+) -> Result<Option<String>, TransferError> {
+    if !is_safe_name(platform_id) || !is_safe_name(&account.account_id) {
+        return Ok(Some("Not a valid platform or account id".to_string()));
+    }
+    if is_listed(app, platform_id, &account.account_id) {
+        return Ok(Some("Already in this machine's account list".to_string()));
+    }
+    let dir = storage::platform_snapshots_dir(app, platform_id)
+        .map_err(TransferError::Io)?
+        .join(&account.account_id);
+    if dir.exists() {
+        return Ok(Some(
+            "A snapshot is already stored for this account".to_string(),
+        ));
+    }
+
+    if let Some(snapshot) = &account.snapshot {
+        if let Some(file) = snapshot.files.iter().find(|f| !is_safe_relative(&f.path)) {
+            return Ok(Some(format!(
+                "Its snapshot holds a file outside its folder: {}",
+                file.path
+            )));
+        }
+        if let Err(reason) = write_snapshot(&dir, platform_id, snapshot) {
+            discard(&dir);
+            return Err(TransferError::Io(reason));
+        }
+    }
+    if let Err(reason) = add_row(app, platform_id, account) {
+        discard(&dir);
+        return Err(TransferError::Io(reason));
+    }
+    Ok(None)
+}
+
+fn is_listed(app: &dyn AppContext, platform_id: &str, account_id: &str) -> bool {
+    let key = account_id.trim();
+    if platform_id == ids::RIOT {
+        return config::load_config(app)
+            .riot
+            .profiles
+            .iter()
+            .any(|profile| profile.id.trim().eq_ignore_ascii_case(key));
+    }
+    config_bridge::accounts(app, platform_id)
+        .iter()
+        .any(|record| record.account_id.eq_ignore_ascii_case(key))
+}
+
+fn add_row(app: &dyn AppContext, platform_id: &str, account: &AccountEntry) -> Result<(), String> {
+    if platform_id == ids::RIOT {
+        let profile = RiotProfileConfig {
+            id: account.account_id.clone(),
+            ..account
+                .riot_profile
+                .clone()
+                .unwrap_or_else(|| RiotProfileConfig {
+                    label: account.label.clone(),
+                    last_used_at: account.last_used_at,
+                    ..RiotProfileConfig::default()
+                })
+        };
+        return config::update_config(app, |cfg| cfg.riot.profiles.push(profile));
+    }
+    let record = AccountRecord {
+        account_id: account.account_id.clone(),
+        label: account.label.clone(),
+        last_used_at: account.last_used_at,
+        launch_values: account.launch_values.clone(),
+        fields: account.fields.clone(),
+    };
+    config_bridge::add_account(app, platform_id, &record).map(|_| ())
+}
+
+/// Encrypts the snapshot's files for this machine and writes a fresh manifest
+/// over them, keeping the original capture time.
+fn write_snapshot(dir: &Path, platform_id: &str, snapshot: &SnapshotEntry) -> Result<(), String> {
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Could not create directory {}: {e}", dir.display()))?;
+    for file in &snapshot.files {
+        let dest = file
+            .path
+            .split('/')
+            .fold(dir.to_path_buf(), |path, segment| path.join(segment));
+        write_encrypted_bytes(&dest, &file.content)?;
+    }
+    let captured_at = snapshot
+        .captured_at
+        .unwrap_or_else(crate::platforms::now_unix_ms);
+    snapshot_manifest::write(dir, platform_id, snapshot.descriptor_version, captured_at)?;
+    Ok(())
+}
+
+fn discard(dir: &Path) {
+    free_dir_secrets(dir);
+    let _ = fs::remove_dir_all(dir);
+}
+
+/// A platform or account id joined into a path as one component. A leading
+/// dot is refused too: those names are the snapshot directory's own.
+fn is_safe_name(name: &str) -> bool {
+    !name.starts_with('.') && name == name.trim() && is_safe_segment(name)
+}
+
+fn is_safe_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment.len() <= 255
+        && segment != "."
+        && segment != ".."
+        && !segment.chars().any(|c| {
+            c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+        })
+}
+
+fn is_safe_relative(path: &str) -> bool {
+    path.split('/').all(is_safe_segment)
+}
+
+// ---------------------------------------------------------------------------
+// Client stores
+// ---------------------------------------------------------------------------
+
+fn load_store(app: &dyn AppContext, store_id: &str) -> Result<Value, TransferError> {
+    let path = storage::client_store_path(app, store_id).map_err(TransferError::Io)?;
+    Ok(storage::read_json_if_exists::<Value>(&path)
+        .map_err(TransferError::Io)?
+        .unwrap_or(Value::Null))
+}
+
+fn save_store(app: &dyn AppContext, store_id: &str, value: &Value) -> Result<(), TransferError> {
+    storage::save_client_store(app, store_id, value).map_err(TransferError::Io)
+}
+
+/// The entries of each client store that concern the exported accounts, and
+/// nothing about the rest of this machine.
+fn export_stores(
+    app: &dyn AppContext,
+    platforms: &[PlatformEntry],
+) -> Result<BTreeMap<String, Value>, TransferError> {
+    let platform_ids: BTreeSet<&str> = platforms.iter().map(|p| p.platform_id.as_str()).collect();
+    let accounts: BTreeSet<(&str, &str)> = platforms
+        .iter()
+        .flat_map(|p| {
+            p.accounts
+                .iter()
+                .map(|a| (p.platform_id.as_str(), a.account_id.as_str()))
+        })
+        .collect();
+    let account_ids: BTreeSet<&str> = accounts.iter().map(|(_, id)| *id).collect();
+
+    let mut stores = BTreeMap::new();
+    for store_id in ACCOUNT_STORES {
+        let kept = entries_for(&load_store(app, store_id)?, |key| account_ids.contains(key));
+        if !kept.is_empty() {
+            stores.insert(store_id.to_string(), Value::Object(kept));
+        }
+    }
+
+    let folders = load_store(app, storage::STORE_FOLDERS)?;
+    let exported = folder_slice(&folders, &platform_ids, &account_ids);
+    let folder_ids = folder_ids(&exported);
+    if !folder_ids.is_empty()
+        || exported["itemOrder"]
+            .as_object()
+            .is_some_and(|o| !o.is_empty())
+    {
+        let colors = entries_for(
+            &load_store(app, storage::STORE_FOLDER_CARD_COLORS)?,
+            |key| folder_ids.contains(key),
+        );
+        if !colors.is_empty() {
+            stores.insert(
+                storage::STORE_FOLDER_CARD_COLORS.to_string(),
+                Value::Object(colors),
+            );
+        }
+        stores.insert(storage::STORE_FOLDERS.to_string(), exported);
+    }
+
+    let personas: Vec<Value> = load_store(app, storage::STORE_PERSONAS)?
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|persona| {
+            let assignments: Vec<Value> = persona["assignments"]
+                .as_array()?
+                .iter()
+                .filter(
+                    |a| match (a["platformId"].as_str(), a["accountId"].as_str()) {
+                        (Some(platform), Some(id)) => accounts.contains(&(platform, id)),
+                        _ => false,
+                    },
+                )
+                .cloned()
+                .collect();
+            if assignments.is_empty() {
+                return None;
+            }
+            let mut persona = persona.clone();
+            persona["assignments"] = Value::Array(assignments);
+            Some(persona)
+        })
+        .collect();
+    if !personas.is_empty() {
+        stores.insert(storage::STORE_PERSONAS.to_string(), Value::Array(personas));
+    }
+    Ok(stores)
+}
+
+fn entries_for(store: &Value, wanted: impl Fn(&str) -> bool) -> Map<String, Value> {
+    store
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| wanted(key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+fn folder_ids(store: &Value) -> BTreeSet<String> {
+    store["folders"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|folder| folder["id"].as_str().map(str::to_string))
+        .collect()
+}
+
+/// The folders of the exported platforms, and their ordering with every
+/// account that is not exported left out.
+fn folder_slice(store: &Value, platforms: &BTreeSet<&str>, accounts: &BTreeSet<&str>) -> Value {
+    let folders: Vec<Value> = store["folders"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|folder| {
+            folder["platform"]
+                .as_str()
+                .is_some_and(|p| platforms.contains(p))
+        })
+        .cloned()
+        .collect();
+    let kept: BTreeSet<&str> = folders.iter().filter_map(|f| f["id"].as_str()).collect();
+
+    let mut order = Map::new();
+    for (key, refs) in store["itemOrder"].as_object().into_iter().flatten() {
+        let wanted = match key.strip_prefix("root:") {
+            Some(platform) => platforms.contains(platform),
+            None => kept.contains(key.as_str()),
+        };
+        if !wanted {
+            continue;
+        }
+        let refs: Vec<Value> = refs
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|item| match (item["type"].as_str(), item["id"].as_str()) {
+                (Some("account"), Some(id)) => accounts.contains(id),
+                (Some("folder"), Some(id)) => kept.contains(id),
+                _ => false,
+            })
+            .cloned()
+            .collect();
+        order.insert(key.clone(), Value::Array(refs));
+    }
+    json!({
+        "version": store.get("version").cloned().unwrap_or(json!(1)),
+        "folders": folders,
+        "itemOrder": order,
+    })
+}
+
+/// Merges the archive's store entries for the accounts that were imported.
+/// Nothing this machine already holds is replaced: a key, folder or persona
+/// it already has keeps its own value.
+fn import_stores(
+    app: &dyn AppContext,
+    stores: &BTreeMap<String, Value>,
+    imported: &[AccountRef],
+) -> Result<(), TransferError> {
+    let imported_ids: BTreeSet<&str> = imported.iter().map(|a| a.account_id.as_str()).collect();
+
+    for store_id in ACCOUNT_STORES {
+        let Some(theirs) = stores.get(store_id) else {
+            continue;
+        };
+        let mut local = load_store(app, store_id)?;
+        if merge_missing(&mut local, theirs, |key| imported_ids.contains(key)) {
+            save_store(app, store_id, &local)?;
+        }
+    }
+
+    let mut added_folders = BTreeSet::new();
+    if let Some(theirs) = stores.get(storage::STORE_FOLDERS) {
+        let mut local = load_store(app, storage::STORE_FOLDERS)?;
+        added_folders = merge_folders(&mut local, theirs, &imported_ids);
+        save_store(app, storage::STORE_FOLDERS, &local)?;
+    }
+    if let Some(theirs) = stores.get(storage::STORE_FOLDER_CARD_COLORS) {
+        let mut local = load_store(app, storage::STORE_FOLDER_CARD_COLORS)?;
+        if merge_missing(&mut local, theirs, |key| added_folders.contains(key)) {
+            save_store(app, storage::STORE_FOLDER_CARD_COLORS, &local)?;
+        }
+    }
+
+    if let Some(theirs) = stores
+        .get(storage::STORE_PERSONAS)
+        .and_then(Value::as_array)
+    {
+        let mut local = load_store(app, storage::STORE_PERSONAS)?;
+        if !local.is_array() {
+            local = Value::Array(Vec::new());
+        }
+        let known: BTreeSet<String> = local
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p["id"].as_str().map(str::to_string))
+            .collect();
+        let fresh: Vec<Value> = theirs
+            .iter()
+            .filter(|p| p["id"].as_str().is_some_and(|id| !known.contains(id)))
+            .cloned()
+            .collect();
+        if let (false, Some(list)) = (fresh.is_empty(), local.as_array_mut()) {
+            list.extend(fresh);
+            save_store(app, storage::STORE_PERSONAS, &local)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copies the entries of `theirs` that `wanted` selects and `local` lacks.
+/// Returns whether anything was copied.
+fn merge_missing(local: &mut Value, theirs: &Value, wanted: impl Fn(&str) -> bool) -> bool {
+    if !local.is_object() {
+        *local = Value::Object(Map::new());
+    }
+    let Some(local) = local.as_object_mut() else {
+        return false;
+    };
+    let mut changed = false;
+    for (key, value) in theirs.as_object().into_iter().flatten() {
+        if wanted(key) && !local.contains_key(key) {
+            local.insert(key.clone(), value.clone());
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Adds the folders `local` lacks, then files each imported account (and
+/// each added folder) where the archive had it. Returns the ids of the
+/// folders added.
+fn merge_folders(local: &mut Value, theirs: &Value, imported: &BTreeSet<&str>) -> BTreeSet<String> {
+    if !local.is_object() {
+        *local = json!({ "version": 1, "folders": [], "itemOrder": {} });
+    }
+    let known = folder_ids(local);
+    let added: Vec<Value> = theirs["folders"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|f| f["id"].as_str().is_some_and(|id| !known.contains(id)))
+        .cloned()
+        .collect();
+    let added_ids = folder_ids(&json!({ "folders": added }));
+    if !local["folders"].is_array() {
+        local["folders"] = json!([]);
+    }
+    if let Some(folders) = local["folders"].as_array_mut() {
+        folders.extend(added);
+    }
+
+    if !local["itemOrder"].is_object() {
+        local["itemOrder"] = json!({});
+    }
+    for (key, refs) in theirs["itemOrder"].as_object().into_iter().flatten() {
+        if !key.starts_with("root:") && !known.contains(key) && !added_ids.contains(key) {
+            continue;
+        }
+        let list = &mut local["itemOrder"][key];
+        if !list.is_array() {
+            *list = json!([]);
+        }
+        let Some(list) = list.as_array_mut() else {
+            continue;
+        };
+        for item in refs.as_array().into_iter().flatten() {
+            let wanted = match (item["type"].as_str(), item["id"].as_str()) {
+                (Some("account"), Some(id)) => imported.contains(id),
+                (Some("folder"), Some(id)) => added_ids.contains(id),
+                _ => false,
+            };
+            if wanted && !list.contains(item) {
+                list.push(item.clone());
+            }
+        }
+    }
+    added_ids
+}
+
+// ---------------------------------------------------------------------------
+// Sealing
+// ---------------------------------------------------------------------------
+
+fn seal(plaintext: &[u8], passphrase: &str, params: KdfParams) -> Result<Vec<u8>, TransferError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut archive = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+    archive.extend_from_slice(MAGIC);
+    archive.push(FORMAT_VERSION);
+    for value in [params.memory_kib, params.iterations, params.lanes] {
+        archive.extend_from_slice(&value.to_le_bytes());
+    }
+    archive.extend_from_slice(&salt);
+    archive.extend_from_slice(&nonce);
+
+    let key = derive_key(passphrase, &salt, params)?;
+    let sealed = XChaCha20Poly1305::new(Key::from_slice(&key[..]))
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Sealed {
+                msg: plaintext,
+                aad: &archive,
+            },
+        )
+        .map_err(|_| TransferError::Invalid("Could not seal the archive".to_string()))?;
+    archive.extend_from_slice(&sealed);
+    Ok(archive)
+}
+
+fn open(archive: &[u8], passphrase: &str) -> Result<Zeroizing<Vec<u8>>, TransferError> {
+    if archive.len() < HEADER_LEN || !archive.starts_with(MAGIC) {
+        return Err(TransferError::Invalid("Not an accshift export".to_string()));
+    }
+    let (header, sealed) = archive.split_at(HEADER_LEN);
+    if header[MAGIC.len()] != FORMAT_VERSION {
+        return Err(TransferError::Invalid(
+            "The archive was written by a newer accshift. Update this one first.".to_string(),
+        ));
+    }
+    let word = |index: usize| {
+        let at = MAGIC.len() + 1 + 4 * index;
+        u32::from_le_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]])
+    };
+    let params = KdfParams {
+        memory_kib: word(0),
+        iterations: word(1),
+        lanes: word(2),
+    };
+    if !params.acceptable() {
+        return Err(TransferError::Invalid(
+            "The archive asks for a key derivation cost this build refuses".to_string(),
+        ));
+    }
+    let salt_at = MAGIC.len() + 1 + 3 * 4;
+    let salt = &header[salt_at..salt_at + SALT_LEN];
+    let nonce = &header[salt_at + SALT_LEN..];
+
+    let key = derive_key(passphrase, salt, params)?;
+    XChaCha20Poly1305::new(Key::from_slice(&key[..]))
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Sealed {
+                msg: sealed,
+                aad: header,
+            },
+        )
+        .map(Zeroizing::new)
+        .map_err(|_| TransferError::Passphrase)
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    params: KdfParams,
+) -> Result<Zeroizing<[u8; KEY_LEN]>, TransferError> {
+    let argon_params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.lanes,
+        Some(KEY_LEN),
+    )
+    .map_err(|e| TransferError::Invalid(format!("Unusable key derivation parameters: {e}")))?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key[..])
+        .map_err(|e| TransferError::Invalid(format!("Key derivation failed: {e}")))?;
+    Ok(key)
+}
+
+/// Snapshot file content as base64 inside the JSON payload.
+mod base64_bytes {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use zeroize::Zeroizing;
+
+    pub fn serialize<S: Serializer>(
+        bytes: &Zeroizing<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes.as_slice()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Zeroizing<Vec<u8>>, D::Error> {
+        let text = Zeroizing::new(String::deserialize(deserializer)?);
+        STANDARD
+            .decode(text.as_bytes())
+            .map(Zeroizing::new)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platforms::descriptor::test_support::{scratch, TempCtx};
+    use crate::snapshot_manifest::{ManifestEntry, SnapshotManifest, MANIFEST_FILE_NAME};
+    use sha2::Digest;
+
+    /// The smallest cost Argon2 accepts, so the tests do not spend seconds
+    /// deriving keys.
+    const CHEAP: KdfParams = KdfParams {
+        memory_kib: 8,
+        iterations: 1,
+        lanes: 1,
+    };
+
+    fn config_guard() -> std::sync::MutexGuard<'static, ()> {
+        crate::config::config_io_test_mutex()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A plaintext snapshot with a plaintext manifest: reads pass plaintext
+    /// through, so this needs no secret store.
+    fn write_plain_snapshot(dir: &Path, body: &[u8]) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("session.json"), body).unwrap();
+        let manifest = SnapshotManifest {
+            manifest_version: snapshot_manifest::MANIFEST_VERSION,
+            platform_id: ids::GOG.to_string(),
+            descriptor_version: Some(1),
+            captured_at: 500,
+            files: vec![ManifestEntry {
+                path: "session.json".to_string(),
+                size: body.len() as u64,
+                sha256: sha2::Sha256::digest(body)
+                    .iter()
+                    .map(|b| format!("{b:02x}"))
+                    .collect(),
+            }],
+        };
+        fs::write(
+            dir.join(MANIFEST_FILE_NAME),
+            serde_json::to_vec(&manifest).unwrap(),
+        )
+        .unwrap();
+    }
+
+    fn record(account_id: &str, label: &str) -> AccountRecord {
+        AccountRecord {
+            account_id: account_id.to_string(),
+            label: label.to_string(),
+            last_used_at: Some(42),
+            launch_values: BTreeMap::new(),
+            fields: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn a_sealed_archive_opens_with_its_passphrase_only() {
+        let archive = seal(b"payload", "correct horse", CHEAP).unwrap();
+        assert_eq!(&open(&archive, "correct horse").unwrap()[..], b"payload");
+        assert!(matches!(
+            open(&archive, "wrong horse"),
+            Err(TransferError::Passphrase)
+        ));
+        assert!(matches!(
+            open(b"not an archive", "correct horse"),
+            Err(TransferError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn a_header_edited_to_a_cheaper_cost_does_not_open() {
+        // The parameters are authenticated with the payload: lowering them in
+        // a copy is caught, not used.
+        let mut archive = seal(
+            b"payload",
+            "correct horse",
+            KdfParams {
+                memory_kib: 16,
+                ..CHEAP
+            },
+        )
+        .unwrap();
+        archive[MAGIC.len() + 1] = 8;
+        assert!(matches!(
+            open(&archive, "correct horse"),
+            Err(TransferError::Passphrase)
+        ));
+    }
+
+    #[test]
+    fn export_refuses_a_short_passphrase() {
+        let _guard = config_guard();
+        let root = scratch("transfer-short");
+        let ctx = TempCtx { root: root.clone() };
+        assert!(matches!(
+            export_with(&ctx, &root.join("out.accshift"), "short", CHEAP),
+            Err(TransferError::WeakPassphrase)
+        ));
+        assert!(!root.join("out.accshift").exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn export_leaves_out_a_snapshot_that_fails_its_check() {
+        let _guard = config_guard();
+        let root = scratch("transfer-corrupt");
+        let ctx = TempCtx { root: root.clone() };
+        config_bridge::add_account(&ctx, ids::GOG, &record("good", "Good")).unwrap();
+        config_bridge::add_account(&ctx, ids::GOG, &record("bad", "Bad")).unwrap();
+        let snapshots = storage::platform_snapshots_dir(&ctx, ids::GOG).unwrap();
+        write_plain_snapshot(&snapshots.join("good"), b"token");
+        write_plain_snapshot(&snapshots.join("bad"), b"token");
+        fs::write(snapshots.join("bad").join("session.json"), b"trunc").unwrap();
+
+        let path = root.join("out.accshift");
+        let summary = export_with(&ctx, &path, "correct horse", CHEAP).unwrap();
+
+        let exported: Vec<&str> = summary
+            .accounts
+            .iter()
+            .map(|a| a.account_id.as_str())
+            .collect();
+        assert_eq!(exported, vec!["good"]);
+        assert_eq!(summary.skipped.len(), 1);
+        assert_eq!(summary.skipped[0].account_id, "bad");
+
+        let archive = fs::read(&path).unwrap();
+        let payload: Payload =
+            serde_json::from_slice(&open(&archive, "correct horse").unwrap()).unwrap();
+        let snapshot = payload.platforms[0].accounts[0].snapshot.as_ref().unwrap();
+        assert_eq!(snapshot.captured_at, Some(500));
+        assert_eq!(&snapshot.files[0].content[..], b"token");
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn import_reports_conflicts_and_merges_only_what_is_missing() {
+        let _guard = config_guard();
+        let source = scratch("transfer-source");
+        let from = TempCtx {
+            root: source.clone(),
+        };
+        config_bridge::add_account(&from, ids::GOG, &record("shared", "Theirs")).unwrap();
+        config_bridge::add_account(&from, "acme", &record("fresh", "Fresh")).unwrap();
+        config::update_config(&from, |cfg| {
+            cfg.riot.profiles.push(RiotProfileConfig {
+                id: "riot-1".to_string(),
+                label: "Main".to_string(),
+                account_name: "player".to_string(),
+                ..RiotProfileConfig::default()
+            });
+        })
+        .unwrap();
+        storage::save_client_store(
+            &from,
+            storage::STORE_ACCOUNT_CARD_NOTES,
+            &json!({ "fresh": "their note", "shared": "their note", "unrelated": "x" }),
+        )
+        .unwrap();
+        storage::save_client_store(
+            &from,
+            storage::STORE_FOLDERS,
+            &json!({
+                "version": 1,
+                "folders": [{ "id": "f1", "name": "Mains", "parentId": null, "platform": "acme" }],
+                "itemOrder": {
+                    "root:acme": [{ "type": "folder", "id": "f1" }],
+                    "f1": [{ "type": "account", "id": "fresh" }],
+                },
+            }),
+        )
+        .unwrap();
+        storage::save_client_store(
+            &from,
+            storage::STORE_PERSONAS,
+            &json!([
+                { "id": "p1", "name": "Me", "assignments": [{ "platformId": "acme", "accountId": "fresh" }] },
+                { "id": "p2", "name": "Nobody", "assignments": [] },
+            ]),
+        )
+        .unwrap();
+        let path = source.join("out.accshift");
+        export_with(&from, &path, "correct horse", CHEAP).unwrap();
+
+        let target = scratch("transfer-target");
+        let to = TempCtx {
+            root: target.clone(),
+        };
+        config_bridge::add_account(&to, ids::GOG, &record("shared", "Mine")).unwrap();
+        storage::save_client_store(
+            &to,
+            storage::STORE_ACCOUNT_CARD_NOTES,
+            &json!({ "shared": "my note" }),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            import(&to, &path, "wrong horse"),
+            Err(TransferError::Passphrase)
+        ));
+        let report = import(&to, &path, "correct horse").unwrap();
+
+        let imported: Vec<&str> = report
+            .imported
+            .iter()
+            .map(|a| a.account_id.as_str())
+            .collect();
+        assert_eq!(imported, vec!["riot-1", "fresh"]);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].account_id, "shared");
+
+        let gog = config_bridge::accounts(&to, ids::GOG);
+        assert_eq!(gog.len(), 1);
+        assert_eq!(gog[0].label, "Mine", "a conflict never overwrites");
+        let acme = config_bridge::accounts(&to, "acme");
+        assert_eq!(acme[0].label, "Fresh");
+        assert_eq!(acme[0].last_used_at, Some(42));
+        let riot = config::load_config(&to).riot.profiles;
+        assert_eq!(riot[0].account_name, "player");
+
+        let notes = load_store(&to, storage::STORE_ACCOUNT_CARD_NOTES).unwrap();
+        assert_eq!(notes, json!({ "shared": "my note", "fresh": "their note" }));
+        let folders = load_store(&to, storage::STORE_FOLDERS).unwrap();
+        assert_eq!(folders["folders"][0]["id"], "f1");
+        assert_eq!(folders["itemOrder"]["f1"][0]["id"], "fresh");
+        let personas = load_store(&to, storage::STORE_PERSONAS).unwrap();
+        assert_eq!(personas.as_array().unwrap().len(), 1, "{personas}");
+
+        // A second import of the same archive adds nothing.
+        let again = import(&to, &path, "correct horse").unwrap();
+        assert!(again.imported.is_empty());
+        assert_eq!(again.conflicts.len(), 3);
+
+        let _ = fs::remove_dir_all(&source);
+        let _ = fs::remove_dir_all(&target);
+    }
+
+    #[test]
+    fn import_refuses_a_snapshot_file_outside_its_folder() {
+        let _guard = config_guard();
+        let root = scratch("transfer-escape");
+        let ctx = TempCtx { root: root.clone() };
+        let payload = Payload {
+            version: PAYLOAD_VERSION,
+            exported_at: 0,
+            platforms: vec![PlatformEntry {
+                platform_id: ids::GOG.to_string(),
+                accounts: vec![AccountEntry {
+                    account_id: "evil".to_string(),
+                    label: String::new(),
+                    last_used_at: None,
+                    launch_values: BTreeMap::new(),
+                    fields: BTreeMap::new(),
+                    riot_profile: None,
+                    snapshot: Some(SnapshotEntry {
+                        descriptor_version: None,
+                        captured_at: None,
+                        files: vec![SnapshotFile {
+                            path: "../../escaped".to_string(),
+                            content: Zeroizing::new(b"x".to_vec()),
+                        }],
+                    }),
+                }],
+            }],
+            stores: BTreeMap::new(),
+        };
+        let path = root.join("crafted.accshift");
+        let sealed = seal(
+            &serde_json::to_vec(&payload).unwrap(),
+            "correct horse",
+            CHEAP,
+        )
+        .unwrap();
+        fs::write(&path, sealed).unwrap();
+
+        let report = import(&ctx, &path, "correct horse").unwrap();
+        assert!(report.imported.is_empty());
+        assert_eq!(report.conflicts.len(), 1);
+        assert!(config_bridge::accounts(&ctx, ids::GOG).is_empty());
+        let snapshots = storage::platform_snapshots_dir(&ctx, ids::GOG).unwrap();
+        assert!(!snapshots.join("evil").exists());
+        assert!(!snapshots.parent().unwrap().join("escaped").exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn a_snapshot_survives_the_round_trip_and_is_encrypted_again() {
+        let _guard = config_guard();
+        let source = scratch("transfer-round-source");
+        let from = TempCtx {
+            root: source.clone(),
+        };
+        config_bridge::add_account(&from, ids::GOG, &record("acct", "Main")).unwrap();
+        let snapshots = storage::platform_snapshots_dir(&from, ids::GOG).unwrap();
+        write_plain_snapshot(&snapshots.join("acct"), b"token");
+        let path = source.join("out.accshift");
+        export_with(&from, &path, "correct horse", CHEAP).unwrap();
+
+        let target = scratch("transfer-round-target");
+        let to = TempCtx {
+            root: target.clone(),
+        };
+        let report = import(&to, &path, "correct horse").unwrap();
+        assert_eq!(report.imported.len(), 1);
+
+        let dir = storage::platform_snapshots_dir(&to, ids::GOG)
+            .unwrap()
+            .join("acct");
+        let raw = fs::read(dir.join("session.json")).unwrap();
+        assert!(raw.starts_with(crate::snapshot_crypto::ENCRYPTED_HEADER));
+        assert_eq!(
+            read_decrypted_bytes(&dir.join("session.json")).unwrap(),
+            b"token"
+        );
+        let manifest = snapshot_manifest::read(&dir).unwrap().unwrap();
+        assert_eq!(manifest.captured_at, 500);
+        assert!(snapshot_manifest::verify(&dir).failure().is_none());
+
+        let _ = fs::remove_dir_all(&source);
+        let _ = fs::remove_dir_all(&target);
+    }
+}
@@ -0,0 +1,210 @@
+//! Noticing that the user edited a file in one of the folders the app reads.
+//!
+//! The descriptor and theme folders are written by hand, in an editor that
+//! may save a file three times in a second (temp file, rename, metadata). A
+//! watcher that reacted to each of those would reload a half-written file and
+//! report it rejected. So the folders are polled, a change is only reported
+//! once the folder has held still for a quiet period, and what is compared is
+//! a listing, not an event stream: no platform API to disagree between
+//! Windows, macOS and Linux, and nothing to miss while the app was busy.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the folders are listed. Two small directory reads a second.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long a folder has to hold still before a change is reported.
+pub const QUIET_PERIOD: Duration = Duration::from_millis(400);
+
+/// What a folder looked like at one listing: each `*.json` in it, and each
+/// `*.sig` signing one, by name, with its size and modification time. A
+/// missing folder lists as empty, so creating it with a file in it is a change
+/// like any other.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Listing(BTreeMap<OsString, (u64, Option<SystemTime>)>);
+
+impl Listing {
+    fn read(dir: &Path) -> Self {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Self::default();
+        };
+        let files = entries
+            .flatten()
+            .filter(|entry| {
+                Path::new(&entry.file_name())
+                    .extension()
+                    .is_some_and(|ext| {
+                        ext.eq_ignore_ascii_case("json") || ext.eq_ignore_ascii_case("sig")
+                    })
+            })
+            .filter_map(|entry| {
+                let meta = entry.metadata().ok()?;
+                meta.is_file()
+                    .then(|| (entry.file_name(), (meta.len(), meta.modified().ok())))
+            })
+            .collect();
+        Self(files)
+    }
+}
+
+/// A set of folders polled for edits to the `*.json` files in them and their
+/// signatures.
+///
+/// [`FolderWatch::poll`] does one listing and never sleeps, so the debounce
+/// can be driven by a test clock; [`FolderWatch::run`] is the loop a caller
+/// puts on its own thread.
+pub struct FolderWatch {
+    dirs: Vec<PathBuf>,
+    seen: Vec<Listing>,
+    changed: Vec<bool>,
+    last_change: Option<Instant>,
+    quiet: Duration,
+}
+
+impl FolderWatch {
+    /// Starts from the folders as they are now: what is already there is not
+    /// a change.
+    pub fn new(dirs: Vec<PathBuf>, quiet: Duration) -> Self {
+        let seen = dirs.iter().map(|dir| Listing::read(dir)).collect();
+        let changed = vec![false; dirs.len()];
+        Self {
+            dirs,
+            seen,
+            changed,
+            last_change: None,
+            quiet,
+        }
+    }
+
+    /// Lists every folder once. Returns the indices, in the order the folders
+    /// were given, of those that changed and have since held still for the
+    /// quiet period; `None` while nothing has, or while a folder is still
+    /// being written to.
+    pub fn poll(&mut self, now: Instant) -> Option<Vec<usize>> {
+        for (index, dir) in self.dirs.iter().enumerate() {
+            let listing = Listing::read(dir);
+            if listing != self.seen[index] {
+                self.seen[index] = listing;
+                self.changed[index] = true;
+                self.last_change = Some(now);
+            }
+        }
+
+        let settled = self
+            .last_change
+            .is_some_and(|at| now.duration_since(at) >= self.quiet);
+        if !settled {
+            return None;
+        }
+        self.last_change = None;
+        let changed = self
+            .changed
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, flag)| std::mem::take(flag).then_some(index))
+            .collect();
+        Some(changed)
+    }
+
+    /// Polls every `interval` for as long as the process lives, handing each
+    /// settled change to `on_change`.
+    pub fn run(mut self, interval: Duration, mut on_change: impl FnMut(&[usize])) -> ! {
+        loop {
+            std::thread::sleep(interval);
+            if let Some(changed) = self.poll(Instant::now()) {
+                on_change(&changed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tmp(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "accshift-watch-test-{}-{}",
+            tag,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    const QUIET: Duration = Duration::from_millis(400);
+
+    #[test]
+    fn what_was_there_at_the_start_is_not_a_change() {
+        let dir = tmp("start");
+        std::fs::write(dir.join("acme.json"), "{}").unwrap();
+        let mut watch = FolderWatch::new(vec![dir.clone()], QUIET);
+        let start = Instant::now();
+
+        assert_eq!(watch.poll(start), None);
+        assert_eq!(watch.poll(start + QUIET * 3), None);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn a_change_is_reported_once_the_folder_holds_still() {
+        let dir = tmp("settle");
+        let mut watch = FolderWatch::new(vec![dir.clone()], QUIET);
+        let start = Instant::now();
+
+        std::fs::write(dir.join("acme.json"), "{").unwrap();
+        assert_eq!(watch.poll(start), None, "the editor may still be writing");
+        std::fs::write(dir.join("acme.json"), "{}").unwrap();
+        assert_eq!(
+            watch.poll(start + QUIET / 2),
+            None,
+            "a second write restarts the wait"
+        );
+        assert_eq!(watch.poll(start + QUIET), None);
+        assert_eq!(watch.poll(start + QUIET / 2 + QUIET), Some(vec![0]));
+        assert_eq!(watch.poll(start + QUIET * 4), None, "reported once");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn only_the_folders_that_changed_are_named() {
+        let descriptors = tmp("descriptors");
+        let themes = tmp("themes");
+        let mut watch = FolderWatch::new(vec![descriptors.clone(), themes.clone()], QUIET);
+        let start = Instant::now();
+
+        std::fs::write(themes.join("dusk.json"), "{}").unwrap();
+        std::fs::write(themes.join("notes.txt"), "not a theme").unwrap();
+        assert_eq!(watch.poll(start), None);
+        assert_eq!(watch.poll(start + QUIET), Some(vec![1]));
+
+        std::fs::remove_file(themes.join("dusk.json")).unwrap();
+        assert_eq!(watch.poll(start + QUIET * 2), None);
+        assert_eq!(
+            watch.poll(start + QUIET * 3),
+            Some(vec![1]),
+            "a deletion is a change"
+        );
+        let _ = std::fs::remove_dir_all(descriptors);
+        let _ = std::fs::remove_dir_all(themes);
+    }
+
+    #[test]
+    fn a_folder_created_after_the_start_is_watched_too() {
+        let root = tmp("created");
+        let dir = root.join("descriptors");
+        let mut watch = FolderWatch::new(vec![dir.clone()], QUIET);
+        let start = Instant::now();
+
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(watch.poll(start), None, "an empty folder is what was there");
+        std::fs::write(dir.join("acme.json"), "{}").unwrap();
+        assert_eq!(watch.poll(start + QUIET), None);
+        assert_eq!(watch.poll(start + QUIET * 2), Some(vec![0]));
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
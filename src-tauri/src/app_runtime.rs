@@ -1,6 +1,6 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 
 #[derive(Default)]
 pub struct BootState {
@@ -26,3 +26,53 @@ pub fn show_main_window(app_handle: &tauri::AppHandle) -> Result<(), String> {
     let _ = main_window.set_focus();
     Ok(())
 }
+
+/// Event carrying a fresh [`crate::platforms::UserPlatformReport`] after the
+/// descriptor folder changed on disk.
+pub const USER_PLATFORMS_CHANGED: &str = "user-platforms-changed";
+
+/// Event carrying the full custom theme list after the themes folder changed.
+pub const CUSTOM_THEMES_CHANGED: &str = "custom-themes-changed";
+
+/// Watches the descriptor and theme folders for the life of the app, and
+/// pushes what each now holds to the webview once an edit has settled.
+///
+/// This is what lets a descriptor author save in their editor and read the
+/// verdict without clicking reload. The reload reuses the service of any
+/// descriptor that did not change, so saving the same file over and over
+/// costs nothing.
+pub fn spawn_folder_watch(app_handle: &tauri::AppHandle) {
+    let c = crate::ctx(app_handle);
+    let descriptors = crate::platforms::descriptor::user_dir(&c);
+    let themes = crate::storage::themes_dir(&c);
+    let (Ok(descriptors), Ok(themes)) = (descriptors, themes) else {
+        let _ = crate::logging::append_app_log(
+            &c,
+            "warn",
+            "backend.folder-watch",
+            "Descriptor or theme folder unavailable; edits need a manual reload",
+            None,
+        );
+        return;
+    };
+
+    let handle = app_handle.clone();
+    let watch = accshift_core::watch::FolderWatch::new(
+        vec![descriptors, themes],
+        accshift_core::watch::QUIET_PERIOD,
+    );
+    std::thread::spawn(move || {
+        watch.run(accshift_core::watch::POLL_INTERVAL, |changed| {
+            let c = crate::ctx(&handle);
+            if changed.contains(&0) {
+                let report = crate::platforms::reload_user_platforms(&c);
+                let _ = handle.emit(USER_PLATFORMS_CHANGED, &report);
+            }
+            if changed.contains(&1) {
+                if let Ok(themes) = crate::themes::list_custom_themes(&c) {
+                    let _ = handle.emit(CUSTOM_THEMES_CHANGED, &themes);
+                }
+            }
+        })
+    });
+}
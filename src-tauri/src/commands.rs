@@ -3,7 +3,11 @@ use crate::platforms::{require_service, SetupStatus};
 use crate::telemetry;
 use crate::telemetry_runtime::TelemetryState;
 use accshift_core::error::PlatformError;
+use accshift_core::platforms::descriptor::plan::DryRunOperation;
+use accshift_core::snapshot_history::SnapshotGeneration;
+use accshift_core::transfer::{ExportSummary, ImportReport};
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::time::Duration;
 use tauri::Manager;
 
@@ -528,8 +532,77 @@ pub async fn platform_set_account_label(
     .await
 }
 
-/// Everything switching to `account_id` would read, copy, write and close,
-/// doing none of it.
+/// Stores the values the platform's launch arguments read for this account.
+#[tauri::command]
+pub async fn platform_set_account_launch_values(
+    app_handle: tauri::AppHandle,
+    platform_id: String,
+    account_id: String,
+    values: BTreeMap<String, String>,
+) -> Result<(), PlatformError> {
+    let service = require_service(&platform_id)?;
+    let c = ctx(&app_handle);
+    run_blocking("platform_set_account_launch_values", move || {
+        service.set_account_launch_values(c, &account_id, values)
+    })
+    .await
+}
+
+/// The earlier captures kept of an account's snapshot, newest first. A switch
+/// restores one when its params carry `snapshotGeneration`.
+#[tauri::command]
+pub async fn platform_snapshot_generations(
+    app_handle: tauri::AppHandle,
+    platform_id: String,
+    account_id: String,
+) -> Result<Vec<SnapshotGeneration>, PlatformError> {
+    let service = require_service(&platform_id)?;
+    let c = ctx(&app_handle);
+    run_blocking("platform_snapshot_generations", move || {
+        service.snapshot_generations(c, &account_id)
+    })
+    .await
+}
+
+/// Writes every account with a snapshot to `path`, sealed with `passphrase`.
+/// Locked so no capture rewrites a snapshot while it is being read.
+#[tauri::command]
+pub async fn export_accounts(
+    app_handle: tauri::AppHandle,
+    path: String,
+    passphrase: String,
+) -> Result<ExportSummary, PlatformError> {
+    run_locked_blocking("export_accounts", ctx(&app_handle), move |c| {
+        Ok(accshift_core::transfer::export(
+            &*c,
+            std::path::Path::new(&path),
+            &passphrase,
+        )?)
+    })
+    .await
+}
+
+/// Adds the accounts in the archive at `path` to this machine, reporting the
+/// ones it already has instead of overwriting them.
+#[tauri::command]
+pub async fn import_accounts(
+    app_handle: tauri::AppHandle,
+    path: String,
+    passphrase: String,
+) -> Result<ImportReport, PlatformError> {
+    run_locked_blocking("import_accounts", ctx(&app_handle), move |c| {
+        Ok(accshift_core::transfer::import(
+            &*c,
+            std::path::Path::new(&path),
+            &passphrase,
+        )?)
+    })
+    .await
+}
+
+/// Everything `operation` would read, copy, write and close, doing none of it.
+/// `account_id` is empty for a setup or a capture; `params` are the ones the
+/// switch itself would be given.
 ///
 /// Filesystem and registry reads only, hence the blocking pool. A platform with
 /// no plan answers with an error rather than an empty one, so the caller never
@@ -538,11 +611,17 @@ pub async fn platform_set_account_label(
 pub async fn platform_dry_run(
     app_handle: tauri::AppHandle,
     platform_id: String,
+    operation: DryRunOperation,
     account_id: String,
+    params: Option<Value>,
 ) -> Result<serde_json::Value, PlatformError> {
     let service = require_service(&platform_id)?;
     let c = ctx(&app_handle);
-    run_blocking("platform_dry_run", move || service.dry_run(c, &account_id)).await
+    let params = params.unwrap_or(Value::Null);
+    run_blocking("platform_dry_run", move || {
+        service.dry_run(c, operation, &account_id, params)
+    })
+    .await
 }
 
 // ---------------------------------------------------------------------------
@@ -1171,6 +1250,26 @@ pub async fn steam_bulk_edit(
     .await
 }
 
+#[tauri::command(async)]
+pub fn steam_preview_bulk_edit(
+    app_handle: tauri::AppHandle,
+    request: crate::platforms::steam::bulk_edit::BulkEditRequest,
+) -> Result<crate::platforms::steam::bulk_edit::BulkEditResult, PlatformError> {
+    crate::platforms::steam::preview_bulk_edit(ctx(&app_handle), request)
+}
+
+#[tauri::command]
+pub async fn steam_revert_bulk_edit(
+    app_handle: tauri::AppHandle,
+    backup_id: String,
+) -> Result<crate::platforms::steam::bulk_edit::RevertResult, PlatformError> {
+    let c = ctx(&app_handle);
+    run_locked_blocking("steam_revert_bulk_edit", c, move |c| {
+        crate::platforms::steam::revert_bulk_edit(c, backup_id)
+    })
+    .await
+}
+
 #[tauri::command(async)]
 pub fn steam_get_account_games(
     app_handle: tauri::AppHandle,
@@ -1179,19 +1278,53 @@ pub fn steam_get_account_games(
     crate::platforms::steam::get_account_games(ctx(&app_handle), steam_id)
 }
 
-// ---------------------------------------------------------------------------
-// Riot-specific commands. Windows-only (no Linux/macOS Riot client).
-// ---------------------------------------------------------------------------
+#[tauri::command(async)]
+pub fn steam_get_shortcuts(
+    app_handle: tauri::AppHandle,
+    steam_id: String,
+) -> Result<Vec<crate::platforms::steam::shortcuts::Shortcut>, PlatformError> {
+    crate::platforms::steam::get_shortcuts(ctx(&app_handle), steam_id)
+}
+
+#[tauri::command(async)]
+pub fn steam_get_library(
+    app_handle: tauri::AppHandle,
+) -> Result<crate::platforms::steam::library::LibraryIndex, PlatformError> {
+    crate::platforms::steam::get_library(ctx(&app_handle))
+}
+
+#[tauri::command(async)]
+pub fn steam_get_app_owners(
+    app_handle: tauri::AppHandle,
+    app_id: u32,
+) -> Result<Vec<crate::platforms::steam::library::AppOwner>, PlatformError> {
+    crate::platforms::steam::get_app_owners(ctx(&app_handle), app_id)
+}
+
+#[tauri::command(async)]
+pub fn steam_get_collections(
+    app_handle: tauri::AppHandle,
+    steam_id: String,
+) -> Result<Vec<crate::platforms::steam::library_copy::Collection>, PlatformError> {
+    crate::platforms::steam::get_collections(ctx(&app_handle), steam_id)
+}
+
+#[tauri::command(async)]
+pub fn steam_preview_library_copy(
+    app_handle: tauri::AppHandle,
+    request: crate::platforms::steam::library_copy::LibraryCopyRequest,
+) -> Result<Vec<crate::platforms::steam::library_copy::LibraryCopyPlan>, PlatformError> {
+    crate::platforms::steam::preview_library_copy(ctx(&app_handle), request)
+}
 
-#[cfg(windows)]
 #[tauri::command]
-pub async fn riot_capture_profile(
+pub async fn steam_copy_library(
     app_handle: tauri::AppHandle,
-    profile_id: String,
-) -> Result<(), PlatformError> {
+    request: crate::platforms::steam::library_copy::LibraryCopyRequest,
+) -> Result<crate::platforms::steam::library_copy::LibraryCopyResult, PlatformError> {
     let c = ctx(&app_handle);
-    run_locked_blocking("riot_capture_profile", c, move |c| {
-        crate::platforms::riot::capture_profile(c, profile_id).map_err(Into::into)
+    run_locked_blocking("steam_copy_library", c, move |c| {
+        crate::platforms::steam::copy_library(c, request)
     })
     .await
 }
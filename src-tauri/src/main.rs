@@ -334,6 +334,19 @@ fn main() {
                 });
             }
 
+            // A switch the process died in the middle of leaves its rollback
+            // copies behind: the outgoing account's session, in plaintext.
+            let swept = platforms::descriptor::engine::sweep_switch_rollbacks(&setup_ctx);
+            if swept > 0 {
+                let _ = logging::append_app_log(
+                    &setup_ctx,
+                    "info",
+                    "backend.switch-rollback",
+                    &format!("Removed {swept} rollback folder(s) left by an interrupted switch"),
+                    None,
+                );
+            }
+
             // Snapshots captured before encryption shipped are still read as
             // plaintext, and only get encrypted when the account is captured
             // again. A dormant account never is, so sweep the store once per
@@ -364,6 +377,10 @@ fn main() {
                 );
             });
 
+            // The boot payload already read both folders; from here on an edit
+            // to either reaches the webview on its own.
+            app_runtime::spawn_folder_watch(app.handle());
+
             let fallback_handle = app.handle().clone();
             std::thread::spawn(move || {
                 std::thread::sleep(std::time::Duration::from_millis(5000));
@@ -410,6 +427,10 @@ fn main() {
             commands::platform_select_path,
             commands::platform_detect_installed,
             commands::platform_set_account_label,
+            commands::platform_set_account_launch_values,
+            commands::platform_snapshot_generations,
+            commands::export_accounts,
+            commands::import_accounts,
             commands::platform_dry_run,
             // Platforms the user added themselves, from a descriptor file
             commands::reload_user_platforms,
@@ -441,15 +462,20 @@ fn main() {
             commands::steam_open_userdata,
             commands::steam_clear_browser_cache,
             commands::steam_bulk_edit,
+            commands::steam_preview_bulk_edit,
+            commands::steam_revert_bulk_edit,
             commands::steam_get_account_games,
+            commands::steam_get_shortcuts,
+            commands::steam_get_library,
+            commands::steam_get_app_owners,
+            commands::steam_get_collections,
+            commands::steam_preview_library_copy,
+            commands::steam_copy_library,
             commands::cs2_bridge_get_settings,
             commands::cs2_bridge_set_settings,
             commands::cs2_bridge_fetch,
             commands::cs2_bridge_check,
             commands::cs2_bridge_test,
-            // Riot-specific (Windows-only)
-            #[cfg(windows)]
-            commands::riot_capture_profile,
             // Roblox-specific (Windows-only)
             #[cfg(windows)]
             commands::roblox_add_account_by_cookie,
@@ -10,7 +10,12 @@ import { getSettings, hasStoredSettings, saveSettings } from "$lib/features/sett
 import { getInitialActiveTab, isPlatformUsable } from "$lib/app/platformShell.svelte";
 import { getPlatformDefinition } from "$lib/platforms/registry";
 import { trackSettingsSnapshot } from "$lib/app/telemetryClient";
-import { applyCustomThemePayloads, loadCustomThemes } from "$lib/theme/themes";
+import {
+  applyCustomThemePayloads,
+  loadCustomThemes,
+  onCustomThemesChanged,
+} from "$lib/theme/themes";
+import { onUserPlatformsChanged } from "$lib/platforms/descriptors";
 import {
   CLIENT_STORE_ACCOUNT_CARD_COLORS,
   CLIENT_STORE_ACCOUNT_CARD_NOTES,
@@ -175,6 +180,23 @@ export function createAppLifecycleController({
     }
   }
 
+  /** Moves off the active tab when settings or the registry no longer offer it. */
+  function leaveUnusableTab() {
+    if (
+      shell.settings.enabledPlatforms.includes(shell.activeTab) &&
+      isPlatformUsable(shell.activeTab, shell.runtimeOs)
+    ) {
+      return;
+    }
+    shell.setActiveTab(getInitialActiveTab(shell.settings, shell.runtimeOs));
+    navigation.currentFolderId = null;
+    replaceHistoryState({
+      tab: shell.activeTab,
+      folderId: null,
+      showSettings: false,
+    });
+  }
+
   async function refreshExternalStorageState() {
     if (externalStorageRefreshInFlight) return;
     externalStorageRefreshInFlight = true;
@@ -211,18 +233,7 @@ export function createAppLifecycleController({
 
       if (settingsChanged || themesChanged) {
         shell.refreshSettings();
-        if (
-          !shell.settings.enabledPlatforms.includes(shell.activeTab) ||
-          !isPlatformUsable(shell.activeTab, shell.runtimeOs)
-        ) {
-          shell.setActiveTab(getInitialActiveTab(shell.settings, shell.runtimeOs));
-          navigation.currentFolderId = null;
-          replaceHistoryState({
-            tab: shell.activeTab,
-            folderId: null,
-            showSettings: false,
-          });
-        }
+        leaveUnusableTab();
       }
 
       if (viewModeChanged) {
@@ -260,6 +271,43 @@ export function createAppLifecycleController({
     }
   }
 
+  /**
+   * Follows the backend's watch on the descriptor and theme folders: an edit
+   * saved in an editor lands here without a reload click. Returns the call
+   * that stops listening.
+   */
+  function watchFolders(): () => void {
+    let disposed = false;
+    const unlisteners: (() => void)[] = [];
+    const keep = (pending: Promise<() => void>) => {
+      pending
+        .then((unlisten) => {
+          if (disposed) unlisten();
+          else unlisteners.push(unlisten);
+        })
+        .catch((reason) => {
+          console.error("Failed to follow folder changes:", reason);
+        });
+    };
+
+    keep(
+      onCustomThemesChanged(() => {
+        shell.refreshSettings();
+      }),
+    );
+    keep(
+      onUserPlatformsChanged(() => {
+        shell.refreshSettings();
+        leaveUnusableTab();
+      }),
+    );
+
+    return () => {
+      disposed = true;
+      for (const unlisten of unlisteners.splice(0)) unlisten();
+    };
+  }
+
   function handleWindowFocus() {
     void refreshExternalStorageState();
   }
@@ -273,6 +321,7 @@ export function createAppLifecycleController({
   return {
     initializeAppShell,
     refreshExternalStorageState,
+    watchFolders,
     handleWindowFocus,
     handleVisibilityChange,
   };
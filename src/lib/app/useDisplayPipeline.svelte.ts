@@ -46,7 +46,8 @@ export function matchesSearch(account: PlatformAccount, query: string): boolean
     foldSearchText(account.id).includes(q) ||
     foldSearchText(account.username).includes(q) ||
     foldSearchText(account.displayName || "").includes(q) ||
-    foldSearchText(getAccountCardNote(account.id)).includes(q)
+    foldSearchText(getAccountCardNote(account.id)).includes(q) ||
+    Object.values(account.fields ?? {}).some((value) => foldSearchText(value).includes(q))
   );
 }
 
@@ -38,6 +38,10 @@ describe("matchesSearch", () => {
     ).toBe(false);
   });
 
+  it("matches a descriptor field value", () => {
+    expect(matchesSearch(makeAccount({ fields: { tagLine: "EUW" } }), "euw")).toBe(true);
+  });
+
   it("matches partial strings", () => {
     expect(matchesSearch(makeAccount({ id: "longid12345" }), "id123")).toBe(true);
   });
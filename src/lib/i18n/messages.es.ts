@@ -104,9 +104,18 @@ export const ES_MESSAGES: Record<MessageKey, string> = {
     "Su archivo de descriptor sale de tu carpeta. Las cuentas ya capturadas siguen en el disco.",
   "descriptor.previewTitle": "Añadir una plataforma",
   "descriptor.previewOs": "Descrita para {list}",
+  "descriptor.previewExtends": "Basada en {parent}",
+  "descriptor.signedBy": "Firmada por {publisher}",
+  "descriptor.signedByUnknown": "Firmada con una clave no aprobada: {key}",
+  "descriptor.unsigned": "Sin firmar",
+  "descriptor.notCaptured": "Aún sin capturar",
+  "descriptor.previewInherited": "Heredado sin cambios",
   "descriptor.previewRoots": "Carpetas que puede tocar",
   "descriptor.previewSteps": "Lo que haría un cambio de cuenta",
   "descriptor.previewWarnings": "Avisos",
+  "descriptor.previewLint": "Probablemente no intencionado",
+  "descriptor.lintSingle": "1 aviso",
+  "descriptor.lintMultiple": "{count} avisos",
   "descriptor.previewNoPlan": "No se pudo crear una simulación: {problem}",
   "descriptor.previewReplaces": "Esto reemplaza el descriptor que ya está en tu carpeta.",
   "descriptor.previewNothingWritten": "No se escribe nada hasta que lo añadas.",
@@ -117,6 +126,7 @@ export const ES_MESSAGES: Record<MessageKey, string> = {
   "descriptor.actionCapture": "capturar",
   "descriptor.actionRestore": "restaurar",
   "descriptor.actionDelete": "eliminar",
+  "descriptor.actionWrite": "escribir",
   "descriptor.actionClose": "cerrar",
   "descriptor.actionLaunch": "iniciar",
   "settings.dataRefresh": "Actualización de datos",
@@ -403,9 +413,6 @@ export const ES_MESSAGES: Record<MessageKey, string> = {
   "steam.setupReadyWithProfile": "{profile} añadido",
   "steam.setupFailed": "Falló la configuración de Steam",
   "steam.setupFailedMessage": "Accshift no pudo terminar de añadir esta cuenta de Steam.",
-  "riot.captureSession": "Capturar la sesión actual",
-  "riot.recaptureSession": "Volver a capturar la sesión",
-  "riot.capturedSession": "Sesión capturada para {profile}",
   "riot.setupPendingLabel": "Cuenta de Riot",
   "riot.setupWaitingForClient": "Esperando al Riot Client...",
   "riot.setupWaitingForLogin": "Inicia sesión en la cuenta de Riot que quieres añadir.",
@@ -417,11 +424,12 @@ export const ES_MESSAGES: Record<MessageKey, string> = {
   "riot.setupStaySignedIn": "Mantén activada la opción 'Stay signed in'.",
   "riot.setupDetected": "Cuenta detectada",
   "riot.setupConnected": "Conectado",
+  "riot.copyLabelAccountId": "ID de cuenta",
   "riot.forget": "Olvidar cuenta",
   "riot.forgetConfirmTitle": '¿Olvidar "{display}"?',
   "riot.forgetConfirmMessage":
     "Esto elimina el perfil local de Riot y su sesión guardada de Accshift.",
-  "riot.forgotProfile": "{profile} olvidado",
+  "riot.forgotAccount": "{display} olvidado",
   "battlenet.copyUsername": "Copiar nombre de usuario",
   "battlenet.copyLabelUsername": "Nombre de usuario",
   "battlenet.copyEmail": "Copiar correo",
@@ -104,9 +104,18 @@ export const FR_MESSAGES: Record<MessageKey, string> = {
     "Son fichier de descripteur quitte votre dossier. Les comptes déjà capturés restent sur le disque.",
   "descriptor.previewTitle": "Ajouter une plateforme",
   "descriptor.previewOs": "Décrite pour {list}",
+  "descriptor.previewExtends": "Basée sur {parent}",
+  "descriptor.signedBy": "Signée par {publisher}",
+  "descriptor.signedByUnknown": "Signée par une clé non approuvée : {key}",
+  "descriptor.unsigned": "Non signée",
+  "descriptor.notCaptured": "Pas encore capturé",
+  "descriptor.previewInherited": "Repris tels quels du parent",
   "descriptor.previewRoots": "Dossiers auxquels elle peut toucher",
   "descriptor.previewSteps": "Ce que ferait un changement de compte",
   "descriptor.previewWarnings": "Avertissements",
+  "descriptor.previewLint": "Probablement involontaire",
+  "descriptor.lintSingle": "1 avertissement",
+  "descriptor.lintMultiple": "{count} avertissements",
   "descriptor.previewNoPlan": "Impossible de construire un essai à blanc : {problem}",
   "descriptor.previewReplaces": "Ceci remplace le descripteur déjà présent dans votre dossier.",
   "descriptor.previewNothingWritten": "Rien n'est écrit tant que vous n'avez pas ajouté.",
@@ -117,6 +126,7 @@ export const FR_MESSAGES: Record<MessageKey, string> = {
   "descriptor.actionCapture": "capturer",
   "descriptor.actionRestore": "restaurer",
   "descriptor.actionDelete": "supprimer",
+  "descriptor.actionWrite": "écrire",
   "descriptor.actionClose": "fermer",
   "descriptor.actionLaunch": "lancer",
   "settings.dataRefresh": "Rafraîchissement des données",
@@ -400,9 +410,6 @@ export const FR_MESSAGES: Record<MessageKey, string> = {
   "steam.setupReadyWithProfile": "{profile} ajouté",
   "steam.setupFailed": "Échec de la configuration Steam",
   "steam.setupFailedMessage": "Accshift n'a pas pu finaliser l'ajout de ce compte Steam.",
-  "riot.captureSession": "Capturer la session actuelle",
-  "riot.recaptureSession": "Recapturer la session",
-  "riot.capturedSession": "Session capturée pour {profile}",
   "riot.setupPendingLabel": "Compte Riot",
   "riot.setupWaitingForClient": "En attente du client Riot...",
   "riot.setupWaitingForLogin": "Connecte-toi au compte Riot que tu veux ajouter.",
@@ -414,11 +421,12 @@ export const FR_MESSAGES: Record<MessageKey, string> = {
   "riot.setupStaySignedIn": "Garde 'Rester connecté' activé.",
   "riot.setupDetected": "Compte détecté",
   "riot.setupConnected": "Connecté",
+  "riot.copyLabelAccountId": "ID de compte",
   "riot.forget": "Oublier le compte",
   "riot.forgetConfirmTitle": 'Oublier "{display}" ?',
   "riot.forgetConfirmMessage":
     "Cela retire le profil Riot local et sa session sauvegardée d'Accshift.",
-  "riot.forgotProfile": "{profile} oublié",
+  "riot.forgotAccount": "{display} oublié",
   "battlenet.copyUsername": "Copier le pseudo",
   "battlenet.copyLabelUsername": "Pseudo",
   "battlenet.copyEmail": "Copier l'email",
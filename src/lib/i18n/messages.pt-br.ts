@@ -104,9 +104,18 @@ export const PT_BR_MESSAGES: Record<MessageKey, string> = {
     "O arquivo de descritor sai da sua pasta. As contas já capturadas continuam no disco.",
   "descriptor.previewTitle": "Adicionar uma plataforma",
   "descriptor.previewOs": "Descrita para {list}",
+  "descriptor.previewExtends": "Baseada em {parent}",
+  "descriptor.signedBy": "Assinada por {publisher}",
+  "descriptor.signedByUnknown": "Assinada por uma chave não aprovada: {key}",
+  "descriptor.unsigned": "Não assinada",
+  "descriptor.notCaptured": "Ainda não capturada",
+  "descriptor.previewInherited": "Herdado sem alterações",
   "descriptor.previewRoots": "Pastas em que pode mexer",
   "descriptor.previewSteps": "O que uma troca de conta faria",
   "descriptor.previewWarnings": "Avisos",
+  "descriptor.previewLint": "Provavelmente não intencional",
+  "descriptor.lintSingle": "1 aviso",
+  "descriptor.lintMultiple": "{count} avisos",
   "descriptor.previewNoPlan": "Não foi possível montar uma simulação: {problem}",
   "descriptor.previewReplaces": "Isso substitui o descritor que já está na sua pasta.",
   "descriptor.previewNothingWritten": "Nada é gravado até você adicionar.",
@@ -117,6 +126,7 @@ export const PT_BR_MESSAGES: Record<MessageKey, string> = {
   "descriptor.actionCapture": "capturar",
   "descriptor.actionRestore": "restaurar",
   "descriptor.actionDelete": "remover",
+  "descriptor.actionWrite": "gravar",
   "descriptor.actionClose": "fechar",
   "descriptor.actionLaunch": "iniciar",
   "settings.dataRefresh": "Atualização de dados",
@@ -399,9 +409,6 @@ export const PT_BR_MESSAGES: Record<MessageKey, string> = {
   "steam.setupReadyWithProfile": "{profile} adicionada",
   "steam.setupFailed": "Falha na configuração do Steam",
   "steam.setupFailedMessage": "O Accshift não conseguiu terminar de adicionar esta conta Steam.",
-  "riot.captureSession": "Capturar a sessão atual",
-  "riot.recaptureSession": "Recapturar sessão",
-  "riot.capturedSession": "Sessão capturada para {profile}",
   "riot.setupPendingLabel": "Conta Riot",
   "riot.setupWaitingForClient": "Aguardando o Riot Client...",
   "riot.setupWaitingForLogin": "Entre na conta Riot que você quer adicionar.",
@@ -413,10 +420,11 @@ export const PT_BR_MESSAGES: Record<MessageKey, string> = {
   "riot.setupStaySignedIn": "Mantenha 'Continuar conectado' ativado.",
   "riot.setupDetected": "Conta detectada",
   "riot.setupConnected": "Conectado",
+  "riot.copyLabelAccountId": "ID da conta",
   "riot.forget": "Esquecer conta",
   "riot.forgetConfirmTitle": 'Esquecer "{display}"?',
   "riot.forgetConfirmMessage": "Isso remove o perfil Riot local e a sessão salva do Accshift.",
-  "riot.forgotProfile": "{profile} esquecido",
+  "riot.forgotAccount": "{display} esquecido",
   "battlenet.copyUsername": "Copiar nome de usuário",
   "battlenet.copyLabelUsername": "Nome de usuário",
   "battlenet.copyEmail": "Copiar e-mail",
@@ -104,9 +104,18 @@ export const PT_MESSAGES: Record<MessageKey, string> = {
     "O ficheiro de descritor sai da sua pasta. As contas já capturadas ficam no disco.",
   "descriptor.previewTitle": "Adicionar uma plataforma",
   "descriptor.previewOs": "Descrita para {list}",
+  "descriptor.previewExtends": "Baseada em {parent}",
+  "descriptor.signedBy": "Assinada por {publisher}",
+  "descriptor.signedByUnknown": "Assinada por uma chave não aprovada: {key}",
+  "descriptor.unsigned": "Não assinada",
+  "descriptor.notCaptured": "Ainda não capturada",
+  "descriptor.previewInherited": "Herdado sem alterações",
   "descriptor.previewRoots": "Pastas em que pode tocar",
   "descriptor.previewSteps": "O que uma troca de conta faria",
   "descriptor.previewWarnings": "Avisos",
+  "descriptor.previewLint": "Provavelmente não intencional",
+  "descriptor.lintSingle": "1 aviso",
+  "descriptor.lintMultiple": "{count} avisos",
   "descriptor.previewNoPlan": "Não foi possível criar uma simulação: {problem}",
   "descriptor.previewReplaces": "Isto substitui o descritor que já está na sua pasta.",
   "descriptor.previewNothingWritten": "Nada é escrito até que o adicione.",
@@ -117,6 +126,7 @@ export const PT_MESSAGES: Record<MessageKey, string> = {
   "descriptor.actionCapture": "capturar",
   "descriptor.actionRestore": "restaurar",
   "descriptor.actionDelete": "remover",
+  "descriptor.actionWrite": "escrever",
   "descriptor.actionClose": "fechar",
   "descriptor.actionLaunch": "iniciar",
   "settings.dataRefresh": "Atualização de dados",
@@ -400,9 +410,6 @@ export const PT_MESSAGES: Record<MessageKey, string> = {
   "steam.setupReadyWithProfile": "{profile} adicionada",
   "steam.setupFailed": "A configuração do Steam falhou",
   "steam.setupFailedMessage": "O Accshift não conseguiu concluir a adição desta conta Steam.",
-  "riot.captureSession": "Capturar a sessão atual",
-  "riot.recaptureSession": "Voltar a capturar a sessão",
-  "riot.capturedSession": "Sessão capturada para {profile}",
   "riot.setupPendingLabel": "Conta Riot",
   "riot.setupWaitingForClient": "À espera do Riot Client...",
   "riot.setupWaitingForLogin": "Inicia sessão na conta Riot que queres adicionar.",
@@ -414,11 +421,12 @@ export const PT_MESSAGES: Record<MessageKey, string> = {
   "riot.setupStaySignedIn": "Mantém 'Manter sessão iniciada' ativado.",
   "riot.setupDetected": "Conta detetada",
   "riot.setupConnected": "Ligado",
+  "riot.copyLabelAccountId": "ID da conta",
   "riot.forget": "Esquecer conta",
   "riot.forgetConfirmTitle": "Esquecer «{display}»?",
   "riot.forgetConfirmMessage":
     "Isto remove o perfil Riot local e a sua sessão guardada do Accshift.",
-  "riot.forgotProfile": "{profile} esquecido",
+  "riot.forgotAccount": "{display} esquecido",
   "battlenet.copyUsername": "Copiar nome de utilizador",
   "battlenet.copyLabelUsername": "Nome de utilizador",
   "battlenet.copyEmail": "Copiar email",
@@ -103,9 +103,18 @@ export const RU_MESSAGES: Record<MessageKey, string> = {
     "Файл дескриптора покинет вашу папку. Уже сохранённые аккаунты останутся на диске.",
   "descriptor.previewTitle": "Добавить платформу",
   "descriptor.previewOs": "Описана для {list}",
+  "descriptor.previewExtends": "Основана на {parent}",
+  "descriptor.signedBy": "Подписана: {publisher}",
+  "descriptor.signedByUnknown": "Подписана недоверенным ключом: {key}",
+  "descriptor.unsigned": "Не подписана",
+  "descriptor.notCaptured": "Ещё не сохранён",
+  "descriptor.previewInherited": "Унаследовано без изменений",
   "descriptor.previewRoots": "Папки, которых она может касаться",
   "descriptor.previewSteps": "Что сделало бы переключение аккаунта",
   "descriptor.previewWarnings": "Предупреждения",
+  "descriptor.previewLint": "Вероятно, не задумано",
+  "descriptor.lintSingle": "1 предупреждение",
+  "descriptor.lintMultiple": "Предупреждений: {count}",
   "descriptor.previewNoPlan": "Не удалось построить пробный запуск: {problem}",
   "descriptor.previewReplaces": "Это заменит дескриптор, который уже лежит в вашей папке.",
   "descriptor.previewNothingWritten": "Ничего не записывается, пока вы не добавите.",
@@ -116,6 +125,7 @@ export const RU_MESSAGES: Record<MessageKey, string> = {
   "descriptor.actionCapture": "снимок",
   "descriptor.actionRestore": "восстановление",
   "descriptor.actionDelete": "удаление",
+  "descriptor.actionWrite": "запись",
   "descriptor.actionClose": "закрытие",
   "descriptor.actionLaunch": "запуск",
   "settings.dataRefresh": "Обновление данных",
@@ -404,9 +414,6 @@ export const RU_MESSAGES: Record<MessageKey, string> = {
   "steam.setupReadyWithProfile": "Добавлен {profile}",
   "steam.setupFailed": "Не удалось настроить Steam",
   "steam.setupFailedMessage": "Accshift не смог завершить добавление этого аккаунта Steam.",
-  "riot.captureSession": "Захватить текущую сессию",
-  "riot.recaptureSession": "Захватить сессию заново",
-  "riot.capturedSession": "Сессия для {profile} захвачена",
   "riot.setupPendingLabel": "Аккаунт Riot",
   "riot.setupWaitingForClient": "Ожидание Riot Client...",
   "riot.setupWaitingForLogin": "Войдите в аккаунт Riot, который хотите добавить.",
@@ -418,11 +425,12 @@ export const RU_MESSAGES: Record<MessageKey, string> = {
   "riot.setupStaySignedIn": "Оставьте включённой опцию «Stay signed in».",
   "riot.setupDetected": "Аккаунт определён",
   "riot.setupConnected": "Подключено",
+  "riot.copyLabelAccountId": "ID аккаунта",
   "riot.forget": "Забыть аккаунт",
   "riot.forgetConfirmTitle": "Забыть «{display}»?",
   "riot.forgetConfirmMessage":
     "Локальный профиль Riot и его сохранённая сессия будут удалены из Accshift.",
-  "riot.forgotProfile": "Забыт {profile}",
+  "riot.forgotAccount": "Забыт {display}",
   "battlenet.copyUsername": "Копировать логин",
   "battlenet.copyLabelUsername": "Логин",
   "battlenet.copyEmail": "Копировать e-mail",
@@ -95,9 +95,18 @@ export const EN_MESSAGES = {
     "Its descriptor file leaves your folder. Accounts already captured stay on disk.",
   "descriptor.previewTitle": "Add a platform",
   "descriptor.previewOs": "Described for {list}",
+  "descriptor.previewExtends": "Based on {parent}",
+  "descriptor.signedBy": "Signed by {publisher}",
+  "descriptor.signedByUnknown": "Signed by a key you have not trusted: {key}",
+  "descriptor.unsigned": "Unsigned",
+  "descriptor.notCaptured": "Not captured yet",
+  "descriptor.previewInherited": "Inherited unchanged",
   "descriptor.previewRoots": "Folders it may touch",
   "descriptor.previewSteps": "What a switch would do",
   "descriptor.previewWarnings": "Warnings",
+  "descriptor.previewLint": "Probably not intended",
+  "descriptor.lintSingle": "1 warning",
+  "descriptor.lintMultiple": "{count} warnings",
   "descriptor.previewNoPlan": "No dry run could be built: {problem}",
   "descriptor.previewReplaces": "This replaces the descriptor already in your folder.",
   "descriptor.previewNothingWritten": "Nothing is written until you add it.",
@@ -108,6 +117,7 @@ export const EN_MESSAGES = {
   "descriptor.actionCapture": "capture",
   "descriptor.actionRestore": "restore",
   "descriptor.actionDelete": "delete",
+  "descriptor.actionWrite": "write",
   "descriptor.actionClose": "close",
   "descriptor.actionLaunch": "launch",
   "settings.dataRefresh": "Data Refresh",
@@ -391,9 +401,6 @@ export const EN_MESSAGES = {
   "steam.setupReadyWithProfile": "Added {profile}",
   "steam.setupFailed": "Steam setup failed",
   "steam.setupFailedMessage": "Accshift could not finish adding this Steam account.",
-  "riot.captureSession": "Capture current session",
-  "riot.recaptureSession": "Re-capture session",
-  "riot.capturedSession": "Captured session for {profile}",
   "riot.setupPendingLabel": "Riot Account",
   "riot.setupWaitingForClient": "Waiting for Riot Client...",
   "riot.setupWaitingForLogin": "Sign in to the Riot account you want to add.",
@@ -405,11 +412,12 @@ export const EN_MESSAGES = {
   "riot.setupStaySignedIn": "Keep 'Stay signed in' enabled.",
   "riot.setupDetected": "Detected account",
   "riot.setupConnected": "Connected",
+  "riot.copyLabelAccountId": "Account ID",
   "riot.forget": "Forget account",
   "riot.forgetConfirmTitle": 'Forget "{display}"?',
   "riot.forgetConfirmMessage":
     "This removes the local Riot profile and its saved session from Accshift.",
-  "riot.forgotProfile": "Forgot {profile}",
+  "riot.forgotAccount": "Forgot {display}",
   "battlenet.copyUsername": "Copy username",
   "battlenet.copyLabelUsername": "Username",
   "battlenet.copyEmail": "Copy email",
@@ -100,9 +100,18 @@ export const ZH_MESSAGES: Record<MessageKey, string> = {
   "descriptor.removeConfirmMessage": "它的描述符文件会离开你的文件夹。已保存的账号仍留在磁盘上。",
   "descriptor.previewTitle": "添加平台",
   "descriptor.previewOs": "适用于 {list}",
+  "descriptor.previewExtends": "基于 {parent}",
+  "descriptor.signedBy": "由 {publisher} 签名",
+  "descriptor.signedByUnknown": "由未信任的密钥签名：{key}",
+  "descriptor.unsigned": "未签名",
+  "descriptor.notCaptured": "尚未捕获",
+  "descriptor.previewInherited": "沿用父描述的字段",
   "descriptor.previewRoots": "它可以访问的文件夹",
   "descriptor.previewSteps": "切换账号会做什么",
   "descriptor.previewWarnings": "警告",
+  "descriptor.previewLint": "可能并非本意",
+  "descriptor.lintSingle": "1 个警告",
+  "descriptor.lintMultiple": "{count} 个警告",
   "descriptor.previewNoPlan": "无法生成试运行：{problem}",
   "descriptor.previewReplaces": "这会替换你文件夹中已有的描述符。",
   "descriptor.previewNothingWritten": "在你添加之前不会写入任何内容。",
@@ -113,6 +122,7 @@ export const ZH_MESSAGES: Record<MessageKey, string> = {
   "descriptor.actionCapture": "保存",
   "descriptor.actionRestore": "恢复",
   "descriptor.actionDelete": "删除",
+  "descriptor.actionWrite": "写入",
   "descriptor.actionClose": "关闭",
   "descriptor.actionLaunch": "启动",
   "settings.dataRefresh": "数据刷新",
@@ -383,9 +393,6 @@ export const ZH_MESSAGES: Record<MessageKey, string> = {
   "steam.setupReadyWithProfile": "已添加 {profile}",
   "steam.setupFailed": "Steam 设置失败",
   "steam.setupFailedMessage": "Accshift 无法完成该 Steam 账号的添加。",
-  "riot.captureSession": "捕获当前会话",
-  "riot.recaptureSession": "重新捕获会话",
-  "riot.capturedSession": "已捕获 {profile} 的会话",
   "riot.setupPendingLabel": "Riot 账号",
   "riot.setupWaitingForClient": "等待 Riot 客户端...",
   "riot.setupWaitingForLogin": "请登录你要添加的 Riot 账号。",
@@ -397,10 +404,11 @@ export const ZH_MESSAGES: Record<MessageKey, string> = {
   "riot.setupStaySignedIn": "请保持「保持登录」为开启状态。",
   "riot.setupDetected": "已检测到账号",
   "riot.setupConnected": "已连接",
+  "riot.copyLabelAccountId": "账号 ID",
   "riot.forget": "移除账号",
   "riot.forgetConfirmTitle": "移除「{display}」？",
   "riot.forgetConfirmMessage": "这会从 Accshift 中删除本地 Riot 档案及其保存的会话。",
-  "riot.forgotProfile": "已移除 {profile}",
+  "riot.forgotAccount": "已移除 {display}",
   "battlenet.copyUsername": "复制用户名",
   "battlenet.copyLabelUsername": "用户名",
   "battlenet.copyEmail": "复制邮箱",
@@ -6,7 +6,7 @@
  * frozen README dataset and the throwaway dev datasets answer the exact same
  * command surface.
  */
-import type { RiotProfile } from "$lib/platforms/riot/types";
+import type { GenericRawAccount } from "$lib/platforms/genericAdapter";
 import type { BanInfo, ProfileInfo, SteamAccount } from "$lib/platforms/steam/types";
 
 // Fixed clock. No Date.now() anywhere in the mock, so two runs — and two
@@ -32,8 +32,8 @@ export interface MockSpec {
   runtimeOs: string;
   steamAccounts: MockAccount[];
   currentSteamAccount: string;
-  riotProfiles: RiotProfile[];
-  currentRiotProfile: string;
+  riotAccounts: GenericRawAccount[];
+  currentRiotAccount: string;
   /** `client.*` / `cache.*` stores, merged over the generated avatar cache. */
   stores: Record<string, unknown>;
   steamPath: string;
@@ -156,7 +156,7 @@ export function generateAccounts(count: number, avatars: string[]): MockAccount[
  */
 export function createHandlers(spec: MockSpec): Record<string, Handler> {
   let currentAccount = spec.currentSteamAccount;
-  let currentRiotProfile = spec.currentRiotProfile;
+  let currentRiotAccount = spec.currentRiotAccount;
 
   const findAccount = (id: string) => spec.steamAccounts.find((a) => a.steam_id === id);
 
@@ -174,6 +174,8 @@ export function createHandlers(spec: MockSpec): Record<string, Handler> {
   const emptyUserPlatforms = () => ({
     dir: "C:\\Users\\player\\AppData\\Roaming\\accshift\\platforms",
     loaded: [],
+    signers: {},
+    lint: {},
     skipped: [],
     rejected: [],
   });
@@ -217,19 +219,19 @@ export function createHandlers(spec: MockSpec): Record<string, Handler> {
     platform_get_path: () => spec.steamPath,
     platform_get_accounts: (args) =>
       args.platformId === "riot"
-        ? spec.riotProfiles
+        ? spec.riotAccounts
         : args.platformId === "steam"
           ? steamAccountsPayload(spec.steamAccounts)
           : [],
     platform_get_current_account: (args) =>
       args.platformId === "riot"
-        ? currentRiotProfile
+        ? currentRiotAccount
         : args.platformId === "steam"
           ? currentAccount
           : "",
     platform_get_startup_snapshot: (args) => {
       if (args.platformId === "riot") {
-        return { profiles: spec.riotProfiles, currentProfile: currentRiotProfile };
+        return { accounts: spec.riotAccounts, currentAccount: currentRiotAccount };
       }
       if (args.platformId === "steam") {
         return { accounts: steamAccountsPayload(spec.steamAccounts), currentAccount };
@@ -242,7 +244,7 @@ export function createHandlers(spec: MockSpec): Record<string, Handler> {
       await delay(spec.switchDelayMs);
       if (typeof args.accountId === "string") {
         if (args.platformId === "riot") {
-          currentRiotProfile = args.accountId;
+          currentRiotAccount = args.accountId;
         } else {
           currentAccount = args.accountId;
         }
@@ -250,6 +252,12 @@ export function createHandlers(spec: MockSpec): Record<string, Handler> {
       return null;
     },
     platform_set_account_label: () => null,
+    platform_set_account_launch_values: () => null,
+    platform_snapshot_generations: () => [],
+    // Never reads or writes a real archive: the paths are the recording
+    // machine's.
+    export_accounts: () => ({ accounts: [], skipped: [] }),
+    import_accounts: () => ({ exportedAt: NOW * 1000, imported: [], conflicts: [] }),
     // The descriptor folder is the recording machine's, so a mock session
     // reports it empty and every call that would change it does nothing. The
     // picker in particular must never open a real file dialog.
@@ -265,7 +273,7 @@ export function createHandlers(spec: MockSpec): Record<string, Handler> {
     open_descriptors_folder: () => null,
     platform_dry_run: (args) => ({
       platformId: String(args.platformId ?? ""),
-      operation: "switch",
+      operation: String(args.operation ?? "switch"),
       accountId: String(args.accountId ?? ""),
       applied: false,
       roots: [],
@@ -289,6 +297,11 @@ export function createHandlers(spec: MockSpec): Record<string, Handler> {
     },
     steam_get_copyable_games: () => [],
     steam_get_account_games: () => [],
+    steam_get_shortcuts: () => [],
+    steam_get_library: () => ({ accounts: [] }),
+    steam_get_app_owners: () => [],
+    steam_get_collections: () => [],
+    steam_preview_library_copy: () => [],
     cs2_bridge_get_settings: () => ({ enabled: false, url: "", apiKey: "" }),
     // Anything that would pull focus out of the window.
     open_url: () => null,
@@ -145,7 +145,7 @@ const api = {
     frozen: FROZEN,
     runtimeOs: spec.runtimeOs,
     accounts: spec.steamAccounts.length,
-    riotProfiles: spec.riotProfiles.length,
+    riotAccounts: spec.riotAccounts.length,
     latencyMs,
     failures: { ...failures },
     stores: Object.keys(spec.stores),
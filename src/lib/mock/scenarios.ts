@@ -52,8 +52,8 @@ function baseSpec(): MockSpec {
     runtimeOs: "windows",
     steamAccounts: [],
     currentSteamAccount: "",
-    riotProfiles: [],
-    currentRiotProfile: "",
+    riotAccounts: [],
+    currentRiotAccount: "",
     stores: { "client.settings": { ...BASE_SETTINGS, enabledPlatforms: ["steam"] } },
     steamPath: STEAM_PATH_WINDOWS,
     hasSteamApiKey: true,
@@ -124,23 +124,13 @@ function demoScenario(): MockSpec {
     steamAccounts: DEMO_ACCOUNTS,
     currentSteamAccount: DEMO_ACCOUNTS[0].steam_id,
     // Riot keeps captured session snapshots rather than logins, so its cards
-    // show a label and a state instead of a username.
-    riotProfiles: [
-      { id: "riot-1", label: "main", snapshot_state: "ready", last_used_at: (NOW - HOUR) * 1000 },
-      {
-        id: "riot-2",
-        label: "smurf",
-        snapshot_state: "ready",
-        last_used_at: (NOW - 6 * DAY) * 1000,
-      },
-      {
-        id: "riot-3",
-        label: "duo account",
-        snapshot_state: "ready",
-        last_used_at: (NOW - 19 * DAY) * 1000,
-      },
+    // show a label instead of a username.
+    riotAccounts: [
+      { accountId: "riot-1", label: "main", lastUsedAt: (NOW - HOUR) * 1000 },
+      { accountId: "riot-2", label: "smurf", lastUsedAt: (NOW - 6 * DAY) * 1000 },
+      { accountId: "riot-3", label: "duo account", lastUsedAt: (NOW - 19 * DAY) * 1000 },
     ],
-    currentRiotProfile: "riot-1",
+    currentRiotAccount: "riot-1",
     stores: {
       "client.settings": { ...BASE_SETTINGS, enabledPlatforms: ["steam", "riot"] },
       "client.folders": foldersStore(
@@ -240,15 +230,24 @@ function devScenario(): MockSpec {
     label: "agent playground — edge cases, safe to change",
     steamAccounts: DEV_ACCOUNTS,
     currentSteamAccount: DEV_ACCOUNTS[0].steam_id,
-    // One profile per snapshot_state, which is the whole state machine of the
-    // Riot cards and cannot be reproduced from a real config on demand.
-    riotProfiles: [
-      { id: "riot-1", label: "ready", snapshot_state: "ready", last_used_at: (NOW - HOUR) * 1000 },
-      { id: "riot-2", label: "capturing", snapshot_state: "capturing", last_used_at: null },
-      { id: "riot-3", label: "awaiting", snapshot_state: "awaiting_capture", last_used_at: null },
-      { id: "riot-4", label: "setup pending", snapshot_state: "setup_pending", last_used_at: null },
+    // A legacy profile id next to a puuid, with and without a Riot ID read
+    // back, and one never switched to: the shapes a migrated config mixes.
+    riotAccounts: [
+      {
+        accountId: "riot-profile-3f2a9c1e-1b7d-4e0a-9c55-2d8e61f04b7a",
+        label: "legacy profile",
+        lastUsedAt: (NOW - HOUR) * 1000,
+        fields: { gameName: "Player", tagLine: "EUW" },
+      },
+      {
+        accountId: "0c7d1f52-8a4e-5b9c-b1d3-6e2f7a9c4d18",
+        label: "Smurf#0001",
+        lastUsedAt: (NOW - 2 * DAY) * 1000,
+        fields: { gameName: "Smurf", tagLine: "0001" },
+      },
+      { accountId: "riot-profile-unnamed", label: "no riot id", lastUsedAt: null },
     ],
-    currentRiotProfile: "riot-1",
+    currentRiotAccount: "riot-profile-3f2a9c1e-1b7d-4e0a-9c55-2d8e61f04b7a",
     stores: {
       "client.settings": {
         ...BASE_SETTINGS,
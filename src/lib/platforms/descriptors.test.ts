@@ -5,8 +5,17 @@ vi.mock("@tauri-apps/api/core", () => ({
   invoke: (...args: unknown[]) => invokeMock(...args),
 }));
 
+const listeners = new Map<string, (event: { payload: unknown }) => void>();
+vi.mock("@tauri-apps/api/event", () => ({
+  listen: async (name: string, handler: (event: { payload: unknown }) => void) => {
+    listeners.set(name, handler);
+    return () => listeners.delete(name);
+  },
+}));
+
 const {
   installDescriptorFile,
+  onUserPlatformsChanged,
   reloadUserPlatforms,
   removeDescriptor,
   selectDescriptorFile,
@@ -21,7 +30,7 @@ const ACME = {
 };
 
 function report(loaded: (typeof ACME)[]) {
-  return { dir: "C:/data/platforms", loaded, skipped: [], rejected: [] };
+  return { dir: "C:/data/platforms", loaded, signers: {}, lint: {}, skipped: [], rejected: [] };
 }
 
 beforeEach(() => {
@@ -62,6 +71,21 @@ describe("descriptor folder calls", () => {
     expect(getPlatformDefinition("acme")?.name).toBe("Acme Launcher");
   });
 
+  it("takes in the report the backend pushes when a file changes on disk", async () => {
+    const seen: string[] = [];
+    const unlisten = await onUserPlatformsChanged((pushed) => {
+      seen.push(pushed.dir);
+      expect(getPlatformDefinition("acme")?.userProvided).toBe(true);
+    });
+
+    listeners.get("user-platforms-changed")?.({ payload: report([ACME]) });
+
+    expect(seen).toEqual(["C:/data/platforms"]);
+    expect(invokeMock).not.toHaveBeenCalled();
+    unlisten();
+    expect(listeners.has("user-platforms-changed")).toBe(false);
+  });
+
   it("reads a cancelled file picker as nothing to do, not as a failure", async () => {
     invokeMock.mockRejectedValue("cancelled");
 
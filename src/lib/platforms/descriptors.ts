@@ -6,15 +6,18 @@
  * judge a candidate file before installing it, install it, drop it.
  *
  * Every call re-reads the folder and returns the whole report, so the UI never
- * has to guess what the backend now believes.
+ * has to guess what the backend now believes. The backend also watches the
+ * folder and pushes a report of its own when a file there is edited.
  */
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { registerUserPlatforms, type PlatformDescriptor } from "./registry";
+import type { MessageKey, TranslationParams } from "$lib/i18n";
 
 /** What a dry run would do to one file, folder, registry value or process. */
 export interface PlanStep {
-  action: "read" | "capture" | "restore" | "delete" | "close" | "launch";
-  kind: "file" | "directory" | "registryValue" | "process" | "executable";
+  action: "read" | "capture" | "restore" | "delete" | "write" | "close" | "launch";
+  kind: "file" | "directory" | "fileKey" | "registryValue" | "process" | "executable" | "request" | "account";
   /** The live path, registry value or process name, fully resolved. */
   target: string;
   /** Where the data would come from or go, for a capture or a restore. */
@@ -23,10 +26,14 @@ export interface PlanStep {
   note?: string;
 }
 
+/** The operations a dry run can describe. */
+export type DryRunOperation = "switch" | "forget" | "setup" | "capture";
+
 /** Everything an operation would do, having done none of it. */
 export interface DryRunPlan {
   platformId: string;
-  operation: string;
+  operation: DryRunOperation;
+  /** Empty for a setup waiting on a sign-in, or a capture with nobody signed in. */
   accountId: string;
   /** Always false. A plan is never a report of work done. */
   applied: boolean;
@@ -36,11 +43,48 @@ export interface DryRunPlan {
   warnings: string[];
 }
 
+/**
+ * Who signed a descriptor file, from the `.sig` next to it. A file whose
+ * signature does not match never gets this far: the backend refuses it.
+ */
+export type DescriptorSigner =
+  | { status: "unsigned" }
+  | { status: "trusted"; publisher: string }
+  | { status: "unknown"; publicKey: string };
+
+/** The line that labels a descriptor with its signer. */
+export function describeSigner(
+  signer: DescriptorSigner | undefined,
+  t: (key: MessageKey, params?: TranslationParams) => string,
+): string {
+  switch (signer?.status) {
+    case "trusted":
+      return t("descriptor.signedBy", { publisher: signer.publisher });
+    case "unknown":
+      return t("descriptor.signedByUnknown", { key: signer.publicKey });
+    default:
+      return t("descriptor.unsigned");
+  }
+}
+
+/**
+ * Something a valid descriptor probably did not mean to do. `code` is stable,
+ * `field` is the dotted path it is about.
+ */
+export interface LintWarning {
+  code: string;
+  field: string;
+  message: string;
+}
+
 /** A descriptor file judged without installing it. */
 export interface DescriptorPreview {
   /** The file the user picked, as they picked it. */
   source: string;
   descriptor: PlatformDescriptor;
+  signer: DescriptorSigner;
+  /** Never blocks the install; shown so the author can fix the file. */
+  lint: LintWarning[];
   /** The name it would take in the folder, always `<id>.json`. */
   fileName: string;
   /** A file of that name is already there, so this replaces rather than adds. */
@@ -56,6 +100,10 @@ export interface DescriptorPreview {
 export interface UserPlatformReport {
   dir: string;
   loaded: PlatformDescriptor[];
+  /** Who signed each loaded descriptor, by platform id. */
+  signers: Record<string, DescriptorSigner>;
+  /** Lint warnings by platform id, for the ids that have any. */
+  lint: Record<string, LintWarning[]>;
   skipped: { id: string; reason: string }[];
   rejected: { source: string; field: string; problem: string }[];
 }
@@ -76,6 +124,19 @@ export async function reloadUserPlatforms(): Promise<UserPlatformReport> {
   return adopt(await invoke<UserPlatformReport>("reload_user_platforms"));
 }
 
+/**
+ * Calls `handler` with every report the backend pushes after a file in the
+ * folder was added, edited or deleted. The registry has already taken the
+ * report in by the time `handler` runs.
+ */
+export function onUserPlatformsChanged(
+  handler: (report: UserPlatformReport) => void,
+): Promise<UnlistenFn> {
+  return listen<UserPlatformReport>("user-platforms-changed", (event) => {
+    handler(adopt(event.payload));
+  });
+}
+
 /**
  * Opens the file picker. Returns null when the user cancelled or the native
  * dialog failed, both of which mean the same thing here: leave everything
@@ -109,7 +170,21 @@ export async function openDescriptorsFolder(): Promise<void> {
   await invoke("open_descriptors_folder");
 }
 
-/** What switching this account would read, copy, write and close. */
-export async function dryRunSwitch(platformId: string, accountId: string): Promise<DryRunPlan> {
-  return await invoke<DryRunPlan>("platform_dry_run", { platformId, accountId });
+/**
+ * What an operation would read, copy, write and close. Setup and capture act
+ * on whoever is signed in, so they take no account. `params` are the ones the
+ * switch itself would be sent (Steam reads its launch options from them).
+ */
+export async function dryRun(
+  platformId: string,
+  operation: DryRunOperation,
+  accountId = "",
+  params: Record<string, unknown> = {},
+): Promise<DryRunPlan> {
+  return await invoke<DryRunPlan>("platform_dry_run", {
+    platformId,
+    operation,
+    accountId,
+    params,
+  });
 }
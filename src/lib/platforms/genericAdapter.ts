@@ -19,6 +19,10 @@ export interface GenericRawAccount {
   label: string;
   lastUsedAt?: number | null;
   snapshotSaved?: boolean;
+  /** What the descriptor's launch arguments read through `${account.<name>}`. */
+  launchValues?: Record<string, string>;
+  /** The descriptor's account fields this account has a value for. */
+  fields?: Record<string, string>;
 }
 
 interface GenericStartupSnapshot<TRaw> {
@@ -57,6 +61,8 @@ function defaultToAccount(raw: GenericRawAccount): PlatformAccount {
     displayName: raw.label || raw.accountId,
     username: raw.accountId,
     lastLoginAt: raw.lastUsedAt ?? null,
+    fields: raw.fields,
+    captured: raw.snapshotSaved,
   };
 }
 
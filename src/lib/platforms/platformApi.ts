@@ -3,6 +3,14 @@ import type { PlatformAddFlowStatus } from "$lib/shared/platform";
 import { logAppEvent, serializeLogValue } from "$lib/shared/appLogger";
 import { toPlatformAddFlowStatus } from "$lib/platforms/addFlow";
 
+/** One earlier capture of an account's snapshot, newest first in a list. */
+export interface SnapshotGeneration {
+  /** Unix milliseconds; also what a switch passes as `snapshotGeneration`. */
+  capturedAt: number;
+  files?: number;
+  status: "intact" | "unverified" | "unreadable" | "corrupt";
+}
+
 interface SetupStatusPayload {
   setupId: string;
   state: string;
@@ -73,6 +81,20 @@ export function createPlatformApi(platformId: string) {
     return invoke("platform_set_account_label", { platformId, accountId, label });
   }
 
+  function setAccountLaunchValues(
+    accountId: string,
+    values: Record<string, string>,
+  ): Promise<void> {
+    return invoke("platform_set_account_launch_values", { platformId, accountId, values });
+  }
+
+  function getSnapshotGenerations(accountId: string): Promise<SnapshotGeneration[]> {
+    return invoke<SnapshotGeneration[]>("platform_snapshot_generations", {
+      platformId,
+      accountId,
+    });
+  }
+
   return {
     getAccounts,
     getCurrentAccount,
@@ -83,5 +105,7 @@ export function createPlatformApi(platformId: string) {
     cancelSetup,
     forgetAccount,
     setAccountLabel,
+    setAccountLaunchValues,
+    getSnapshotGenerations,
   };
 }
@@ -18,6 +18,10 @@ import { steamSettingsSchema } from "./steam/settingsSchema";
 export interface PlatformDescriptor {
   id: string;
   name: string;
+  /** The shipped descriptor this one is written on top of, if any. */
+  extends?: string;
+  /** Dotted paths of the fields taken from `extends` unchanged. */
+  inherited?: string[];
   os: Record<string, { executable?: { candidates?: { kind: string; template?: string }[] } }>;
 }
 
@@ -36,7 +40,7 @@ const SHIPPED_DESCRIPTORS = Object.values(
  * drawn in, its settings tab, the features the app layer may offer for it.
  *
  * A descriptor owns the name, the systems and where the launcher lives, so
- * none of those appear here for a platform that has one. The four platforms
+ * none of those appear here for a platform that has one. The three platforms
  * with no descriptor carry them, because nothing else describes those. */
 type PlatformChrome = Partial<PlatformDef> & Pick<PlatformDef, "accent">;
 
@@ -70,13 +74,10 @@ const PLATFORM_CHROME: Record<string, PlatformChrome> = {
     },
   },
   riot: {
-    name: "Riot Games",
     accent: "#ef4444",
-    supportedOs: ["windows"],
     settingsTabKey: "settings.riot",
     settingsComponent: () => import("./riot/RiotSettingsTab.svelte"),
     pathLabelKey: "settings.riotClientPath",
-    pathPlaceholder: "C:\\Riot Games\\Riot Client\\RiotClientServices.exe",
     capabilities: {
       lastLoginUnknownKey: "time.neverConnected",
       externalDataStores: [STORAGE_TARGET_RIOT_SNAPSHOTS],
@@ -6,6 +6,13 @@ import type {
   ProfileInfo,
   BanInfo,
   CopyableGame,
+  SteamShortcut,
+  SteamCollection,
+  SteamLibraryIndex,
+  SteamAppOwner,
+  LibraryCopyRequest,
+  LibraryCopyPlan,
+  LibraryCopyResult,
   SteamStartupSnapshot,
 } from "./types";
 import { getSettings } from "../../features/settings/store";
@@ -216,6 +223,29 @@ export interface BulkEditRequest {
   newsPopup: boolean | null;
   doNotDisturb: boolean | null;
   launchOptions: LaunchOptionEdit[];
+  /** Any other keys; paths start below the file's root key. */
+  edits?: VdfEdit[];
+}
+
+export type SteamConfigFile = "localconfig" | "sharedconfig";
+
+export type VdfEdit =
+  | { op: "set"; file?: SteamConfigFile; path: string[]; value: string }
+  | { op: "delete"; file?: SteamConfigFile; path: string[] };
+
+export interface KeyEditResult {
+  file: SteamConfigFile;
+  path: string[];
+  before: string | null;
+  after: string | null;
+  outcome: "changed" | "unchanged" | "failed";
+  error: string | null;
+}
+
+export interface AccountEditResult {
+  steamId: string;
+  keys: KeyEditResult[];
+  error: string | null;
 }
 
 export interface BulkEditFailure {
@@ -226,6 +256,23 @@ export interface BulkEditFailure {
 export interface BulkEditResult {
   succeeded: number;
   failed: BulkEditFailure[];
+  accounts: AccountEditResult[];
+  /** Pass to `revertBulkEdit` to undo the batch. */
+  backupId: string | null;
+}
+
+/** A key the revert left alone because something changed it since the batch. */
+export interface BulkEditRevertConflict {
+  steamId: string;
+  file: SteamConfigFile;
+  path: string[];
+  current: string | null;
+}
+
+export interface BulkEditRevertResult {
+  restored: number;
+  failed: BulkEditFailure[];
+  conflicts: BulkEditRevertConflict[];
 }
 
 export async function bulkEdit(request: BulkEditRequest): Promise<BulkEditResult> {
@@ -234,6 +281,7 @@ export async function bulkEdit(request: BulkEditRequest): Promise<BulkEditResult
     newsPopup: request.newsPopup,
     doNotDisturb: request.doNotDisturb,
     launchOptionCount: request.launchOptions.length,
+    editCount: request.edits?.length ?? 0,
   };
   void logAppEvent("info", "frontend.steam.bulk_edit", "Bulk edit started", details);
   try {
@@ -253,6 +301,75 @@ export async function bulkEdit(request: BulkEditRequest): Promise<BulkEditResult
   }
 }
 
+export async function previewBulkEdit(request: BulkEditRequest): Promise<BulkEditResult> {
+  return invoke<BulkEditResult>("steam_preview_bulk_edit", { request });
+}
+
+export async function revertBulkEdit(backupId: string): Promise<BulkEditRevertResult> {
+  void logAppEvent("info", "frontend.steam.bulk_edit", "Bulk edit revert started", { backupId });
+  try {
+    const result = await invoke<BulkEditRevertResult>("steam_revert_bulk_edit", { backupId });
+    void logAppEvent("info", "frontend.steam.bulk_edit", "Bulk edit reverted", {
+      backupId,
+      restored: result.restored,
+      failed: result.failed.length,
+      conflicts: result.conflicts.length,
+    });
+    return result;
+  } catch (reason) {
+    void logAppEvent("error", "frontend.steam.bulk_edit", "Bulk edit revert failed", {
+      backupId,
+      error: serializeLogValue(reason),
+    });
+    throw reason;
+  }
+}
+
 export async function getAccountGames(steamId: string): Promise<CopyableGame[]> {
   return invoke<CopyableGame[]>("steam_get_account_games", { steamId });
 }
+
+export async function getShortcuts(steamId: string): Promise<SteamShortcut[]> {
+  return invoke<SteamShortcut[]>("steam_get_shortcuts", { steamId });
+}
+
+export async function getLibrary(): Promise<SteamLibraryIndex> {
+  return invoke<SteamLibraryIndex>("steam_get_library");
+}
+
+export async function getAppOwners(appId: number): Promise<SteamAppOwner[]> {
+  return invoke<SteamAppOwner[]>("steam_get_app_owners", { appId });
+}
+
+export async function getCollections(steamId: string): Promise<SteamCollection[]> {
+  return invoke<SteamCollection[]>("steam_get_collections", { steamId });
+}
+
+export async function previewLibraryCopy(request: LibraryCopyRequest): Promise<LibraryCopyPlan[]> {
+  return invoke<LibraryCopyPlan[]>("steam_preview_library_copy", { request });
+}
+
+export async function copyLibrary(request: LibraryCopyRequest): Promise<LibraryCopyResult> {
+  const details = {
+    fromSteamId: request.fromSteamId,
+    targetCount: request.toSteamIds.length,
+    shortcutCount: request.shortcutIds.length,
+    collectionCount: request.collectionIds.length,
+  };
+  void logAppEvent("info", "frontend.steam.copy_library", "Library copy started", details);
+  try {
+    const result = await invoke<LibraryCopyResult>("steam_copy_library", { request });
+    void logAppEvent("info", "frontend.steam.copy_library", "Library copy completed", {
+      ...details,
+      succeeded: result.applied.length,
+      failed: result.failed.length,
+    });
+    return result;
+  } catch (reason) {
+    void logAppEvent("error", "frontend.steam.copy_library", "Library copy failed", {
+      ...details,
+      error: serializeLogValue(reason),
+    });
+    throw reason;
+  }
+}
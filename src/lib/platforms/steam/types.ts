@@ -46,3 +46,79 @@ export interface CopyableGame {
   app_id: string;
   name: string;
 }
+
+/** A non-Steam game from the account's shortcuts.vdf. */
+export interface SteamShortcut {
+  appId: number;
+  name: string;
+  exe: string;
+  startDir: string;
+  icon: string;
+  launchOptions: string;
+  hidden: boolean;
+  lastPlayed: number | null;
+  tags: string[];
+}
+
+export interface SteamLibraryGame {
+  appId: number;
+  name: string;
+  /** Unix seconds. */
+  lastPlayed: number | null;
+  playtimeMinutes: number;
+  installed: boolean;
+}
+
+export interface SteamAccountLibrary {
+  steamId: string;
+  accountName: string;
+  personaName: string;
+  /** Most recently played first. */
+  games: SteamLibraryGame[];
+  error: string | null;
+}
+
+export interface SteamLibraryIndex {
+  accounts: SteamAccountLibrary[];
+}
+
+export interface SteamAppOwner {
+  steamId: string;
+  accountName: string;
+  lastPlayed: number | null;
+  playtimeMinutes: number;
+  installed: boolean;
+}
+
+export interface SteamCollection {
+  id: string;
+  name: string;
+  appIds: number[];
+  dynamic: boolean;
+}
+
+export interface LibraryCopyRequest {
+  fromSteamId: string;
+  toSteamIds: string[];
+  shortcutIds: number[];
+  collectionIds: string[];
+}
+
+export interface LibraryCopyItem {
+  kind: "shortcut" | "collection";
+  name: string;
+  sourceId: string;
+  targetId: string;
+  action: "add" | "replace" | "skip";
+  artwork: string[];
+}
+
+export interface LibraryCopyPlan {
+  steamId: string;
+  items: LibraryCopyItem[];
+}
+
+export interface LibraryCopyResult {
+  applied: LibraryCopyPlan[];
+  failed: { steamId: string; error: string }[];
+}
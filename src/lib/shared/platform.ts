@@ -8,6 +8,12 @@ export interface PlatformAccount {
   displayName: string;
   username: string;
   lastLoginAt?: number | null;
+  /** A descriptor platform's extra account values (tag line, region...),
+   * by field name. Search matches them too. */
+  fields?: Record<string, string>;
+  /** False for an account the launcher remembers but accshift has no
+   * snapshot of yet. Left out by platforms that do not say. */
+  captured?: boolean;
 }
 
 export interface PlatformContextMenuConfirmConfig {
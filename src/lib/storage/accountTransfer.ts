@@ -0,0 +1,40 @@
+/**
+ * Moving accounts to another machine: one archive sealed with a passphrase,
+ * written by an export and read back by an import. The Rust side decrypts
+ * and re-encrypts the snapshots; nothing here ever sees them.
+ */
+import { invoke } from "@tauri-apps/api/core";
+
+export interface TransferredAccount {
+  platformId: string;
+  accountId: string;
+  label: string;
+}
+
+/** An account left out, and why. */
+export interface SkippedAccount {
+  platformId: string;
+  accountId: string;
+  reason: string;
+}
+
+export interface ExportSummary {
+  accounts: TransferredAccount[];
+  /** Snapshots that failed their integrity check or would not decrypt. */
+  skipped: SkippedAccount[];
+}
+
+export interface ImportReport {
+  exportedAt: number;
+  imported: TransferredAccount[];
+  /** Accounts this machine already had. They are never overwritten. */
+  conflicts: SkippedAccount[];
+}
+
+export async function exportAccounts(path: string, passphrase: string): Promise<ExportSummary> {
+  return await invoke<ExportSummary>("export_accounts", { path, passphrase });
+}
+
+export async function importAccounts(path: string, passphrase: string): Promise<ImportReport> {
+  return await invoke<ImportReport>("import_accounts", { path, passphrase });
+}
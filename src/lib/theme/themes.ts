@@ -1,4 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import type { MessageKey } from "$lib/i18n";
 import {
   AVATAR_RADIUS,
@@ -335,6 +336,17 @@ export async function loadCustomThemes(): Promise<void> {
   }
 }
 
+/**
+ * Takes in the theme list the backend pushes after a file in the themes folder
+ * was edited on disk, then calls `handler`.
+ */
+export function onCustomThemesChanged(handler: () => void): Promise<UnlistenFn> {
+  return listen<CustomThemePayload[]>("custom-themes-changed", (event) => {
+    applyCustomThemePayloads(event.payload);
+    handler();
+  });
+}
+
 export async function saveThemeDocument(document: ThemeDocument): Promise<void> {
   if (builtInDocuments.has(document.id)) {
     throw new Error(`Cannot overwrite built-in theme: ${document.id}`);